@@ -259,6 +259,10 @@ builtin_macros_test_case_non_item = `#[test_case]` attribute is only allowed on
 builtin_macros_test_runner_invalid = `test_runner` argument must be a path
 builtin_macros_test_runner_nargs = `#![test_runner(..)]` accepts exactly 1 argument
 
+builtin_macros_test_timeout_invalid = `#[test_timeout]` expects a positive number of seconds, found `{$value}`
+
+builtin_macros_test_timeout_not_test = `#[test_timeout]` can only be applied to `#[test]` and `#[bench]` functions
+
 builtin_macros_tests_not_support = building tests with panic=abort is not supported without `-Zpanic_abort_tests`
 
 builtin_macros_trace_macros = trace_macros! accepts only `true` or `false`
@@ -671,6 +671,21 @@ pub(crate) struct TestBadFn {
     pub(crate) kind: &'static str,
 }
 
+#[derive(Diagnostic)]
+#[diag(builtin_macros_test_timeout_invalid)]
+pub(crate) struct TestTimeoutInvalid {
+    #[primary_span]
+    pub(crate) span: Span,
+    pub(crate) value: Symbol,
+}
+
+#[derive(Diagnostic)]
+#[diag(builtin_macros_test_timeout_not_test)]
+pub(crate) struct TestTimeoutNotTest {
+    #[primary_span]
+    pub(crate) span: Span,
+}
+
 #[derive(Diagnostic)]
 #[diag(builtin_macros_asm_explicit_register_name)]
 pub(crate) struct AsmExplicitRegisterName {
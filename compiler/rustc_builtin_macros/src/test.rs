@@ -369,6 +369,15 @@ pub(crate) fn expand_test_or_bench(
                                             }
                                         },
                                     ),
+                                    // timeout: Some(...) | None
+                                    field(
+                                        "timeout",
+                                        if let Some(secs) = test_timeout(cx, &item) {
+                                            cx.expr_some(sp, cx.expr_u64(sp, secs))
+                                        } else {
+                                            cx.expr_none(sp)
+                                        },
+                                    ),
                                     // },
                                 ],
                             ),
@@ -524,6 +533,19 @@ fn should_panic(cx: &ExtCtxt<'_>, i: &ast::Item) -> ShouldPanic {
     }
 }
 
+fn test_timeout(cx: &ExtCtxt<'_>, i: &ast::Item) -> Option<u64> {
+    // Handle #[test_timeout = "seconds"]
+    let attr = attr::find_by_name(&i.attrs, sym::test_timeout)?;
+    let value = attr.value_str()?;
+    match value.as_str().parse::<u64>() {
+        Ok(secs) if secs > 0 => Some(secs),
+        _ => {
+            cx.dcx().emit_err(errors::TestTimeoutInvalid { span: attr.span, value });
+            None
+        }
+    }
+}
+
 enum TestType {
     UnitTest,
     IntegrationTest,
@@ -138,6 +138,15 @@ fn visit_crate(&mut self, c: &mut ast::Crate) {
             self.tests.push(test);
         }
 
+        // The `#[test]` expansion puts the test case right before the function, so a function
+        // with a timeout that isn't preceded by its test case was never a test.
+        if let ast::ItemKind::Fn(_) = item.kind
+            && let Some(attr) = attr::find_by_name(&item.attrs, sym::test_timeout)
+            && !self.tests.iter().any(|test| test.ident.name == item.ident.name)
+        {
+            self.cx.ext_cx.dcx().emit_err(errors::TestTimeoutNotTest { span: attr.span });
+        }
+
         // We don't want to recurse into anything other than mods, since
         // mods or tests inside of functions will break things
         if let ast::ItemKind::Mod(_, ModKind::Loaded(.., ast::ModSpans { inner_span: span, .. })) =
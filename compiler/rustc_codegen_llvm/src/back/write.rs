@@ -208,7 +208,9 @@ pub fn target_machine_factory(
     let abi = SmallCStr::new(&sess.target.llvm_abiname);
     let trap_unreachable =
         sess.opts.unstable_opts.trap_unreachable.unwrap_or(sess.target.trap_unreachable);
-    let emit_stack_size_section = sess.opts.unstable_opts.emit_stack_sizes;
+    // `-Zprint-stack-depth` reads the frame sizes back from the objects.
+    let emit_stack_size_section =
+        sess.opts.unstable_opts.emit_stack_sizes || sess.opts.unstable_opts.print_stack_depth;
 
     let verbose_asm = sess.opts.unstable_opts.verbose_asm;
     let relax_elf_relocations =
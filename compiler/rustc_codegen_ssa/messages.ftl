@@ -40,6 +40,8 @@ codegen_ssa_extract_bundled_libs_write_file = failed to write file '{$rlib}': {$
 
 codegen_ssa_failed_to_get_layout = failed to get layout for {$ty}: {$err}
 
+codegen_ssa_failed_to_read_stack_sizes = failed to read the stack sizes from {$path}: {$error}
+
 codegen_ssa_failed_to_write = failed to write {$path}: {$error}
 
 codegen_ssa_field_associated_value_expected = associated value expected for `{$name}`
@@ -190,6 +192,9 @@ codegen_ssa_no_natvis_directory = error enumerating natvis directory: {$error}
 
 codegen_ssa_no_saved_object_file = cached cgu {$cgu_name} should have an object file, but doesn't
 
+codegen_ssa_no_stack_sizes = no stack sizes were found for `-Zprint-stack-depth`
+    .note = the frame sizes are read from the `.stack_sizes` sections that LLVM emits for ELF targets
+
 codegen_ssa_processing_dymutil_failed = processing debug info with `dsymutil` failed: {$status}
     .note = {$output}
 
@@ -215,6 +220,14 @@ codegen_ssa_shuffle_indices_evaluation = could not evaluate shuffle_indices at c
 
 codegen_ssa_specify_libraries_to_link = use the `-l` flag to specify native libraries to link
 
+codegen_ssa_stack_depth_unbounded =
+    the stack depth of `{$name}` is unbounded, so it cannot be checked against its limit of {$limit} bytes
+    .note = {$reasons}
+
+codegen_ssa_stack_limit_exceeded =
+    `{$name}` may use {$depth} bytes of stack, exceeding its limit of {$limit} bytes
+    .note = the deepest call path is {$path}
+
 codegen_ssa_static_library_native_artifacts = Link against the following native artifacts when linking against this static library. The order and any duplication can be significant on some platforms.
 
 codegen_ssa_static_library_native_artifacts_to_file = Native artifacts to link against have been written to {$path}. The order and any duplication can be significant on some platforms.
@@ -5,5 +5,6 @@
 pub mod lto;
 pub mod metadata;
 pub mod rpath;
+mod stack_depth;
 pub mod symbol_export;
 pub mod write;
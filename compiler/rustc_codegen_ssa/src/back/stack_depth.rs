@@ -0,0 +1,293 @@
+//! The worst-case stack depth analysis of `-Zprint-stack-depth`.
+//!
+//! The call graph is recorded by the mono item collector before codegen starts. The frame size
+//! of every function is read back from the `.stack_sizes` sections that LLVM puts into the object
+//! files. A function without a frame size of its own was inlined into all of its callers, so its
+//! frame is already part of theirs and only its callees are taken into account.
+
+use std::fmt;
+use std::path::Path;
+
+use object::read::Bytes;
+use object::{Architecture, Object, ObjectSection, ObjectSymbol, RelocationTarget, SymbolKind};
+use rustc_data_structures::fx::FxHashMap;
+use rustc_data_structures::stack::ensure_sufficient_stack;
+use rustc_session::code_stats::{OpaqueCall, StackGraphNode};
+use rustc_session::Session;
+
+use super::write::CompiledModules;
+use crate::errors;
+
+/// How many of the reasons for an unbounded stack depth are listed in an error.
+const MAX_REASONS_IN_ERROR: usize = 3;
+
+pub(super) fn check_stack_depth(sess: &Session, compiled_modules: &CompiledModules) {
+    let graph = sess.code_stats.take_stack_graph();
+
+    let mut frame_sizes = FxHashMap::default();
+    let modules = compiled_modules.modules.iter().chain(&compiled_modules.allocator_module);
+    for path in modules.filter_map(|module| module.object.as_deref()) {
+        if let Err(error) = read_frame_sizes(path, &mut frame_sizes) {
+            sess.dcx().emit_err(errors::FailedToReadStackSizes { path, error });
+        }
+    }
+    if frame_sizes.is_empty() {
+        if !graph.is_empty() {
+            sess.dcx().emit_warn(errors::NoStackSizes);
+        }
+        return;
+    }
+
+    let mut depths = StackDepths {
+        graph: &graph,
+        frame_sizes: &frame_sizes,
+        states: graph.iter().map(|_| State::Unvisited).collect(),
+        back_edges: Vec::new(),
+    };
+    let mut entries: Vec<_> = (0..graph.len()).filter(|&node| graph[node].is_entry).collect();
+    entries.sort_by(|&a, &b| graph[a].name.cmp(&graph[b].name));
+
+    for entry in entries {
+        let node = &graph[entry];
+        let depth = depths.depth(entry);
+        let path = depths.deepest_path(entry);
+        let reasons = depths.unbounded_reasons(entry);
+
+        if reasons.is_empty() {
+            println!("print-stack-depth entry: `{}`: {} bytes", node.name, depth);
+        } else {
+            println!(
+                "print-stack-depth entry: `{}`: unbounded, at least {} bytes",
+                node.name, depth
+            );
+        }
+        let indent = "    ";
+        for &callee in &path {
+            let frame_size = depths.frame_size(callee);
+            println!("print-stack-depth {indent}`{}`: {frame_size} bytes", graph[callee].name);
+        }
+        for reason in &reasons {
+            println!("print-stack-depth {indent}unbounded: {reason}");
+        }
+
+        let Some(limit) = node.stack_limit else { continue };
+        if depth > limit {
+            let path: Vec<_> =
+                path.iter().map(|&callee| format!("`{}`", graph[callee].name)).collect();
+            sess.dcx().emit_err(errors::StackLimitExceeded {
+                span: node.span,
+                name: &node.name,
+                depth,
+                limit,
+                path: path.join(" -> "),
+            });
+        } else if !reasons.is_empty() {
+            let mut listed: Vec<_> = reasons
+                .iter()
+                .take(MAX_REASONS_IN_ERROR)
+                .map(|reason| reason.to_string())
+                .collect();
+            if reasons.len() > MAX_REASONS_IN_ERROR {
+                listed.push(format!("{} more", reasons.len() - MAX_REASONS_IN_ERROR));
+            }
+            sess.dcx().emit_err(errors::StackDepthUnbounded {
+                span: node.span,
+                name: &node.name,
+                limit,
+                reasons: listed.join(", "),
+            });
+        }
+    }
+}
+
+/// Adds the frame sizes from the `.stack_sizes` sections of the object file at `path` to
+/// `frame_sizes`, keyed by symbol name.
+fn read_frame_sizes(path: &Path, frame_sizes: &mut FxHashMap<String, u64>) -> Result<(), String> {
+    let data = std::fs::read(path).map_err(|error| error.to_string())?;
+    let file = object::File::parse(&*data).map_err(|error| error.to_string())?;
+    let pointer_size = if file.is_64() { 8 } else { 4 };
+    // The symbol of a Thumb function has the lowest bit of its address set.
+    let address_mask = if file.architecture() == Architecture::Arm { !1 } else { !0 };
+
+    // Local functions may be referred to by their section and offset instead of their symbol.
+    let mut functions = FxHashMap::default();
+    for symbol in file.symbols() {
+        if symbol.kind() == SymbolKind::Text
+            && let Some(section) = symbol.section_index()
+            && let Ok(name) = symbol.name()
+        {
+            functions.insert((section, symbol.address() & address_mask), name);
+        }
+    }
+
+    for section in file.sections() {
+        if section.name() != Ok(".stack_sizes") {
+            continue;
+        }
+        let contents = section.data().map_err(|error| error.to_string())?;
+        let relocations: FxHashMap<_, _> = section.relocations().collect();
+
+        // Each entry is the address of a function followed by its frame size as ULEB128.
+        let mut entries = Bytes(contents);
+        while !entries.is_empty() {
+            let offset = contents.len() - entries.len();
+            let malformed = || format!("malformed `.stack_sizes` entry at offset {offset}");
+            entries.skip(pointer_size).map_err(|()| malformed())?;
+            let frame_size = entries.read_uleb128().map_err(|()| malformed())?;
+
+            let Some(relocation) = relocations.get(&(offset as u64)) else { continue };
+            let RelocationTarget::Symbol(index) = relocation.target() else { continue };
+            let symbol = file.symbol_by_index(index).map_err(|error| error.to_string())?;
+            let addend = if relocation.has_implicit_addend() {
+                let bytes = &contents[offset..offset + pointer_size];
+                let mut buf = [0; 8];
+                if file.is_little_endian() {
+                    buf[..pointer_size].copy_from_slice(bytes);
+                    u64::from_le_bytes(buf)
+                } else {
+                    buf[8 - pointer_size..].copy_from_slice(bytes);
+                    u64::from_be_bytes(buf)
+                }
+            } else {
+                relocation.addend() as u64
+            };
+
+            let name = if symbol.kind() == SymbolKind::Section {
+                let Some(section) = symbol.section_index() else { continue };
+                match functions.get(&(section, addend & address_mask)) {
+                    Some(&name) => name,
+                    None => continue,
+                }
+            } else {
+                symbol.name().map_err(|error| error.to_string())?
+            };
+            // ThinLTO gives the local symbols it promotes a unique suffix.
+            let name = name.split_once(".llvm.").map_or(name, |(name, _)| name);
+            let size = frame_sizes.entry(name.to_string()).or_insert(0);
+            *size = (*size).max(frame_size);
+        }
+    }
+    Ok(())
+}
+
+#[derive(Copy, Clone)]
+enum State {
+    Unvisited,
+    OnStack,
+    /// The depth in bytes, which is only a lower bound if the node reaches an unbounded call, and
+    /// the callee on the deepest path.
+    Done(u64, Option<usize>),
+}
+
+struct StackDepths<'a> {
+    graph: &'a [StackGraphNode],
+    frame_sizes: &'a FxHashMap<String, u64>,
+    states: Vec<State>,
+    /// The calls that close a cycle, as `(caller, callee)`.
+    back_edges: Vec<(usize, usize)>,
+}
+
+/// Why the stack depth of an entry point cannot be bounded.
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+enum Unbounded<'a> {
+    Recursion { caller: &'a str, callee: &'a str },
+    Indirect { caller: &'a str },
+    External { caller: &'a str, callee: &'a str },
+}
+
+impl fmt::Display for Unbounded<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Unbounded::Recursion { caller, callee } if caller == callee => {
+                write!(f, "`{caller}` calls itself")
+            }
+            Unbounded::Recursion { caller, callee } => {
+                write!(f, "`{caller}` recursively calls `{callee}`")
+            }
+            Unbounded::Indirect { caller } => write!(f, "`{caller}` makes an indirect call"),
+            Unbounded::External { caller, callee } => {
+                write!(f, "`{caller}` calls `{callee}` from another crate")
+            }
+        }
+    }
+}
+
+impl<'a> StackDepths<'a> {
+    fn frame_size(&self, node: usize) -> u64 {
+        self.frame_sizes.get(&self.graph[node].symbol_name).copied().unwrap_or(0)
+    }
+
+    /// Returns the largest stack depth that can be reached from `node`, following the calls whose
+    /// callees are known.
+    fn depth(&mut self, node: usize) -> u64 {
+        match self.states[node] {
+            State::Done(depth, _) => return depth,
+            State::OnStack => unreachable!("cycles are cut before they are entered"),
+            State::Unvisited => {}
+        }
+        self.states[node] = State::OnStack;
+
+        let graph = self.graph;
+        let mut deepest: Option<(u64, usize)> = None;
+        for &callee in &graph[node].callees {
+            if let State::OnStack = self.states[callee] {
+                self.back_edges.push((node, callee));
+                continue;
+            }
+            let depth = ensure_sufficient_stack(|| self.depth(callee));
+            if deepest.map_or(true, |(deepest, _)| depth > deepest) {
+                deepest = Some((depth, callee));
+            }
+        }
+
+        let depth = self.frame_size(node) + deepest.map_or(0, |(depth, _)| depth);
+        self.states[node] = State::Done(depth, deepest.map(|(_, callee)| callee));
+        depth
+    }
+
+    /// Returns the functions on the deepest call path starting at `entry`, which must have been
+    /// passed to `depth` already.
+    fn deepest_path(&self, entry: usize) -> Vec<usize> {
+        let mut path = vec![entry];
+        while let State::Done(_, Some(callee)) = self.states[*path.last().unwrap()] {
+            path.push(callee);
+        }
+        path
+    }
+
+    /// Returns everything reachable from `entry` that makes its stack depth unbounded, sorted.
+    fn unbounded_reasons(&self, entry: usize) -> Vec<Unbounded<'a>> {
+        let graph = self.graph;
+        let mut reachable = vec![false; graph.len()];
+        let mut stack = vec![entry];
+        reachable[entry] = true;
+        while let Some(node) = stack.pop() {
+            for &callee in &graph[node].callees {
+                if !reachable[callee] {
+                    reachable[callee] = true;
+                    stack.push(callee);
+                }
+            }
+        }
+
+        let mut reasons: Vec<_> = self
+            .back_edges
+            .iter()
+            .filter(|&&(caller, _)| reachable[caller])
+            .map(|&(caller, callee)| Unbounded::Recursion {
+                caller: &graph[caller].name,
+                callee: &graph[callee].name,
+            })
+            .collect();
+        for (node, _) in reachable.iter().enumerate().filter(|&(_, &is_reachable)| is_reachable) {
+            let caller = &graph[node].name;
+            reasons.extend(graph[node].opaque_calls.iter().map(|call| match call {
+                OpaqueCall::Indirect => Unbounded::Indirect { caller },
+                OpaqueCall::External(callee) => Unbounded::External { caller, callee },
+            }));
+        }
+        reasons.sort();
+        reasons.dedup();
+        reasons
+    }
+}
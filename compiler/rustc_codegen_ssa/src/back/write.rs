@@ -40,6 +40,7 @@
 
 use super::link::{self, ensure_removed};
 use super::lto::{self, SerializedModule};
+use super::stack_depth;
 use super::symbol_export::symbol_name_for_instance_in_crate;
 use crate::errors::ErrorCreatingRemarkDir;
 use crate::traits::*;
@@ -644,7 +645,11 @@ fn produce_final_output_artifacts(
                 user_wants_objects = true;
                 copy_if_one_unit(OutputType::Object, true);
             }
-            OutputType::Mir | OutputType::Metadata | OutputType::Exe | OutputType::DepInfo => {}
+            OutputType::Mir
+            | OutputType::Metadata
+            | OutputType::Exe
+            | OutputType::DepInfo
+            | OutputType::CallGraph => {}
         }
     }
 
@@ -2059,6 +2064,10 @@ pub fn join(self, sess: &Session) -> (CodegenResults, FxIndexMap<WorkProductId,
 
         let work_products =
             copy_all_cgu_workproducts_to_incr_comp_cache_dir(sess, &compiled_modules);
+        // This needs to read the object files before the temporary ones are removed.
+        if sess.opts.unstable_opts.print_stack_depth {
+            stack_depth::check_stack_depth(sess, &compiled_modules);
+        }
         produce_final_output_artifacts(sess, &compiled_modules, &self.output_filenames);
 
         // FIXME: time_llvm_passes support - does this use a global context or
@@ -526,6 +526,19 @@ fn emit_error_with_label(
                     ))
                 })
             }
+            sym::panic_free => codegen_fn_attrs.flags |= CodegenFnAttrFlags::PANIC_FREE,
+            sym::stack_limit => {
+                if let Some(val) = attr.value_str() {
+                    match val.as_str().parse() {
+                        Ok(limit) => codegen_fn_attrs.stack_limit = Some(limit),
+                        Err(_) => {
+                            let msg =
+                                format!("`stack_limit` must be a number of bytes, not `{val}`");
+                            tcx.dcx().span_err(attr.span, msg);
+                        }
+                    }
+                }
+            }
             _ => {}
         }
     }
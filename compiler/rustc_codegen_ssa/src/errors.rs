@@ -1038,3 +1038,38 @@ pub struct CompilerBuiltinsCannotCall {
     pub caller: String,
     pub callee: String,
 }
+
+#[derive(Diagnostic)]
+#[diag(codegen_ssa_failed_to_read_stack_sizes)]
+pub struct FailedToReadStackSizes<'a> {
+    pub path: &'a Path,
+    pub error: String,
+}
+
+#[derive(Diagnostic)]
+#[diag(codegen_ssa_no_stack_sizes)]
+#[note]
+pub struct NoStackSizes;
+
+#[derive(Diagnostic)]
+#[diag(codegen_ssa_stack_limit_exceeded)]
+#[note]
+pub struct StackLimitExceeded<'a> {
+    #[primary_span]
+    pub span: Span,
+    pub name: &'a str,
+    pub depth: u64,
+    pub limit: u64,
+    pub path: String,
+}
+
+#[derive(Diagnostic)]
+#[diag(codegen_ssa_stack_depth_unbounded)]
+#[note]
+pub struct StackDepthUnbounded<'a> {
+    #[primary_span]
+    pub span: Span,
+    pub name: &'a str,
+    pub limit: u64,
+    pub reasons: String,
+}
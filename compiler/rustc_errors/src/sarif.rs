@@ -0,0 +1,508 @@
+//! A SARIF emitter for errors.
+//!
+//! [SARIF] 2.1.0 is the format code scanning tools and security dashboards
+//! ingest. Diagnostics are mapped to *results*: error codes and lint names become
+//! *rules*, primary spans become *locations*, secondary spans and child notes
+//! become *related locations*, and suggestions become *fixes*.
+//!
+//! Unlike the JSON emitter, which prints every diagnostic as soon as it is
+//! emitted, a SARIF log is a single document describing the whole run. The
+//! diagnostics are therefore collected, and the log is written when the emitter
+//! is dropped.
+//!
+//! [SARIF]: https://docs.oasis-open.org/sarif/sarif/v2.1.0/sarif-v2.1.0.html
+
+use std::error::Report;
+use std::fmt::Write as _;
+use std::io::{self, Write};
+use std::thread;
+
+use derive_setters::Setters;
+use rustc_data_structures::fx::FxIndexMap;
+use rustc_data_structures::sync::{IntoDynSyncSend, Lrc};
+use rustc_error_messages::FluentArgs;
+use rustc_lint_defs::Applicability;
+use rustc_span::source_map::SourceMap;
+use rustc_span::Span;
+use serde::Serialize;
+
+use crate::diagnostic::IsLint;
+use crate::emitter::Emitter;
+use crate::registry::Registry;
+use crate::translation::{to_fluent_args, Translate};
+use crate::{CodeSuggestion, DiagInner, FluentBundle, LazyFallbackBundle, Level, Subdiag};
+
+#[cfg(test)]
+mod tests;
+
+const SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+#[derive(Setters)]
+pub struct SarifEmitter {
+    #[setters(skip)]
+    dst: IntoDynSyncSend<Box<dyn Write + Send>>,
+    registry: Option<Registry>,
+    #[setters(skip)]
+    sm: Lrc<SourceMap>,
+    fluent_bundle: Option<Lrc<FluentBundle>>,
+    #[setters(skip)]
+    fallback_bundle: LazyFallbackBundle,
+    /// The name of the tool the log is attributed to, e.g. `rustc` or `rustdoc`.
+    tool_name: &'static str,
+    tool_version: Option<&'static str>,
+    /// Don't write a log if nothing was emitted. This is used for the emitter of
+    /// early diagnostics, which is superseded by the session's one.
+    skip_empty_log: bool,
+    #[setters(skip)]
+    rules: FxIndexMap<String, Rule>,
+    #[setters(skip)]
+    results: Vec<SarifResult>,
+    #[setters(skip)]
+    notifications: Vec<Notification>,
+    #[setters(skip)]
+    has_errors: bool,
+}
+
+impl SarifEmitter {
+    pub fn new(
+        dst: Box<dyn Write + Send>,
+        sm: Lrc<SourceMap>,
+        fallback_bundle: LazyFallbackBundle,
+    ) -> SarifEmitter {
+        SarifEmitter {
+            dst: IntoDynSyncSend(dst),
+            registry: None,
+            sm,
+            fluent_bundle: None,
+            fallback_bundle,
+            tool_name: "rustc",
+            tool_version: None,
+            skip_empty_log: false,
+            rules: FxIndexMap::default(),
+            results: Vec::new(),
+            notifications: Vec::new(),
+            has_errors: false,
+        }
+    }
+
+    fn write_log(&mut self) -> io::Result<()> {
+        let log = SarifLog {
+            schema: SCHEMA,
+            version: "2.1.0",
+            runs: [Run {
+                tool: Tool {
+                    driver: ToolComponent {
+                        name: self.tool_name,
+                        version: self.tool_version,
+                        information_uri: "https://www.rust-lang.org/",
+                        rules: self.rules.values().collect(),
+                    },
+                },
+                invocations: [Invocation {
+                    execution_successful: !self.has_errors,
+                    tool_execution_notifications: &self.notifications,
+                }],
+                // Columns are counted in characters, not in UTF-16 code units.
+                column_kind: "unicodeCodePoints",
+                results: &self.results,
+            }],
+        };
+        serde_json::to_writer_pretty(&mut *self.dst, &log)?;
+        self.dst.write_all(b"\n")?;
+        self.dst.flush()
+    }
+
+    /// Returns the index of the rule for the diagnostic's error code or lint name,
+    /// adding it to the rules of the run if needed.
+    fn rule(&mut self, diag: &DiagInner) -> Option<(String, usize)> {
+        let (id, help_uri, description) = if let Some(code) = diag.code {
+            let description = self
+                .registry
+                .as_ref()
+                .and_then(|registry| registry.try_find_description(code).ok())
+                .map(|text| Message { text: text.trim().to_owned() });
+            let uri = format!("https://doc.rust-lang.org/error_codes/{code}.html");
+            (code.to_string(), Some(uri), description)
+        } else if let Some(IsLint { name, .. }) = &diag.is_lint {
+            let uri = if let Some(name) = name.strip_prefix("clippy::") {
+                Some(format!("https://rust-lang.github.io/rust-clippy/master/index.html#{name}"))
+            } else if let Some(name) = name.strip_prefix("rustdoc::") {
+                Some(format!("https://doc.rust-lang.org/rustdoc/lints.html#{name}"))
+            } else {
+                None
+            };
+            (name.clone(), uri, None)
+        } else {
+            return None;
+        };
+        let entry = self.rules.entry(id.clone());
+        let index = entry.index();
+        entry.or_insert(Rule { id: id.clone(), help_uri, full_description: description });
+        Some((id, index))
+    }
+
+    fn location(&self, span: Span, message: Option<String>, id: Option<usize>) -> Option<Location> {
+        Some(Location {
+            id,
+            physical_location: self.physical_location(span, true)?,
+            message: message.map(|text| Message { text }),
+        })
+    }
+
+    fn physical_location(&self, span: Span, with_snippet: bool) -> Option<PhysicalLocation> {
+        if span.is_dummy() {
+            return None;
+        }
+        // Point at the macro invocation rather than into the source of another crate.
+        let span = if self.sm.is_imported(span) { span.source_callsite() } else { span };
+        let start = self.sm.lookup_char_pos(span.lo());
+        let end = self.sm.lookup_char_pos(span.hi());
+        let byte_offset = start.file.original_relative_byte_pos(span.lo()).0;
+        let byte_end = start.file.original_relative_byte_pos(span.hi()).0;
+        let snippet = if with_snippet { self.sm.span_to_snippet(span).ok() } else { None };
+        Some(PhysicalLocation {
+            artifact_location: ArtifactLocation {
+                uri: path_to_uri(&self.sm.filename_for_diagnostics(&start.file.name).to_string()),
+            },
+            region: Region {
+                start_line: start.line,
+                start_column: start.col.0 + 1,
+                end_line: end.line,
+                end_column: end.col.0 + 1,
+                byte_offset,
+                byte_length: byte_end - byte_offset,
+                snippet: snippet.map(|text| Message { text }),
+            },
+        })
+    }
+
+    fn fixes(&self, suggestion: &CodeSuggestion, args: &FluentArgs<'_>) -> Vec<Fix> {
+        let description =
+            self.translate_message(&suggestion.msg, args).map_err(Report::new).unwrap();
+        suggestion
+            .substitutions
+            .iter()
+            .filter_map(|substitution| {
+                // Group the replacements by file, keeping the order of the parts.
+                let mut changes: FxIndexMap<String, Vec<Replacement>> = FxIndexMap::default();
+                for part in &substitution.parts {
+                    let PhysicalLocation { artifact_location, region } =
+                        self.physical_location(part.span, false)?;
+                    changes.entry(artifact_location.uri).or_default().push(Replacement {
+                        deleted_region: region,
+                        inserted_content: Message { text: part.snippet.clone() },
+                    });
+                }
+                Some(Fix {
+                    description: Message { text: description.to_string() },
+                    artifact_changes: changes
+                        .into_iter()
+                        .map(|(uri, replacements)| ArtifactChange {
+                            artifact_location: ArtifactLocation { uri },
+                            replacements,
+                        })
+                        .collect(),
+                    properties: FixProperties { applicability: suggestion.applicability },
+                })
+            })
+            .collect()
+    }
+
+    fn add_child(&self, result: &mut SarifResult, subdiag: &Subdiag, args: &FluentArgs<'_>) {
+        let text = format!(
+            "{}: {}",
+            subdiag.level.to_str(),
+            self.translate_messages(&subdiag.messages, args)
+        );
+        let primary_spans = subdiag.span.primary_spans();
+        if primary_spans.is_empty() {
+            // Notes without a span are part of the message, like they are
+            // rendered below it by the human-readable emitter.
+            write!(result.message.text, "\n{text}").unwrap();
+            return;
+        }
+        for &span in primary_spans {
+            let id = result.related_locations.len();
+            if let Some(location) = self.location(span, Some(text.clone()), Some(id)) {
+                result.related_locations.push(location);
+            }
+        }
+    }
+}
+
+impl Drop for SarifEmitter {
+    fn drop(&mut self) {
+        if self.skip_empty_log && self.results.is_empty() && self.notifications.is_empty() {
+            return;
+        }
+        if let Err(e) = self.write_log()
+            && !thread::panicking()
+        {
+            panic!("failed to print diagnostics: {e:?}");
+        }
+    }
+}
+
+impl Translate for SarifEmitter {
+    fn fluent_bundle(&self) -> Option<&Lrc<FluentBundle>> {
+        self.fluent_bundle.as_ref()
+    }
+
+    fn fallback_fluent_bundle(&self) -> &FluentBundle {
+        &self.fallback_bundle
+    }
+}
+
+impl Emitter for SarifEmitter {
+    fn emit_diagnostic(&mut self, diag: DiagInner) {
+        let args = to_fluent_args(diag.args.iter());
+        let text = self.translate_messages(&diag.messages, &args).to_string();
+
+        // Failure notes, like "aborting due to 2 previous errors", summarize the
+        // run rather than point at a problem in the code.
+        if diag.level == Level::FailureNote {
+            self.notifications.push(Notification { level: "note", message: Message { text } });
+            return;
+        }
+        if diag.is_error() {
+            self.has_errors = true;
+        }
+
+        let rule = self.rule(&diag);
+        let mut result = SarifResult {
+            rule_id: rule.as_ref().map(|(id, _)| id.clone()),
+            rule_index: rule.map(|(_, index)| index),
+            level: sarif_level(diag.level),
+            message: Message { text },
+            locations: Vec::new(),
+            related_locations: Vec::new(),
+            fixes: Vec::new(),
+        };
+
+        for span_label in diag.span.span_labels() {
+            let label = span_label
+                .label
+                .as_ref()
+                .map(|label| self.translate_message(label, &args).unwrap().to_string());
+            if span_label.is_primary {
+                if let Some(location) = self.location(span_label.span, label, None) {
+                    result.locations.push(location);
+                }
+            } else {
+                let id = result.related_locations.len();
+                if let Some(location) = self.location(span_label.span, label, Some(id)) {
+                    result.related_locations.push(location);
+                }
+            }
+        }
+        for child in &diag.children {
+            self.add_child(&mut result, child, &args);
+        }
+        for suggestion in diag.suggestions.iter().flatten() {
+            result.fixes.extend(self.fixes(suggestion, &args));
+        }
+
+        self.results.push(result);
+    }
+
+    fn source_map(&self) -> Option<&Lrc<SourceMap>> {
+        Some(&self.sm)
+    }
+
+    fn should_show_explain(&self) -> bool {
+        false
+    }
+}
+
+fn sarif_level(level: Level) -> &'static str {
+    match level {
+        Level::Bug | Level::DelayedBug | Level::Fatal | Level::Error => "error",
+        Level::ForceWarning(_) | Level::Warning => "warning",
+        Level::Note
+        | Level::OnceNote
+        | Level::Help
+        | Level::OnceHelp
+        | Level::FailureNote
+        | Level::Allow
+        | Level::Expect(_) => "note",
+    }
+}
+
+/// Turns a file name as it is shown in diagnostics into a URI reference: relative
+/// paths stay relative, and absolute ones become `file://` URIs.
+fn path_to_uri(path: &str) -> String {
+    let path = path.replace('\\', "/");
+    let bytes = path.as_bytes();
+    let mut uri = String::with_capacity(path.len());
+    let rest = if bytes.first() == Some(&b'/') {
+        uri.push_str("file://");
+        bytes
+    } else if bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':' {
+        // A Windows path with a drive letter.
+        uri.push_str("file:///");
+        uri.push(bytes[0] as char);
+        uri.push(':');
+        &bytes[2..]
+    } else {
+        bytes
+    };
+    for &b in rest {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => {
+                uri.push(b as char)
+            }
+            _ => write!(uri, "%{b:02X}").unwrap(),
+        }
+    }
+    uri
+}
+
+// The following data types are provided just for serialisation.
+
+#[derive(Serialize)]
+struct SarifLog<'a> {
+    #[serde(rename = "$schema")]
+    schema: &'static str,
+    version: &'static str,
+    runs: [Run<'a>; 1],
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Run<'a> {
+    tool: Tool<'a>,
+    invocations: [Invocation<'a>; 1],
+    column_kind: &'static str,
+    results: &'a [SarifResult],
+}
+
+#[derive(Serialize)]
+struct Tool<'a> {
+    driver: ToolComponent<'a>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ToolComponent<'a> {
+    name: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    version: Option<&'a str>,
+    information_uri: &'static str,
+    rules: Vec<&'a Rule>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Rule {
+    /// The error code (e.g. "E0308") or lint name.
+    id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    help_uri: Option<String>,
+    /// The explanation of the error code.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    full_description: Option<Message>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Invocation<'a> {
+    execution_successful: bool,
+    tool_execution_notifications: &'a [Notification],
+}
+
+#[derive(Serialize)]
+struct Notification {
+    level: &'static str,
+    message: Message,
+}
+
+/// A message, or the contents of a region of a file.
+#[derive(Serialize)]
+struct Message {
+    text: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifResult {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rule_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rule_index: Option<usize>,
+    /// "error", "warning" or "note".
+    level: &'static str,
+    message: Message,
+    locations: Vec<Location>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    related_locations: Vec<Location>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    fixes: Vec<Fix>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Location {
+    /// Identifies related locations within a result.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<usize>,
+    physical_location: PhysicalLocation,
+    /// The label of the span.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message: Option<Message>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PhysicalLocation {
+    artifact_location: ArtifactLocation,
+    region: Region,
+}
+
+#[derive(Serialize)]
+struct ArtifactLocation {
+    uri: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Region {
+    /// 1-based.
+    start_line: usize,
+    /// 1-based, character offset.
+    start_column: usize,
+    end_line: usize,
+    end_column: usize,
+    byte_offset: u32,
+    byte_length: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    snippet: Option<Message>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Fix {
+    description: Message,
+    artifact_changes: Vec<ArtifactChange>,
+    properties: FixProperties,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ArtifactChange {
+    artifact_location: ArtifactLocation,
+    replacements: Vec<Replacement>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Replacement {
+    deleted_region: Region,
+    inserted_content: Message,
+}
+
+/// SARIF has no notion of how reliable a fix is, so this is stored in the
+/// property bag of the fix.
+#[derive(Serialize)]
+struct FixProperties {
+    applicability: Applicability,
+}
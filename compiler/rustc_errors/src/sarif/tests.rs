@@ -0,0 +1,159 @@
+use std::path::Path;
+use std::str;
+use std::sync::{Arc, Mutex};
+
+use rustc_span::source_map::FilePathMapping;
+use rustc_span::BytePos;
+use serde_json::Value;
+
+use super::*;
+use crate::{DiagCtxt, E0308};
+
+struct Shared<T> {
+    data: Arc<Mutex<T>>,
+}
+
+impl<T: Write> Write for Shared<T> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.data.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.data.lock().unwrap().flush()
+    }
+}
+
+/// Runs `f` with a `DiagCtxt` using a `SarifEmitter`, and returns the log.
+fn sarif_log(code: &str, f: impl FnOnce(&DiagCtxt)) -> Value {
+    rustc_span::create_default_session_globals_then(|| {
+        let sm = Lrc::new(SourceMap::new(FilePathMapping::empty()));
+        sm.new_source_file(Path::new("src/main.rs").to_owned().into(), code.to_owned());
+        let fallback_bundle =
+            crate::fallback_fluent_bundle(vec![crate::DEFAULT_LOCALE_RESOURCE], false);
+
+        let output = Arc::new(Mutex::new(Vec::new()));
+        let emitter =
+            SarifEmitter::new(Box::new(Shared { data: output.clone() }), sm, fallback_bundle)
+                .registry(Some(Registry::new(crate::codes::DIAGNOSTICS)))
+                .tool_version(Some("1.0.0"));
+        let dcx = DiagCtxt::new(Box::new(emitter));
+        f(&dcx);
+        drop(dcx);
+
+        let bytes = output.lock().unwrap();
+        serde_json::from_str(str::from_utf8(&bytes).unwrap()).unwrap()
+    })
+}
+
+fn span(lo: u32, hi: u32) -> Span {
+    Span::with_root_ctxt(BytePos(lo), BytePos(hi))
+}
+
+#[test]
+fn error_with_labels_notes_and_fixes() {
+    let log = sarif_log("fn main() {\n    let x: u8 = \"a\";\n}\n", |dcx| {
+        dcx.handle()
+            .struct_span_err(span(28, 31), "mismatched types")
+            .with_code(E0308)
+            .with_span_label(span(28, 31), "expected `u8`, found `&str`")
+            .with_span_label(span(23, 25), "expected due to this")
+            .with_note("a note without a span")
+            .with_span_note(span(16, 17), "a note with a span")
+            .with_span_suggestion(
+                span(23, 25),
+                "change the type",
+                "&str",
+                Applicability::MaybeIncorrect,
+            )
+            .emit();
+    });
+
+    assert_eq!(log["version"], "2.1.0");
+    let run = &log["runs"][0];
+    assert_eq!(run["tool"]["driver"]["name"], "rustc");
+    assert_eq!(run["tool"]["driver"]["version"], "1.0.0");
+    let rule = &run["tool"]["driver"]["rules"][0];
+    assert_eq!(rule["id"], "E0308");
+    assert_eq!(rule["helpUri"], "https://doc.rust-lang.org/error_codes/E0308.html");
+    assert!(rule["fullDescription"]["text"].as_str().unwrap().starts_with("Expected type"));
+    assert_eq!(run["invocations"][0]["executionSuccessful"], false);
+
+    let result = &run["results"][0];
+    assert_eq!(result["ruleId"], "E0308");
+    assert_eq!(result["ruleIndex"], 0);
+    assert_eq!(result["level"], "error");
+    assert_eq!(result["message"]["text"], "mismatched types\nnote: a note without a span");
+
+    let location = &result["locations"][0];
+    assert_eq!(location["message"]["text"], "expected `u8`, found `&str`");
+    assert_eq!(location["physicalLocation"]["artifactLocation"]["uri"], "src/main.rs");
+    let region = &location["physicalLocation"]["region"];
+    assert_eq!((&region["startLine"], &region["startColumn"]), (&2.into(), &17.into()));
+    assert_eq!((&region["endLine"], &region["endColumn"]), (&2.into(), &20.into()));
+    assert_eq!((&region["byteOffset"], &region["byteLength"]), (&28.into(), &3.into()));
+    assert_eq!(region["snippet"]["text"], "\"a\"");
+
+    let related = result["relatedLocations"].as_array().unwrap();
+    assert_eq!(related.len(), 2);
+    assert_eq!(related[0]["id"], 0);
+    assert_eq!(related[0]["message"]["text"], "expected due to this");
+    assert_eq!(related[1]["id"], 1);
+    assert_eq!(related[1]["message"]["text"], "note: a note with a span");
+
+    let fix = &result["fixes"][0];
+    assert_eq!(fix["description"]["text"], "change the type");
+    assert_eq!(fix["properties"]["applicability"], "MaybeIncorrect");
+    let change = &fix["artifactChanges"][0];
+    assert_eq!(change["artifactLocation"]["uri"], "src/main.rs");
+    let replacement = &change["replacements"][0];
+    assert_eq!(replacement["deletedRegion"]["byteOffset"], 23);
+    assert_eq!(replacement["insertedContent"]["text"], "&str");
+}
+
+#[test]
+fn lints_share_rules() {
+    let log = sarif_log("fn main() { let a = 1; let b = 2; }", |dcx| {
+        for (lo, hi) in [(16, 17), (27, 28)] {
+            let mut diag = dcx.handle().struct_span_warn(span(lo, hi), "unused variable");
+            diag.is_lint("clippy::unused_variable".to_owned(), false);
+            diag.emit();
+        }
+    });
+
+    let run = &log["runs"][0];
+    let rules = run["tool"]["driver"]["rules"].as_array().unwrap();
+    assert_eq!(rules.len(), 1);
+    assert_eq!(rules[0]["id"], "clippy::unused_variable");
+    assert_eq!(
+        rules[0]["helpUri"],
+        "https://rust-lang.github.io/rust-clippy/master/index.html#unused_variable"
+    );
+    let results = run["results"].as_array().unwrap();
+    assert_eq!(results.len(), 2);
+    assert!(results.iter().all(|r| r["level"] == "warning" && r["ruleIndex"] == 0));
+    assert_eq!(run["invocations"][0]["executionSuccessful"], true);
+}
+
+#[test]
+fn failure_notes_are_notifications() {
+    let log = sarif_log("", |dcx| {
+        dcx.handle().err("no input");
+        dcx.handle().struct_failure_note("aborting due to 1 previous error").emit();
+    });
+
+    let run = &log["runs"][0];
+    assert_eq!(run["results"].as_array().unwrap().len(), 1);
+    assert_eq!(run["results"][0]["locations"].as_array().unwrap().len(), 0);
+    let notification = &run["invocations"][0]["toolExecutionNotifications"][0];
+    assert_eq!(notification["level"], "note");
+    assert_eq!(notification["message"]["text"], "aborting due to 1 previous error");
+}
+
+#[test]
+fn uris() {
+    assert_eq!(path_to_uri("src/main.rs"), "src/main.rs");
+    assert_eq!(path_to_uri("src\\my file.rs"), "src/my%20file.rs");
+    assert_eq!(path_to_uri("/home/me/lib.rs"), "file:///home/me/lib.rs");
+    assert_eq!(path_to_uri("C:\\src\\lib.rs"), "file:///C:/src/lib.rs");
+    assert_eq!(path_to_uri("<anon>"), "%3Canon%3E");
+}
@@ -517,6 +517,10 @@ pub struct BuiltinAttribute {
         EncodeCrossCrate::Yes, custom_test_frameworks,
         "custom test frameworks are an unstable feature",
     ),
+    gated!(
+        test_timeout, Normal, template!(NameValueStr: "seconds"), ErrorFollowing,
+        EncodeCrossCrate::No, experimental!(test_timeout)
+    ),
     // RFC #1268
     gated!(
         marker, Normal, template!(Word), WarnFollowing, EncodeCrossCrate::No,
@@ -590,6 +594,16 @@ pub struct BuiltinAttribute {
         patchable_function_entry, Normal, template!(List: "prefix_nops = m, entry_nops = n"), ErrorPreceding,
         EncodeCrossCrate::Yes, experimental!(patchable_function_entry)
     ),
+    // `#[stack_limit = "N"]`, checked by `-Zprint-stack-depth`
+    gated!(
+        stack_limit, Normal, template!(NameValueStr: "N"), ErrorFollowing,
+        EncodeCrossCrate::No, experimental!(stack_limit)
+    ),
+    // `#[panic_free]`, checked by the mono item collector
+    gated!(
+        panic_free, Normal, template!(Word), WarnFollowing,
+        EncodeCrossCrate::No, experimental!(panic_free)
+    ),
 
     // ==========================================================================
     // Internal attributes: Stability, deprecation, and unsafe:
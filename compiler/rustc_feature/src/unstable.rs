@@ -564,6 +564,8 @@ pub fn internal(&self, feature: Symbol) -> bool {
     (unstable, offset_of_slice, "1.81.0", Some(126151)),
     /// Allows using `#[optimize(X)]`.
     (unstable, optimize_attribute, "1.34.0", Some(54882)),
+    /// Allows `#[panic_free]` to reject functions that can reach a panic.
+    (unstable, panic_free, "CURRENT_RUSTC_VERSION", None),
     /// Allows specifying nop padding on functions for dynamic patching.
     (unstable, patchable_function_entry, "1.81.0", Some(123115)),
     /// Allows postfix match `expr.match { ... }`
@@ -597,6 +599,8 @@ pub fn internal(&self, feature: Symbol) -> bool {
     (unstable, simd_ffi, "1.0.0", Some(27731)),
     /// Allows specialization of implementations (RFC 1210).
     (incomplete, specialization, "1.7.0", Some(31844)),
+    /// Allows `#[stack_limit = "N"]` to give a function a worst-case stack depth budget in bytes.
+    (unstable, stack_limit, "CURRENT_RUSTC_VERSION", None),
     /// Allows attributes on expressions and non-item statements.
     (unstable, stmt_expr_attributes, "1.6.0", Some(15701)),
     /// Allows lints part of the strict provenance effort.
@@ -605,6 +609,8 @@ pub fn internal(&self, feature: Symbol) -> bool {
     (unstable, string_deref_patterns, "1.67.0", Some(87121)),
     /// Allows the use of `#[target_feature]` on safe functions.
     (unstable, target_feature_11, "1.45.0", Some(69098)),
+    /// Allows `#[test_timeout = "secs"]` to bound how long a `#[test]` may run.
+    (unstable, test_timeout, "CURRENT_RUSTC_VERSION", None),
     /// Allows using `#[thread_local]` on `static` items.
     (unstable, thread_local, "1.0.0", Some(29594)),
     /// Allows defining `trait X = A + B;` alias items.
@@ -27,7 +27,9 @@
 use rustc_passes::{abi_test, hir_stats, layout_test};
 use rustc_resolve::Resolver;
 use rustc_session::code_stats::VTableSizeInfo;
-use rustc_session::config::{CrateType, Input, OutFileName, OutputFilenames, OutputType};
+use rustc_session::config::{
+    CrateType, Input, OutFileName, OutputFilenames, OutputType, PrintTypeSizesFormat,
+};
 use rustc_session::cstore::Untracked;
 use rustc_session::output::{collect_crate_types, filename_for_input, find_crate_name};
 use rustc_session::search_paths::PathKind;
@@ -881,7 +883,10 @@ fn analysis(tcx: TyCtxt<'_>, (): ()) -> Result<()> {
         let _ = tcx.all_diagnostic_items(());
     });
 
-    if sess.opts.unstable_opts.print_vtable_sizes {
+    // The JSON type size report includes the vtables as well.
+    if sess.opts.unstable_opts.print_vtable_sizes
+        || sess.opts.unstable_opts.print_type_sizes == Some(PrintTypeSizesFormat::Json)
+    {
         let traits = tcx.traits(LOCAL_CRATE);
 
         for &tr in traits {
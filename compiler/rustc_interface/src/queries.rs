@@ -13,7 +13,7 @@
 use rustc_middle::dep_graph::DepGraph;
 use rustc_middle::ty::{GlobalCtxt, TyCtxt};
 use rustc_serialize::opaque::FileEncodeResult;
-use rustc_session::config::{self, OutputFilenames, OutputType};
+use rustc_session::config::{self, OutputFilenames, OutputType, PrintTypeSizesFormat};
 use rustc_session::Session;
 
 use crate::errors::FailedWritingFile;
@@ -130,8 +130,13 @@ pub fn codegen_and_build_linker(
 
         // This must run after monomorphization so that all generic types
         // have been instantiated.
-        if tcx.sess.opts.unstable_opts.print_type_sizes {
-            tcx.sess.code_stats.print_type_sizes();
+        match tcx.sess.opts.unstable_opts.print_type_sizes {
+            Some(PrintTypeSizesFormat::Text) => tcx.sess.code_stats.print_type_sizes(),
+            Some(PrintTypeSizesFormat::Json) => {
+                let crate_name = tcx.crate_name(LOCAL_CRATE);
+                tcx.sess.code_stats.print_type_sizes_json(tcx.sess.source_map(), crate_name);
+            }
+            None => {}
         }
 
         if tcx.sess.opts.unstable_opts.print_vtable_sizes {
@@ -49,6 +49,9 @@ pub struct CodegenFnAttrs {
     /// The `#[patchable_function_entry(...)]` attribute. Indicates how many nops should be around
     /// the function entry.
     pub patchable_function_entry: Option<PatchableFunctionEntry>,
+    /// The `#[stack_limit = "N"]` attribute. The number of bytes of stack the function may use,
+    /// including everything it calls, as checked by `-Zprint-stack-depth`.
+    pub stack_limit: Option<u64>,
 }
 
 #[derive(Copy, Clone, Debug, TyEncodable, TyDecodable, HashStable)]
@@ -126,6 +129,9 @@ impl CodegenFnAttrFlags: u32 {
         const ALLOCATOR_ZEROED          = 1 << 18;
         /// `#[no_builtins]`: indicates that disable implicit builtin knowledge of functions for the function.
         const NO_BUILTINS               = 1 << 19;
+        /// `#[panic_free]`: the function must not be able to reach a panic, which is checked
+        /// once the mono items have been collected.
+        const PANIC_FREE                = 1 << 20;
     }
 }
 rustc_data_structures::external_bitflags_debug! { CodegenFnAttrFlags }
@@ -149,6 +155,7 @@ pub const fn new() -> CodegenFnAttrs {
             instruction_set: None,
             alignment: None,
             patchable_function_entry: None,
+            stack_limit: None,
         }
     }
 
@@ -305,6 +305,20 @@ pub enum Visibility {
     Protected,
 }
 
+/// How an instance uses one of the functions returned by the `instance_callees` query.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, HashStable)]
+pub enum CalleeKind {
+    /// A direct call, including the calls to the panic functions behind assertions.
+    Call,
+    /// The drop glue run by a `Drop` terminator.
+    Drop,
+    /// A method or the drop glue put into a vtable by an unsizing cast.
+    VTable,
+    /// A function that is turned into a function pointer or named by a `sym` operand of inline
+    /// assembly.
+    Reify,
+}
+
 impl<'tcx> CodegenUnit<'tcx> {
     #[inline]
     pub fn new(name: Symbol) -> CodegenUnit<'tcx> {
@@ -58,7 +58,7 @@
     EvalStaticInitializerRawResult, EvalToAllocationRawResult, EvalToConstValueResult,
     EvalToValTreeResult, GlobalId, LitToConstError, LitToConstInput,
 };
-use crate::mir::mono::CodegenUnit;
+use crate::mir::mono::{CalleeKind, CodegenUnit};
 use crate::query::erase::{erase, restore, Erase};
 use crate::query::plumbing::{
     query_ensure, query_ensure_error_guaranteed, query_get_at, CyclePlaceholder, DynamicQuery,
@@ -1959,6 +1959,13 @@
         desc { "collect_and_partition_mono_items" }
     }
 
+    /// The functions that the given instance may call, along with how it uses them. The MIR of the
+    /// instance is scanned the same way as by mono item collection, except that the functions that
+    /// are codegened by other crates are kept as well.
+    query instance_callees(key: ty::Instance<'tcx>) -> &'tcx [(ty::Instance<'tcx>, CalleeKind)] {
+        desc { "finding the functions called by `{}`", key }
+    }
+
     query is_codegened_item(def_id: DefId) -> bool {
         desc { |tcx| "determining whether `{}` needs codegen", tcx.def_path_str(def_id) }
     }
@@ -1,6 +1,9 @@
 monomorphize_couldnt_dump_mono_stats =
     unexpected error occurred while dumping monomorphization stats: {$error}
 
+monomorphize_couldnt_emit_call_graph =
+    unexpected error occurred while emitting the call graph: {$error}
+
 monomorphize_encountered_error_while_instantiating =
     the above error was encountered while instantiating `{$formatted_item}`
 
@@ -13,6 +16,23 @@ monomorphize_no_optimized_mir =
     missing optimized MIR for an item in the crate `{$crate_name}`
     .note = missing optimized MIR for this item (was the crate `{$crate_name}` compiled with `--emit=metadata`?)
 
+monomorphize_panic_free_can_panic =
+    `{$name}` is marked `#[panic_free]` but can reach `{$panic}`
+
+monomorphize_panic_free_external_call =
+    cannot verify call to `{$callee}` from another crate in `#[panic_free]` function `{$name}`
+
+monomorphize_panic_free_indirect_call =
+    cannot verify call through function pointer in `#[panic_free]` function `{$name}`
+
+monomorphize_panic_free_uninstantiated =
+    cannot verify `#[panic_free]` function `{$name}` because it is not instantiated in this crate
+    .note = generic functions are checked in the crates that instantiate them with `#![feature(panic_free)]`
+
+monomorphize_panic_path_indirect_step = `{$caller}` calls through a function pointer or vtable here
+
+monomorphize_panic_path_step = `{$caller}` calls `{$callee}` here
+
 monomorphize_recursion_limit =
     reached the recursion limit while instantiating `{$shrunk}`
     .note = `{$def_path_str}` defined here
@@ -0,0 +1,268 @@
+//! Writes the monomorphized call graph requested with `--emit=call-graph`.
+//!
+//! The nodes are the mono items found by the collector and the edges are the uses it recorded in
+//! the [`UsageMap`], labelled with how the item is used. Each node also carries the codegen units
+//! it was placed in by partitioning, so that the graph can be used to attribute binary size.
+//!
+//! The calls between functions are also handed to the stack depth analysis of
+//! `-Zprint-stack-depth`, which can only run once codegen has determined the frame sizes.
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+use rustc_data_structures::fx::FxHashMap;
+use rustc_middle::mir::mono::{CodegenUnit, InstantiationMode, MonoItem};
+use rustc_middle::ty::print::with_no_trimmed_paths;
+use rustc_middle::ty::{InstanceKind, TyCtxt};
+use rustc_session::code_stats::{self, StackGraphNode};
+use rustc_session::config::{CallGraphFormat, OutFileName, OutputType};
+use rustc_span::symbol::Symbol;
+
+use crate::collector::{OpaqueCall, UsageMap, UseKind};
+
+#[derive(serde::Serialize)]
+struct CallGraph {
+    crate_name: String,
+    nodes: Vec<Node>,
+    edges: Vec<Edge>,
+}
+
+#[derive(serde::Serialize)]
+struct Node {
+    name: String,
+    symbol: String,
+    kind: &'static str,
+    span: String,
+    size_estimate: usize,
+    /// Whether a copy of the item is instantiated in every codegen unit that uses it.
+    inline: bool,
+    cgus: Vec<CguPlacement>,
+}
+
+#[derive(serde::Serialize)]
+struct CguPlacement {
+    name: String,
+    linkage: String,
+}
+
+#[derive(serde::Serialize, PartialEq, Eq, PartialOrd, Ord)]
+struct Edge {
+    from: usize,
+    to: usize,
+    kind: &'static str,
+}
+
+impl UseKind {
+    fn name(self) -> &'static str {
+        match self {
+            UseKind::Call => "call",
+            UseKind::Drop => "drop",
+            UseKind::VTable => "vtable",
+            UseKind::Reify => "reify",
+            UseKind::Reference => "reference",
+        }
+    }
+}
+
+pub(crate) fn emit_call_graph<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    items: &[MonoItem<'tcx>],
+    usage_map: &UsageMap<'tcx>,
+    codegen_units: &[CodegenUnit<'tcx>],
+    crate_name: Symbol,
+) -> io::Result<()> {
+    let graph = build_call_graph(tcx, items, usage_map, codegen_units, crate_name);
+
+    match tcx.output_filenames(()).path(OutputType::CallGraph) {
+        OutFileName::Stdout => write_call_graph(tcx, &graph, &mut io::stdout().lock())?,
+        OutFileName::Real(path) => {
+            let mut file = BufWriter::new(File::create(&path)?);
+            write_call_graph(tcx, &graph, &mut file)?;
+            file.flush()?;
+            if tcx.sess.opts.json_artifact_notifications {
+                tcx.dcx().emit_artifact_notification(&path, "call-graph");
+            }
+        }
+    }
+    Ok(())
+}
+
+fn build_call_graph<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    items: &[MonoItem<'tcx>],
+    usage_map: &UsageMap<'tcx>,
+    codegen_units: &[CodegenUnit<'tcx>],
+    crate_name: Symbol,
+) -> CallGraph {
+    let mut item_to_cgus: FxHashMap<_, Vec<_>> = Default::default();
+    for cgu in codegen_units {
+        for (&mono_item, data) in cgu.items() {
+            item_to_cgus.entry(mono_item).or_default().push(CguPlacement {
+                name: cgu.name().to_string(),
+                linkage: format!("{:?}", data.linkage),
+            });
+        }
+    }
+
+    let mut nodes: Vec<_> = items
+        .iter()
+        .map(|&item| {
+            let kind = match item {
+                MonoItem::Fn(_) => "fn",
+                MonoItem::Static(_) => "static",
+                MonoItem::GlobalAsm(_) => "global_asm",
+            };
+            let span = tcx.def_span(item.def_id());
+            let mut cgus = item_to_cgus.remove(&item).unwrap_or_default();
+            cgus.sort_by(|a, b| a.name.cmp(&b.name));
+            let node = Node {
+                name: with_no_trimmed_paths!(item.to_string()),
+                symbol: item.symbol_name(tcx).name.to_string(),
+                kind,
+                span: tcx.sess.source_map().span_to_embeddable_string(span),
+                size_estimate: item.size_estimate(tcx),
+                inline: item.instantiation_mode(tcx) == InstantiationMode::LocalCopy,
+                cgus,
+            };
+            (item, node)
+        })
+        .collect();
+    // Symbol names are unique, so this gives every node a stable index.
+    nodes.sort_by(|(_, a), (_, b)| (&a.name, &a.symbol).cmp(&(&b.name, &b.symbol)));
+
+    let index: FxHashMap<_, _> =
+        nodes.iter().enumerate().map(|(i, &(item, _))| (item, i)).collect();
+    let index = &index;
+    let mut edges: Vec<_> = nodes
+        .iter()
+        .enumerate()
+        .flat_map(|(from, &(item, _))| {
+            usage_map.get_used_items(item).iter().filter_map(move |&(used_item, kind)| {
+                let to = *index.get(&used_item)?;
+                Some(Edge { from, to, kind: kind.name() })
+            })
+        })
+        .collect();
+    edges.sort();
+    edges.dedup();
+
+    CallGraph {
+        crate_name: crate_name.to_string(),
+        nodes: nodes.into_iter().map(|(_, node)| node).collect(),
+        edges,
+    }
+}
+
+fn write_call_graph(tcx: TyCtxt<'_>, graph: &CallGraph, w: &mut dyn Write) -> io::Result<()> {
+    match tcx.sess.opts.unstable_opts.call_graph_format {
+        CallGraphFormat::Json => {
+            serde_json::to_writer(&mut *w, graph)?;
+            writeln!(w)
+        }
+        CallGraphFormat::Dot => write_dot(graph, w),
+    }
+}
+
+fn write_dot(graph: &CallGraph, w: &mut dyn Write) -> io::Result<()> {
+    fn escape(s: &str) -> String {
+        s.replace('\\', "\\\\").replace('"', "\\\"")
+    }
+
+    writeln!(w, "digraph \"{}\" {{", escape(&graph.crate_name))?;
+    writeln!(w, "    node [shape=box, fontname=\"monospace\"];")?;
+    for (i, node) in graph.nodes.iter().enumerate() {
+        let cgus: Vec<_> = node.cgus.iter().map(|cgu| cgu.name.as_str()).collect();
+        write!(
+            w,
+            "    n{i} [label=\"{name}\\lsize estimate: {size}\\lcgus: {cgus}\\l\"",
+            name = escape(&node.name),
+            size = node.size_estimate,
+            cgus = escape(&cgus.join(", ")),
+        )?;
+        if node.inline {
+            write!(w, ", style=dashed")?;
+        }
+        writeln!(w, "];")?;
+    }
+    for Edge { from, to, kind } in &graph.edges {
+        if *kind == UseKind::Call.name() {
+            writeln!(w, "    n{from} -> n{to};")?;
+        } else {
+            writeln!(w, "    n{from} -> n{to} [label=\"{kind}\", style=dashed];")?;
+        }
+    }
+    writeln!(w, "}}")
+}
+
+/// Records the calls between the functions in `items` in the session, for `-Zprint-stack-depth`.
+pub(crate) fn record_stack_graph<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    items: &[MonoItem<'tcx>],
+    usage_map: &UsageMap<'tcx>,
+) {
+    let entry_fn = tcx.entry_fn(()).map(|(def_id, _)| def_id);
+    let functions: Vec<_> = items
+        .iter()
+        .filter_map(|&item| match item {
+            MonoItem::Fn(instance) => Some(instance),
+            MonoItem::Static(_) | MonoItem::GlobalAsm(_) => None,
+        })
+        .collect();
+    let index: FxHashMap<_, _> =
+        functions.iter().enumerate().map(|(i, &instance)| (instance, i)).collect();
+
+    let nodes = functions
+        .iter()
+        .map(|&instance| {
+            let item = MonoItem::Fn(instance);
+            let mut callees: Vec<_> = usage_map
+                .get_used_items(item)
+                .iter()
+                .filter_map(|&(used_item, kind)| match (used_item, kind) {
+                    (MonoItem::Fn(callee), UseKind::Call | UseKind::Drop) => {
+                        index.get(&callee).copied()
+                    }
+                    _ => None,
+                })
+                .collect();
+            callees.sort_unstable();
+            callees.dedup();
+
+            let mut opaque_calls: Vec<_> = usage_map
+                .get_opaque_calls(item)
+                .iter()
+                .map(|call| match call.node {
+                    OpaqueCall::Indirect => code_stats::OpaqueCall::Indirect,
+                    OpaqueCall::External(callee) => {
+                        code_stats::OpaqueCall::External(with_no_trimmed_paths!(callee.to_string()))
+                    }
+                })
+                .collect();
+            opaque_calls.sort_unstable();
+            opaque_calls.dedup();
+
+            // Only functions that are written by the user can be entry points or have a budget.
+            let (is_entry, stack_limit) = match instance.def {
+                InstanceKind::Item(def_id) => {
+                    let attrs = tcx.codegen_fn_attrs(def_id);
+                    let is_entry = Some(def_id) == entry_fn
+                        || attrs.contains_extern_indicator()
+                        || attrs.stack_limit.is_some();
+                    (is_entry, attrs.stack_limit)
+                }
+                _ => (false, None),
+            };
+
+            StackGraphNode {
+                name: with_no_trimmed_paths!(instance.to_string()),
+                symbol_name: item.symbol_name(tcx).name.to_string(),
+                span: tcx.def_span(instance.def_id()),
+                callees,
+                opaque_calls,
+                is_entry,
+                stack_limit,
+            }
+        })
+        .collect();
+    tcx.sess.code_stats.record_stack_graph(nodes);
+}
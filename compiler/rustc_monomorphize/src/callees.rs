@@ -0,0 +1,73 @@
+//! The `instance_callees` query, which lists the functions a single instance may call.
+//!
+//! Calls, drops, casts and `sym` operands of inline assembly are resolved by the same functions as
+//! in the collector, but the callees are kept whether or not they are codegened in the current
+//! crate, including virtual calls and intrinsics, and nothing is collected from the constants the
+//! instance uses. This is meant for tools that want to follow the call graph from an instance of
+//! their choosing, rather than collect all the mono items of a crate.
+
+use rustc_data_structures::fx::FxIndexSet;
+use rustc_middle::bug;
+use rustc_middle::mir::mono::CalleeKind;
+use rustc_middle::mir::visit::Visitor as MirVisitor;
+use rustc_middle::mir::{self, traversal, Location};
+use rustc_middle::ty::{Instance, TyCtxt};
+use rustc_middle::util::Providers;
+
+use crate::collector::{for_each_rvalue_use, for_each_terminator_use, UseKind};
+
+fn instance_callees<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    instance: Instance<'tcx>,
+) -> &'tcx [(Instance<'tcx>, CalleeKind)] {
+    let body = tcx.instance_mir(instance.def);
+    let mut collector = CalleeCollector { tcx, body, instance, callees: FxIndexSet::default() };
+    for (bb, data) in traversal::mono_reachable(body, tcx, instance) {
+        collector.visit_basic_block_data(bb, data);
+    }
+    tcx.arena.alloc_from_iter(collector.callees)
+}
+
+struct CalleeCollector<'a, 'tcx> {
+    tcx: TyCtxt<'tcx>,
+    body: &'a mir::Body<'tcx>,
+    instance: Instance<'tcx>,
+    callees: FxIndexSet<(Instance<'tcx>, CalleeKind)>,
+}
+
+impl<'a, 'tcx> CalleeCollector<'a, 'tcx> {
+    fn push(&mut self, callee: Instance<'tcx>, kind: UseKind) {
+        let kind = match kind {
+            UseKind::Call => CalleeKind::Call,
+            UseKind::Drop => CalleeKind::Drop,
+            UseKind::VTable => CalleeKind::VTable,
+            UseKind::Reify => CalleeKind::Reify,
+            UseKind::Reference => bug!("{callee} is not used by a terminator or rvalue"),
+        };
+        self.callees.insert((callee, kind));
+    }
+}
+
+impl<'a, 'tcx> MirVisitor<'tcx> for CalleeCollector<'a, 'tcx> {
+    fn visit_rvalue(&mut self, rvalue: &mir::Rvalue<'tcx>, location: Location) {
+        let span = self.body.source_info(location).span;
+        let (tcx, instance, body) = (self.tcx, self.instance, self.body);
+        for_each_rvalue_use(tcx, instance, body, rvalue, span, |callee, kind| {
+            self.push(callee, kind)
+        });
+        self.super_rvalue(rvalue, location);
+    }
+
+    fn visit_terminator(&mut self, terminator: &mir::Terminator<'tcx>, location: Location) {
+        let span = self.body.source_info(location).span;
+        let (tcx, instance, body) = (self.tcx, self.instance, self.body);
+        for_each_terminator_use(tcx, instance, body, terminator, span, |callee, kind| {
+            self.push(callee, kind)
+        });
+        self.super_terminator(terminator, location);
+    }
+}
+
+pub(crate) fn provide(providers: &mut Providers) {
+    providers.instance_callees = instance_callees;
+}
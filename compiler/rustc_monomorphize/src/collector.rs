@@ -207,6 +207,7 @@
 
 mod move_check;
 
+use std::iter;
 use std::path::PathBuf;
 
 use move_check::MoveCheckState;
@@ -248,11 +249,41 @@ pub enum MonoItemCollectionStrategy {
 }
 
 pub struct UsageMap<'tcx> {
-    // Maps every mono item to the mono items used by it.
-    used_map: UnordMap<MonoItem<'tcx>, Vec<MonoItem<'tcx>>>,
+    // Maps every mono item to the mono items used by it, and how they are used.
+    used_map: UnordMap<MonoItem<'tcx>, Vec<(MonoItem<'tcx>, UseKind)>>,
 
     // Maps every mono item to the mono items that use it.
     user_map: UnordMap<MonoItem<'tcx>, Vec<MonoItem<'tcx>>>,
+
+    // Maps mono items to the calls they make to functions that are not mono items of this crate.
+    // Only recorded for `-Zprint-stack-depth` and `#[panic_free]`.
+    opaque_calls: UnordMap<MonoItem<'tcx>, Vec<Spanned<OpaqueCall<'tcx>>>>,
+}
+
+/// How a mono item uses one of the items recorded for it in the [`UsageMap`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum UseKind {
+    /// A direct call, including calls to the panic functions behind assertions.
+    Call,
+    /// Drop glue run by a `Drop` terminator.
+    Drop,
+    /// A method or the drop glue put into a vtable by an unsizing cast.
+    VTable,
+    /// A function that is reified into a function pointer or named by a `sym` operand of inline
+    /// assembly.
+    Reify,
+    /// Anything else, like statics and the items that constants point to.
+    Reference,
+}
+
+/// A call whose callee is not a mono item of this crate, recorded for `-Zprint-stack-depth` and
+/// `#[panic_free]`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub(crate) enum OpaqueCall<'tcx> {
+    /// A call through a function pointer or a vtable.
+    Indirect,
+    /// A call to a function that is codegened by another crate or not written in Rust at all.
+    External(Instance<'tcx>),
 }
 
 type MonoItems<'tcx> = Vec<Spanned<MonoItem<'tcx>>>;
@@ -288,35 +319,62 @@ enum CollectionMode {
 
 impl<'tcx> UsageMap<'tcx> {
     fn new() -> UsageMap<'tcx> {
-        UsageMap { used_map: Default::default(), user_map: Default::default() }
+        UsageMap {
+            used_map: Default::default(),
+            user_map: Default::default(),
+            opaque_calls: Default::default(),
+        }
     }
 
+    /// `use_kinds` describes the first items of `used_items`, the remaining ones are recorded as
+    /// [`UseKind::Reference`].
     fn record_used<'a>(
         &mut self,
         user_item: MonoItem<'tcx>,
         used_items: &'a [Spanned<MonoItem<'tcx>>],
+        use_kinds: &[UseKind],
+        opaque_calls: Vec<Spanned<OpaqueCall<'tcx>>>,
     ) where
         'tcx: 'a,
     {
-        let used_items: Vec<_> = used_items.iter().map(|item| item.node).collect();
-        for &used_item in used_items.iter() {
+        let used_items: Vec<_> = used_items
+            .iter()
+            .enumerate()
+            .map(|(i, item)| (item.node, use_kinds.get(i).copied().unwrap_or(UseKind::Reference)))
+            .collect();
+        for &(used_item, _) in used_items.iter() {
             self.user_map.entry(used_item).or_default().push(user_item);
         }
 
         assert!(self.used_map.insert(user_item, used_items).is_none());
+        if !opaque_calls.is_empty() {
+            self.opaque_calls.insert(user_item, opaque_calls);
+        }
     }
 
     pub fn get_user_items(&self, item: MonoItem<'tcx>) -> &[MonoItem<'tcx>] {
         self.user_map.get(&item).map(|items| items.as_slice()).unwrap_or(&[])
     }
 
+    /// Returns the mono items used by `item`, in the order they were found, along with how they
+    /// are used. An item shows up once per use.
+    pub(crate) fn get_used_items(&self, item: MonoItem<'tcx>) -> &[(MonoItem<'tcx>, UseKind)] {
+        self.used_map.get(&item).map(|items| items.as_slice()).unwrap_or(&[])
+    }
+
+    /// Returns the calls made by `item` that cannot be followed in the usage map. These are only
+    /// recorded with `-Zprint-stack-depth` or when `#[panic_free]` is enabled.
+    pub(crate) fn get_opaque_calls(&self, item: MonoItem<'tcx>) -> &[Spanned<OpaqueCall<'tcx>>] {
+        self.opaque_calls.get(&item).map(|calls| calls.as_slice()).unwrap_or(&[])
+    }
+
     /// Internally iterate over all inlined items used by `item`.
     pub fn for_each_inlined_used_item<F>(&self, tcx: TyCtxt<'tcx>, item: MonoItem<'tcx>, mut f: F)
     where
         F: FnMut(MonoItem<'tcx>),
     {
         let used_items = self.used_map.get(&item).unwrap();
-        for used_item in used_items.iter() {
+        for (used_item, _) in used_items.iter() {
             let is_inlined = used_item.instantiation_mode(tcx) == InstantiationMode::LocalCopy;
             if is_inlined {
                 f(*used_item);
@@ -358,6 +416,8 @@ fn collect_items_rec<'tcx>(
     }
 
     let mut used_items = MonoItems::new();
+    let mut use_kinds = Vec::new();
+    let mut opaque_calls = Vec::new();
     let mut mentioned_items = MonoItems::new();
     let recursion_depth_reset;
 
@@ -447,6 +507,8 @@ fn collect_items_rec<'tcx>(
                     tcx,
                     instance,
                     &mut used_items,
+                    &mut use_kinds,
+                    &mut opaque_calls,
                     &mut mentioned_items,
                     mode,
                 )
@@ -515,7 +577,12 @@ fn collect_items_rec<'tcx>(
     // This is part of the output of collection and hence only relevant for "used" items.
     // ("Mentioned" items are only considered internally during collection.)
     if mode == CollectionMode::UsedItems {
-        state.usage_map.lock_mut().record_used(starting_item.node, &used_items);
+        state.usage_map.lock_mut().record_used(
+            starting_item.node,
+            &used_items,
+            &use_kinds,
+            opaque_calls,
+        );
     }
 
     if mode == CollectionMode::MentionedItems {
@@ -603,6 +670,10 @@ struct MirUsedCollector<'a, 'tcx> {
     tcx: TyCtxt<'tcx>,
     body: &'a mir::Body<'tcx>,
     used_items: &'a mut MonoItems<'tcx>,
+    /// How the items in `used_items` are used, see `record_use_kind`.
+    use_kinds: &'a mut Vec<UseKind>,
+    /// Calls that are not reflected in `used_items`, see `record_opaque_call`.
+    opaque_calls: &'a mut Vec<Spanned<OpaqueCall<'tcx>>>,
     /// See the comment in `collect_items_of_instance` for the purpose of this set.
     /// Note that this contains *not-monomorphized* items!
     used_mentioned_items: &'a mut UnordSet<MentionedItem<'tcx>>,
@@ -648,6 +719,46 @@ fn eval_constant(
             }
         }
     }
+
+    /// Records that the items pushed to `used_items` since the last call are used as `kind`.
+    fn record_use_kind(&mut self, kind: UseKind) {
+        let new_items = self.used_items.len() - self.use_kinds.len();
+        self.use_kinds.extend(iter::repeat(kind).take(new_items));
+    }
+
+    /// Records a call if its callee is not a mono item of this crate, where `None` stands for a
+    /// call through a function pointer or a vtable. Only needed for `-Zprint-stack-depth` and
+    /// `#[panic_free]`.
+    fn record_opaque_call(&mut self, callee: Option<Instance<'tcx>>, span: Span) {
+        let call = match callee {
+            None => OpaqueCall::Indirect,
+            Some(instance) => match instance.def {
+                ty::InstanceKind::Virtual(..) => OpaqueCall::Indirect,
+                // Intrinsics are lowered by codegen, and empty drop glue is never called.
+                ty::InstanceKind::Intrinsic(_) | ty::InstanceKind::DropGlue(_, None) => return,
+                _ if self.tcx.should_codegen_locally(instance) => return,
+                _ => OpaqueCall::External(instance),
+            },
+        };
+        self.opaque_calls.push(respan(span, call));
+    }
+
+    /// Pushes a function found by [`for_each_terminator_use`] or [`for_each_rvalue_use`] and
+    /// records how it is used. Calls and drops are also recorded with `record_opaque_call` if
+    /// `record_opaque_calls` is set.
+    fn push_use(
+        &mut self,
+        instance: Instance<'tcx>,
+        kind: UseKind,
+        source: Span,
+        record_opaque_calls: bool,
+    ) {
+        push_instance_use(self.tcx, instance, source, self.used_items);
+        self.record_use_kind(kind);
+        if record_opaque_calls && matches!(kind, UseKind::Call | UseKind::Drop) {
+            self.record_opaque_call(Some(instance), source);
+        }
+    }
 }
 
 impl<'a, 'tcx> MirVisitor<'tcx> for MirUsedCollector<'a, 'tcx> {
@@ -655,11 +766,10 @@ fn visit_rvalue(&mut self, rvalue: &mir::Rvalue<'tcx>, location: Location) {
         debug!("visiting rvalue {:?}", *rvalue);
 
         let span = self.body.source_info(location).span;
+        // Whatever was found since the last statement or terminator comes from constants.
+        self.record_use_kind(UseKind::Reference);
 
         match *rvalue {
-            // When doing an cast from a regular pointer to a fat pointer, we
-            // have to instantiate all methods of the trait being cast to, so we
-            // can build the appropriate vtable.
             mir::Rvalue::Cast(
                 mir::CastKind::PointerCoercion(PointerCoercion::Unsize),
                 ref operand,
@@ -670,24 +780,6 @@ fn visit_rvalue(&mut self, rvalue: &mir::Rvalue<'tcx>, location: Location) {
                 // *Before* monomorphizing, record that we already handled this mention.
                 self.used_mentioned_items
                     .insert(MentionedItem::UnsizeCast { source_ty, target_ty });
-                let target_ty = self.monomorphize(target_ty);
-                let source_ty = self.monomorphize(source_ty);
-                let (source_ty, target_ty) =
-                    find_vtable_types_for_unsizing(self.tcx.at(span), source_ty, target_ty);
-                // This could also be a different Unsize instruction, like
-                // from a fixed sized array to a slice. But we are only
-                // interested in things that produce a vtable.
-                if (target_ty.is_trait() && !source_ty.is_trait())
-                    || (target_ty.is_dyn_star() && !source_ty.is_dyn_star())
-                {
-                    create_mono_items_for_vtable_methods(
-                        self.tcx,
-                        target_ty,
-                        source_ty,
-                        span,
-                        self.used_items,
-                    );
-                }
             }
             mir::Rvalue::Cast(
                 mir::CastKind::PointerCoercion(PointerCoercion::ReifyFnPointer),
@@ -697,8 +789,6 @@ fn visit_rvalue(&mut self, rvalue: &mir::Rvalue<'tcx>, location: Location) {
                 let fn_ty = operand.ty(self.body, self.tcx);
                 // *Before* monomorphizing, record that we already handled this mention.
                 self.used_mentioned_items.insert(MentionedItem::Fn(fn_ty));
-                let fn_ty = self.monomorphize(fn_ty);
-                visit_fn_use(self.tcx, fn_ty, false, span, self.used_items);
             }
             mir::Rvalue::Cast(
                 mir::CastKind::PointerCoercion(PointerCoercion::ClosureFnPointer(_)),
@@ -708,16 +798,6 @@ fn visit_rvalue(&mut self, rvalue: &mir::Rvalue<'tcx>, location: Location) {
                 let source_ty = operand.ty(self.body, self.tcx);
                 // *Before* monomorphizing, record that we already handled this mention.
                 self.used_mentioned_items.insert(MentionedItem::Closure(source_ty));
-                let source_ty = self.monomorphize(source_ty);
-                if let ty::Closure(def_id, args) = *source_ty.kind() {
-                    let instance =
-                        Instance::resolve_closure(self.tcx, def_id, args, ty::ClosureKind::FnOnce);
-                    if self.tcx.should_codegen_locally(instance) {
-                        self.used_items.push(create_fn_mono_item(self.tcx, instance, span));
-                    }
-                } else {
-                    bug!()
-                }
             }
             mir::Rvalue::ThreadLocalRef(def_id) => {
                 assert!(self.tcx.is_thread_local_static(def_id));
@@ -729,6 +809,10 @@ fn visit_rvalue(&mut self, rvalue: &mir::Rvalue<'tcx>, location: Location) {
             }
             _ => { /* not interesting */ }
         }
+        let (tcx, instance, body) = (self.tcx, self.instance, self.body);
+        for_each_rvalue_use(tcx, instance, body, rvalue, span, |used, kind| {
+            self.push_use(used, kind, span, false)
+        });
 
         self.super_rvalue(rvalue, location);
     }
@@ -747,12 +831,11 @@ fn visit_terminator(&mut self, terminator: &mir::Terminator<'tcx>, location: Loc
         let source = self.body.source_info(location).span;
 
         let tcx = self.tcx;
-        let push_mono_lang_item = |this: &mut Self, lang_item: LangItem| {
-            let instance = Instance::mono(tcx, tcx.require_lang_item(lang_item, Some(source)));
-            if tcx.should_codegen_locally(instance) {
-                this.used_items.push(create_fn_mono_item(tcx, instance, source));
-            }
-        };
+        let record_opaque_calls =
+            tcx.sess.opts.unstable_opts.print_stack_depth || tcx.features().panic_free;
+        let mut drops_dyn = false;
+
+        self.record_use_kind(UseKind::Reference);
 
         match terminator.kind {
             mir::TerminatorKind::Call { ref func, ref args, ref fn_span, .. }
@@ -762,14 +845,19 @@ fn visit_terminator(&mut self, terminator: &mir::Terminator<'tcx>, location: Loc
                 self.used_mentioned_items.insert(MentionedItem::Fn(callee_ty));
                 let callee_ty = self.monomorphize(callee_ty);
                 self.check_fn_args_move_size(callee_ty, args, *fn_span, location);
-                visit_fn_use(self.tcx, callee_ty, true, source, &mut self.used_items)
+                if record_opaque_calls && !matches!(callee_ty.kind(), ty::FnDef(..)) {
+                    self.record_opaque_call(None, source);
+                }
             }
             mir::TerminatorKind::Drop { ref place, .. } => {
                 let ty = place.ty(self.body, self.tcx).ty;
                 // *Before* monomorphizing, record that we already handled this mention.
                 self.used_mentioned_items.insert(MentionedItem::Drop(ty));
-                let ty = self.monomorphize(ty);
-                visit_drop_use(self.tcx, ty, true, source, self.used_items);
+                // Dropping a trait object calls the drop glue from its vtable.
+                drops_dyn = matches!(self.monomorphize(ty).kind(), ty::Dynamic(..));
+                if record_opaque_calls && drops_dyn {
+                    self.record_opaque_call(None, source);
+                }
             }
             mir::TerminatorKind::InlineAsm { ref operands, .. } => {
                 for op in operands {
@@ -778,48 +866,26 @@ fn visit_terminator(&mut self, terminator: &mir::Terminator<'tcx>, location: Loc
                             let fn_ty = value.const_.ty();
                             // *Before* monomorphizing, record that we already handled this mention.
                             self.used_mentioned_items.insert(MentionedItem::Fn(fn_ty));
-                            let fn_ty = self.monomorphize(fn_ty);
-                            visit_fn_use(self.tcx, fn_ty, false, source, self.used_items);
                         }
                         mir::InlineAsmOperand::SymStatic { def_id } => {
                             let instance = Instance::mono(self.tcx, def_id);
                             if self.tcx.should_codegen_locally(instance) {
                                 trace!("collecting asm sym static {:?}", def_id);
                                 self.used_items.push(respan(source, MonoItem::Static(def_id)));
+                                self.record_use_kind(UseKind::Reference);
                             }
                         }
                         _ => {}
                     }
                 }
             }
-            mir::TerminatorKind::Assert { ref msg, .. } => match &**msg {
-                mir::AssertKind::BoundsCheck { .. } => {
-                    push_mono_lang_item(self, LangItem::PanicBoundsCheck);
-                }
-                mir::AssertKind::MisalignedPointerDereference { .. } => {
-                    push_mono_lang_item(self, LangItem::PanicMisalignedPointerDereference);
-                }
-                _ => {
-                    push_mono_lang_item(self, msg.panic_function());
-                }
-            },
-            mir::TerminatorKind::UnwindTerminate(reason) => {
-                push_mono_lang_item(self, reason.lang_item());
-            }
-            mir::TerminatorKind::Goto { .. }
-            | mir::TerminatorKind::SwitchInt { .. }
-            | mir::TerminatorKind::UnwindResume
-            | mir::TerminatorKind::Return
-            | mir::TerminatorKind::Unreachable => {}
-            mir::TerminatorKind::CoroutineDrop
-            | mir::TerminatorKind::Yield { .. }
-            | mir::TerminatorKind::FalseEdge { .. }
-            | mir::TerminatorKind::FalseUnwind { .. } => bug!(),
-        }
-
-        if let Some(mir::UnwindAction::Terminate(reason)) = terminator.unwind() {
-            push_mono_lang_item(self, reason.lang_item());
+            _ => {}
         }
+        let (instance, body) = (self.instance, self.body);
+        for_each_terminator_use(tcx, instance, body, terminator, source, |used, kind| {
+            let record_opaque_call = record_opaque_calls && !(drops_dyn && kind == UseKind::Drop);
+            self.push_use(used, kind, source, record_opaque_call)
+        });
 
         self.visiting_call_terminator = matches!(terminator.kind, mir::TerminatorKind::Call { .. });
         self.super_terminator(terminator, location);
@@ -851,6 +917,48 @@ fn visit_fn_use<'tcx>(
     is_direct_call: bool,
     source: Span,
     output: &mut MonoItems<'tcx>,
+) {
+    for_each_fn_use(tcx, ty, is_direct_call, source, &mut |instance| {
+        push_instance_use(tcx, instance, source, output)
+    });
+}
+
+fn visit_instance_use<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    instance: ty::Instance<'tcx>,
+    is_direct_call: bool,
+    source: Span,
+    output: &mut MonoItems<'tcx>,
+) {
+    debug!("visit_item_use({:?}, is_direct_call={:?})", instance, is_direct_call);
+    for_each_instance_use(tcx, instance, is_direct_call, &mut |instance| {
+        push_instance_use(tcx, instance, source, output)
+    });
+}
+
+/// Adds a mono item for an instance found by one of the `for_each_*_use` functions, unless it is
+/// codegened by another crate or has no code of its own, like virtual calls and intrinsics.
+fn push_instance_use<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    instance: Instance<'tcx>,
+    source: Span,
+    output: &mut MonoItems<'tcx>,
+) {
+    if !matches!(instance.def, ty::InstanceKind::Virtual(..) | ty::InstanceKind::Intrinsic(_))
+        && tcx.should_codegen_locally(instance)
+    {
+        output.push(create_fn_mono_item(tcx, instance, source));
+    }
+}
+
+/// Calls `f` with the instance that a call of, or a function pointer to, a value of type `ty`
+/// uses, along with the instances that one needs, see [`for_each_instance_use`].
+fn for_each_fn_use<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    ty: Ty<'tcx>,
+    is_direct_call: bool,
+    source: Span,
+    f: &mut impl FnMut(Instance<'tcx>),
 ) {
     if let ty::FnDef(def_id, args) = *ty.kind() {
         let instance = if is_direct_call {
@@ -861,21 +969,19 @@ fn visit_fn_use<'tcx>(
                 _ => bug!("failed to resolve instance for {ty}"),
             }
         };
-        visit_instance_use(tcx, instance, is_direct_call, source, output);
+        for_each_instance_use(tcx, instance, is_direct_call, f);
     }
 }
 
-fn visit_instance_use<'tcx>(
+/// Calls `f` with `instance`, unless it is empty drop glue that is called directly, and with the
+/// functions that codegen may lower a call of an intrinsic to. This does not check whether any of
+/// them are codegened in the current crate.
+fn for_each_instance_use<'tcx>(
     tcx: TyCtxt<'tcx>,
     instance: ty::Instance<'tcx>,
     is_direct_call: bool,
-    source: Span,
-    output: &mut MonoItems<'tcx>,
+    f: &mut impl FnMut(Instance<'tcx>),
 ) {
-    debug!("visit_item_use({:?}, is_direct_call={:?})", instance, is_direct_call);
-    if !tcx.should_codegen_locally(instance) {
-        return;
-    }
     if let ty::InstanceKind::Intrinsic(def_id) = instance.def {
         let name = tcx.item_name(def_id);
         if let Some(_requirement) = ValidityRequirement::from_intrinsic(name) {
@@ -884,16 +990,10 @@ fn visit_instance_use<'tcx>(
             // of those intrinsics, we need to include a mono item for panic_nounwind, else we may try to
             // codegen a call to that function without generating code for the function itself.
             let def_id = tcx.require_lang_item(LangItem::PanicNounwind, None);
-            let panic_instance = Instance::mono(tcx, def_id);
-            if tcx.should_codegen_locally(panic_instance) {
-                output.push(create_fn_mono_item(tcx, panic_instance, source));
-            }
+            f(Instance::mono(tcx, def_id));
         } else if tcx.has_attr(def_id, sym::rustc_intrinsic) {
             // Codegen the fallback body of intrinsics with fallback bodies
-            let instance = ty::Instance::new(def_id, instance.args);
-            if tcx.should_codegen_locally(instance) {
-                output.push(create_fn_mono_item(tcx, instance, source));
-            }
+            f(ty::Instance::new(def_id, instance.args));
         }
     }
 
@@ -902,6 +1002,7 @@ fn visit_instance_use<'tcx>(
             if !is_direct_call {
                 bug!("{:?} being reified", instance);
             }
+            f(instance);
         }
         ty::InstanceKind::ThreadLocalShim(..) => {
             bug!("{:?} being reified", instance);
@@ -909,7 +1010,7 @@ fn visit_instance_use<'tcx>(
         ty::InstanceKind::DropGlue(_, None) | ty::InstanceKind::AsyncDropGlueCtorShim(_, None) => {
             // Don't need to emit noop drop glue if we are calling directly.
             if !is_direct_call {
-                output.push(create_fn_mono_item(tcx, instance, source));
+                f(instance);
             }
         }
         ty::InstanceKind::DropGlue(_, Some(_))
@@ -923,8 +1024,147 @@ fn visit_instance_use<'tcx>(
         | ty::InstanceKind::FnPtrShim(..)
         | ty::InstanceKind::CloneShim(..)
         | ty::InstanceKind::FnPtrAddrShim(..) => {
-            output.push(create_fn_mono_item(tcx, instance, source));
+            f(instance);
+        }
+    }
+}
+
+/// Calls `f` with every function that `terminator` in the body of `instance` uses, along with
+/// how it is used. This is the resolution shared by the mono item collector and the
+/// `instance_callees` query, so it keeps the functions that are codegened by other crates.
+pub(crate) fn for_each_terminator_use<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    instance: Instance<'tcx>,
+    body: &mir::Body<'tcx>,
+    terminator: &mir::Terminator<'tcx>,
+    source: Span,
+    mut f: impl FnMut(Instance<'tcx>, UseKind),
+) {
+    let monomorphize = |ty: Ty<'tcx>| {
+        instance.instantiate_mir_and_normalize_erasing_regions(
+            tcx,
+            ty::ParamEnv::reveal_all(),
+            ty::EarlyBinder::bind(ty),
+        )
+    };
+    let lang_item = |lang_item| Instance::mono(tcx, tcx.require_lang_item(lang_item, Some(source)));
+
+    match terminator.kind {
+        mir::TerminatorKind::Call { ref func, .. }
+        | mir::TerminatorKind::TailCall { ref func, .. } => {
+            let callee_ty = monomorphize(func.ty(body, tcx));
+            for_each_fn_use(tcx, callee_ty, true, source, &mut |used| f(used, UseKind::Call));
+        }
+        mir::TerminatorKind::Drop { ref place, .. } => {
+            let ty = monomorphize(place.ty(body, tcx).ty);
+            let drop = Instance::resolve_drop_in_place(tcx, ty);
+            for_each_instance_use(tcx, drop, true, &mut |used| f(used, UseKind::Drop));
+        }
+        mir::TerminatorKind::InlineAsm { ref operands, .. } => {
+            for op in operands {
+                if let mir::InlineAsmOperand::SymFn { ref value } = *op {
+                    let fn_ty = monomorphize(value.const_.ty());
+                    for_each_fn_use(tcx, fn_ty, false, source, &mut |used| f(used, UseKind::Reify));
+                }
+            }
+        }
+        mir::TerminatorKind::Assert { ref msg, .. } => match &**msg {
+            mir::AssertKind::BoundsCheck { .. } => {
+                f(lang_item(LangItem::PanicBoundsCheck), UseKind::Call);
+            }
+            mir::AssertKind::MisalignedPointerDereference { .. } => {
+                f(lang_item(LangItem::PanicMisalignedPointerDereference), UseKind::Call);
+            }
+            _ => {
+                f(lang_item(msg.panic_function()), UseKind::Call);
+            }
+        },
+        mir::TerminatorKind::UnwindTerminate(reason) => {
+            f(lang_item(reason.lang_item()), UseKind::Call);
+        }
+        mir::TerminatorKind::Goto { .. }
+        | mir::TerminatorKind::SwitchInt { .. }
+        | mir::TerminatorKind::UnwindResume
+        | mir::TerminatorKind::Return
+        | mir::TerminatorKind::Unreachable => {}
+        mir::TerminatorKind::CoroutineDrop
+        | mir::TerminatorKind::Yield { .. }
+        | mir::TerminatorKind::FalseEdge { .. }
+        | mir::TerminatorKind::FalseUnwind { .. } => bug!(),
+    }
+
+    if let Some(mir::UnwindAction::Terminate(reason)) = terminator.unwind() {
+        f(lang_item(reason.lang_item()), UseKind::Call);
+    }
+}
+
+/// Calls `f` with every function that `rvalue` in the body of `instance` uses, along with how it
+/// is used. Like [`for_each_terminator_use`], this keeps the functions that are codegened by other
+/// crates.
+pub(crate) fn for_each_rvalue_use<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    instance: Instance<'tcx>,
+    body: &mir::Body<'tcx>,
+    rvalue: &mir::Rvalue<'tcx>,
+    source: Span,
+    mut f: impl FnMut(Instance<'tcx>, UseKind),
+) {
+    let monomorphize = |ty: Ty<'tcx>| {
+        instance.instantiate_mir_and_normalize_erasing_regions(
+            tcx,
+            ty::ParamEnv::reveal_all(),
+            ty::EarlyBinder::bind(ty),
+        )
+    };
+
+    match *rvalue {
+        // When doing an cast from a regular pointer to a fat pointer, we
+        // have to instantiate all methods of the trait being cast to, so we
+        // can build the appropriate vtable.
+        mir::Rvalue::Cast(
+            mir::CastKind::PointerCoercion(PointerCoercion::Unsize),
+            ref operand,
+            target_ty,
+        )
+        | mir::Rvalue::Cast(mir::CastKind::DynStar, ref operand, target_ty) => {
+            let target_ty = monomorphize(target_ty);
+            let source_ty = monomorphize(operand.ty(body, tcx));
+            let (source_ty, target_ty) =
+                find_vtable_types_for_unsizing(tcx.at(source), source_ty, target_ty);
+            // This could also be a different Unsize instruction, like
+            // from a fixed sized array to a slice. But we are only
+            // interested in things that produce a vtable.
+            if (target_ty.is_trait() && !source_ty.is_trait())
+                || (target_ty.is_dyn_star() && !source_ty.is_dyn_star())
+            {
+                for_each_vtable_use(tcx, target_ty, source_ty, &mut |used| {
+                    f(used, UseKind::VTable)
+                });
+            }
+        }
+        mir::Rvalue::Cast(
+            mir::CastKind::PointerCoercion(PointerCoercion::ReifyFnPointer),
+            ref operand,
+            _,
+        ) => {
+            let fn_ty = monomorphize(operand.ty(body, tcx));
+            for_each_fn_use(tcx, fn_ty, false, source, &mut |used| f(used, UseKind::Reify));
         }
+        mir::Rvalue::Cast(
+            mir::CastKind::PointerCoercion(PointerCoercion::ClosureFnPointer(_)),
+            ref operand,
+            _,
+        ) => {
+            let source_ty = monomorphize(operand.ty(body, tcx));
+            if let ty::Closure(def_id, args) = *source_ty.kind() {
+                let instance =
+                    Instance::resolve_closure(tcx, def_id, args, ty::ClosureKind::FnOnce);
+                f(instance, UseKind::Reify);
+            } else {
+                bug!()
+            }
+        }
+        _ => { /* not interesting */ }
     }
 }
 
@@ -1102,6 +1342,19 @@ fn create_mono_items_for_vtable_methods<'tcx>(
     impl_ty: Ty<'tcx>,
     source: Span,
     output: &mut MonoItems<'tcx>,
+) {
+    for_each_vtable_use(tcx, trait_ty, impl_ty, &mut |instance| {
+        push_instance_use(tcx, instance, source, output)
+    });
+}
+
+/// Calls `f` with each method that is referenced by the vtable for the given trait/impl pair, and
+/// with its destructor.
+fn for_each_vtable_use<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    trait_ty: Ty<'tcx>,
+    impl_ty: Ty<'tcx>,
+    f: &mut impl FnMut(Instance<'tcx>),
 ) {
     assert!(!trait_ty.has_escaping_bound_vars() && !impl_ty.has_escaping_bound_vars());
 
@@ -1115,27 +1368,23 @@ fn create_mono_items_for_vtable_methods<'tcx>(
         // Walk all methods of the trait, including those of its supertraits
         let entries = tcx.vtable_entries(poly_trait_ref);
         debug!(?entries);
-        let methods = entries
-            .iter()
-            .filter_map(|entry| match entry {
+        for entry in entries {
+            match *entry {
                 VtblEntry::MetadataDropInPlace
                 | VtblEntry::MetadataSize
                 | VtblEntry::MetadataAlign
-                | VtblEntry::Vacant => None,
+                | VtblEntry::Vacant => {}
                 VtblEntry::TraitVPtr(_) => {
                     // all super trait items already covered, so skip them.
-                    None
-                }
-                VtblEntry::Method(instance) => {
-                    Some(*instance).filter(|instance| tcx.should_codegen_locally(*instance))
                 }
-            })
-            .map(|item| create_fn_mono_item(tcx, item, source));
-        output.extend(methods);
+                VtblEntry::Method(instance) => f(instance),
+            }
+        }
     }
 
     // Also add the destructor.
-    visit_drop_use(tcx, impl_ty, false, source, output);
+    let drop = Instance::resolve_drop_in_place(tcx, impl_ty);
+    for_each_instance_use(tcx, drop, false, f);
 }
 
 /// Scans the CTFE alloc in order to find function pointers and statics that must be monomorphized.
@@ -1191,11 +1440,13 @@ fn assoc_fn_of_type<'tcx>(tcx: TyCtxt<'tcx>, def_id: DefId, fn_ident: Ident) ->
 /// Scans the MIR in order to find function calls, closures, and drop-glue.
 ///
 /// Anything that's found is added to `output`. Furthermore the "mentioned items" of the MIR are returned.
-#[instrument(skip(tcx, used_items, mentioned_items), level = "debug")]
+#[instrument(skip(tcx, used_items, use_kinds, opaque_calls, mentioned_items), level = "debug")]
 fn collect_items_of_instance<'tcx>(
     tcx: TyCtxt<'tcx>,
     instance: Instance<'tcx>,
     used_items: &mut MonoItems<'tcx>,
+    use_kinds: &mut Vec<UseKind>,
+    opaque_calls: &mut Vec<Spanned<OpaqueCall<'tcx>>>,
     mentioned_items: &mut MonoItems<'tcx>,
     mode: CollectionMode,
 ) {
@@ -1215,6 +1466,8 @@ fn collect_items_of_instance<'tcx>(
         tcx,
         body,
         used_items,
+        use_kinds,
+        opaque_calls,
         used_mentioned_items: &mut used_mentioned_items,
         instance,
         visiting_call_terminator: false,
@@ -1225,6 +1478,7 @@ fn collect_items_of_instance<'tcx>(
         for (bb, data) in traversal::mono_reachable(body, tcx, instance) {
             collector.visit_basic_block_data(bb, data)
         }
+        collector.record_use_kind(UseKind::Reference);
     }
 
     // Always visit all `required_consts`, so that we evaluate them and abort compilation if any of
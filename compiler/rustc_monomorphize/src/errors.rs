@@ -1,7 +1,7 @@
 use std::path::PathBuf;
 
 use rustc_errors::{Diag, DiagCtxtHandle, Diagnostic, EmissionGuarantee, Level};
-use rustc_macros::{Diagnostic, LintDiagnostic};
+use rustc_macros::{Diagnostic, LintDiagnostic, Subdiagnostic};
 use rustc_span::{Span, Symbol};
 
 use crate::fluent_generated as fluent;
@@ -74,6 +74,12 @@ pub struct CouldntDumpMonoStats {
     pub error: String,
 }
 
+#[derive(Diagnostic)]
+#[diag(monomorphize_couldnt_emit_call_graph)]
+pub struct CouldntEmitCallGraph {
+    pub error: String,
+}
+
 #[derive(Diagnostic)]
 #[diag(monomorphize_encountered_error_while_instantiating)]
 pub struct EncounteredErrorWhileInstantiating {
@@ -92,3 +98,63 @@ pub struct EncounteredErrorWhileInstantiating {
 pub struct UnknownCguCollectionMode<'a> {
     pub mode: &'a str,
 }
+
+#[derive(Diagnostic)]
+#[diag(monomorphize_panic_free_can_panic)]
+pub struct PanicFreeCanPanic {
+    #[primary_span]
+    pub span: Span,
+    pub name: String,
+    pub panic: String,
+    #[subdiagnostic]
+    pub steps: Vec<PanicPathStep>,
+}
+
+#[derive(Diagnostic)]
+#[diag(monomorphize_panic_free_indirect_call)]
+pub struct PanicFreeIndirectCall {
+    #[primary_span]
+    pub span: Span,
+    pub name: String,
+    #[subdiagnostic]
+    pub steps: Vec<PanicPathStep>,
+    #[subdiagnostic]
+    pub call: PanicPathIndirectStep,
+}
+
+#[derive(Diagnostic)]
+#[diag(monomorphize_panic_free_external_call)]
+pub struct PanicFreeExternalCall {
+    #[primary_span]
+    pub span: Span,
+    pub name: String,
+    pub callee: String,
+    #[subdiagnostic]
+    pub steps: Vec<PanicPathStep>,
+}
+
+#[derive(Diagnostic)]
+#[diag(monomorphize_panic_free_uninstantiated)]
+#[note]
+pub struct PanicFreeUninstantiated {
+    #[primary_span]
+    pub span: Span,
+    pub name: String,
+}
+
+#[derive(Subdiagnostic)]
+#[note(monomorphize_panic_path_step)]
+pub struct PanicPathStep {
+    #[primary_span]
+    pub span: Span,
+    pub caller: String,
+    pub callee: String,
+}
+
+#[derive(Subdiagnostic)]
+#[note(monomorphize_panic_path_indirect_step)]
+pub struct PanicPathIndirectStep {
+    #[primary_span]
+    pub span: Span,
+    pub caller: String,
+}
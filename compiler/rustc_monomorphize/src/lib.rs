@@ -10,8 +10,11 @@
 use rustc_middle::{bug, traits};
 use rustc_span::ErrorGuaranteed;
 
+mod call_graph;
+mod callees;
 mod collector;
 mod errors;
+mod panic_free;
 mod partitioning;
 mod polymorphize;
 mod util;
@@ -41,6 +44,7 @@ fn custom_coerce_unsize_info<'tcx>(
 }
 
 pub fn provide(providers: &mut Providers) {
+    callees::provide(providers);
     partitioning::provide(providers);
     polymorphize::provide(providers);
 }
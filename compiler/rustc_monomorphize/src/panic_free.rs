@@ -0,0 +1,267 @@
+//! The check behind `#[panic_free]`.
+//!
+//! Starting at every function marked `#[panic_free]`, the calls and drops recorded in the
+//! [`UsageMap`] are followed through the optimized MIR of the mono items of this crate until they
+//! reach an entry point of the panic machinery. The functions of other crates that are not
+//! instantiated here and calls through function pointers and vtables cannot be followed, so
+//! unless the callee is a panic entry point, each of these calls is reported as unverifiable.
+//! Generic functions are only checked where they are instantiated.
+
+use std::collections::VecDeque;
+
+use rustc_data_structures::fx::{FxHashMap, FxHashSet, FxIndexMap, FxIndexSet};
+use rustc_hir::def::DefKind;
+use rustc_hir::LangItem;
+use rustc_middle::middle::codegen_fn_attrs::CodegenFnAttrFlags;
+use rustc_middle::mir::mono::MonoItem;
+use rustc_middle::mir::{self, AssertKind};
+use rustc_middle::ty::print::with_no_trimmed_paths;
+use rustc_middle::ty::{self, Instance, InstanceKind, Ty, TyCtxt};
+use rustc_span::Span;
+
+use crate::collector::{OpaqueCall, UsageMap, UseKind};
+use crate::errors::{
+    PanicFreeCanPanic, PanicFreeExternalCall, PanicFreeIndirectCall, PanicFreeUninstantiated,
+    PanicPathIndirectStep, PanicPathStep,
+};
+
+pub(crate) fn check_panic_free_fns<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    items: &[MonoItem<'tcx>],
+    usage_map: &UsageMap<'tcx>,
+) {
+    let mut roots: Vec<_> = items
+        .iter()
+        .filter_map(|&item| match item {
+            MonoItem::Fn(instance @ Instance { def: InstanceKind::Item(def_id), .. })
+                if tcx.codegen_fn_attrs(def_id).flags.contains(CodegenFnAttrFlags::PANIC_FREE) =>
+            {
+                Some(instance)
+            }
+            _ => None,
+        })
+        .collect();
+    // Report in a stable order rather than in the order of the mono items.
+    roots.sort_by_cached_key(|&instance| name(instance));
+
+    // Generic functions that are never instantiated here have no mono items to check.
+    let instantiated: FxHashSet<_> = roots.iter().map(|root| root.def_id()).collect();
+    for def_id in tcx.hir_crate_items(()).definitions() {
+        if matches!(tcx.def_kind(def_id), DefKind::Fn | DefKind::AssocFn)
+            && tcx.codegen_fn_attrs(def_id).flags.contains(CodegenFnAttrFlags::PANIC_FREE)
+            && tcx.generics_of(def_id).requires_monomorphization(tcx)
+            && !instantiated.contains(&def_id.to_def_id())
+        {
+            tcx.dcx().emit_err(PanicFreeUninstantiated {
+                span: tcx.def_span(def_id),
+                name: with_no_trimmed_paths!(tcx.def_path_str(def_id)),
+            });
+        }
+    }
+
+    for root in roots {
+        check_panic_free_fn(tcx, root, usage_map);
+    }
+}
+
+fn name(instance: Instance<'_>) -> String {
+    with_no_trimmed_paths!(instance.to_string())
+}
+
+/// Reports an error for every function reachable from `root` that calls a panic entry point or
+/// makes a call that cannot be followed, along with the shortest call path from `root` to that
+/// call.
+fn check_panic_free_fn<'tcx>(tcx: TyCtxt<'tcx>, root: Instance<'tcx>, usage_map: &UsageMap<'tcx>) {
+    // The function each reached function was first called from.
+    let mut reached_from = FxHashMap::default();
+    reached_from.insert(root, None);
+    let mut queue = VecDeque::from([root]);
+    // The calls of panic entry points, as `(caller, callee)`.
+    let mut panics = FxIndexSet::default();
+    // The calls that cannot be followed, as `(caller, call)`, along with the first call site.
+    let mut opaque_calls = FxIndexMap::default();
+
+    while let Some(caller) = queue.pop_front() {
+        let item = MonoItem::Fn(caller);
+        for &(used_item, kind) in usage_map.get_used_items(item) {
+            let (MonoItem::Fn(callee), UseKind::Call | UseKind::Drop) = (used_item, kind) else {
+                continue;
+            };
+            if is_panic_entry_point(tcx, callee) {
+                panics.insert((caller, callee));
+            } else if !reached_from.contains_key(&callee) {
+                reached_from.insert(callee, Some(caller));
+                queue.push_back(callee);
+            }
+        }
+        for call in usage_map.get_opaque_calls(item) {
+            match call.node {
+                OpaqueCall::External(callee) if is_panic_entry_point(tcx, callee) => {
+                    panics.insert((caller, callee));
+                }
+                OpaqueCall::External(callee) if is_unwind_terminate(tcx, callee) => {}
+                _ => {
+                    opaque_calls.entry((caller, call.node)).or_insert(call.span);
+                }
+            }
+        }
+    }
+
+    let span = tcx.def_span(root.def_id());
+    for (caller, panic) in panics {
+        let mut steps = path_to(tcx, &reached_from, caller);
+        steps.push(PanicPathStep {
+            span: call_site(tcx, caller, panic),
+            caller: name(caller),
+            callee: name(panic),
+        });
+        tcx.dcx().emit_err(PanicFreeCanPanic { span, name: name(root), panic: name(panic), steps });
+    }
+    for ((caller, call), call_span) in opaque_calls {
+        let steps = path_to(tcx, &reached_from, caller);
+        match call {
+            OpaqueCall::Indirect => tcx.dcx().emit_err(PanicFreeIndirectCall {
+                span,
+                name: name(root),
+                steps,
+                call: PanicPathIndirectStep { span: call_span, caller: name(caller) },
+            }),
+            OpaqueCall::External(callee) => {
+                let mut steps = steps;
+                steps.push(PanicPathStep {
+                    span: call_span,
+                    caller: name(caller),
+                    callee: name(callee),
+                });
+                tcx.dcx().emit_err(PanicFreeExternalCall {
+                    span,
+                    name: name(root),
+                    callee: name(callee),
+                    steps,
+                })
+            }
+        };
+    }
+}
+
+/// Returns the steps of the call path from the root to `caller`, which is empty if `caller` is
+/// the root itself.
+fn path_to<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    reached_from: &FxHashMap<Instance<'tcx>, Option<Instance<'tcx>>>,
+    caller: Instance<'tcx>,
+) -> Vec<PanicPathStep> {
+    let mut path = vec![caller];
+    while let Some(&Some(caller)) = reached_from.get(path.last().unwrap()) {
+        path.push(caller);
+    }
+    path.reverse();
+
+    path.array_windows()
+        .map(|&[caller, callee]| PanicPathStep {
+            span: call_site(tcx, caller, callee),
+            caller: name(caller),
+            callee: name(callee),
+        })
+        .collect()
+}
+
+/// Whether calling `instance` starts a panic. Besides the panic lang items, this includes the
+/// `#[track_caller]` functions that never return, which is how the standard library reports
+/// failures like unwrapping `None` without inlining the formatting of the message.
+fn is_panic_entry_point<'tcx>(tcx: TyCtxt<'tcx>, instance: Instance<'tcx>) -> bool {
+    let InstanceKind::Item(def_id) = instance.def else { return false };
+    if let Some(lang_item) = tcx.lang_items().from_def_id(def_id) {
+        // `panic_cannot_unwind` and `panic_in_cleanup` are left out, as they only run when a panic
+        // is already unwinding.
+        return matches!(
+            lang_item,
+            LangItem::Panic
+                | LangItem::PanicNounwind
+                | LangItem::PanicFmt
+                | LangItem::ConstPanicFmt
+                | LangItem::PanicBoundsCheck
+                | LangItem::PanicMisalignedPointerDereference
+                | LangItem::PanicImpl
+                | LangItem::PanicAddOverflow
+                | LangItem::PanicSubOverflow
+                | LangItem::PanicMulOverflow
+                | LangItem::PanicDivOverflow
+                | LangItem::PanicRemOverflow
+                | LangItem::PanicNegOverflow
+                | LangItem::PanicShrOverflow
+                | LangItem::PanicShlOverflow
+                | LangItem::PanicDivZero
+                | LangItem::PanicRemZero
+                | LangItem::PanicCoroutineResumed
+                | LangItem::PanicAsyncFnResumed
+                | LangItem::PanicAsyncGenFnResumed
+                | LangItem::PanicGenFnNone
+                | LangItem::PanicCoroutineResumedPanic
+                | LangItem::PanicAsyncFnResumedPanic
+                | LangItem::PanicAsyncGenFnResumedPanic
+                | LangItem::PanicGenFnNonePanic
+                | LangItem::BeginPanic
+        );
+    }
+    matches!(tcx.def_kind(def_id), DefKind::Fn | DefKind::AssocFn)
+        && tcx.codegen_fn_attrs(def_id).flags.contains(CodegenFnAttrFlags::TRACK_CALLER)
+        && tcx.fn_sig(def_id).skip_binder().output().skip_binder().is_never()
+}
+
+/// Whether `instance` is `panic_cannot_unwind` or `panic_in_cleanup`, which abort when a panic is
+/// already unwinding and so don't start one.
+fn is_unwind_terminate<'tcx>(tcx: TyCtxt<'tcx>, instance: Instance<'tcx>) -> bool {
+    let InstanceKind::Item(def_id) = instance.def else { return false };
+    matches!(
+        tcx.lang_items().from_def_id(def_id),
+        Some(LangItem::PanicCannotUnwind | LangItem::PanicInCleanup)
+    )
+}
+
+/// Returns the span of the terminator through which `caller` calls or drops into `callee`, or the
+/// span of `caller` itself if there is none.
+fn call_site<'tcx>(tcx: TyCtxt<'tcx>, caller: Instance<'tcx>, callee: Instance<'tcx>) -> Span {
+    let body = tcx.instance_mir(caller.def);
+    let param_env = ty::ParamEnv::reveal_all();
+    let monomorphize = |ty: Ty<'tcx>| {
+        caller.instantiate_mir_and_normalize_erasing_regions(
+            tcx,
+            param_env,
+            ty::EarlyBinder::bind(ty),
+        )
+    };
+
+    for block in body.basic_blocks.iter() {
+        let terminator = block.terminator();
+        let span = terminator.source_info.span;
+        let target = match &terminator.kind {
+            mir::TerminatorKind::Call { func, .. } | mir::TerminatorKind::TailCall { func, .. } => {
+                match *monomorphize(func.ty(body, tcx)).kind() {
+                    ty::FnDef(def_id, args) => {
+                        Instance::expect_resolve(tcx, param_env, def_id, args, span)
+                    }
+                    _ => continue,
+                }
+            }
+            mir::TerminatorKind::Drop { place, .. } => {
+                Instance::resolve_drop_in_place(tcx, monomorphize(place.ty(body, tcx).ty))
+            }
+            mir::TerminatorKind::Assert { msg, .. } => {
+                let lang_item = match **msg {
+                    AssertKind::BoundsCheck { .. } => LangItem::PanicBoundsCheck,
+                    AssertKind::MisalignedPointerDereference { .. } => {
+                        LangItem::PanicMisalignedPointerDereference
+                    }
+                    _ => msg.panic_function(),
+                };
+                Instance::mono(tcx, tcx.require_lang_item(lang_item, Some(span)))
+            }
+            _ => continue,
+        };
+        if target == callee {
+            return span;
+        }
+    }
+    tcx.def_span(caller.def_id())
+}
@@ -116,13 +116,16 @@
 use rustc_middle::ty::visit::TypeVisitableExt;
 use rustc_middle::ty::{self, InstanceKind, TyCtxt};
 use rustc_middle::util::Providers;
-use rustc_session::config::{DumpMonoStatsFormat, SwitchWithOptPath};
+use rustc_session::config::{DumpMonoStatsFormat, OutputType, SwitchWithOptPath};
 use rustc_session::CodegenUnits;
 use rustc_span::symbol::Symbol;
 use tracing::debug;
 
 use crate::collector::{self, MonoItemCollectionStrategy, UsageMap};
-use crate::errors::{CouldntDumpMonoStats, SymbolAlreadyDefined, UnknownCguCollectionMode};
+use crate::errors::{
+    CouldntDumpMonoStats, CouldntEmitCallGraph, SymbolAlreadyDefined, UnknownCguCollectionMode,
+};
+use crate::{call_graph, panic_free};
 
 struct PartitioningCx<'a, 'tcx> {
     tcx: TyCtxt<'tcx>,
@@ -1123,6 +1126,10 @@ fn collect_and_partition_mono_items(tcx: TyCtxt<'_>, (): ()) -> (&DefIdSet, &[Co
 
     let (items, usage_map) = collector::collect_crate_mono_items(tcx, collection_strategy);
 
+    if tcx.features().panic_free {
+        panic_free::check_panic_free_fns(tcx, &items, &usage_map);
+    }
+
     // If there was an error during collection (e.g. from one of the constants we evaluated),
     // then we stop here. This way codegen does not have to worry about failing constants.
     // (codegen relies on this and ICEs will happen if this is violated.)
@@ -1168,6 +1175,22 @@ fn collect_and_partition_mono_items(tcx: TyCtxt<'_>, (): ()) -> (&DefIdSet, &[Co
         }
     }
 
+    if tcx.sess.opts.output_types.contains_key(&OutputType::CallGraph) {
+        if let Err(err) = call_graph::emit_call_graph(
+            tcx,
+            &items,
+            &usage_map,
+            codegen_units,
+            tcx.crate_name(LOCAL_CRATE),
+        ) {
+            tcx.dcx().emit_fatal(CouldntEmitCallGraph { error: err.to_string() });
+        }
+    }
+
+    if tcx.sess.opts.unstable_opts.print_stack_depth {
+        call_graph::record_stack_graph(tcx, &items, &usage_map);
+    }
+
     if tcx.sess.opts.unstable_opts.print_mono_items.is_some() {
         let mut item_to_cgus: UnordMap<_, Vec<_>> = Default::default();
 
@@ -158,7 +158,9 @@ fn check_attributes(
                     self.check_rustc_std_internal_symbol(attr, span, target)
                 }
                 [sym::naked] => self.check_naked(hir_id, attr, span, target, attrs),
-                [sym::rustc_never_returns_null_ptr] => {
+                [sym::rustc_never_returns_null_ptr]
+                | [sym::stack_limit]
+                | [sym::panic_free] => {
                     self.check_applied_to_fn_or_method(hir_id, attr, span, target)
                 }
                 [sym::rustc_legacy_const_generics] => {
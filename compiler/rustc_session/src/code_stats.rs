@@ -3,8 +3,10 @@
 use rustc_data_structures::fx::{FxHashMap, FxHashSet};
 use rustc_data_structures::sync::Lock;
 use rustc_span::def_id::DefId;
-use rustc_span::Symbol;
+use rustc_span::source_map::SourceMap;
+use rustc_span::{Span, Symbol};
 use rustc_target::abi::{Align, Size};
+use serde_json::{json, Value};
 
 #[derive(Clone, PartialEq, Eq, Hash, Debug)]
 pub struct VariantInfo {
@@ -60,6 +62,35 @@ pub enum DataTypeKind {
     Coroutine,
 }
 
+/// The largest niche of a type, i.e. the scalar with invalid values that an enclosing enum
+/// can use to store its tag.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct NicheInfo {
+    pub offset: u64,
+    pub size: u64,
+    /// Start of the inclusive range of valid values. The range wraps around if `start > end`.
+    pub valid_range_start: u128,
+    pub valid_range_end: u128,
+    /// Number of invalid values still available for niche optimizations.
+    pub available: u128,
+}
+
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub enum TagEncodingInfo {
+    /// The tag stores the discriminant of the variant.
+    Direct,
+    /// The tag lives in a niche of `untagged_variant`, the other variants are encoded as
+    /// `niche_start` onwards, in the order of `niche_variants`.
+    Niche { untagged_variant: Symbol, niche_variants: Vec<Symbol>, niche_start: u128 },
+}
+
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct TagInfo {
+    pub offset: u64,
+    pub size: u64,
+    pub encoding: TagEncodingInfo,
+}
+
 #[derive(PartialEq, Eq, Hash, Debug)]
 pub struct TypeSizeInfo {
     pub kind: DataTypeKind,
@@ -69,6 +100,10 @@ pub struct TypeSizeInfo {
     pub packed: bool,
     pub opt_discr_size: Option<u64>,
     pub variants: Vec<VariantInfo>,
+    pub tag: Option<TagInfo>,
+    pub niche: Option<NicheInfo>,
+    /// Where the type is defined, if it has a definition.
+    pub span: Option<Span>,
 }
 
 pub struct VTableSizeInfo {
@@ -90,10 +125,36 @@ pub struct VTableSizeInfo {
     pub upcasting_cost_percent: f64,
 }
 
+/// A function in the call graph walked by `-Zprint-stack-depth`.
+#[derive(Debug)]
+pub struct StackGraphNode {
+    pub name: String,
+    pub symbol_name: String,
+    pub span: Span,
+    /// Indices of the nodes called directly by this function, including drop glue.
+    pub callees: Vec<usize>,
+    /// Calls whose callee is not part of the graph.
+    pub opaque_calls: Vec<OpaqueCall>,
+    /// Whether the function can be entered from outside of the graph, like `main` or an
+    /// exported interrupt handler.
+    pub is_entry: bool,
+    /// The budget given with `#[stack_limit = "N"]`, in bytes.
+    pub stack_limit: Option<u64>,
+}
+
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum OpaqueCall {
+    /// A call through a function pointer or a vtable.
+    Indirect,
+    /// A call to a function that is not codegened in this crate, with its name.
+    External(String),
+}
+
 #[derive(Default)]
 pub struct CodeStats {
     type_sizes: Lock<FxHashSet<TypeSizeInfo>>,
     vtable_sizes: Lock<FxHashMap<DefId, VTableSizeInfo>>,
+    stack_graph: Lock<Vec<StackGraphNode>>,
 }
 
 impl CodeStats {
@@ -106,6 +167,9 @@ pub fn record_type_size<S: ToString>(
         packed: bool,
         opt_discr_size: Option<Size>,
         mut variants: Vec<VariantInfo>,
+        tag: Option<TagInfo>,
+        niche: Option<NicheInfo>,
+        span: Option<Span>,
     ) {
         // Sort variants so the largest ones are shown first. A stable sort is
         // used here so that source code order is preserved for all variants
@@ -123,6 +187,9 @@ pub fn record_type_size<S: ToString>(
             packed,
             opt_discr_size: opt_discr_size.map(|s| s.bytes()),
             variants,
+            tag,
+            niche,
+            span,
         };
         self.type_sizes.borrow_mut().insert(info);
     }
@@ -135,6 +202,16 @@ pub fn record_vtable_size(&self, trait_did: DefId, trait_name: &str, info: VTabl
         );
     }
 
+    pub fn record_stack_graph(&self, nodes: Vec<StackGraphNode>) {
+        *self.stack_graph.lock() = nodes;
+    }
+
+    /// Takes the call graph recorded by the mono item collector, for the stack depth analysis
+    /// that runs once codegen has determined the frame sizes.
+    pub fn take_stack_graph(&self) -> Vec<StackGraphNode> {
+        std::mem::take(&mut *self.stack_graph.lock())
+    }
+
     pub fn print_type_sizes(&self) {
         let type_sizes = self.type_sizes.borrow();
         // We will soon sort, so the initial order does not matter.
@@ -239,6 +316,45 @@ pub fn print_type_sizes(&self) {
         }
     }
 
+    /// Prints the recorded type and vtable sizes as a single JSON document, so that tools can
+    /// compare layouts between compilations. Values that may not fit in a `u64` (niche ranges)
+    /// are written as decimal strings.
+    pub fn print_type_sizes_json(&self, source_map: &SourceMap, crate_name: Symbol) {
+        let type_sizes = self.type_sizes.borrow();
+        // We will soon sort, so the initial order does not matter.
+        #[allow(rustc::potential_query_instability)]
+        let mut sorted: Vec<_> = type_sizes.iter().collect();
+        // Same order as the text output.
+        sorted.sort_by_key(|info| (cmp::Reverse(info.overall_size), &info.type_description));
+        let types: Vec<_> =
+            sorted.into_iter().map(|info| type_size_json(info, source_map)).collect();
+
+        let vtable_sizes = self.vtable_sizes.lock();
+        // We will soon sort, so the initial order does not matter.
+        #[allow(rustc::potential_query_instability)]
+        let mut vtables: Vec<_> = vtable_sizes.values().collect();
+        vtables.sort_by(|a, b| a.trait_name.cmp(&b.trait_name));
+        let vtables: Vec<_> = vtables
+            .into_iter()
+            .map(|info| {
+                json!({
+                    "trait_name": info.trait_name,
+                    "entries": info.entries,
+                    "entries_ignoring_upcasting": info.entries_ignoring_upcasting,
+                    "entries_for_upcasting": info.entries_for_upcasting,
+                    "upcasting_cost_percent": info.upcasting_cost_percent,
+                })
+            })
+            .collect();
+
+        let report = json!({
+            "crate_name": crate_name.as_str(),
+            "types": types,
+            "vtables": vtables,
+        });
+        println!("{}", serde_json::to_string_pretty(&report).unwrap());
+    }
+
     pub fn print_vtable_sizes(&self, crate_name: Symbol) {
         // We will soon sort, so the initial order does not matter.
         #[allow(rustc::potential_query_instability)]
@@ -268,3 +384,125 @@ pub fn print_vtable_sizes(&self, crate_name: Symbol) {
         }
     }
 }
+
+fn type_size_json(info: &TypeSizeInfo, source_map: &SourceMap) -> Value {
+    let TypeSizeInfo {
+        kind,
+        type_description,
+        align,
+        overall_size,
+        packed,
+        opt_discr_size,
+        variants,
+        tag,
+        niche,
+        span,
+    } = info;
+
+    let kind = match kind {
+        DataTypeKind::Struct => "struct",
+        DataTypeKind::Union => "union",
+        DataTypeKind::Enum => "enum",
+        DataTypeKind::Closure => "closure",
+        DataTypeKind::Coroutine => "coroutine",
+    };
+
+    let span = span.map(|span| {
+        let loc = source_map.lookup_char_pos(span.lo());
+        json!({
+            "file": source_map.filename_for_diagnostics(&loc.file.name).to_string(),
+            "line": loc.line,
+            "column": loc.col_display + 1,
+        })
+    });
+
+    let tag_json = tag.as_ref().map(|TagInfo { offset, size, encoding }| {
+        let encoding = match encoding {
+            TagEncodingInfo::Direct => json!({ "kind": "direct" }),
+            TagEncodingInfo::Niche { untagged_variant, niche_variants, niche_start } => json!({
+                "kind": "niche",
+                "untagged_variant": untagged_variant.as_str(),
+                "niche_variants": niche_variants.iter().map(|v| v.as_str()).collect::<Vec<_>>(),
+                "niche_start": niche_start.to_string(),
+            }),
+        };
+        json!({ "offset": offset, "size": size, "encoding": encoding })
+    });
+
+    let niche = niche.map(|niche| {
+        json!({
+            "offset": niche.offset,
+            "size": niche.size,
+            "valid_range_start": niche.valid_range_start.to_string(),
+            "valid_range_end": niche.valid_range_end.to_string(),
+            "available": niche.available.to_string(),
+        })
+    });
+
+    // The tag is not one of the fields, but the bytes it occupies are not padding either.
+    let tag_range = tag.as_ref().map(|tag| (tag.offset, tag.size));
+    let mut max_variant_size = opt_discr_size.unwrap_or(0);
+    let variants: Vec<_> = variants
+        .iter()
+        .map(|variant| {
+            max_variant_size = cmp::max(max_variant_size, variant.size);
+            variant_json(variant, tag_range)
+        })
+        .collect();
+
+    json!({
+        "type": type_description,
+        "kind": kind,
+        "size": overall_size,
+        "align": align,
+        "packed": packed,
+        "span": span,
+        "tag": tag_json,
+        "niche": niche,
+        "variants": variants,
+        "end_padding": overall_size.saturating_sub(max_variant_size),
+    })
+}
+
+fn variant_json(variant: &VariantInfo, tag_range: Option<(u64, u64)>) -> Value {
+    let VariantInfo { name, kind, size, align, fields } = variant;
+
+    // Same order as the text output: by increasing offset, zero-sized fields first.
+    let mut fields = fields.clone();
+    fields.sort_by_key(|f| (f.offset, f.size));
+
+    let mut occupied: Vec<_> = fields.iter().map(|f| (f.offset, f.size)).chain(tag_range).collect();
+    occupied.sort();
+    let mut padding = vec![];
+    let mut min_offset = 0;
+    for (offset, size) in occupied {
+        if offset > min_offset {
+            padding.push(json!({ "offset": min_offset, "size": offset - min_offset }));
+        }
+        // Union fields overlap, so only ever move forward.
+        min_offset = cmp::max(min_offset, offset + size);
+    }
+
+    let fields: Vec<_> = fields
+        .iter()
+        .map(|field| {
+            json!({
+                "kind": field.kind.to_string(),
+                "name": field.name.as_str(),
+                "offset": field.offset,
+                "size": field.size,
+                "align": field.align,
+                "type": field.type_name.map(|t| t.to_string()),
+            })
+        })
+        .collect();
+
+    json!({
+        "name": name.map(|n| n.to_string()),
+        "size": size,
+        "size_is_minimum": *kind == SizeKind::Min,
+        "align": align,
+        "fields": fields,
+        "padding": padding,
+    })
+}
@@ -495,6 +495,7 @@ pub enum OutputType {
     Object,
     Exe,
     DepInfo,
+    CallGraph,
 }
 
 impl StableOrd for OutputType {
@@ -515,7 +516,10 @@ fn to_stable_hash_key(&self, _: &HCX) -> Self::KeyType {
 impl OutputType {
     fn is_compatible_with_codegen_units_and_single_output_file(&self) -> bool {
         match *self {
-            OutputType::Exe | OutputType::DepInfo | OutputType::Metadata => true,
+            OutputType::Exe
+            | OutputType::DepInfo
+            | OutputType::Metadata
+            | OutputType::CallGraph => true,
             OutputType::Bitcode
             | OutputType::ThinLinkBitcode
             | OutputType::Assembly
@@ -536,6 +540,7 @@ pub fn shorthand(&self) -> &'static str {
             OutputType::Metadata => "metadata",
             OutputType::Exe => "link",
             OutputType::DepInfo => "dep-info",
+            OutputType::CallGraph => "call-graph",
         }
     }
 
@@ -550,13 +555,14 @@ fn from_shorthand(shorthand: &str) -> Option<Self> {
             "metadata" => OutputType::Metadata,
             "link" => OutputType::Exe,
             "dep-info" => OutputType::DepInfo,
+            "call-graph" => OutputType::CallGraph,
             _ => return None,
         })
     }
 
     fn shorthands_display() -> String {
         format!(
-            "`{}`, `{}`, `{}`, `{}`, `{}`, `{}`, `{}`, `{}`, `{}`",
+            "`{}`, `{}`, `{}`, `{}`, `{}`, `{}`, `{}`, `{}`, `{}`, `{}`",
             OutputType::Bitcode.shorthand(),
             OutputType::ThinLinkBitcode.shorthand(),
             OutputType::Assembly.shorthand(),
@@ -566,6 +572,7 @@ fn shorthands_display() -> String {
             OutputType::Metadata.shorthand(),
             OutputType::Exe.shorthand(),
             OutputType::DepInfo.shorthand(),
+            OutputType::CallGraph.shorthand(),
         )
     }
 
@@ -579,6 +586,7 @@ pub fn extension(&self) -> &'static str {
             OutputType::Object => "o",
             OutputType::Metadata => "rmeta",
             OutputType::DepInfo => "d",
+            OutputType::CallGraph => "callgraph",
             OutputType::Exe => "",
         }
     }
@@ -588,7 +596,8 @@ pub fn is_text_output(&self) -> bool {
             OutputType::Assembly
             | OutputType::LlvmAssembly
             | OutputType::Mir
-            | OutputType::DepInfo => true,
+            | OutputType::DepInfo
+            | OutputType::CallGraph => true,
             OutputType::Bitcode
             | OutputType::ThinLinkBitcode
             | OutputType::Object
@@ -611,6 +620,8 @@ pub enum ErrorOutputType {
         /// human output.
         json_rendered: HumanReadableErrorType,
     },
+    /// A single SARIF log for the whole run, consumed by code scanning tools.
+    Sarif,
 }
 
 impl Default for ErrorOutputType {
@@ -681,7 +692,8 @@ pub fn should_codegen(&self) -> bool {
             | OutputType::LlvmAssembly
             | OutputType::Mir
             | OutputType::Object
-            | OutputType::Exe => true,
+            | OutputType::Exe
+            | OutputType::CallGraph => true,
             OutputType::Metadata | OutputType::DepInfo => false,
         })
     }
@@ -696,7 +708,8 @@ pub fn should_link(&self) -> bool {
             | OutputType::Mir
             | OutputType::Metadata
             | OutputType::Object
-            | OutputType::DepInfo => false,
+            | OutputType::DepInfo
+            | OutputType::CallGraph => false,
             OutputType::Exe => true,
         })
     }
@@ -1476,7 +1489,7 @@ pub fn rustc_short_optgroups() -> Vec<RustcOptGroup> {
             "emit",
             "Comma separated list of types of output for \
              the compiler to emit",
-            "[asm|llvm-bc|llvm-ir|obj|metadata|link|dep-info|mir]",
+            "[asm|llvm-bc|llvm-ir|obj|metadata|link|dep-info|mir|call-graph]",
         ),
         opt::multi_s(
             "",
@@ -1718,14 +1731,15 @@ pub fn parse_error_format(
             Some("json") => ErrorOutputType::Json { pretty: false, json_rendered },
             Some("pretty-json") => ErrorOutputType::Json { pretty: true, json_rendered },
             Some("short") => ErrorOutputType::HumanReadable(HumanReadableErrorType::Short(color)),
+            Some("sarif") => ErrorOutputType::Sarif,
 
             Some(arg) => {
                 early_dcx.abort_if_error_and_set_error_format(ErrorOutputType::HumanReadable(
                     HumanReadableErrorType::Default(color),
                 ));
                 early_dcx.early_fatal(format!(
-                    "argument for `--error-format` must be `human`, `json` or \
-                     `short` (instead was `{arg}`)"
+                    "argument for `--error-format` must be `human`, `json`, \
+                     `short` or `sarif` (instead was `{arg}`)"
                 ))
             }
         }
@@ -1789,6 +1803,9 @@ fn check_error_format_stability(
         {
             early_dcx.early_fatal("`--error-format=human-annotate-rs` is unstable");
         }
+        if let ErrorOutputType::Sarif = error_format {
+            early_dcx.early_fatal("`--error-format=sarif` is unstable");
+        }
     }
 }
 
@@ -1808,10 +1825,12 @@ fn parse_output_types(
                         display = OutputType::shorthands_display(),
                     ))
                 });
-                if output_type == OutputType::ThinLinkBitcode && !unstable_opts.unstable_options {
+                if matches!(output_type, OutputType::ThinLinkBitcode | OutputType::CallGraph)
+                    && !unstable_opts.unstable_options
+                {
                     early_dcx.early_fatal(format!(
                         "{} requested but -Zunstable-options not specified",
-                        OutputType::ThinLinkBitcode.shorthand()
+                        output_type.shorthand()
                     ));
                 }
                 output_types.insert(output_type, path);
@@ -3254,6 +3273,24 @@ pub fn extension(self) -> &'static str {
     }
 }
 
+/// Which format to use for `-Z print-type-sizes`
+#[derive(Clone, Copy, PartialEq, Hash, Debug)]
+pub enum PrintTypeSizesFormat {
+    /// Print `print-type-size` lines meant to be read by humans
+    Text,
+    /// Emit a single structured JSON document
+    Json,
+}
+
+/// Which format to use for `--emit=call-graph`
+#[derive(Clone, Copy, PartialEq, Hash, Debug)]
+pub enum CallGraphFormat {
+    /// Emit structured JSON
+    Json,
+    /// Emit a Graphviz graph
+    Dot,
+}
+
 /// `-Z patchable-function-entry` representation - how many nops to put before and after function
 /// entry.
 #[derive(Clone, Copy, PartialEq, Hash, Debug, Default)]
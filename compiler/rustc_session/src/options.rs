@@ -395,7 +395,10 @@ mod desc {
     pub const parse_linker_flavor: &str = ::rustc_target::spec::LinkerFlavorCli::one_of();
     pub const parse_optimization_fuel: &str = "crate=integer";
     pub const parse_dump_mono_stats: &str = "`markdown` (default) or `json`";
+    pub const parse_call_graph_format: &str = "`json` (default) or `dot`";
     pub const parse_instrument_coverage: &str = parse_bool;
+    pub const parse_print_type_sizes: &str =
+        "either a boolean (`yes`, `no`, `on`, `off`, etc), `text`, or `json`";
     pub const parse_coverage_options: &str =
         "`block` | `branch` | `condition` | `mcdc` | `no-mir-spans`";
     pub const parse_instrument_xray: &str = "either a boolean (`yes`, `no`, `on`, `off`, etc), or a comma separated list of settings: `always` or `never` (mutually exclusive), `ignore-loops`, `instruction-threshold=N`, `skip-entry`, `skip-exit`";
@@ -968,6 +971,41 @@ pub(crate) fn parse_dump_mono_stats(slot: &mut DumpMonoStatsFormat, v: Option<&s
         }
     }
 
+    pub(crate) fn parse_call_graph_format(slot: &mut CallGraphFormat, v: Option<&str>) -> bool {
+        match v {
+            None => true,
+            Some("json") => {
+                *slot = CallGraphFormat::Json;
+                true
+            }
+            Some("dot") => {
+                *slot = CallGraphFormat::Dot;
+                true
+            }
+            Some(_) => false,
+        }
+    }
+
+    pub(crate) fn parse_print_type_sizes(
+        slot: &mut Option<PrintTypeSizesFormat>,
+        v: Option<&str>,
+    ) -> bool {
+        if v.is_some() {
+            let mut bool_arg = false;
+            if parse_bool(&mut bool_arg, v) {
+                *slot = bool_arg.then_some(PrintTypeSizesFormat::Text);
+                return true;
+            }
+        }
+
+        *slot = match v {
+            None | Some("text") => Some(PrintTypeSizesFormat::Text),
+            Some("json") => Some(PrintTypeSizesFormat::Json),
+            Some(_) => return false,
+        };
+        true
+    }
+
     pub(crate) fn parse_instrument_coverage(
         slot: &mut InstrumentCoverage,
         v: Option<&str>,
@@ -1644,6 +1682,8 @@ pub(crate) fn parse_wasm_c_abi(slot: &mut WasmCAbi, v: Option<&str>) -> bool {
         "emit noalias metadata for box (default: yes)"),
     branch_protection: Option<BranchProtection> = (None, parse_branch_protection, [TRACKED],
         "set options for branch target identification and pointer authentication on AArch64"),
+    call_graph_format: CallGraphFormat = (CallGraphFormat::Json, parse_call_graph_format, [UNTRACKED],
+        "the format to use for `--emit=call-graph` (default: `json`)"),
     cf_protection: CFProtection = (CFProtection::None, parse_cfprotection, [TRACKED],
         "instrument control-flow architecture protection"),
     check_cfg_all_expected: bool = (false, parse_bool, [UNTRACKED],
@@ -1920,8 +1960,12 @@ pub(crate) fn parse_wasm_c_abi(slot: &mut WasmCAbi, v: Option<&str>) -> bool {
         "print the result of the monomorphization collection pass. \
          Value `lazy` means to use normal collection; `eager` means to collect all items.
          Note that this overwrites the effect `-Clink-dead-code` has on collection!"),
-    print_type_sizes: bool = (false, parse_bool, [UNTRACKED],
-        "print layout information for each type encountered (default: no)"),
+    print_stack_depth: bool = (false, parse_bool, [TRACKED],
+        "print the worst-case stack depth of every entry point after codegen and check the \
+        `#[stack_limit]` budgets (default: no)"),
+    print_type_sizes: Option<PrintTypeSizesFormat> = (None, parse_print_type_sizes, [UNTRACKED],
+        "print layout information for each type encountered, either as text or \
+        as a JSON document with `json` (default: no)"),
     print_vtable_sizes: bool = (false, parse_bool, [UNTRACKED],
         "print size comparison between old and new vtable layouts (default: no)"),
     proc_macro_backtrace: bool = (false, parse_bool, [UNTRACKED],
@@ -19,6 +19,7 @@
 use rustc_errors::emitter::{stderr_destination, DynEmitter, HumanEmitter, HumanReadableErrorType};
 use rustc_errors::json::JsonEmitter;
 use rustc_errors::registry::Registry;
+use rustc_errors::sarif::SarifEmitter;
 use rustc_errors::{
     fallback_fluent_bundle, Diag, DiagCtxt, DiagCtxtHandle, DiagMessage, Diagnostic,
     ErrorGuaranteed, FatalAbort, FluentBundle, LazyFallbackBundle, TerminalUrl,
@@ -35,7 +36,9 @@
 };
 
 use crate::code_stats::CodeStats;
-pub use crate::code_stats::{DataTypeKind, FieldInfo, FieldKind, SizeKind, VariantInfo};
+pub use crate::code_stats::{
+    DataTypeKind, FieldInfo, FieldKind, NicheInfo, SizeKind, TagEncodingInfo, TagInfo, VariantInfo,
+};
 use crate::config::{
     self, CoverageLevel, CrateType, ErrorOutputType, FunctionReturn, Input, InstrumentCoverage,
     OptLevel, OutFileName, OutputType, RemapPathScopeComponents, SwitchWithOptPath,
@@ -312,7 +315,7 @@ pub fn create_feature_err<'a>(&'a self, err: impl Diagnostic<'a>, feature: Symbo
     /// Record the fact that we called `trimmed_def_paths`, and do some
     /// checking about whether its cost was justified.
     pub fn record_trimmed_def_paths(&self) {
-        if self.opts.unstable_opts.print_type_sizes
+        if self.opts.unstable_opts.print_type_sizes.is_some()
             || self.opts.unstable_opts.query_dep_graph
             || self.opts.unstable_opts.dump_mir.is_some()
             || self.opts.unstable_opts.unpretty.is_some()
@@ -997,6 +1000,17 @@ fn default_emitter(
             .track_diagnostics(track_diagnostics)
             .terminal_url(terminal_url),
         ),
+        config::ErrorOutputType::Sarif => Box::new(
+            SarifEmitter::new(
+                Box::new(io::BufWriter::new(io::stderr())),
+                source_map,
+                fallback_bundle,
+            )
+            .registry(Some(registry))
+            .fluent_bundle(bundle)
+            .tool_name(if sopts.actually_rustdoc { "rustdoc" } else { "rustc" })
+            .tool_version(option_env!("CFG_VERSION")),
+        ),
     }
 }
 
@@ -1439,6 +1453,15 @@ fn mk_emitter(output: ErrorOutputType) -> Box<DynEmitter> {
             pretty,
             json_rendered,
         )),
+        config::ErrorOutputType::Sarif => Box::new(
+            SarifEmitter::new(
+                Box::new(io::BufWriter::new(io::stderr())),
+                Lrc::new(SourceMap::new(FilePathMapping::empty())),
+                fallback_bundle,
+            )
+            // Leave writing an empty log to the emitter of the session.
+            .skip_empty_log(true),
+        ),
     };
     emitter
 }
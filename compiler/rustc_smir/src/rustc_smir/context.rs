@@ -9,6 +9,7 @@
 use std::iter;
 
 use rustc_abi::HasDataLayout;
+use rustc_data_structures::fx::FxIndexMap;
 use rustc_hir::LangItem;
 use rustc_middle::ty::layout::{
     FnAbiOf, FnAbiOfHelpers, HasParamEnv, HasTyCtxt, LayoutOf, LayoutOfHelpers,
@@ -22,7 +23,7 @@
 use stable_mir::abi::{FnAbi, Layout, LayoutShape};
 use stable_mir::compiler_interface::Context;
 use stable_mir::mir::alloc::GlobalAlloc;
-use stable_mir::mir::mono::{InstanceDef, StaticDef};
+use stable_mir::mir::mono::{Callee, CguPlacement, CodegenItem, InstanceDef, StaticDef};
 use stable_mir::mir::{BinOp, Body, Place, UnOp};
 use stable_mir::target::{MachineInfo, MachineSize};
 use stable_mir::ty::{
@@ -58,6 +59,26 @@ fn all_local_items(&self) -> stable_mir::CrateItems {
         tables.tcx.mir_keys(()).iter().map(|item| tables.crate_item(item.to_def_id())).collect()
     }
 
+    fn all_mono_items(&self) -> Vec<CodegenItem> {
+        let mut tables = self.0.borrow_mut();
+        let tcx = tables.tcx;
+        let (_, codegen_units) = tcx.collect_and_partition_mono_items(());
+        let mut placements: FxIndexMap<_, Vec<_>> = FxIndexMap::default();
+        for cgu in codegen_units {
+            for (item, data) in cgu.items_in_deterministic_order(tcx) {
+                let placement = CguPlacement {
+                    name: cgu.name().to_string(),
+                    linkage: data.linkage.stable(&mut *tables),
+                };
+                placements.entry(item).or_default().push(placement);
+            }
+        }
+        placements
+            .into_iter()
+            .map(|(item, cgus)| CodegenItem { item: item.stable(&mut *tables), cgus })
+            .collect()
+    }
+
     fn mir_body(&self, item: stable_mir::DefId) -> stable_mir::mir::Body {
         let mut tables = self.0.borrow_mut();
         let def_id = tables[item];
@@ -595,6 +616,28 @@ fn instance_mangled_name(&self, instance: InstanceDef) -> Symbol {
         tables.tcx.symbol_name(instance).name.to_string()
     }
 
+    fn instance_callees(&self, def: InstanceDef) -> Vec<Callee> {
+        let mut tables = self.0.borrow_mut();
+        let instance = tables.instances[def];
+        if !tables.instance_has_body(instance) {
+            return Vec::new();
+        }
+        let instance = match instance.def {
+            // The callees of an intrinsic are the ones of its fallback body.
+            ty::InstanceKind::Intrinsic(def_id) => Instance::new(def_id, instance.args),
+            _ => instance,
+        };
+        tables
+            .tcx
+            .instance_callees(instance)
+            .iter()
+            .map(|(callee, kind)| Callee {
+                instance: callee.stable(&mut *tables),
+                kind: kind.stable(&mut *tables),
+            })
+            .collect()
+    }
+
     fn is_empty_drop_shim(&self, def: InstanceDef) -> bool {
         let tables = self.0.borrow_mut();
         let instance = tables.instances[def];
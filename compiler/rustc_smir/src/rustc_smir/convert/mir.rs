@@ -1,7 +1,7 @@
 //! Conversion of internal Rust compiler `mir` items to stable ones.
 
 use rustc_middle::mir::interpret::alloc_range;
-use rustc_middle::mir::mono::MonoItem;
+use rustc_middle::mir::mono::{CalleeKind, Linkage, MonoItem};
 use rustc_middle::{bug, mir};
 use stable_mir::mir::alloc::GlobalAlloc;
 use stable_mir::mir::{ConstOperand, Statement, UserTypeProjection, VarDebugInfoFragment};
@@ -779,3 +779,38 @@ fn stable(&self, tables: &mut Tables<'_>) -> Self::T {
         }
     }
 }
+
+impl<'tcx> Stable<'tcx> for Linkage {
+    type T = stable_mir::mir::mono::Linkage;
+
+    fn stable(&self, _: &mut Tables<'_>) -> Self::T {
+        use stable_mir::mir::mono::Linkage as StableLinkage;
+        match self {
+            Linkage::External => StableLinkage::External,
+            Linkage::AvailableExternally => StableLinkage::AvailableExternally,
+            Linkage::LinkOnceAny => StableLinkage::LinkOnceAny,
+            Linkage::LinkOnceODR => StableLinkage::LinkOnceODR,
+            Linkage::WeakAny => StableLinkage::WeakAny,
+            Linkage::WeakODR => StableLinkage::WeakODR,
+            Linkage::Appending => StableLinkage::Appending,
+            Linkage::Internal => StableLinkage::Internal,
+            Linkage::Private => StableLinkage::Private,
+            Linkage::ExternalWeak => StableLinkage::ExternalWeak,
+            Linkage::Common => StableLinkage::Common,
+        }
+    }
+}
+
+impl<'tcx> Stable<'tcx> for CalleeKind {
+    type T = stable_mir::mir::mono::CalleeKind;
+
+    fn stable(&self, _: &mut Tables<'_>) -> Self::T {
+        use stable_mir::mir::mono::CalleeKind as StableCalleeKind;
+        match self {
+            CalleeKind::Call => StableCalleeKind::Call,
+            CalleeKind::Drop => StableCalleeKind::Drop,
+            CalleeKind::VTable => StableCalleeKind::VTable,
+            CalleeKind::Reify => StableCalleeKind::Reify,
+        }
+    }
+}
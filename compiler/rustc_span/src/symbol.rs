@@ -1376,6 +1376,7 @@
         panic_const_shr_overflow,
         panic_const_sub_overflow,
         panic_fmt,
+        panic_free,
         panic_handler,
         panic_impl,
         panic_implementation,
@@ -1815,6 +1816,7 @@
         sse,
         sse4a_target_feature,
         stable,
+        stack_limit,
         staged_api,
         start,
         state,
@@ -1881,6 +1883,7 @@
         test_case,
         test_removed_feature,
         test_runner,
+        test_timeout,
         test_unstable_lint,
         thread,
         thread_local,
@@ -16,7 +16,9 @@
     self, AdtDef, CoroutineArgsExt, EarlyBinder, FieldDef, GenericArgsRef, Ty, TyCtxt,
     TypeVisitableExt,
 };
-use rustc_session::{DataTypeKind, FieldInfo, FieldKind, SizeKind, VariantInfo};
+use rustc_session::{
+    DataTypeKind, FieldInfo, FieldKind, NicheInfo, SizeKind, TagEncodingInfo, TagInfo, VariantInfo,
+};
 use rustc_span::sym;
 use rustc_span::symbol::Symbol;
 use rustc_target::abi::*;
@@ -70,7 +72,7 @@ fn layout_of<'tcx>(
 
     // If we are running with `-Zprint-type-sizes`, maybe record layouts
     // for dumping later.
-    if cx.tcx.sess.opts.unstable_opts.print_type_sizes {
+    if cx.tcx.sess.opts.unstable_opts.print_type_sizes.is_some() {
         record_layout_for_printing(&cx, layout);
     }
 
@@ -1016,8 +1018,15 @@ fn record_layout_for_printing<'tcx>(cx: &LayoutCx<'tcx, TyCtxt<'tcx>>, layout: T
     }
 
     // (delay format until we actually need it)
-    let record = |kind, packed, opt_discr_size, variants| {
+    let record = |kind, packed, opt_discr_size, variants, tag, span| {
         let type_desc = with_no_trimmed_paths!(format!("{}", layout.ty));
+        let niche = layout.largest_niche.map(|niche| NicheInfo {
+            offset: niche.offset.bytes(),
+            size: niche.value.size(cx).bytes(),
+            valid_range_start: niche.valid_range.start,
+            valid_range_end: niche.valid_range.end,
+            available: niche.available(cx),
+        });
         cx.tcx.sess.code_stats.record_type_size(
             kind,
             type_desc,
@@ -1026,6 +1035,9 @@ fn record_layout_for_printing<'tcx>(cx: &LayoutCx<'tcx, TyCtxt<'tcx>>, layout: T
             packed,
             opt_discr_size,
             variants,
+            tag,
+            niche,
+            span,
         );
     };
 
@@ -1035,7 +1047,9 @@ fn record_layout_for_printing<'tcx>(cx: &LayoutCx<'tcx, TyCtxt<'tcx>>, layout: T
             let adt_kind = adt_def.adt_kind();
             let adt_packed = adt_def.repr().pack.is_some();
             let (variant_infos, opt_discr_size) = variant_info_for_adt(cx, layout, adt_def);
-            record(adt_kind.into(), adt_packed, opt_discr_size, variant_infos);
+            let tag = tag_info(cx, layout, |index| adt_def.variant(index).name);
+            let span = cx.tcx.def_span(adt_def.did());
+            record(adt_kind.into(), adt_packed, opt_discr_size, variant_infos, tag, Some(span));
         }
 
         ty::Coroutine(def_id, args) => {
@@ -1043,12 +1057,17 @@ fn record_layout_for_printing<'tcx>(cx: &LayoutCx<'tcx, TyCtxt<'tcx>>, layout: T
             // Coroutines always have a begin/poisoned/end state with additional suspend points
             let (variant_infos, opt_discr_size) =
                 variant_info_for_coroutine(cx, layout, def_id, args);
-            record(DataTypeKind::Coroutine, false, opt_discr_size, variant_infos);
+            let tag = tag_info(cx, layout, |index| {
+                Symbol::intern(&ty::CoroutineArgs::variant_name(index))
+            });
+            let span = cx.tcx.def_span(def_id);
+            record(DataTypeKind::Coroutine, false, opt_discr_size, variant_infos, tag, Some(span));
         }
 
-        ty::Closure(..) => {
+        ty::Closure(def_id, _) => {
             debug!("print-type-size t: `{:?}` record closure", layout.ty);
-            record(DataTypeKind::Closure, false, None, vec![]);
+            let span = cx.tcx.def_span(def_id);
+            record(DataTypeKind::Closure, false, None, vec![], None, Some(span));
         }
 
         _ => {
@@ -1057,6 +1076,32 @@ fn record_layout_for_printing<'tcx>(cx: &LayoutCx<'tcx, TyCtxt<'tcx>>, layout: T
     };
 }
 
+/// Describes where and how the tag of a multi-variant layout is stored.
+fn tag_info<'tcx>(
+    cx: &LayoutCx<'tcx, TyCtxt<'tcx>>,
+    layout: TyAndLayout<'tcx>,
+    variant_name: impl Fn(VariantIdx) -> Symbol,
+) -> Option<TagInfo> {
+    let Variants::Multiple { tag, ref tag_encoding, tag_field, .. } = layout.variants else {
+        return None;
+    };
+    let encoding = match *tag_encoding {
+        TagEncoding::Direct => TagEncodingInfo::Direct,
+        TagEncoding::Niche { untagged_variant, ref niche_variants, niche_start } => {
+            TagEncodingInfo::Niche {
+                untagged_variant: variant_name(untagged_variant),
+                niche_variants: niche_variants.clone().map(&variant_name).collect(),
+                niche_start,
+            }
+        }
+    };
+    Some(TagInfo {
+        offset: layout.fields.offset(tag_field).bytes(),
+        size: tag.size(cx).bytes(),
+        encoding,
+    })
+}
+
 fn variant_info_for_adt<'tcx>(
     cx: &LayoutCx<'tcx, TyCtxt<'tcx>>,
     layout: TyAndLayout<'tcx>,
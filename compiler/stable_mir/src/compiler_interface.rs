@@ -8,7 +8,7 @@
 use crate::abi::{FnAbi, Layout, LayoutShape};
 use crate::crate_def::Attribute;
 use crate::mir::alloc::{AllocId, GlobalAlloc};
-use crate::mir::mono::{Instance, InstanceDef, StaticDef};
+use crate::mir::mono::{Callee, CodegenItem, Instance, InstanceDef, StaticDef};
 use crate::mir::{BinOp, Body, Place, UnOp};
 use crate::target::MachineInfo;
 use crate::ty::{
@@ -28,6 +28,8 @@ pub trait Context {
     fn entry_fn(&self) -> Option<CrateItem>;
     /// Retrieve all items of the local crate that have a MIR associated with them.
     fn all_local_items(&self) -> CrateItems;
+    /// Retrieve all the mono items that will be codegened for the local crate.
+    fn all_mono_items(&self) -> Vec<CodegenItem>;
     /// Retrieve the body of a function.
     /// This function will panic if the body is not available.
     fn mir_body(&self, item: DefId) -> mir::Body;
@@ -175,6 +177,9 @@ pub trait Context {
     /// Get the instance mangled name.
     fn instance_mangled_name(&self, instance: InstanceDef) -> Symbol;
 
+    /// Get the functions an instance may call.
+    fn instance_callees(&self, def: InstanceDef) -> Vec<Callee>;
+
     /// Check if this is an empty DropGlue shim.
     fn is_empty_drop_shim(&self, def: InstanceDef) -> bool;
 
@@ -25,6 +25,7 @@
 use crate::compiler_interface::with;
 pub use crate::crate_def::{CrateDef, CrateDefType, DefId};
 pub use crate::error::*;
+use crate::mir::mono::CodegenItem;
 use crate::mir::{Body, Mutability};
 use crate::ty::{ForeignModuleDef, ImplDef, IndexedVal, Span, TraitDef, Ty};
 
@@ -180,6 +181,14 @@ pub fn all_local_items() -> CrateItems {
     with(|cx| cx.all_local_items())
 }
 
+/// Retrieve all the mono items the compiler is going to codegen for the local crate, along with
+/// the codegen units they are placed in.
+///
+/// This runs the collection and partitioning of the mono items, if that has not happened yet.
+pub fn all_mono_items() -> Vec<CodegenItem> {
+    with(|cx| cx.all_mono_items())
+}
+
 pub fn all_trait_decls() -> TraitDecls {
     with(|cx| cx.all_trait_decls())
 }
@@ -16,6 +16,40 @@ pub enum MonoItem {
     GlobalAsm(Opaque),
 }
 
+/// A mono item that the compiler is going to codegen, along with where it is placed.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize)]
+pub struct CodegenItem {
+    pub item: MonoItem,
+    /// The codegen units the item is placed in. An item that is instantiated in every codegen unit
+    /// that uses it, like an `#[inline]` function, can be placed in several of them.
+    pub cgus: Vec<CguPlacement>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize)]
+pub struct CguPlacement {
+    /// The name of the codegen unit.
+    pub name: Symbol,
+    pub linkage: Linkage,
+}
+
+/// The linkage of a mono item in a codegen unit.
+///
+/// See <https://llvm.org/docs/LangRef.html#linkage-types> for more details about these variants.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize)]
+pub enum Linkage {
+    External,
+    AvailableExternally,
+    LinkOnceAny,
+    LinkOnceODR,
+    WeakAny,
+    WeakODR,
+    Appending,
+    Internal,
+    Private,
+    ExternalWeak,
+    Common,
+}
+
 #[derive(Copy, Clone, PartialEq, Eq, Hash, Serialize)]
 pub struct Instance {
     /// The type of instance.
@@ -176,6 +210,20 @@ pub fn try_const_eval(&self, const_ty: Ty) -> Result<Allocation, Error> {
         with(|cx| cx.eval_instance(self.def, const_ty))
     }
 
+    /// Get the functions this instance may call, after resolution.
+    ///
+    /// Besides direct calls, this includes the drop glue run by drops, the methods and drop glue
+    /// put into vtables by unsizing casts, and the functions turned into function pointers or
+    /// named by `sym` operands of inline assembly.
+    /// Calls through function pointers cannot be resolved, and calls through trait objects are
+    /// returned as `Virtual` instances. Functions that are only referred to by constants are not
+    /// included.
+    ///
+    /// This returns an empty list for an instance without a body.
+    pub fn callees(&self) -> Vec<Callee> {
+        with(|cx| cx.instance_callees(self.def))
+    }
+
     /// Emit the body of this instance if it has one.
     pub fn emit_mir<W: io::Write>(&self, w: &mut W) -> io::Result<()> {
         if let Some(body) = self.body() { body.dump(w, &self.name()) } else { Ok(()) }
@@ -243,6 +291,27 @@ fn from(value: StaticDef) -> Self {
     }
 }
 
+/// A function that an instance may call, see [`Instance::callees`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize)]
+pub struct Callee {
+    pub instance: Instance,
+    pub kind: CalleeKind,
+}
+
+/// How an instance uses one of its callees.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize)]
+pub enum CalleeKind {
+    /// A direct call, including the calls to the panic functions behind assertions.
+    Call,
+    /// The drop glue run by a `Drop` terminator.
+    Drop,
+    /// A method or the drop glue put into a vtable by an unsizing cast.
+    VTable,
+    /// A function that is turned into a function pointer or named by a `sym` operand of inline
+    /// assembly.
+    Reify,
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize)]
 pub struct InstanceDef(usize);
 
@@ -22,10 +22,27 @@ fn already_rounded_value_should_use_exponential(&self) -> bool {
 
 impl_general_format! { f32 f64 }
 
+// `f16` and `f128` compare the bits of the absolute value instead, which are ordered like the
+// values, so that no float arithmetic is needed for them.
+impl GeneralFormat for f16 {
+    fn already_rounded_value_should_use_exponential(&self) -> bool {
+        let abs = self.to_bits() & !(1 << 15);
+        // All finite `f16` values are below 1e+16.
+        (abs != 0 && abs < 1e-4_f16.to_bits()) || abs >= f16::INFINITY.to_bits()
+    }
+}
+
+impl GeneralFormat for f128 {
+    fn already_rounded_value_should_use_exponential(&self) -> bool {
+        let abs = self.to_bits() & !(1 << 127);
+        (abs != 0 && abs < 1e-4_f128.to_bits()) || abs >= 1e+16_f128.to_bits()
+    }
+}
+
 // Don't inline this so callers don't use the stack space this function
 // requires unless they have to.
 #[inline(never)]
-fn float_to_decimal_common_exact<T>(
+fn float_to_decimal_common_exact<T, const N: usize>(
     fmt: &mut Formatter<'_>,
     num: &T,
     sign: flt2dec::Sign,
@@ -34,7 +51,7 @@ fn float_to_decimal_common_exact<T>(
 where
     T: flt2dec::DecodableFloat,
 {
-    let mut buf: [MaybeUninit<u8>; 1024] = [MaybeUninit::uninit(); 1024]; // enough for f32 and f64
+    let mut buf: [MaybeUninit<u8>; N] = [MaybeUninit::uninit(); N];
     let mut parts: [MaybeUninit<numfmt::Part<'_>>; 4] = [MaybeUninit::uninit(); 4];
     let formatted = flt2dec::to_exact_fixed_str(
         flt2dec::strategy::grisu::format_exact,
@@ -51,7 +68,7 @@ fn float_to_decimal_common_exact<T>(
 // Don't inline this so callers that call both this and the above won't wind
 // up using the combined stack space of both functions in some cases.
 #[inline(never)]
-fn float_to_decimal_common_shortest<T>(
+fn float_to_decimal_common_shortest<T, const N: usize>(
     fmt: &mut Formatter<'_>,
     num: &T,
     sign: flt2dec::Sign,
@@ -60,9 +77,7 @@ fn float_to_decimal_common_shortest<T>(
 where
     T: flt2dec::DecodableFloat,
 {
-    // enough for f32 and f64
-    let mut buf: [MaybeUninit<u8>; flt2dec::MAX_SIG_DIGITS] =
-        [MaybeUninit::uninit(); flt2dec::MAX_SIG_DIGITS];
+    let mut buf: [MaybeUninit<u8>; N] = [MaybeUninit::uninit(); N];
     let mut parts: [MaybeUninit<numfmt::Part<'_>>; 4] = [MaybeUninit::uninit(); 4];
     let formatted = flt2dec::to_shortest_str(
         flt2dec::strategy::grisu::format_shortest,
@@ -76,7 +91,10 @@ fn float_to_decimal_common_shortest<T>(
     unsafe { fmt.pad_formatted_parts(&formatted) }
 }
 
-fn float_to_decimal_display<T>(fmt: &mut Formatter<'_>, num: &T) -> Result
+fn float_to_decimal_display<T, const SHORTEST_LEN: usize, const EXACT_LEN: usize>(
+    fmt: &mut Formatter<'_>,
+    num: &T,
+) -> Result
 where
     T: flt2dec::DecodableFloat,
 {
@@ -87,17 +105,17 @@ fn float_to_decimal_display<T>(fmt: &mut Formatter<'_>, num: &T) -> Result
     };
 
     if let Some(precision) = fmt.precision {
-        float_to_decimal_common_exact(fmt, num, sign, precision)
+        float_to_decimal_common_exact::<T, EXACT_LEN>(fmt, num, sign, precision)
     } else {
         let min_precision = 0;
-        float_to_decimal_common_shortest(fmt, num, sign, min_precision)
+        float_to_decimal_common_shortest::<T, SHORTEST_LEN>(fmt, num, sign, min_precision)
     }
 }
 
 // Don't inline this so callers don't use the stack space this function
 // requires unless they have to.
 #[inline(never)]
-fn float_to_exponential_common_exact<T>(
+fn float_to_exponential_common_exact<T, const N: usize>(
     fmt: &mut Formatter<'_>,
     num: &T,
     sign: flt2dec::Sign,
@@ -107,7 +125,7 @@ fn float_to_exponential_common_exact<T>(
 where
     T: flt2dec::DecodableFloat,
 {
-    let mut buf: [MaybeUninit<u8>; 1024] = [MaybeUninit::uninit(); 1024]; // enough for f32 and f64
+    let mut buf: [MaybeUninit<u8>; N] = [MaybeUninit::uninit(); N];
     let mut parts: [MaybeUninit<numfmt::Part<'_>>; 6] = [MaybeUninit::uninit(); 6];
     let formatted = flt2dec::to_exact_exp_str(
         flt2dec::strategy::grisu::format_exact,
@@ -125,7 +143,7 @@ fn float_to_exponential_common_exact<T>(
 // Don't inline this so callers that call both this and the above won't wind
 // up using the combined stack space of both functions in some cases.
 #[inline(never)]
-fn float_to_exponential_common_shortest<T>(
+fn float_to_exponential_common_shortest<T, const N: usize>(
     fmt: &mut Formatter<'_>,
     num: &T,
     sign: flt2dec::Sign,
@@ -134,9 +152,7 @@ fn float_to_exponential_common_shortest<T>(
 where
     T: flt2dec::DecodableFloat,
 {
-    // enough for f32 and f64
-    let mut buf: [MaybeUninit<u8>; flt2dec::MAX_SIG_DIGITS] =
-        [MaybeUninit::uninit(); flt2dec::MAX_SIG_DIGITS];
+    let mut buf: [MaybeUninit<u8>; N] = [MaybeUninit::uninit(); N];
     let mut parts: [MaybeUninit<numfmt::Part<'_>>; 6] = [MaybeUninit::uninit(); 6];
     let formatted = flt2dec::to_shortest_exp_str(
         flt2dec::strategy::grisu::format_shortest,
@@ -152,7 +168,11 @@ fn float_to_exponential_common_shortest<T>(
 }
 
 // Common code of floating point LowerExp and UpperExp.
-fn float_to_exponential_common<T>(fmt: &mut Formatter<'_>, num: &T, upper: bool) -> Result
+fn float_to_exponential_common<T, const SHORTEST_LEN: usize, const EXACT_LEN: usize>(
+    fmt: &mut Formatter<'_>,
+    num: &T,
+    upper: bool,
+) -> Result
 where
     T: flt2dec::DecodableFloat,
 {
@@ -164,13 +184,16 @@ fn float_to_exponential_common<T>(fmt: &mut Formatter<'_>, num: &T, upper: bool)
 
     if let Some(precision) = fmt.precision {
         // 1 integral digit + `precision` fractional digits = `precision + 1` total digits
-        float_to_exponential_common_exact(fmt, num, sign, precision + 1, upper)
+        float_to_exponential_common_exact::<T, EXACT_LEN>(fmt, num, sign, precision + 1, upper)
     } else {
-        float_to_exponential_common_shortest(fmt, num, sign, upper)
+        float_to_exponential_common_shortest::<T, SHORTEST_LEN>(fmt, num, sign, upper)
     }
 }
 
-fn float_to_general_debug<T>(fmt: &mut Formatter<'_>, num: &T) -> Result
+fn float_to_general_debug<T, const SHORTEST_LEN: usize, const EXACT_LEN: usize>(
+    fmt: &mut Formatter<'_>,
+    num: &T,
+) -> Result
 where
     T: flt2dec::DecodableFloat + GeneralFormat,
 {
@@ -182,66 +205,64 @@ fn float_to_general_debug<T>(fmt: &mut Formatter<'_>, num: &T) -> Result
 
     if let Some(precision) = fmt.precision {
         // this behavior of {:.PREC?} predates exponential formatting for {:?}
-        float_to_decimal_common_exact(fmt, num, sign, precision)
+        float_to_decimal_common_exact::<T, EXACT_LEN>(fmt, num, sign, precision)
     } else {
         // since there is no precision, there will be no rounding
         if num.already_rounded_value_should_use_exponential() {
             let upper = false;
-            float_to_exponential_common_shortest(fmt, num, sign, upper)
+            float_to_exponential_common_shortest::<T, SHORTEST_LEN>(fmt, num, sign, upper)
         } else {
             let min_precision = 1;
-            float_to_decimal_common_shortest(fmt, num, sign, min_precision)
+            float_to_decimal_common_shortest::<T, SHORTEST_LEN>(fmt, num, sign, min_precision)
         }
     }
 }
 
+// `$exact_buf_len` is the size of the buffer for the exact modes, which must be at least
+// `flt2dec::estimate_max_buf_len` of the smallest exponent of the type (12,413 for `f128`).
+// The shortest modes only need `DecodableFloat::MAX_SIG_DIGITS`.
 macro_rules! floating {
-    ($ty:ident) => {
+    ($ty:ident, $exact_buf_len:expr) => {
+        floating! { $ty, { <$ty as flt2dec::DecodableFloat>::MAX_SIG_DIGITS }, $exact_buf_len }
+    };
+    ($ty:ident, $shortest_buf_len:expr, $exact_buf_len:expr) => {
         #[stable(feature = "rust1", since = "1.0.0")]
         impl Debug for $ty {
             fn fmt(&self, fmt: &mut Formatter<'_>) -> Result {
-                float_to_general_debug(fmt, self)
+                float_to_general_debug::<$ty, { $shortest_buf_len }, { $exact_buf_len }>(fmt, self)
             }
         }
 
         #[stable(feature = "rust1", since = "1.0.0")]
         impl Display for $ty {
             fn fmt(&self, fmt: &mut Formatter<'_>) -> Result {
-                float_to_decimal_display(fmt, self)
+                float_to_decimal_display::<$ty, { $shortest_buf_len }, { $exact_buf_len }>(
+                    fmt, self,
+                )
             }
         }
 
         #[stable(feature = "rust1", since = "1.0.0")]
         impl LowerExp for $ty {
             fn fmt(&self, fmt: &mut Formatter<'_>) -> Result {
-                float_to_exponential_common(fmt, self, false)
+                float_to_exponential_common::<$ty, { $shortest_buf_len }, { $exact_buf_len }>(
+                    fmt, self, false,
+                )
             }
         }
 
         #[stable(feature = "rust1", since = "1.0.0")]
         impl UpperExp for $ty {
             fn fmt(&self, fmt: &mut Formatter<'_>) -> Result {
-                float_to_exponential_common(fmt, self, true)
+                float_to_exponential_common::<$ty, { $shortest_buf_len }, { $exact_buf_len }>(
+                    fmt, self, true,
+                )
             }
         }
     };
 }
 
-floating! { f32 }
-floating! { f64 }
-
-#[stable(feature = "rust1", since = "1.0.0")]
-impl Debug for f16 {
-    #[inline]
-    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
-        write!(f, "{:#06x}", self.to_bits())
-    }
-}
-
-#[stable(feature = "rust1", since = "1.0.0")]
-impl Debug for f128 {
-    #[inline]
-    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
-        write!(f, "{:#034x}", self.to_bits())
-    }
-}
+floating! { f16, 1024 }
+floating! { f32, 1024 }
+floating! { f64, 1024 }
+floating! { f128, 12416 }
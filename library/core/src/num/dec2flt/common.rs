@@ -1,5 +1,7 @@
 //! Common utilities, for internal use only.
 
+use crate::num::dec2flt::float::Mantissa;
+
 /// Helper methods to process immutable bytes.
 pub(crate) trait ByteSlice {
     /// Reads 8 bytes as a 64-bit integer in little-endian order.
@@ -61,19 +63,21 @@ pub(crate) fn is_8digits(v: u64) -> bool {
     (a | b) & 0x8080_8080_8080_8080 == 0
 }
 
-/// A custom 64-bit floating point type, representing `f * 2^e`.
+/// A custom floating point type, representing `f * 2^e`.
 /// e is biased, so it be directly shifted into the exponent bits.
+///
+/// The significant digits are a `u64` for all types but `f128`.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
-pub struct BiasedFp {
+pub struct BiasedFp<M = u64> {
     /// The significant digits.
-    pub f: u64,
+    pub f: M,
     /// The biased, binary exponent.
     pub e: i32,
 }
 
-impl BiasedFp {
+impl<M: Mantissa> BiasedFp<M> {
     #[inline]
     pub const fn zero_pow2(e: i32) -> Self {
-        Self { f: 0, e }
+        Self { f: M::ZERO, e }
     }
 }
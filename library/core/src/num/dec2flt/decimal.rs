@@ -10,9 +10,44 @@
 //! available online: <https://nigeltao.github.io/blog/2020/parse-number-f64-simple.html>.
 
 use crate::num::dec2flt::common::{is_8digits, ByteSlice};
+use crate::num::dec2flt::float::Mantissa;
 
+/// The maximum number of digits required to unambiguously round a float.
+///
+/// For a double-precision IEEE 754 float, this required 767 digits,
+/// so we store the max digits + 1.
+///
+/// We can exactly represent a float in radix `b` from radix 2 if
+/// `b` is divisible by 2. This function calculates the exact number of
+/// digits required to exactly represent that float.
+///
+/// According to the "Handbook of Floating Point Arithmetic",
+/// for IEEE754, with emin being the min exponent, p2 being the
+/// precision, and b being the radix, the number of digits follows as:
+///
+/// `−emin + p2 + ⌊(emin + 1) log(2, b) − log(1 − 2^(−p2), b)⌋`
+///
+/// For f32, this follows as:
+///     emin = -126
+///     p2 = 24
+///
+/// For f64, this follows as:
+///     emin = -1022
+///     p2 = 53
+///
+/// In Python:
+///     `-emin + p2 + math.floor((emin+ 1)*math.log(2, b)-math.log(1-2**(-p2), b))`
+pub const MAX_DIGITS: usize = 768;
+
+/// The maximum number of digits required to unambiguously round an `f128`.
+///
+/// Following the formula for [`MAX_DIGITS`] with emin = -16382 and p2 = 113,
+/// this requires 11563 digits, so we store the max digits + 1.
+pub const MAX_DIGITS_F128: usize = 11564;
+
+/// A decimal with room for `N` significant digits.
 #[derive(Clone)]
-pub struct Decimal {
+pub struct Decimal<const N: usize> {
     /// The number of significant digits in the decimal.
     pub num_digits: usize,
     /// The offset of the decimal point in the significant digits.
@@ -20,49 +55,23 @@ pub struct Decimal {
     /// If the number of significant digits stored in the decimal is truncated.
     pub truncated: bool,
     /// Buffer of the raw digits, in the range [0, 9].
-    pub digits: [u8; Self::MAX_DIGITS],
+    pub digits: [u8; N],
 }
 
-impl Default for Decimal {
+impl<const N: usize> Default for Decimal<N> {
     fn default() -> Self {
-        Self { num_digits: 0, decimal_point: 0, truncated: false, digits: [0; Self::MAX_DIGITS] }
+        Self { num_digits: 0, decimal_point: 0, truncated: false, digits: [0; N] }
     }
 }
 
-impl Decimal {
-    /// The maximum number of digits required to unambiguously round a float.
-    ///
-    /// For a double-precision IEEE 754 float, this required 767 digits,
-    /// so we store the max digits + 1.
-    ///
-    /// We can exactly represent a float in radix `b` from radix 2 if
-    /// `b` is divisible by 2. This function calculates the exact number of
-    /// digits required to exactly represent that float.
-    ///
-    /// According to the "Handbook of Floating Point Arithmetic",
-    /// for IEEE754, with emin being the min exponent, p2 being the
-    /// precision, and b being the radix, the number of digits follows as:
-    ///
-    /// `−emin + p2 + ⌊(emin + 1) log(2, b) − log(1 − 2^(−p2), b)⌋`
-    ///
-    /// For f32, this follows as:
-    ///     emin = -126
-    ///     p2 = 24
-    ///
-    /// For f64, this follows as:
-    ///     emin = -1022
-    ///     p2 = 53
-    ///
-    /// In Python:
-    ///     `-emin + p2 + math.floor((emin+ 1)*math.log(2, b)-math.log(1-2**(-p2), b))`
-    pub const MAX_DIGITS: usize = 768;
+impl<const N: usize> Decimal<N> {
     /// The max digits that can be exactly represented in a 64-bit integer.
     pub const MAX_DIGITS_WITHOUT_OVERFLOW: usize = 19;
     pub const DECIMAL_POINT_RANGE: i32 = 2047;
 
     /// Append a digit to the buffer.
     pub fn try_add_digit(&mut self, digit: u8) {
-        if self.num_digits < Self::MAX_DIGITS {
+        if self.num_digits < N {
             self.digits[self.num_digits] = digit;
         }
         self.num_digits += 1;
@@ -72,29 +81,32 @@ pub fn try_add_digit(&mut self, digit: u8) {
     pub fn trim(&mut self) {
         // All of the following calls to `Decimal::trim` can't panic because:
         //
-        //  1. `parse_decimal` sets `num_digits` to a max of `Decimal::MAX_DIGITS`.
+        //  1. `parse_decimal` sets `num_digits` to a max of `N`.
         //  2. `right_shift` sets `num_digits` to `write_index`, which is bounded by `num_digits`.
-        //  3. `left_shift` `num_digits` to a max of `Decimal::MAX_DIGITS`.
+        //  3. `left_shift` `num_digits` to a max of `N`.
         //
         // Trim is only called in `right_shift` and `left_shift`.
-        debug_assert!(self.num_digits <= Self::MAX_DIGITS);
+        debug_assert!(self.num_digits <= N);
         while self.num_digits != 0 && self.digits[self.num_digits - 1] == 0 {
             self.num_digits -= 1;
         }
     }
 
-    pub fn round(&self) -> u64 {
+    /// Rounds the decimal to an integer, saturating at the maximum of `M`.
+    pub fn round<M: Mantissa>(&self) -> M {
+        // The most digits that can't overflow `M`, even when rounding up.
+        let max_decimal_point = (M::BITS * 3 / 10 - 1) as i32;
         if self.num_digits == 0 || self.decimal_point < 0 {
-            return 0;
-        } else if self.decimal_point > 18 {
-            return 0xFFFF_FFFF_FFFF_FFFF_u64;
+            return M::ZERO;
+        } else if self.decimal_point > max_decimal_point {
+            return M::MAX;
         }
         let dp = self.decimal_point as usize;
-        let mut n = 0_u64;
+        let mut n = M::ZERO;
         for i in 0..dp {
-            n *= 10;
+            n = n * M::from(10);
             if i < self.num_digits {
-                n += self.digits[i] as u64;
+                n = n + M::from(self.digits[i] as u64);
             }
         }
         let mut round_up = false;
@@ -105,7 +117,7 @@ pub fn round(&self) -> u64 {
             }
         }
         if round_up {
-            n += 1;
+            n = n + M::ONE;
         }
         n
     }
@@ -125,7 +137,7 @@ pub fn left_shift(&mut self, shift: usize) {
             n += (self.digits[read_index] as u64) << shift;
             let quotient = n / 10;
             let remainder = n - (10 * quotient);
-            if write_index < Self::MAX_DIGITS {
+            if write_index < N {
                 self.digits[write_index] = remainder as u8;
             } else if remainder > 0 {
                 self.truncated = true;
@@ -136,7 +148,7 @@ pub fn left_shift(&mut self, shift: usize) {
             write_index -= 1;
             let quotient = n / 10;
             let remainder = n - (10 * quotient);
-            if write_index < Self::MAX_DIGITS {
+            if write_index < N {
                 self.digits[write_index] = remainder as u8;
             } else if remainder > 0 {
                 self.truncated = true;
@@ -144,8 +156,8 @@ pub fn left_shift(&mut self, shift: usize) {
             n = quotient;
         }
         self.num_digits += num_new_digits;
-        if self.num_digits > Self::MAX_DIGITS {
-            self.num_digits = Self::MAX_DIGITS;
+        if self.num_digits > N {
+            self.num_digits = N;
         }
         self.decimal_point += num_new_digits as i32;
         self.trim();
@@ -189,7 +201,7 @@ pub fn right_shift(&mut self, shift: usize) {
         while n > 0 {
             let new_digit = (n >> shift) as u8;
             n = 10 * (n & mask);
-            if write_index < Self::MAX_DIGITS {
+            if write_index < N {
                 self.digits[write_index] = new_digit;
                 write_index += 1;
             } else if new_digit > 0 {
@@ -202,7 +214,7 @@ pub fn right_shift(&mut self, shift: usize) {
 }
 
 /// Parse a big integer representation of the float as a decimal.
-pub fn parse_decimal(mut s: &[u8]) -> Decimal {
+pub fn parse_decimal<const N: usize>(mut s: &[u8]) -> Decimal<N> {
     let mut d = Decimal::default();
     let start = s;
 
@@ -221,7 +233,7 @@ pub fn parse_decimal(mut s: &[u8]) -> Decimal {
                 s = s_next;
             }
         }
-        while s.len() >= 8 && d.num_digits + 8 < Decimal::MAX_DIGITS {
+        while s.len() >= 8 && d.num_digits + 8 < N {
             let v = s.read_u64();
             if !is_8digits(v) {
                 break;
@@ -246,9 +258,9 @@ pub fn parse_decimal(mut s: &[u8]) -> Decimal {
         d.decimal_point += n_trailing_zeros as i32;
         d.num_digits -= n_trailing_zeros;
         d.decimal_point += d.num_digits as i32;
-        if d.num_digits > Decimal::MAX_DIGITS {
+        if d.num_digits > N {
             d.truncated = true;
-            d.num_digits = Decimal::MAX_DIGITS;
+            d.num_digits = N;
         }
     }
     if let Some((&ch, s_next)) = s.split_first() {
@@ -272,13 +284,13 @@ pub fn parse_decimal(mut s: &[u8]) -> Decimal {
             d.decimal_point += if neg_exp { -exp_num } else { exp_num };
         }
     }
-    for i in d.num_digits..Decimal::MAX_DIGITS_WITHOUT_OVERFLOW {
+    for i in d.num_digits..Decimal::<N>::MAX_DIGITS_WITHOUT_OVERFLOW {
         d.digits[i] = 0;
     }
     d
 }
 
-fn number_of_digits_decimal_left_shift(d: &Decimal, mut shift: usize) -> usize {
+fn number_of_digits_decimal_left_shift<const N: usize>(d: &Decimal<N>, mut shift: usize) -> usize {
     #[rustfmt::skip]
     const TABLE: [u16; 65] = [
         0x0000, 0x0800, 0x0801, 0x0803, 0x1006, 0x1009, 0x100D, 0x1812, 0x1817, 0x181D, 0x2024,
@@ -1,10 +1,76 @@
 //! Helper trait for generic float types.
 
 use crate::fmt::{Debug, LowerExp};
+use crate::num::dec2flt::common::BiasedFp;
+use crate::num::dec2flt::decimal::{MAX_DIGITS, MAX_DIGITS_F128};
+use crate::num::dec2flt::number::Number;
+use crate::num::dec2flt::slow::parse_long_mantissa;
 use crate::num::FpCategory;
-use crate::ops::{Add, Div, Mul, Neg};
+use crate::ops::{Add, BitAnd, BitAndAssign, BitOr, BitOrAssign, Div, Mul, Neg, Shl, Shr, Sub};
 
-/// A helper trait to avoid duplicating basically all the conversion code for `f32` and `f64`.
+/// The unsigned integer holding the significand of a [`RawFloat`]. This is `u64` for all
+/// types but `f128`, so that the narrower types don't pay for 128-bit arithmetic.
+#[doc(hidden)]
+pub trait Mantissa:
+    Sized
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + BitAnd<Output = Self>
+    + BitAndAssign
+    + BitOr<Output = Self>
+    + BitOrAssign
+    + Shl<usize, Output = Self>
+    + Shr<usize, Output = Self>
+    + From<u64>
+    + Into<u128>
+    + TryInto<u64>
+    + Ord
+    + Default
+    + Clone
+    + Copy
+    + Debug
+{
+    const ZERO: Self;
+    const ONE: Self;
+    const MAX: Self;
+    const BITS: u32;
+
+    fn leading_zeros(self) -> u32;
+    fn checked_add(self, rhs: Self) -> Option<Self>;
+    fn checked_sub(self, rhs: Self) -> Option<Self>;
+}
+
+macro_rules! impl_mantissa {
+    ($($t:ty)*) => {
+        $(impl Mantissa for $t {
+            const ZERO: Self = 0;
+            const ONE: Self = 1;
+            const MAX: Self = <$t>::MAX;
+            const BITS: u32 = <$t>::BITS;
+
+            #[inline]
+            fn leading_zeros(self) -> u32 {
+                <$t>::leading_zeros(self)
+            }
+
+            #[inline]
+            fn checked_add(self, rhs: Self) -> Option<Self> {
+                <$t>::checked_add(self, rhs)
+            }
+
+            #[inline]
+            fn checked_sub(self, rhs: Self) -> Option<Self> {
+                <$t>::checked_sub(self, rhs)
+            }
+        })*
+    };
+}
+
+impl_mantissa! { u64 u128 }
+
+/// A helper trait to avoid duplicating basically all the conversion code for `f16`, `f32`,
+/// `f64` and `f128`.
 ///
 /// See the parent module's doc comment for why this is necessary.
 ///
@@ -24,6 +90,9 @@ pub trait RawFloat:
     + Copy
     + Debug
 {
+    /// The integer type of the significand.
+    type Mant: Mantissa;
+
     const INFINITY: Self;
     const NEG_INFINITY: Self;
     const NAN: Self;
@@ -88,7 +157,7 @@ pub trait RawFloat:
     fn from_u64(v: u64) -> Self;
 
     /// Performs a raw transmutation from an integer.
-    fn from_u64_bits(v: u64) -> Self;
+    fn from_raw_bits(v: Self::Mant) -> Self;
 
     /// Gets a small power-of-ten for fast-path multiplication.
     fn pow10_fast_path(exponent: usize) -> Self;
@@ -97,10 +166,90 @@ pub trait RawFloat:
     fn classify(self) -> FpCategory;
 
     /// Returns the mantissa, exponent and sign as integers.
-    fn integer_decode(self) -> (u64, i16, i8);
+    fn integer_decode(self) -> (Self::Mant, i16, i8);
+
+    /// Tries the fast path with native arithmetic.
+    ///
+    /// It relies on the arithmetic being correctly rounded, which `f16` and `f128` do not have
+    /// on every target, so these never take it.
+    #[inline]
+    fn try_fast_path(num: &Number) -> Option<Self> {
+        num.try_fast_path::<Self>()
+    }
+
+    /// Parses the significant digits of `s` with the slow path.
+    ///
+    /// The decimal it uses has to fit all digits that can influence the rounding, which is far
+    /// more for `f128` than for the other types.
+    fn parse_long_mantissa(s: &[u8]) -> BiasedFp<Self::Mant> {
+        parse_long_mantissa::<Self, MAX_DIGITS>(s)
+    }
+}
+
+impl RawFloat for f16 {
+    type Mant = u64;
+
+    const INFINITY: Self = f16::INFINITY;
+    const NEG_INFINITY: Self = f16::NEG_INFINITY;
+    const NAN: Self = f16::NAN;
+    const NEG_NAN: Self = -f16::NAN;
+
+    const MANTISSA_EXPLICIT_BITS: usize = 10;
+    const MIN_EXPONENT_ROUND_TO_EVEN: i32 = -22;
+    const MAX_EXPONENT_ROUND_TO_EVEN: i32 = 5;
+    const MIN_EXPONENT_FAST_PATH: i64 = -4; // assuming FLT_EVAL_METHOD = 0
+    const MAX_EXPONENT_FAST_PATH: i64 = 4;
+    const MAX_EXPONENT_DISGUISED_FAST_PATH: i64 = 7;
+    const MINIMUM_EXPONENT: i32 = -15;
+    const INFINITE_POWER: i32 = 0x1F;
+    const SIGN_INDEX: usize = 15;
+    const SMALLEST_POWER_OF_TEN: i32 = -27;
+    const LARGEST_POWER_OF_TEN: i32 = 4;
+
+    #[inline]
+    fn from_u64(v: u64) -> Self {
+        debug_assert!(v <= Self::MAX_MANTISSA_FAST_PATH);
+        v as _
+    }
+
+    #[inline]
+    fn from_raw_bits(v: u64) -> Self {
+        f16::from_bits((v & 0xFFFF) as u16)
+    }
+
+    #[inline]
+    fn pow10_fast_path(exponent: usize) -> Self {
+        #[allow(clippy::use_self)]
+        const TABLE: [f16; 8] = [1e0, 1e1, 1e2, 1e3, 1e4, 0., 0., 0.];
+        TABLE[exponent & 7]
+    }
+
+    /// Returns the mantissa, exponent and sign as integers.
+    fn integer_decode(self) -> (u64, i16, i8) {
+        let bits = self.to_bits();
+        let sign: i8 = if bits >> 15 == 0 { 1 } else { -1 };
+        let mut exponent: i16 = ((bits >> 10) & 0x1f) as i16;
+        let mantissa = if exponent == 0 { (bits & 0x3ff) << 1 } else { (bits & 0x3ff) | 0x400 };
+        // Exponent bias + mantissa shift
+        exponent -= 15 + 10;
+        (mantissa as u64, exponent, sign)
+    }
+
+    #[inline]
+    fn try_fast_path(_: &Number) -> Option<Self> {
+        None
+    }
+
+    fn classify(self) -> FpCategory {
+        // `f16::classify` compares floats to work around excess precision on some targets,
+        // but parsing only ever creates floats from their bits.
+        f16::classify_bits(self.to_bits())
+    }
 }
 
 impl RawFloat for f32 {
+    type Mant = u64;
+
     const INFINITY: Self = f32::INFINITY;
     const NEG_INFINITY: Self = f32::NEG_INFINITY;
     const NAN: Self = f32::NAN;
@@ -125,7 +274,7 @@ fn from_u64(v: u64) -> Self {
     }
 
     #[inline]
-    fn from_u64_bits(v: u64) -> Self {
+    fn from_raw_bits(v: u64) -> Self {
         f32::from_bits((v & 0xFFFFFFFF) as u32)
     }
 
@@ -154,6 +303,8 @@ fn classify(self) -> FpCategory {
 }
 
 impl RawFloat for f64 {
+    type Mant = u64;
+
     const INFINITY: Self = f64::INFINITY;
     const NEG_INFINITY: Self = f64::NEG_INFINITY;
     const NAN: Self = f64::NAN;
@@ -178,7 +329,7 @@ fn from_u64(v: u64) -> Self {
     }
 
     #[inline]
-    fn from_u64_bits(v: u64) -> Self {
+    fn from_raw_bits(v: u64) -> Self {
         f64::from_bits(v)
     }
 
@@ -209,3 +360,78 @@ fn classify(self) -> FpCategory {
         self.classify()
     }
 }
+
+impl RawFloat for f128 {
+    type Mant = u128;
+
+    const INFINITY: Self = f128::INFINITY;
+    const NEG_INFINITY: Self = f128::NEG_INFINITY;
+    const NAN: Self = f128::NAN;
+    const NEG_NAN: Self = -f128::NAN;
+
+    const MANTISSA_EXPLICIT_BITS: usize = 112;
+    // Eisel-Lemire is never used for `f128`, see `compute_float`.
+    const MIN_EXPONENT_ROUND_TO_EVEN: i32 = 0;
+    const MAX_EXPONENT_ROUND_TO_EVEN: i32 = 49;
+    const MIN_EXPONENT_FAST_PATH: i64 = -48; // assuming FLT_EVAL_METHOD = 0
+    const MAX_EXPONENT_FAST_PATH: i64 = 48;
+    const MAX_EXPONENT_DISGUISED_FAST_PATH: i64 = 82;
+    const MINIMUM_EXPONENT: i32 = -16383;
+    const INFINITE_POWER: i32 = 0x7FFF;
+    const SIGN_INDEX: usize = 127;
+    const SMALLEST_POWER_OF_TEN: i32 = -4984;
+    const LARGEST_POWER_OF_TEN: i32 = 4932;
+    // Every `u64` is exactly representable.
+    const MAX_MANTISSA_FAST_PATH: u64 = u64::MAX;
+
+    #[inline]
+    fn from_u64(v: u64) -> Self {
+        v as _
+    }
+
+    #[inline]
+    fn from_raw_bits(v: u128) -> Self {
+        f128::from_bits(v)
+    }
+
+    #[inline]
+    fn pow10_fast_path(exponent: usize) -> Self {
+        #[allow(clippy::use_self)]
+        const TABLE: [f128; 64] = [
+            1e0, 1e1, 1e2, 1e3, 1e4, 1e5, 1e6, 1e7, 1e8, 1e9, 1e10, 1e11, 1e12, 1e13, 1e14, 1e15,
+            1e16, 1e17, 1e18, 1e19, 1e20, 1e21, 1e22, 1e23, 1e24, 1e25, 1e26, 1e27, 1e28, 1e29,
+            1e30, 1e31, 1e32, 1e33, 1e34, 1e35, 1e36, 1e37, 1e38, 1e39, 1e40, 1e41, 1e42, 1e43,
+            1e44, 1e45, 1e46, 1e47, 1e48, 0., 0., 0., 0., 0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+            0.,
+        ];
+        TABLE[exponent & 63]
+    }
+
+    /// Returns the mantissa, exponent and sign as integers.
+    fn integer_decode(self) -> (u128, i16, i8) {
+        let bits = self.to_bits();
+        let sign: i8 = if bits >> 127 == 0 { 1 } else { -1 };
+        let mut exponent: i16 = ((bits >> 112) & 0x7fff) as i16;
+        let mantissa = if exponent == 0 {
+            (bits & 0xffff_ffff_ffff_ffff_ffff_ffff_ffff) << 1
+        } else {
+            (bits & 0xffff_ffff_ffff_ffff_ffff_ffff_ffff) | 0x1_0000_0000_0000_0000_0000_0000_0000
+        };
+        // Exponent bias + mantissa shift
+        exponent -= 16383 + 112;
+        (mantissa, exponent, sign)
+    }
+
+    #[inline]
+    fn try_fast_path(_: &Number) -> Option<Self> {
+        None
+    }
+
+    fn classify(self) -> FpCategory {
+        self.classify()
+    }
+
+    fn parse_long_mantissa(s: &[u8]) -> BiasedFp<Self::Mant> {
+        parse_long_mantissa::<Self, MAX_DIGITS_F128>(s)
+    }
+}
@@ -24,7 +24,7 @@
 /// at a Gigabyte per Second" in section 5, "Fast Algorithm", and
 /// section 6, "Exact Numbers And Ties", available online:
 /// <https://arxiv.org/abs/2101.11408.pdf>.
-pub fn compute_float<F: RawFloat>(q: i64, mut w: u64) -> BiasedFp {
+pub fn compute_float<F: RawFloat>(q: i64, mut w: u64) -> BiasedFp<F::Mant> {
     let fp_zero = BiasedFp::zero_pow2(0);
     let fp_inf = BiasedFp::zero_pow2(F::INFINITE_POWER);
     let fp_error = BiasedFp::zero_pow2(-1);
@@ -35,6 +35,11 @@ pub fn compute_float<F: RawFloat>(q: i64, mut w: u64) -> BiasedFp {
     } else if q > F::LARGEST_POWER_OF_TEN as i64 {
         return fp_inf;
     }
+    // The 128-bit product only has enough precision for significands of up to 61 bits,
+    // so `f128` always takes the slow path.
+    if F::MANTISSA_EXPLICIT_BITS + 3 > 64 {
+        return fp_error;
+    }
     // Normalize our significant digits, so the most-significant bit is set.
     let lz = w.leading_zeros();
     w <<= lz;
@@ -73,7 +78,7 @@ pub fn compute_float<F: RawFloat>(q: i64, mut w: u64) -> BiasedFp {
         mantissa += mantissa & 1;
         mantissa >>= 1;
         power2 = (mantissa >= (1_u64 << F::MANTISSA_EXPLICIT_BITS)) as i32;
-        return BiasedFp { f: mantissa, e: power2 };
+        return BiasedFp { f: mantissa.into(), e: power2 };
     }
     // Need to handle rounding ties. Normally, we need to round up,
     // but if we fall right in between and we have an even basis, we
@@ -111,7 +116,7 @@ pub fn compute_float<F: RawFloat>(q: i64, mut w: u64) -> BiasedFp {
         // Exponent is above largest normal value, must be infinite.
         return fp_inf;
     }
-    BiasedFp { f: mantissa, e: power2 }
+    BiasedFp { f: mantissa.into(), e: power2 }
 }
 
 /// Calculate a base 2 exponent from a decimal exponent.
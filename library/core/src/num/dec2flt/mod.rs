@@ -34,6 +34,10 @@
 //! a large-decimal representation, shifting the digits into range, calculating
 //! the upper significant bits and exactly round to the nearest representation.
 //!
+//! `f16` and `f128` skip the fast path with native floats, since their arithmetic is emulated
+//! (and not always correctly rounded) on many targets. The significand of `f128` is also too
+//! wide for Eisel-Lemire, so it always goes straight to the large-decimal representation.
+//!
 //! Another aspect that needs attention is the ``RawFloat`` trait by which almost all functions
 //! are parametrized. One might think that it's enough to parse to `f64` and cast the result to
 //! `f32`. Unfortunately this is not the world we live in, and this has nothing to do with using
@@ -79,7 +83,6 @@
 use self::float::RawFloat;
 use self::lemire::compute_float;
 use self::parse::{parse_inf_nan, parse_number};
-use self::slow::parse_long_mantissa;
 use crate::error::Error;
 use crate::fmt;
 use crate::str::FromStr;
@@ -150,7 +153,7 @@ impl FromStr for $t {
             // be filled with that of `dec2flt`, which has #[inline(always)].
             // Since `dec2flt` is generic, a normal inline attribute on this function
             // with `dec2flt` having no attributes results in heavily repeated
-            // generation of `dec2flt`, despite the fact only a maximum of 2
+            // generation of `dec2flt`, despite the fact only a maximum of 4
             // possible instances can ever exist. Adding #[inline(never)] avoids this.
             #[inline(never)]
             fn from_str(src: &str) -> Result<Self, ParseFloatError> {
@@ -159,13 +162,15 @@ fn from_str(src: &str) -> Result<Self, ParseFloatError> {
         }
     };
 }
+from_str_float_impl!(f16);
 from_str_float_impl!(f32);
 from_str_float_impl!(f64);
+from_str_float_impl!(f128);
 
 /// An error which can be returned when parsing a float.
 ///
 /// This error is used as the error type for the [`FromStr`] implementation
-/// for [`f32`] and [`f64`].
+/// for the floating-point types, such as [`f32`] and [`f64`].
 ///
 /// # Example
 ///
@@ -219,11 +224,15 @@ pub fn pfe_invalid() -> ParseFloatError {
     ParseFloatError { kind: FloatErrorKind::Invalid }
 }
 
-/// Converts a `BiasedFp` to the closest machine float type.
-fn biased_fp_to_float<T: RawFloat>(x: BiasedFp) -> T {
+/// Converts a `BiasedFp` and a sign to the closest machine float type.
+///
+/// The sign is applied to the bits rather than by negation, which may not
+/// be native to `f16` and `f128`.
+fn biased_fp_to_float<T: RawFloat>(x: BiasedFp<T::Mant>, negative: bool) -> T {
     let mut word = x.f;
-    word |= (x.e as u64) << T::MANTISSA_EXPLICIT_BITS;
-    T::from_u64_bits(word)
+    word |= T::Mant::from(x.e as u64) << T::MANTISSA_EXPLICIT_BITS;
+    word |= T::Mant::from(negative as u64) << T::SIGN_INDEX;
+    T::from_raw_bits(word)
 }
 
 /// Converts a decimal string into a floating point number.
@@ -250,7 +259,7 @@ pub fn dec2flt<F: RawFloat>(s: &str) -> Result<F, ParseFloatError> {
     };
     num.negative = negative;
     if !cfg!(feature = "optimize_for_size") {
-        if let Some(value) = num.try_fast_path::<F>() {
+        if let Some(value) = F::try_fast_path(&num) {
             return Ok(value);
         }
     }
@@ -266,12 +275,8 @@ pub fn dec2flt<F: RawFloat>(s: &str) -> Result<F, ParseFloatError> {
     // Unable to correctly round the float using the Eisel-Lemire algorithm.
     // Fallback to a slower, but always correct algorithm.
     if fp.e < 0 {
-        fp = parse_long_mantissa::<F>(s);
+        fp = F::parse_long_mantissa(s);
     }
 
-    let mut float = biased_fp_to_float::<F>(fp);
-    if num.negative {
-        float = -float;
-    }
-    Ok(float)
+    Ok(biased_fp_to_float::<F>(fp, num.negative))
 }
@@ -2,7 +2,7 @@
 
 use crate::num::dec2flt::common::BiasedFp;
 use crate::num::dec2flt::decimal::{parse_decimal, Decimal};
-use crate::num::dec2flt::float::RawFloat;
+use crate::num::dec2flt::float::{Mantissa, RawFloat};
 
 /// Parse the significant digits and biased, binary exponent of a float.
 ///
@@ -23,7 +23,9 @@
 ///
 /// The algorithms described here are based on "Processing Long Numbers Quickly",
 /// available here: <https://arxiv.org/pdf/2101.11408.pdf#section.11>.
-pub(crate) fn parse_long_mantissa<F: RawFloat>(s: &[u8]) -> BiasedFp {
+///
+/// The decimal holds up to `N` digits, which must be enough to round to `F`.
+pub(crate) fn parse_long_mantissa<F: RawFloat, const N: usize>(s: &[u8]) -> BiasedFp<F::Mant> {
     const MAX_SHIFT: usize = 60;
     const NUM_POWERS: usize = 19;
     const POWERS: [u8; 19] =
@@ -36,12 +38,14 @@ pub(crate) fn parse_long_mantissa<F: RawFloat>(s: &[u8]) -> BiasedFp {
     let fp_zero = BiasedFp::zero_pow2(0);
     let fp_inf = BiasedFp::zero_pow2(F::INFINITE_POWER);
 
-    let mut d = parse_decimal(s);
+    let mut d = parse_decimal::<N>(s);
 
     // Short-circuit if the value can only be a literal 0 or infinity.
-    if d.num_digits == 0 || d.decimal_point < -324 {
+    // The decimal exponent bounds are for a 19-digit integer significand,
+    // while `decimal_point` is the exponent for a significand in [0.1, 1).
+    if d.num_digits == 0 || d.decimal_point < F::SMALLEST_POWER_OF_TEN + 18 {
         return fp_zero;
-    } else if d.decimal_point >= 310 {
+    } else if d.decimal_point >= F::LARGEST_POWER_OF_TEN + 2 {
         return fp_inf;
     }
     let mut exp2 = 0_i32;
@@ -50,7 +54,7 @@ pub(crate) fn parse_long_mantissa<F: RawFloat>(s: &[u8]) -> BiasedFp {
         let n = d.decimal_point as usize;
         let shift = get_shift(n);
         d.right_shift(shift);
-        if d.decimal_point < -Decimal::DECIMAL_POINT_RANGE {
+        if d.decimal_point < -Decimal::<N>::DECIMAL_POINT_RANGE {
             return fp_zero;
         }
         exp2 += shift as i32;
@@ -67,7 +71,7 @@ pub(crate) fn parse_long_mantissa<F: RawFloat>(s: &[u8]) -> BiasedFp {
             get_shift((-d.decimal_point) as _)
         };
         d.left_shift(shift);
-        if d.decimal_point > Decimal::DECIMAL_POINT_RANGE {
+        if d.decimal_point > Decimal::<N>::DECIMAL_POINT_RANGE {
             return fp_inf;
         }
         exp2 -= shift as i32;
@@ -86,10 +90,15 @@ pub(crate) fn parse_long_mantissa<F: RawFloat>(s: &[u8]) -> BiasedFp {
         return fp_inf;
     }
     // Shift the decimal to the hidden bit, and then round the value
-    // to get the high mantissa+1 bits.
-    d.left_shift(F::MANTISSA_EXPLICIT_BITS + 1);
-    let mut mantissa = d.round();
-    if mantissa >= (1_u64 << (F::MANTISSA_EXPLICIT_BITS + 1)) {
+    // to get the high mantissa+1 bits. `f128` needs more than a single shift.
+    let mut shift = F::MANTISSA_EXPLICIT_BITS + 1;
+    while shift > MAX_SHIFT {
+        d.left_shift(MAX_SHIFT);
+        shift -= MAX_SHIFT;
+    }
+    d.left_shift(shift);
+    let mut mantissa = d.round::<F::Mant>();
+    if mantissa >= (F::Mant::ONE << (F::MANTISSA_EXPLICIT_BITS + 1)) {
         // Rounding up overflowed to the carry bit, need to
         // shift back to the hidden bit.
         d.right_shift(1);
@@ -100,10 +109,10 @@ pub(crate) fn parse_long_mantissa<F: RawFloat>(s: &[u8]) -> BiasedFp {
         }
     }
     let mut power2 = exp2 - F::MINIMUM_EXPONENT;
-    if mantissa < (1_u64 << F::MANTISSA_EXPLICIT_BITS) {
+    if mantissa < (F::Mant::ONE << F::MANTISSA_EXPLICIT_BITS) {
         power2 -= 1;
     }
     // Zero out all the bits above the explicit mantissa bits.
-    mantissa &= (1_u64 << F::MANTISSA_EXPLICIT_BITS) - 1;
+    mantissa &= (F::Mant::ONE << F::MANTISSA_EXPLICIT_BITS) - F::Mant::ONE;
     BiasedFp { f: mantissa, e: power2 }
 }
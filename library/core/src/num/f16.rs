@@ -482,7 +482,7 @@ pub const fn classify(self) -> FpCategory {
     /// plus a transmute. We do not live in a just world, but we can make it more so.
     #[inline]
     #[rustc_const_unstable(feature = "const_float_classify", issue = "72505")]
-    const fn classify_bits(b: u16) -> FpCategory {
+    pub(crate) const fn classify_bits(b: u16) -> FpCategory {
         match (b & Self::MAN_MASK, b & Self::EXP_MASK) {
             (0, Self::EXP_MASK) => FpCategory::Infinite,
             (_, Self::EXP_MASK) => FpCategory::Nan,
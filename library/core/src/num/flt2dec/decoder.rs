@@ -1,6 +1,6 @@
 //! Decodes a floating-point value into individual parts and error ranges.
 
-use crate::num::dec2flt::float::RawFloat;
+use crate::num::dec2flt::float::{Mantissa, RawFloat};
 use crate::num::FpCategory;
 
 /// Decoded unsigned finite value, such that:
@@ -10,14 +10,16 @@
 /// - Any number from `(mant - minus) * 2^exp` to `(mant + plus) * 2^exp` will
 ///   round to the original value. The range is inclusive only when
 ///   `inclusive` is `true`.
+///
+/// The mantissa and error ranges are a `u64` for all types but `f128`.
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
-pub struct Decoded {
+pub struct Decoded<M = u64> {
     /// The scaled mantissa.
-    pub mant: u64,
+    pub mant: M,
     /// The lower error range.
-    pub minus: u64,
+    pub minus: M,
     /// The upper error range.
-    pub plus: u64,
+    pub plus: M,
     /// The shared exponent in base 2.
     pub exp: i16,
     /// True when the error range is inclusive.
@@ -26,9 +28,26 @@ pub struct Decoded {
     pub inclusive: bool,
 }
 
+impl<M: Mantissa> Decoded<M> {
+    /// Returns the value with `u64` mantissa and error ranges,
+    /// unless it is of a type wider than `f64`.
+    pub fn to_u64(&self) -> Option<Decoded> {
+        if M::BITS > u64::BITS {
+            return None;
+        }
+        Some(Decoded {
+            mant: self.mant.try_into().ok()?,
+            minus: self.minus.try_into().ok()?,
+            plus: self.plus.try_into().ok()?,
+            exp: self.exp,
+            inclusive: self.inclusive,
+        })
+    }
+}
+
 /// Decoded unsigned value.
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
-pub enum FullDecoded {
+pub enum FullDecoded<M = u64> {
     /// Not-a-number.
     Nan,
     /// Infinities, either positive or negative.
@@ -36,15 +55,25 @@ pub enum FullDecoded {
     /// Zero, either positive or negative.
     Zero,
     /// Finite numbers with further decoded fields.
-    Finite(Decoded),
+    Finite(Decoded<M>),
 }
 
 /// A floating point type which can be `decode`d.
 pub trait DecodableFloat: RawFloat + Copy {
+    /// The maximum number of significant digits of the shortest representation,
+    /// see [`MAX_SIG_DIGITS`](super::MAX_SIG_DIGITS).
+    const MAX_SIG_DIGITS: usize = super::MAX_SIG_DIGITS;
+
     /// The minimum positive normalized value.
     fn min_pos_norm_value() -> Self;
 }
 
+impl DecodableFloat for f16 {
+    fn min_pos_norm_value() -> Self {
+        f16::MIN_POSITIVE
+    }
+}
+
 impl DecodableFloat for f32 {
     fn min_pos_norm_value() -> Self {
         f32::MIN_POSITIVE
@@ -57,11 +86,20 @@ fn min_pos_norm_value() -> Self {
     }
 }
 
+impl DecodableFloat for f128 {
+    // `ceil(113 * log_10 2 + 1)`
+    const MAX_SIG_DIGITS: usize = 36;
+
+    fn min_pos_norm_value() -> Self {
+        f128::MIN_POSITIVE
+    }
+}
+
 /// Returns a sign (true when negative) and `FullDecoded` value
 /// from given floating point number.
-pub fn decode<T: DecodableFloat>(v: T) -> (/*negative?*/ bool, FullDecoded) {
+pub fn decode<T: DecodableFloat>(v: T) -> (/*negative?*/ bool, FullDecoded<T::Mant>) {
     let (mant, exp, sign) = v.integer_decode();
-    let even = (mant & 1) == 0;
+    let even = (mant & T::Mant::ONE) == T::Mant::ZERO;
     let decoded = match v.classify() {
         FpCategory::Nan => FullDecoded::Nan,
         FpCategory::Infinite => FullDecoded::Infinite,
@@ -70,7 +108,13 @@ pub fn decode<T: DecodableFloat>(v: T) -> (/*negative?*/ bool, FullDecoded) {
             // neighbors: (mant - 2, exp) -- (mant, exp) -- (mant + 2, exp)
             // Float::integer_decode always preserves the exponent,
             // so the mantissa is scaled for subnormals.
-            FullDecoded::Finite(Decoded { mant, minus: 1, plus: 1, exp, inclusive: even })
+            FullDecoded::Finite(Decoded {
+                mant,
+                minus: T::Mant::ONE,
+                plus: T::Mant::ONE,
+                exp,
+                inclusive: even,
+            })
         }
         FpCategory::Normal => {
             let minnorm = <T as DecodableFloat>::min_pos_norm_value().integer_decode();
@@ -79,8 +123,8 @@ pub fn decode<T: DecodableFloat>(v: T) -> (/*negative?*/ bool, FullDecoded) {
                 // where maxmant = minnormmant * 2 - 1
                 FullDecoded::Finite(Decoded {
                     mant: mant << 2,
-                    minus: 1,
-                    plus: 2,
+                    minus: T::Mant::ONE,
+                    plus: T::Mant::from(2),
                     exp: exp - 2,
                     inclusive: even,
                 })
@@ -88,8 +132,8 @@ pub fn decode<T: DecodableFloat>(v: T) -> (/*negative?*/ bool, FullDecoded) {
                 // neighbors: (mant - 1, exp) -- (mant, exp) -- (mant + 1, exp)
                 FullDecoded::Finite(Decoded {
                     mant: mant << 1,
-                    minus: 1,
-                    plus: 1,
+                    minus: T::Mant::ONE,
+                    plus: T::Mant::ONE,
                     exp: exp - 1,
                     inclusive: even,
                 })
@@ -125,6 +125,7 @@
 pub use self::decoder::{decode, DecodableFloat, Decoded, FullDecoded};
 use super::fmt::{Formatted, Part};
 use crate::mem::MaybeUninit;
+use crate::num::dec2flt::float::Mantissa;
 
 pub mod decoder;
 pub mod estimator;
@@ -140,6 +141,9 @@ pub mod strategy {
 /// It is a bit non-trivial to derive, but this is one plus the maximal number of
 /// significant decimal digits from formatting algorithms with the shortest result.
 /// The exact formula is `ceil(# bits in mantissa * log_10 2 + 1)`.
+///
+/// This is enough for `f64` and narrower types; `f128` needs
+/// [`DecodableFloat::MAX_SIG_DIGITS`] digits.
 pub const MAX_SIG_DIGITS: usize = 17;
 
 /// When `d` contains decimal digits, increase the last digit and propagate carry.
@@ -306,7 +310,7 @@ pub enum Sign {
 
 /// Returns the static byte string corresponding to the sign to be formatted.
 /// It can be either `""`, `"+"` or `"-"`.
-fn determine_sign(sign: Sign, decoded: &FullDecoded, negative: bool) -> &'static str {
+fn determine_sign<M: Copy>(sign: Sign, decoded: &FullDecoded<M>, negative: bool) -> &'static str {
     match (*decoded, sign) {
         (FullDecoded::Nan, _) => "",
         (_, Sign::Minus) => {
@@ -342,7 +346,7 @@ fn determine_sign(sign: Sign, decoded: &FullDecoded, negative: bool) -> &'static
 /// additional zeroes after rendered digits. Thus `frac_digits` of 0 means that
 /// it will only print given digits and nothing else.
 ///
-/// The byte buffer should be at least `MAX_SIG_DIGITS` bytes long.
+/// The byte buffer should be at least `T::MAX_SIG_DIGITS` bytes long.
 /// There should be at least 4 parts available, due to the worst case like
 /// `[+][0.][0000][2][0000]` with `frac_digits = 10`.
 pub fn to_shortest_str<'a, T, F>(
@@ -355,10 +359,10 @@ pub fn to_shortest_str<'a, T, F>(
 ) -> Formatted<'a>
 where
     T: DecodableFloat,
-    F: FnMut(&Decoded, &'a mut [MaybeUninit<u8>]) -> (&'a [u8], i16),
+    F: FnMut(&Decoded<T::Mant>, &'a mut [MaybeUninit<u8>]) -> (&'a [u8], i16),
 {
     assert!(parts.len() >= 4);
-    assert!(buf.len() >= MAX_SIG_DIGITS);
+    assert!(buf.len() >= T::MAX_SIG_DIGITS);
 
     let (negative, full_decoded) = decode(v);
     let sign = determine_sign(sign, &full_decoded, negative);
@@ -416,7 +420,7 @@ pub fn to_shortest_str<'a, T, F>(
 /// instead of the actual `v`! Thus any printed exponent in the exponential form
 /// cannot be in this range, avoiding any confusion.
 ///
-/// The byte buffer should be at least `MAX_SIG_DIGITS` bytes long.
+/// The byte buffer should be at least `T::MAX_SIG_DIGITS` bytes long.
 /// There should be at least 6 parts available, due to the worst case like
 /// `[+][1][.][2345][e][-][6]`.
 pub fn to_shortest_exp_str<'a, T, F>(
@@ -430,10 +434,10 @@ pub fn to_shortest_exp_str<'a, T, F>(
 ) -> Formatted<'a>
 where
     T: DecodableFloat,
-    F: FnMut(&Decoded, &'a mut [MaybeUninit<u8>]) -> (&'a [u8], i16),
+    F: FnMut(&Decoded<T::Mant>, &'a mut [MaybeUninit<u8>]) -> (&'a [u8], i16),
 {
     assert!(parts.len() >= 6);
-    assert!(buf.len() >= MAX_SIG_DIGITS);
+    assert!(buf.len() >= T::MAX_SIG_DIGITS);
     assert!(dec_bounds.0 <= dec_bounds.1);
 
     let (negative, full_decoded) = decode(v);
@@ -491,8 +495,12 @@ pub fn to_shortest_exp_str<'a, T, F>(
 /// allocate all the buffer beforehand. Consequently, for any given arguments,
 /// 826 bytes of buffer should be sufficient for `f64`. Compare this with
 /// the actual number for the worst case: 770 bytes (when `exp = -1074`).
-fn estimate_max_buf_len(exp: i16) -> usize {
-    21 + ((if exp < 0 { -12 } else { 5 } * exp as i32) as usize >> 4)
+///
+/// The `u128` mantissa of `f128` needs `ceil(log_10 (2^128 - 1)) = 39` digits
+/// instead of 20, so the bound grows to 12,413 bytes (when `exp = -16496`).
+fn estimate_max_buf_len<M: Mantissa>(exp: i16) -> usize {
+    let mant_len = if M::BITS > u64::BITS { 41 } else { 21 };
+    mant_len + ((if exp < 0 { -12 } else { 5 } * exp as i32) as usize >> 4)
 }
 
 /// Formats given floating point number into the exponential form with
@@ -522,7 +530,7 @@ pub fn to_exact_exp_str<'a, T, F>(
 ) -> Formatted<'a>
 where
     T: DecodableFloat,
-    F: FnMut(&Decoded, &'a mut [MaybeUninit<u8>], i16) -> (&'a [u8], i16),
+    F: FnMut(&Decoded<T::Mant>, &'a mut [MaybeUninit<u8>], i16) -> (&'a [u8], i16),
 {
     assert!(parts.len() >= 6);
     assert!(ndigits > 0);
@@ -561,7 +569,7 @@ pub fn to_exact_exp_str<'a, T, F>(
             }
         }
         FullDecoded::Finite(ref decoded) => {
-            let maxlen = estimate_max_buf_len(decoded.exp);
+            let maxlen = estimate_max_buf_len::<T::Mant>(decoded.exp);
             assert!(buf.len() >= ndigits || buf.len() >= maxlen);
 
             let trunc = if ndigits < maxlen { ndigits } else { maxlen };
@@ -597,7 +605,7 @@ pub fn to_exact_fixed_str<'a, T, F>(
 ) -> Formatted<'a>
 where
     T: DecodableFloat,
-    F: FnMut(&Decoded, &'a mut [MaybeUninit<u8>], i16) -> (&'a [u8], i16),
+    F: FnMut(&Decoded<T::Mant>, &'a mut [MaybeUninit<u8>], i16) -> (&'a [u8], i16),
 {
     assert!(parts.len() >= 4);
 
@@ -634,7 +642,7 @@ pub fn to_exact_fixed_str<'a, T, F>(
             }
         }
         FullDecoded::Finite(ref decoded) => {
-            let maxlen = estimate_max_buf_len(decoded.exp);
+            let maxlen = estimate_max_buf_len::<T::Mant>(decoded.exp);
             assert!(buf.len() >= maxlen);
 
             // it *is* possible that `frac_digits` is ridiculously large.
@@ -6,7 +6,8 @@
 
 use crate::cmp::Ordering;
 use crate::mem::MaybeUninit;
-use crate::num::bignum::{Big32x40 as Big, Digit32 as Digit};
+use crate::num::bignum::{Big32x40, Big32x530, Digit32 as Digit};
+use crate::num::dec2flt::float::Mantissa;
 use crate::num::flt2dec::estimator::estimate_scaling_factor;
 use crate::num::flt2dec::{round_up, Decoded, MAX_SIG_DIGITS};
 
@@ -29,9 +30,67 @@
     0x55bc28f2, 0x80dcc7f7, 0xf46eeddc, 0x5fdcefce, 0x553f7,
 ];
 
+/// The bignum operations used by Dragon.
+///
+/// `f128` values need a far larger bignum than the other types, so the algorithms are generic
+/// over the bignum type, and only `f128` values use the large one.
 #[doc(hidden)]
-pub fn mul_pow10(x: &mut Big, n: usize) -> &mut Big {
-    debug_assert!(n < 512);
+pub trait Big: Clone + Ord {
+    fn from_small(v: Digit) -> Self;
+    fn from_u128(v: u128) -> Self;
+    fn is_zero(&self) -> bool;
+    fn add(&mut self, other: &Self) -> &mut Self;
+    fn sub(&mut self, other: &Self) -> &mut Self;
+    fn mul_small(&mut self, other: Digit) -> &mut Self;
+    fn mul_pow2(&mut self, bits: usize) -> &mut Self;
+    fn mul_digits(&mut self, other: &[Digit]) -> &mut Self;
+    fn div_rem_small(&mut self, other: Digit) -> (&mut Self, Digit);
+}
+
+macro_rules! impl_big {
+    ($($name:ident)*) => {
+        $(impl Big for $name {
+            fn from_small(v: Digit) -> Self {
+                $name::from_small(v)
+            }
+            fn from_u128(v: u128) -> Self {
+                $name::from_u128(v)
+            }
+            fn is_zero(&self) -> bool {
+                $name::is_zero(self)
+            }
+            fn add(&mut self, other: &Self) -> &mut Self {
+                $name::add(self, other)
+            }
+            fn sub(&mut self, other: &Self) -> &mut Self {
+                $name::sub(self, other)
+            }
+            fn mul_small(&mut self, other: Digit) -> &mut Self {
+                $name::mul_small(self, other)
+            }
+            fn mul_pow2(&mut self, bits: usize) -> &mut Self {
+                $name::mul_pow2(self, bits)
+            }
+            fn mul_digits(&mut self, other: &[Digit]) -> &mut Self {
+                $name::mul_digits(self, other)
+            }
+            fn div_rem_small(&mut self, other: Digit) -> (&mut Self, Digit) {
+                $name::div_rem_small(self, other)
+            }
+        })*
+    };
+}
+
+impl_big! { Big32x40 Big32x530 }
+
+#[doc(hidden)]
+pub fn mul_pow10<B: Big>(x: &mut B, mut n: usize) -> &mut B {
+    // only `f128` needs powers beyond 10^511
+    while n >= 512 {
+        x.mul_digits(&POW10TO256);
+        x.mul_digits(&POW10TO256);
+        n -= 512;
+    }
     if n & 7 != 0 {
         x.mul_small(POW10[n & 7]);
     }
@@ -56,7 +115,7 @@ pub fn mul_pow10(x: &mut Big, n: usize) -> &mut Big {
     x
 }
 
-fn div_2pow10(x: &mut Big, mut n: usize) -> &mut Big {
+fn div_2pow10<B: Big>(x: &mut B, mut n: usize) -> &mut B {
     let largest = POW10.len() - 1;
     while n > largest {
         x.div_rem_small(POW10[largest]);
@@ -67,13 +126,13 @@ fn div_2pow10(x: &mut Big, mut n: usize) -> &mut Big {
 }
 
 // only usable when `x < 16 * scale`; `scaleN` should be `scale.mul_small(N)`
-fn div_rem_upto_16<'a>(
-    x: &'a mut Big,
-    scale: &Big,
-    scale2: &Big,
-    scale4: &Big,
-    scale8: &Big,
-) -> (u8, &'a mut Big) {
+fn div_rem_upto_16<'a, B: Big>(
+    x: &'a mut B,
+    scale: &B,
+    scale2: &B,
+    scale4: &B,
+    scale8: &B,
+) -> (u8, &'a mut B) {
     let mut d = 0;
     if *x >= *scale8 {
         x.sub(scale8);
@@ -96,8 +155,22 @@ fn div_rem_upto_16<'a>(
 }
 
 /// The shortest mode implementation for Dragon.
-pub fn format_shortest<'a>(
-    d: &Decoded,
+pub fn format_shortest<'a, M: Mantissa>(
+    d: &Decoded<M>,
+    buf: &'a mut [MaybeUninit<u8>],
+) -> (/*digits*/ &'a [u8], /*exp*/ i16) {
+    if M::BITS <= u64::BITS {
+        format_shortest_with::<Big32x40, M>(d, buf)
+    } else {
+        format_shortest_with::<Big32x530, M>(d, buf)
+    }
+}
+
+// Not inlined, so that formatting values in range of `f64` doesn't use the stack space
+// of the `f128` bignums.
+#[inline(never)]
+fn format_shortest_with<'a, B: Big, M: Mantissa>(
+    d: &Decoded<M>,
     buf: &'a mut [MaybeUninit<u8>],
 ) -> (/*digits*/ &'a [u8], /*exp*/ i16) {
     // the number `v` to format is known to be:
@@ -112,9 +185,9 @@ pub fn format_shortest<'a>(
     // `high = (mant + plus) * 2^exp` will map to this exact floating point number,
     // with bounds included when the original mantissa was even (i.e., `!mant_was_odd`).
 
-    assert!(d.mant > 0);
-    assert!(d.minus > 0);
-    assert!(d.plus > 0);
+    assert!(d.mant > M::ZERO);
+    assert!(d.minus > M::ZERO);
+    assert!(d.plus > M::ZERO);
     assert!(d.mant.checked_add(d.plus).is_some());
     assert!(d.mant.checked_sub(d.minus).is_some());
     assert!(buf.len() >= MAX_SIG_DIGITS);
@@ -130,10 +203,10 @@ pub fn format_shortest<'a>(
     // - `v = mant / scale`
     // - `low = (mant - minus) / scale`
     // - `high = (mant + plus) / scale`
-    let mut mant = Big::from_u64(d.mant);
-    let mut minus = Big::from_u64(d.minus);
-    let mut plus = Big::from_u64(d.plus);
-    let mut scale = Big::from_small(1);
+    let mut mant = B::from_u128(d.mant.into());
+    let mut minus = B::from_u128(d.minus.into());
+    let mut plus = B::from_u128(d.plus.into());
+    let mut scale = B::from_small(1);
     if d.exp < 0 {
         scale.mul_pow2(-d.exp as usize);
     } else {
@@ -256,14 +329,28 @@ pub fn format_shortest<'a>(
 }
 
 /// The exact and fixed mode implementation for Dragon.
-pub fn format_exact<'a>(
-    d: &Decoded,
+pub fn format_exact<'a, M: Mantissa>(
+    d: &Decoded<M>,
+    buf: &'a mut [MaybeUninit<u8>],
+    limit: i16,
+) -> (/*digits*/ &'a [u8], /*exp*/ i16) {
+    if M::BITS <= u64::BITS {
+        format_exact_with::<Big32x40, M>(d, buf, limit)
+    } else {
+        format_exact_with::<Big32x530, M>(d, buf, limit)
+    }
+}
+
+// Not inlined for the same reason as `format_shortest_with`.
+#[inline(never)]
+fn format_exact_with<'a, B: Big, M: Mantissa>(
+    d: &Decoded<M>,
     buf: &'a mut [MaybeUninit<u8>],
     limit: i16,
 ) -> (/*digits*/ &'a [u8], /*exp*/ i16) {
-    assert!(d.mant > 0);
-    assert!(d.minus > 0);
-    assert!(d.plus > 0);
+    assert!(d.mant > M::ZERO);
+    assert!(d.minus > M::ZERO);
+    assert!(d.plus > M::ZERO);
     assert!(d.mant.checked_add(d.plus).is_some());
     assert!(d.mant.checked_sub(d.minus).is_some());
 
@@ -271,8 +358,8 @@ pub fn format_exact<'a>(
     let mut k = estimate_scaling_factor(d.mant, d.exp);
 
     // `v = mant / scale`.
-    let mut mant = Big::from_u64(d.mant);
-    let mut scale = Big::from_small(1);
+    let mut mant = B::from_u128(d.mant.into());
+    let mut scale = B::from_small(1);
     if d.exp < 0 {
         scale.mul_pow2(-d.exp as usize);
     } else {
@@ -6,6 +6,7 @@
 //!   accurately with integers. SIGPLAN Not. 45, 6 (June 2010), 233-243.
 
 use crate::mem::MaybeUninit;
+use crate::num::dec2flt::float::Mantissa;
 use crate::num::diy_float::Fp;
 use crate::num::flt2dec::{round_up, Decoded, MAX_SIG_DIGITS};
 
@@ -450,18 +451,22 @@ fn round_and_weed(
 
 /// The shortest mode implementation for Grisu with Dragon fallback.
 ///
-/// This should be used for most cases.
-pub fn format_shortest<'a>(
-    d: &Decoded,
+/// This should be used for most cases. Values of types wider than `f64`
+/// are out of the range of Grisu and always use Dragon.
+pub fn format_shortest<'a, M: Mantissa>(
+    d: &Decoded<M>,
     buf: &'a mut [MaybeUninit<u8>],
 ) -> (/*digits*/ &'a [u8], /*exp*/ i16) {
     use crate::num::flt2dec::strategy::dragon::format_shortest as fallback;
+    let Some(d) = d.to_u64() else {
+        return fallback(d, buf);
+    };
     // SAFETY: The borrow checker is not smart enough to let us use `buf`
     // in the second branch, so we launder the lifetime here. But we only re-use
     // `buf` if `format_shortest_opt` returned `None` so this is okay.
-    match format_shortest_opt(d, unsafe { &mut *(buf as *mut _) }) {
+    match format_shortest_opt(&d, unsafe { &mut *(buf as *mut _) }) {
         Some(ret) => ret,
-        None => fallback(d, buf),
+        None => fallback(&d, buf),
     }
 }
 
@@ -759,18 +764,22 @@ unsafe fn possibly_round(
 
 /// The exact and fixed mode implementation for Grisu with Dragon fallback.
 ///
-/// This should be used for most cases.
-pub fn format_exact<'a>(
-    d: &Decoded,
+/// This should be used for most cases. Values of types wider than `f64`
+/// are out of the range of Grisu and always use Dragon.
+pub fn format_exact<'a, M: Mantissa>(
+    d: &Decoded<M>,
     buf: &'a mut [MaybeUninit<u8>],
     limit: i16,
 ) -> (/*digits*/ &'a [u8], /*exp*/ i16) {
     use crate::num::flt2dec::strategy::dragon::format_exact as fallback;
+    let Some(d) = d.to_u64() else {
+        return fallback(d, buf, limit);
+    };
     // SAFETY: The borrow checker is not smart enough to let us use `buf`
     // in the second branch, so we launder the lifetime here. But we only re-use
     // `buf` if `format_exact_opt` returned `None` so this is okay.
-    match format_exact_opt(d, unsafe { &mut *(buf as *mut _) }, limit) {
+    match format_exact_opt(&d, unsafe { &mut *(buf as *mut _) }, limit) {
         Some(ret) => ret,
-        None => fallback(d, buf, limit),
+        None => fallback(&d, buf, limit),
     }
 }
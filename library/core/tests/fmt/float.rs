@@ -170,6 +170,51 @@ fn test_format_f32_rounds_ties_to_even() {
     assert_eq!("-1.28E2", format!("{:.2E}", -128.5f32));
 }
 
+// Formatting `f16` and `f128` only needs integer arithmetic, but not every target can compile these
+// types yet.
+#[test]
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+fn test_format_f16() {
+    assert_eq!("1.5", format!("{}", 1.5f16));
+    assert_eq!("0.1", format!("{:?}", 0.1f16));
+    assert_eq!("0.100", format!("{:.3}", 0.1f16));
+    assert_eq!("0.0999755859375", format!("{:.13}", 0.1f16));
+    assert_eq!("2", format!("{:.0}", 2.5f16));
+    assert_eq!("65500", format!("{}", f16::MAX));
+    assert_eq!("65504", format!("{:.0}", f16::MAX));
+    assert_eq!("65500.0", format!("{:?}", f16::MAX));
+    assert_eq!("6.55e4", format!("{:e}", f16::MAX));
+    assert_eq!("6e-8", format!("{:?}", f16::from_bits(0x0001)));
+    assert_eq!("-0.0", format!("{:?}", f16::from_bits(0x8000)));
+    assert_eq!("inf", format!("{}", f16::INFINITY));
+    assert_eq!("NaN", format!("{:?}", f16::NAN));
+}
+
+#[test]
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+fn test_format_f128() {
+    assert_eq!("1.5", format!("{}", 1.5f128));
+    assert_eq!("0.1", format!("{:?}", 0.1f128));
+    assert_eq!("0.1000000000000000000000000000000000048148", format!("{:.40}", 0.1f128));
+    assert_eq!("1e16", format!("{:?}", 1e16f128));
+    assert_eq!("1000000000000000.0", format!("{:?}", 1e15f128));
+    assert_eq!("1.189731495357231765085759326628007e4932", format!("{:e}", f128::MAX));
+    assert_eq!("3.3621031431120935062626778173217526e-4932", format!("{:?}", f128::MIN_POSITIVE));
+    assert_eq!("6e-4966", format!("{:?}", f128::from_bits(1)));
+    assert_eq!("6.47518e-4966", format!("{:.5e}", f128::from_bits(1)));
+    assert_eq!("-inf", format!("{}", f128::NEG_INFINITY));
+
+    // `(2^113 - 1) * 2^24` has more digits than any `u64` mantissa.
+    let wide = 174224571863520493293247799005065307488256f128;
+    assert_eq!("174224571863520493293247799005065307488256", format!("{:.0}", wide));
+    assert_eq!("1.7422457186352049329324779900506530748826e41", format!("{:.40e}", wide));
+
+    let max = format!("{:.0}", f128::MAX);
+    assert_eq!(max.len(), 4933);
+    assert!(max.starts_with("11897314953572317650"));
+    assert!(max.ends_with("3137363968"));
+}
+
 fn is_exponential(s: &str) -> bool {
     s.contains("e") || s.contains("E")
 }
@@ -31,3 +31,34 @@ fn test_f64_integer_decode() {
     let (nan_m, nan_e, _nan_s) = f64::NAN.integer_decode();
     assert_eq!((nan_m, nan_e), (6755399441055744, 972));
 }
+
+// Like the other tests of `f16` and `f128`, these only run on the targets known to compile them.
+#[test]
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+fn test_f16_integer_decode() {
+    assert_eq!(1.5f16.integer_decode(), (1536, -10, 1));
+    assert_eq!(f16::from_bits(0xe539).integer_decode(), (1337, 0, -1));
+    assert_eq!(0f16.integer_decode(), (0, -25, 1));
+    assert_eq!(f16::from_bits(0x8000).integer_decode(), (0, -25, -1));
+    assert_eq!(f16::INFINITY.integer_decode(), (1024, 6, 1));
+    assert_eq!(f16::NEG_INFINITY.integer_decode(), (1024, 6, -1));
+
+    let (nan_m, nan_e, _nan_s) = f16::NAN.integer_decode();
+    assert_eq!((nan_m, nan_e), (1536, 6));
+}
+
+#[test]
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+fn test_f128_integer_decode() {
+    assert_eq!(1.5f128.integer_decode(), (7788445287802241442795744493830144, -112, 1));
+    assert_eq!(0f128.integer_decode(), (0, -16495, 1));
+    assert_eq!(f128::from_bits(1 << 127).integer_decode(), (0, -16495, -1));
+    assert_eq!(f128::INFINITY.integer_decode(), (5192296858534827628530496329220096, 16272, 1));
+    assert_eq!(
+        f128::NEG_INFINITY.integer_decode(),
+        (5192296858534827628530496329220096, 16272, -1)
+    );
+
+    let (nan_m, nan_e, _nan_s) = f128::NAN.integer_decode();
+    assert_eq!((nan_m, nan_e), (7788445287802241442795744493830144, 16272));
+}
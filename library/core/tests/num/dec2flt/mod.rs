@@ -127,3 +127,50 @@ fn massive_exponent() {
     assert_eq!(format!("1e-{max}000").parse(), Ok(0.0));
     assert_eq!(format!("1e{max}000").parse(), Ok(f64::INFINITY));
 }
+
+// `f16` and `f128` are compared by their bits, since their arithmetic isn't supported by every
+// target yet. The types themselves are only tested on the targets known to compile them.
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+macro_rules! test_bits {
+    ($t:ty, $s:expr, $bits:expr) => {{
+        assert_eq!($s.parse::<$t>().map(<$t>::to_bits), Ok($bits));
+    }};
+}
+
+#[test]
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+fn parse_f16() {
+    test_bits!(f16, "1.5", 0x3e00);
+    test_bits!(f16, "-2.5", 0xc100);
+    test_bits!(f16, "0.1", 0x2e66);
+    test_bits!(f16, "65504", 0x7bff);
+    test_bits!(f16, "65519", 0x7bff);
+    test_bits!(f16, "65520", 0x7c00);
+    test_bits!(f16, "1e5", 0x7c00);
+    test_bits!(f16, "6e-8", 0x0001);
+    test_bits!(f16, "3e-8", 0x0001);
+    test_bits!(f16, "2.9e-8", 0x0000);
+    test_bits!(f16, "-0", 0x8000);
+    test_bits!(f16, "-inf", 0xfc00);
+    test_bits!(f16, "NaN", f16::NAN.to_bits());
+}
+
+#[test]
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+fn parse_f128() {
+    test_bits!(f128, "1.5", 0x3fff8000000000000000000000000000);
+    test_bits!(f128, "-2.5", 0xc0004000000000000000000000000000);
+    test_bits!(f128, "0.1", 0x3ffb999999999999999999999999999a);
+    test_bits!(f128, "1e-4000", 0x0c17387ae70c9e700b8049732d11a23d);
+    test_bits!(f128, "1e4932", 0x7ffeae596552b8fded99d037e3d04b75);
+    test_bits!(
+        f128,
+        "1.18973149535723176508575932662800702e4932",
+        0x7ffeffffffffffffffffffffffffffff
+    );
+    test_bits!(f128, "1.2e4932", 0x7fff0000000000000000000000000000);
+    test_bits!(f128, "6.5e-4966", 0x00000000000000000000000000000001);
+    test_bits!(f128, "3e-4966", 0x00000000000000000000000000000000);
+    test_bits!(f128, "-inf", 0xffff0000000000000000000000000000);
+    test_bits!(f128, "NaN", f128::NAN.to_bits());
+}
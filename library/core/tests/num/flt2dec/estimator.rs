@@ -1,4 +1,9 @@
-use core::num::flt2dec::estimator::*;
+use core::num::flt2dec::estimator;
+
+// The mantissas below are all in the range of `f64`.
+fn estimate_scaling_factor(mant: u64, exp: i16) -> i16 {
+    estimator::estimate_scaling_factor(mant, exp)
+}
 
 #[test]
 fn test_estimate_scaling_factor() {
@@ -13,7 +13,7 @@ mod strategy {
 }
 mod random;
 
-pub fn decode_finite<T: DecodableFloat>(v: T) -> Decoded {
+pub fn decode_finite<T: DecodableFloat>(v: T) -> Decoded<T::Mant> {
     match decode(v).1 {
         FullDecoded::Finite(decoded) => decoded,
         full_decoded => panic!("expected finite, got {full_decoded:?} instead"),
@@ -30,7 +30,7 @@ macro_rules! check_shortest {
     ($f:ident{$($k:ident: $v:expr),+} => $buf:expr, $exp:expr) => (
         check_shortest!($f{$($k: $v),+} => $buf, $exp;
                         "shortest mismatch for {v:?}: actual {actual:?}, expected {expected:?}",
-                        v = Decoded { $($k: $v),+ })
+                        v = Decoded::<u64> { $($k: $v),+ })
     );
 
     ($f:ident($v:expr) => $buf:expr, $exp:expr; $fmt:expr, $($key:ident = $val:expr),*) => ({
@@ -90,7 +90,7 @@ fn ldexp_f64(a: f64, b: i32) -> f64 {
 
 fn check_exact<F, T>(mut f: F, v: T, vstr: &str, expected: &[u8], expectedk: i16)
 where
-    T: DecodableFloat,
+    T: DecodableFloat<Mant = u64>,
     F: for<'a> FnMut(&Decoded, &'a mut [MaybeUninit<u8>], i16) -> (&'a [u8], i16),
 {
     // use a large enough buffer
@@ -170,7 +170,7 @@ fn check_exact<F, T>(mut f: F, v: T, vstr: &str, expected: &[u8], expectedk: i16
     }
 }
 
-trait TestableFloat: DecodableFloat + fmt::Display {
+trait TestableFloat: DecodableFloat<Mant = u64> + fmt::Display {
     /// Returns `x * 2^exp`. Almost same to `std::{f32,f64}::ldexp`.
     /// This is used for testing.
     fn ldexpi(f: i64, exp: isize) -> Self;
@@ -500,7 +500,7 @@ pub fn to_shortest_str_test<F>(mut f_: F)
 
     fn to_string<T, F>(f: &mut F, v: T, sign: Sign, frac_digits: usize) -> String
     where
-        T: DecodableFloat,
+        T: DecodableFloat<Mant = u64>,
         F: for<'a> FnMut(&Decoded, &'a mut [MaybeUninit<u8>]) -> (&'a [u8], i16),
     {
         to_string_with_parts(|buf, parts| {
@@ -588,7 +588,7 @@ pub fn to_shortest_exp_str_test<F>(mut f_: F)
 
     fn to_string<T, F>(f: &mut F, v: T, sign: Sign, exp_bounds: (i16, i16), upper: bool) -> String
     where
-        T: DecodableFloat,
+        T: DecodableFloat<Mant = u64>,
         F: for<'a> FnMut(&Decoded, &'a mut [MaybeUninit<u8>]) -> (&'a [u8], i16),
     {
         to_string_with_parts(|buf, parts| {
@@ -687,7 +687,7 @@ pub fn to_exact_exp_str_test<F>(mut f_: F)
 
     fn to_string<T, F>(f: &mut F, v: T, sign: Sign, ndigits: usize, upper: bool) -> String
     where
-        T: DecodableFloat,
+        T: DecodableFloat<Mant = u64>,
         F: for<'a> FnMut(&Decoded, &'a mut [MaybeUninit<u8>], i16) -> (&'a [u8], i16),
     {
         to_string_with_parts(|buf, parts| {
@@ -943,7 +943,7 @@ pub fn to_exact_fixed_str_test<F>(mut f_: F)
 
     fn to_string<T, F>(f: &mut F, v: T, sign: Sign, frac_digits: usize) -> String
     where
-        T: DecodableFloat,
+        T: DecodableFloat<Mant = u64>,
         F: for<'a> FnMut(&Decoded, &'a mut [MaybeUninit<u8>], i16) -> (&'a [u8], i16),
     {
         to_string_with_parts(|buf, parts| {
@@ -7,7 +7,7 @@
 
 use rand::distributions::{Distribution, Uniform};
 
-pub fn decode_finite<T: DecodableFloat>(v: T) -> Decoded {
+pub fn decode_finite<T: DecodableFloat>(v: T) -> Decoded<T::Mant> {
     match decode(v).1 {
         FullDecoded::Finite(decoded) => decoded,
         full_decoded => panic!("expected finite, got {full_decoded:?} instead"),
@@ -23,6 +23,8 @@ pub trait Sealed {}
 
 use crate::sealed::Sealed;
 
+mod math;
+
 /// This trait provides a possibly-temporary implementation of float functions
 /// that may, in the absence of hardware support, canonicalize to calling an
 /// operating system's `math.h` dynamically-loaded library (also known as a
@@ -42,6 +44,13 @@ pub trait Sealed {}
 /// when either the compiler or its supporting runtime functions are improved.
 /// For now this trait is available to permit experimentation with SIMD float
 /// operations that may lack hardware support, such as `mul_add`.
+///
+/// The transcendental functions (`sin`, `exp`, `ln`, and so on) don't rely on
+/// `math.h`: they are implemented with portable vector operations, so every
+/// element is computed with the same instructions. Their accuracy is documented
+/// in units in the last place (ULP) of the correctly rounded result. The
+/// trigonometric functions fall back to the scalar functions for elements with
+/// very large magnitude (at least `4.2e8` for `f32` and `1647099` for `f64`).
 pub trait StdFloat: Sealed + Sized {
     /// Elementwise fused multiply-add. Computes `(self * a) + b` with only one rounding error,
     /// yielding a more accurate result than an unfused multiply-add.
@@ -66,31 +75,65 @@ fn sqrt(self) -> Self {
 
     /// Produces a vector where every element has the sine of the value
     /// in the equivalently-indexed element in `self`.
+    ///
+    /// Accurate to 1 ULP.
     #[must_use = "method returns a new vector and does not mutate the original value"]
     fn sin(self) -> Self;
 
     /// Produces a vector where every element has the cosine of the value
     /// in the equivalently-indexed element in `self`.
+    ///
+    /// Accurate to 1 ULP.
     #[must_use = "method returns a new vector and does not mutate the original value"]
     fn cos(self) -> Self;
 
+    /// Produces a vector where every element has the tangent of the value
+    /// in the equivalently-indexed element in `self`.
+    ///
+    /// Accurate to 1 ULP for `f32` and 3 ULP for `f64`.
+    #[must_use = "method returns a new vector and does not mutate the original value"]
+    fn tan(self) -> Self;
+
+    /// Produces a vector where every element has the four quadrant arctangent of the
+    /// equivalently-indexed elements in `self` (the y coordinate) and `other` (the x
+    /// coordinate), in radians.
+    ///
+    /// Accurate to 1 ULP.
+    #[must_use = "method returns a new vector and does not mutate the original value"]
+    fn atan2(self, other: Self) -> Self;
+
     /// Produces a vector where every element has the exponential (base e) of the value
     /// in the equivalently-indexed element in `self`.
+    ///
+    /// Accurate to 1 ULP.
     #[must_use = "method returns a new vector and does not mutate the original value"]
     fn exp(self) -> Self;
 
     /// Produces a vector where every element has the exponential (base 2) of the value
     /// in the equivalently-indexed element in `self`.
+    ///
+    /// Accurate to 1 ULP.
     #[must_use = "method returns a new vector and does not mutate the original value"]
     fn exp2(self) -> Self;
 
+    /// Produces a vector where every element has the value of the equivalently-indexed
+    /// element in `self` raised to the power of the equivalently-indexed element in `n`.
+    ///
+    /// Special cases follow [`f64::powf`]. Accurate to 1 ULP.
+    #[must_use = "method returns a new vector and does not mutate the original value"]
+    fn powf(self, n: Self) -> Self;
+
     /// Produces a vector where every element has the natural logarithm of the value
     /// in the equivalently-indexed element in `self`.
+    ///
+    /// Accurate to 1 ULP.
     #[must_use = "method returns a new vector and does not mutate the original value"]
     fn ln(self) -> Self;
 
     /// Produces a vector where every element has the logarithm with respect to an arbitrary
     /// in the equivalently-indexed elements in `self` and `base`.
+    ///
+    /// Accurate to 3 ULP.
     #[inline]
     #[must_use = "method returns a new vector and does not mutate the original value"]
     fn log(self, base: Self) -> Self {
@@ -99,11 +142,15 @@ fn log(self, base: Self) -> Self {
 
     /// Produces a vector where every element has the base-2 logarithm of the value
     /// in the equivalently-indexed element in `self`.
+    ///
+    /// Accurate to 1 ULP.
     #[must_use = "method returns a new vector and does not mutate the original value"]
     fn log2(self) -> Self;
 
     /// Produces a vector where every element has the base-10 logarithm of the value
     /// in the equivalently-indexed element in `self`.
+    ///
+    /// Accurate to 1 ULP for `f32` and 2 ULP for `f64`.
     #[must_use = "method returns a new vector and does not mutate the original value"]
     fn log10(self) -> Self;
 
@@ -145,9 +192,10 @@ impl<const N: usize> Sealed for Simd<f64, N> where LaneCount<N>: SupportedLaneCo
 
 macro_rules! impl_float {
     {
-        $($fn:ident: $intrinsic:ident,)*
+        $($ty:ident),*
     } => {
-        impl<const N: usize> StdFloat for Simd<f32, N>
+        $(
+        impl<const N: usize> StdFloat for Simd<$ty, N>
         where
             LaneCount<N>: SupportedLaneCount,
         {
@@ -156,52 +204,58 @@ fn fract(self) -> Self {
                 self - self.trunc()
             }
 
-            $(
             #[inline]
-            fn $fn(self) -> Self {
-                unsafe { intrinsics::$intrinsic(self) }
+            fn sin(self) -> Self {
+                math::$ty::sin(self)
             }
-            )*
-        }
 
-        impl<const N: usize> StdFloat for Simd<f64, N>
-        where
-            LaneCount<N>: SupportedLaneCount,
-        {
             #[inline]
-            fn fract(self) -> Self {
-                self - self.trunc()
+            fn cos(self) -> Self {
+                math::$ty::cos(self)
+            }
+
+            #[inline]
+            fn tan(self) -> Self {
+                math::$ty::tan(self)
+            }
+
+            #[inline]
+            fn atan2(self, other: Self) -> Self {
+                math::$ty::atan2(self, other)
+            }
+
+            #[inline]
+            fn exp(self) -> Self {
+                math::$ty::exp(self)
+            }
+
+            #[inline]
+            fn exp2(self) -> Self {
+                math::$ty::exp2(self)
+            }
+
+            #[inline]
+            fn powf(self, n: Self) -> Self {
+                math::$ty::powf(self, n)
+            }
+
+            #[inline]
+            fn ln(self) -> Self {
+                math::$ty::ln(self)
+            }
+
+            #[inline]
+            fn log2(self) -> Self {
+                math::$ty::log2(self)
             }
 
-            $(
             #[inline]
-            fn $fn(self) -> Self {
-                // https://github.com/llvm/llvm-project/issues/83729
-                #[cfg(target_arch = "aarch64")]
-                {
-                    let mut ln = Self::splat(0f64);
-                    for i in 0..N {
-                        ln[i] = self[i].$fn()
-                    }
-                    ln
-                }
-
-                #[cfg(not(target_arch = "aarch64"))]
-                {
-                    unsafe { intrinsics::$intrinsic(self) }
-                }
+            fn log10(self) -> Self {
+                math::$ty::log10(self)
             }
-            )*
         }
+        )*
     }
 }
 
-impl_float! {
-    sin: simd_fsin,
-    cos: simd_fcos,
-    exp: simd_fexp,
-    exp2: simd_fexp2,
-    ln: simd_flog,
-    log2: simd_flog2,
-    log10: simd_flog10,
-}
+impl_float! { f32, f64 }
@@ -0,0 +1,5 @@
+//! Portable implementations of the transcendental functions, built only from lane-wise
+//! arithmetic, comparisons and bit manipulation.
+
+pub(crate) mod f32;
+pub(crate) mod f64;
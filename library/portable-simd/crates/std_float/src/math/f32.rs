@@ -0,0 +1,382 @@
+//! Vectorized implementations of the `f32` math functions.
+//!
+//! The exponentials and logarithms are evaluated in `f32`. The trigonometric functions reduce
+//! their argument and evaluate their polynomials in `f64`, like musl's `sinf` and friends, and
+//! `powf` and `atan2` are computed entirely by their `f64` counterparts.
+
+use super::f64::round_int;
+use crate::simd::prelude::*;
+use crate::simd::{LaneCount, SupportedLaneCount};
+
+/// `1.5 * 2^23`. Adding and subtracting it rounds `|x| < 2^22` to the nearest integer, leaving
+/// that integer in the low bits of the intermediate sum.
+const SHIFTER: f32 = 12582912.0;
+
+#[inline]
+fn splat<const N: usize>(x: f32) -> Simd<f32, N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    Simd::splat(x)
+}
+
+#[inline]
+fn splat64<const N: usize>(x: f64) -> Simd<f64, N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    Simd::splat(x)
+}
+
+/// Evaluates the polynomial with coefficients `c`, lowest degree first, at `x`.
+#[inline]
+fn horner<const N: usize>(x: Simd<f32, N>, c: &[f32]) -> Simd<f32, N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let (last, rest) = c.split_last().unwrap();
+    rest.iter()
+        .rev()
+        .fold(splat(*last), |acc, &c| acc * x + splat(c))
+}
+
+/// Rounds `x` to the nearest integer, returned both as a float and as an integer.
+///
+/// Only valid for `|x| < 2^22`.
+#[inline]
+fn round_int32<const N: usize>(x: Simd<f32, N>) -> (Simd<f32, N>, Simd<i32, N>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let t = x + splat(SHIFTER);
+    let n = t.to_bits().cast::<i32>() - Simd::splat(SHIFTER.to_bits() as i32);
+    (t - splat(SHIFTER), n)
+}
+
+/// Computes `2^n` for `-126 <= n <= 127`.
+#[inline]
+fn pow2i<const N: usize>(n: Simd<i32, N>) -> Simd<f32, N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    Simd::from_bits(((n + Simd::splat(127)) << 23).cast())
+}
+
+/// Computes `x * 2^n` for `-252 <= n <= 254` with a single rounding, as long as `x * 2^(n / 2)`
+/// is normal.
+#[inline]
+fn scale<const N: usize>(x: Simd<f32, N>, n: Simd<i32, N>) -> Simd<f32, N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let n1 = n >> 1;
+    x * pow2i(n1) * pow2i(n - n1)
+}
+
+/// Computes `e^r` for `|r| <= ln(2) / 2` with its Taylor series.
+#[inline]
+fn exp_kernel<const N: usize>(r: Simd<f32, N>) -> Simd<f32, N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    const C: [f32; 7] = [
+        1.0 / 2.0,
+        1.0 / 6.0,
+        1.0 / 24.0,
+        1.0 / 120.0,
+        1.0 / 720.0,
+        1.0 / 5040.0,
+        1.0 / 40320.0,
+    ];
+    splat(1.0) + (r + r * r * horner(r, &C))
+}
+
+pub(crate) fn exp<const N: usize>(x: Simd<f32, N>) -> Simd<f32, N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    const LN2_HI: f32 = 6.9314575195e-01; // 0x3f317200
+    const LN2_LO: f32 = 1.4286067653e-06; // 0x35bfbe8e
+
+    // Largest argument with a finite result, and smallest one that doesn't round to zero.
+    const MAX: f32 = 88.72283172607421875; // 0x42b17217
+    const MIN: f32 = -103.972076416015625; // 0xc2cff1b5
+
+    // x = k ln(2) + r. Since `LN2_HI` has 16 significant bits, `k * LN2_HI` is exact.
+    let (kf, k) = round_int32(x * splat(core::f32::consts::LOG2_E));
+    let r = (x - kf * splat(LN2_HI)) - kf * splat(LN2_LO);
+    let y = scale(exp_kernel(r), k);
+
+    let y = x.simd_gt(splat(MAX)).select(splat(f32::INFINITY), y);
+    x.simd_lt(splat(MIN)).select(splat(0.0), y)
+}
+
+pub(crate) fn exp2<const N: usize>(x: Simd<f32, N>) -> Simd<f32, N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let (kf, k) = round_int32(x);
+    let y = scale(exp_kernel((x - kf) * splat(core::f32::consts::LN_2)), k);
+
+    let y = x.simd_ge(splat(128.0)).select(splat(f32::INFINITY), y);
+    x.simd_lt(splat(-150.0)).select(splat(0.0), y)
+}
+
+/// Splits `x` into `k` and `f` such that `x = 2^k (1 + f)` and `sqrt(2) / 2 <= 1 + f < sqrt(2)`.
+///
+/// Only valid for positive finite `x`.
+#[inline]
+fn log_reduce<const N: usize>(x: Simd<f32, N>) -> (Simd<f32, N>, Simd<f32, N>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    // Scale subnormals up by 2^25.
+    let subnormal = x.simd_lt(splat(f32::MIN_POSITIVE));
+    let x = subnormal.select(x * splat(33554432.0), x);
+    let bias = subnormal.select(Simd::splat(-127 - 25), Simd::splat(-127));
+
+    // Offset the exponent so that it is incremented for mantissas above sqrt(2).
+    let ix = x.to_bits() + Simd::splat(0x3f800000 - 0x3f3504f3);
+    let k = (ix >> 23).cast::<i32>() + bias;
+    let m = Simd::<f32, N>::from_bits((ix & Simd::splat(0x007fffff)) + Simd::splat(0x3f3504f3));
+    (k.cast(), m - splat(1.0))
+}
+
+/// Returns `hfsq` and `s * (hfsq + R)` such that `log(1 + f) = f - hfsq + s * (hfsq + R)`.
+#[inline]
+fn log_kernel<const N: usize>(f: Simd<f32, N>) -> (Simd<f32, N>, Simd<f32, N>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    const LG1: f32 = 0.66666662693; // 0xaaaaaa.0p-24
+    const LG2: f32 = 0.40000972152; // 0xccce13.0p-25
+    const LG3: f32 = 0.28498786688; // 0x91e9ee.0p-25
+    const LG4: f32 = 0.24279078841; // 0xf89e26.0p-26
+
+    let hfsq = splat(0.5) * f * f;
+    let s = f / (splat(2.0) + f);
+    let z = s * s;
+    let w = z * z;
+    let r = z * (splat(LG1) + w * splat(LG3)) + w * (splat(LG2) + w * splat(LG4));
+    (hfsq, s * (hfsq + r))
+}
+
+/// Splits `log(1 + f)` into `hi + lo`, where `hi` has only 12 significant bits.
+#[inline]
+fn log_kernel_hi_lo<const N: usize>(f: Simd<f32, N>) -> (Simd<f32, N>, Simd<f32, N>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let (hfsq, sr) = log_kernel(f);
+    let hi = Simd::from_bits((f - hfsq).to_bits() & Simd::splat(0xfffff000));
+    (hi, f - hi - hfsq + sr)
+}
+
+/// Replaces the result of a logarithm for zero, negative, infinite and NaN arguments.
+#[inline]
+fn log_special<const N: usize>(x: Simd<f32, N>, y: Simd<f32, N>) -> Simd<f32, N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let y = x.simd_eq(splat(0.0)).select(splat(f32::NEG_INFINITY), y);
+    let y = x.simd_eq(splat(f32::INFINITY)).select(x, y);
+    let y = x.simd_lt(splat(0.0)).select(splat(f32::NAN), y);
+    x.is_nan().select(x, y)
+}
+
+pub(crate) fn ln<const N: usize>(x: Simd<f32, N>) -> Simd<f32, N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    const LN2_HI: f32 = 6.9313812256e-01; // 0x3f317180
+    const LN2_LO: f32 = 9.0580006145e-06; // 0x3717f7d1
+
+    let (k, f) = log_reduce(x);
+    let (hfsq, sr) = log_kernel(f);
+    let y = sr + k * splat(LN2_LO) - hfsq + f + k * splat(LN2_HI);
+    log_special(x, y)
+}
+
+pub(crate) fn log2<const N: usize>(x: Simd<f32, N>) -> Simd<f32, N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    const IVLN2HI: f32 = 1.4428710938e+00; // 0x3fb8b000
+    const IVLN2LO: f32 = -1.7605285393e-04; // 0xb9389ad4
+
+    let (k, f) = log_reduce(x);
+    let (hi, lo) = log_kernel_hi_lo(f);
+    let y = (lo + hi) * splat(IVLN2LO) + lo * splat(IVLN2HI) + hi * splat(IVLN2HI) + k;
+    log_special(x, y)
+}
+
+pub(crate) fn log10<const N: usize>(x: Simd<f32, N>) -> Simd<f32, N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    const IVLN10HI: f32 = 4.3432617188e-01; // 0x3ede6000
+    const IVLN10LO: f32 = -3.1689971365e-05; // 0xb804ead9
+    const LOG10_2HI: f32 = 3.0102920532e-01; // 0x3e9a2080
+    const LOG10_2LO: f32 = 7.9034151668e-07; // 0x355427db
+
+    let (k, f) = log_reduce(x);
+    let (hi, lo) = log_kernel_hi_lo(f);
+    let y = k * splat(LOG10_2LO)
+        + (lo + hi) * splat(IVLN10LO)
+        + lo * splat(IVLN10HI)
+        + hi * splat(IVLN10HI)
+        + k * splat(LOG10_2HI);
+    log_special(x, y)
+}
+
+/// Arguments at least this large are passed to the scalar functions instead of `rem_pio2`.
+const REDUCE_MAX: f32 = 4.2e8; // just below 2^28 * pi / 2
+
+/// Reduces `x` to `y` with `|y| <= pi / 4` and returns it with `n` such that `x = n pi / 2 + y`.
+///
+/// Only valid for `|x| < REDUCE_MAX`, so that `n` has at most 28 bits and multiplying it by the
+/// 25-bit `PIO2_1` is exact.
+#[inline]
+fn rem_pio2<const N: usize>(x: Simd<f64, N>) -> (Simd<f64, N>, Simd<i64, N>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    const INVPIO2: f64 = 6.36619772367581382433e-01; // 0x3FE45F30, 0x6DC9C883
+    const PIO2_1: f64 = 1.57079631090164184570e+00; // 0x3FF921FB, 0x50000000
+    const PIO2_1T: f64 = 1.58932547735281966916e-08; // 0x3E5110b4, 0x611A6263
+
+    let (nf, n) = round_int(x * splat64(INVPIO2));
+    (x - nf * splat64(PIO2_1) - nf * splat64(PIO2_1T), n)
+}
+
+/// Computes `sin(x)` for `|x| <= pi / 4`.
+#[inline]
+fn sin_kernel<const N: usize>(x: Simd<f64, N>) -> Simd<f64, N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    const S1: f64 = -0.16666666641626524; // -0x15555554cbac77.0p-55
+    const S2: f64 = 0.008333329385889463; // 0x111110896efbb2.0p-59
+    const S3: f64 = -0.00019839334836096632; // -0x1a00f9e2cae774.0p-65
+    const S4: f64 = 2.718311493989822e-06; // 0x16cd878c3b46a7.0p-71
+
+    let z = x * x;
+    let w = z * z;
+    let r = splat64(S3) + z * splat64(S4);
+    let s = z * x;
+    (x + s * (splat64(S1) + z * splat64(S2))) + s * w * r
+}
+
+/// Computes `cos(x)` for `|x| <= pi / 4`.
+#[inline]
+fn cos_kernel<const N: usize>(x: Simd<f64, N>) -> Simd<f64, N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    const C0: f64 = -0.499999997251031; // -0x1ffffffd0c5e81.0p-54
+    const C1: f64 = 0.04166662332373906; // 0x155553e1053a42.0p-57
+    const C2: f64 = -0.001388676377460993; // -0x16c087e80f1e27.0p-62
+    const C3: f64 = 2.439044879627741e-05; // 0x199342e0ee5069.0p-68
+
+    let z = x * x;
+    let w = z * z;
+    let r = splat64(C2) + z * splat64(C3);
+    ((splat64(1.0) + z * splat64(C0)) + w * splat64(C1)) + (w * z) * r
+}
+
+/// Computes `tan(x)` for `|x| <= pi / 4`.
+#[inline]
+fn tan_kernel<const N: usize>(x: Simd<f64, N>) -> Simd<f64, N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    const T: [f64; 6] = [
+        0.3333313950307914,   // 0x15554d3418c99f.0p-54
+        0.13339200271297674,  // 0x1112fd38999f72.0p-55
+        0.05338123784456704,  // 0x1b54c91d865afe.0p-57
+        0.024528318116654728, // 0x191df3908c33ce.0p-58
+        0.002974357433599673, // 0x185dadfcecf44e.0p-61
+        0.009465647849436732, // 0x1362b9bf971bcd.0p-59
+    ];
+
+    let z = x * x;
+    let r = splat64(T[4]) + z * splat64(T[5]);
+    let t = splat64(T[2]) + z * splat64(T[3]);
+    let w = z * z;
+    let s = z * x;
+    let u = splat64(T[0]) + z * splat64(T[1]);
+    (x + s * u) + (s * w) * (t + w * r)
+}
+
+/// Replaces the lanes of `y` whose argument `x` is too large for `rem_pio2` with `f(x)`.
+#[inline]
+fn reduce_fallback<const N: usize>(
+    x: Simd<f32, N>,
+    mut y: Simd<f32, N>,
+    f: fn(f32) -> f32,
+) -> Simd<f32, N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let large = x.abs().simd_ge(splat(REDUCE_MAX)) & x.is_finite();
+    if large.any() {
+        for i in 0..N {
+            if large.test(i) {
+                y[i] = f(x[i]);
+            }
+        }
+    }
+    y
+}
+
+pub(crate) fn sin<const N: usize>(x: Simd<f32, N>) -> Simd<f32, N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let (y, n) = rem_pio2(x.cast());
+    let odd = (n & Simd::splat(1)).simd_eq(Simd::splat(1));
+    let r = odd.select(cos_kernel(y), sin_kernel(y));
+    let r = (n & Simd::splat(2)).simd_eq(Simd::splat(2)).select(-r, r);
+    // The kernel turns -0 into +0.
+    let r = x.simd_eq(splat(0.0)).select(x, r.cast());
+    reduce_fallback(x, r, f32::sin)
+}
+
+pub(crate) fn cos<const N: usize>(x: Simd<f32, N>) -> Simd<f32, N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let (y, n) = rem_pio2(x.cast());
+    let odd = (n & Simd::splat(1)).simd_eq(Simd::splat(1));
+    let r = odd.select(sin_kernel(y), cos_kernel(y));
+    let r = ((n + Simd::splat(1)) & Simd::splat(2))
+        .simd_eq(Simd::splat(2))
+        .select(-r, r);
+    reduce_fallback(x, r.cast(), f32::cos)
+}
+
+pub(crate) fn tan<const N: usize>(x: Simd<f32, N>) -> Simd<f32, N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let (y, n) = rem_pio2(x.cast());
+    let t = tan_kernel(y);
+    // tan(y + pi / 2) = -1 / tan(y)
+    let odd = (n & Simd::splat(1)).simd_eq(Simd::splat(1));
+    let r = odd.select(splat64(-1.0) / t, t);
+    reduce_fallback(x, r.cast(), f32::tan)
+}
+
+pub(crate) fn atan2<const N: usize>(y: Simd<f32, N>, x: Simd<f32, N>) -> Simd<f32, N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    super::f64::atan2(y.cast(), x.cast()).cast()
+}
+
+pub(crate) fn powf<const N: usize>(x: Simd<f32, N>, y: Simd<f32, N>) -> Simd<f32, N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    super::f64::powf(x.cast(), y.cast()).cast()
+}
@@ -0,0 +1,598 @@
+//! Vectorized implementations of the `f64` math functions.
+//!
+//! Most algorithms follow FreeBSD's msun (by way of musl), rewritten without branches so that
+//! every lane takes the same path. Special values are patched in with selects at the end.
+
+use crate::simd::prelude::*;
+use crate::simd::{LaneCount, SupportedLaneCount};
+
+/// `1.5 * 2^52`. Adding and subtracting it rounds `|x| < 2^51` to the nearest integer, leaving
+/// that integer in the low bits of the intermediate sum.
+const SHIFTER: f64 = 6755399441055744.0;
+
+const LN2_HI: f64 = 6.93147180369123816490e-01; // 0x3fe62e42_fee00000
+const LN2_LO: f64 = 1.90821492927058770002e-10; // 0x3dea39ef_35793c76
+
+#[inline]
+fn splat<const N: usize>(x: f64) -> Simd<f64, N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    Simd::splat(x)
+}
+
+/// Evaluates the polynomial with coefficients `c`, lowest degree first, at `x`.
+#[inline]
+fn horner<const N: usize>(x: Simd<f64, N>, c: &[f64]) -> Simd<f64, N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let (last, rest) = c.split_last().unwrap();
+    rest.iter()
+        .rev()
+        .fold(splat(*last), |acc, &c| acc * x + splat(c))
+}
+
+/// Rounds `x` to the nearest integer, returned both as a float and as an integer.
+///
+/// Only valid for `|x| < 2^51`.
+#[inline]
+pub(super) fn round_int<const N: usize>(x: Simd<f64, N>) -> (Simd<f64, N>, Simd<i64, N>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let t = x + splat(SHIFTER);
+    let n = t.to_bits().cast::<i64>() - Simd::splat(SHIFTER.to_bits() as i64);
+    (t - splat(SHIFTER), n)
+}
+
+/// Computes `2^n` for `-1022 <= n <= 1023`.
+#[inline]
+fn pow2i<const N: usize>(n: Simd<i64, N>) -> Simd<f64, N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    Simd::from_bits(((n + Simd::splat(1023)) << 52).cast())
+}
+
+/// Computes `x * 2^n` for `-2044 <= n <= 2046` with a single rounding, as long as `x * 2^(n / 2)`
+/// is normal.
+#[inline]
+fn scale<const N: usize>(x: Simd<f64, N>, n: Simd<i64, N>) -> Simd<f64, N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let n1 = n >> 1;
+    x * pow2i(n1) * pow2i(n - n1)
+}
+
+/// Computes `e^r` for `|r| <= ln(2) / 2` with its Taylor series.
+#[inline]
+fn exp_kernel<const N: usize>(r: Simd<f64, N>) -> Simd<f64, N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    const C: [f64; 12] = [
+        1.0 / 2.0,
+        1.0 / 6.0,
+        1.0 / 24.0,
+        1.0 / 120.0,
+        1.0 / 720.0,
+        1.0 / 5040.0,
+        1.0 / 40320.0,
+        1.0 / 362880.0,
+        1.0 / 3628800.0,
+        1.0 / 39916800.0,
+        1.0 / 479001600.0,
+        1.0 / 6227020800.0,
+    ];
+    splat(1.0) + (r + r * r * horner(r, &C))
+}
+
+pub(crate) fn exp<const N: usize>(x: Simd<f64, N>) -> Simd<f64, N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    // Largest argument with a finite result, and smallest one that doesn't round to zero.
+    const MAX: f64 = 709.782712893383973096;
+    const MIN: f64 = -745.13321910194110842;
+
+    // x = k ln(2) + r. Since `LN2_HI` has 32 significant bits, `k * LN2_HI` is exact.
+    let (kf, k) = round_int(x * splat(core::f64::consts::LOG2_E));
+    let r = (x - kf * splat(LN2_HI)) - kf * splat(LN2_LO);
+    let y = scale(exp_kernel(r), k);
+
+    let y = x.simd_gt(splat(MAX)).select(splat(f64::INFINITY), y);
+    x.simd_lt(splat(MIN)).select(splat(0.0), y)
+}
+
+pub(crate) fn exp2<const N: usize>(x: Simd<f64, N>) -> Simd<f64, N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let (kf, k) = round_int(x);
+    let y = scale(exp_kernel((x - kf) * splat(core::f64::consts::LN_2)), k);
+
+    let y = x.simd_ge(splat(1024.0)).select(splat(f64::INFINITY), y);
+    x.simd_lt(splat(-1075.0)).select(splat(0.0), y)
+}
+
+/// Splits `x` into `k` and `f` such that `x = 2^k (1 + f)` and `sqrt(2) / 2 <= 1 + f < sqrt(2)`.
+///
+/// Only valid for positive finite `x`.
+#[inline]
+fn log_reduce<const N: usize>(x: Simd<f64, N>) -> (Simd<f64, N>, Simd<f64, N>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    // Scale subnormals up by 2^54.
+    let subnormal = x.simd_lt(splat(f64::MIN_POSITIVE));
+    let x = subnormal.select(x * splat(18014398509481984.0), x);
+    let bias = subnormal.select(Simd::splat(-1023 - 54), Simd::splat(-1023));
+
+    // Offset the exponent so that it is incremented for mantissas above sqrt(2).
+    let ix = x.to_bits() + Simd::splat((0x3ff00000 - 0x3fe6a09e) << 32);
+    let k = (ix >> 52).cast::<i64>() + bias;
+    let m = Simd::<f64, N>::from_bits(
+        (ix & Simd::splat(0x000fffff_ffffffff)) + Simd::splat(0x3fe6a09e << 32),
+    );
+    (k.cast(), m - splat(1.0))
+}
+
+/// Returns `hfsq` and `s * (hfsq + R)` such that `log(1 + f) = f - hfsq + s * (hfsq + R)`.
+#[inline]
+fn log_kernel<const N: usize>(f: Simd<f64, N>) -> (Simd<f64, N>, Simd<f64, N>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    const LG1: f64 = 6.666666666666735130e-01; // 3FE55555 55555593
+    const LG2: f64 = 3.999999999940941908e-01; // 3FD99999 9997FA04
+    const LG3: f64 = 2.857142874366239149e-01; // 3FD24924 94229359
+    const LG4: f64 = 2.222219843214978396e-01; // 3FCC71C5 1D8E78AF
+    const LG5: f64 = 1.818357216161805012e-01; // 3FC74664 96CB03DE
+    const LG6: f64 = 1.531383769920937332e-01; // 3FC39A09 D078C69F
+    const LG7: f64 = 1.479819860511658591e-01; // 3FC2F112 DF3E5244
+
+    let hfsq = splat(0.5) * f * f;
+    let s = f / (splat(2.0) + f);
+    let z = s * s;
+    let w = z * z;
+    let r = z * horner(w, &[LG1, LG3, LG5, LG7]) + w * horner(w, &[LG2, LG4, LG6]);
+    (hfsq, s * (hfsq + r))
+}
+
+/// Splits `log(1 + f)` into `hi + lo`, where `hi` has only 20 significant bits.
+#[inline]
+fn log_kernel_hi_lo<const N: usize>(f: Simd<f64, N>) -> (Simd<f64, N>, Simd<f64, N>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let (hfsq, sr) = log_kernel(f);
+    let hi = Simd::from_bits((f - hfsq).to_bits() & Simd::splat(0xffffffff_00000000));
+    (hi, f - hi - hfsq + sr)
+}
+
+/// Replaces the result of a logarithm for zero, negative, infinite and NaN arguments.
+#[inline]
+fn log_special<const N: usize>(x: Simd<f64, N>, y: Simd<f64, N>) -> Simd<f64, N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let y = x.simd_eq(splat(0.0)).select(splat(f64::NEG_INFINITY), y);
+    let y = x.simd_eq(splat(f64::INFINITY)).select(x, y);
+    let y = x.simd_lt(splat(0.0)).select(splat(f64::NAN), y);
+    x.is_nan().select(x, y)
+}
+
+pub(crate) fn ln<const N: usize>(x: Simd<f64, N>) -> Simd<f64, N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let (k, f) = log_reduce(x);
+    let (hfsq, sr) = log_kernel(f);
+    let y = sr + k * splat(LN2_LO) - hfsq + f + k * splat(LN2_HI);
+    log_special(x, y)
+}
+
+pub(crate) fn log2<const N: usize>(x: Simd<f64, N>) -> Simd<f64, N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    const IVLN2HI: f64 = 1.44269504072144627571e+00; // 0x3ff71547_65200000
+    const IVLN2LO: f64 = 1.67517131648865118353e-10; // 0x3de705fc_2eefa200
+
+    let (k, f) = log_reduce(x);
+    let (hi, lo) = log_kernel_hi_lo(f);
+    let val_hi = hi * splat(IVLN2HI);
+    let val_lo = (lo + hi) * splat(IVLN2LO) + lo * splat(IVLN2HI);
+
+    // Add `k` in extra precision, as it may be much larger than `val_hi`.
+    let w = k + val_hi;
+    let val_lo = val_lo + ((k - w) + val_hi);
+    log_special(x, val_lo + w)
+}
+
+pub(crate) fn log10<const N: usize>(x: Simd<f64, N>) -> Simd<f64, N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    const IVLN10HI: f64 = 4.34294481878168880939e-01; // 0x3fdbcb7b_15200000
+    const IVLN10LO: f64 = 2.50829467116452752298e-11; // 0x3dbb9438_ca9aadd5
+    const LOG10_2HI: f64 = 3.01029995663611771306e-01; // 0x3FD34413_509F6000
+    const LOG10_2LO: f64 = 3.69423907715893078616e-13; // 0x3D59FEF3_11F12B36
+
+    let (k, f) = log_reduce(x);
+    let (hi, lo) = log_kernel_hi_lo(f);
+    let val_hi = hi * splat(IVLN10HI);
+    let y = k * splat(LOG10_2HI);
+    let val_lo = k * splat(LOG10_2LO) + (lo + hi) * splat(IVLN10LO) + lo * splat(IVLN10HI);
+
+    let w = y + val_hi;
+    let val_lo = val_lo + ((y - w) + val_hi);
+    log_special(x, val_lo + w)
+}
+
+/// Arguments at least this large are passed to the scalar functions instead of `rem_pio2`.
+const REDUCE_MAX: f64 = 1647099.0; // just below 2^20 * pi / 2
+
+/// Reduces `x` to `y0 + y1` with `|y0 + y1| <= pi / 4` and returns it with `n` such that
+/// `x = n pi / 2 + y0 + y1`.
+///
+/// Only valid for `|x| < REDUCE_MAX`, so that `n` has at most 20 bits and multiplying it by the
+/// 33-bit pieces of `pi / 2` is exact.
+#[inline]
+fn rem_pio2<const N: usize>(x: Simd<f64, N>) -> (Simd<f64, N>, Simd<f64, N>, Simd<i64, N>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    const INVPIO2: f64 = 6.36619772367581382433e-01; // 0x3FE45F30_6DC9C883
+    const PIO2_1: f64 = 1.57079632673412561417e+00; // 0x3FF921FB_54400000
+    const PIO2_2: f64 = 6.07710050630396597660e-11; // 0x3DD0B461_1A600000
+    const PIO2_3: f64 = 2.02226624871116645580e-21; // 0x3BA3198A_2E000000
+    const PIO2_3T: f64 = 8.47842766036889956997e-32; // 0x397B839A_252049C1
+
+    let (nf, n) = round_int(x * splat(INVPIO2));
+    let r = x - nf * splat(PIO2_1);
+
+    // Two more rounds, which is enough for the worst cancellation. The tail of the second round
+    // would only be replaced by the third one, so it is never computed.
+    let t = r;
+    let w = nf * splat(PIO2_2);
+    let r = t - w;
+    let t = r;
+    let w = nf * splat(PIO2_3);
+    let r = t - w;
+    let w = nf * splat(PIO2_3T) - ((t - r) - w);
+
+    let y0 = r - w;
+    (y0, (r - y0) - w, n)
+}
+
+/// Computes `sin(x + y)` for `|x + y| <= pi / 4`, where `y` is the tail of `x`.
+#[inline]
+fn sin_kernel<const N: usize>(x: Simd<f64, N>, y: Simd<f64, N>) -> Simd<f64, N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    const S1: f64 = -1.66666666666666324348e-01; // 0xBFC55555, 0x55555549
+    const S2: f64 = 8.33333333332248946124e-03; // 0x3F811111, 0x1110F8A6
+    const S3: f64 = -1.98412698298579493134e-04; // 0xBF2A01A0, 0x19C161D5
+    const S4: f64 = 2.75573137070700676789e-06; // 0x3EC71DE3, 0x57B1FE7D
+    const S5: f64 = -2.50507602534068634195e-08; // 0xBE5AE5E6, 0x8A2B9CEB
+    const S6: f64 = 1.58969099521155010221e-10; // 0x3DE5D93A, 0x5ACFD57C
+
+    let z = x * x;
+    let w = z * z;
+    let r = splat(S2) + z * (splat(S3) + z * splat(S4)) + z * w * (splat(S5) + z * splat(S6));
+    let v = z * x;
+    x - ((z * (splat(0.5) * y - v * r) - y) - v * splat(S1))
+}
+
+/// Computes `cos(x + y)` for `|x + y| <= pi / 4`, where `y` is the tail of `x`.
+#[inline]
+fn cos_kernel<const N: usize>(x: Simd<f64, N>, y: Simd<f64, N>) -> Simd<f64, N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    const C1: f64 = 4.16666666666666019037e-02; // 0x3FA55555, 0x5555554C
+    const C2: f64 = -1.38888888888741095749e-03; // 0xBF56C16C, 0x16C15177
+    const C3: f64 = 2.48015872894767294178e-05; // 0x3EFA01A0, 0x19CB1590
+    const C4: f64 = -2.75573143513906633035e-07; // 0xBE927E4F, 0x809C52AD
+    const C5: f64 = 2.08757232129817482790e-09; // 0x3E21EE9E, 0xBDB4B1C4
+    const C6: f64 = -1.13596475577881948265e-11; // 0xBDA8FAE9, 0xBE8838D4
+
+    let z = x * x;
+    let w = z * z;
+    let r = z * horner(z, &[C1, C2, C3]) + w * w * horner(z, &[C4, C5, C6]);
+    let hz = splat(0.5) * z;
+    let w = splat(1.0) - hz;
+    w + (((splat(1.0) - w) - hz) + (z * r - x * y))
+}
+
+/// Replaces the lanes of `y` whose argument `x` is too large for `rem_pio2` with `f(x)`.
+#[inline]
+fn reduce_fallback<const N: usize>(
+    x: Simd<f64, N>,
+    mut y: Simd<f64, N>,
+    f: fn(f64) -> f64,
+) -> Simd<f64, N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let large = x.abs().simd_ge(splat(REDUCE_MAX)) & x.is_finite();
+    if large.any() {
+        for i in 0..N {
+            if large.test(i) {
+                y[i] = f(x[i]);
+            }
+        }
+    }
+    y
+}
+
+pub(crate) fn sin<const N: usize>(x: Simd<f64, N>) -> Simd<f64, N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let (y0, y1, n) = rem_pio2(x);
+    let s = sin_kernel(y0, y1);
+    let c = cos_kernel(y0, y1);
+    let y = (n & Simd::splat(1)).simd_eq(Simd::splat(1)).select(c, s);
+    let y = (n & Simd::splat(2)).simd_eq(Simd::splat(2)).select(-y, y);
+    reduce_fallback(x, y, f64::sin)
+}
+
+pub(crate) fn cos<const N: usize>(x: Simd<f64, N>) -> Simd<f64, N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let (y0, y1, n) = rem_pio2(x);
+    let s = sin_kernel(y0, y1);
+    let c = cos_kernel(y0, y1);
+    let y = (n & Simd::splat(1)).simd_eq(Simd::splat(1)).select(s, c);
+    let y = ((n + Simd::splat(1)) & Simd::splat(2))
+        .simd_eq(Simd::splat(2))
+        .select(-y, y);
+    reduce_fallback(x, y, f64::cos)
+}
+
+pub(crate) fn tan<const N: usize>(x: Simd<f64, N>) -> Simd<f64, N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let (y0, y1, n) = rem_pio2(x);
+    let s = sin_kernel(y0, y1);
+    let c = cos_kernel(y0, y1);
+    // tan(y + pi / 2) = -cos(y) / sin(y)
+    let odd = (n & Simd::splat(1)).simd_eq(Simd::splat(1));
+    let y = odd.select(-c, s) / odd.select(s, c);
+    reduce_fallback(x, y, f64::tan)
+}
+
+/// Computes `atan(t)` for `t >= 0`, including infinity.
+#[inline]
+fn atan_kernel<const N: usize>(t: Simd<f64, N>) -> Simd<f64, N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    const AT: [f64; 11] = [
+        3.33333333333329318027e-01,  // 0x3FD55555, 0x5555550D
+        -1.99999999998764832476e-01, // 0xBFC99999, 0x9998EBC4
+        1.42857142725034663711e-01,  // 0x3FC24924, 0x920083FF
+        -1.11111104054623557880e-01, // 0xBFBC71C6, 0xFE231671
+        9.09088713343650656196e-02,  // 0x3FB745CD, 0xC54C206E
+        -7.69187620504482999495e-02, // 0xBFB3B0F2, 0xAF749A6D
+        6.66107313738753120669e-02,  // 0x3FB10D66, 0xA0D03D51
+        -5.83357013379057348645e-02, // 0xBFADDE2D, 0x52DEFD9A
+        4.97687799461593236017e-02,  // 0x3FA97B4B, 0x24760DEB
+        -3.65315727442169155270e-02, // 0xBFA2B444, 0x2C6A6C2F
+        1.62858201153657823623e-02,  // 0x3F90AD3A, 0xE322DA11
+    ];
+
+    // Reduce `t` to `|x| <= 7/16` with `atan(t) = atan(c) + atan(x)`, where `atan(c)` is split
+    // into `hi + lo`. The last interval maps infinity to -0.
+    let lt = |c: f64| t.simd_lt(splat(c));
+    let pick = |a: f64, b: f64, c: f64, d: f64, e: f64| {
+        lt(7.0 / 16.0).select(
+            splat(a),
+            lt(11.0 / 16.0).select(
+                splat(b),
+                lt(19.0 / 16.0).select(splat(c), lt(39.0 / 16.0).select(splat(d), splat(e))),
+            ),
+        )
+    };
+    let num = lt(7.0 / 16.0).select(
+        t,
+        lt(11.0 / 16.0).select(
+            splat(2.0) * t - splat(1.0),
+            lt(19.0 / 16.0).select(
+                t - splat(1.0),
+                lt(39.0 / 16.0).select(t - splat(1.5), splat(-1.0)),
+            ),
+        ),
+    );
+    let den = lt(7.0 / 16.0).select(
+        splat(1.0),
+        lt(11.0 / 16.0).select(
+            splat(2.0) + t,
+            lt(19.0 / 16.0).select(
+                t + splat(1.0),
+                lt(39.0 / 16.0).select(splat(1.0) + splat(1.5) * t, t),
+            ),
+        ),
+    );
+    let hi = pick(
+        0.0,
+        4.63647609000806093515e-01, // atan(0.5)hi 0x3FDDAC67, 0x0561BB4F
+        7.85398163397448278999e-01, // atan(1.0)hi 0x3FE921FB, 0x54442D18
+        9.82793723247329054082e-01, // atan(1.5)hi 0x3FEF730B, 0xD281F69B
+        1.57079632679489655800e+00, // atan(inf)hi 0x3FF921FB, 0x54442D18
+    );
+    let lo = pick(
+        0.0,
+        2.26987774529616870924e-17, // atan(0.5)lo 0x3C7A2B7F, 0x222F65E2
+        3.06161699786838301793e-17, // atan(1.0)lo 0x3C81A626, 0x33145C07
+        1.39033110312309984516e-17, // atan(1.5)lo 0x3C700788, 0x7AF0CBBD
+        6.12323399573676603587e-17, // atan(inf)lo 0x3C91A626, 0x33145C07
+    );
+
+    let x = num / den;
+    let z = x * x;
+    let w = z * z;
+    let s1 = z * horner(w, &[AT[0], AT[2], AT[4], AT[6], AT[8], AT[10]]);
+    let s2 = w * horner(w, &[AT[1], AT[3], AT[5], AT[7], AT[9]]);
+    hi - ((x * (s1 + s2) - lo) - x)
+}
+
+pub(crate) fn atan2<const N: usize>(y: Simd<f64, N>, x: Simd<f64, N>) -> Simd<f64, N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    const PI: f64 = 3.1415926535897931160E+00; // 0x400921FB, 0x54442D18
+    const PI_LO: f64 = 1.2246467991473531772E-16; // 0x3CA1A626, 0x33145C07
+
+    let ax = x.abs();
+    let ay = y.abs();
+    let zero = splat(0.0);
+    let inf = splat(f64::INFINITY);
+
+    // Both zero or both infinite would divide to NaN, instead of giving 0 and pi/4 respectively.
+    let t = ay / ax;
+    let t = (ax.simd_eq(zero) & ay.simd_eq(zero)).select(zero, t);
+    let t = (ax.simd_eq(inf) & ay.simd_eq(inf)).select(splat(1.0), t);
+
+    let z = atan_kernel(t);
+    let z = x
+        .is_sign_negative()
+        .select(splat(PI) - (z - splat(PI_LO)), z);
+    // Avoid the rounding error from the line above for exact multiples of pi/2.
+    let right = (ax.simd_eq(zero) & ay.simd_gt(zero)) | (ay.simd_eq(inf) & x.is_finite());
+    let z = right.select(splat(core::f64::consts::FRAC_PI_2), z);
+    z.copysign(y)
+}
+
+/// Splits `x` into `hi + lo` with 26 significant bits each.
+#[inline]
+fn split<const N: usize>(x: Simd<f64, N>) -> (Simd<f64, N>, Simd<f64, N>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let c = x * splat(134217729.0); // 2^27 + 1
+    let hi = c - (c - x);
+    (hi, x - hi)
+}
+
+/// Computes `a * b` exactly as `hi + lo`.
+#[inline]
+fn two_prod<const N: usize>(a: Simd<f64, N>, b: Simd<f64, N>) -> (Simd<f64, N>, Simd<f64, N>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let p = a * b;
+    let (ah, al) = split(a);
+    let (bh, bl) = split(b);
+    (p, ((ah * bh - p) + ah * bl + al * bh) + al * bl)
+}
+
+/// Computes `a + b` exactly as `hi + lo`, assuming `|a| >= |b|`.
+#[inline]
+fn fast_two_sum<const N: usize>(a: Simd<f64, N>, b: Simd<f64, N>) -> (Simd<f64, N>, Simd<f64, N>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let s = a + b;
+    (s, b - (s - a))
+}
+
+/// Computes `ln(x)` as `hi + lo` with about 70 bits of precision, for positive finite `x`.
+#[inline]
+fn ln_extended<const N: usize>(x: Simd<f64, N>) -> (Simd<f64, N>, Simd<f64, N>)
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    const TWO_THIRDS_HI: f64 = 0.6666666666666666; // 0x3FE55555_55555555
+    const TWO_THIRDS_LO: f64 = 3.700743415417188e-17; // 0x3C855555_55555555
+
+    let (k, f) = log_reduce(x);
+
+    // ln(1 + f) = 2 atanh(s) = 2s + 2s^3 / 3 + 2s^5 / 5 + ..., with s = f / (2 + f).
+    let (dh, dl) = fast_two_sum(splat(2.0), f);
+    let sh = f / dh;
+    let (p, e) = two_prod(sh, dh);
+    let sl = (((f - p) - e) - sh * dl) / dh;
+
+    // |s| <= 0.1716, so 2s^3 / 3 has to be exact to 8 more bits than the rest.
+    let (zh, zl) = two_prod(sh, sh);
+    let zl = zl + splat(2.0) * sh * sl;
+    let (ch, cl) = two_prod(zh, sh);
+    let cl = cl + zl * sh + zh * sl;
+    let (th, tl) = two_prod(ch, splat(TWO_THIRDS_HI));
+    let tl = tl + ch * splat(TWO_THIRDS_LO) + cl * splat(TWO_THIRDS_HI);
+
+    const Q: [f64; 12] = [
+        2.0 / 5.0,
+        2.0 / 7.0,
+        2.0 / 9.0,
+        2.0 / 11.0,
+        2.0 / 13.0,
+        2.0 / 15.0,
+        2.0 / 17.0,
+        2.0 / 19.0,
+        2.0 / 21.0,
+        2.0 / 23.0,
+        2.0 / 25.0,
+        2.0 / 27.0,
+    ];
+    let rest = ch * zh * horner(zh, &Q);
+
+    let (rh, rl) = fast_two_sum(splat(2.0) * sh, th);
+    let rl = rl + (splat(2.0) * sl + tl + rest);
+
+    // `k * LN2_HI` is exact, and larger than `rh` unless it is zero.
+    let (hi, lo) = fast_two_sum(k * splat(LN2_HI), rh);
+    (hi, lo + (rl + k * splat(LN2_LO)))
+}
+
+pub(crate) fn powf<const N: usize>(x: Simd<f64, N>, y: Simd<f64, N>) -> Simd<f64, N>
+where
+    LaneCount<N>: SupportedLaneCount,
+{
+    let ax = x.abs();
+    let ay = y.abs();
+    let zero = splat(0.0);
+    let one = splat(1.0);
+    let inf = splat(f64::INFINITY);
+
+    // |x|^y = e^(y ln|x|), with the exponent computed in extra precision.
+    let (lh, ll) = ln_extended(ax);
+    let (th, tl) = two_prod(y, lh);
+    let tl = tl + y * ll;
+    let (kf, k) = round_int(th * splat(core::f64::consts::LOG2_E));
+    let r = (th - kf * splat(LN2_HI)) + (tl - kf * splat(LN2_LO));
+    let z = scale(exp_kernel(r), k);
+    let z = th.simd_gt(splat(710.0)).select(inf, z);
+    let z = th.simd_lt(splat(-746.0)).select(zero, z);
+
+    // Zero and infinite arguments, where the logarithm above is meaningless.
+    let y_neg = y.simd_lt(zero);
+    let z = ax.simd_eq(zero).select(y_neg.select(inf, zero), z);
+    let z = ax.simd_eq(inf).select(y_neg.select(zero, inf), z);
+    let grows = !(ax.simd_lt(one) ^ y_neg);
+    let z = ay.simd_eq(inf).select(grows.select(inf, zero), z);
+    let z = ax.simd_eq(one).select(one, z);
+
+    // Every float of at least 2^52 is an even integer, except those below 2^53 may be odd.
+    let two52 = splat(4503599627370496.0);
+    let small = ay.simd_lt(two52);
+    let v = small.select(ay + two52, ay);
+    let y_int = !small | (v - two52).simd_eq(ay);
+    let y_odd = y_int
+        & ay.simd_lt(splat(9007199254740992.0))
+        & (v.to_bits() & Simd::splat(1)).simd_eq(Simd::splat(1));
+
+    // Negative bases only have a (real) result for integer exponents.
+    let z = (x.is_sign_negative() & y_odd).select(-z, z);
+    let z = (x.simd_lt(zero) & x.is_finite() & !y_int).select(splat(f64::NAN), z);
+    let z = (x.is_nan() | y.is_nan()).select(x + y, z);
+    (y.simd_eq(zero) | x.simd_eq(one)).select(one, z)
+}
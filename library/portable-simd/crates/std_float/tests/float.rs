@@ -16,15 +16,35 @@ fn $func<const LANES: usize>() {
     }
 }
 
-macro_rules! binary_test {
-    { $scalar:tt, $($func:tt),+ } => {
+// The scalar functions aren't correctly rounded either, so these allow one more ULP than the
+// documented accuracy of the vector functions.
+macro_rules! unary_approx_test {
+    { $scalar:tt, $($func:tt: $ulps:literal),+ } => {
         test_helpers::test_lanes! {
             $(
             fn $func<const LANES: usize>() {
-                test_helpers::test_binary_elementwise(
+                test_helpers::test_unary_elementwise_ulps(
+                    &core_simd::simd::Simd::<$scalar, LANES>::$func,
+                    &$scalar::$func,
+                    &|_| true,
+                    $ulps,
+                )
+            }
+            )*
+        }
+    }
+}
+
+macro_rules! binary_approx_test {
+    { $scalar:tt, $($func:tt: $ulps:literal),+ } => {
+        test_helpers::test_lanes! {
+            $(
+            fn $func<const LANES: usize>() {
+                test_helpers::test_binary_elementwise_ulps(
                     &core_simd::simd::Simd::<$scalar, LANES>::$func,
                     &$scalar::$func,
                     &|_, _| true,
+                    $ulps,
                 )
             }
             )*
@@ -49,12 +69,17 @@ fn $func<const LANES: usize>() {
 }
 
 macro_rules! impl_tests {
-    { $scalar:tt } => {
+    {
+        $scalar:tt,
+        unary: { $($unary:tt: $unary_ulps:literal),+ },
+        binary: { $($binary:tt: $binary_ulps:literal),+ }
+    } => {
         mod $scalar {
             use std_float::StdFloat;
 
-            unary_test! { $scalar, sqrt, sin, cos, exp, exp2, ln, log2, log10, ceil, floor, round, trunc }
-            binary_test! { $scalar, log }
+            unary_test! { $scalar, sqrt, ceil, floor, round, trunc }
+            unary_approx_test! { $scalar, $($unary: $unary_ulps),+ }
+            binary_approx_test! { $scalar, $($binary: $binary_ulps),+ }
             ternary_test! { $scalar, mul_add }
 
             test_helpers::test_lanes! {
@@ -70,5 +95,14 @@ fn fract<const LANES: usize>() {
     }
 }
 
-impl_tests! { f32 }
-impl_tests! { f64 }
+impl_tests! {
+    f32,
+    unary: { sin: 2, cos: 2, tan: 2, exp: 2, exp2: 2, ln: 2, log2: 2, log10: 2 },
+    binary: { log: 4, powf: 2, atan2: 2 }
+}
+
+impl_tests! {
+    f64,
+    unary: { sin: 2, cos: 2, tan: 4, exp: 2, exp2: 2, ln: 2, log2: 2, log10: 3 },
+    binary: { log: 4, powf: 2, atan2: 2 }
+}
@@ -15,6 +15,9 @@
 pub mod subnormals;
 use subnormals::FlushSubnormals;
 
+#[macro_use]
+pub mod ulps;
+
 /// Specifies the default strategy for testing a type.
 ///
 /// This strategy should be what "makes sense" to test.
@@ -227,6 +230,32 @@ pub fn test_unary_elementwise_flush_subnormals<
     });
 }
 
+/// Test a unary vector function against a unary scalar function, applied elementwise, allowing
+/// the results to differ by up to `max_ulps` units in the last place.
+pub fn test_unary_elementwise_ulps<Scalar, Vector, const LANES: usize>(
+    fv: &dyn Fn(Vector) -> Vector,
+    fs: &dyn Fn(Scalar) -> Scalar,
+    check: &dyn Fn([Scalar; LANES]) -> bool,
+    max_ulps: u64,
+) where
+    Scalar: Copy + core::fmt::Debug + DefaultStrategy + ulps::Ulps,
+    Vector: Into<[Scalar; LANES]> + From<[Scalar; LANES]> + Copy,
+{
+    test_1(&|x: [Scalar; LANES]| {
+        proptest::prop_assume!(check(x));
+        let result_v: [Scalar; LANES] = fv(x.into()).into();
+        let result_s: [Scalar; LANES] = x
+            .iter()
+            .copied()
+            .map(fs)
+            .collect::<Vec<_>>()
+            .try_into()
+            .unwrap();
+        crate::prop_assert_ulps!(result_v, result_s, max_ulps);
+        Ok(())
+    });
+}
+
 /// Test a unary vector function against a unary scalar function, applied elementwise.
 #[inline(never)]
 pub fn test_unary_mask_elementwise<Scalar, Vector, Mask, const LANES: usize>(
@@ -369,6 +398,33 @@ pub fn test_binary_mask_elementwise<Scalar1, Scalar2, Vector1, Vector2, Mask, co
     });
 }
 
+/// Test a binary vector function against a binary scalar function, applied elementwise, allowing
+/// the results to differ by up to `max_ulps` units in the last place.
+pub fn test_binary_elementwise_ulps<Scalar, Vector, const LANES: usize>(
+    fv: &dyn Fn(Vector, Vector) -> Vector,
+    fs: &dyn Fn(Scalar, Scalar) -> Scalar,
+    check: &dyn Fn([Scalar; LANES], [Scalar; LANES]) -> bool,
+    max_ulps: u64,
+) where
+    Scalar: Copy + core::fmt::Debug + DefaultStrategy + ulps::Ulps,
+    Vector: Into<[Scalar; LANES]> + From<[Scalar; LANES]> + Copy,
+{
+    test_2(&|x: [Scalar; LANES], y: [Scalar; LANES]| {
+        proptest::prop_assume!(check(x, y));
+        let result_v: [Scalar; LANES] = fv(x.into(), y.into()).into();
+        let result_s: [Scalar; LANES] = x
+            .iter()
+            .copied()
+            .zip(y.iter().copied())
+            .map(|(x, y)| fs(x, y))
+            .collect::<Vec<_>>()
+            .try_into()
+            .unwrap();
+        crate::prop_assert_ulps!(result_v, result_s, max_ulps);
+        Ok(())
+    });
+}
+
 /// Test a binary vector-scalar function against a binary scalar function, applied elementwise.
 #[inline(never)]
 pub fn test_binary_scalar_rhs_elementwise<
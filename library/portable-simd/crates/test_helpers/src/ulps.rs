@@ -0,0 +1,63 @@
+//! Compare floating point numbers by their distance in units in the last place.
+
+pub trait Ulps {
+    /// Returns how many representable values separate `self` and `other`.
+    ///
+    /// NaNs are equal to each other and infinitely far from everything else. Signed zeros are
+    /// equal.
+    fn ulps(&self, other: &Self) -> u64;
+}
+
+macro_rules! impl_float_ulps {
+    { $($type:ty => $int:ty),* } => {
+        $(
+        impl Ulps for $type {
+            fn ulps(&self, other: &Self) -> u64 {
+                if self.is_nan() || other.is_nan() {
+                    return if self.is_nan() && other.is_nan() { 0 } else { u64::MAX };
+                }
+                // Map the bits onto a monotonic integer scale, with both zeros at 0.
+                let ordered = |x: $type| {
+                    let bits = x.to_bits() as $int;
+                    if bits < 0 { <$int>::MIN - bits } else { bits }
+                };
+                (ordered(*self) as i64).abs_diff(ordered(*other) as i64)
+            }
+        }
+        )*
+    }
+}
+
+impl_float_ulps! { f32 => i32, f64 => i64 }
+
+#[doc(hidden)]
+pub struct UlpsWrapper<'a, T>(pub &'a T, pub u64);
+
+impl<T: Ulps, const N: usize> PartialEq for UlpsWrapper<'_, [T; N]> {
+    fn eq(&self, other: &Self) -> bool {
+        let max_ulps = self.1.min(other.1);
+        self.0
+            .iter()
+            .zip(other.0.iter())
+            .all(|(a, b)| a.ulps(b) <= max_ulps)
+    }
+}
+
+impl<T: core::fmt::Debug> core::fmt::Debug for UlpsWrapper<'_, T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "{:?} (within {} ulps)", self.0, self.1)
+    }
+}
+
+#[macro_export]
+macro_rules! prop_assert_ulps {
+    { $a:expr, $b:expr, $max_ulps:expr $(,)? } => {
+        {
+            use $crate::ulps::UlpsWrapper;
+            let a = $a;
+            let b = $b;
+            let max_ulps = $max_ulps;
+            proptest::prop_assert_eq!(UlpsWrapper(&a, max_ulps), UlpsWrapper(&b, max_ulps));
+        }
+    };
+}
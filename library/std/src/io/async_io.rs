@@ -0,0 +1,452 @@
+//! Poll-based asynchronous counterparts of [`Read`], [`Write`] and [`BufRead`].
+//!
+//! These traits only describe how to make progress on I/O without blocking. They
+//! don't depend on any particular executor or reactor, so that libraries can be
+//! written against them and used with any runtime that implements them for its
+//! I/O types.
+
+#[cfg(test)]
+mod tests;
+
+use crate::alloc::Allocator;
+use crate::io::{self, BufRead, Cursor, Empty, IoSlice, IoSliceMut, Read, Repeat, Sink, Write};
+use crate::ops::DerefMut;
+use crate::pin::Pin;
+use crate::task::{Context, Poll};
+
+/// Reads bytes from a source asynchronously.
+///
+/// This is the asynchronous version of [`Read`]. Instead of blocking when no
+/// data is available, [`poll_read`] returns [`Poll::Pending`] and arranges for
+/// the current task to be woken up when it can make progress.
+///
+/// [`poll_read`]: AsyncRead::poll_read
+///
+/// # Examples
+///
+/// ```
+/// #![feature(async_io, block_on)]
+/// use std::future::poll_fn;
+/// use std::io::AsyncRead;
+/// use std::pin::Pin;
+/// use std::task::block_on;
+///
+/// let mut reader: &[u8] = b"hello";
+/// let mut buf = [0; 3];
+/// let n = block_on(poll_fn(|cx| Pin::new(&mut reader).poll_read(cx, &mut buf))).unwrap();
+/// assert_eq!(&buf[..n], b"hel");
+/// ```
+#[unstable(feature = "async_io", issue = "none")]
+pub trait AsyncRead {
+    /// Attempts to read bytes into `buf`, returning how many bytes were read.
+    ///
+    /// On success, returns `Poll::Ready(Ok(n))` with the same meaning as the
+    /// return value of [`Read::read`]: in particular, 0 means that the end of
+    /// the stream was reached, or that `buf` is empty.
+    ///
+    /// If no data is available yet, returns `Poll::Pending` and arranges for
+    /// `cx.waker()` to be woken up once the object may be readable. Errors of
+    /// kind [`WouldBlock`] should not be returned; [`Interrupted`] errors may
+    /// be retried like with [`Read::read`].
+    ///
+    /// [`WouldBlock`]: io::ErrorKind::WouldBlock
+    /// [`Interrupted`]: io::ErrorKind::Interrupted
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>>;
+
+    /// Like [`poll_read`], except that it reads into a slice of buffers.
+    ///
+    /// The default implementation calls [`poll_read`] with the first non-empty
+    /// buffer, or an empty one if there is none, like [`Read::read_vectored`].
+    ///
+    /// [`poll_read`]: AsyncRead::poll_read
+    fn poll_read_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &mut [IoSliceMut<'_>],
+    ) -> Poll<io::Result<usize>> {
+        let buf = bufs.iter_mut().find(|b| !b.is_empty()).map_or(&mut [][..], |b| &mut **b);
+        self.poll_read(cx, buf)
+    }
+}
+
+/// Writes bytes into a sink asynchronously.
+///
+/// This is the asynchronous version of [`Write`]. Instead of blocking when the
+/// sink can't accept any more data, [`poll_write`] returns [`Poll::Pending`] and
+/// arranges for the current task to be woken up when it can make progress.
+///
+/// [`poll_write`]: AsyncWrite::poll_write
+///
+/// # Examples
+///
+/// ```
+/// #![feature(async_io, block_on)]
+/// use std::future::poll_fn;
+/// use std::io::AsyncWrite;
+/// use std::pin::Pin;
+/// use std::task::block_on;
+///
+/// let mut writer = Vec::new();
+/// block_on(poll_fn(|cx| Pin::new(&mut writer).poll_write(cx, b"hello"))).unwrap();
+/// block_on(poll_fn(|cx| Pin::new(&mut writer).poll_close(cx))).unwrap();
+/// assert_eq!(writer, b"hello");
+/// ```
+#[unstable(feature = "async_io", issue = "none")]
+pub trait AsyncWrite {
+    /// Attempts to write bytes from `buf`, returning how many bytes were written.
+    ///
+    /// On success, returns `Poll::Ready(Ok(n))` with the same meaning as the
+    /// return value of [`Write::write`].
+    ///
+    /// If the object can't accept any data yet, returns `Poll::Pending` and
+    /// arranges for `cx.waker()` to be woken up once it may be writable.
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>>;
+
+    /// Like [`poll_write`], except that it writes from a slice of buffers.
+    ///
+    /// The default implementation calls [`poll_write`] with the first non-empty
+    /// buffer, or an empty one if there is none, like [`Write::write_vectored`].
+    ///
+    /// [`poll_write`]: AsyncWrite::poll_write
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        let buf = bufs.iter().find(|b| !b.is_empty()).map_or(&[][..], |b| &**b);
+        self.poll_write(cx, buf)
+    }
+
+    /// Attempts to flush buffered data to its destination, like [`Write::flush`].
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>>;
+
+    /// Attempts to flush buffered data and close the object.
+    ///
+    /// This is how the end of the data is signaled, e.g. by shutting down the
+    /// write half of a socket. The object should not be written to afterwards.
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>>;
+}
+
+/// An [`AsyncRead`] with an internal buffer.
+///
+/// This is the asynchronous version of [`BufRead`].
+#[unstable(feature = "async_io", issue = "none")]
+pub trait AsyncBufRead: AsyncRead {
+    /// Attempts to return the contents of the internal buffer, filling it with
+    /// more data if it is empty.
+    ///
+    /// Like with [`BufRead::fill_buf`], an empty buffer means that the end of
+    /// the stream was reached. If the buffer is empty and no data is available
+    /// yet, returns `Poll::Pending` and arranges for `cx.waker()` to be woken up
+    /// once the object may be readable.
+    fn poll_fill_buf(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<&[u8]>>;
+
+    /// Marks `amt` bytes of the buffer returned by [`poll_fill_buf`] as read, like
+    /// [`BufRead::consume`].
+    ///
+    /// [`poll_fill_buf`]: AsyncBufRead::poll_fill_buf
+    fn consume(self: Pin<&mut Self>, amt: usize);
+}
+
+// =============================================================================
+// Forwarding implementations
+
+macro_rules! deref_async_read {
+    () => {
+        fn poll_read(
+            self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &mut [u8],
+        ) -> Poll<io::Result<usize>> {
+            Pin::new(&mut **self.get_mut()).poll_read(cx, buf)
+        }
+
+        fn poll_read_vectored(
+            self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            bufs: &mut [IoSliceMut<'_>],
+        ) -> Poll<io::Result<usize>> {
+            Pin::new(&mut **self.get_mut()).poll_read_vectored(cx, bufs)
+        }
+    };
+}
+
+macro_rules! deref_async_write {
+    () => {
+        fn poll_write(
+            self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &[u8],
+        ) -> Poll<io::Result<usize>> {
+            Pin::new(&mut **self.get_mut()).poll_write(cx, buf)
+        }
+
+        fn poll_write_vectored(
+            self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            bufs: &[IoSlice<'_>],
+        ) -> Poll<io::Result<usize>> {
+            Pin::new(&mut **self.get_mut()).poll_write_vectored(cx, bufs)
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Pin::new(&mut **self.get_mut()).poll_flush(cx)
+        }
+
+        fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Pin::new(&mut **self.get_mut()).poll_close(cx)
+        }
+    };
+}
+
+macro_rules! deref_async_buf_read {
+    () => {
+        fn poll_fill_buf(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<&[u8]>> {
+            Pin::new(&mut **self.get_mut()).poll_fill_buf(cx)
+        }
+
+        fn consume(self: Pin<&mut Self>, amt: usize) {
+            Pin::new(&mut **self.get_mut()).consume(amt)
+        }
+    };
+}
+
+#[unstable(feature = "async_io", issue = "none")]
+impl<R: AsyncRead + Unpin + ?Sized> AsyncRead for &mut R {
+    deref_async_read!();
+}
+
+#[unstable(feature = "async_io", issue = "none")]
+impl<R: AsyncRead + Unpin + ?Sized> AsyncRead for Box<R> {
+    deref_async_read!();
+}
+
+#[unstable(feature = "async_io", issue = "none")]
+impl<W: AsyncWrite + Unpin + ?Sized> AsyncWrite for &mut W {
+    deref_async_write!();
+}
+
+#[unstable(feature = "async_io", issue = "none")]
+impl<W: AsyncWrite + Unpin + ?Sized> AsyncWrite for Box<W> {
+    deref_async_write!();
+}
+
+#[unstable(feature = "async_io", issue = "none")]
+impl<B: AsyncBufRead + Unpin + ?Sized> AsyncBufRead for &mut B {
+    deref_async_buf_read!();
+}
+
+#[unstable(feature = "async_io", issue = "none")]
+impl<B: AsyncBufRead + Unpin + ?Sized> AsyncBufRead for Box<B> {
+    deref_async_buf_read!();
+}
+
+#[unstable(feature = "async_io", issue = "none")]
+impl<P> AsyncRead for Pin<P>
+where
+    P: DerefMut<Target: AsyncRead> + Unpin,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        self.get_mut().as_mut().poll_read(cx, buf)
+    }
+
+    fn poll_read_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &mut [IoSliceMut<'_>],
+    ) -> Poll<io::Result<usize>> {
+        self.get_mut().as_mut().poll_read_vectored(cx, bufs)
+    }
+}
+
+#[unstable(feature = "async_io", issue = "none")]
+impl<P> AsyncWrite for Pin<P>
+where
+    P: DerefMut<Target: AsyncWrite> + Unpin,
+{
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        self.get_mut().as_mut().poll_write(cx, buf)
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        self.get_mut().as_mut().poll_write_vectored(cx, bufs)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.get_mut().as_mut().poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.get_mut().as_mut().poll_close(cx)
+    }
+}
+
+#[unstable(feature = "async_io", issue = "none")]
+impl<P> AsyncBufRead for Pin<P>
+where
+    P: DerefMut<Target: AsyncBufRead> + Unpin,
+{
+    fn poll_fill_buf(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<&[u8]>> {
+        self.get_mut().as_mut().poll_fill_buf(cx)
+    }
+
+    fn consume(self: Pin<&mut Self>, amt: usize) {
+        self.get_mut().as_mut().consume(amt)
+    }
+}
+
+// =============================================================================
+// In-memory implementations
+//
+// These never block, so they are ready right away and forward to the
+// synchronous implementations.
+
+macro_rules! sync_async_read {
+    () => {
+        fn poll_read(
+            self: Pin<&mut Self>,
+            _cx: &mut Context<'_>,
+            buf: &mut [u8],
+        ) -> Poll<io::Result<usize>> {
+            Poll::Ready(Read::read(self.get_mut(), buf))
+        }
+
+        fn poll_read_vectored(
+            self: Pin<&mut Self>,
+            _cx: &mut Context<'_>,
+            bufs: &mut [IoSliceMut<'_>],
+        ) -> Poll<io::Result<usize>> {
+            Poll::Ready(Read::read_vectored(self.get_mut(), bufs))
+        }
+    };
+}
+
+macro_rules! sync_async_write {
+    () => {
+        fn poll_write(
+            self: Pin<&mut Self>,
+            _cx: &mut Context<'_>,
+            buf: &[u8],
+        ) -> Poll<io::Result<usize>> {
+            Poll::Ready(Write::write(self.get_mut(), buf))
+        }
+
+        fn poll_write_vectored(
+            self: Pin<&mut Self>,
+            _cx: &mut Context<'_>,
+            bufs: &[IoSlice<'_>],
+        ) -> Poll<io::Result<usize>> {
+            Poll::Ready(Write::write_vectored(self.get_mut(), bufs))
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(Write::flush(self.get_mut()))
+        }
+
+        fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(Write::flush(self.get_mut()))
+        }
+    };
+}
+
+macro_rules! sync_async_buf_read {
+    () => {
+        fn poll_fill_buf(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<&[u8]>> {
+            Poll::Ready(BufRead::fill_buf(self.get_mut()))
+        }
+
+        fn consume(self: Pin<&mut Self>, amt: usize) {
+            BufRead::consume(self.get_mut(), amt)
+        }
+    };
+}
+
+#[unstable(feature = "async_io", issue = "none")]
+impl AsyncRead for &[u8] {
+    sync_async_read!();
+}
+
+#[unstable(feature = "async_io", issue = "none")]
+impl AsyncBufRead for &[u8] {
+    sync_async_buf_read!();
+}
+
+#[unstable(feature = "async_io", issue = "none")]
+impl<A: Allocator + Unpin> AsyncWrite for Vec<u8, A> {
+    sync_async_write!();
+}
+
+#[unstable(feature = "async_io", issue = "none")]
+impl<T: AsRef<[u8]> + Unpin> AsyncRead for Cursor<T> {
+    sync_async_read!();
+}
+
+#[unstable(feature = "async_io", issue = "none")]
+impl<T: AsRef<[u8]> + Unpin> AsyncBufRead for Cursor<T> {
+    sync_async_buf_read!();
+}
+
+#[unstable(feature = "async_io", issue = "none")]
+impl AsyncWrite for Cursor<&mut [u8]> {
+    sync_async_write!();
+}
+
+#[unstable(feature = "async_io", issue = "none")]
+impl<A: Allocator + Unpin> AsyncWrite for Cursor<&mut Vec<u8, A>> {
+    sync_async_write!();
+}
+
+#[unstable(feature = "async_io", issue = "none")]
+impl<A: Allocator + Unpin> AsyncWrite for Cursor<Vec<u8, A>> {
+    sync_async_write!();
+}
+
+#[unstable(feature = "async_io", issue = "none")]
+impl<A: Allocator + Unpin> AsyncWrite for Cursor<Box<[u8], A>> {
+    sync_async_write!();
+}
+
+#[unstable(feature = "async_io", issue = "none")]
+impl AsyncRead for Empty {
+    sync_async_read!();
+}
+
+#[unstable(feature = "async_io", issue = "none")]
+impl AsyncBufRead for Empty {
+    sync_async_buf_read!();
+}
+
+#[unstable(feature = "async_io", issue = "none")]
+impl AsyncWrite for Empty {
+    sync_async_write!();
+}
+
+#[unstable(feature = "async_io", issue = "none")]
+impl AsyncRead for Repeat {
+    sync_async_read!();
+}
+
+#[unstable(feature = "async_io", issue = "none")]
+impl AsyncWrite for Sink {
+    sync_async_write!();
+}
@@ -0,0 +1,141 @@
+use crate::future::poll_fn;
+use crate::io::{self, AsyncBufRead, AsyncRead, AsyncWrite, Cursor, IoSlice, IoSliceMut};
+use crate::pin::Pin;
+use crate::task::{block_on, Context, Poll};
+
+fn read<R: AsyncRead + Unpin>(reader: &mut R, buf: &mut [u8]) -> io::Result<usize> {
+    block_on(poll_fn(|cx| Pin::new(&mut *reader).poll_read(cx, buf)))
+}
+
+fn write<W: AsyncWrite + Unpin>(writer: &mut W, buf: &[u8]) -> io::Result<usize> {
+    block_on(poll_fn(|cx| Pin::new(&mut *writer).poll_write(cx, buf)))
+}
+
+/// Returns `Pending` every other poll, waking itself up right away, and reads or
+/// writes at most one byte at a time.
+struct Trickle {
+    data: Vec<u8>,
+    pending: bool,
+}
+
+impl Trickle {
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+        self.pending = !self.pending;
+        if self.pending {
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        } else {
+            Poll::Ready(())
+        }
+    }
+}
+
+impl AsyncRead for Trickle {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        if this.poll_ready(cx).is_pending() {
+            return Poll::Pending;
+        }
+        if this.data.is_empty() || buf.is_empty() {
+            return Poll::Ready(Ok(0));
+        }
+        buf[0] = this.data.remove(0);
+        Poll::Ready(Ok(1))
+    }
+}
+
+impl AsyncWrite for Trickle {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        if this.poll_ready(cx).is_pending() {
+            return Poll::Pending;
+        }
+        this.data.extend(buf.first());
+        Poll::Ready(Ok(buf.len().min(1)))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.get_mut().poll_ready(cx).map(Ok)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.poll_flush(cx)
+    }
+}
+
+#[test]
+fn pending_reader_and_writer() {
+    let mut t = Trickle { data: b"ab".to_vec(), pending: false };
+    let mut buf = [0; 4];
+    assert_eq!(read(&mut t, &mut buf).unwrap(), 1);
+    assert_eq!(read(&mut t, &mut buf[1..]).unwrap(), 1);
+    assert_eq!(read(&mut t, &mut buf).unwrap(), 0);
+    assert_eq!(&buf[..2], b"ab");
+
+    assert_eq!(write(&mut t, b"xyz").unwrap(), 1);
+    block_on(poll_fn(|cx| Pin::new(&mut t).poll_close(cx))).unwrap();
+    assert_eq!(t.data, b"x");
+}
+
+#[test]
+fn default_vectored_uses_first_nonempty_buffer() {
+    let mut t = Trickle { data: b"ab".to_vec(), pending: false };
+    let (mut a, mut b) = ([0; 0], [0; 2]);
+    let mut bufs = [IoSliceMut::new(&mut a), IoSliceMut::new(&mut b)];
+    let n = block_on(poll_fn(|cx| Pin::new(&mut t).poll_read_vectored(cx, &mut bufs))).unwrap();
+    assert_eq!(n, 1);
+    assert_eq!(b, [b'a', 0]);
+
+    let bufs = [IoSlice::new(b""), IoSlice::new(b"cd")];
+    let n = block_on(poll_fn(|cx| Pin::new(&mut t).poll_write_vectored(cx, &bufs))).unwrap();
+    assert_eq!(n, 1);
+    assert_eq!(t.data, b"bc");
+}
+
+#[test]
+fn in_memory() {
+    let mut slice: &[u8] = b"hello world";
+    let mut buf = [0; 5];
+    assert_eq!(read(&mut slice, &mut buf).unwrap(), 5);
+    assert_eq!(&buf, b"hello");
+
+    let fill = block_on(poll_fn(|cx| Pin::new(&mut slice).poll_fill_buf(cx).map_ok(|b| b.len())));
+    assert_eq!(fill.unwrap(), 6);
+    Pin::new(&mut slice).consume(1);
+    assert_eq!(slice, b"world");
+
+    let mut vec = Vec::new();
+    assert_eq!(write(&mut vec, b"abc").unwrap(), 3);
+    let mut cursor = Cursor::new(vec);
+    cursor.set_position(1);
+    assert_eq!(write(&mut cursor, b"xyz").unwrap(), 3);
+    assert_eq!(cursor.get_ref(), b"axyz");
+
+    cursor.set_position(0);
+    assert_eq!(read(&mut cursor, &mut buf).unwrap(), 4);
+    assert_eq!(&buf[..4], b"axyz");
+
+    assert_eq!(read(&mut io::empty(), &mut buf).unwrap(), 0);
+    assert_eq!(read(&mut io::repeat(1), &mut buf).unwrap(), 5);
+    assert_eq!(write(&mut io::sink(), b"abc").unwrap(), 3);
+}
+
+#[test]
+fn forwarding() {
+    let mut boxed: Box<dyn AsyncRead + Unpin> =
+        Box::new(Trickle { data: b"a".to_vec(), pending: false });
+    let mut buf = [0; 1];
+    assert_eq!(read(&mut &mut boxed, &mut buf).unwrap(), 1);
+
+    let mut pinned: Pin<Box<dyn AsyncWrite>> = Box::pin(Vec::new());
+    assert_eq!(write(&mut pinned, b"ab").unwrap(), 2);
+    block_on(poll_fn(|cx| pinned.as_mut().poll_flush(cx))).unwrap();
+}
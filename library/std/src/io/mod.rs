@@ -303,6 +303,8 @@
 
 pub(crate) use error::const_io_error;
 
+#[unstable(feature = "async_io", issue = "none")]
+pub use self::async_io::{AsyncBufRead, AsyncRead, AsyncWrite};
 #[stable(feature = "bufwriter_into_parts", since = "1.56.0")]
 pub use self::buffered::WriterPanicked;
 #[unstable(feature = "raw_os_error_ty", issue = "107792")]
@@ -329,6 +331,7 @@
 use crate::ops::{Deref, DerefMut};
 use crate::{cmp, fmt, slice, str, sys};
 
+mod async_io;
 mod buffered;
 pub(crate) mod copy;
 mod cursor;
@@ -626,6 +626,11 @@ pub mod task {
     #[doc(inline)]
     #[stable(feature = "futures_api", since = "1.36.0")]
     pub use core::task::*;
+
+    #[unstable(feature = "block_on", issue = "none")]
+    pub use self::block_on::block_on;
+
+    mod block_on;
 }
 
 #[doc = include_str!("../../stdarch/crates/core_arch/src/core_arch_docs.md")]
@@ -0,0 +1,231 @@
+//! Batched file and socket I/O with [io_uring].
+//!
+//! Submitting a batch of reads and writes with [`submit`] or [`IoUring::submit`]
+//! takes a single system call, instead of one per operation. This pays off for
+//! workloads doing many small positional reads and writes, such as databases.
+//!
+//! Every submission waits until all its operations have completed, so operations
+//! can borrow their buffers like regular [`read_at`] and [`write_at`] calls do.
+//! Nothing is done in the background.
+//!
+//! io_uring is only available on Linux 5.1 and later, and may be disabled by the
+//! system administrator or a seccomp filter. [`submit`] then transparently falls
+//! back to running the operations one after the other. Likewise, operations
+//! without an offset are run with regular system calls on kernels older than 5.6,
+//! which can't use the current file position.
+//!
+//! [io_uring]: https://man7.org/linux/man-pages/man7/io_uring.7.html
+//! [`read_at`]: crate::os::unix::fs::FileExt::read_at
+//! [`write_at`]: crate::os::unix::fs::FileExt::write_at
+//!
+//! # Examples
+//!
+//! Read two parts of a file at once:
+//!
+//! ```no_run
+//! #![feature(linux_io_uring)]
+//! use std::fs::File;
+//! use std::io::IoSliceMut;
+//! use std::os::fd::AsFd;
+//! use std::os::linux::io_uring::{self, Op};
+//!
+//! fn main() -> std::io::Result<()> {
+//!     let file = File::open("foo.db")?;
+//!     let (mut header, mut magic, mut page) = ([0; 16], [0; 4], [0; 4096]);
+//!     let mut bufs = [IoSliceMut::new(&mut header), IoSliceMut::new(&mut magic)];
+//!
+//!     let mut ops = [
+//!         Op::read_vectored_at(file.as_fd(), &mut bufs, 0),
+//!         Op::read_at(file.as_fd(), &mut page, 4096),
+//!     ];
+//!     io_uring::submit(&mut ops)?;
+//!     for op in &ops {
+//!         println!("read {} bytes", op.result().unwrap()?);
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+#![unstable(feature = "linux_io_uring", issue = "none")]
+
+use crate::fmt;
+use crate::io::{IoSlice, IoSliceMut, Result};
+use crate::os::fd::BorrowedFd;
+#[cfg(not(doc))]
+use crate::sys::linux::io_uring::{self as imp, IoUring as InnerIoUring, Op as InnerOp};
+
+#[cfg(doc)]
+struct InnerIoUring;
+#[cfg(doc)]
+struct InnerOp<'a>(crate::marker::PhantomData<&'a ()>);
+
+/// A read or write operation, to be run with [`submit`] or [`IoUring::submit`].
+///
+/// The operation borrows the file descriptor and buffers it uses. Once it has
+/// run, its outcome is available from [`result`](Op::result).
+///
+/// Operations on the same file descriptor in a batch may run in any order, and
+/// concurrently.
+#[repr(transparent)]
+pub struct Op<'a> {
+    inner: InnerOp<'a>,
+}
+
+impl<'a> Op<'a> {
+    /// Reads from `fd` into `buf`, starting at `offset`, like [`read_at`].
+    ///
+    /// [`read_at`]: crate::os::unix::fs::FileExt::read_at
+    pub fn read_at(fd: BorrowedFd<'a>, buf: &'a mut [u8], offset: u64) -> Op<'a> {
+        Op { inner: InnerOp::read(fd, buf, Some(offset)) }
+    }
+
+    /// Writes `buf` to `fd`, starting at `offset`, like [`write_at`].
+    ///
+    /// [`write_at`]: crate::os::unix::fs::FileExt::write_at
+    pub fn write_at(fd: BorrowedFd<'a>, buf: &'a [u8], offset: u64) -> Op<'a> {
+        Op { inner: InnerOp::write(fd, buf, Some(offset)) }
+    }
+
+    /// Reads from `fd` into `bufs`, starting at `offset`, like [`read_vectored_at`].
+    ///
+    /// [`read_vectored_at`]: crate::os::unix::fs::FileExt::read_vectored_at
+    pub fn read_vectored_at(
+        fd: BorrowedFd<'a>,
+        bufs: &'a mut [IoSliceMut<'a>],
+        offset: u64,
+    ) -> Op<'a> {
+        Op { inner: InnerOp::read_vectored(fd, bufs, Some(offset)) }
+    }
+
+    /// Writes `bufs` to `fd`, starting at `offset`, like [`write_vectored_at`].
+    ///
+    /// [`write_vectored_at`]: crate::os::unix::fs::FileExt::write_vectored_at
+    pub fn write_vectored_at(fd: BorrowedFd<'a>, bufs: &'a [IoSlice<'a>], offset: u64) -> Op<'a> {
+        Op { inner: InnerOp::write_vectored(fd, bufs, Some(offset)) }
+    }
+
+    /// Reads from `fd` into `buf` at its current position, like [`Read::read`].
+    ///
+    /// This is meant for sockets and pipes. Since the operations of a batch
+    /// may run in any order, the position of a file is unpredictable if more
+    /// than one of them uses it.
+    ///
+    /// [`Read::read`]: crate::io::Read::read
+    pub fn read(fd: BorrowedFd<'a>, buf: &'a mut [u8]) -> Op<'a> {
+        Op { inner: InnerOp::read(fd, buf, None) }
+    }
+
+    /// Writes `buf` to `fd` at its current position, like [`Write::write`].
+    ///
+    /// This is meant for sockets and pipes. Since the operations of a batch
+    /// may run in any order, the position of a file is unpredictable if more
+    /// than one of them uses it.
+    ///
+    /// [`Write::write`]: crate::io::Write::write
+    pub fn write(fd: BorrowedFd<'a>, buf: &'a [u8]) -> Op<'a> {
+        Op { inner: InnerOp::write(fd, buf, None) }
+    }
+
+    /// Returns the number of bytes read or written, or `None` if the operation
+    /// hasn't been run yet.
+    ///
+    /// Like with the corresponding system calls, fewer bytes than requested may
+    /// be read or written.
+    pub fn result(&self) -> Option<Result<usize>> {
+        self.inner.result()
+    }
+
+    fn as_inner_slice<'b>(ops: &'b mut [Op<'a>]) -> &'b mut [InnerOp<'a>] {
+        // SAFETY: `Op` is a transparent wrapper around `InnerOp`.
+        unsafe { &mut *(ops as *mut [Op<'a>] as *mut [InnerOp<'a>]) }
+    }
+}
+
+impl fmt::Debug for Op<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.inner.fmt(f)
+    }
+}
+
+/// An io_uring instance, i.e. a pair of submission and completion queues shared
+/// with the kernel.
+///
+/// Most programs can use [`submit`] instead, which uses a ring per thread and
+/// works on every kernel. Owning a ring is useful to pick its size, or to make
+/// sure that the operations are really batched.
+///
+/// # Examples
+///
+/// ```no_run
+/// #![feature(linux_io_uring)]
+/// use std::fs::File;
+/// use std::os::fd::AsFd;
+/// use std::os::linux::io_uring::{IoUring, Op};
+///
+/// fn main() -> std::io::Result<()> {
+///     let mut ring = IoUring::new(256)?;
+///     let file = File::create("foo.txt")?;
+///     let mut ops: Vec<_> = (0..1024)
+///         .map(|i| Op::write_at(file.as_fd(), b"0123456789abcdef", i * 16))
+///         .collect();
+///     // Takes 4 system calls.
+///     ring.submit(&mut ops)?;
+///     Ok(())
+/// }
+/// ```
+pub struct IoUring {
+    inner: InnerIoUring,
+}
+
+impl IoUring {
+    /// Creates an io_uring instance whose submission queue holds `entries`
+    /// operations, rounded up to a power of two.
+    ///
+    /// # Errors
+    ///
+    /// Fails with `ENOSYS` if the kernel doesn't support io_uring, with `EPERM`
+    /// if it is disabled, and with `EINVAL` if `entries` is 0 or too large.
+    pub fn new(entries: u32) -> Result<IoUring> {
+        InnerIoUring::new(entries).map(|inner| IoUring { inner })
+    }
+
+    /// Returns the number of entries of the submission queue, which is the most
+    /// operations that are submitted with a single system call.
+    pub fn entries(&self) -> u32 {
+        self.inner.entries()
+    }
+
+    /// Runs `ops` and waits for all of them to complete.
+    ///
+    /// The operations are submitted in batches of at most
+    /// [`entries`](IoUring::entries). The outcome of each operation is available
+    /// from [`Op::result`] afterwards.
+    ///
+    /// # Errors
+    ///
+    /// Only fails if the operations couldn't be submitted at all. If a batch
+    /// fails part way, the operations of that batch which ran still have their
+    /// results set, and those of later batches are left alone.
+    pub fn submit(&mut self, ops: &mut [Op<'_>]) -> Result<()> {
+        self.inner.submit(Op::as_inner_slice(ops))
+    }
+}
+
+impl fmt::Debug for IoUring {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IoUring").field("entries", &self.entries()).finish_non_exhaustive()
+    }
+}
+
+/// Runs `ops` and waits for all of them to complete, using an [`IoUring`] owned
+/// by the current thread.
+///
+/// If io_uring isn't available, the operations are run one after the other with
+/// regular system calls instead.
+///
+/// # Errors
+///
+/// See [`IoUring::submit`].
+pub fn submit(ops: &mut [Op<'_>]) -> Result<()> {
+    imp::submit(Op::as_inner_slice(ops))
+}
@@ -4,6 +4,7 @@
 #![doc(cfg(target_os = "linux"))]
 
 pub mod fs;
+pub mod io_uring;
 pub mod net;
 pub mod process;
 pub mod raw;
@@ -42,3 +42,42 @@ fn from(err: SendError<T>) -> SendTimeoutError<T> {
         }
     }
 }
+
+/// An error returned from the [`try_select`] method.
+///
+/// Failed because none of the channel operations were ready.
+///
+/// [`try_select`]: super::Select::try_select
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+#[unstable(feature = "mpmc_channel", issue = "126840")]
+pub struct TrySelectError;
+
+#[unstable(feature = "mpmc_channel", issue = "126840")]
+impl fmt::Display for TrySelectError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        "all operations in select would block".fmt(f)
+    }
+}
+
+#[unstable(feature = "mpmc_channel", issue = "126840")]
+impl error::Error for TrySelectError {}
+
+/// An error returned from the [`select_timeout`] and [`select_deadline`] methods.
+///
+/// Failed because none of the channel operations became ready before the timeout.
+///
+/// [`select_timeout`]: super::Select::select_timeout
+/// [`select_deadline`]: super::Select::select_deadline
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+#[unstable(feature = "mpmc_channel", issue = "126840")]
+pub struct SelectTimeoutError;
+
+#[unstable(feature = "mpmc_channel", issue = "126840")]
+impl fmt::Display for SelectTimeoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        "timed out waiting on select".fmt(f)
+    }
+}
+
+#[unstable(feature = "mpmc_channel", issue = "126840")]
+impl error::Error for SelectTimeoutError {}
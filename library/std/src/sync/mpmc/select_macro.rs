@@ -0,0 +1,170 @@
+//! The `select!` macro.
+
+/// Waits on multiple channel operations at once.
+///
+/// This macro allows you to define a set of channel operations, wait until any one of them
+/// becomes ready, and finally execute it. If multiple operations are ready at the same time, a
+/// random one among them is selected.
+///
+/// It is also possible to define a `default` case that gets executed if none of the operations
+/// are ready, either right away or for a certain duration of time.
+///
+/// An operation is considered to be ready if it doesn't have to block. Note that it is ready even
+/// when it will simply return an error because the channel is disconnected.
+///
+/// The cases are:
+///
+/// - `recv(r) -> res => body`: receives from `r`, binding the [`Result`] of [`recv`] to `res`.
+/// - `send(s, msg) -> res => body`: sends `msg` into `s`, binding the [`Result`] of [`send`] to
+///   `res`. `msg` is only evaluated if this operation is selected.
+/// - `default => body`: runs `body` if none of the operations are ready right away.
+/// - `default(timeout) => body`: runs `body` if none of the operations become ready within the
+///   [`Duration`] `timeout`.
+///
+/// The receivers and senders can belong to both [`mpmc`](self) and
+/// [`mpsc`](crate::sync::mpsc) channels. A `default` case, if any, must come last.
+///
+/// `select!` is a convenience wrapper around [`Select`], which can also select over a
+/// dynamically created list of channel operations.
+///
+/// [`recv`]: SelectedOperation::recv
+/// [`send`]: SelectedOperation::send
+/// [`Duration`]: crate::time::Duration
+///
+/// # Examples
+///
+/// Receive a message from whichever channel gets one first, giving up after a second:
+///
+/// ```
+/// #![feature(mpmc_channel)]
+///
+/// use std::sync::mpmc::{channel, select};
+/// use std::thread;
+/// use std::time::Duration;
+///
+/// let (s1, r1) = channel();
+/// let (s2, r2) = channel::<i32>();
+///
+/// thread::spawn(move || s1.send(10).unwrap());
+///
+/// select! {
+///     recv(r1) -> msg => assert_eq!(msg, Ok(10)),
+///     recv(r2) -> msg => panic!("unexpected message: {msg:?}"),
+///     default(Duration::from_secs(1)) => println!("timed out"),
+/// }
+/// # drop(s2);
+/// ```
+///
+/// Send a message if the channel isn't full, without blocking:
+///
+/// ```
+/// #![feature(mpmc_channel)]
+///
+/// use std::sync::mpmc::{select, sync_channel};
+///
+/// let (s, r) = sync_channel(1);
+///
+/// for i in 0..2 {
+///     select! {
+///         send(s, i) -> res => res.unwrap(),
+///         default => println!("the channel is full, dropping {i}"),
+///     }
+/// }
+/// assert_eq!(r.recv(), Ok(0));
+/// ```
+#[unstable(feature = "mpmc_channel", issue = "126840")]
+#[allow_internal_unstable(mpmc_channel)]
+#[rustc_macro_transparency = "semitransparent"]
+pub macro select {
+    // Skips the comma after a case whose body is a block.
+    (@parse $sel:ident [$($cases:tt)*] , $($rest:tt)*) => {
+        $crate::sync::mpmc::select!(@parse $sel [$($cases)*] $($rest)*)
+    },
+
+    (@parse $sel:ident [$($cases:tt)*] recv($r:expr) -> $res:pat => $body:block $($rest:tt)*) => {
+        $crate::sync::mpmc::select!(@recv $sel [$($cases)*] ($r) ($res) ($body) $($rest)*)
+    },
+    (@parse $sel:ident [$($cases:tt)*]
+        recv($r:expr) -> $res:pat => $body:expr $(, $($rest:tt)*)?) => {
+        $crate::sync::mpmc::select!(@recv $sel [$($cases)*] ($r) ($res) ($body) $($($rest)*)?)
+    },
+    (@parse $sel:ident [$($cases:tt)*]
+        send($s:expr, $msg:expr) -> $res:pat => $body:block $($rest:tt)*) => {
+        $crate::sync::mpmc::select!(@send $sel [$($cases)*] ($s) ($msg) ($res) ($body) $($rest)*)
+    },
+    (@parse $sel:ident [$($cases:tt)*]
+        send($s:expr, $msg:expr) -> $res:pat => $body:expr $(, $($rest:tt)*)?) => {
+        $crate::sync::mpmc::select!(
+            @send $sel [$($cases)*] ($s) ($msg) ($res) ($body) $($($rest)*)?
+        )
+    },
+
+    // All operations are registered, so select one of them. The handles of the operations are
+    // passed along by name, since they were bound in the expansions that registered them.
+    (@parse $sel:ident [$($cases:tt)*]) => {{
+        let oper = $sel.select();
+        $crate::sync::mpmc::select!(@dispatch oper [$($cases)*])
+    }},
+    (@parse $sel:ident [$($cases:tt)*] default => $body:expr $(,)?) => {
+        match $sel.try_select() {
+            $crate::result::Result::Ok(oper) => {
+                $crate::sync::mpmc::select!(@dispatch oper [$($cases)*])
+            }
+            $crate::result::Result::Err(_) => $body,
+        }
+    },
+    (@parse $sel:ident [$($cases:tt)*] default($timeout:expr) => $body:expr $(,)?) => {
+        match $sel.select_timeout($timeout) {
+            $crate::result::Result::Ok(oper) => {
+                $crate::sync::mpmc::select!(@dispatch oper [$($cases)*])
+            }
+            $crate::result::Result::Err(_) => $body,
+        }
+    },
+    (@parse $($tokens:tt)*) => {
+        $crate::compile_error!(
+            "expected `recv(r) -> res => body`, `send(s, msg) -> res => body`, \
+             or a final `default` case in `select!`"
+        )
+    },
+
+    (@recv $sel:ident [$($cases:tt)*] ($r:expr) ($res:pat) ($body:expr) $($rest:tt)*) => {{
+        let r = &$r;
+        let index = $sel.recv(r);
+        $crate::sync::mpmc::select!(@parse $sel [$($cases)* (recv r index ($res) ($body))] $($rest)*)
+    }},
+    (@send $sel:ident [$($cases:tt)*] ($s:expr) ($msg:expr) ($res:pat) ($body:expr) $($rest:tt)*) => {{
+        let s = &$s;
+        let index = $sel.send(s);
+        $crate::sync::mpmc::select!(
+            @parse $sel [$($cases)* (send s index ($msg) ($res) ($body))] $($rest)*
+        )
+    }},
+
+    (@dispatch $oper:ident []) => {
+        $crate::unreachable!()
+    },
+    (@dispatch $oper:ident
+        [(recv $r:ident $index:ident ($res:pat) ($body:expr)) $($rest:tt)*]) => {
+        if $oper.index() == $index {
+            let $res = $oper.recv($r);
+            $body
+        } else {
+            $crate::sync::mpmc::select!(@dispatch $oper [$($rest)*])
+        }
+    },
+    (@dispatch $oper:ident
+        [(send $s:ident $index:ident ($msg:expr) ($res:pat) ($body:expr)) $($rest:tt)*]) => {
+        if $oper.index() == $index {
+            let $res = $oper.send($s, $msg);
+            $body
+        } else {
+            $crate::sync::mpmc::select!(@dispatch $oper [$($rest)*])
+        }
+    },
+
+    ($($case:tt)+) => {{
+        let mut sel = $crate::sync::mpmc::Select::new();
+        $crate::sync::mpmc::select!(@parse sel [] $($case)+)
+    }},
+}
@@ -0,0 +1,293 @@
+use super::*;
+use crate::sync::mpsc;
+use crate::thread;
+use crate::time::{Duration, Instant};
+
+const MS: Duration = Duration::from_millis(1);
+
+#[test]
+fn select_smoke() {
+    let (s1, r1) = channel::<i32>();
+    let (s2, r2) = channel::<i32>();
+
+    s1.send(1).unwrap();
+    let mut sel = Select::new();
+    let oper1 = sel.recv(&r1);
+    let oper2 = sel.recv(&r2);
+    let oper = sel.select();
+    assert_eq!(oper.index(), oper1);
+    assert_eq!(oper.recv(&r1), Ok(1));
+
+    s2.send(2).unwrap();
+    let oper = sel.select();
+    assert_eq!(oper.index(), oper2);
+    assert_eq!(oper.recv(&r2), Ok(2));
+}
+
+#[test]
+fn select_disconnected() {
+    let (s1, r1) = channel::<i32>();
+    let (s2, r2) = sync_channel::<i32>(0);
+
+    drop(s1);
+    let mut sel = Select::new();
+    let oper1 = sel.recv(&r1);
+    sel.recv(&r2);
+    let oper = sel.select();
+    assert_eq!(oper.index(), oper1);
+    assert_eq!(oper.recv(&r1), Err(RecvError));
+
+    // A removed operation can't be selected anymore.
+    sel.remove(oper1);
+    assert!(sel.try_select().is_err());
+    drop(s2);
+}
+
+#[test]
+fn select_nothing_ready() {
+    let (_s1, r1) = channel::<i32>();
+    let (_s2, r2) = sync_channel::<i32>(0);
+
+    let mut sel = Select::new();
+    sel.recv(&r1);
+    sel.recv(&r2);
+    assert_eq!(sel.try_select().unwrap_err(), TrySelectError);
+
+    let start = Instant::now();
+    assert_eq!(sel.select_timeout(100 * MS).unwrap_err(), SelectTimeoutError);
+    assert!(start.elapsed() >= 100 * MS);
+
+    // Without any operations, select just waits for the timeout.
+    assert!(Select::new().select_timeout(10 * MS).is_err());
+}
+
+#[test]
+fn select_blocks_until_ready() {
+    let (s1, r1) = sync_channel::<i32>(0);
+    let (s2, r2) = channel::<i32>();
+
+    let t = thread::spawn(move || {
+        thread::sleep(50 * MS);
+        s1.send(1).unwrap();
+        thread::sleep(50 * MS);
+        s2.send(2).unwrap();
+    });
+
+    for _ in 0..2 {
+        let mut sel = Select::new();
+        let oper1 = sel.recv(&r1);
+        let oper2 = sel.recv(&r2);
+        let oper = sel.select_timeout(Duration::from_secs(10)).unwrap();
+        match oper.index() {
+            i if i == oper1 => assert_eq!(oper.recv(&r1), Ok(1)),
+            i if i == oper2 => assert_eq!(oper.recv(&r2), Ok(2)),
+            _ => unreachable!(),
+        }
+    }
+    t.join().unwrap();
+}
+
+#[test]
+fn select_send() {
+    let (s1, r1) = sync_channel::<i32>(1);
+    let (s2, r2) = sync_channel::<i32>(0);
+
+    let t = thread::spawn(move || {
+        thread::sleep(50 * MS);
+        assert_eq!(r2.recv(), Ok(2));
+    });
+
+    s1.send(0).unwrap();
+    let mut sel = Select::new();
+    sel.send(&s1);
+    let oper2 = sel.send(&s2);
+    // The bounded channel is full, so this pairs up with the receiver on the other thread.
+    let oper = sel.select();
+    assert_eq!(oper.index(), oper2);
+    assert_eq!(oper.send(&s2, 2), Ok(()));
+    t.join().unwrap();
+
+    assert_eq!(r1.recv(), Ok(0));
+    sel.remove(oper2);
+    let oper = sel.try_select().unwrap();
+    assert_eq!(oper.send(&s1, 1), Ok(()));
+    assert_eq!(r1.recv(), Ok(1));
+}
+
+#[test]
+fn select_zero_capacity_both_sides() {
+    let (s, r) = sync_channel::<usize>(0);
+    const N: usize = 1000;
+
+    let t = thread::spawn(move || {
+        for i in 0..N {
+            let mut sel = Select::new();
+            sel.send(&s);
+            sel.select().send(&s, i).unwrap();
+        }
+    });
+
+    for i in 0..N {
+        let mut sel = Select::new();
+        sel.recv(&r);
+        assert_eq!(sel.select().recv(&r), Ok(i));
+    }
+    t.join().unwrap();
+}
+
+#[test]
+fn select_is_fair() {
+    let (s1, r1) = channel::<()>();
+    let (s2, r2) = channel::<()>();
+    const N: usize = 1000;
+
+    for _ in 0..N {
+        s1.send(()).unwrap();
+        s2.send(()).unwrap();
+    }
+
+    let mut hits = [0; 2];
+    for _ in 0..N {
+        let mut sel = Select::new();
+        let oper1 = sel.recv(&r1);
+        let oper2 = sel.recv(&r2);
+        let oper = sel.select();
+        let index = oper.index();
+        match index {
+            i if i == oper1 => oper.recv(&r1).unwrap(),
+            i if i == oper2 => oper.recv(&r2).unwrap(),
+            _ => unreachable!(),
+        }
+        hits[index] += 1;
+    }
+    assert!(hits.iter().all(|x| *x >= N / 4));
+}
+
+#[test]
+fn select_mpsc() {
+    let (s1, r1) = mpsc::channel::<i32>();
+    let (s2, r2) = mpsc::sync_channel::<i32>(0);
+    let (s3, r3) = channel::<i32>();
+
+    let t = thread::spawn(move || {
+        assert_eq!(r2.recv(), Ok(2));
+    });
+
+    let mut sel = Select::new();
+    let oper1 = sel.recv(&r1);
+    let oper2 = sel.send(&s2);
+    let oper3 = sel.recv(&r3);
+    let oper = sel.select();
+    assert_eq!(oper.index(), oper2);
+    assert_eq!(oper.send(&s2, 2), Ok(()));
+    t.join().unwrap();
+
+    s1.send(1).unwrap();
+    sel.remove(oper2);
+    let oper = sel.select();
+    assert_eq!(oper.index(), oper1);
+    assert_eq!(oper.recv(&r1), Ok(1));
+
+    s3.send(3).unwrap();
+    let oper = sel.select();
+    assert_eq!(oper.index(), oper3);
+    assert_eq!(oper.recv(&r3), Ok(3));
+}
+
+#[test]
+#[should_panic(expected = "passed a receiver that wasn't selected")]
+fn selected_operation_checks_receiver() {
+    let (s1, r1) = channel::<i32>();
+    let (_s2, r2) = channel::<i32>();
+
+    s1.send(1).unwrap();
+    let mut sel = Select::new();
+    sel.recv(&r1);
+    sel.recv(&r2);
+    let _ = sel.select().recv(&r2);
+}
+
+#[test]
+#[should_panic(expected = "dropped `SelectedOperation` without completing the operation")]
+fn selected_operation_must_be_completed() {
+    let (s, r) = channel::<i32>();
+
+    s.send(1).unwrap();
+    let mut sel = Select::new();
+    sel.recv(&r);
+    drop(sel.select());
+}
+
+#[test]
+fn select_macro() {
+    let (s1, r1) = channel::<i32>();
+    let (s2, r2) = sync_channel::<i32>(1);
+
+    s1.send(1).unwrap();
+    let msg = select! {
+        recv(r1) -> msg => msg.unwrap(),
+        recv(r2) -> _ => unreachable!(),
+    };
+    assert_eq!(msg, 1);
+
+    // Block bodies don't need a comma, and messages are only evaluated if they are selected.
+    let mut evaluated = 0;
+    select! {
+        recv(r1) -> _ => { unreachable!() }
+        send(s2, { evaluated += 1; 2 }) -> res => { res.unwrap() }
+    }
+    select! {
+        send(s2, { evaluated += 1; 3 }) -> res => res.unwrap(),
+        default => {}
+    }
+    assert_eq!(evaluated, 1);
+    assert_eq!(r2.recv(), Ok(2));
+
+    let start = Instant::now();
+    let timed_out = select! {
+        recv(r1) -> _ => false,
+        recv(r2) -> _ => false,
+        default(50 * MS) => true,
+    };
+    assert!(timed_out);
+    assert!(start.elapsed() >= 50 * MS);
+
+    // Control flow in the bodies applies to the surrounding code.
+    s1.send(1).unwrap();
+    drop(s1);
+    let mut received = 0;
+    loop {
+        select! {
+            recv(r1) -> msg => match msg {
+                Ok(_) => received += 1,
+                Err(_) => break,
+            }
+        }
+    }
+    assert_eq!(received, 1);
+}
+
+#[test]
+fn select_macro_mpsc() {
+    let (s, r) = mpsc::sync_channel::<i32>(0);
+    let (done_s, done_r) = channel::<()>();
+
+    let t = thread::spawn(move || {
+        for i in 0..10 {
+            select! {
+                send(s, i) -> res => res.unwrap(),
+            }
+        }
+        done_s.send(()).unwrap();
+    });
+
+    let mut received = Vec::new();
+    loop {
+        select! {
+            recv(r) -> msg => received.push(msg.unwrap()),
+            recv(done_r) -> _ => break,
+        }
+    }
+    t.join().unwrap();
+    assert_eq!(received, (0..10).collect::<Vec<_>>());
+}
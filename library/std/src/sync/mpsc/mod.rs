@@ -144,9 +144,8 @@
 mod sync_tests;
 
 // MPSC channels are built as a wrapper around MPMC channels, which
-// were ported from the `crossbeam-channel` crate. MPMC channels are
-// not exposed publicly, but if you are curious about the implementation,
-// that's where everything is.
+// were ported from the `crossbeam-channel` crate. If you are curious
+// about the implementation, that's where everything is.
 
 use crate::sync::mpmc;
 use crate::time::{Duration, Instant};
@@ -1092,6 +1091,39 @@ fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
     }
 }
 
+/// Allows `Sender`s to be used with `mpmc::Select`.
+#[unstable(feature = "sealed", issue = "none")]
+impl<T> crate::sealed::Sealed for Sender<T> {}
+
+#[unstable(feature = "mpmc_channel", issue = "126840")]
+impl<T> mpmc::SelectSender<T> for Sender<T> {
+    fn as_sender(&self) -> &mpmc::Sender<T> {
+        &self.inner
+    }
+}
+
+/// Allows `SyncSender`s to be used with `mpmc::Select`.
+#[unstable(feature = "sealed", issue = "none")]
+impl<T> crate::sealed::Sealed for SyncSender<T> {}
+
+#[unstable(feature = "mpmc_channel", issue = "126840")]
+impl<T> mpmc::SelectSender<T> for SyncSender<T> {
+    fn as_sender(&self) -> &mpmc::Sender<T> {
+        &self.inner
+    }
+}
+
+/// Allows `Receiver`s to be used with `mpmc::Select`.
+#[unstable(feature = "sealed", issue = "none")]
+impl<T> crate::sealed::Sealed for Receiver<T> {}
+
+#[unstable(feature = "mpmc_channel", issue = "126840")]
+impl<T> mpmc::SelectReceiver<T> for Receiver<T> {
+    fn as_receiver(&self) -> &mpmc::Receiver<T> {
+        &self.inner
+    }
+}
+
 #[stable(feature = "rust1", since = "1.0.0")]
 impl<T> fmt::Debug for SendError<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -0,0 +1,509 @@
+//! A minimal io_uring instance for submitting batches of reads and writes with a
+//! single `io_uring_enter` call.
+//!
+//! Only the subset of io_uring needed for positional and streaming vectored I/O
+//! is implemented: no polling mode, no registered files or buffers and no linked
+//! operations. `submit` always waits until every operation it submitted has
+//! completed, so operations can safely borrow their buffers.
+//!
+//! Kernels older than 5.1, or systems where io_uring is disabled, reject
+//! `io_uring_setup` with `ENOSYS` or `EPERM`. [`submit`] detects that once and
+//! falls back to running the operations with plain `preadv`/`pwritev` calls.
+//!
+//! Using the current file position, as streaming reads and writes do, needs
+//! `IORING_FEAT_RW_CUR_POS` from Linux 5.6. Earlier kernels reject an offset of -1
+//! with `EINVAL`, so rings created there run those operations with plain
+//! `readv`/`writev` calls and only submit the positional ones.
+
+use crate::cell::RefCell;
+use crate::marker::PhantomData;
+use crate::mem::ManuallyDrop;
+use crate::os::fd::{AsRawFd, BorrowedFd, FromRawFd};
+use crate::sync::atomic::{AtomicU32, AtomicU8, Ordering};
+use crate::sys::cvt;
+use crate::sys::pal::unix::fd::FileDesc;
+use crate::{cmp, fmt, io, mem, ptr, slice};
+
+#[cfg(test)]
+mod tests;
+
+const IORING_OFF_SQ_RING: libc::off_t = 0;
+const IORING_OFF_CQ_RING: libc::off_t = 0x8000000;
+const IORING_OFF_SQES: libc::off_t = 0x10000000;
+
+const IORING_OP_READV: u8 = 1;
+const IORING_OP_WRITEV: u8 = 2;
+
+const IORING_ENTER_GETEVENTS: libc::c_uint = 1;
+
+const IORING_FEAT_RW_CUR_POS: u32 = 1 << 3;
+
+/// Number of entries of the per-thread ring used by [`submit`].
+const DEFAULT_ENTRIES: u32 = 64;
+
+#[repr(C)]
+#[derive(Default)]
+struct SqringOffsets {
+    head: u32,
+    tail: u32,
+    ring_mask: u32,
+    ring_entries: u32,
+    flags: u32,
+    dropped: u32,
+    array: u32,
+    resv1: u32,
+    user_addr: u64,
+}
+
+#[repr(C)]
+#[derive(Default)]
+struct CqringOffsets {
+    head: u32,
+    tail: u32,
+    ring_mask: u32,
+    ring_entries: u32,
+    overflow: u32,
+    cqes: u32,
+    flags: u32,
+    resv1: u32,
+    user_addr: u64,
+}
+
+/// `struct io_uring_params`
+#[repr(C)]
+#[derive(Default)]
+struct Params {
+    sq_entries: u32,
+    cq_entries: u32,
+    flags: u32,
+    sq_thread_cpu: u32,
+    sq_thread_idle: u32,
+    features: u32,
+    wq_fd: u32,
+    resv: [u32; 3],
+    sq_off: SqringOffsets,
+    cq_off: CqringOffsets,
+}
+
+/// `struct io_uring_sqe`, with the unions flattened to the members used here.
+#[repr(C)]
+struct Sqe {
+    opcode: u8,
+    flags: u8,
+    ioprio: u16,
+    fd: i32,
+    off: u64,
+    addr: u64,
+    len: u32,
+    rw_flags: u32,
+    user_data: u64,
+    buf_index: u16,
+    personality: u16,
+    splice_fd_in: i32,
+    addr3: u64,
+    pad: u64,
+}
+
+/// `struct io_uring_cqe`
+#[repr(C)]
+struct Cqe {
+    user_data: u64,
+    res: i32,
+    flags: u32,
+}
+
+/// A memory mapping of one of the rings, unmapped on drop.
+struct Mmap {
+    ptr: *mut libc::c_void,
+    len: usize,
+}
+
+impl Mmap {
+    fn new(fd: &FileDesc, len: usize, offset: libc::off_t) -> io::Result<Mmap> {
+        let ptr = unsafe {
+            libc::mmap(
+                ptr::null_mut(),
+                len,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_SHARED | libc::MAP_POPULATE,
+                fd.as_raw_fd(),
+                offset,
+            )
+        };
+        if ptr == libc::MAP_FAILED {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(Mmap { ptr, len })
+        }
+    }
+
+    /// # Safety
+    ///
+    /// `offset` must be in bounds and suitably aligned for a `T`.
+    unsafe fn at<T>(&self, offset: u32) -> *mut T {
+        unsafe { self.ptr.byte_add(offset as usize).cast() }
+    }
+}
+
+impl Drop for Mmap {
+    fn drop(&mut self) {
+        unsafe { libc::munmap(self.ptr, self.len) };
+    }
+}
+
+pub struct IoUring {
+    // Field order matters: the rings must be unmapped before the ring fd is closed.
+    sq_ring: Mmap,
+    cq_ring: Mmap,
+    sqes: Mmap,
+    fd: FileDesc,
+
+    sq_head: *const AtomicU32,
+    sq_tail: *const AtomicU32,
+    sq_mask: u32,
+    sq_array: *mut u32,
+    sq_entries: u32,
+    /// Whether the kernel accepts an offset of -1 for the current file position.
+    rw_cur_pos: bool,
+
+    cq_head: *const AtomicU32,
+    cq_tail: *const AtomicU32,
+    cq_mask: u32,
+    cqes: *const Cqe,
+}
+
+// The ring is only ever accessed through `&mut self`, so the raw pointers into the
+// shared mappings don't prevent moving it to another thread.
+unsafe impl Send for IoUring {}
+
+impl IoUring {
+    pub fn new(entries: u32) -> io::Result<IoUring> {
+        let mut params = Params::default();
+        let fd = cvt(unsafe {
+            libc::syscall(libc::SYS_io_uring_setup, entries, &mut params as *mut Params)
+        })?;
+        let fd = unsafe { FileDesc::from_raw_fd(fd as libc::c_int) };
+
+        let sq_off = &params.sq_off;
+        let cq_off = &params.cq_off;
+        let sq_len = sq_off.array as usize + params.sq_entries as usize * mem::size_of::<u32>();
+        let cq_len = cq_off.cqes as usize + params.cq_entries as usize * mem::size_of::<Cqe>();
+        let sq_ring = Mmap::new(&fd, sq_len, IORING_OFF_SQ_RING)?;
+        let cq_ring = Mmap::new(&fd, cq_len, IORING_OFF_CQ_RING)?;
+        let sqes =
+            Mmap::new(&fd, params.sq_entries as usize * mem::size_of::<Sqe>(), IORING_OFF_SQES)?;
+
+        // SAFETY: the offsets were provided by the kernel for these mappings.
+        unsafe {
+            Ok(IoUring {
+                sq_head: sq_ring.at(sq_off.head),
+                sq_tail: sq_ring.at(sq_off.tail),
+                sq_mask: *sq_ring.at::<u32>(sq_off.ring_mask),
+                sq_array: sq_ring.at(sq_off.array),
+                sq_entries: params.sq_entries,
+                rw_cur_pos: params.features & IORING_FEAT_RW_CUR_POS != 0,
+                cq_head: cq_ring.at(cq_off.head),
+                cq_tail: cq_ring.at(cq_off.tail),
+                cq_mask: *cq_ring.at::<u32>(cq_off.ring_mask),
+                cqes: cq_ring.at(cq_off.cqes),
+                sq_ring,
+                cq_ring,
+                sqes,
+                fd,
+            })
+        }
+    }
+
+    pub fn entries(&self) -> u32 {
+        self.sq_entries
+    }
+
+    /// Submits `ops` and waits for all of them to complete, in batches of at most
+    /// `entries()` operations.
+    ///
+    /// An error is only returned if the operations couldn't be submitted; the
+    /// outcome of each submitted operation is stored in it.
+    pub fn submit(&mut self, ops: &mut [Op<'_>]) -> io::Result<()> {
+        for batch in ops.chunks_mut(self.sq_entries as usize) {
+            self.submit_batch(batch)?;
+        }
+        Ok(())
+    }
+
+    fn submit_batch(&mut self, ops: &mut [Op<'_>]) -> io::Result<()> {
+        // We are the only writer of the submission queue tail, and the kernel only
+        // reads it during `io_uring_enter`.
+        let base = unsafe { (*self.sq_tail).load(Ordering::Relaxed) };
+        let mut queued = 0;
+        for (i, op) in ops.iter_mut().enumerate() {
+            op.res = None;
+            if op.offset.is_none() && !self.rw_cur_pos {
+                op.run_blocking();
+                continue;
+            }
+            let tail = base.wrapping_add(queued);
+            let index = tail & self.sq_mask;
+            let (addr, len) = op.iovecs();
+            let sqe = Sqe {
+                opcode: match op.kind {
+                    Kind::Read => IORING_OP_READV,
+                    Kind::Write => IORING_OP_WRITEV,
+                },
+                flags: 0,
+                ioprio: 0,
+                fd: op.fd.as_raw_fd(),
+                // An offset of -1 means the current file position, which is
+                // also what the kernel does for sockets and pipes.
+                off: op.offset.unwrap_or(u64::MAX),
+                addr: addr as u64,
+                len: len as u32,
+                rw_flags: 0,
+                user_data: i as u64,
+                buf_index: 0,
+                personality: 0,
+                splice_fd_in: 0,
+                addr3: 0,
+                pad: 0,
+            };
+            unsafe {
+                self.sqes.at::<Sqe>(0).add(index as usize).write(sqe);
+                self.sq_array.add(index as usize).write(index);
+            }
+            queued += 1;
+        }
+
+        let mut pending = queued;
+        unsafe { (*self.sq_tail).store(base.wrapping_add(pending), Ordering::Release) };
+
+        let mut completed = 0;
+        let mut error = None;
+        loop {
+            completed += self.reap(ops);
+            if completed == pending {
+                return error.map_or(Ok(()), Err);
+            }
+
+            // The kernel advances the head as it consumes entries, which is more
+            // reliable than the return value if waiting was interrupted.
+            let submitted = unsafe { (*self.sq_head).load(Ordering::Acquire) }.wrapping_sub(base);
+            let ret = cvt(unsafe {
+                libc::syscall(
+                    libc::SYS_io_uring_enter,
+                    self.fd.as_raw_fd(),
+                    pending - submitted,
+                    pending - completed,
+                    IORING_ENTER_GETEVENTS,
+                    ptr::null::<libc::sigset_t>(),
+                    0usize,
+                )
+            });
+            match ret {
+                Ok(_) => {}
+                Err(e) if e.is_interrupted() => {}
+                Err(e) if matches!(e.raw_os_error(), Some(libc::EAGAIN | libc::EBUSY)) => {}
+                Err(e) => {
+                    let submitted =
+                        unsafe { (*self.sq_head).load(Ordering::Acquire) }.wrapping_sub(base);
+                    if error.is_some() || submitted == pending {
+                        // The kernel may still be writing into buffers we are
+                        // about to give back to the caller.
+                        rtabort!("io_uring_enter failed with operations in flight: {e}");
+                    }
+                    // Withdraw the entries the kernel hasn't seen yet, and wait
+                    // for the ones it has before reporting the error.
+                    unsafe {
+                        (*self.sq_tail).store(base.wrapping_add(submitted), Ordering::Release)
+                    };
+                    pending = submitted;
+                    error = Some(e);
+                }
+            }
+        }
+    }
+
+    /// Stores the results of all available completions in `ops`, returning how many
+    /// there were.
+    fn reap(&mut self, ops: &mut [Op<'_>]) -> u32 {
+        let head = unsafe { (*self.cq_head).load(Ordering::Relaxed) };
+        let tail = unsafe { (*self.cq_tail).load(Ordering::Acquire) };
+        let mut n = 0;
+        while head.wrapping_add(n) != tail {
+            let cqe = unsafe { &*self.cqes.add((head.wrapping_add(n) & self.cq_mask) as usize) };
+            let res = cqe.res;
+            ops[cqe.user_data as usize].res =
+                Some(if res < 0 { Err(-res) } else { Ok(res as usize) });
+            n += 1;
+        }
+        unsafe { (*self.cq_head).store(tail, Ordering::Release) };
+        n
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Kind {
+    Read,
+    Write,
+}
+
+enum Bufs {
+    Single(libc::iovec),
+    Vectored(*const libc::iovec, usize),
+}
+
+/// A read or write to be submitted to an [`IoUring`].
+pub struct Op<'a> {
+    fd: BorrowedFd<'a>,
+    kind: Kind,
+    bufs: Bufs,
+    offset: Option<u64>,
+    res: Option<Result<usize, i32>>,
+    _buffers: PhantomData<&'a mut [u8]>,
+}
+
+// `Op` only stores the buffers it borrows as raw pointers so that they can be handed
+// to the kernel; it is as thread safe as the borrows themselves.
+unsafe impl Send for Op<'_> {}
+unsafe impl Sync for Op<'_> {}
+
+impl fmt::Debug for Op<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Op")
+            .field("fd", &self.fd)
+            .field("kind", &self.kind)
+            .field("offset", &self.offset)
+            .field("result", &self.res)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'a> Op<'a> {
+    fn new(fd: BorrowedFd<'a>, kind: Kind, bufs: Bufs, offset: Option<u64>) -> Op<'a> {
+        Op { fd, kind, bufs, offset, res: None, _buffers: PhantomData }
+    }
+
+    fn single(buf: *const u8, len: usize) -> Bufs {
+        // The result of a completion is an `i32`.
+        Bufs::Single(libc::iovec {
+            iov_base: buf as *mut libc::c_void,
+            iov_len: cmp::min(len, i32::MAX as usize),
+        })
+    }
+
+    fn vectored(bufs: *const libc::iovec, len: usize) -> Bufs {
+        Bufs::Vectored(bufs, cmp::min(len, libc::UIO_MAXIOV as usize))
+    }
+
+    pub fn read(fd: BorrowedFd<'a>, buf: &'a mut [u8], offset: Option<u64>) -> Op<'a> {
+        Op::new(fd, Kind::Read, Op::single(buf.as_mut_ptr(), buf.len()), offset)
+    }
+
+    pub fn write(fd: BorrowedFd<'a>, buf: &'a [u8], offset: Option<u64>) -> Op<'a> {
+        Op::new(fd, Kind::Write, Op::single(buf.as_ptr(), buf.len()), offset)
+    }
+
+    pub fn read_vectored(
+        fd: BorrowedFd<'a>,
+        bufs: &'a mut [io::IoSliceMut<'a>],
+        offset: Option<u64>,
+    ) -> Op<'a> {
+        // `IoSliceMut` is a transparent wrapper around `iovec`.
+        Op::new(fd, Kind::Read, Op::vectored(bufs.as_mut_ptr().cast(), bufs.len()), offset)
+    }
+
+    pub fn write_vectored(
+        fd: BorrowedFd<'a>,
+        bufs: &'a [io::IoSlice<'a>],
+        offset: Option<u64>,
+    ) -> Op<'a> {
+        Op::new(fd, Kind::Write, Op::vectored(bufs.as_ptr().cast(), bufs.len()), offset)
+    }
+
+    pub fn result(&self) -> Option<io::Result<usize>> {
+        self.res.map(|res| res.map_err(io::Error::from_raw_os_error))
+    }
+
+    /// Returns the `iovec` array to pass to the kernel. This points into `self` for
+    /// single buffers, so `self` must not move until the operation has completed.
+    fn iovecs(&self) -> (*const libc::iovec, usize) {
+        match &self.bufs {
+            Bufs::Single(iov) => (iov, 1),
+            Bufs::Vectored(iovs, len) => (*iovs, *len),
+        }
+    }
+
+    /// Runs the operation with a regular system call.
+    fn run_blocking(&mut self) {
+        let fd = ManuallyDrop::new(unsafe { FileDesc::from_raw_fd(self.fd.as_raw_fd()) });
+        let (iovs, len) = self.iovecs();
+        let res = unsafe {
+            match self.kind {
+                Kind::Read => {
+                    let bufs = slice::from_raw_parts_mut(iovs as *mut io::IoSliceMut<'_>, len);
+                    match self.offset {
+                        Some(offset) => fd.read_vectored_at(bufs, offset),
+                        None => fd.read_vectored(bufs),
+                    }
+                }
+                Kind::Write => {
+                    let bufs = slice::from_raw_parts(iovs as *const io::IoSlice<'_>, len);
+                    match self.offset {
+                        Some(offset) => fd.write_vectored_at(bufs, offset),
+                        None => fd.write_vectored(bufs),
+                    }
+                }
+            }
+        };
+        self.res = Some(res.map_err(|e| e.raw_os_error().unwrap_or(libc::EIO)));
+    }
+}
+
+/// Runs `ops` on a lazily created per-thread [`IoUring`], or one after the other
+/// with regular system calls if io_uring isn't available.
+pub fn submit(ops: &mut [Op<'_>]) -> io::Result<()> {
+    const NOT_PROBED: u8 = 0;
+    const UNAVAILABLE: u8 = 1;
+    const AVAILABLE: u8 = 2;
+
+    // Kernels prior to 5.1 don't have io_uring, and it can be disabled with the
+    // `kernel.io_uring_disabled` sysctl or by seccomp filters (e.g. Docker's
+    // default profile). We store the availability in a global to avoid
+    // unnecessary syscalls.
+    static HAS_IO_URING: AtomicU8 = AtomicU8::new(NOT_PROBED);
+
+    thread_local! {
+        static RING: RefCell<Option<IoUring>> = const { RefCell::new(None) };
+    }
+
+    let fallback = |ops: &mut [Op<'_>]| {
+        ops.iter_mut().for_each(Op::run_blocking);
+        Ok(())
+    };
+
+    if HAS_IO_URING.load(Ordering::Relaxed) == UNAVAILABLE {
+        return fallback(ops);
+    }
+
+    // The ring may already be borrowed if this is called from a thread local
+    // destructor, in which case we also fall back.
+    RING.try_with(|ring| {
+        let Ok(mut ring) = ring.try_borrow_mut() else { return fallback(ops) };
+        if ring.is_none() {
+            match IoUring::new(DEFAULT_ENTRIES) {
+                Ok(new) => {
+                    HAS_IO_URING.store(AVAILABLE, Ordering::Relaxed);
+                    *ring = Some(new);
+                }
+                Err(e) => {
+                    if matches!(e.raw_os_error(), Some(libc::ENOSYS | libc::EPERM)) {
+                        HAS_IO_URING.store(UNAVAILABLE, Ordering::Relaxed);
+                    }
+                    // Other errors, such as running out of locked memory, may be
+                    // temporary.
+                    return fallback(ops);
+                }
+            }
+        }
+        ring.as_mut().unwrap().submit(ops)
+    })
+    .unwrap_or_else(|_| fallback(ops))
+}
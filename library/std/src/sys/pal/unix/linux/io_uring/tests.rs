@@ -0,0 +1,131 @@
+use super::{submit, IoUring, Op};
+use crate::fs::{self, File};
+use crate::io::{self, IoSlice, IoSliceMut, Read};
+use crate::os::fd::AsFd;
+use crate::os::unix::net::UnixStream;
+use crate::sys_common::io::test::tmpdir;
+
+/// Returns `None` if io_uring isn't supported, e.g. because of an old kernel or
+/// a seccomp filter.
+fn ring(entries: u32) -> Option<IoUring> {
+    match IoUring::new(entries) {
+        Ok(ring) => Some(ring),
+        Err(e) if matches!(e.raw_os_error(), Some(libc::ENOSYS | libc::EPERM)) => None,
+        Err(e) => panic!("io_uring_setup failed: {e}"),
+    }
+}
+
+#[test]
+fn submit_positional() {
+    let tmp = tmpdir();
+    let file =
+        File::options().read(true).write(true).create_new(true).open(tmp.join("data")).unwrap();
+    let fd = file.as_fd();
+
+    let bufs = [IoSlice::new(b" "), IoSlice::new(b"world")];
+    let mut ops = [Op::write(fd, b"hello", Some(0)), Op::write_vectored(fd, &bufs, Some(5))];
+    submit(&mut ops).unwrap();
+    assert_eq!(ops[0].result().unwrap().unwrap(), 5);
+    assert_eq!(ops[1].result().unwrap().unwrap(), 6);
+
+    let (mut a, mut b, mut c) = ([0; 5], [0; 1], [0; 3]);
+    let mut bufs = [IoSliceMut::new(&mut b), IoSliceMut::new(&mut c)];
+    let mut ops = [Op::read(fd, &mut a, Some(6)), Op::read_vectored(fd, &mut bufs, Some(0))];
+    submit(&mut ops).unwrap();
+    assert_eq!(ops[0].result().unwrap().unwrap(), 5);
+    assert_eq!(ops[1].result().unwrap().unwrap(), 4);
+    assert_eq!((&a, &b, &c), (b"world", b"h", b"ell"));
+}
+
+#[test]
+fn submit_reports_errors_per_operation() {
+    let tmp = tmpdir();
+    let path = tmp.join("data");
+    fs::write(&path, b"abc").unwrap();
+    let file = File::open(&path).unwrap();
+
+    let mut buf = [0; 3];
+    let mut ops =
+        [Op::write(file.as_fd(), b"xyz", Some(0)), Op::read(file.as_fd(), &mut buf, Some(0))];
+    submit(&mut ops).unwrap();
+    assert_eq!(ops[0].result().unwrap().unwrap_err().raw_os_error(), Some(libc::EBADF));
+    assert_eq!(ops[1].result().unwrap().unwrap(), 3);
+    assert_eq!(&buf, b"abc");
+}
+
+#[test]
+fn submit_stream() {
+    let (a, mut b) = UnixStream::pair().unwrap();
+
+    let mut ops = [Op::write(a.as_fd(), b"ping", None), Op::write(a.as_fd(), b"pong", None)];
+    submit(&mut ops).unwrap();
+    assert!(ops.iter().all(|op| op.result().unwrap().unwrap() == 4));
+
+    let mut received = [0; 8];
+    b.read_exact(&mut received).unwrap();
+    assert_eq!(&received, b"pingpong");
+}
+
+#[test]
+fn ring_stream_without_cur_pos() {
+    let Some(mut ring) = ring(4) else { return };
+    // Pretend to be on a kernel older than 5.6, which can't use the file position.
+    ring.rw_cur_pos = false;
+
+    let tmp = tmpdir();
+    let path = tmp.join("data");
+    let file = File::options().write(true).create_new(true).open(&path).unwrap();
+    let fd = file.as_fd();
+    let mut ops = [Op::write(fd, b"hello", None), Op::write(fd, b"!", Some(9))];
+    ring.submit(&mut ops).unwrap();
+    assert_eq!(ops[0].result().unwrap().unwrap(), 5);
+    assert_eq!(ops[1].result().unwrap().unwrap(), 1);
+    assert_eq!(fs::read(&path).unwrap(), b"hello\0\0\0\0!");
+}
+
+#[test]
+fn ring_batches() {
+    let Some(mut ring) = ring(4) else { return };
+    assert_eq!(ring.entries(), 4);
+
+    let tmp = tmpdir();
+    let file =
+        File::options().read(true).write(true).create_new(true).open(tmp.join("data")).unwrap();
+    let data: Vec<u8> = (0..=255).collect();
+    let mut ops: Vec<_> = data
+        .chunks(10)
+        .enumerate()
+        .map(|(i, chunk)| Op::write(file.as_fd(), chunk, Some(i as u64 * 10)))
+        .collect();
+    // More operations than entries are submitted in several batches.
+    ring.submit(&mut ops).unwrap();
+    assert!(ops.iter().all(|op| op.result().unwrap().is_ok()));
+    drop(ops);
+
+    let mut read = vec![0; 256];
+    let mut ops: Vec<_> = read
+        .chunks_mut(7)
+        .enumerate()
+        .map(|(i, chunk)| Op::read(file.as_fd(), chunk, Some(i as u64 * 7)))
+        .collect();
+    ring.submit(&mut ops).unwrap();
+    let total: usize = ops.iter().map(|op| op.result().unwrap().unwrap()).sum();
+    assert_eq!(total, 256);
+    drop(ops);
+    assert_eq!(read, data);
+
+    // Reading past the end of the file isn't an error.
+    let mut buf = [0; 4];
+    let mut ops = [Op::read(file.as_fd(), &mut buf, Some(1000))];
+    ring.submit(&mut ops).unwrap();
+    assert_eq!(ops[0].result().unwrap().unwrap(), 0);
+}
+
+#[test]
+fn ring_rejects_invalid_sizes() {
+    if ring(1).is_none() {
+        return;
+    }
+    let err = IoUring::new(0).map(drop).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+}
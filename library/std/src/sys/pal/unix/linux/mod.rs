@@ -1 +1,2 @@
+pub mod io_uring;
 pub mod pidfd;
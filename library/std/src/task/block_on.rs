@@ -0,0 +1,86 @@
+#[cfg(test)]
+mod tests;
+
+use crate::future::Future;
+use crate::pin::pin;
+use crate::sync::Arc;
+use crate::task::{Context, Poll, Wake, Waker};
+use crate::thread::{self, Thread};
+
+/// Runs a future to completion on the current thread, blocking the thread while
+/// the future is pending.
+///
+/// The thread is [parked](thread::park) until the future's [`Waker`] is woken
+/// up, which may happen from any thread. This is a minimal executor, meant for
+/// tests, examples and programs that need to call the occasional `async`
+/// function: it doesn't provide any I/O or timers, and futures that rely on a
+/// particular runtime for those will never complete.
+///
+/// # Examples
+///
+/// ```
+/// #![feature(block_on)]
+/// use std::task::block_on;
+///
+/// async fn add(a: i32, b: i32) -> i32 {
+///     a + b
+/// }
+///
+/// assert_eq!(block_on(add(1, 2)), 3);
+/// ```
+///
+/// Waiting for another thread:
+///
+/// ```
+/// #![feature(block_on)]
+/// use std::future::poll_fn;
+/// use std::sync::{Arc, Mutex};
+/// use std::task::{block_on, Poll, Waker};
+/// use std::thread;
+///
+/// let state: Arc<Mutex<(bool, Option<Waker>)>> = Arc::default();
+///
+/// let state2 = Arc::clone(&state);
+/// thread::spawn(move || {
+///     let (done, waker) = &mut *state2.lock().unwrap();
+///     *done = true;
+///     if let Some(waker) = waker.take() {
+///         waker.wake();
+///     }
+/// });
+///
+/// block_on(poll_fn(|cx| {
+///     let (done, waker) = &mut *state.lock().unwrap();
+///     if *done {
+///         Poll::Ready(())
+///     } else {
+///         *waker = Some(cx.waker().clone());
+///         Poll::Pending
+///     }
+/// }));
+/// ```
+#[unstable(feature = "block_on", issue = "none")]
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut cx = Context::from_waker(&waker);
+    loop {
+        match future.as_mut().poll(&mut cx) {
+            Poll::Ready(output) => return output,
+            // Spurious wakeups just lead to polling the future again.
+            Poll::Pending => thread::park(),
+        }
+    }
+}
+
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.0.unpark();
+    }
+}
@@ -0,0 +1,50 @@
+use super::block_on;
+use crate::future::{poll_fn, ready};
+use crate::sync::mpsc::channel;
+use crate::task::Poll;
+use crate::thread;
+
+#[test]
+fn ready_future() {
+    assert_eq!(block_on(ready(42)), 42);
+    assert_eq!(block_on(async { block_on(async { 1 }) + 1 }), 2);
+}
+
+#[test]
+fn woken_from_other_thread() {
+    let (tx, rx) = channel();
+    let mut polls = 0;
+    let value = block_on(poll_fn(|cx| {
+        polls += 1;
+        match polls {
+            1 => {
+                let waker = cx.waker().clone();
+                let tx = tx.clone();
+                thread::spawn(move || {
+                    tx.send(7).unwrap();
+                    waker.wake();
+                });
+                Poll::Pending
+            }
+            _ => match rx.try_recv() {
+                Ok(value) => Poll::Ready(value),
+                Err(_) => Poll::Pending,
+            },
+        }
+    }));
+    assert_eq!(value, 7);
+}
+
+#[test]
+fn woken_by_ref_before_returning_pending() {
+    let mut woken = false;
+    block_on(poll_fn(|cx| {
+        if woken {
+            return Poll::Ready(());
+        }
+        woken = true;
+        // The unpark token makes the park return right away.
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    }));
+}
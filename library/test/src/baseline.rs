@@ -0,0 +1,226 @@
+//! Module `baseline` saves benchmark results under a name with `--save-baseline`,
+//! and compares later runs against them with `--baseline`.
+//!
+//! Baselines are stored as `<name>.baseline` files in the directory given by the
+//! `RUST_BENCH_BASELINE_DIR` environment variable, or `target/bench-baselines`
+//! if it isn't set. Each line of the file holds the sample count, mean, variance
+//! and median (in ns/iter) of one benchmark, followed by its name.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{self, Component, Path, PathBuf};
+
+use super::bench::BenchSamples;
+
+/// Environment variable for overriding the directory baselines are stored in.
+pub const BASELINE_DIR_ENV_NAME: &str = "RUST_BENCH_BASELINE_DIR";
+
+/// Default threshold, in percent, below which a change is considered noise.
+pub(crate) const DEFAULT_NOISE_THRESHOLD: f64 = 2.0;
+
+const HEADER: &str = "# libtest benchmark baseline v1";
+
+/// What is kept of a benchmark's samples to compare against it later.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BaselineSample {
+    pub samples: usize,
+    pub mean: f64,
+    pub var: f64,
+    pub median: f64,
+}
+
+impl BaselineSample {
+    pub fn new(bs: &BenchSamples) -> Self {
+        let summ = &bs.ns_iter_summ;
+        BaselineSample { samples: bs.samples, mean: summ.mean, var: summ.var, median: summ.median }
+    }
+}
+
+/// Checks that `name` is a plain file name, so that a baseline can't be read or written outside
+/// of the baseline directory.
+pub fn check_name(name: &str) -> Result<(), String> {
+    let mut components = Path::new(name).components();
+    let is_file_name = matches!(components.next(), Some(Component::Normal(_)))
+        && components.next().is_none()
+        && !name.chars().any(path::is_separator);
+    if is_file_name {
+        Ok(())
+    } else {
+        Err(format!("invalid baseline name `{name}`: it must not contain path separators or `..`"))
+    }
+}
+
+/// Benchmark results saved under a name.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Baseline {
+    benches: BTreeMap<String, BaselineSample>,
+}
+
+impl Baseline {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The directory baselines are stored in.
+    pub fn dir() -> PathBuf {
+        std::env::var_os(BASELINE_DIR_ENV_NAME)
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from("target").join("bench-baselines"))
+    }
+
+    pub fn path_in(dir: &Path, name: &str) -> io::Result<PathBuf> {
+        check_name(name).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        Ok(dir.join(format!("{name}.baseline")))
+    }
+
+    pub fn insert(&mut self, name: &str, bs: &BenchSamples) {
+        self.benches.insert(name.to_owned(), BaselineSample::new(bs));
+    }
+
+    pub fn get(&self, name: &str) -> Option<&BaselineSample> {
+        self.benches.get(name)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.benches.is_empty()
+    }
+
+    pub fn load(name: &str) -> io::Result<Self> {
+        Self::load_in(&Self::dir(), name)
+    }
+
+    pub fn load_in(dir: &Path, name: &str) -> io::Result<Self> {
+        let path = Self::path_in(dir, name)?;
+        let contents = fs::read_to_string(&path).map_err(|e| {
+            io::Error::new(
+                e.kind(),
+                format!("couldn't read baseline `{name}` from {}: {e}", path.display()),
+            )
+        })?;
+        Self::parse(&contents).map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("couldn't parse baseline `{name}` in {}: {e}", path.display()),
+            )
+        })
+    }
+
+    pub fn save(&self, name: &str) -> io::Result<()> {
+        self.save_in(&Self::dir(), name)
+    }
+
+    pub fn save_in(&self, dir: &Path, name: &str) -> io::Result<()> {
+        let path = Self::path_in(dir, name)?;
+        fs::create_dir_all(dir)?;
+        let mut file = io::BufWriter::new(fs::File::create(path)?);
+        writeln!(file, "{HEADER}")?;
+        for (name, s) in &self.benches {
+            // `{:?}` prints floats in a form that parses back to the same value.
+            writeln!(file, "{} {:?} {:?} {:?} {name}", s.samples, s.mean, s.var, s.median)?;
+        }
+        file.flush()
+    }
+
+    fn parse(contents: &str) -> Result<Self, String> {
+        let mut lines = contents.lines();
+        if lines.next() != Some(HEADER) {
+            return Err("unknown format".to_owned());
+        }
+
+        let mut benches = BTreeMap::new();
+        for (i, line) in lines.enumerate() {
+            let invalid = || format!("invalid entry on line {}", i + 2);
+            let mut fields = line.splitn(5, ' ');
+            let mut next = || fields.next().ok_or_else(invalid);
+            let samples = next()?.parse().map_err(|_| invalid())?;
+            let mean = next()?.parse().map_err(|_| invalid())?;
+            let var = next()?.parse().map_err(|_| invalid())?;
+            let median = next()?.parse().map_err(|_| invalid())?;
+            let name = next()?.to_owned();
+            benches.insert(name, BaselineSample { samples, mean, var, median });
+        }
+        Ok(Baseline { benches })
+    }
+}
+
+/// Whether a benchmark got slower or faster than its baseline.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChangeVerdict {
+    Regressed,
+    Improved,
+    /// The change is within the noise threshold, or not statistically significant.
+    NoChange,
+}
+
+impl fmt::Display for ChangeVerdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ChangeVerdict::Regressed => "regressed",
+            ChangeVerdict::Improved => "improved",
+            ChangeVerdict::NoChange => "no change",
+        })
+    }
+}
+
+/// Change of a benchmark's mean time per iteration relative to its baseline, in percent,
+/// with a 95% confidence interval.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BenchChange {
+    pub mean_pct: f64,
+    pub ci_low_pct: f64,
+    pub ci_high_pct: f64,
+    pub verdict: ChangeVerdict,
+}
+
+impl fmt::Display for BenchChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:+.2}% [{:+.2}% {:+.2}%] ({})",
+            self.mean_pct, self.ci_low_pct, self.ci_high_pct, self.verdict
+        )
+    }
+}
+
+/// Compares `new` against `old` with Welch's t-test, which doesn't assume that both
+/// have the same variance. The change is a regression (or an improvement) only if the
+/// whole confidence interval is beyond `noise_threshold` percent.
+pub fn compare(old: &BaselineSample, new: &BaselineSample, noise_threshold: f64) -> BenchChange {
+    let (old_se2, new_se2) = (old.var / old.samples as f64, new.var / new.samples as f64);
+    let se = (old_se2 + new_se2).sqrt();
+    let diff = new.mean - old.mean;
+
+    let margin = if se > 0.0 && old.samples > 1 && new.samples > 1 {
+        // Welch–Satterthwaite approximation of the degrees of freedom.
+        let df = (old_se2 + new_se2).powi(2)
+            / (old_se2.powi(2) / (old.samples - 1) as f64
+                + new_se2.powi(2) / (new.samples - 1) as f64);
+        student_t_975(df) * se
+    } else {
+        0.0
+    };
+
+    let pct = |ns: f64| if old.mean > 0.0 { ns / old.mean * 100.0 } else { 0.0 };
+    let (mean_pct, ci_low_pct, ci_high_pct) = (pct(diff), pct(diff - margin), pct(diff + margin));
+    let verdict = if ci_low_pct > noise_threshold {
+        ChangeVerdict::Regressed
+    } else if ci_high_pct < -noise_threshold {
+        ChangeVerdict::Improved
+    } else {
+        ChangeVerdict::NoChange
+    };
+    BenchChange { mean_pct, ci_low_pct, ci_high_pct, verdict }
+}
+
+/// Approximates the 97.5th percentile of Student's t-distribution with `df` degrees
+/// of freedom, i.e. the critical value of a two-sided test at 95% confidence, with the
+/// Cornish-Fisher expansion around the normal distribution.
+fn student_t_975(df: f64) -> f64 {
+    const Z: f64 = 1.959963984540054;
+    let (z3, z5, z7) = (Z.powi(3), Z.powi(5), Z.powi(7));
+    Z + (z3 + Z) / (4.0 * df)
+        + (5.0 * z5 + 16.0 * z3 + 3.0 * Z) / (96.0 * df.powi(2))
+        + (3.0 * z7 + 19.0 * z5 + 17.0 * z3 - 15.0 * Z) / (384.0 * df.powi(3))
+}
@@ -56,9 +56,14 @@ pub fn bench<F>(&mut self, mut f: F) -> Result<Option<stats::Summary>, String>
     }
 }
 
+/// Number of samples taken in each round of benchmarking.
+const SAMPLE_COUNT: usize = 50;
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct BenchSamples {
     pub ns_iter_summ: stats::Summary,
+    /// Number of samples `ns_iter_summ` was computed from.
+    pub samples: usize,
     pub mb_s: usize,
 }
 
@@ -140,7 +145,7 @@ pub fn iter<T, F>(inner: &mut F) -> stats::Summary
     n = cmp::max(1, n);
 
     let mut total_run = Duration::new(0, 0);
-    let samples: &mut [f64] = &mut [0.0_f64; 50];
+    let samples: &mut [f64] = &mut [0.0_f64; SAMPLE_COUNT];
     loop {
         let loop_start = Instant::now();
 
@@ -216,14 +221,15 @@ pub fn benchmark<F>(
             let ns_iter = cmp::max(ns_iter_summ.median as u64, 1);
             let mb_s = bs.bytes * 1000 / ns_iter;
 
-            let bs = BenchSamples { ns_iter_summ, mb_s: mb_s as usize };
+            let bs = BenchSamples { ns_iter_summ, samples: SAMPLE_COUNT, mb_s: mb_s as usize };
             TestResult::TrBench(bs)
         }
         Ok(Ok(None)) => {
             // iter not called, so no data.
             // FIXME: error in this case?
             let samples: &mut [f64] = &mut [0.0_f64; 1];
-            let bs = BenchSamples { ns_iter_summ: stats::Summary::new(samples), mb_s: 0 };
+            let ns_iter_summ = stats::Summary::new(samples);
+            let bs = BenchSamples { ns_iter_summ, samples: samples.len(), mb_s: 0 };
             TestResult::TrBench(bs)
         }
         Err(_) => TestResult::TrFailed,
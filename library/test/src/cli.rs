@@ -3,7 +3,9 @@
 use std::env;
 use std::io::{self, IsTerminal};
 use std::path::PathBuf;
+use std::time::Duration;
 
+use super::baseline;
 use super::options::{ColorConfig, Options, OutputFormat, RunIgnored};
 use super::time::TestTimeOptions;
 
@@ -26,6 +28,18 @@ pub struct TestOpts {
     pub test_threads: Option<usize>,
     pub skip: Vec<String>,
     pub time_options: Option<TestTimeOptions>,
+    /// Kill (or, for in-process tests, abandon) tests that run longer than this.
+    pub test_timeout: Option<Duration>,
+    /// Number of times a failed test is run again before it is reported as failed.
+    pub retries: usize,
+    /// Only run (or list) the tests in one shard, given as `(index, total)`.
+    pub shard: Option<(usize, usize)>,
+    /// Save the benchmark results under this name, to compare later runs against.
+    pub save_baseline: Option<String>,
+    /// Compare the benchmark results against the ones saved under this name.
+    pub baseline: Option<String>,
+    /// Changes of benchmarks smaller than this percentage are considered noise, 2% by default.
+    pub noise_threshold: Option<f64>,
     /// Stop at first failing test.
     /// May run a few more tests due to threading, but will
     /// abort as soon as possible.
@@ -102,8 +116,10 @@ fn optgroups() -> getopts::Options {
             pretty = Print verbose output;
             terse  = Display one character per test;
             json   = Output a json document;
-            junit  = Output a JUnit document",
-            "pretty|terse|json|junit",
+            junit  = Output a JUnit document;
+            tap    = Output a TAP 14 stream;
+            github = Annotate failed tests for GitHub Actions",
+            "pretty|terse|json|junit|tap|github",
         )
         .optflag("", "show-output", "Show captured stdout of successful tests")
         .optopt(
@@ -142,12 +158,62 @@ fn optgroups() -> getopts::Options {
             `CRITICAL_TIME` here means the limit that should not be exceeded by test.
             ",
         )
+        .optopt(
+            "",
+            "test-timeout",
+            "Fail tests that run for longer than SECS seconds.
+
+            Tests run in a subprocess (panic=abort) are killed; tests run
+            in-process are reported as failed and left running in the
+            background. Individual tests can override the limit with
+            `#[test_timeout = \"SECS\"]`.",
+            "SECS",
+        )
+        .optopt(
+            "",
+            "retries",
+            "Run failed tests again up to N times. Tests that pass on a
+            retry are reported as flaky instead of failed.",
+            "N",
+        )
         .optflag("", "shuffle", "Run tests in random order")
         .optopt(
             "",
             "shuffle-seed",
             "Run tests in random order; seed the random number generator with SEED",
             "SEED",
+        )
+        .optopt(
+            "",
+            "shard-index",
+            "Run only the tests in shard INDEX (starting from 0) out of --total-shards",
+            "INDEX",
+        )
+        .optopt(
+            "",
+            "total-shards",
+            "Split the tests into N disjoint shards, to run them on several machines",
+            "N",
+        )
+        .optopt(
+            "",
+            "save-baseline",
+            "Save the benchmark results under NAME, to compare later runs against with
+            --baseline",
+            "NAME",
+        )
+        .optopt(
+            "",
+            "baseline",
+            "Compare the benchmark results against the ones saved under NAME",
+            "NAME",
+        )
+        .optopt(
+            "",
+            "noise-threshold",
+            "Report benchmarks as regressed or improved compared to --baseline
+            only if they changed by more than PCT percent (default: 2)",
+            "PCT",
         );
     opts
 }
@@ -171,6 +237,15 @@ fn usage(binary: &str, options: &getopts::Options) {
 tests in the same order again. Note that --shuffle and --shuffle-seed do not
 affect whether the tests are run in parallel.
 
+The tests can be split across several machines with --shard-index and
+--total-shards (or RUST_TEST_SHARD_INDEX and RUST_TEST_TOTAL_SHARDS). Each test
+is assigned to a shard based on its name, so running every shard index from 0
+to N-1 runs each test exactly once.
+
+Benchmark results can be saved with --save-baseline NAME, and later runs can be
+compared against them with --baseline NAME. Baselines are stored in
+target/bench-baselines, or in RUST_BENCH_BASELINE_DIR if it is set.
+
 All tests have their standard output and standard error captured by default.
 This can be overridden with the --nocapture flag or setting RUST_TEST_NOCAPTURE
 environment variable to a value other than "0". Logging is not captured by default.
@@ -259,8 +334,17 @@ fn parse_opts_impl(matches: getopts::Matches) -> OptRes {
     let force_run_in_process = unstable_optflag!(matches, allow_unstable, "force-run-in-process");
     let exclude_should_panic = unstable_optflag!(matches, allow_unstable, "exclude-should-panic");
     let time_options = get_time_options(&matches, allow_unstable)?;
+    let test_timeout = get_test_timeout(&matches, allow_unstable)?;
+    let retries = get_retries(&matches, allow_unstable)?;
     let shuffle = get_shuffle(&matches, allow_unstable)?;
     let shuffle_seed = get_shuffle_seed(&matches, allow_unstable)?;
+    let shard = get_shard(&matches, allow_unstable)?;
+    let save_baseline = unstable_optopt!(matches, allow_unstable, "save-baseline");
+    let baseline = unstable_optopt!(matches, allow_unstable, "baseline");
+    for name in save_baseline.iter().chain(&baseline) {
+        baseline::check_name(name)?;
+    }
+    let noise_threshold = get_noise_threshold(&matches, allow_unstable)?;
 
     let include_ignored = matches.opt_present("include-ignored");
     let quiet = matches.opt_present("quiet");
@@ -299,6 +383,12 @@ fn parse_opts_impl(matches: getopts::Matches) -> OptRes {
         test_threads,
         skip,
         time_options,
+        test_timeout,
+        retries,
+        shard,
+        save_baseline,
+        baseline,
+        noise_threshold,
         options,
         fail_fast: false,
     };
@@ -336,6 +426,65 @@ fn get_time_options(
     Ok(options)
 }
 
+fn get_test_timeout(
+    matches: &getopts::Matches,
+    allow_unstable: bool,
+) -> OptPartRes<Option<Duration>> {
+    let test_timeout = match unstable_optopt!(matches, allow_unstable, "test-timeout") {
+        Some(n_str) => match n_str.parse::<u64>() {
+            Ok(0) => return Err("argument for --test-timeout must not be 0".to_string()),
+            Ok(n) => Some(Duration::from_secs(n)),
+            Err(e) => {
+                return Err(format!(
+                    "argument for --test-timeout must be a number > 0 \
+                     (error: {e})"
+                ));
+            }
+        },
+        None => None,
+    };
+
+    Ok(test_timeout)
+}
+
+fn get_retries(matches: &getopts::Matches, allow_unstable: bool) -> OptPartRes<usize> {
+    let retries = match unstable_optopt!(matches, allow_unstable, "retries") {
+        Some(n_str) => match n_str.parse::<usize>() {
+            Ok(n) => n,
+            Err(e) => {
+                return Err(format!(
+                    "argument for --retries must be a number \
+                     (error: {e})"
+                ));
+            }
+        },
+        None => 0,
+    };
+
+    Ok(retries)
+}
+
+fn get_noise_threshold(
+    matches: &getopts::Matches,
+    allow_unstable: bool,
+) -> OptPartRes<Option<f64>> {
+    let noise_threshold = match unstable_optopt!(matches, allow_unstable, "noise-threshold") {
+        Some(pct_str) => match pct_str.parse::<f64>() {
+            Ok(pct) if pct >= 0.0 => Some(pct),
+            Ok(_) => return Err("argument for --noise-threshold must not be negative".to_string()),
+            Err(e) => {
+                return Err(format!(
+                    "argument for --noise-threshold must be a number \
+                     (error: {e})"
+                ));
+            }
+        },
+        None => None,
+    };
+
+    Ok(noise_threshold)
+}
+
 fn get_shuffle(matches: &getopts::Matches, allow_unstable: bool) -> OptPartRes<bool> {
     let mut shuffle = unstable_optflag!(matches, allow_unstable, "shuffle");
     if !shuffle && allow_unstable {
@@ -375,6 +524,47 @@ fn get_shuffle_seed(matches: &getopts::Matches, allow_unstable: bool) -> OptPart
     Ok(shuffle_seed)
 }
 
+fn get_shard(
+    matches: &getopts::Matches,
+    allow_unstable: bool,
+) -> OptPartRes<Option<(usize, usize)>> {
+    fn parse(
+        opt: &str,
+        val: Option<String>,
+        var: &str,
+        allow_unstable: bool,
+    ) -> OptPartRes<Option<usize>> {
+        let val = match val {
+            Some(val) => val,
+            None if allow_unstable => match env::var(var) {
+                Ok(val) => val,
+                Err(_) => return Ok(None),
+            },
+            None => return Ok(None),
+        };
+        match val.parse::<usize>() {
+            Ok(n) => Ok(Some(n)),
+            Err(e) => Err(format!("argument for --{opt} must be a number (error: {e})")),
+        }
+    }
+
+    let index = unstable_optopt!(matches, allow_unstable, "shard-index");
+    let total = unstable_optopt!(matches, allow_unstable, "total-shards");
+    let index = parse("shard-index", index, "RUST_TEST_SHARD_INDEX", allow_unstable)?;
+    let total = parse("total-shards", total, "RUST_TEST_TOTAL_SHARDS", allow_unstable)?;
+
+    match (index, total) {
+        (None, None) => Ok(None),
+        (Some(_), None) => Err("--shard-index requires --total-shards".to_string()),
+        (None, Some(_)) => Err("--total-shards requires --shard-index".to_string()),
+        (Some(_), Some(0)) => Err("argument for --total-shards must not be 0".to_string()),
+        (Some(index), Some(total)) if index >= total => Err(format!(
+            "argument for --shard-index must be less than --total-shards ({index} >= {total})"
+        )),
+        (Some(index), Some(total)) => Ok(Some((index, total))),
+    }
+}
+
 fn get_test_threads(matches: &getopts::Matches) -> OptPartRes<Option<usize>> {
     let test_threads = match matches.opt_str("test-threads") {
         Some(n_str) => match n_str.parse::<usize>() {
@@ -414,9 +604,21 @@ fn get_format(
             }
             OutputFormat::Junit
         }
+        Some("tap") => {
+            if !allow_unstable {
+                return Err("The \"tap\" format is only accepted on the nightly compiler with -Z unstable-options".into());
+            }
+            OutputFormat::Tap
+        }
+        Some("github") => {
+            if !allow_unstable {
+                return Err("The \"github\" format is only accepted on the nightly compiler with -Z unstable-options".into());
+            }
+            OutputFormat::Github
+        }
         Some(v) => {
             return Err(format!(
-                "argument for --format must be pretty, terse, json or junit (was \
+                "argument for --format must be pretty, terse, json, junit, tap or github (was \
                  {v})"
             ));
         }
@@ -5,11 +5,15 @@
 use std::io::prelude::Write;
 use std::time::Instant;
 
-use super::bench::fmt_bench_samples;
+use super::baseline::{
+    self, Baseline, BaselineSample, BenchChange, ChangeVerdict, DEFAULT_NOISE_THRESHOLD,
+};
+use super::bench::{fmt_bench_samples, BenchSamples};
 use super::cli::TestOpts;
 use super::event::{CompletedTest, TestEvent};
 use super::formatters::{
-    JsonFormatter, JunitFormatter, OutputFormatter, PrettyFormatter, TerseFormatter,
+    GithubFormatter, JsonFormatter, JunitFormatter, OutputFormatter, PrettyFormatter, TapFormatter,
+    TerseFormatter,
 };
 use super::helpers::concurrency::get_concurrency;
 use super::helpers::metrics::MetricMap;
@@ -88,6 +92,15 @@ pub struct ConsoleTestState {
     pub not_failures: Vec<(TestDesc, Vec<u8>)>,
     pub ignores: Vec<(TestDesc, Vec<u8>)>,
     pub time_failures: Vec<(TestDesc, Vec<u8>)>,
+    /// Tests that passed after being retried, with the number of attempts they took.
+    pub flaky: Vec<(TestDesc, usize)>,
+    /// Results of the benchmarks run so far, saved with `--save-baseline`.
+    pub bench_results: Baseline,
+    /// Results given with `--baseline` to compare the benchmarks against.
+    pub baseline: Option<Baseline>,
+    pub noise_threshold: f64,
+    /// Benchmarks that got slower than in `baseline`.
+    pub regressions: Vec<(TestDesc, BenchChange)>,
     pub options: Options,
 }
 
@@ -97,6 +110,10 @@ pub fn new(opts: &TestOpts) -> io::Result<ConsoleTestState> {
             Some(ref path) => Some(File::create(path)?),
             None => None,
         };
+        let baseline = match opts.baseline {
+            Some(ref name) => Some(Baseline::load(name)?),
+            None => None,
+        };
 
         Ok(ConsoleTestState {
             log_out,
@@ -112,6 +129,11 @@ pub fn new(opts: &TestOpts) -> io::Result<ConsoleTestState> {
             not_failures: Vec::new(),
             ignores: Vec::new(),
             time_failures: Vec::new(),
+            flaky: Vec::new(),
+            bench_results: Baseline::new(),
+            baseline,
+            noise_threshold: opts.noise_threshold.unwrap_or(DEFAULT_NOISE_THRESHOLD),
+            regressions: Vec::new(),
             options: opts.options,
         })
     }
@@ -154,6 +176,8 @@ pub fn write_log_result(
                     }
                     TestResult::TrBench(ref bs) => fmt_bench_samples(bs),
                     TestResult::TrTimedFail => "failed (time limit exceeded)".to_owned(),
+                    TestResult::TrTimedOut(timeout) =>
+                        format!("failed (timed out after {timeout:?})"),
                 },
                 name,
             )
@@ -164,6 +188,13 @@ pub fn write_log_result(
         self.write_log(|| "\n")
     }
 
+    /// Compares the result of a benchmark against the `--baseline`, if there is one
+    /// and it has a result for the same benchmark.
+    pub fn compare_to_baseline(&self, desc: &TestDesc, bs: &BenchSamples) -> Option<BenchChange> {
+        let old = self.baseline.as_ref()?.get(desc.name.as_slice())?;
+        Some(baseline::compare(old, &BaselineSample::new(bs), self.noise_threshold))
+    }
+
     fn current_test_count(&self) -> usize {
         self.passed + self.failed + self.ignored + self.measured
     }
@@ -177,7 +208,7 @@ pub fn list_tests_console(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> io::Res
     };
 
     let mut out: Box<dyn OutputFormatter> = match opts.format {
-        OutputFormat::Pretty | OutputFormat::Junit => {
+        OutputFormat::Pretty | OutputFormat::Junit | OutputFormat::Tap | OutputFormat::Github => {
             Box::new(PrettyFormatter::new(output, false, 0, false, None))
         }
         OutputFormat::Terse => Box::new(TerseFormatter::new(output, false, 0, false)),
@@ -218,6 +249,9 @@ fn handle_test_result(st: &mut ConsoleTestState, completed_test: CompletedTest)
     match completed_test.result {
         TestResult::TrOk => {
             st.passed += 1;
+            if completed_test.attempts > 1 {
+                st.flaky.push((test.clone(), completed_test.attempts));
+            }
             st.not_failures.push((test, stdout));
         }
         TestResult::TrIgnored => {
@@ -225,6 +259,12 @@ fn handle_test_result(st: &mut ConsoleTestState, completed_test: CompletedTest)
             st.ignores.push((test, stdout));
         }
         TestResult::TrBench(bs) => {
+            if let Some(change) = st.compare_to_baseline(&test, &bs) {
+                if change.verdict == ChangeVerdict::Regressed {
+                    st.regressions.push((test.clone(), change));
+                }
+            }
+            st.bench_results.insert(test.name.as_slice(), &bs);
             st.metrics.insert_metric(
                 test.name.as_slice(),
                 bs.ns_iter_summ.median,
@@ -246,6 +286,17 @@ fn handle_test_result(st: &mut ConsoleTestState, completed_test: CompletedTest)
             st.failed += 1;
             st.time_failures.push((test, stdout));
         }
+        TestResult::TrTimedOut(timeout) => {
+            st.failed += 1;
+            let mut stdout = stdout;
+            // Put the note on a line of its own, whatever the test printed last.
+            if !stdout.is_empty() && !stdout.ends_with(b"\n") {
+                stdout.push(b'\n');
+            }
+            stdout
+                .extend_from_slice(format!("note: test timed out after {timeout:?}\n").as_bytes());
+            st.failures.push((test, stdout));
+        }
     }
 }
 
@@ -266,6 +317,15 @@ fn on_test_event(
         }
         TestEvent::TeWait(ref test) => out.write_test_start(test)?,
         TestEvent::TeTimeout(ref test) => out.write_timeout(test)?,
+        TestEvent::TeRetry(completed_test) => {
+            let test = &completed_test.desc;
+            let result = &completed_test.result;
+            let exec_time = &completed_test.exec_time;
+            let stdout = &completed_test.stdout;
+
+            st.write_log_result(test, result, exec_time.as_ref())?;
+            out.write_retry(test, result, completed_test.attempts, stdout)?;
+        }
         TestEvent::TeResult(completed_test) => {
             let test = &completed_test.desc;
             let result = &completed_test.result;
@@ -273,7 +333,14 @@ fn on_test_event(
             let stdout = &completed_test.stdout;
 
             st.write_log_result(test, result, exec_time.as_ref())?;
-            out.write_result(test, result, exec_time.as_ref(), stdout, st)?;
+            out.write_result(
+                test,
+                result,
+                exec_time.as_ref(),
+                completed_test.attempts,
+                stdout,
+                st,
+            )?;
             handle_test_result(st, completed_test);
         }
     }
@@ -310,6 +377,8 @@ pub fn run_tests_console(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> io::Resu
         }
         OutputFormat::Json => Box::new(JsonFormatter::new(output)),
         OutputFormat::Junit => Box::new(JunitFormatter::new(output)),
+        OutputFormat::Tap => Box::new(TapFormatter::new(output)),
+        OutputFormat::Github => Box::new(GithubFormatter::new(output)),
     };
     let mut st = ConsoleTestState::new(opts)?;
 
@@ -322,6 +391,16 @@ pub fn run_tests_console(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> io::Resu
     run_tests(opts, tests, |x| on_test_event(&x, &mut st, &mut *out))?;
     st.exec_time = start_time.map(|t| TestSuiteExecTime(t.elapsed()));
 
+    if let Some(ref name) = opts.save_baseline {
+        // Don't replace a baseline with an empty one when no benchmark ran, e.g. because of a
+        // filter or a run without `--bench`.
+        if st.bench_results.is_empty() {
+            eprintln!("note: no benchmarks were run, not saving baseline `{name}`");
+        } else {
+            st.bench_results.save(name)?;
+        }
+    }
+
     assert!(opts.fail_fast || st.current_test_count() == st.total);
 
     out.write_run_finish(&st)
@@ -12,6 +12,8 @@ pub struct CompletedTest {
     pub result: TestResult,
     pub exec_time: Option<TestExecTime>,
     pub stdout: Vec<u8>,
+    /// Number of times the test was run; more than one if it was retried after failing.
+    pub attempts: usize,
 }
 
 impl CompletedTest {
@@ -22,7 +24,7 @@ pub fn new(
         exec_time: Option<TestExecTime>,
         stdout: Vec<u8>,
     ) -> Self {
-        Self { id, desc, result, exec_time, stdout }
+        Self { id, desc, result, exec_time, stdout, attempts: 1 }
     }
 }
 
@@ -31,6 +33,8 @@ pub enum TestEvent {
     TeFiltered(usize, Option<u64>),
     TeWait(TestDesc),
     TeResult(CompletedTest),
+    /// A failed attempt of a test that is about to be run again.
+    TeRetry(CompletedTest),
     TeTimeout(TestDesc),
     TeFilteredOut(usize),
 }
@@ -0,0 +1,212 @@
+use std::io;
+use std::io::prelude::Write;
+
+use super::OutputFormatter;
+use crate::baseline::ChangeVerdict;
+use crate::console::{ConsoleTestDiscoveryState, ConsoleTestState, OutputLocation};
+use crate::test_result::TestResult;
+use crate::time;
+use crate::types::TestDesc;
+
+/// Writes GitHub Actions [workflow commands] that annotate the source of failed tests,
+/// pointing at the location of the panic when it can be found in the captured output.
+///
+/// [workflow commands]: https://docs.github.com/en/actions/using-workflows/workflow-commands-for-github-actions
+pub(crate) struct GithubFormatter<T> {
+    out: OutputLocation<T>,
+}
+
+impl<T: Write> GithubFormatter<T> {
+    pub fn new(out: OutputLocation<T>) -> Self {
+        Self { out }
+    }
+
+    #[cfg(test)]
+    pub fn output_location(&self) -> &OutputLocation<T> {
+        &self.out
+    }
+
+    fn write_line(&mut self, s: &str) -> io::Result<()> {
+        self.out.write_all(format!("{s}\n").as_bytes())
+    }
+
+    fn write_annotation(
+        &mut self,
+        level: &str,
+        location: &Location,
+        title: &str,
+        message: &str,
+    ) -> io::Result<()> {
+        let mut properties = Vec::new();
+        if !location.file.is_empty() {
+            properties.push(format!("file={}", escape_property(&location.file)));
+        }
+        if location.line != 0 {
+            properties.push(format!("line={}", location.line));
+        }
+        if location.col != 0 {
+            properties.push(format!("col={}", location.col));
+        }
+        properties.push(format!("title={}", escape_property(title)));
+        self.write_line(&format!("::{level} {}::{}", properties.join(","), escape_data(message)))
+    }
+}
+
+/// Where an annotation points to.
+#[derive(Debug, PartialEq)]
+struct Location {
+    file: String,
+    line: usize,
+    col: usize,
+}
+
+impl Location {
+    fn of_test(desc: &TestDesc) -> Self {
+        Location { file: desc.source_file.to_owned(), line: desc.start_line, col: desc.start_col }
+    }
+}
+
+/// Finds the location and message of the first panic in the captured output of a test,
+/// which looks like:
+///
+/// ```text
+/// thread 'tests::it_works' panicked at src/lib.rs:10:5:
+/// assertion failed: false
+/// note: run with `RUST_BACKTRACE=1` environment variable to display a backtrace
+/// ```
+fn parse_panic(output: &str) -> Option<(Location, String)> {
+    let mut lines = output.lines();
+    let location = lines.by_ref().find_map(|line| {
+        if !line.starts_with("thread '") {
+            return None;
+        }
+        let (_, location) = line.split_once(" panicked at ")?;
+        let mut parts = location.strip_suffix(':')?.rsplitn(3, ':');
+        let col = parts.next()?.parse().ok()?;
+        let line = parts.next()?.parse().ok()?;
+        let file = parts.next()?.to_owned();
+        Some(Location { file, line, col })
+    })?;
+
+    let message = lines
+        .take_while(|line| !line.starts_with("note: ") && !line.starts_with("stack backtrace:"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    Some((location, message))
+}
+
+// Workflow command values are percent-encoded, and so are the separators of properties.
+fn escape_data(s: &str) -> String {
+    s.replace('%', "%25").replace('\r', "%0D").replace('\n', "%0A")
+}
+
+fn escape_property(s: &str) -> String {
+    escape_data(s).replace(':', "%3A").replace(',', "%2C")
+}
+
+impl<T: Write> OutputFormatter for GithubFormatter<T> {
+    fn write_discovery_start(&mut self) -> io::Result<()> {
+        Err(io::Error::new(io::ErrorKind::NotFound, "Not yet implemented!"))
+    }
+
+    fn write_test_discovered(&mut self, _desc: &TestDesc, _test_type: &str) -> io::Result<()> {
+        Err(io::Error::new(io::ErrorKind::NotFound, "Not yet implemented!"))
+    }
+
+    fn write_discovery_finish(&mut self, _state: &ConsoleTestDiscoveryState) -> io::Result<()> {
+        Err(io::Error::new(io::ErrorKind::NotFound, "Not yet implemented!"))
+    }
+
+    fn write_run_start(&mut self, test_count: usize, shuffle_seed: Option<u64>) -> io::Result<()> {
+        let noun = if test_count != 1 { "tests" } else { "test" };
+        let shuffle_seed_msg = if let Some(shuffle_seed) = shuffle_seed {
+            format!(" (shuffle seed: {shuffle_seed})")
+        } else {
+            String::new()
+        };
+        self.write_line(&format!("running {test_count} {noun}{shuffle_seed_msg}"))
+    }
+
+    fn write_test_start(&mut self, _desc: &TestDesc) -> io::Result<()> {
+        // We do not output anything on test start.
+        Ok(())
+    }
+
+    fn write_timeout(&mut self, desc: &TestDesc) -> io::Result<()> {
+        let message =
+            format!("test has been running for over {} seconds", time::TEST_WARN_TIMEOUT_S);
+        self.write_annotation("warning", &Location::of_test(desc), desc.name.as_slice(), &message)
+    }
+
+    fn write_retry(
+        &mut self,
+        _desc: &TestDesc,
+        _result: &TestResult,
+        _attempt: usize,
+        _stdout: &[u8],
+    ) -> io::Result<()> {
+        // Only the final result of a retried test is annotated.
+        Ok(())
+    }
+
+    fn write_result(
+        &mut self,
+        desc: &TestDesc,
+        result: &TestResult,
+        _exec_time: Option<&time::TestExecTime>,
+        attempts: usize,
+        stdout: &[u8],
+        state: &ConsoleTestState,
+    ) -> io::Result<()> {
+        let message = match *result {
+            TestResult::TrFailed => "test failed".to_owned(),
+            TestResult::TrFailedMsg(ref msg) => msg.clone(),
+            TestResult::TrTimedFail => "time limit exceeded".to_owned(),
+            TestResult::TrTimedOut(timeout) => format!("timed out after {timeout:?}"),
+            TestResult::TrOk if attempts > 1 => {
+                let message = format!("test is flaky: it passed after {attempts} attempts");
+                let location = Location::of_test(desc);
+                return self.write_annotation("warning", &location, desc.name.as_slice(), &message);
+            }
+            TestResult::TrBench(ref bs) => {
+                return match state.compare_to_baseline(desc, bs) {
+                    Some(change) if change.verdict == ChangeVerdict::Regressed => {
+                        let message = format!("benchmark regressed: {change}");
+                        let location = Location::of_test(desc);
+                        self.write_annotation("warning", &location, desc.name.as_slice(), &message)
+                    }
+                    _ => Ok(()),
+                };
+            }
+            TestResult::TrOk | TestResult::TrIgnored => return Ok(()),
+        };
+
+        // Point at the panic if there was one, since that's where the test went wrong.
+        let stdout = String::from_utf8_lossy(stdout);
+        let (location, message) = match parse_panic(&stdout) {
+            Some((location, panic_message))
+                if *result == TestResult::TrFailed && !panic_message.is_empty() =>
+            {
+                (location, panic_message)
+            }
+            Some((location, _)) => (location, message),
+            None => (Location::of_test(desc), message),
+        };
+        self.write_annotation("error", &location, desc.name.as_slice(), &message)
+    }
+
+    fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
+        let success = state.failed == 0;
+        self.write_line(&format!(
+            "test result: {}. {} passed; {} failed; {} ignored; {} measured; {} filtered out",
+            if success { "ok" } else { "FAILED" },
+            state.passed,
+            state.failed,
+            state.ignored,
+            state.measured,
+            state.filtered_out
+        ))?;
+
+        Ok(success)
+    }
+}
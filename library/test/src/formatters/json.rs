@@ -119,6 +119,7 @@ fn write_result(
         desc: &TestDesc,
         result: &TestResult,
         exec_time: Option<&time::TestExecTime>,
+        attempts: usize,
         stdout: &[u8],
         state: &ConsoleTestState,
     ) -> io::Result<()> {
@@ -129,6 +130,15 @@ fn write_result(
             None
         };
         match *result {
+            TestResult::TrOk if attempts > 1 => self.write_event(
+                "test",
+                desc.name.as_slice(),
+                "ok",
+                exec_time,
+                stdout,
+                Some(&*format!(r#""flaky": true, "attempts": {attempts}"#)),
+            ),
+
             TestResult::TrOk => {
                 self.write_event("test", desc.name.as_slice(), "ok", exec_time, stdout, None)
             }
@@ -146,6 +156,15 @@ fn write_result(
                 Some(r#""reason": "time limit exceeded""#),
             ),
 
+            TestResult::TrTimedOut(timeout) => self.write_event(
+                "test",
+                desc.name.as_slice(),
+                "failed",
+                exec_time,
+                stdout,
+                Some(&*format!(r#""reason": "timed out", "timeout": {}"#, timeout.as_secs_f64())),
+            ),
+
             TestResult::TrFailedMsg(ref m) => self.write_event(
                 "test",
                 desc.name.as_slice(),
@@ -175,13 +194,21 @@ fn write_result(
                 } else {
                     format!(r#", "mib_per_second": {}"#, bs.mb_s)
                 };
+                let change = if let Some(change) = state.compare_to_baseline(desc, bs) {
+                    format!(
+                        r#", "baseline_change": {{ "mean_pct": {}, "ci_low_pct": {}, "ci_high_pct": {}, "verdict": "{}" }}"#,
+                        change.mean_pct, change.ci_low_pct, change.ci_high_pct, change.verdict
+                    )
+                } else {
+                    String::new()
+                };
                 let name = EscapedString(desc.name.as_slice());
 
                 self.writeln_message(&format!(
                     "{{ \"type\": \"bench\", \
                      \"name\": \"{name}\", \
                      \"median\": {median}, \
-                     \"deviation\": {deviation}{mbps} }}\n",
+                     \"deviation\": {deviation}{mbps}{change} }}\n",
                 ))
             }
         }
@@ -195,6 +222,26 @@ fn write_timeout(&mut self, desc: &TestDesc) -> io::Result<()> {
         ))
     }
 
+    fn write_retry(
+        &mut self,
+        desc: &TestDesc,
+        result: &TestResult,
+        attempt: usize,
+        stdout: &[u8],
+    ) -> io::Result<()> {
+        let stdout = if !stdout.is_empty() { Some(String::from_utf8_lossy(stdout)) } else { None };
+        let extra = match *result {
+            TestResult::TrFailedMsg(ref m) => {
+                format!(r#""attempt": {attempt}, "message": "{}""#, EscapedString(m))
+            }
+            TestResult::TrTimedFail => {
+                format!(r#""attempt": {attempt}, "reason": "time limit exceeded""#)
+            }
+            _ => format!(r#""attempt": {attempt}"#),
+        };
+        self.write_event("test", desc.name.as_slice(), "retry", None, stdout, Some(&extra))
+    }
+
     fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
         let event = if state.failed == 0 { "ok" } else { "failed" };
         let passed = state.passed;
@@ -202,6 +249,16 @@ fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
         let ignored = state.ignored;
         let measured = state.measured;
         let filtered_out = state.filtered_out;
+        let regressed_json = if state.baseline.is_some() {
+            format!(r#", "regressed": {}"#, state.regressions.len())
+        } else {
+            String::new()
+        };
+        let flaky_json = if state.flaky.is_empty() {
+            String::new()
+        } else {
+            format!(r#", "flaky": {}"#, state.flaky.len())
+        };
         let exec_time_json = if let Some(ref exec_time) = state.exec_time {
             format!(r#", "exec_time": {}"#, exec_time.0.as_secs_f64())
         } else {
@@ -210,7 +267,7 @@ fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
         let newline = "\n";
 
         self.writeln_message(&format!(
-            r#"{{ "type": "suite", "event": "{event}", "passed": {passed}, "failed": {failed}, "ignored": {ignored}, "measured": {measured}, "filtered_out": {filtered_out}{exec_time_json} }}{newline}"#
+            r#"{{ "type": "suite", "event": "{event}", "passed": {passed}, "failed": {failed}, "ignored": {ignored}, "measured": {measured}, "filtered_out": {filtered_out}{flaky_json}{regressed_json}{exec_time_json} }}{newline}"#
         ))?;
 
         Ok(state.failed == 0)
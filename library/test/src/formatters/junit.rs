@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::io::prelude::Write;
 use std::io::{self};
 use std::time::Duration;
@@ -11,11 +12,18 @@
 pub struct JunitFormatter<T> {
     out: OutputLocation<T>,
     results: Vec<(TestDesc, TestResult, Duration, Vec<u8>)>,
+    /// Failed attempts of retried tests, keyed by test name.
+    reruns: HashMap<String, Vec<(TestResult, Vec<u8>)>>,
 }
 
 impl<T: Write> JunitFormatter<T> {
     pub fn new(out: OutputLocation<T>) -> Self {
-        Self { out, results: Vec::new() }
+        Self { out, results: Vec::new(), reruns: HashMap::new() }
+    }
+
+    #[cfg(test)]
+    pub fn output_location(&self) -> &OutputLocation<T> {
+        &self.out
     }
 
     fn write_message(&mut self, s: &str) -> io::Result<()> {
@@ -23,6 +31,30 @@ fn write_message(&mut self, s: &str) -> io::Result<()> {
 
         self.out.write_all(s.as_ref())
     }
+
+    /// Writes the failed attempts of a retried test as `flakyFailure` elements if
+    /// the test eventually passed, or `rerunFailure` elements if it didn't.
+    fn write_reruns(&mut self, element: &str, desc: &TestDesc) -> io::Result<()> {
+        for (result, stdout) in self.reruns.remove(desc.name.as_slice()).unwrap_or_default() {
+            let (message, ty) = match result {
+                TestResult::TrFailedMsg(ref m) => {
+                    (format!(" message=\"{}\"", str_to_attr(m)), "assert")
+                }
+                TestResult::TrTimedFail => (String::new(), "timeout"),
+                _ => (String::new(), "assert"),
+            };
+            if stdout.is_empty() {
+                self.write_message(&format!("<{element}{message} type=\"{ty}\"/>"))?;
+            } else {
+                self.write_message(&format!("<{element}{message} type=\"{ty}\">"))?;
+                self.write_message("<system-out>")?;
+                self.write_message(&str_to_cdata(&String::from_utf8_lossy(&stdout)))?;
+                self.write_message("</system-out>")?;
+                self.write_message(&format!("</{element}>"))?;
+            }
+        }
+        Ok(())
+    }
 }
 
 fn str_to_cdata(s: &str) -> String {
@@ -37,6 +69,25 @@ fn str_to_cdata(s: &str) -> String {
     format!("<![CDATA[{}]]>", escaped_output)
 }
 
+/// Escapes `s` for use in a double-quoted attribute value. Newlines are escaped as well, both
+/// to keep the output on one line and because they would be normalized to spaces otherwise.
+fn str_to_attr(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\n' => out.push_str("&#xA;"),
+            '\r' => out.push_str("&#xD;"),
+            '\t' => out.push_str("&#x9;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
 impl<T: Write> OutputFormatter for JunitFormatter<T> {
     fn write_discovery_start(&mut self) -> io::Result<()> {
         Err(io::Error::new(io::ErrorKind::NotFound, "Not yet implemented!"))
@@ -69,11 +120,24 @@ fn write_timeout(&mut self, _desc: &TestDesc) -> io::Result<()> {
         Ok(())
     }
 
+    fn write_retry(
+        &mut self,
+        desc: &TestDesc,
+        result: &TestResult,
+        _attempt: usize,
+        stdout: &[u8],
+    ) -> io::Result<()> {
+        let reruns = self.reruns.entry(desc.name.as_slice().to_owned()).or_default();
+        reruns.push((result.clone(), stdout.to_vec()));
+        Ok(())
+    }
+
     fn write_result(
         &mut self,
         desc: &TestDesc,
         result: &TestResult,
         exec_time: Option<&time::TestExecTime>,
+        _attempts: usize,
         stdout: &[u8],
         _state: &ConsoleTestState,
     ) -> io::Result<()> {
@@ -114,6 +178,7 @@ fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
                         self.write_message(&str_to_cdata(&String::from_utf8_lossy(&stdout)))?;
                         self.write_message("</system-out>")?;
                     }
+                    self.write_reruns("rerunFailure", &desc)?;
                     self.write_message("</testcase>")?;
                 }
 
@@ -125,12 +190,16 @@ fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
                         test_name,
                         duration.as_secs_f64()
                     ))?;
-                    self.write_message(&format!("<failure message=\"{m}\" type=\"assert\"/>"))?;
+                    self.write_message(&format!(
+                        "<failure message=\"{}\" type=\"assert\"/>",
+                        str_to_attr(m)
+                    ))?;
                     if !stdout.is_empty() {
                         self.write_message("<system-out>")?;
                         self.write_message(&str_to_cdata(&String::from_utf8_lossy(&stdout)))?;
                         self.write_message("</system-out>")?;
                     }
+                    self.write_reruns("rerunFailure", &desc)?;
                     self.write_message("</testcase>")?;
                 }
 
@@ -143,6 +212,27 @@ fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
                         duration.as_secs_f64()
                     ))?;
                     self.write_message("<failure type=\"timeout\"/>")?;
+                    self.write_reruns("rerunFailure", &desc)?;
+                    self.write_message("</testcase>")?;
+                }
+
+                TestResult::TrTimedOut(timeout) => {
+                    self.write_message(&format!(
+                        "<testcase classname=\"{}\" \
+                         name=\"{}\" time=\"{}\">",
+                        class_name,
+                        test_name,
+                        duration.as_secs_f64()
+                    ))?;
+                    self.write_message(&format!(
+                        "<failure message=\"timed out after {timeout:?}\" type=\"timeout\"/>"
+                    ))?;
+                    if !stdout.is_empty() {
+                        self.write_message("<system-out>")?;
+                        self.write_message(&str_to_cdata(&String::from_utf8_lossy(&stdout)))?;
+                        self.write_message("</system-out>")?;
+                    }
+                    self.write_reruns("rerunFailure", &desc)?;
                     self.write_message("</testcase>")?;
                 }
 
@@ -162,12 +252,17 @@ fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
                         test_name,
                         duration.as_secs_f64()
                     ))?;
-                    if stdout.is_empty() || !state.options.display_output {
+                    let display_output = !stdout.is_empty() && state.options.display_output;
+                    if !display_output && !self.reruns.contains_key(desc.name.as_slice()) {
                         self.write_message("/>")?;
                     } else {
-                        self.write_message("><system-out>")?;
-                        self.write_message(&str_to_cdata(&String::from_utf8_lossy(&stdout)))?;
-                        self.write_message("</system-out>")?;
+                        self.write_message(">")?;
+                        if display_output {
+                            self.write_message("<system-out>")?;
+                            self.write_message(&str_to_cdata(&String::from_utf8_lossy(&stdout)))?;
+                            self.write_message("</system-out>")?;
+                        }
+                        self.write_reruns("flakyFailure", &desc)?;
                         self.write_message("</testcase>")?;
                     }
                 }
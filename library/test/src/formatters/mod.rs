@@ -6,14 +6,18 @@
 use crate::time;
 use crate::types::{TestDesc, TestName};
 
+mod github;
 mod json;
 mod junit;
 mod pretty;
+mod tap;
 mod terse;
 
+pub(crate) use self::github::GithubFormatter;
 pub(crate) use self::json::JsonFormatter;
 pub(crate) use self::junit::JunitFormatter;
 pub(crate) use self::pretty::PrettyFormatter;
+pub(crate) use self::tap::TapFormatter;
 pub(crate) use self::terse::TerseFormatter;
 
 pub(crate) trait OutputFormatter {
@@ -24,11 +28,19 @@ pub(crate) trait OutputFormatter {
     fn write_run_start(&mut self, test_count: usize, shuffle_seed: Option<u64>) -> io::Result<()>;
     fn write_test_start(&mut self, desc: &TestDesc) -> io::Result<()>;
     fn write_timeout(&mut self, desc: &TestDesc) -> io::Result<()>;
+    fn write_retry(
+        &mut self,
+        desc: &TestDesc,
+        result: &TestResult,
+        attempt: usize,
+        stdout: &[u8],
+    ) -> io::Result<()>;
     fn write_result(
         &mut self,
         desc: &TestDesc,
         result: &TestResult,
         exec_time: Option<&time::TestExecTime>,
+        attempts: usize,
         stdout: &[u8],
         state: &ConsoleTestState,
     ) -> io::Result<()>;
@@ -1,7 +1,9 @@
 use std::io;
 use std::io::prelude::Write;
+use std::time::Duration;
 
 use super::OutputFormatter;
+use crate::baseline::{BenchChange, ChangeVerdict};
 use crate::bench::fmt_bench_samples;
 use crate::console::{ConsoleTestDiscoveryState, ConsoleTestState, OutputLocation};
 use crate::test_result::TestResult;
@@ -51,10 +53,18 @@ pub fn write_ignored(&mut self, message: Option<&'static str>) -> io::Result<()>
         }
     }
 
+    pub fn write_flaky_ok(&mut self, attempts: usize) -> io::Result<()> {
+        self.write_short_result(&format!("ok (flaky, {attempts} attempts)"), term::color::YELLOW)
+    }
+
     pub fn write_time_failed(&mut self) -> io::Result<()> {
         self.write_short_result("FAILED (time limit exceeded)", term::color::RED)
     }
 
+    pub fn write_timed_out(&mut self, timeout: Duration) -> io::Result<()> {
+        self.write_short_result(&format!("FAILED (timed out after {timeout:?})"), term::color::RED)
+    }
+
     pub fn write_bench(&mut self) -> io::Result<()> {
         self.write_pretty("bench", term::color::CYAN)
     }
@@ -166,6 +176,37 @@ pub fn write_time_failures(&mut self, state: &ConsoleTestState) -> io::Result<()
         self.write_results(&state.time_failures, "failures (time limit exceeded)")
     }
 
+    pub fn write_flaky_tests(&mut self, state: &ConsoleTestState) -> io::Result<()> {
+        self.write_plain("\nflaky:\n")?;
+        let mut flaky: Vec<_> =
+            state.flaky.iter().map(|(f, attempts)| (f.name.to_string(), attempts)).collect();
+        flaky.sort();
+        for (name, attempts) in &flaky {
+            self.write_plain(&format!("    {name} ({attempts} attempts)\n"))?;
+        }
+        Ok(())
+    }
+
+    pub fn write_regressions(&mut self, state: &ConsoleTestState) -> io::Result<()> {
+        self.write_plain("\nregressions:\n")?;
+        let mut regressions: Vec<_> =
+            state.regressions.iter().map(|(f, change)| (f.name.to_string(), change)).collect();
+        regressions.sort_by(|a, b| a.0.cmp(&b.0));
+        for (name, change) in &regressions {
+            self.write_plain(&format!("    {name}: {change}\n"))?;
+        }
+        Ok(())
+    }
+
+    fn write_bench_change(&mut self, change: &BenchChange) -> io::Result<()> {
+        self.write_plain(" change: ")?;
+        match change.verdict {
+            ChangeVerdict::Regressed => self.write_pretty(&change.to_string(), term::color::RED),
+            ChangeVerdict::Improved => self.write_pretty(&change.to_string(), term::color::GREEN),
+            ChangeVerdict::NoChange => self.write_plain(change.to_string()),
+        }
+    }
+
     fn write_test_name(&mut self, desc: &TestDesc) -> io::Result<()> {
         let name = desc.padded_name(self.max_name_len, desc.name.padding());
         if let Some(test_mode) = desc.test_mode() {
@@ -233,22 +274,28 @@ fn write_result(
         desc: &TestDesc,
         result: &TestResult,
         exec_time: Option<&time::TestExecTime>,
+        attempts: usize,
         _: &[u8],
-        _: &ConsoleTestState,
+        state: &ConsoleTestState,
     ) -> io::Result<()> {
         if self.is_multithreaded {
             self.write_test_name(desc)?;
         }
 
         match *result {
+            TestResult::TrOk if attempts > 1 => self.write_flaky_ok(attempts)?,
             TestResult::TrOk => self.write_ok()?,
             TestResult::TrFailed | TestResult::TrFailedMsg(_) => self.write_failed()?,
             TestResult::TrIgnored => self.write_ignored(desc.ignore_message)?,
             TestResult::TrBench(ref bs) => {
                 self.write_bench()?;
                 self.write_plain(format!(": {}", fmt_bench_samples(bs)))?;
+                if let Some(change) = state.compare_to_baseline(desc, bs) {
+                    self.write_bench_change(&change)?;
+                }
             }
             TestResult::TrTimedFail => self.write_time_failed()?,
+            TestResult::TrTimedOut(timeout) => self.write_timed_out(timeout)?,
         }
 
         self.write_time(desc, exec_time)?;
@@ -263,6 +310,24 @@ fn write_timeout(&mut self, desc: &TestDesc) -> io::Result<()> {
         ))
     }
 
+    fn write_retry(
+        &mut self,
+        desc: &TestDesc,
+        _: &TestResult,
+        attempt: usize,
+        _: &[u8],
+    ) -> io::Result<()> {
+        if self.is_multithreaded {
+            self.write_test_name(desc)?;
+        }
+
+        self.write_short_result(
+            &format!("FAILED (attempt {attempt}, retrying)"),
+            term::color::YELLOW,
+        )?;
+        self.write_plain("\n")
+    }
+
     fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
         if state.options.display_output {
             self.write_successes(state)?;
@@ -278,6 +343,14 @@ fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
             }
         }
 
+        if !state.flaky.is_empty() {
+            self.write_flaky_tests(state)?;
+        }
+
+        if !state.regressions.is_empty() {
+            self.write_regressions(state)?;
+        }
+
         self.write_plain("\ntest result: ")?;
 
         if success {
@@ -287,8 +360,13 @@ fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
             self.write_pretty("FAILED", term::color::RED)?;
         }
 
+        let flaky = if state.flaky.is_empty() {
+            String::new()
+        } else {
+            format!(" ({} flaky)", state.flaky.len())
+        };
         let s = format!(
-            ". {} passed; {} failed; {} ignored; {} measured; {} filtered out",
+            ". {} passed{flaky}; {} failed; {} ignored; {} measured; {} filtered out",
             state.passed, state.failed, state.ignored, state.measured, state.filtered_out
         );
 
@@ -0,0 +1,197 @@
+use std::io;
+use std::io::prelude::Write;
+
+use super::OutputFormatter;
+use crate::bench::fmt_bench_samples;
+use crate::console::{ConsoleTestDiscoveryState, ConsoleTestState, OutputLocation};
+use crate::test_result::TestResult;
+use crate::time;
+use crate::types::TestDesc;
+
+/// Writes the results as a [TAP 14](https://testanything.org/tap-version-14-specification.html)
+/// stream. Failures, retries and captured output are described in YAML diagnostic blocks.
+pub(crate) struct TapFormatter<T> {
+    out: OutputLocation<T>,
+    /// Number of the last test point written.
+    test_number: usize,
+}
+
+impl<T: Write> TapFormatter<T> {
+    pub fn new(out: OutputLocation<T>) -> Self {
+        Self { out, test_number: 0 }
+    }
+
+    #[cfg(test)]
+    pub fn output_location(&self) -> &OutputLocation<T> {
+        &self.out
+    }
+
+    fn write_line(&mut self, s: &str) -> io::Result<()> {
+        // Like the json formatter, write each line with a single call so that output from
+        // other threads can't end up in the middle of it.
+        self.out.write_all(format!("{s}\n").as_bytes())
+    }
+
+    fn write_test_point(
+        &mut self,
+        ok: bool,
+        desc: &TestDesc,
+        directive: Option<&str>,
+        diagnostics: &[(&str, String)],
+    ) -> io::Result<()> {
+        self.test_number += 1;
+        let status = if ok { "ok" } else { "not ok" };
+        // `#` starts a directive, so it has to be escaped in the description.
+        let name = desc.name.as_slice().replace('\\', "\\\\").replace('#', "\\#");
+        let directive = directive.map(|d| format!(" # {d}")).unwrap_or_default();
+        self.write_line(&format!("{status} {} - {name}{directive}", self.test_number))?;
+
+        if !diagnostics.is_empty() {
+            self.write_line("  ---")?;
+            for (key, value) in diagnostics {
+                self.write_line(&format!("  {key}: {value}"))?;
+            }
+            self.write_line("  ...")?;
+        }
+        Ok(())
+    }
+}
+
+/// Formats `s` as a YAML double-quoted scalar.
+fn yaml_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Formats `s` as a YAML literal block scalar, nested under a key of the diagnostic block.
+fn yaml_block(s: &str) -> String {
+    // The indentation is given explicitly, relative to the keys of the diagnostic block, since
+    // it would otherwise be inferred from the first line, which may itself start with spaces.
+    let mut out = String::from("|2");
+    for line in s.trim_end_matches('\n').lines() {
+        out.push('\n');
+        if !line.is_empty() {
+            out.push_str("    ");
+            out.push_str(line);
+        }
+    }
+    out
+}
+
+impl<T: Write> OutputFormatter for TapFormatter<T> {
+    fn write_discovery_start(&mut self) -> io::Result<()> {
+        Err(io::Error::new(io::ErrorKind::NotFound, "Not yet implemented!"))
+    }
+
+    fn write_test_discovered(&mut self, _desc: &TestDesc, _test_type: &str) -> io::Result<()> {
+        Err(io::Error::new(io::ErrorKind::NotFound, "Not yet implemented!"))
+    }
+
+    fn write_discovery_finish(&mut self, _state: &ConsoleTestDiscoveryState) -> io::Result<()> {
+        Err(io::Error::new(io::ErrorKind::NotFound, "Not yet implemented!"))
+    }
+
+    fn write_run_start(&mut self, _test_count: usize, shuffle_seed: Option<u64>) -> io::Result<()> {
+        // The plan is written at the end, since with `--fail-fast` not every test is run.
+        self.write_line("TAP version 14")?;
+        if let Some(shuffle_seed) = shuffle_seed {
+            self.write_line(&format!("# shuffle seed: {shuffle_seed}"))?;
+        }
+        Ok(())
+    }
+
+    fn write_test_start(&mut self, _desc: &TestDesc) -> io::Result<()> {
+        // We do not output anything on test start.
+        Ok(())
+    }
+
+    fn write_timeout(&mut self, desc: &TestDesc) -> io::Result<()> {
+        self.write_line(&format!(
+            "# test {} has been running for over {} seconds",
+            desc.name,
+            time::TEST_WARN_TIMEOUT_S
+        ))
+    }
+
+    fn write_retry(
+        &mut self,
+        desc: &TestDesc,
+        _result: &TestResult,
+        attempt: usize,
+        _stdout: &[u8],
+    ) -> io::Result<()> {
+        self.write_line(&format!("# test {} failed on attempt {attempt}, retrying", desc.name))
+    }
+
+    fn write_result(
+        &mut self,
+        desc: &TestDesc,
+        result: &TestResult,
+        exec_time: Option<&time::TestExecTime>,
+        attempts: usize,
+        stdout: &[u8],
+        state: &ConsoleTestState,
+    ) -> io::Result<()> {
+        let mut diagnostics = Vec::new();
+        let message = match *result {
+            TestResult::TrFailed => Some("test failed".to_owned()),
+            TestResult::TrFailedMsg(ref msg) => Some(msg.clone()),
+            TestResult::TrTimedFail => Some("time limit exceeded".to_owned()),
+            TestResult::TrTimedOut(timeout) => Some(format!("timed out after {timeout:?}")),
+            TestResult::TrOk | TestResult::TrIgnored | TestResult::TrBench(_) => None,
+        };
+        let failed = message.is_some();
+        if let Some(message) = message {
+            diagnostics.push(("message", yaml_string(&message)));
+            diagnostics.push(("severity", "fail".to_owned()));
+        }
+        if let TestResult::TrBench(ref bs) = *result {
+            diagnostics.push(("bench", yaml_string(&fmt_bench_samples(bs))));
+            if let Some(change) = state.compare_to_baseline(desc, bs) {
+                diagnostics.push(("baseline_change", yaml_string(&change.to_string())));
+            }
+        }
+        if attempts > 1 {
+            diagnostics.push(("attempts", attempts.to_string()));
+        }
+        if let Some(exec_time) = exec_time {
+            diagnostics.push(("duration_ms", (exec_time.0.as_secs_f64() * 1000.0).to_string()));
+        }
+        if !stdout.is_empty() && (failed || state.options.display_output) {
+            diagnostics.push(("stdout", yaml_block(&String::from_utf8_lossy(stdout))));
+        }
+
+        let directive = match *result {
+            TestResult::TrIgnored => Some(match desc.ignore_message {
+                Some(msg) => format!("SKIP {msg}"),
+                None => "SKIP".to_owned(),
+            }),
+            _ => None,
+        };
+
+        self.write_test_point(!failed, desc, directive.as_deref(), &diagnostics)
+    }
+
+    fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
+        self.write_line(&format!("1..{}", self.test_number))?;
+        self.write_line(&format!(
+            "# {} passed; {} failed; {} ignored; {} measured; {} filtered out",
+            state.passed, state.failed, state.ignored, state.measured, state.filtered_out
+        ))?;
+
+        Ok(state.failed == 0)
+    }
+}
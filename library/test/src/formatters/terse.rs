@@ -2,6 +2,7 @@
 use std::io::prelude::Write;
 
 use super::OutputFormatter;
+use crate::baseline::{BenchChange, ChangeVerdict};
 use crate::bench::fmt_bench_samples;
 use crate::console::{ConsoleTestDiscoveryState, ConsoleTestState, OutputLocation};
 use crate::test_result::TestResult;
@@ -172,6 +173,37 @@ pub fn write_failures(&mut self, state: &ConsoleTestState) -> io::Result<()> {
         Ok(())
     }
 
+    pub fn write_flaky_tests(&mut self, state: &ConsoleTestState) -> io::Result<()> {
+        self.write_plain("\nflaky:\n")?;
+        let mut flaky: Vec<_> =
+            state.flaky.iter().map(|(f, attempts)| (f.name.to_string(), attempts)).collect();
+        flaky.sort();
+        for (name, attempts) in &flaky {
+            self.write_plain(&format!("    {name} ({attempts} attempts)\n"))?;
+        }
+        Ok(())
+    }
+
+    pub fn write_regressions(&mut self, state: &ConsoleTestState) -> io::Result<()> {
+        self.write_plain("\nregressions:\n")?;
+        let mut regressions: Vec<_> =
+            state.regressions.iter().map(|(f, change)| (f.name.to_string(), change)).collect();
+        regressions.sort_by(|a, b| a.0.cmp(&b.0));
+        for (name, change) in &regressions {
+            self.write_plain(&format!("    {name}: {change}\n"))?;
+        }
+        Ok(())
+    }
+
+    fn write_bench_change(&mut self, change: &BenchChange) -> io::Result<()> {
+        self.write_plain(" change: ")?;
+        match change.verdict {
+            ChangeVerdict::Regressed => self.write_pretty(&change.to_string(), term::color::RED),
+            ChangeVerdict::Improved => self.write_pretty(&change.to_string(), term::color::GREEN),
+            ChangeVerdict::NoChange => self.write_plain(change.to_string()),
+        }
+    }
+
     fn write_test_name(&mut self, desc: &TestDesc) -> io::Result<()> {
         let name = desc.padded_name(self.max_name_len, desc.name.padding());
         if let Some(test_mode) = desc.test_mode() {
@@ -225,21 +257,27 @@ fn write_result(
         desc: &TestDesc,
         result: &TestResult,
         _: Option<&time::TestExecTime>,
+        _: usize,
         _: &[u8],
-        _: &ConsoleTestState,
+        state: &ConsoleTestState,
     ) -> io::Result<()> {
         match *result {
             TestResult::TrOk => self.write_ok(),
-            TestResult::TrFailed | TestResult::TrFailedMsg(_) | TestResult::TrTimedFail => {
-                self.write_failed(desc.name.as_slice())
-            }
+            TestResult::TrFailed
+            | TestResult::TrFailedMsg(_)
+            | TestResult::TrTimedFail
+            | TestResult::TrTimedOut(_) => self.write_failed(desc.name.as_slice()),
             TestResult::TrIgnored => self.write_ignored(),
             TestResult::TrBench(ref bs) => {
                 if self.is_multithreaded {
                     self.write_test_name(desc)?;
                 }
                 self.write_bench()?;
-                self.write_plain(format!(": {}\n", fmt_bench_samples(bs)))
+                self.write_plain(format!(": {}", fmt_bench_samples(bs)))?;
+                if let Some(change) = state.compare_to_baseline(desc, bs) {
+                    self.write_bench_change(&change)?;
+                }
+                self.write_plain("\n")
             }
         }
     }
@@ -252,6 +290,11 @@ fn write_timeout(&mut self, desc: &TestDesc) -> io::Result<()> {
         ))
     }
 
+    fn write_retry(&mut self, _: &TestDesc, _: &TestResult, _: usize, _: &[u8]) -> io::Result<()> {
+        // Only the final result of a retried test is shown.
+        Ok(())
+    }
+
     fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
         if state.options.display_output {
             self.write_outputs(state)?;
@@ -261,6 +304,14 @@ fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
             self.write_failures(state)?;
         }
 
+        if !state.flaky.is_empty() {
+            self.write_flaky_tests(state)?;
+        }
+
+        if !state.regressions.is_empty() {
+            self.write_regressions(state)?;
+        }
+
         self.write_plain("\ntest result: ")?;
 
         if success {
@@ -270,8 +321,13 @@ fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
             self.write_pretty("FAILED", term::color::RED)?;
         }
 
+        let flaky = if state.flaky.is_empty() {
+            String::new()
+        } else {
+            format!(" ({} flaky)", state.flaky.len())
+        };
         let s = format!(
-            ". {} passed; {} failed; {} ignored; {} measured; {} filtered out",
+            ". {} passed{flaky}; {} failed; {} ignored; {} measured; {} filtered out",
             state.passed, state.failed, state.ignored, state.measured, state.filtered_out
         );
 
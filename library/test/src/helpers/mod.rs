@@ -3,4 +3,6 @@
 
 pub mod concurrency;
 pub mod metrics;
+pub mod retry;
+pub mod shard;
 pub mod shuffle;
@@ -0,0 +1,76 @@
+//! Helper module for re-running failed tests requested with `--retries`.
+
+use std::collections::HashMap;
+
+use crate::event::CompletedTest;
+use crate::test_result::TestResult;
+use crate::types::{TestDescAndFn, TestFn, TestId};
+
+pub struct RetryState {
+    max_retries: usize,
+    /// Copies of the functions of running tests, kept to run them again if they fail.
+    test_fns: HashMap<TestId, TestFn>,
+    /// Number of failed attempts of tests that are being retried.
+    failed_attempts: HashMap<TestId, usize>,
+}
+
+impl RetryState {
+    pub fn new(max_retries: usize) -> Self {
+        RetryState { max_retries, test_fns: HashMap::new(), failed_attempts: HashMap::new() }
+    }
+
+    /// Remembers how to run `test` again. Dynamic tests can only be run once,
+    /// so they are never retried.
+    pub fn track(&mut self, id: TestId, test: &TestDescAndFn) {
+        if self.max_retries == 0 || self.test_fns.contains_key(&id) {
+            return;
+        }
+        if let Some(testfn) = clone_test_fn(&test.testfn) {
+            self.test_fns.insert(id, testfn);
+        }
+    }
+
+    /// Decides what to do with a finished test, setting its `attempts`. Returns the
+    /// test to run again if it failed and has retries left, or `None` if
+    /// `completed_test` is its final result.
+    pub fn complete(&mut self, completed_test: &mut CompletedTest) -> Option<TestDescAndFn> {
+        let id = completed_test.id;
+        let failed_attempts = self.failed_attempts.get(&id).copied().unwrap_or(0);
+
+        // Tests that timed out in-process are abandoned rather than stopped, and their
+        // late result would be mistaken for the result of the retry, so they aren't retried.
+        let should_retry = match completed_test.result {
+            TestResult::TrFailed | TestResult::TrFailedMsg(_) | TestResult::TrTimedFail => {
+                failed_attempts < self.max_retries
+            }
+            TestResult::TrOk
+            | TestResult::TrIgnored
+            | TestResult::TrBench(_)
+            | TestResult::TrTimedOut(_) => false,
+        };
+
+        if should_retry {
+            if let Some(testfn) = self.test_fns.get(&id).and_then(clone_test_fn) {
+                self.failed_attempts.insert(id, failed_attempts + 1);
+                completed_test.attempts = failed_attempts + 1;
+                return Some(TestDescAndFn { desc: completed_test.desc.clone(), testfn });
+            }
+        }
+
+        self.test_fns.remove(&id);
+        self.failed_attempts.remove(&id);
+        completed_test.attempts = failed_attempts + 1;
+        None
+    }
+}
+
+fn clone_test_fn(testfn: &TestFn) -> Option<TestFn> {
+    match *testfn {
+        TestFn::StaticTestFn(f) => Some(TestFn::StaticTestFn(f)),
+        TestFn::StaticBenchAsTestFn(f) => Some(TestFn::StaticBenchAsTestFn(f)),
+        TestFn::StaticBenchFn(_)
+        | TestFn::DynTestFn(_)
+        | TestFn::DynBenchFn(_)
+        | TestFn::DynBenchAsTestFn(_) => None,
+    }
+}
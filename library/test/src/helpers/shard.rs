@@ -0,0 +1,19 @@
+//! Helper module for splitting the tests into shards with `--shard-index` and `--total-shards`.
+
+use crate::types::TestName;
+
+/// Returns whether the test named `name` belongs to shard `index` out of `total`.
+pub fn is_in_shard(name: &TestName, index: usize, total: usize) -> bool {
+    (stable_hash(name.as_slice()) % total as u64) as usize == index
+}
+
+// FNV-1a. `DefaultHasher` isn't used because its output may change between
+// releases, and every machine has to agree on which shard a test is in.
+fn stable_hash(s: &str) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in s.bytes() {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
@@ -55,11 +55,12 @@ pub mod test {
 use std::mem::ManuallyDrop;
 use std::panic::{self, catch_unwind, AssertUnwindSafe, PanicHookInfo};
 use std::process::{self, Command, Termination};
-use std::sync::mpsc::{channel, Sender};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError, Sender};
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 use std::{env, io, thread};
 
+mod baseline;
 pub mod bench;
 mod cli;
 mod console;
@@ -80,6 +81,8 @@ pub mod test {
 
 use event::{CompletedTest, TestEvent};
 use helpers::concurrency::get_concurrency;
+use helpers::retry::RetryState;
+use helpers::shard::is_in_shard;
 use helpers::shuffle::{get_shuffle_seed, shuffle_tests};
 use options::RunStrategy;
 use test_result::*;
@@ -263,7 +266,6 @@ pub fn run_tests<F>(
 {
     use std::collections::HashMap;
     use std::hash::{BuildHasherDefault, DefaultHasher};
-    use std::sync::mpsc::RecvTimeoutError;
 
     struct RunningTest {
         join_handle: Option<thread::JoinHandle<()>>,
@@ -291,6 +293,15 @@ struct TimeoutEntry {
         timeout: Instant,
     }
 
+    // Tests running in-process can't be killed, so once they exceed their
+    // `--test-timeout` they are reported as timed out and their thread is abandoned.
+    struct HardTimeoutEntry {
+        id: TestId,
+        desc: TestDesc,
+        timeout: Duration,
+        deadline: Instant,
+    }
+
     let tests_len = tests.len();
 
     let mut filtered = FilteredTests { tests: Vec::new(), benches: Vec::new(), next_id: 0 };
@@ -343,6 +354,14 @@ struct TimeoutEntry {
 
     let mut running_tests: TestMap = HashMap::default();
     let mut timeout_queue: VecDeque<TimeoutEntry> = VecDeque::new();
+    let mut hard_timeouts: Vec<HardTimeoutEntry> = Vec::new();
+    let mut retry_state = RetryState::new(opts.retries);
+
+    // Subprocesses enforce their own timeout (see `spawn_test_subprocess`).
+    let hard_timeout_for = |desc: &TestDesc| match run_strategy {
+        RunStrategy::InProcess => time::get_test_timeout(desc, opts.test_timeout),
+        RunStrategy::SpawnPrimary => None,
+    };
 
     fn get_timed_out_tests(
         running_tests: &TestMap,
@@ -362,8 +381,38 @@ fn get_timed_out_tests(
         timed_out
     }
 
-    fn calc_timeout(timeout_queue: &VecDeque<TimeoutEntry>) -> Option<Duration> {
-        timeout_queue.front().map(|&TimeoutEntry { timeout: next_timeout, .. }| {
+    fn get_hung_tests(
+        running_tests: &mut TestMap,
+        hard_timeouts: &mut Vec<HardTimeoutEntry>,
+    ) -> Vec<CompletedTest> {
+        let now = Instant::now();
+        let mut hung = Vec::new();
+        let mut i = 0;
+        while i < hard_timeouts.len() {
+            if now < hard_timeouts[i].deadline {
+                i += 1;
+                continue;
+            }
+            let HardTimeoutEntry { id, desc, timeout, .. } = hard_timeouts.swap_remove(i);
+            // Dropping the join handle detaches the thread running the hung test.
+            if running_tests.remove(&id).is_some() {
+                hung.push(CompletedTest::new(id, desc, TrTimedOut(timeout), None, Vec::new()));
+            }
+        }
+        hung
+    }
+
+    fn calc_timeout(
+        timeout_queue: &VecDeque<TimeoutEntry>,
+        hard_timeouts: &[HardTimeoutEntry],
+    ) -> Option<Duration> {
+        let next_warning = timeout_queue.front().map(|entry| entry.timeout);
+        let next_deadline = hard_timeouts.iter().map(|entry| entry.deadline).min();
+        let next_timeout = match (next_warning, next_deadline) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        next_timeout.map(|next_timeout| {
             let now = Instant::now();
             if next_timeout >= now { next_timeout - now } else { Duration::new(0, 0) }
         })
@@ -374,14 +423,34 @@ fn calc_timeout(timeout_queue: &VecDeque<TimeoutEntry>) -> Option<Duration> {
             let (id, test) = remaining.pop_front().unwrap();
             let event = TestEvent::TeWait(test.desc.clone());
             notify_about_test_event(event)?;
+            let desc = test.desc.clone();
+            let hard_timeout = hard_timeout_for(&desc);
+            retry_state.track(id, &test);
             let join_handle = run_test(opts, !opts.run_tests, id, test, run_strategy, tx.clone());
             // Wait for the test to complete.
-            let mut completed_test = rx.recv().unwrap();
-            RunningTest { join_handle }.join(&mut completed_test);
+            let deadline = hard_timeout.map(|timeout| Instant::now() + timeout);
+            let mut completed_test = match recv_test_result(&rx, id, deadline) {
+                Some(mut completed_test) => {
+                    RunningTest { join_handle }.join(&mut completed_test);
+                    completed_test
+                }
+                // Dropping the join handle detaches the thread running the hung test.
+                None => {
+                    let result = TrTimedOut(hard_timeout.unwrap());
+                    CompletedTest::new(id, desc, result, None, Vec::new())
+                }
+            };
+
+            if let Some(test) = retry_state.complete(&mut completed_test) {
+                let event = TestEvent::TeRetry(completed_test);
+                notify_about_test_event(event)?;
+                remaining.push_front((id, test));
+                continue;
+            }
 
             let fail_fast = match completed_test.result {
                 TrIgnored | TrOk | TrBench(_) => false,
-                TrFailed | TrFailedMsg(_) | TrTimedFail => opts.fail_fast,
+                TrFailed | TrFailedMsg(_) | TrTimedFail | TrTimedOut(_) => opts.fail_fast,
             };
 
             let event = TestEvent::TeResult(completed_test);
@@ -400,6 +469,15 @@ fn calc_timeout(timeout_queue: &VecDeque<TimeoutEntry>) -> Option<Duration> {
 
                 let event = TestEvent::TeWait(desc.clone());
                 notify_about_test_event(event)?; //here no pad
+                if let Some(hard_timeout) = hard_timeout_for(&desc) {
+                    hard_timeouts.push(HardTimeoutEntry {
+                        id,
+                        desc: desc.clone(),
+                        timeout: hard_timeout,
+                        deadline: Instant::now() + hard_timeout,
+                    });
+                }
+                retry_state.track(id, &test);
                 let join_handle =
                     run_test(opts, !opts.run_tests, id, test, run_strategy, tx.clone());
                 running_tests.insert(id, RunningTest { join_handle });
@@ -408,41 +486,68 @@ fn calc_timeout(timeout_queue: &VecDeque<TimeoutEntry>) -> Option<Duration> {
             }
 
             let mut res;
+            let mut hung_tests;
             loop {
-                if let Some(timeout) = calc_timeout(&timeout_queue) {
+                if let Some(timeout) = calc_timeout(&timeout_queue, &hard_timeouts) {
                     res = rx.recv_timeout(timeout);
                     for test in get_timed_out_tests(&running_tests, &mut timeout_queue) {
                         let event = TestEvent::TeTimeout(test);
                         notify_about_test_event(event)?;
                     }
+                    hung_tests = get_hung_tests(&mut running_tests, &mut hard_timeouts);
 
                     match res {
-                        Err(RecvTimeoutError::Timeout) => {
+                        Err(RecvTimeoutError::Timeout) if hung_tests.is_empty() => {
                             // Result is not yet ready, continue waiting.
                         }
                         _ => {
-                            // We've got a result, stop the loop.
+                            // We've got a result (or abandoned a hung test), stop the loop.
                             break;
                         }
                     }
                 } else {
                     res = rx.recv().map_err(|_| RecvTimeoutError::Disconnected);
+                    hung_tests = Vec::new();
                     break;
                 }
             }
 
-            let mut completed_test = res.unwrap();
-            let running_test = running_tests.remove(&completed_test.id).unwrap();
-            running_test.join(&mut completed_test);
+            let mut completed_tests = hung_tests;
+            match res {
+                Ok(mut completed_test) => {
+                    // Late results of tests that were already reported as timed out are dropped.
+                    if let Some(running_test) = running_tests.remove(&completed_test.id) {
+                        running_test.join(&mut completed_test);
+                        hard_timeouts.retain(|entry| entry.id != completed_test.id);
+                        completed_tests.push(completed_test);
+                    }
+                }
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => panic!("test result channel disconnected"),
+            }
 
-            let fail_fast = match completed_test.result {
-                TrIgnored | TrOk | TrBench(_) => false,
-                TrFailed | TrFailedMsg(_) | TrTimedFail => opts.fail_fast,
-            };
+            let mut fail_fast = false;
+            for mut completed_test in completed_tests {
+                if let Some(test) = retry_state.complete(&mut completed_test) {
+                    let id = completed_test.id;
+                    // Don't warn about the retry based on when the first attempt started.
+                    timeout_queue.retain(|entry| entry.id != id);
+                    let event = TestEvent::TeRetry(completed_test);
+                    notify_about_test_event(event)?;
+                    remaining.push_front((id, test));
+                    pending -= 1;
+                    continue;
+                }
 
-            let event = TestEvent::TeResult(completed_test);
-            notify_about_test_event(event)?;
-            pending -= 1;
+                fail_fast |= match completed_test.result {
+                    TrIgnored | TrOk | TrBench(_) => false,
+                    TrFailed | TrFailedMsg(_) | TrTimedFail | TrTimedOut(_) => opts.fail_fast,
+                };
+
+                let event = TestEvent::TeResult(completed_test);
+                notify_about_test_event(event)?;
+                pending -= 1;
+            }
 
             if fail_fast {
                 // Prevent remaining test threads from panicking
@@ -469,6 +574,32 @@ fn calc_timeout(timeout_queue: &VecDeque<TimeoutEntry>) -> Option<Duration> {
     Ok(())
 }
 
+/// Waits for the result of the test `id`, skipping late results of tests that were
+/// previously abandoned after timing out. Returns `None` if `deadline` passes first.
+fn recv_test_result(
+    rx: &Receiver<CompletedTest>,
+    id: TestId,
+    deadline: Option<Instant>,
+) -> Option<CompletedTest> {
+    loop {
+        let completed_test = match deadline {
+            Some(deadline) => {
+                match rx.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+                    Ok(completed_test) => completed_test,
+                    Err(RecvTimeoutError::Timeout) => return None,
+                    Err(RecvTimeoutError::Disconnected) => {
+                        panic!("test result channel disconnected")
+                    }
+                }
+            }
+            None => rx.recv().unwrap(),
+        };
+        if completed_test.id == id {
+            return Some(completed_test);
+        }
+    }
+}
+
 pub fn filter_tests(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> Vec<TestDescAndFn> {
     let mut filtered = tests;
     let matches_filter = |test: &TestDescAndFn, filter: &str| {
@@ -507,6 +638,11 @@ pub fn filter_tests(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> Vec<TestDescA
         RunIgnored::No => {}
     }
 
+    // Keep only the tests in this shard
+    if let Some((index, total)) = opts.shard {
+        filtered.retain(|test| is_in_shard(&test.desc.name, index, total));
+    }
+
     filtered
 }
 
@@ -558,6 +694,7 @@ pub fn run_test(
             let name = desc.name.clone();
             let nocapture = opts.nocapture;
             let time_options = opts.time_options;
+            let test_timeout = time::get_test_timeout(&desc, opts.test_timeout);
             let bench_benchmarks = opts.bench_benchmarks;
 
             let runtest = move || match strategy {
@@ -577,6 +714,7 @@ pub fn run_test(
                     time_options.is_some(),
                     monitor_ch,
                     time_options,
+                    test_timeout,
                     bench_benchmarks,
                 ),
             };
@@ -677,6 +815,7 @@ fn spawn_test_subprocess(
     report_time: bool,
     monitor_ch: Sender<CompletedTest>,
     time_opts: Option<time::TestTimeOptions>,
+    test_timeout: Option<Duration>,
     bench_benchmarks: bool,
 ) {
     let (result, test_output, exec_time) = (|| {
@@ -688,13 +827,21 @@ fn spawn_test_subprocess(
         if bench_benchmarks {
             command.env(SECONDARY_TEST_BENCH_BENCHMARKS_VAR, "1");
         }
+        command.stdin(process::Stdio::null());
         if nocapture {
             command.stdout(process::Stdio::inherit());
             command.stderr(process::Stdio::inherit());
+        } else {
+            command.stdout(process::Stdio::piped());
+            command.stderr(process::Stdio::piped());
         }
 
         let start = report_time.then(Instant::now);
-        let output = match command.output() {
+        let output = match test_timeout {
+            Some(timeout) => output_with_timeout(&mut command, timeout),
+            None => command.output().map(|output| (output, false)),
+        };
+        let (output, timed_out) = match output {
             Ok(out) => out,
             Err(e) => {
                 let err = format!("Failed to spawn {} as child for test: {:?}", args[0], e);
@@ -711,7 +858,10 @@ fn spawn_test_subprocess(
         formatters::write_stderr_delimiter(&mut test_output, &desc.name);
         test_output.extend_from_slice(&stderr);
 
-        let result = get_result_from_exit_code(&desc, status, &time_opts, &exec_time);
+        let result = match test_timeout {
+            Some(timeout) if timed_out => TrTimedOut(timeout),
+            _ => get_result_from_exit_code(&desc, status, &time_opts, &exec_time),
+        };
         (result, test_output, exec_time)
     })();
 
@@ -719,6 +869,54 @@ fn spawn_test_subprocess(
     monitor_ch.send(message).unwrap();
 }
 
+/// Like `Command::output`, but kills the child if it is still running after `timeout`.
+/// Also returns whether the child had to be killed.
+fn output_with_timeout(
+    command: &mut Command,
+    timeout: Duration,
+) -> io::Result<(process::Output, bool)> {
+    use std::io::Read;
+
+    fn drain<R: Read + Send + 'static>(pipe: Option<R>) -> Option<thread::JoinHandle<Vec<u8>>> {
+        pipe.map(|mut pipe| {
+            thread::spawn(move || {
+                let mut buf = Vec::new();
+                let _ = pipe.read_to_end(&mut buf);
+                buf
+            })
+        })
+    }
+
+    fn collect(reader: Option<thread::JoinHandle<Vec<u8>>>) -> Vec<u8> {
+        reader.map(|reader| reader.join().unwrap_or_default()).unwrap_or_default()
+    }
+
+    let deadline = Instant::now() + timeout;
+    let mut child = command.spawn()?;
+
+    // Drain the pipes on separate threads so that the child can't block on a
+    // full pipe while we are waiting for it to exit.
+    let stdout = drain(child.stdout.take());
+    let stderr = drain(child.stderr.take());
+
+    let mut timed_out = false;
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if Instant::now() >= deadline {
+            // The child may have exited in the meantime, in which case `kill`
+            // fails and `wait` simply reaps it.
+            let _ = child.kill();
+            timed_out = true;
+            break child.wait()?;
+        }
+        thread::sleep(Duration::from_millis(10));
+    };
+
+    Ok((process::Output { status, stdout: collect(stdout), stderr: collect(stderr) }, timed_out))
+}
+
 fn run_test_in_spawned_subprocess(desc: TestDesc, runnable_test: RunnableTest) -> ! {
     let builtin_panic_hook = panic::take_hook();
     let record_result = Arc::new(move |panic_info: Option<&'_ PanicHookInfo<'_>>| {
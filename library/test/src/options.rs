@@ -36,6 +36,10 @@ pub enum OutputFormat {
     Json,
     /// JUnit output
     Junit,
+    /// TAP 14 output
+    Tap,
+    /// GitHub Actions annotations for failed tests
+    Github,
 }
 
 /// Whether ignored test should be run or not
@@ -2,6 +2,7 @@
 #[cfg(unix)]
 use std::os::unix::process::ExitStatusExt;
 use std::process::ExitStatus;
+use std::time::Duration;
 
 pub use self::TestResult::*;
 use super::bench::BenchSamples;
@@ -35,6 +36,9 @@ pub enum TestResult {
     TrIgnored,
     TrBench(BenchSamples),
     TrTimedFail,
+    /// The test did not finish within its `--test-timeout` (or `#[test_timeout]`)
+    /// limit and was killed or abandoned.
+    TrTimedOut(Duration),
 }
 
 /// Creates a `TestResult` depending on the raw result of test execution
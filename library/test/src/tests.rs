@@ -1,7 +1,8 @@
 use super::*;
 use crate::{
+    baseline::{self, Baseline, BaselineSample, ChangeVerdict, DEFAULT_NOISE_THRESHOLD},
     console::OutputLocation,
-    formatters::PrettyFormatter,
+    formatters::{GithubFormatter, JunitFormatter, OutputFormatter, PrettyFormatter, TapFormatter},
     test::{
         parse_opts,
         MetricMap,
@@ -32,6 +33,12 @@ fn new() -> TestOpts {
             test_threads: None,
             skip: vec![],
             time_options: None,
+            test_timeout: None,
+            retries: 0,
+            shard: None,
+            save_baseline: None,
+            baseline: None,
+            noise_threshold: None,
             options: Options::new(),
             fail_fast: false,
         }
@@ -54,6 +61,7 @@ fn one_ignored_one_unignored_test() -> Vec<TestDescAndFn> {
                 compile_fail: false,
                 no_run: false,
                 test_type: TestType::Unknown,
+                timeout: None,
             },
             testfn: DynTestFn(Box::new(move || Ok(()))),
         },
@@ -71,6 +79,7 @@ fn one_ignored_one_unignored_test() -> Vec<TestDescAndFn> {
                 compile_fail: false,
                 no_run: false,
                 test_type: TestType::Unknown,
+                timeout: None,
             },
             testfn: DynTestFn(Box::new(move || Ok(()))),
         },
@@ -96,6 +105,7 @@ fn f() -> Result<(), String> {
             compile_fail: false,
             no_run: false,
             test_type: TestType::Unknown,
+            timeout: None,
         },
         testfn: DynTestFn(Box::new(f)),
     };
@@ -124,6 +134,7 @@ fn f() -> Result<(), String> {
             compile_fail: false,
             no_run: false,
             test_type: TestType::Unknown,
+            timeout: None,
         },
         testfn: DynTestFn(Box::new(f)),
     };
@@ -155,6 +166,7 @@ fn f() -> Result<(), String> {
             compile_fail: false,
             no_run: false,
             test_type: TestType::Unknown,
+            timeout: None,
         },
         testfn: DynTestFn(Box::new(f)),
     };
@@ -186,6 +198,7 @@ fn f() -> Result<(), String> {
             compile_fail: false,
             no_run: false,
             test_type: TestType::Unknown,
+            timeout: None,
         },
         testfn: DynTestFn(Box::new(f)),
     };
@@ -222,6 +235,7 @@ fn f() -> Result<(), String> {
             compile_fail: false,
             no_run: false,
             test_type: TestType::Unknown,
+            timeout: None,
         },
         testfn: DynTestFn(Box::new(f)),
     };
@@ -263,6 +277,7 @@ fn f() -> Result<(), String> {
             compile_fail: false,
             no_run: false,
             test_type: TestType::Unknown,
+            timeout: None,
         },
         testfn: DynTestFn(Box::new(f)),
     };
@@ -297,6 +312,7 @@ fn f() -> Result<(), String> {
                 compile_fail: false,
                 no_run: false,
                 test_type: TestType::Unknown,
+                timeout: None,
             },
             testfn: DynTestFn(Box::new(f)),
         };
@@ -330,6 +346,7 @@ fn f() -> Result<(), String> {
             compile_fail: false,
             no_run: false,
             test_type: TestType::Unknown,
+            timeout: None,
         },
         testfn: DynTestFn(Box::new(f)),
     };
@@ -372,6 +389,7 @@ fn f() -> Result<(), String> {
             compile_fail: false,
             no_run: false,
             test_type,
+            timeout: None,
         },
         testfn: DynTestFn(Box::new(f)),
     };
@@ -416,6 +434,7 @@ fn typed_test_desc(test_type: TestType) -> TestDesc {
         compile_fail: false,
         no_run: false,
         test_type,
+        timeout: None,
     }
 }
 
@@ -533,6 +552,7 @@ pub fn exclude_should_panic_option() {
             compile_fail: false,
             no_run: false,
             test_type: TestType::Unknown,
+            timeout: None,
         },
         testfn: DynTestFn(Box::new(move || Ok(()))),
     });
@@ -562,6 +582,7 @@ fn tests() -> Vec<TestDescAndFn> {
                     compile_fail: false,
                     no_run: false,
                     test_type: TestType::Unknown,
+                    timeout: None,
                 },
                 testfn: DynTestFn(Box::new(move || Ok(()))),
             })
@@ -658,6 +679,7 @@ fn testfn() -> Result<(), String> {
                 compile_fail: false,
                 no_run: false,
                 test_type: TestType::Unknown,
+                timeout: None,
             },
             testfn: DynTestFn(Box::new(testfn)),
         };
@@ -731,6 +753,139 @@ pub fn order_depends_on_more_than_seed() {
     assert!(left.iter().zip(right).any(|(a, b)| a.0 != b.0));
 }
 
+#[test]
+pub fn shards_partition_tests() {
+    let names = |tests: Vec<TestDescAndFn>| {
+        tests.into_iter().map(|t| t.desc.name.to_string()).collect::<Vec<_>>()
+    };
+    let all = names(filter_tests(&TestOpts::new(), sample_tests()));
+
+    let total = 3;
+    let mut union = Vec::new();
+    for index in 0..total {
+        let opts = TestOpts { shard: Some((index, total)), ..TestOpts::new() };
+        let shard = names(filter_tests(&opts, sample_tests()));
+        assert!(shard.len() < all.len());
+        // The same shard is selected every time.
+        assert_eq!(shard, names(filter_tests(&opts, sample_tests())));
+        union.extend(shard);
+    }
+
+    union.sort();
+    let mut all = all;
+    all.sort();
+    assert_eq!(union, all);
+}
+
+#[test]
+fn parse_shard_options() {
+    let args = |index: &str, total: &str| {
+        vec![
+            "progname".to_string(),
+            "--shard-index".to_string(),
+            index.to_string(),
+            "--total-shards".to_string(),
+            total.to_string(),
+            "-Zunstable-options".to_string(),
+        ]
+    };
+    let opts = parse_opts(&args("1", "4")).unwrap().unwrap();
+    assert_eq!(opts.shard, Some((1, 4)));
+
+    assert!(parse_opts(&args("4", "4")).unwrap().is_err());
+    assert!(parse_opts(&args("0", "0")).unwrap().is_err());
+
+    let mut only_index = args("1", "4");
+    only_index.drain(3..5);
+    assert!(parse_opts(&only_index).unwrap().is_err());
+
+    let mut stable = args("1", "4");
+    stable.pop();
+    assert!(parse_opts(&stable).unwrap().is_err());
+}
+
+#[test]
+fn parse_baseline_options() {
+    let args = |extra: &[&str]| {
+        let mut args = vec!["progname".to_string(), "-Zunstable-options".to_string()];
+        args.extend(extra.iter().map(|s| s.to_string()));
+        args
+    };
+    let opts =
+        parse_opts(&args(&["--save-baseline", "new", "--baseline", "old"])).unwrap().unwrap();
+    assert_eq!(opts.save_baseline.as_deref(), Some("new"));
+    assert_eq!(opts.baseline.as_deref(), Some("old"));
+    assert_eq!(opts.noise_threshold, None);
+
+    let opts = parse_opts(&args(&["--noise-threshold", "5"])).unwrap().unwrap();
+    assert_eq!(opts.noise_threshold, Some(5.0));
+    assert!(parse_opts(&args(&["--noise-threshold", "-1"])).unwrap().is_err());
+    assert!(parse_opts(&args(&["--noise-threshold", "x"])).unwrap().is_err());
+
+    for name in ["../x", "a/b", "/abs", "..", "."] {
+        assert!(parse_opts(&args(&["--save-baseline", name])).unwrap().is_err(), "{name}");
+        assert!(parse_opts(&args(&["--baseline", name])).unwrap().is_err(), "{name}");
+    }
+
+    let stable = vec!["progname".to_string(), "--baseline".to_string(), "old".to_string()];
+    assert!(parse_opts(&stable).unwrap().is_err());
+}
+
+#[test]
+fn baseline_compare_detects_changes() {
+    let sample = |mean: f64| BaselineSample { samples: 50, mean, var: 100.0, median: mean };
+    let old = sample(1000.0);
+
+    let same = baseline::compare(&old, &sample(1000.0), DEFAULT_NOISE_THRESHOLD);
+    assert_eq!(same.verdict, ChangeVerdict::NoChange);
+    assert_eq!(same.mean_pct, 0.0);
+    assert!(same.ci_low_pct < 0.0 && same.ci_high_pct > 0.0);
+
+    let slower = baseline::compare(&old, &sample(1100.0), DEFAULT_NOISE_THRESHOLD);
+    assert_eq!(slower.verdict, ChangeVerdict::Regressed);
+    assert!((slower.mean_pct - 10.0).abs() < 1e-9);
+
+    let faster = baseline::compare(&old, &sample(900.0), DEFAULT_NOISE_THRESHOLD);
+    assert_eq!(faster.verdict, ChangeVerdict::Improved);
+
+    // A significant change that is within the noise threshold doesn't count.
+    let small = baseline::compare(&old, &sample(1010.0), DEFAULT_NOISE_THRESHOLD);
+    assert_eq!(small.verdict, ChangeVerdict::NoChange);
+
+    // Neither does a large change that is within the noise of the samples.
+    let noisy = |mean: f64| BaselineSample { samples: 5, mean, var: 250_000.0, median: mean };
+    let change = baseline::compare(&noisy(1000.0), &noisy(1100.0), DEFAULT_NOISE_THRESHOLD);
+    assert_eq!(change.verdict, ChangeVerdict::NoChange);
+}
+
+#[test]
+fn baseline_save_and_load() {
+    let dir = std::env::temp_dir().join(format!("libtest-baseline-{}", std::process::id()));
+
+    let samples = [1234.5, 1200.25, 1100.0, 1337.1];
+    let bs = bench::BenchSamples {
+        ns_iter_summ: crate::stats::Summary::new(&samples),
+        samples: samples.len(),
+        mb_s: 0,
+    };
+    let mut saved = Baseline::new();
+    saved.insert("benches::with spaces", &bs);
+    saved.save_in(&dir, "main").unwrap();
+
+    let loaded = Baseline::load_in(&dir, "main").unwrap();
+    assert_eq!(loaded, saved);
+    assert_eq!(loaded.get("benches::with spaces"), Some(&BaselineSample::new(&bs)));
+    assert!(Baseline::load_in(&dir, "missing").is_err());
+    assert!(saved.save_in(&dir, "../escaped").is_err());
+    assert!(!dir.join("../escaped.baseline").exists());
+
+    let broken = Baseline::path_in(&dir, "broken").unwrap();
+    std::fs::write(broken, "# libtest benchmark baseline v1\n50 1.0\n").unwrap();
+    assert!(Baseline::load_in(&dir, "broken").is_err());
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
 #[test]
 pub fn test_metricmap_compare() {
     let mut m1 = MetricMap::new();
@@ -792,6 +947,7 @@ fn f(_: &mut Bencher) -> Result<(), String> {
         compile_fail: false,
         no_run: false,
         test_type: TestType::Unknown,
+        timeout: None,
     };
 
     crate::bench::benchmark(TestId(0), desc, tx, true, f);
@@ -820,6 +976,7 @@ fn f(b: &mut Bencher) -> Result<(), String> {
         compile_fail: false,
         no_run: false,
         test_type: TestType::Unknown,
+        timeout: None,
     };
 
     crate::bench::benchmark(TestId(0), desc, tx, true, f);
@@ -841,6 +998,7 @@ fn should_sort_failures_before_printing_them() {
         compile_fail: false,
         no_run: false,
         test_type: TestType::Unknown,
+        timeout: None,
     };
 
     let test_b = TestDesc {
@@ -856,6 +1014,7 @@ fn should_sort_failures_before_printing_them() {
         compile_fail: false,
         no_run: false,
         test_type: TestType::Unknown,
+        timeout: None,
     };
 
     let mut out = PrettyFormatter::new(OutputLocation::Raw(Vec::new()), false, 10, false, None);
@@ -875,6 +1034,11 @@ fn should_sort_failures_before_printing_them() {
         not_failures: Vec::new(),
         ignores: Vec::new(),
         time_failures: Vec::new(),
+        flaky: Vec::new(),
+        bench_results: Baseline::new(),
+        baseline: None,
+        noise_threshold: DEFAULT_NOISE_THRESHOLD,
+        regressions: Vec::new(),
     };
 
     out.write_failures(&st).unwrap();
@@ -908,6 +1072,7 @@ fn f(_: &mut Bencher) -> Result<(), String> {
             compile_fail: false,
             no_run: false,
             test_type: TestType::Unknown,
+            timeout: None,
         },
         testfn: DynBenchFn(Box::new(f)),
     };
@@ -922,3 +1087,316 @@ fn f(_: &mut Bencher) -> Result<(), String> {
     let result = rx.recv().unwrap().result;
     assert_eq!(result, TrFailed);
 }
+
+#[test]
+fn parse_test_timeout_option() {
+    let args = vec![
+        "progname".to_string(),
+        "--test-timeout".to_string(),
+        "30".to_string(),
+        "-Zunstable-options".to_string(),
+    ];
+    let opts = parse_opts(&args).unwrap().unwrap();
+    assert_eq!(opts.test_timeout, Some(Duration::from_secs(30)));
+
+    let args = vec!["progname".to_string(), "--test-timeout".to_string(), "30".to_string()];
+    assert!(parse_opts(&args).unwrap().is_err());
+}
+
+#[test]
+#[cfg(not(target_os = "emscripten"))]
+fn test_timeout_abandons_hung_in_process_tests() {
+    fn hung_test(name: &'static str) -> TestDescAndFn {
+        TestDescAndFn {
+            desc: TestDesc {
+                name: StaticTestName(name),
+                ignore: false,
+                ignore_message: None,
+                source_file: "",
+                start_line: 0,
+                start_col: 0,
+                end_line: 0,
+                end_col: 0,
+                should_panic: ShouldPanic::No,
+                compile_fail: false,
+                no_run: false,
+                test_type: TestType::Unknown,
+                timeout: None,
+            },
+            testfn: DynTestFn(Box::new(move || {
+                thread::sleep(Duration::from_secs(600));
+                Ok(())
+            })),
+        }
+    }
+
+    for test_threads in [1, 2] {
+        let mut tests = one_ignored_one_unignored_test();
+        tests.push(hung_test("hung"));
+        let (tx, rx) = channel();
+        let notify = move |event: TestEvent| {
+            if let TestEvent::TeResult(result) = event {
+                tx.send(result).unwrap();
+            }
+            Ok(())
+        };
+        let opts = TestOpts {
+            run_tests: true,
+            test_threads: Some(test_threads),
+            test_timeout: Some(Duration::from_millis(100)),
+            ..TestOpts::new()
+        };
+        run_tests(&opts, tests, notify).unwrap();
+
+        let mut results: Vec<_> = rx.iter().map(|t| (t.desc.name.to_string(), t.result)).collect();
+        results.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(
+            results,
+            [
+                ("1".to_string(), TrIgnored),
+                ("2".to_string(), TrOk),
+                ("hung".to_string(), TrTimedOut(Duration::from_millis(100))),
+            ]
+        );
+    }
+}
+
+#[test]
+fn test_timeout_attribute_overrides_cli_option() {
+    let mut desc = typed_test_desc(TestType::UnitTest);
+    let default = Some(Duration::from_secs(60));
+    assert_eq!(time::get_test_timeout(&desc, default), default);
+    assert_eq!(time::get_test_timeout(&desc, None), None);
+
+    desc.timeout = Some(5);
+    assert_eq!(time::get_test_timeout(&desc, default), Some(Duration::from_secs(5)));
+    assert_eq!(time::get_test_timeout(&desc, None), Some(Duration::from_secs(5)));
+}
+
+#[test]
+fn parse_retries_option() {
+    let args = vec![
+        "progname".to_string(),
+        "--retries".to_string(),
+        "3".to_string(),
+        "-Zunstable-options".to_string(),
+    ];
+    let opts = parse_opts(&args).unwrap().unwrap();
+    assert_eq!(opts.retries, 3);
+
+    let args = vec!["progname".to_string(), "--retries".to_string(), "3".to_string()];
+    assert!(parse_opts(&args).unwrap().is_err());
+}
+
+#[test]
+#[cfg(not(target_os = "emscripten"))]
+fn test_retries_report_flaky_tests() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static FLAKY_RUNS: AtomicUsize = AtomicUsize::new(0);
+    static BROKEN_RUNS: AtomicUsize = AtomicUsize::new(0);
+
+    fn flaky() -> Result<(), String> {
+        if FLAKY_RUNS.fetch_add(1, Ordering::SeqCst) == 0 { panic!() } else { Ok(()) }
+    }
+    fn broken() -> Result<(), String> {
+        BROKEN_RUNS.fetch_add(1, Ordering::SeqCst);
+        panic!()
+    }
+
+    fn test(name: &'static str, f: fn() -> Result<(), String>) -> TestDescAndFn {
+        TestDescAndFn {
+            desc: TestDesc {
+                name: StaticTestName(name),
+                ignore: false,
+                ignore_message: None,
+                source_file: "",
+                start_line: 0,
+                start_col: 0,
+                end_line: 0,
+                end_col: 0,
+                should_panic: ShouldPanic::No,
+                compile_fail: false,
+                no_run: false,
+                test_type: TestType::Unknown,
+                timeout: None,
+            },
+            testfn: StaticTestFn(f),
+        }
+    }
+
+    for test_threads in [1, 2] {
+        FLAKY_RUNS.store(0, Ordering::SeqCst);
+        BROKEN_RUNS.store(0, Ordering::SeqCst);
+        let tests = vec![test("flaky", flaky), test("broken", broken)];
+        let (tx, rx) = channel();
+        let notify = move |event: TestEvent| {
+            match event {
+                TestEvent::TeRetry(result) => tx.send(("retry", result)).unwrap(),
+                TestEvent::TeResult(result) => tx.send(("result", result)).unwrap(),
+                _ => {}
+            }
+            Ok(())
+        };
+        let opts = TestOpts {
+            run_tests: true,
+            test_threads: Some(test_threads),
+            retries: 2,
+            ..TestOpts::new()
+        };
+        run_tests(&opts, tests, notify).unwrap();
+
+        let mut events: Vec<_> = rx
+            .iter()
+            .map(|(event, t)| (t.desc.name.to_string(), event, t.attempts, t.result == TrOk))
+            .collect();
+        events.sort();
+        assert_eq!(
+            events,
+            [
+                ("broken".to_string(), "result", 3, false),
+                ("broken".to_string(), "retry", 1, false),
+                ("broken".to_string(), "retry", 2, false),
+                ("flaky".to_string(), "result", 2, true),
+                ("flaky".to_string(), "retry", 1, false),
+            ]
+        );
+        assert_eq!(BROKEN_RUNS.load(Ordering::SeqCst), 3);
+    }
+}
+
+fn formatter_test_desc() -> TestDesc {
+    TestDesc {
+        name: StaticTestName("tests::it_fails"),
+        ignore: false,
+        ignore_message: None,
+        source_file: "src/lib.rs",
+        start_line: 7,
+        start_col: 4,
+        end_line: 7,
+        end_col: 12,
+        should_panic: ShouldPanic::No,
+        compile_fail: false,
+        no_run: false,
+        test_type: TestType::UnitTest,
+        timeout: None,
+    }
+}
+
+const PANIC_OUTPUT: &str = "hello\n\
+thread 'tests::it_fails' panicked at src/lib.rs:9:9:\n\
+assertion failed: 1 == 2\n\
+note: run with `RUST_BACKTRACE=1` environment variable to display a backtrace\n";
+
+#[test]
+fn tap_formatter_writes_diagnostics() {
+    let desc = formatter_test_desc();
+    let ignored = TestDesc { ignore_message: Some("slow"), ..formatter_test_desc() };
+    let st = console::ConsoleTestState::new(&TestOpts::new()).unwrap();
+    let mut out = TapFormatter::new(OutputLocation::Raw(Vec::new()));
+
+    out.write_run_start(3, None).unwrap();
+    out.write_result(&desc, &TrOk, None, 1, b"", &st).unwrap();
+    out.write_result(&desc, &TrFailed, None, 1, PANIC_OUTPUT.as_bytes(), &st).unwrap();
+    out.write_result(&ignored, &TrIgnored, None, 1, b"", &st).unwrap();
+    out.write_run_finish(&st).unwrap();
+
+    let s = match out.output_location() {
+        &OutputLocation::Raw(ref m) => String::from_utf8_lossy(&m[..]),
+        &OutputLocation::Pretty(_) => unreachable!(),
+    };
+    let expected = "TAP version 14
+ok 1 - tests::it_fails
+not ok 2 - tests::it_fails
+  ---
+  message: \"test failed\"
+  severity: fail
+  stdout: |2
+    hello
+    thread 'tests::it_fails' panicked at src/lib.rs:9:9:
+    assertion failed: 1 == 2
+    note: run with `RUST_BACKTRACE=1` environment variable to display a backtrace
+  ...
+ok 3 - tests::it_fails # SKIP slow
+1..3
+# 0 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out
+";
+    assert_eq!(s, expected);
+}
+
+#[test]
+fn tap_formatter_keeps_leading_spaces_of_output() {
+    let desc = formatter_test_desc();
+    let st = console::ConsoleTestState::new(&TestOpts::new()).unwrap();
+    let mut out = TapFormatter::new(OutputLocation::Raw(Vec::new()));
+
+    let stdout = "  left: 1\n right: 2\n\nend\n";
+    out.write_result(&desc, &TrFailed, None, 1, stdout.as_bytes(), &st).unwrap();
+
+    let s = match out.output_location() {
+        &OutputLocation::Raw(ref m) => String::from_utf8_lossy(&m[..]),
+        &OutputLocation::Pretty(_) => unreachable!(),
+    };
+    let expected = "not ok 1 - tests::it_fails
+  ---
+  message: \"test failed\"
+  severity: fail
+  stdout: |2
+      left: 1
+     right: 2
+
+    end
+  ...
+";
+    assert_eq!(s, expected);
+}
+
+#[test]
+fn junit_formatter_escapes_messages() {
+    let desc = formatter_test_desc();
+    let st = console::ConsoleTestState::new(&TestOpts::new()).unwrap();
+    let mut out = JunitFormatter::new(OutputLocation::Raw(Vec::new()));
+
+    let retried = TrFailedMsg("expected \"<a>\" & got\n\"<b>\"".to_string());
+    out.write_retry(&desc, &retried, 1, b"").unwrap();
+    let failed = TrFailedMsg("panic did not contain expected string\n      \"x\"".to_string());
+    out.write_result(&desc, &failed, None, 2, b"", &st).unwrap();
+    out.write_run_finish(&st).unwrap();
+
+    let s = match out.output_location() {
+        &OutputLocation::Raw(ref m) => String::from_utf8_lossy(&m[..]),
+        &OutputLocation::Pretty(_) => unreachable!(),
+    };
+    assert!(s.contains(
+        "<failure message=\"panic did not contain expected string&#xA;      &quot;x&quot;\" \
+         type=\"assert\"/>"
+    ));
+    assert!(s.contains(
+        "<rerunFailure message=\"expected &quot;&lt;a&gt;&quot; &amp; got&#xA;&quot;&lt;b&gt;&quot;\" \
+         type=\"assert\"/>"
+    ));
+}
+
+#[test]
+fn github_formatter_points_at_panic() {
+    let desc = formatter_test_desc();
+    let st = console::ConsoleTestState::new(&TestOpts::new()).unwrap();
+    let mut out = GithubFormatter::new(OutputLocation::Raw(Vec::new()));
+
+    out.write_result(&desc, &TrOk, None, 1, b"", &st).unwrap();
+    out.write_result(&desc, &TrFailed, None, 1, PANIC_OUTPUT.as_bytes(), &st).unwrap();
+    let did_not_panic = TrFailedMsg("test did not panic as expected".to_string());
+    out.write_result(&desc, &did_not_panic, None, 1, b"", &st).unwrap();
+    out.write_result(&desc, &TrOk, None, 2, b"", &st).unwrap();
+
+    let s = match out.output_location() {
+        &OutputLocation::Raw(ref m) => String::from_utf8_lossy(&m[..]),
+        &OutputLocation::Pretty(_) => unreachable!(),
+    };
+    let expected = "\
+::error file=src/lib.rs,line=9,col=9,title=tests%3A%3Ait_fails::assertion failed: 1 == 2
+::error file=src/lib.rs,line=7,col=4,title=tests%3A%3Ait_fails::test did not panic as expected
+::warning file=src/lib.rs,line=7,col=4,title=tests%3A%3Ait_fails::test is flaky: it passed after 2 attempts
+";
+    assert_eq!(s, expected);
+}
@@ -61,6 +61,12 @@ pub fn get_default_test_timeout() -> Instant {
     Instant::now() + Duration::from_secs(TEST_WARN_TIMEOUT_S)
 }
 
+/// Returns the hard time limit for the test, if any. A `#[test_timeout]` on the
+/// test itself takes precedence over the `--test-timeout` default.
+pub fn get_test_timeout(desc: &TestDesc, default: Option<Duration>) -> Option<Duration> {
+    desc.timeout.map(Duration::from_secs).or(default)
+}
+
 /// The measured execution time of a unit test.
 #[derive(Debug, Clone, PartialEq)]
 pub struct TestExecTime(pub Duration);
@@ -207,6 +207,8 @@ pub struct TestDesc {
     pub compile_fail: bool,
     pub no_run: bool,
     pub test_type: TestType,
+    /// Per-test override of `--test-timeout`, in seconds, set with `#[test_timeout]`.
+    pub timeout: Option<u64>,
 }
 
 impl TestDesc {
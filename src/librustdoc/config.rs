@@ -33,6 +33,7 @@ pub(crate) enum OutputFormat {
     Json,
     #[default]
     Html,
+    Markdown,
 }
 
 impl OutputFormat {
@@ -48,6 +49,7 @@ fn try_from(value: &str) -> Result<Self, Self::Error> {
         match value {
             "json" => Ok(OutputFormat::Json),
             "html" => Ok(OutputFormat::Html),
+            "markdown" => Ok(OutputFormat::Markdown),
             _ => Err(format!("unknown output format `{value}`")),
         }
     }
@@ -684,9 +686,9 @@ fn println_condition(condition: Condition) {
             Some(s) => match OutputFormat::try_from(s.as_str()) {
                 Ok(out_fmt) => {
                     if !out_fmt.is_json() && show_coverage {
-                        dcx.fatal(
-                            "html output format isn't supported for the --show-coverage option",
-                        );
+                        dcx.fatal(format!(
+                            "{s} output format isn't supported for the --show-coverage option"
+                        ));
                     }
                     out_fmt
                 }
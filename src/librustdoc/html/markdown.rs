@@ -1928,6 +1928,133 @@ pub(crate) fn rust_code_blocks(md: &str, extra_info: &ExtraInfo<'_>) -> Vec<Rust
     code_blocks
 }
 
+/// Rewrites a doc comment so that it can be embedded into a Markdown page by the Markdown
+/// backend: the fences of Rust code blocks are tagged as `rust` and lose their hidden lines, ATX
+/// headings are demoted by `heading_offset` levels, and the links whose destination (or reference,
+/// for shortcut links without a definition) is a key of `links` point at its value instead.
+pub(crate) fn markdown_for_markdown_output(
+    md: &str,
+    heading_offset: u32,
+    links: &FxHashMap<&str, String>,
+) -> String {
+    let mut edits: Vec<(Range<usize>, Cow<'_, str>)> = Vec::new();
+    let mut replacer = |broken_link: BrokenLink<'_>| {
+        links.get(&*broken_link.reference).map(|href| (href.clone().into(), "".into()))
+    };
+    let mut p = Parser::new_with_broken_link_callback(md, main_body_opts(), Some(&mut replacer))
+        .into_offset_iter();
+
+    while let Some((event, offset)) = p.next() {
+        match event {
+            Event::Start(Tag::Link { link_type, dest_url, title, .. }) => {
+                // The link text may contain brackets of its own, so the end of the text is the
+                // end of its last event rather than the first `]` of the link.
+                let mut text_end = offset.start + 1;
+                for (event, inner_offset) in p.by_ref() {
+                    if let Event::End(TagEnd::Link) = event {
+                        break;
+                    }
+                    text_end = text_end.max(inner_offset.end);
+                }
+                let href = match link_type {
+                    LinkType::Inline => match links.get(&*dest_url) {
+                        Some(href) => href.as_str(),
+                        None => continue,
+                    },
+                    // These were resolved by `replacer`, so the destination already is the href.
+                    LinkType::ReferenceUnknown
+                    | LinkType::CollapsedUnknown
+                    | LinkType::ShortcutUnknown => &*dest_url,
+                    // The destinations of the other links are in reference definitions, which are
+                    // rewritten below.
+                    _ => continue,
+                };
+                let mut end = offset.end;
+                // The range of a collapsed link leaves out its empty `[]`.
+                if link_type == LinkType::CollapsedUnknown && md[end..].starts_with("[]") {
+                    end += 2;
+                }
+                let Some(close) = md[text_end..end].find(']') else { continue };
+                edits.push((
+                    text_end + close..end,
+                    format!("]({href}{})", markdown_link_title(&title)).into(),
+                ));
+            }
+            Event::Start(Tag::Heading { level, .. }) if md[offset.clone()].starts_with('#') => {
+                let hashes = md[offset.clone()].bytes().take_while(|&b| b == b'#').count();
+                let level = std::cmp::min(level as u32 + heading_offset, MAX_HEADER_LEVEL);
+                let hashes = offset.start..offset.start + hashes;
+                edits.push((hashes, "#".repeat(level as usize).into()));
+            }
+            Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(syntax))) => {
+                let lang_string = if syntax.is_empty() {
+                    LangString::default()
+                } else {
+                    LangString::parse_without_check(&syntax, ErrorCodes::No, false)
+                };
+                if !lang_string.rust {
+                    continue;
+                }
+                let fence = md[offset.clone()].lines().next().unwrap_or_default();
+                let info_start =
+                    fence.len() - fence.trim_start().trim_start_matches(['`', '~']).len();
+                edits.push((offset.start + info_start..offset.start + fence.len(), "rust".into()));
+
+                while let Some((Event::Text(_), text_offset)) = p.next() {
+                    let mut start = text_offset.start;
+                    for line in md[text_offset].split_inclusive('\n') {
+                        match map_line(line) {
+                            Line::Hidden(_) => {
+                                // Also drop the container prefix (`> `, list indentation) that
+                                // precedes the hidden line.
+                                let line_start = md[..start].rfind('\n').map_or(0, |i| i + 1);
+                                edits.push((line_start..start + line.len(), "".into()))
+                            }
+                            Line::Shown(Cow::Owned(shown)) => {
+                                edits.push((start..start + line.len(), shown.into()))
+                            }
+                            Line::Shown(Cow::Borrowed(_)) => {}
+                        }
+                        start += line.len();
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    for (_, def) in p.reference_definitions().iter() {
+        let Some(href) = links.get(&*def.dest) else { continue };
+        let Some((colon, _)) = md[def.span.clone()]
+            .match_indices("]:")
+            .find(|&(i, _)| !md[def.span.start..def.span.start + i].ends_with('\\'))
+        else {
+            continue;
+        };
+        let title = def.title.as_deref().map(markdown_link_title).unwrap_or_default();
+        edits.push((def.span.start + colon + 2..def.span.end, format!(" {href}{title}").into()));
+    }
+    edits.sort_by_key(|(range, _)| range.start);
+
+    let mut out = String::with_capacity(md.len());
+    let mut last = 0;
+    for (range, replacement) in edits {
+        out.push_str(&md[last..range.start]);
+        out.push_str(&replacement);
+        last = range.end;
+    }
+    out.push_str(&md[last..]);
+    out
+}
+
+/// Formats the title of a link, including the space separating it from the destination.
+fn markdown_link_title(title: &str) -> String {
+    if title.is_empty() {
+        return String::new();
+    }
+    format!(" \"{}\"", title.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
 #[derive(Clone, Default, Debug)]
 pub struct IdMap {
     map: FxHashMap<Cow<'static, str>, usize>,
@@ -1,8 +1,10 @@
+use rustc_data_structures::fx::FxHashMap;
 use rustc_span::edition::{Edition, DEFAULT_EDITION};
 
 use super::{
-    find_testable_code, plain_text_summary, short_markdown_summary, ErrorCodes, HeadingOffset,
-    IdMap, Ignore, LangString, LangStringToken, Markdown, MarkdownItemInfo, TagIterator,
+    find_testable_code, markdown_for_markdown_output, plain_text_summary, short_markdown_summary,
+    ErrorCodes, HeadingOffset, IdMap, Ignore, LangString, LangStringToken, Markdown,
+    MarkdownItemInfo, TagIterator,
 };
 
 #[test]
@@ -536,3 +538,52 @@ fn t(input: &str, expect: &str) {
 </code></pre></div>",
     );
 }
+
+#[test]
+fn test_markdown_for_markdown_output() {
+    fn t(input: &str, heading_offset: u32, expect: &str) {
+        let output = markdown_for_markdown_output(input, heading_offset, &FxHashMap::default());
+        assert_eq!(output, expect, "original: {}", input);
+    }
+
+    t("# Examples\n\nText", 1, "## Examples\n\nText");
+    t("##### Deep\n", 3, "###### Deep\n");
+    t("Title\n=====\n", 1, "Title\n=====\n");
+    t("```\n# fn main() {\nlet x = 1;\n# }\n```", 0, "```rust\nlet x = 1;\n```");
+    t("```no_run\n## not hidden\n```", 0, "```rust\n# not hidden\n```");
+    t("~~~text\n# kept\n~~~", 0, "~~~text\n# kept\n~~~");
+    t("```\n# Not a heading\n```\n# Heading", 1, "```rust\n```\n## Heading");
+}
+
+#[test]
+fn test_markdown_for_markdown_output_links() {
+    let links = FxHashMap::from_iter([
+        ("Foo", "struct.Foo.md".to_owned()),
+        ("crate::Bar", "struct.Bar.md".to_owned()),
+        ("`Baz`", "enum.Baz.md".to_owned()),
+        ("Qux", "trait.Qux.md".to_owned()),
+    ]);
+    let t = |input: &str, expect: &str| {
+        let output = markdown_for_markdown_output(input, 0, &links);
+        assert_eq!(output, expect, "original: {}", input);
+    };
+
+    t(
+        "[a](Foo), [b](<crate::Bar> \"the bar\")",
+        "[a](struct.Foo.md), [b](struct.Bar.md \"the bar\")",
+    );
+    t("[the [nested] text](Foo)", "[the [nested] text](struct.Foo.md)");
+    t("[![img](x.png)](Foo)", "[![img](x.png)](struct.Foo.md)");
+    t(
+        "[`Baz`], [x][`Baz`], [`Baz`][]",
+        "[`Baz`](enum.Baz.md), [x](enum.Baz.md), [`Baz`](enum.Baz.md)",
+    );
+    t(
+        "[q], [r][q]\n\n[q]: Qux 'a \"title\"'\n",
+        "[q], [r][q]\n\n[q]: trait.Qux.md \"a \\\"title\\\"\"\n",
+    );
+    t("[q]\n\n[q]:\n  Qux\n", "[q]\n\n[q]: trait.Qux.md\n");
+    // Code isn't Markdown, and links that weren't resolved are left alone.
+    t("`[a](Foo)`\n\n```text\n[a](Foo)\n```\n", "`[a](Foo)`\n\n```text\n[a](Foo)\n```\n");
+    t("[Unknown], [u](Unknown), ![img](Foo)", "[Unknown], [u](Unknown), ![img](Foo)");
+}
@@ -118,6 +118,7 @@ macro_rules! map {
 mod json;
 pub(crate) mod lint;
 mod markdown;
+mod md;
 mod passes;
 mod scrape_examples;
 mod theme;
@@ -828,6 +829,9 @@ fn main_args(
                     config::OutputFormat::Json => sess.time("render_json", || {
                         run_renderer::<json::JsonRenderer<'_>>(krate, render_opts, cache, tcx)
                     }),
+                    config::OutputFormat::Markdown => sess.time("render_markdown", || {
+                        run_renderer::<md::MarkdownRenderer<'_>>(krate, render_opts, cache, tcx)
+                    }),
                 }
             })
         })
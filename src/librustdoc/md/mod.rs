@@ -0,0 +1,500 @@
+//! Rustdoc's Markdown backend
+//!
+//! This module renders a crate as a tree of Markdown files laid out like the HTML output: every
+//! module gets an `index.md` listing its items, and every other item gets a `{type}.{name}.md`
+//! page next to it. Declarations are written as `rust` code blocks and resolved intra-doc links
+//! become relative links between the generated files.
+
+mod print;
+
+use std::fmt::Write;
+use std::fs;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use rustc_attr::{DeprecatedSince, Deprecation, StabilityLevel};
+use rustc_data_structures::fx::FxHashMap;
+use rustc_hir::def_id::DefId;
+use rustc_middle::ty::TyCtxt;
+use rustc_span::hygiene::MacroKind;
+use rustc_span::{sym, Symbol};
+
+use crate::clean::cfg::Cfg;
+use crate::clean::types::{ExternalLocation, ItemLink};
+use crate::config::RenderOptions;
+use crate::docfs::PathError;
+use crate::error::Error;
+use crate::formats::cache::Cache;
+use crate::formats::item_type::ItemType;
+use crate::formats::{FormatRenderer, Impl};
+use crate::html::format::{href_relative_parts, join_with_double_colon};
+use crate::html::markdown::{markdown_for_markdown_output, plain_text_summary};
+use crate::html::render::{item_ty_to_section, ItemSection};
+use crate::passes::collect_intra_doc_links::UrlFragment;
+use crate::{clean, try_err};
+
+#[derive(Clone)]
+pub(crate) struct MarkdownRenderer<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    /// The path of the module being rendered, starting with the crate name.
+    current: Vec<Symbol>,
+    /// The directory the pages of the module being rendered are written to.
+    dst: PathBuf,
+    /// The `cfg` of the module being rendered. It is left out of the portability notes of the
+    /// module's items, as the HTML backend does.
+    module_cfg: Option<Arc<Cfg>>,
+    /// Whether we are inside a module removed by `strip-private`. Its items are only documented
+    /// where they are re-exported, so no pages are written for them.
+    in_stripped_module: bool,
+    cache: Rc<Cache>,
+}
+
+impl<'tcx> MarkdownRenderer<'tcx> {
+    /// Returns a link to the page documenting `did`, relative to the current module. Items from
+    /// crates whose documentation is hosted elsewhere link to their HTML pages.
+    fn href(&self, did: DefId) -> Option<String> {
+        let cache = &self.cache;
+        let (fqp, shortty, mut parts) = if let Some(&(ref fqp, shortty)) = cache.paths.get(&did) {
+            (fqp, shortty, self.relative_parts(shortty, fqp))
+        } else {
+            let &(ref fqp, shortty) = cache.external_paths.get(&did)?;
+            match cache.extern_locations.get(&did.krate)? {
+                ExternalLocation::Remote(url) => {
+                    let mut parts = vec![url.trim_end_matches('/').to_owned()];
+                    parts.extend(module_fqp(shortty, fqp).iter().map(|s| s.to_string()));
+                    parts.push(page_name(shortty, fqp, "html"));
+                    return Some(parts.join("/"));
+                }
+                ExternalLocation::Local => (fqp, shortty, self.relative_parts(shortty, fqp)),
+                ExternalLocation::Unknown => return None,
+            }
+        };
+        parts.push(page_name(shortty, fqp, "md"));
+        Some(parts.join("/"))
+    }
+
+    fn relative_parts(&self, shortty: ItemType, fqp: &[Symbol]) -> Vec<String> {
+        href_relative_parts(module_fqp(shortty, fqp), &self.current)
+            .map(|s| s.to_string())
+            .collect()
+    }
+
+    /// Returns the documentation of `item` with its headings demoted by `heading_offset` levels
+    /// and its intra-doc links pointing at the generated pages.
+    fn docs(&self, item: &clean::Item, heading_offset: u32) -> Option<String> {
+        let doc = item.opt_doc_value()?;
+
+        let mut hrefs = FxHashMap::default();
+        for ItemLink { link, page_id, fragment, .. } in
+            self.cache.intra_doc_links.get(&item.item_id).into_iter().flatten()
+        {
+            let Some(mut href) = self.href(*page_id) else { continue };
+            // Generated pages have no anchors for the items they document, but links to headings
+            // and to the HTML documentation of other crates keep their fragment.
+            match fragment {
+                Some(fragment) if href.ends_with(".html") => fragment.render(&mut href, self.tcx),
+                Some(UrlFragment::UserWritten(fragment)) => {
+                    href.push('#');
+                    href.push_str(fragment);
+                }
+                _ => {}
+            }
+            hrefs.entry(&**link).or_insert(href);
+        }
+        Some(markdown_for_markdown_output(&doc, heading_offset, &hrefs))
+    }
+
+    /// Writes the deprecation, stability and portability notes of `item` as a block quote.
+    fn write_notes(&self, page: &mut String, item: &clean::Item, parent_cfg: Option<&Cfg>) {
+        let mut notes = Vec::new();
+
+        if let Some(depr @ Deprecation { note, since, suggestion: _ }) = item.deprecation(self.tcx)
+        {
+            let mut message = match since {
+                DeprecatedSince::RustcVersion(version) => {
+                    if depr.is_in_effect() {
+                        format!("Deprecated since {version}")
+                    } else {
+                        format!("Deprecating in {version}")
+                    }
+                }
+                DeprecatedSince::Future => String::from("Deprecating in a future version"),
+                DeprecatedSince::NonStandard(since) => format!("Deprecated since {since}"),
+                DeprecatedSince::Unspecified | DeprecatedSince::Err => String::from("Deprecated"),
+            };
+            if let Some(note) = note {
+                write!(message, ": {note}").unwrap();
+            }
+            notes.push(format!("👎 {message}"));
+        }
+
+        // Like the HTML backend, don't flag the permanently unstable compiler crates.
+        if let Some(stab) = item.stability(self.tcx)
+            && let StabilityLevel::Unstable { issue, .. } = stab.level
+            && stab.feature != sym::rustc_private
+        {
+            let mut message =
+                format!("🔬 This is a nightly-only experimental API. (`{}`", stab.feature);
+            if let Some(issue) = issue {
+                write!(message, " #{issue}").unwrap();
+            }
+            message.push(')');
+            notes.push(message);
+        }
+
+        let cfg = match (&item.cfg, parent_cfg) {
+            (Some(cfg), Some(parent_cfg)) => cfg.simplify_with(parent_cfg),
+            (cfg, _) => cfg.as_deref().cloned(),
+        };
+        if let Some(cfg) = cfg {
+            notes.push(format!("{}.", cfg.render_long_plain()));
+        }
+
+        for (i, note) in notes.iter().enumerate() {
+            if i > 0 {
+                page.push_str(">\n");
+            }
+            writeln!(page, "> {note}").unwrap();
+        }
+        if !notes.is_empty() {
+            page.push('\n');
+        }
+    }
+
+    fn write_declaration(&self, page: &mut String, item: &clean::Item) {
+        if let Some(decl) = print::item_declaration(item, self.tcx, &self.cache) {
+            writeln!(page, "```rust\n{decl}\n```\n").unwrap();
+        }
+    }
+
+    fn write_docs(&self, page: &mut String, item: &clean::Item, heading_offset: u32) {
+        if let Some(docs) = self.docs(item, heading_offset) {
+            page.push_str(docs.trim_end());
+            page.push_str("\n\n");
+        }
+    }
+
+    /// Writes a section with a subsection for each of `members`, which are fields, variants or
+    /// associated items of `parent`.
+    fn write_members(
+        &self,
+        page: &mut String,
+        title: &str,
+        level: usize,
+        members: Vec<&clean::Item>,
+        parent: &clean::Item,
+    ) {
+        if members.is_empty() {
+            return;
+        }
+        writeln!(page, "{} {title}\n", "#".repeat(level)).unwrap();
+        for member in members {
+            let Some(signature) = print::member_signature(member, self.tcx) else { continue };
+            writeln!(page, "{} `{signature}`\n", "#".repeat(level + 1)).unwrap();
+            self.write_notes(page, member, parent.cfg.as_deref());
+            self.write_docs(page, member, level as u32 + 1);
+            if let clean::VariantItem(clean::Variant {
+                kind: clean::VariantKind::Struct(ref s),
+                ..
+            }) = *member.kind
+            {
+                let documented = s
+                    .fields
+                    .iter()
+                    .filter(|f| !f.is_stripped() && f.opt_doc_value().is_some())
+                    .collect();
+                self.write_members(page, "Fields", level + 2, documented, member);
+            }
+        }
+    }
+
+    fn write_trait_items(&self, page: &mut String, item: &clean::Item, t: &clean::Trait) {
+        let sections: [(&str, fn(&clean::Item) -> bool); 6] = [
+            ("Required Associated Types", |i| i.is_ty_associated_type()),
+            ("Provided Associated Types", |i| i.is_associated_type()),
+            ("Required Associated Constants", |i| i.is_ty_associated_const()),
+            ("Provided Associated Constants", |i| i.is_associated_const()),
+            ("Required Methods", |i| i.is_ty_method()),
+            ("Provided Methods", |i| i.is_method()),
+        ];
+        for (title, filter) in sections {
+            let items = t.items.iter().filter(|i| !i.is_stripped() && filter(i)).collect();
+            self.write_members(page, title, 2, items, item);
+        }
+    }
+
+    /// Writes the implementations of the type with the given `DefId`: inherent impls with their
+    /// items, then lists of trait, auto trait and blanket implementations.
+    fn write_impls(&self, page: &mut String, item: &clean::Item, did: DefId) {
+        let Some(impls) = self.cache.impls.get(&did) else { return };
+        let (inherent, traits): (Vec<&Impl>, Vec<&Impl>) =
+            impls.iter().partition(|i| i.inner_impl().trait_.is_none());
+
+        let mut inherent = inherent.into_iter().filter(|i| !i.impl_item.is_stripped()).peekable();
+        if inherent.peek().is_some() {
+            page.push_str("## Implementations\n\n");
+            for i in inherent {
+                let header = print::impl_header(i.inner_impl(), self.tcx);
+                writeln!(page, "### `{header}`\n").unwrap();
+                self.write_notes(page, &i.impl_item, item.cfg.as_deref());
+                self.write_docs(page, &i.impl_item, 3);
+                for assoc in i.inner_impl().items.iter().filter(|i| !i.is_stripped()) {
+                    let Some(signature) = print::member_signature(assoc, self.tcx) else {
+                        continue;
+                    };
+                    writeln!(page, "#### `{signature}`\n").unwrap();
+                    self.write_notes(page, assoc, i.impl_item.cfg.as_deref());
+                    self.write_docs(page, assoc, 4);
+                }
+            }
+        }
+
+        let (auto, traits): (Vec<&Impl>, Vec<&Impl>) =
+            traits.into_iter().partition(|i| i.inner_impl().kind.is_auto());
+        let (blanket, traits): (Vec<&Impl>, Vec<&Impl>) =
+            traits.into_iter().partition(|i| i.inner_impl().kind.is_blanket());
+        self.write_impl_list(page, "Trait Implementations", &traits, |i| i.trait_did());
+        self.write_impl_list(page, "Auto Trait Implementations", &auto, |i| i.trait_did());
+        self.write_impl_list(page, "Blanket Implementations", &blanket, |i| i.trait_did());
+    }
+
+    /// Writes a sorted list of impl headers, each linking to the page of the item returned by
+    /// `link_to` when there is one.
+    fn write_impl_list(
+        &self,
+        page: &mut String,
+        title: &str,
+        impls: &[&Impl],
+        link_to: impl Fn(&Impl) -> Option<DefId>,
+    ) {
+        let mut entries: Vec<_> = impls
+            .iter()
+            .filter(|i| !i.impl_item.is_stripped())
+            .map(|i| {
+                let header = print::impl_header(i.inner_impl(), self.tcx);
+                match link_to(i).and_then(|did| self.href(did)) {
+                    Some(href) => format!("- [`{header}`]({href})"),
+                    None => format!("- `{header}`"),
+                }
+            })
+            .collect();
+        if entries.is_empty() {
+            return;
+        }
+        entries.sort();
+        entries.dedup();
+        writeln!(page, "## {title}\n").unwrap();
+        for entry in entries {
+            page.push_str(&entry);
+            page.push('\n');
+        }
+        page.push('\n');
+    }
+
+    fn module_page(&self, item: &clean::Item, module: &clean::Module) -> String {
+        let mut page = String::new();
+        let kind = if item.is_crate() { "Crate" } else { "Module" };
+        writeln!(page, "# {kind} `{}`\n", join_with_double_colon(&self.current)).unwrap();
+        self.write_notes(&mut page, item, self.module_cfg.as_deref());
+        self.write_docs(&mut page, item, 1);
+
+        let mut sections: FxHashMap<ItemSection, Vec<String>> = FxHashMap::default();
+        for child in &module.items {
+            let Some(name) = child.name else { continue };
+            if child.is_stripped() {
+                continue;
+            }
+            let entry = match *child.kind {
+                clean::ImportItem(ref import) => {
+                    if !import.should_be_displayed {
+                        continue;
+                    }
+                    format!("- `{}`", print::import(child, import, self.tcx))
+                }
+                clean::ExternCrateItem { .. } => continue,
+                _ => {
+                    let ty = child.type_();
+                    let href = match ty {
+                        ItemType::Module => format!("{name}/index.md"),
+                        _ => format!("{ty}.{name}.md"),
+                    };
+                    let summary =
+                        plain_text_summary(&child.doc_value(), &child.link_names(&self.cache));
+                    if summary.is_empty() {
+                        format!("- [`{name}`]({href})")
+                    } else {
+                        format!("- [`{name}`]({href}): {summary}")
+                    }
+                }
+            };
+            sections.entry(item_ty_to_section(child.type_())).or_default().push(entry);
+        }
+        for section in ItemSection::ALL {
+            let Some(entries) = sections.get_mut(section) else { continue };
+            entries.sort();
+            writeln!(page, "## {}\n", section.name()).unwrap();
+            for entry in entries {
+                page.push_str(entry);
+                page.push('\n');
+            }
+            page.push('\n');
+        }
+        page
+    }
+
+    fn item_page(&self, item: &clean::Item) -> Option<String> {
+        let kind = match *item.kind {
+            clean::FunctionItem(..) | clean::ForeignFunctionItem(..) => "Function",
+            clean::TraitItem(..) => "Trait",
+            clean::StructItem(..) => "Struct",
+            clean::UnionItem(..) => "Union",
+            clean::EnumItem(..) => "Enum",
+            clean::TypeAliasItem(..) => "Type Alias",
+            clean::MacroItem(..) => "Macro",
+            clean::ProcMacroItem(ref mac) => match mac.kind {
+                MacroKind::Bang => "Macro",
+                MacroKind::Attr => "Attribute Macro",
+                MacroKind::Derive => "Derive Macro",
+            },
+            clean::PrimitiveItem(..) => "Primitive Type",
+            clean::StaticItem(..) | clean::ForeignStaticItem(..) => "Static",
+            clean::ConstantItem(..) => "Constant",
+            clean::ForeignTypeItem => "Foreign Type",
+            clean::KeywordItem => "Keyword",
+            clean::TraitAliasItem(..) => "Trait Alias",
+            // We don't generate pages for any other type.
+            _ => return None,
+        };
+
+        let mut page = String::new();
+        let mut path = self.current.clone();
+        path.push(item.name.unwrap());
+        writeln!(page, "# {kind} `{}`\n", join_with_double_colon(&path)).unwrap();
+        self.write_notes(&mut page, item, self.module_cfg.as_deref());
+        self.write_declaration(&mut page, item);
+        self.write_docs(&mut page, item, 1);
+
+        match *item.kind {
+            clean::StructItem(clean::Struct { ref fields, .. }) => {
+                let fields = fields.iter().filter(|f| !f.is_stripped()).collect();
+                self.write_members(&mut page, "Fields", 2, fields, item);
+            }
+            clean::UnionItem(clean::Union { ref fields, .. }) => {
+                let fields = fields.iter().filter(|f| !f.is_stripped()).collect();
+                self.write_members(&mut page, "Fields", 2, fields, item);
+            }
+            clean::EnumItem(ref e) => {
+                self.write_members(&mut page, "Variants", 2, e.variants().collect(), item);
+            }
+            clean::TraitItem(ref t) => {
+                self.write_trait_items(&mut page, item, t);
+                let implementors = self.cache.implementors.get(&t.def_id);
+                let implementors: Vec<_> = implementors.into_iter().flatten().collect();
+                let cache = &self.cache;
+                self.write_impl_list(&mut page, "Implementors", &implementors, |i| {
+                    i.inner_impl().for_.def_id(cache)
+                });
+            }
+            _ => {}
+        }
+
+        let did = match *item.kind {
+            clean::PrimitiveItem(prim) => self.cache.primitive_locations.get(&prim).copied(),
+            clean::StructItem(..)
+            | clean::UnionItem(..)
+            | clean::EnumItem(..)
+            | clean::TypeAliasItem(..)
+            | clean::ForeignTypeItem => item.item_id.as_def_id(),
+            _ => None,
+        };
+        if let Some(did) = did {
+            self.write_impls(&mut page, item, did);
+        }
+        Some(page)
+    }
+
+    fn write_page(&self, file_name: &str, page: String) -> Result<(), Error> {
+        try_err!(fs::create_dir_all(&self.dst), &self.dst);
+        let path = self.dst.join(file_name);
+        try_err!(fs::write(&path, page.trim_end().to_owned() + "\n"), &path);
+        Ok(())
+    }
+}
+
+impl<'tcx> FormatRenderer<'tcx> for MarkdownRenderer<'tcx> {
+    fn descr() -> &'static str {
+        "markdown"
+    }
+
+    const RUN_ON_MODULE: bool = true;
+
+    fn init(
+        krate: clean::Crate,
+        options: RenderOptions,
+        cache: Cache,
+        tcx: TyCtxt<'tcx>,
+    ) -> Result<(Self, clean::Crate), Error> {
+        debug!("Initializing markdown renderer");
+
+        Ok((
+            MarkdownRenderer {
+                tcx,
+                current: Vec::new(),
+                dst: options.output,
+                module_cfg: None,
+                in_stripped_module: false,
+                cache: Rc::new(cache),
+            },
+            krate,
+        ))
+    }
+
+    fn make_child_renderer(&self) -> Self {
+        self.clone()
+    }
+
+    fn item(&mut self, item: clean::Item) -> Result<(), Error> {
+        if self.in_stripped_module || item.is_stripped() {
+            return Ok(());
+        }
+        let Some(page) = self.item_page(&item) else { return Ok(()) };
+        let file_name = format!("{}.{}.md", item.type_(), item.name.unwrap());
+        self.write_page(&file_name, page)
+    }
+
+    fn mod_item_in(&mut self, item: &clean::Item) -> Result<(), Error> {
+        let name = item.name.unwrap();
+        self.current.push(name);
+        self.dst.push(name.as_str());
+        self.in_stripped_module |= item.is_stripped();
+        if !self.in_stripped_module
+            && let clean::ModuleItem(ref module) = *item.kind
+        {
+            let page = self.module_page(item, module);
+            self.write_page("index.md", page)?;
+        }
+        self.module_cfg = item.cfg.clone();
+        Ok(())
+    }
+
+    fn after_krate(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn cache(&self) -> &Cache {
+        &self.cache
+    }
+}
+
+/// Returns the path of the module whose directory holds the page for the item at `fqp`.
+fn module_fqp(shortty: ItemType, fqp: &[Symbol]) -> &[Symbol] {
+    if shortty == ItemType::Module { fqp } else { &fqp[..fqp.len() - 1] }
+}
+
+fn page_name(shortty: ItemType, fqp: &[Symbol], extension: &str) -> String {
+    match shortty {
+        ItemType::Module => format!("index.{extension}"),
+        _ => format!("{shortty}.{}.{extension}", fqp.last().unwrap()),
+    }
+}
@@ -0,0 +1,741 @@
+//! Printing of `clean` types and item declarations as plain Rust source.
+//!
+//! The printers in `html::format` emit HTML and need the HTML renderer's `Context`; the Markdown
+//! backend puts signatures in fenced code blocks, so this module prints them without any markup.
+
+use std::fmt::Write;
+
+use rustc_data_structures::fx::FxHashSet;
+use rustc_hir as hir;
+use rustc_hir::def::CtorKind;
+use rustc_middle::ty::{self, TyCtxt};
+use rustc_span::hygiene::MacroKind;
+use rustc_span::symbol::kw;
+
+use crate::clean::{self, PrimitiveType};
+use crate::formats::cache::Cache;
+use crate::html::format::{
+    print_abi_with_space, print_constness_with_space, print_default_space,
+    visibility_to_src_with_space, PrintWithSpace,
+};
+
+/// Returns the declaration of `item` as it would appear in source, with the bodies of functions
+/// and the private fields of types elided. Returns `None` for items that have no declaration to
+/// show, like modules, re-exports and primitives.
+pub(super) fn item_declaration(
+    item: &clean::Item,
+    tcx: TyCtxt<'_>,
+    cache: &Cache,
+) -> Option<String> {
+    let mut p = Printer::new(tcx);
+    for attr in item.attributes(tcx, cache, false) {
+        p.push(&attr);
+        p.push("\n");
+    }
+    if p.item(item, true) { Some(p.buf) } else { None }
+}
+
+/// Returns the single-line signature of a field, variant or associated item, used as the heading
+/// of its section on the page of its parent.
+pub(super) fn member_signature(item: &clean::Item, tcx: TyCtxt<'_>) -> Option<String> {
+    let mut p = Printer::new(tcx);
+    if p.item(item, false) { Some(p.buf) } else { None }
+}
+
+pub(super) fn impl_header(impl_: &clean::Impl, tcx: TyCtxt<'_>) -> String {
+    let mut p = Printer::new(tcx);
+    p.impl_header(impl_);
+    p.buf
+}
+
+pub(super) fn import(item: &clean::Item, import: &clean::Import, tcx: TyCtxt<'_>) -> String {
+    let mut p = Printer::new(tcx);
+    p.visibility(item);
+    p.push("use ");
+    let source = import.source.path.whole_name();
+    match import.kind {
+        clean::ImportKind::Simple(name) if name == import.source.path.last() => p.push(&source),
+        clean::ImportKind::Simple(name) => {
+            write!(p.buf, "{source} as {name}").unwrap();
+        }
+        clean::ImportKind::Glob if source.is_empty() => p.push("*"),
+        clean::ImportKind::Glob => {
+            write!(p.buf, "{source}::*").unwrap();
+        }
+    }
+    p.push(";");
+    p.buf
+}
+
+struct Printer<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    buf: String,
+}
+
+impl<'tcx> Printer<'tcx> {
+    fn new(tcx: TyCtxt<'tcx>) -> Self {
+        Printer { tcx, buf: String::new() }
+    }
+
+    fn push(&mut self, s: &str) {
+        self.buf.push_str(s);
+    }
+
+    fn sep<T>(
+        &mut self,
+        items: impl IntoIterator<Item = T>,
+        sep: &str,
+        mut f: impl FnMut(&mut Self, T),
+    ) {
+        for (i, item) in items.into_iter().enumerate() {
+            if i > 0 {
+                self.push(sep);
+            }
+            f(self, item);
+        }
+    }
+
+    /// Prints the declaration of `item`. `full` selects between the multi-line declaration shown
+    /// on an item's own page and the single-line form used for members.
+    fn item(&mut self, item: &clean::Item, full: bool) -> bool {
+        let Some(name) = item.name else { return false };
+        match &*item.kind {
+            clean::FunctionItem(f)
+            | clean::ForeignFunctionItem(f, _)
+            | clean::TyMethodItem(f)
+            | clean::MethodItem(f, _) => self.function(item, f, full),
+            clean::StructItem(s) => {
+                self.visibility(item);
+                write!(self.buf, "struct {name}").unwrap();
+                self.generics(&s.generics);
+                self.struct_body(s.ctor_kind, &s.generics, &s.fields, full);
+            }
+            clean::UnionItem(u) => {
+                self.visibility(item);
+                write!(self.buf, "union {name}").unwrap();
+                self.generics(&u.generics);
+                self.struct_body(None, &u.generics, &u.fields, full);
+            }
+            clean::EnumItem(e) => {
+                self.visibility(item);
+                write!(self.buf, "enum {name}").unwrap();
+                self.generics(&e.generics);
+                self.where_clause(&e.generics, full);
+                if !full {
+                    return true;
+                }
+                self.push(if e.generics.where_predicates.is_empty() { " {\n" } else { "\n{\n" });
+                for variant in e.variants() {
+                    self.push("    ");
+                    self.variant(variant);
+                    self.push(",\n");
+                }
+                if e.has_stripped_entries() {
+                    self.push("    // some variants omitted\n");
+                }
+                self.push("}");
+            }
+            clean::TraitItem(t) => self.trait_(item, t, full),
+            clean::TraitAliasItem(t) => {
+                self.visibility(item);
+                write!(self.buf, "trait {name}").unwrap();
+                self.generics(&t.generics);
+                self.push(" = ");
+                self.bounds(&t.bounds);
+                self.where_clause(&t.generics, full);
+                self.push(";");
+            }
+            clean::TypeAliasItem(t) => {
+                self.visibility(item);
+                write!(self.buf, "type {name}").unwrap();
+                self.generics(&t.generics);
+                self.where_clause(&t.generics, full);
+                self.push(" = ");
+                self.ty(&t.type_);
+                self.push(";");
+            }
+            clean::ConstantItem(c) => {
+                self.visibility(item);
+                write!(self.buf, "const {name}: ").unwrap();
+                self.ty(&c.type_);
+                write!(self.buf, " = {};", c.expr(self.tcx)).unwrap();
+            }
+            clean::StaticItem(s) | clean::ForeignStaticItem(s, _) => {
+                self.visibility(item);
+                write!(self.buf, "static {}{name}: ", s.mutability.print_with_space()).unwrap();
+                self.ty(&s.type_);
+                self.push(";");
+            }
+            clean::ForeignTypeItem => {
+                self.visibility(item);
+                write!(self.buf, "type {name};").unwrap();
+            }
+            clean::MacroItem(m) => self.push(&m.source),
+            clean::ProcMacroItem(m) => match m.kind {
+                MacroKind::Bang => {
+                    write!(self.buf, "{name}!() {{ /* proc-macro */ }}").unwrap();
+                }
+                MacroKind::Attr => {
+                    write!(self.buf, "#[{name}]").unwrap();
+                }
+                MacroKind::Derive => {
+                    write!(self.buf, "#[derive({name})]").unwrap();
+                    if !m.helpers.is_empty() {
+                        self.push("\n// Helper attributes: ");
+                        self.sep(&m.helpers, ", ", |p, helper| {
+                            write!(p.buf, "#[{helper}]").unwrap();
+                        });
+                    }
+                }
+            },
+            clean::StructFieldItem(ty) => {
+                self.visibility(item);
+                write!(self.buf, "{name}: ").unwrap();
+                self.ty(ty);
+            }
+            clean::VariantItem(_) => self.variant(item),
+            clean::TyAssocConstItem(generics, ty) => {
+                write!(self.buf, "const {name}").unwrap();
+                self.generics(generics);
+                self.push(": ");
+                self.ty(ty);
+                self.where_clause(generics, false);
+            }
+            clean::AssocConstItem(c) => {
+                self.visibility(item);
+                write!(self.buf, "const {name}").unwrap();
+                self.generics(&c.generics);
+                self.push(": ");
+                self.ty(&c.type_);
+                write!(self.buf, " = {}", c.expr(self.tcx)).unwrap();
+                self.where_clause(&c.generics, false);
+            }
+            clean::TyAssocTypeItem(generics, bounds) => {
+                write!(self.buf, "type {name}").unwrap();
+                self.generics(generics);
+                if !bounds.is_empty() {
+                    self.push(": ");
+                    self.bounds(bounds);
+                }
+                self.where_clause(generics, false);
+            }
+            clean::AssocTypeItem(t, _) => {
+                write!(self.buf, "type {name}").unwrap();
+                self.generics(&t.generics);
+                self.where_clause(&t.generics, false);
+                self.push(" = ");
+                self.ty(&t.type_);
+            }
+            clean::ExternCrateItem { .. }
+            | clean::ImportItem(_)
+            | clean::ModuleItem(_)
+            | clean::ImplItem(_)
+            | clean::PrimitiveItem(_)
+            | clean::KeywordItem
+            | clean::StrippedItem(_) => return false,
+        }
+        true
+    }
+
+    fn visibility(&mut self, item: &clean::Item) {
+        if let Some(def_id) = item.item_id.as_def_id() {
+            let vis = item.visibility(self.tcx);
+            let vis = visibility_to_src_with_space(vis, self.tcx, def_id, item.is_doc_hidden());
+            write!(self.buf, "{vis}").unwrap();
+        }
+    }
+
+    fn function(&mut self, item: &clean::Item, f: &clean::Function, full: bool) {
+        let tcx = self.tcx;
+        let header = item.fn_header(tcx).expect("printing a function which isn't a function");
+        self.visibility(item);
+        write!(
+            self.buf,
+            "{default}{constness}{asyncness}{safety}{abi:#}fn {name}",
+            default = print_default_space(item.is_default()),
+            constness = print_constness_with_space(
+                &header.constness,
+                item.stable_since(tcx),
+                item.const_stability(tcx),
+            ),
+            asyncness = header.asyncness.print_with_space(),
+            safety = header.safety.print_with_space(),
+            abi = print_abi_with_space(header.abi),
+            name = item.name.unwrap(),
+        )
+        .unwrap();
+        self.generics(&f.generics);
+        self.fn_decl(&f.decl);
+        self.where_clause(&f.generics, full);
+    }
+
+    fn struct_body(
+        &mut self,
+        ctor_kind: Option<CtorKind>,
+        generics: &clean::Generics,
+        fields: &[clean::Item],
+        full: bool,
+    ) {
+        match ctor_kind {
+            Some(CtorKind::Fn) => {
+                self.push("(");
+                self.sep(fields, ", ", |p, field| match *field.kind {
+                    clean::StructFieldItem(ref ty) => {
+                        p.visibility(field);
+                        p.ty(ty);
+                    }
+                    _ => p.push("_"),
+                });
+                self.push(")");
+                self.where_clause(generics, full);
+                self.push(";");
+            }
+            Some(CtorKind::Const) => {
+                self.where_clause(generics, full);
+                self.push(";");
+            }
+            None => {
+                self.where_clause(generics, full);
+                if !full {
+                    return;
+                }
+                self.push(if generics.where_predicates.is_empty() { " {\n" } else { "\n{\n" });
+                for field in fields.iter().filter(|field| !field.is_stripped()) {
+                    self.push("    ");
+                    self.item(field, false);
+                    self.push(",\n");
+                }
+                if fields.iter().any(|field| field.is_stripped()) {
+                    self.push("    /* private fields */\n");
+                }
+                self.push("}");
+            }
+        }
+    }
+
+    fn variant(&mut self, item: &clean::Item) {
+        let name = item.name.unwrap();
+        let clean::VariantItem(variant) = &*item.kind else {
+            unreachable!("printing a variant which isn't a variant")
+        };
+        self.push(name.as_str());
+        match &variant.kind {
+            clean::VariantKind::CLike => {
+                if let Some(discriminant) = &variant.discriminant {
+                    let expr = discriminant
+                        .expr(self.tcx)
+                        .unwrap_or_else(|| discriminant.value(self.tcx, true));
+                    write!(self.buf, " = {expr}").unwrap();
+                }
+            }
+            clean::VariantKind::Tuple(fields) => {
+                self.push("(");
+                self.sep(fields, ", ", |p, field| match *field.kind {
+                    clean::StructFieldItem(ref ty) => p.ty(ty),
+                    _ => p.push("_"),
+                });
+                self.push(")");
+            }
+            clean::VariantKind::Struct(s) => {
+                let mut fields = s.fields.iter().filter(|field| !field.is_stripped()).peekable();
+                let has_visible_fields = fields.peek().is_some();
+                self.push(" { ");
+                self.sep(fields, ", ", |p, field| {
+                    p.item(field, false);
+                });
+                if s.has_stripped_entries() {
+                    self.push(if has_visible_fields { ", .." } else { ".." });
+                }
+                self.push(" }");
+            }
+        }
+    }
+
+    fn trait_(&mut self, item: &clean::Item, t: &clean::Trait, full: bool) {
+        let tcx = self.tcx;
+        self.visibility(item);
+        write!(self.buf, "{}", t.safety(tcx).print_with_space()).unwrap();
+        if t.is_auto(tcx) {
+            self.push("auto ");
+        }
+        write!(self.buf, "trait {}", item.name.unwrap()).unwrap();
+        self.generics(&t.generics);
+        if !t.bounds.is_empty() {
+            self.push(": ");
+            self.bounds(&t.bounds);
+        }
+        self.where_clause(&t.generics, full);
+        if !full {
+            return;
+        }
+
+        let items: Vec<_> = t.items.iter().filter(|item| !item.is_stripped()).collect();
+        if items.is_empty() {
+            self.push(" { }");
+            return;
+        }
+        self.push(if t.generics.where_predicates.is_empty() { " {\n" } else { "\n{\n" });
+        for assoc in items {
+            self.push("    ");
+            self.item(assoc, false);
+            let is_method = matches!(*assoc.kind, clean::MethodItem(..));
+            self.push(if is_method { " { ... }\n" } else { ";\n" });
+        }
+        self.push("}");
+    }
+
+    fn impl_header(&mut self, impl_: &clean::Impl) {
+        self.push("impl");
+        self.generics(&impl_.generics);
+        self.push(" ");
+        if let Some(trait_) = &impl_.trait_ {
+            if let ty::ImplPolarity::Negative = impl_.polarity {
+                self.push("!");
+            }
+            self.path(trait_, false);
+            self.push(" for ");
+        }
+        self.ty(impl_.kind.as_blanket_ty().unwrap_or(&impl_.for_));
+        self.where_clause(&impl_.generics, false);
+    }
+
+    fn generics(&mut self, generics: &clean::Generics) {
+        let mut params = generics.params.iter().filter(|p| !p.is_synthetic_param()).peekable();
+        if params.peek().is_none() {
+            return;
+        }
+        self.push("<");
+        self.sep(params, ", ", Self::generic_param);
+        self.push(">");
+    }
+
+    fn generic_param(&mut self, param: &clean::GenericParamDef) {
+        match &param.kind {
+            clean::GenericParamDefKind::Lifetime { outlives } => {
+                self.push(param.name.as_str());
+                if !outlives.is_empty() {
+                    self.push(": ");
+                    self.sep(outlives, " + ", |p, lt| p.push(lt.0.as_str()));
+                }
+            }
+            clean::GenericParamDefKind::Type { bounds, default, .. } => {
+                self.push(param.name.as_str());
+                if !bounds.is_empty() {
+                    self.push(": ");
+                    self.bounds(bounds);
+                }
+                if let Some(ty) = default {
+                    self.push(" = ");
+                    self.ty(ty);
+                }
+            }
+            clean::GenericParamDefKind::Const { ty, default, .. } => {
+                write!(self.buf, "const {}: ", param.name).unwrap();
+                self.ty(ty);
+                if let Some(default) = default {
+                    write!(self.buf, " = {default}").unwrap();
+                }
+            }
+        }
+    }
+
+    /// Prints the where clause of `generics`, either on its own lines with one predicate per line
+    /// like rustfmt does, or inline.
+    fn where_clause(&mut self, generics: &clean::Generics, multiline: bool) {
+        let predicates: Vec<_> = generics
+            .where_predicates
+            .iter()
+            .filter(|pred| match pred {
+                clean::WherePredicate::BoundPredicate { bounds, .. } => !bounds.is_empty(),
+                _ => true,
+            })
+            .collect();
+        if predicates.is_empty() {
+            return;
+        }
+        if multiline {
+            self.push("\nwhere\n");
+            for pred in predicates {
+                self.push("    ");
+                self.where_predicate(pred);
+                self.push(",\n");
+            }
+            // The caller continues on the line after the last predicate.
+            self.buf.pop();
+        } else {
+            self.push(" where ");
+            self.sep(predicates, ", ", Self::where_predicate);
+        }
+    }
+
+    fn where_predicate(&mut self, pred: &clean::WherePredicate) {
+        match pred {
+            clean::WherePredicate::BoundPredicate { ty, bounds, bound_params } => {
+                self.higher_ranked_params(bound_params);
+                self.ty(ty);
+                self.push(": ");
+                self.bounds(bounds);
+            }
+            clean::WherePredicate::RegionPredicate { lifetime, bounds } => {
+                self.push(lifetime.0.as_str());
+                self.push(":");
+                if !bounds.is_empty() {
+                    self.push(" ");
+                    self.bounds(bounds);
+                }
+            }
+            clean::WherePredicate::EqPredicate { lhs, rhs } => {
+                self.ty(lhs);
+                self.push(" == ");
+                self.term(rhs);
+            }
+        }
+    }
+
+    fn higher_ranked_params(&mut self, params: &[clean::GenericParamDef]) {
+        if !params.is_empty() {
+            self.push("for<");
+            self.sep(params, ", ", Self::generic_param);
+            self.push("> ");
+        }
+    }
+
+    fn bounds(&mut self, bounds: &[clean::GenericBound]) {
+        let mut seen = FxHashSet::default();
+        self.sep(bounds.iter().filter(|b| seen.insert(*b)), " + ", Self::bound);
+    }
+
+    fn bound(&mut self, bound: &clean::GenericBound) {
+        match bound {
+            clean::GenericBound::Outlives(lt) => self.push(lt.0.as_str()),
+            clean::GenericBound::TraitBound(poly, modifier) => {
+                self.push(match modifier {
+                    hir::TraitBoundModifier::None => "",
+                    hir::TraitBoundModifier::Maybe => "?",
+                    hir::TraitBoundModifier::Negative => "!",
+                    // `const` and `~const` trait bounds are experimental; don't render them.
+                    hir::TraitBoundModifier::Const | hir::TraitBoundModifier::MaybeConst => "",
+                });
+                self.poly_trait(poly);
+            }
+            clean::GenericBound::Use(args) => {
+                self.push("use<");
+                self.sep(args, ", ", |p, arg| p.push(arg.as_str()));
+                self.push(">");
+            }
+        }
+    }
+
+    fn poly_trait(&mut self, poly: &clean::PolyTrait) {
+        self.higher_ranked_params(&poly.generic_params);
+        self.path(&poly.trait_, false);
+    }
+
+    /// Prints the last segment of `path`, or all of it if `print_all` is set.
+    fn path(&mut self, path: &clean::Path, print_all: bool) {
+        let last = path.segments.last().unwrap();
+        if print_all {
+            for seg in &path.segments[..path.segments.len() - 1] {
+                if seg.name != kw::PathRoot {
+                    self.push(seg.name.as_str());
+                }
+                self.push("::");
+            }
+        }
+        self.push(last.name.as_str());
+        self.generic_args(&last.args);
+    }
+
+    fn generic_args(&mut self, args: &clean::GenericArgs) {
+        match args {
+            clean::GenericArgs::AngleBracketed { args, constraints } => {
+                if args.is_empty() && constraints.is_empty() {
+                    return;
+                }
+                self.push("<");
+                self.sep(args.iter(), ", ", Self::generic_arg);
+                if !args.is_empty() && !constraints.is_empty() {
+                    self.push(", ");
+                }
+                self.sep(constraints, ", ", Self::constraint);
+                self.push(">");
+            }
+            clean::GenericArgs::Parenthesized { inputs, output } => {
+                self.push("(");
+                self.sep(inputs.iter(), ", ", Self::ty);
+                self.push(")");
+                if let Some(output) = output {
+                    self.push(" -> ");
+                    self.ty(output);
+                }
+            }
+        }
+    }
+
+    fn generic_arg(&mut self, arg: &clean::GenericArg) {
+        match arg {
+            clean::GenericArg::Lifetime(lt) => self.push(lt.0.as_str()),
+            clean::GenericArg::Type(ty) => self.ty(ty),
+            clean::GenericArg::Const(ct) => {
+                let expr = ct.expr(self.tcx);
+                self.push(&expr);
+            }
+            clean::GenericArg::Infer => self.push("_"),
+        }
+    }
+
+    fn constraint(&mut self, constraint: &clean::AssocItemConstraint) {
+        self.push(constraint.assoc.name.as_str());
+        self.generic_args(&constraint.assoc.args);
+        match &constraint.kind {
+            clean::AssocItemConstraintKind::Equality { term } => {
+                self.push(" = ");
+                self.term(term);
+            }
+            clean::AssocItemConstraintKind::Bound { bounds } => {
+                if !bounds.is_empty() {
+                    self.push(": ");
+                    self.bounds(bounds);
+                }
+            }
+        }
+    }
+
+    fn term(&mut self, term: &clean::Term) {
+        match term {
+            clean::Term::Type(ty) => self.ty(ty),
+            clean::Term::Constant(ct) => {
+                let expr = ct.expr(self.tcx);
+                self.push(&expr);
+            }
+        }
+    }
+
+    fn fn_decl(&mut self, decl: &clean::FnDecl) {
+        self.push("(");
+        self.sep(&decl.inputs.values, ", ", |p, input| match input.to_self() {
+            Some(clean::SelfValue) => p.push("self"),
+            Some(clean::SelfBorrowed(lt, mutability)) => {
+                p.push("&");
+                if let Some(lt) = lt {
+                    write!(p.buf, "{} ", lt.0).unwrap();
+                }
+                write!(p.buf, "{}self", mutability.print_with_space()).unwrap();
+            }
+            Some(clean::SelfExplicit(ty)) => {
+                p.push("self: ");
+                p.ty(&ty);
+            }
+            None => {
+                if input.is_const {
+                    p.push("const ");
+                }
+                write!(p.buf, "{}: ", input.name).unwrap();
+                p.ty(&input.type_);
+            }
+        });
+        if decl.c_variadic {
+            self.push(if decl.inputs.values.is_empty() { "..." } else { ", ..." });
+        }
+        self.push(")");
+        if !decl.output.is_unit() {
+            self.push(" -> ");
+            self.ty(&decl.output);
+        }
+    }
+
+    fn ty(&mut self, ty: &clean::Type) {
+        match ty {
+            clean::Type::Path { path } => self.path(path, path.is_assoc_ty()),
+            clean::DynTrait(bounds, lt) => {
+                self.push("dyn ");
+                self.sep(bounds, " + ", Self::poly_trait);
+                if let Some(lt) = lt {
+                    write!(self.buf, " + {}", lt.0).unwrap();
+                }
+            }
+            clean::Generic(name) => self.push(name.as_str()),
+            clean::Primitive(PrimitiveType::Never) => self.push("!"),
+            clean::Primitive(PrimitiveType::Unit) => self.push("()"),
+            clean::Primitive(prim) => self.push(prim.as_sym().as_str()),
+            clean::BareFunction(decl) => {
+                self.higher_ranked_params(&decl.generic_params);
+                write!(
+                    self.buf,
+                    "{}{:#}fn",
+                    decl.safety.print_with_space(),
+                    print_abi_with_space(decl.abi)
+                )
+                .unwrap();
+                self.fn_decl(&decl.decl);
+            }
+            clean::Tuple(tys) => {
+                self.push("(");
+                self.sep(tys, ", ", Self::ty);
+                self.push(if tys.len() == 1 { ",)" } else { ")" });
+            }
+            clean::Slice(ty) => {
+                self.push("[");
+                self.ty(ty);
+                self.push("]");
+            }
+            clean::Array(ty, len) => {
+                self.push("[");
+                self.ty(ty);
+                write!(self.buf, "; {len}]").unwrap();
+            }
+            clean::Type::Pat(ty, pat) => {
+                self.ty(ty);
+                write!(self.buf, " is {pat}").unwrap();
+            }
+            clean::RawPointer(mutability, ty) => {
+                self.push(match mutability {
+                    hir::Mutability::Mut => "*mut ",
+                    hir::Mutability::Not => "*const ",
+                });
+                self.ty(ty);
+            }
+            clean::BorrowedRef { lifetime, mutability, type_ } => {
+                self.push("&");
+                if let Some(lt) = lifetime {
+                    write!(self.buf, "{} ", lt.0).unwrap();
+                }
+                self.push(mutability.print_with_space());
+                let needs_parens = match &**type_ {
+                    clean::DynTrait(bounds, lt) => bounds.len() > 1 || lt.is_some(),
+                    clean::ImplTrait(bounds) => bounds.len() > 1,
+                    _ => false,
+                };
+                if needs_parens {
+                    self.push("(");
+                }
+                self.ty(type_);
+                if needs_parens {
+                    self.push(")");
+                }
+            }
+            clean::QPath(qpath) => {
+                let clean::QPathData { assoc, self_type, trait_, should_show_cast } = &**qpath;
+                if let Some(trait_) = trait_
+                    && *should_show_cast
+                {
+                    self.push("<");
+                    self.ty(self_type);
+                    self.push(" as ");
+                    self.path(trait_, false);
+                    self.push(">::");
+                } else {
+                    self.ty(self_type);
+                    self.push("::");
+                }
+                self.push(assoc.name.as_str());
+                self.generic_args(&assoc.args);
+            }
+            clean::Infer => self.push("_"),
+            clean::ImplTrait(bounds) => {
+                self.push("impl ");
+                self.bounds(bounds);
+            }
+        }
+    }
+}
@@ -55,7 +55,7 @@ pub(crate) fn add_file(&mut self, builder: &mut Builder, target: &str, path: &st
     pub(crate) fn add_tarball(&mut self, builder: &mut Builder, target: &str, base_path: &str) {
         let files = self.target.entry(target.into()).or_insert_with(Vec::new);
         let base_path = builder.input.join(base_path);
-        for compression in &["gz", "xz"] {
+        for compression in &["gz", "xz", "zst"] {
             if let Some(tarball) = tarball_variant(builder, &base_path, compression) {
                 files.push(ArtifactFile {
                     url: builder.url(&tarball),
@@ -80,6 +80,8 @@ pub(crate) struct Target {
     pub(crate) hash: Option<FileHash>,
     pub(crate) xz_url: Option<String>,
     pub(crate) xz_hash: Option<FileHash>,
+    pub(crate) zst_url: Option<String>,
+    pub(crate) zst_hash: Option<FileHash>,
     pub(crate) components: Option<Vec<Component>>,
     pub(crate) extensions: Option<Vec<Component>>,
 }
@@ -89,8 +91,9 @@ pub(crate) fn from_compressed_tar(builder: &mut Builder, base_path: &str) -> Sel
         let base_path = builder.input.join(base_path);
         let gz = tarball_variant(builder, &base_path, "gz");
         let xz = tarball_variant(builder, &base_path, "xz");
+        let zst = tarball_variant(builder, &base_path, "zst");
 
-        if gz.is_none() && xz.is_none() {
+        if gz.is_none() && xz.is_none() && zst.is_none() {
             return Self::unavailable();
         }
 
@@ -104,6 +107,9 @@ pub(crate) fn from_compressed_tar(builder: &mut Builder, base_path: &str) -> Sel
             // .xz
             xz_url: xz.as_ref().map(|path| builder.url(path)),
             xz_hash: xz.map(FileHash::Missing),
+            // .zst
+            zst_url: zst.as_ref().map(|path| builder.url(path)),
+            zst_hash: zst.map(FileHash::Missing),
         }
     }
 
@@ -172,6 +178,9 @@ pub(crate) fn visit_file_hashes(manifest: &mut Manifest, mut f: impl FnMut(&mut
             if let Some(hash) = &mut target.xz_hash {
                 f(hash);
             }
+            if let Some(hash) = &mut target.zst_hash {
+                f(hash);
+            }
         }
     }
 
@@ -7,6 +7,7 @@
 use flate2::read::GzDecoder;
 use tar::Archive;
 use xz2::read::XzDecoder;
+use zstd::stream::read::Decoder as ZstDecoder;
 
 const DEFAULT_TARGET: &str = "x86_64-unknown-linux-gnu";
 
@@ -184,7 +185,7 @@ pub(crate) fn version(&mut self, mut package: &PkgType) -> Result<VersionInfo, E
     }
 
     fn load_version_from_tarball(&mut self, package: &PkgType) -> Result<VersionInfo, Error> {
-        for ext in ["xz", "gz"] {
+        for ext in ["xz", "gz", "zst"] {
             let info =
                 self.load_version_from_tarball_inner(&self.dist_path.join(self.archive_name(
                     package,
@@ -196,7 +197,7 @@ fn load_version_from_tarball(&mut self, package: &PkgType) -> Result<VersionInfo
             }
         }
 
-        // If neither tarball is present, we fallback to returning the non-present info.
+        // If no tarball is present, we fallback to returning the non-present info.
         Ok(VersionInfo::default())
     }
 
@@ -215,6 +216,8 @@ fn load_version_from_tarball_inner(&mut self, tarball: &Path) -> Result<VersionI
                 Box::new(GzDecoder::new(file))
             } else if tarball.extension().map_or(false, |e| e == "xz") {
                 Box::new(XzDecoder::new(file))
+            } else if tarball.extension().map_or(false, |e| e == "zst") {
+                Box::new(ZstDecoder::new(file)?)
             } else {
                 unimplemented!("tarball extension not recognized: {}", tarball.display())
             });
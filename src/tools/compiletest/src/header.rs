@@ -1325,6 +1325,7 @@ macro_rules! decision {
         compile_fail: false,
         no_run: false,
         test_type: test::TestType::Unknown,
+        timeout: None,
     }
 }
 
@@ -573,6 +573,12 @@ pub fn test_opts(config: &Config) -> test::TestOpts {
         list: false,
         options: test::Options::new(),
         time_options: None,
+        test_timeout: None,
+        retries: 0,
+        shard: None,
+        save_baseline: None,
+        baseline: None,
+        noise_threshold: None,
         force_run_in_process: false,
         fail_fast: std::env::var_os("RUSTC_TEST_FAIL_FAST").is_some(),
     }
@@ -440,6 +440,8 @@ fn main() {
                 "WARNING: the flag `-Zmiri-disable-abi-check` no longer has any effect; \
                     ABI checks cannot be disabled any more"
             );
+        } else if arg == "-Zmiri-debug" {
+            miri_config.debugger = true;
         } else if arg == "-Zmiri-disable-isolation" {
             if matches!(isolation_enabled, Some(true)) {
                 show_error!(
@@ -556,6 +558,18 @@ fn main() {
         } else if let Some(param) = arg.strip_prefix("-Zmiri-preemption-rate=") {
             miri_config.preemption_rate =
                 parse_rate(param).unwrap_or_else(|err| show_error!("-Zmiri-preemption-rate {err}"));
+        } else if arg == "-Zmiri-explore-schedules" {
+            miri_config.explore_schedules = Some(2);
+        } else if let Some(param) = arg.strip_prefix("-Zmiri-explore-schedules=") {
+            let bound = param.parse::<usize>().unwrap_or_else(|err| {
+                show_error!("-Zmiri-explore-schedules requires a `usize`: {}", err)
+            });
+            miri_config.explore_schedules = Some(bound);
+        } else if let Some(param) = arg.strip_prefix("-Zmiri-replay-schedule=") {
+            let schedule = param.parse::<miri::Schedule>().unwrap_or_else(|err| {
+                show_error!("-Zmiri-replay-schedule requires a schedule printed by Miri: {err}")
+            });
+            miri_config.schedule = Some(schedule);
         } else if arg == "-Zmiri-report-progress" {
             // This makes it take a few seconds between progress reports on my laptop.
             miri_config.report_progress = Some(1_000_000);
@@ -588,6 +602,16 @@ fn main() {
             } else {
                 show_error!("-Zmiri-native-lib `{}` does not exist", filename);
             }
+        } else if let Some(param) = arg.strip_prefix("-Zmiri-virtual-fs=") {
+            if miri_config.virtual_fs.is_some() {
+                show_error!("-Zmiri-virtual-fs cannot be used more than once");
+            }
+            let dir = std::fs::canonicalize(param)
+                .unwrap_or_else(|err| show_error!("-Zmiri-virtual-fs `{param}`: {err}"));
+            if !dir.is_dir() {
+                show_error!("-Zmiri-virtual-fs `{param}` is not a directory");
+            }
+            miri_config.virtual_fs = Some(dir);
         } else if let Some(param) = arg.strip_prefix("-Zmiri-num-cpus=") {
             let num_cpus = param
                 .parse::<u32>()
@@ -629,6 +653,30 @@ fn main() {
         );
     }
 
+    // Exploring and replaying schedules both rely on every run being deterministic, and on the
+    // race detector to catch conflicts between non-atomic accesses that are not interleaved.
+    if miri_config.explore_schedules.is_some() || miri_config.schedule.is_some() {
+        if miri_config.explore_schedules.is_some() && miri_config.schedule.is_some() {
+            show_error!(
+                "-Zmiri-explore-schedules cannot be used along with -Zmiri-replay-schedule"
+            );
+        }
+        if matches!(miri_config.isolated_op, miri::IsolatedOp::Allow) {
+            show_error!(
+                "-Zmiri-explore-schedules and -Zmiri-replay-schedule cannot be used along with -Zmiri-disable-isolation"
+            );
+        }
+        if !miri_config.data_race_detector {
+            show_error!(
+                "-Zmiri-explore-schedules and -Zmiri-replay-schedule cannot be used along with -Zmiri-disable-data-race-detector"
+            );
+        }
+    }
+    // The virtual file system only replaces the host file system when isolation is enabled.
+    if miri_config.virtual_fs.is_some() && matches!(isolation_enabled, Some(false)) {
+        show_error!("-Zmiri-virtual-fs cannot be used along with -Zmiri-disable-isolation");
+    }
+
     debug!("rustc arguments: {:?}", rustc_args);
     debug!("crate arguments: {:?}", miri_config.args);
     run_compiler(
@@ -1,4 +1,5 @@
 use std::cell::Cell;
+use std::collections::BTreeMap;
 use std::time::{Duration, Instant as StdInstant};
 
 /// When using a virtual clock, this defines how many nanoseconds we pretend are passing for each
@@ -134,3 +135,70 @@ pub fn now(&self) -> Instant {
         }
     }
 }
+
+/// Identifies a timer in [`Timers`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TimerId(u64);
+
+/// Timers that expire at some point on the monotone clock, no matter whether a thread is waiting
+/// for them. These back `alarm` and `timerfd`. Each armed timer carries an event of type `T`
+/// that says what to do when it expires.
+#[derive(Debug)]
+pub struct Timers<T> {
+    next_id: u64,
+    /// The armed timers, with their expiration time measured since the epoch of the clock.
+    armed: BTreeMap<TimerId, (Duration, T)>,
+}
+
+impl<T> Default for Timers<T> {
+    fn default() -> Self {
+        Timers { next_id: 0, armed: BTreeMap::new() }
+    }
+}
+
+impl<T> Timers<T> {
+    /// Creates a new timer, which is not armed yet.
+    pub fn new_timer(&mut self) -> TimerId {
+        let id = TimerId(self.next_id);
+        self.next_id = self.next_id.strict_add(1);
+        id
+    }
+
+    /// Arms the timer to expire at `deadline`, replacing any earlier expiration time.
+    pub fn arm(&mut self, id: TimerId, deadline: Duration, event: T) {
+        self.armed.insert(id, (deadline, event));
+    }
+
+    /// Disarms the timer, and returns when it would have expired.
+    pub fn disarm(&mut self, id: TimerId) -> Option<Duration> {
+        self.armed.remove(&id).map(|(deadline, _)| deadline)
+    }
+
+    /// Returns when the timer expires, if it is armed.
+    pub fn deadline(&self, id: TimerId) -> Option<Duration> {
+        self.armed.get(&id).map(|(deadline, _)| *deadline)
+    }
+
+    /// Returns how long it takes until the next timer expires, or `None` if no timer is armed.
+    pub fn next_wait_time(&self, clock: &Clock) -> Option<Duration> {
+        if self.armed.is_empty() {
+            // Avoid asking the host for the time when there is nothing to wait for.
+            return None;
+        }
+        let now = clock.now().duration_since(clock.epoch());
+        self.armed.values().map(|(deadline, _)| deadline.saturating_sub(now)).min()
+    }
+
+    /// Disarms an expired timer and returns its event, or `None` if no timer has expired.
+    pub fn pop_expired(&mut self, clock: &Clock) -> Option<(TimerId, T)> {
+        let now = clock.now().duration_since(clock.epoch());
+        let id = self
+            .armed
+            .iter()
+            .filter(|(_, (deadline, _))| *deadline <= now)
+            .min_by_key(|(_, (deadline, _))| *deadline)
+            .map(|(id, _)| *id)?;
+        let (_, event) = self.armed.remove(&id).unwrap();
+        Some((id, event))
+    }
+}
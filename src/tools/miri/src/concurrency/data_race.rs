@@ -748,10 +748,13 @@ fn atomic_compare_exchange_scalar(
         // of the time, based on `success_rate`.
         let success_rate = 1.0 - this.machine.cmpxchg_weak_failure_rate;
         let cmpxchg_success = eq.to_scalar().to_bool()?
-            && if can_fail_spuriously {
-                this.machine.rng.get_mut().gen_bool(success_rate)
-            } else {
+            && if !can_fail_spuriously {
                 true
+            } else if let Some(explorer) = &this.machine.schedule_explorer {
+                // Exploration tries the success first, and then a spurious failure.
+                explorer.borrow_mut().choose(2) == 0
+            } else {
+                this.machine.rng.get_mut().gen_bool(success_rate)
             };
         let res = Immediate::ScalarPair(old.to_scalar(), Scalar::from_bool(cmpxchg_success));
 
@@ -778,6 +781,7 @@ fn atomic_compare_exchange_scalar(
     /// Update the data-race detector for an atomic fence on the current thread.
     fn atomic_fence(&mut self, atomic: AtomicFenceOrd) -> InterpResult<'tcx> {
         let this = self.eval_context_mut();
+        this.note_visible_operation();
         let current_span = this.machine.current_span();
         if let Some(data_race) = &mut this.machine.data_race {
             data_race.maybe_perform_sync_operation(
@@ -831,6 +835,7 @@ fn release_clock<'a>(&'a self) -> Option<Ref<'a, VClock>>
         'tcx: 'a,
     {
         let this = self.eval_context_ref();
+        this.note_visible_operation();
         Some(this.machine.data_race.as_ref()?.release_clock(&this.machine.threads))
     }
 
@@ -838,6 +843,7 @@ fn release_clock<'a>(&'a self) -> Option<Ref<'a, VClock>>
     /// the moment when that clock snapshot was taken via `release_clock`.
     fn acquire_clock(&self, clock: &VClock) {
         let this = self.eval_context_ref();
+        this.note_visible_operation();
         if let Some(data_race) = &this.machine.data_race {
             data_race.acquire_clock(clock, &this.machine.threads);
         }
@@ -1160,6 +1166,15 @@ fn allow_data_races_mut<R>(&mut self, op: impl FnOnce(&mut MiriInterpCx<'tcx>) -
         result
     }
 
+    /// Tells schedule exploration that the active thread did something other threads can
+    /// observe, which makes the end of the current basic block a preemption point.
+    fn note_visible_operation(&self) {
+        let this = self.eval_context_ref();
+        if let Some(explorer) = &this.machine.schedule_explorer {
+            explorer.borrow_mut().note_visible_operation();
+        }
+    }
+
     /// Checks that an atomic access is legal at the given place.
     fn atomic_access_check(
         &self,
@@ -1304,6 +1319,7 @@ fn validate_atomic_op<A: Debug + Copy>(
     ) -> InterpResult<'tcx> {
         let this = self.eval_context_ref();
         assert!(access.is_atomic());
+        this.note_visible_operation();
         if let Some(data_race) = &this.machine.data_race {
             if data_race.race_detecting() {
                 let size = place.layout.size;
@@ -0,0 +1,160 @@
+//! Systematic exploration of thread schedules, enabled with `-Zmiri-explore-schedules`.
+//!
+//! Instead of resolving scheduling non-determinism randomly, every choice (which thread runs next,
+//! which store an atomic load reads from, whether a weak compare-exchange fails spuriously) is
+//! turned into a *decision* with a fixed number of options. Option 0 is always what a simple
+//! deterministic scheduler would do: keep running the active thread, switch to the next thread in
+//! round-robin order, read the latest store, and succeed. Picking option `k` instead costs `k`
+//! *delays*, and a run may only spend as many delays as the configured bound. This is
+//! delay-bounded scheduling, which is known to find most concurrency bugs with very small bounds.
+//!
+//! Preemptions are only considered after the active thread performed an operation that other
+//! threads can observe (atomic accesses, fences and synchronization primitives). Non-atomic
+//! accesses do not need to be interleaved since any conflict between them is a data race, and the
+//! race detector reports those independently of the schedule.
+//!
+//! The search is a depth-first traversal of the tree of decisions: after each run, the deepest
+//! decision that still has an affordable untried option is advanced and everything after it is
+//! reset to the default. A [`Schedule`] records only the non-default options, so it is short enough
+//! to print when a run fails and can be passed to `-Zmiri-replay-schedule` to reproduce that run.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// The non-default options taken at the decision points of one run.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Schedule {
+    /// Maps the index of a decision to the option taken there.
+    choices: BTreeMap<usize, usize>,
+}
+
+impl fmt::Display for Schedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, (decision, choice)) in self.choices.iter().enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            write!(f, "{decision}:{choice}")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Schedule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut choices = BTreeMap::new();
+        for entry in s.split(',').filter(|entry| !entry.is_empty()) {
+            let parse = || {
+                let (decision, choice) = entry.split_once(':')?;
+                Some((decision.parse().ok()?, choice.parse().ok()?))
+            };
+            let Some((decision, choice)) = parse() else {
+                return Err(format!("`{entry}` is not of the form `<decision>:<option>`"));
+            };
+            if choice == 0 {
+                // Option 0 is the default, so it is never recorded.
+                continue;
+            }
+            if choices.insert(decision, choice).is_some() {
+                return Err(format!("decision {decision} appears more than once"));
+            }
+        }
+        Ok(Schedule { choices })
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Decision {
+    options: usize,
+    choice: usize,
+}
+
+/// Resolves the scheduling decisions of a single run according to a [`Schedule`], and records
+/// them so that the next schedule to explore can be computed afterwards.
+#[derive(Debug)]
+pub struct ScheduleExplorer {
+    schedule: Schedule,
+    decisions: Vec<Decision>,
+    /// Whether the active thread did something visible to other threads since the last
+    /// preemption point.
+    visible_operation: bool,
+    /// Whether the schedule asked for an option that did not exist in this run.
+    diverged: bool,
+}
+
+impl ScheduleExplorer {
+    pub fn new(schedule: Schedule) -> Self {
+        ScheduleExplorer {
+            schedule,
+            decisions: Vec::new(),
+            visible_operation: false,
+            diverged: false,
+        }
+    }
+
+    /// Picks one of `options` alternatives; 0 is the default. Choices with a single option are
+    /// not decisions and do not get recorded.
+    pub fn choose(&mut self, options: usize) -> usize {
+        if options <= 1 {
+            return 0;
+        }
+        let index = self.decisions.len();
+        let mut choice = self.schedule.choices.get(&index).copied().unwrap_or(0);
+        if choice >= options {
+            // The program did not behave the same way as when this schedule was recorded.
+            self.diverged = true;
+            choice = 0;
+        }
+        self.decisions.push(Decision { options, choice });
+        choice
+    }
+
+    pub fn note_visible_operation(&mut self) {
+        self.visible_operation = true;
+    }
+
+    /// Returns whether there was a visible operation since the last call.
+    pub fn take_visible_operation(&mut self) -> bool {
+        std::mem::take(&mut self.visible_operation)
+    }
+
+    /// The schedule that this run actually followed.
+    pub fn schedule(&self) -> Schedule {
+        let choices = self
+            .decisions
+            .iter()
+            .enumerate()
+            .filter(|(_, decision)| decision.choice != 0)
+            .map(|(index, decision)| (index, decision.choice))
+            .collect();
+        Schedule { choices }
+    }
+
+    pub fn diverged(&self) -> bool {
+        self.diverged
+    }
+
+    /// Computes the schedule to explore after this run, or `None` if every schedule with at most
+    /// `bound` delays has been explored.
+    pub fn next_schedule(&self, bound: usize) -> Option<Schedule> {
+        let mut delays = Vec::with_capacity(self.decisions.len());
+        let mut total = 0usize;
+        for decision in &self.decisions {
+            delays.push(total);
+            total = total.strict_add(decision.choice);
+        }
+        // Advance the deepest decision that has another option within the bound.
+        let index = (0..self.decisions.len()).rev().find(|&index| {
+            let decision = self.decisions[index];
+            decision.choice.strict_add(1) < decision.options
+                && delays[index].strict_add(decision.choice) < bound
+        })?;
+        let mut schedule = self.schedule();
+        schedule.choices.retain(|&decision, _| decision < index);
+        schedule.choices.insert(index, self.decisions[index].choice.strict_add(1));
+        Some(schedule)
+    }
+}
@@ -1,5 +1,6 @@
 //! Implements threads.
 
+use std::cell::RefCell;
 use std::mem;
 use std::num::TryFromIntError;
 use std::sync::atomic::Ordering::Relaxed;
@@ -27,6 +28,8 @@ enum SchedulingAction {
     ExecuteStep,
     /// Execute a timeout callback.
     ExecuteTimeoutCallback,
+    /// Run the events of the timers that have expired.
+    ExpireTimers,
     /// Wait for a bit, until there is a timeout to be called.
     Sleep(Duration),
 }
@@ -172,6 +175,8 @@ pub enum BlockReason {
     Futex { addr: u64 },
     /// Blocked on an InitOnce.
     InitOnce(InitOnceId),
+    /// Blocked until a file description is ready for I/O, e.g. in `accept` or `epoll_wait`.
+    Io,
 }
 
 /// The state of a thread.
@@ -576,10 +581,18 @@ pub fn get_live_thread_count(&self) -> usize {
     }
 
     /// Has the given thread terminated?
-    fn has_terminated(&self, thread_id: ThreadId) -> bool {
+    pub fn has_terminated(&self, thread_id: ThreadId) -> bool {
         self.threads[thread_id].state.is_terminated()
     }
 
+    /// Get what the given thread is blocked on, or `None` if it is not blocked.
+    pub fn get_block_reason(&self, thread_id: ThreadId) -> Option<BlockReason> {
+        match self.threads[thread_id].state {
+            ThreadState::Blocked { reason, .. } => Some(reason),
+            _ => None,
+        }
+    }
+
     /// Have all threads terminated?
     fn have_all_terminated(&self) -> bool {
         self.threads.iter().all(|thread| thread.state.is_terminated())
@@ -751,7 +764,22 @@ fn next_callback_wait_time(&self, clock: &Clock) -> Option<Duration> {
     /// used in stateless model checkers such as Loom: run the active thread as
     /// long as we can and switch only when we have to (the active thread was
     /// blocked, terminated, or has explicitly asked to be preempted).
-    fn schedule(&mut self, clock: &Clock) -> InterpResult<'tcx, SchedulingAction> {
+    ///
+    /// When exploring schedules, the explorer decides which of the enabled threads to switch to,
+    /// with the round-robin choice described below as the default.
+    ///
+    /// `timer_wait_time` is how long it takes until the next timer (see [`Timers`]) expires.
+    fn schedule(
+        &mut self,
+        clock: &Clock,
+        explorer: Option<&RefCell<ScheduleExplorer>>,
+        timer_wait_time: Option<Duration>,
+    ) -> InterpResult<'tcx, SchedulingAction> {
+        // Timers expire independently of what the threads are doing, so we check them even if
+        // the active thread could keep going.
+        if timer_wait_time == Some(Duration::ZERO) {
+            return Ok(SchedulingAction::ExpireTimers);
+        }
         // This thread and the program can keep going.
         if self.threads[self.active_thread].state.is_enabled() && !self.yield_active_thread {
             // The currently active thread is still enabled, just continue with it.
@@ -763,7 +791,8 @@ fn schedule(&mut self, clock: &Clock) -> InterpResult<'tcx, SchedulingAction> {
         // `pthread_cond_timedwait`, "an error is returned if [...] the absolute time specified by
         // abstime has already been passed at the time of the call".
         // <https://pubs.opengroup.org/onlinepubs/9699919799/functions/pthread_cond_timedwait.html>
-        let potential_sleep_time = self.next_callback_wait_time(clock);
+        let potential_sleep_time =
+            self.next_callback_wait_time(clock).into_iter().chain(timer_wait_time).min();
         if potential_sleep_time == Some(Duration::ZERO) {
             return Ok(SchedulingAction::ExecuteTimeoutCallback);
         }
@@ -775,22 +804,29 @@ fn schedule(&mut self, clock: &Clock) -> InterpResult<'tcx, SchedulingAction> {
         // `skip(N)` means we start iterating at thread N, so we skip 1 more to start just *after*
         // the active thread. Then after that we look at `take(N)`, i.e., the threads *before* the
         // active thread.
-        let threads = self
+        let mut threads = self
             .threads
             .iter_enumerated()
             .skip(self.active_thread.index() + 1)
-            .chain(self.threads.iter_enumerated().take(self.active_thread.index()));
-        for (id, thread) in threads {
-            debug_assert_ne!(self.active_thread, id);
-            if thread.state.is_enabled() {
-                info!(
-                    "---------- Now executing on thread `{}` (previous: `{}`) ----------------------------------------",
-                    self.get_thread_display_name(id),
-                    self.get_thread_display_name(self.active_thread)
-                );
-                self.active_thread = id;
-                break;
+            .chain(self.threads.iter_enumerated().take(self.active_thread.index()))
+            .filter(|(_, thread)| thread.state.is_enabled())
+            .map(|(id, _)| id);
+        let next = match explorer {
+            Some(explorer) => {
+                let candidates: Vec<ThreadId> = threads.collect();
+                let choice = explorer.borrow_mut().choose(candidates.len());
+                candidates.get(choice).copied()
             }
+            None => threads.next(),
+        };
+        if let Some(id) = next {
+            debug_assert_ne!(self.active_thread, id);
+            info!(
+                "---------- Now executing on thread `{}` (previous: `{}`) ----------------------------------------",
+                self.get_thread_display_name(id),
+                self.get_thread_display_name(self.active_thread)
+            );
+            self.active_thread = id;
         }
         self.yield_active_thread = false;
         if self.threads[self.active_thread].state.is_enabled() {
@@ -801,7 +837,7 @@ fn schedule(&mut self, clock: &Clock) -> InterpResult<'tcx, SchedulingAction> {
             unreachable!("all threads terminated without the main thread terminating?!");
         } else if let Some(sleep_time) = potential_sleep_time {
             // All threads are currently blocked, but we have unexecuted
-            // timeout_callbacks, which may unblock some of the threads. Hence,
+            // timeout_callbacks or timers, which may unblock some of the threads. Hence,
             // sleep until the first callback.
             Ok(SchedulingAction::Sleep(sleep_time))
         } else {
@@ -940,6 +976,8 @@ fn start_regular_thread(
         if let Some(cpuset) = this.machine.thread_cpu_affinity.get(&old_thread_id).cloned() {
             this.machine.thread_cpu_affinity.insert(new_thread_id, cpuset);
         }
+        // It also inherits the signal mask.
+        this.machine.signals.thread_created(old_thread_id, new_thread_id);
 
         // Perform the function pointer load in the new thread frame.
         let instance = this.get_ptr_fn(start_routine)?.as_instance()?;
@@ -1178,7 +1216,21 @@ fn maybe_preempt_active_thread(&mut self) {
         use rand::Rng as _;
 
         let this = self.eval_context_mut();
-        if this.machine.rng.get_mut().gen_bool(this.machine.preemption_rate) {
+        if let Some(explorer) = &this.machine.schedule_explorer {
+            // Preempting is only worth exploring after the active thread did something the other
+            // threads could observe, and only if there is some other thread to switch to.
+            let mut explorer = explorer.borrow_mut();
+            let threads = &this.machine.threads;
+            let preempt = explorer.take_visible_operation()
+                && threads.threads.iter_enumerated().any(|(id, thread)| {
+                    id != threads.active_thread && thread.state.is_enabled()
+                })
+                && explorer.choose(2) == 1;
+            drop(explorer);
+            if preempt {
+                this.yield_active_thread();
+            }
+        } else if this.machine.rng.get_mut().gen_bool(this.machine.preemption_rate) {
             this.yield_active_thread();
         }
     }
@@ -1192,8 +1244,14 @@ fn run_threads(&mut self) -> InterpResult<'tcx, !> {
                 this.machine.handle_abnormal_termination();
                 std::process::exit(1);
             }
-            match this.machine.threads.schedule(&this.machine.clock)? {
+            match this.machine.threads.schedule(
+                &this.machine.clock,
+                this.machine.schedule_explorer.as_ref(),
+                this.machine.timers.next_wait_time(&this.machine.clock),
+            )? {
                 SchedulingAction::ExecuteStep => {
+                    this.deliver_pending_signal()?;
+                    this.debugger_before_step();
                     if !this.step()? {
                         // See if this thread can do something else.
                         match this.run_on_stack_empty()? {
@@ -1206,6 +1264,9 @@ fn run_threads(&mut self) -> InterpResult<'tcx, !> {
                 SchedulingAction::ExecuteTimeoutCallback => {
                     this.run_timeout_callback()?;
                 }
+                SchedulingAction::ExpireTimers => {
+                    this.expire_timers()?;
+                }
                 SchedulingAction::Sleep(duration) => {
                     this.machine.clock.sleep(duration);
                 }
@@ -281,6 +281,7 @@ fn buffered_read(
         thread_mgr: &ThreadManager<'_>,
         is_seqcst: bool,
         rng: &mut (impl rand::Rng + ?Sized),
+        explorer: Option<&RefCell<ScheduleExplorer>>,
         validate: impl FnOnce() -> InterpResult<'tcx>,
     ) -> InterpResult<'tcx, (Scalar, LoadRecency)> {
         // Having a live borrow to store_buffer while calling validate_atomic_load is fine
@@ -291,7 +292,7 @@ fn buffered_read(
             // as the race detector will update it
             let (.., clocks) = global.active_thread_state(thread_mgr);
             // Load from a valid entry in the store buffer
-            self.fetch_store(is_seqcst, &clocks, &mut *rng)
+            self.fetch_store(is_seqcst, &clocks, &mut *rng, explorer)
         };
 
         // Unlike in buffered_atomic_write, thread clock updates have to be done
@@ -325,6 +326,7 @@ fn fetch_store<R: rand::Rng + ?Sized>(
         is_seqcst: bool,
         clocks: &ThreadClockSet,
         rng: &mut R,
+        explorer: Option<&RefCell<ScheduleExplorer>>,
     ) -> (&StoreElement, LoadRecency) {
         use rand::seq::IteratorRandom;
         let mut found_sc = false;
@@ -397,7 +399,15 @@ fn fetch_store<R: rand::Rng + ?Sized>(
                 }
             });
 
-        let chosen = candidates.choose(rng).expect("store buffer cannot be empty");
+        let chosen = match explorer {
+            // Candidates are ordered from the latest store backwards, so exploration reads the
+            // latest store by default and then tries each older one.
+            Some(explorer) => {
+                let candidates: Vec<_> = candidates.collect();
+                candidates[explorer.borrow_mut().choose(candidates.len())]
+            }
+            None => candidates.choose(rng).expect("store buffer cannot be empty"),
+        };
         if std::ptr::eq(chosen, self.buffer.back().expect("store buffer cannot be empty")) {
             (chosen, LoadRecency::Latest)
         } else {
@@ -510,6 +520,7 @@ fn buffered_atomic_read(
                     &this.machine.threads,
                     atomic == AtomicReadOrd::SeqCst,
                     &mut *rng,
+                    this.machine.schedule_explorer.as_ref(),
                     validate,
                 )?;
                 if global.track_outdated_loads && recency == LoadRecency::Outdated {
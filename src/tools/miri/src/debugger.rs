@@ -0,0 +1,534 @@
+//! An interactive debugger for the interpreted program, enabled with `-Zmiri-debug`.
+//!
+//! Before each step of the interpreter, we check whether the active thread reached a breakpoint or
+//! finished the kind of step that was asked for. If so, we read commands from the standard input
+//! until one of them resumes execution. The standard input is shared with the interpreted program,
+//! and all output goes to the standard error.
+//!
+//! When the program hits an error, the debugger is entered one last time so that the stack of the
+//! erroneous access can still be inspected.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::{self, BufRead, Write};
+use std::num::NonZero;
+
+use either::Either;
+use rustc_middle::ty::layout::LayoutOf as _;
+use rustc_middle::{mir, ty};
+use rustc_span::{Loc, Span};
+
+use crate::*;
+
+const HELP: &str = "\
+commands:
+  break <function> | break <file>:<line>   set a breakpoint (alias: b)
+  delete <n>                               delete breakpoint <n> (alias: d)
+  breakpoints                              list the breakpoints
+  continue                                 run until the next breakpoint (alias: c)
+  step                                     run until the next source line (alias: s)
+  next                                     like `step`, but do not enter calls (alias: n)
+  stepi                                    run a single MIR statement or terminator (alias: si)
+  finish                                   run until the selected frame returns
+  backtrace                                print the stack of the active thread (alias: bt)
+  frame <n>                                select frame <n> of the backtrace (alias: f)
+  locals                                   print the local variables of the selected frame
+  memory <alloc-id>                        print the contents of an allocation (alias: x)
+  borrows <alloc-id>                       print the borrow tracker state of an allocation
+  quit                                     stop the program (alias: q)
+An empty line repeats the previous command.";
+
+/// A place where the debugger stops.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Breakpoint {
+    /// The start of every function whose path is, or ends in, this path.
+    Function(String),
+    /// A source line in every file whose path is, or ends in, `file`.
+    Line { file: String, line: usize },
+}
+
+impl Breakpoint {
+    fn parse(s: &str) -> Self {
+        // Paths contain `::`, so only a single colon followed by a number is a line.
+        if let Some((file, line)) = s.rsplit_once(':')
+            && !file.is_empty()
+            && !file.ends_with(':')
+            && let Ok(line) = line.parse()
+        {
+            Breakpoint::Line { file: file.to_owned(), line }
+        } else {
+            Breakpoint::Function(s.to_owned())
+        }
+    }
+}
+
+impl fmt::Display for Breakpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Breakpoint::Function(path) => write!(f, "function `{path}`"),
+            Breakpoint::Line { file, line } => write!(f, "{file}:{line}"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Command {
+    Break(Breakpoint),
+    Delete(usize),
+    Breakpoints,
+    Continue,
+    Step,
+    Next,
+    StepInstruction,
+    Finish,
+    Backtrace,
+    Frame(usize),
+    Locals,
+    Memory(AllocId),
+    Borrows(AllocId),
+    Quit,
+    Help,
+}
+
+impl Command {
+    fn parse(line: &str) -> Result<Self, String> {
+        let mut words = line.split_whitespace();
+        let name = words.next().unwrap_or_default();
+        let arg = words.next();
+        if words.next().is_some() {
+            return Err(format!("too many arguments for `{name}`"));
+        }
+        Ok(match (name, arg) {
+            ("break" | "b", Some(arg)) => Command::Break(Breakpoint::parse(arg)),
+            ("delete" | "d", Some(arg)) => Command::Delete(parse_number(arg)?),
+            ("breakpoints", None) => Command::Breakpoints,
+            ("continue" | "c", None) => Command::Continue,
+            ("step" | "s", None) => Command::Step,
+            ("next" | "n", None) => Command::Next,
+            ("stepi" | "si", None) => Command::StepInstruction,
+            ("finish", None) => Command::Finish,
+            ("backtrace" | "bt", None) => Command::Backtrace,
+            ("frame" | "f", Some(arg)) => Command::Frame(parse_number(arg)?),
+            ("locals", None) => Command::Locals,
+            ("memory" | "x", Some(arg)) => Command::Memory(parse_alloc_id(arg)?),
+            ("borrows", Some(arg)) => Command::Borrows(parse_alloc_id(arg)?),
+            ("quit" | "q", None) => Command::Quit,
+            ("help" | "h", None) => Command::Help,
+            _ =>
+                return Err(format!(
+                    "unknown command or wrong number of arguments: `{}`; type `help` for a list of commands",
+                    line.trim()
+                )),
+        })
+    }
+
+    /// Whether this command continues the execution of the program.
+    fn resumes(&self) -> bool {
+        matches!(
+            self,
+            Command::Continue
+                | Command::Step
+                | Command::Next
+                | Command::StepInstruction
+                | Command::Finish
+        )
+    }
+}
+
+fn parse_number(arg: &str) -> Result<usize, String> {
+    arg.parse().map_err(|_| format!("`{arg}` is not a number"))
+}
+
+/// Accepts both `alloc123` (as printed in diagnostics) and `123`.
+fn parse_alloc_id(arg: &str) -> Result<AllocId, String> {
+    arg.strip_prefix("alloc")
+        .unwrap_or(arg)
+        .parse::<NonZero<u64>>()
+        .map(AllocId)
+        .map_err(|_| format!("`{arg}` is not an allocation ID"))
+}
+
+/// Whether `path` is `suffix`, or ends in `::suffix`.
+fn path_matches(path: &str, suffix: &str) -> bool {
+    path.strip_suffix(suffix).is_some_and(|prefix| prefix.is_empty() || prefix.ends_with("::"))
+}
+
+/// Whether `file` is `suffix`, or ends in `/suffix`.
+fn file_matches(file: &str, suffix: &str) -> bool {
+    file.strip_suffix(suffix)
+        .is_some_and(|prefix| prefix.is_empty() || prefix.ends_with('/') || prefix.ends_with('\\'))
+}
+
+/// Where the active thread is about to continue executing.
+#[derive(Clone, Debug)]
+struct Position {
+    thread: ThreadId,
+    /// The number of frames on the stack.
+    depth: usize,
+    file: String,
+    line: usize,
+    col: usize,
+    /// Whether the top frame is about to execute its first statement.
+    at_entry: bool,
+}
+
+impl Position {
+    fn same_line(&self, other: &Position) -> bool {
+        self.thread == other.thread
+            && self.depth == other.depth
+            && self.line == other.line
+            && self.file == other.file
+    }
+}
+
+/// When to stop, apart from breakpoints.
+#[derive(Debug)]
+enum Step {
+    /// Only stop at breakpoints.
+    Continue,
+    /// Stop before the next statement or terminator of any thread.
+    Instruction,
+    /// Stop when a frame of a local crate reaches a different source line than the given one.
+    /// Before the program has started, there is no line to leave yet.
+    Line(Option<Position>),
+    /// Stop when the thread reaches a different source line than the given one without being
+    /// in a function called from there.
+    Next(Position),
+    /// Stop once the thread has fewer than `depth` frames.
+    Finish { thread: ThreadId, depth: usize },
+}
+
+/// The state of the debugger across stops.
+#[derive(Debug)]
+pub struct Debugger {
+    breakpoints: BTreeMap<usize, Breakpoint>,
+    next_breakpoint: usize,
+    step: Step,
+    /// Where the active thread was before the last step.
+    last_position: Option<Position>,
+    /// The frame that `locals` and `finish` act on, counted from the top of the stack.
+    selected_frame: usize,
+    /// The command repeated by an empty line.
+    last_command: Option<Command>,
+}
+
+impl Default for Debugger {
+    fn default() -> Self {
+        Debugger {
+            breakpoints: BTreeMap::new(),
+            next_breakpoint: 1,
+            // Give the user a chance to set breakpoints as soon as the program reaches their code.
+            step: Step::Line(None),
+            last_position: None,
+            selected_frame: 0,
+            last_command: None,
+        }
+    }
+}
+
+impl Debugger {
+    fn should_stop(&self, this: &MiriInterpCx<'_>, position: &Position) -> bool {
+        let frame = this.active_thread_stack().last().unwrap();
+        let new_line = self.last_position.as_ref().is_none_or(|last| !last.same_line(position));
+        let step_done = match &self.step {
+            Step::Continue => false,
+            Step::Instruction => true,
+            Step::Line(from) =>
+                frame.extra.is_user_relevant
+                    && from.as_ref().is_none_or(|from| {
+                        from.thread == position.thread && !from.same_line(position)
+                    }),
+            Step::Next(from) =>
+                from.thread == position.thread
+                    && position.depth <= from.depth
+                    && !from.same_line(position),
+            Step::Finish { thread, depth } => *thread == position.thread && position.depth < *depth,
+        };
+        step_done
+            || (new_line
+                && self.breakpoints.values().any(|breakpoint| match breakpoint {
+                    Breakpoint::Function(path) =>
+                        position.at_entry
+                            && path_matches(&this.tcx.def_path_str(frame.instance.def_id()), path),
+                    Breakpoint::Line { file, line } =>
+                        position.line == *line && file_matches(&position.file, file),
+                }))
+    }
+
+    /// Reads and runs commands until one of them resumes the program. `position` is `None` if the
+    /// program cannot be resumed.
+    fn prompt(&mut self, this: &mut MiriInterpCx<'_>, position: Option<&Position>) {
+        loop {
+            eprint!("(miri) ");
+            io::stderr().flush().unwrap();
+            let mut line = String::new();
+            if matches!(io::stdin().lock().read_line(&mut line), Ok(0) | Err(_)) {
+                // There are no more commands, so let the program run to completion.
+                eprintln!();
+                self.breakpoints.clear();
+                self.step = Step::Continue;
+                return;
+            }
+            let command = if line.trim().is_empty() {
+                match &self.last_command {
+                    Some(command) => command.clone(),
+                    None => continue,
+                }
+            } else {
+                match Command::parse(&line) {
+                    Ok(command) => command,
+                    Err(err) => {
+                        eprintln!("{err}");
+                        continue;
+                    }
+                }
+            };
+            self.last_command = Some(command.clone());
+
+            if command.resumes() {
+                let Some(position) = position else {
+                    eprintln!("the program cannot continue after an error; use `quit` to exit");
+                    continue;
+                };
+                self.step = match command {
+                    Command::Continue => Step::Continue,
+                    Command::Step => Step::Line(Some(position.clone())),
+                    Command::Next => Step::Next(position.clone()),
+                    Command::StepInstruction => Step::Instruction,
+                    Command::Finish => Step::Finish {
+                        thread: position.thread,
+                        depth: position.depth.saturating_sub(self.selected_frame),
+                    },
+                    _ => unreachable!(),
+                };
+                return;
+            }
+            self.run_command(this, command);
+        }
+    }
+
+    /// Runs a command that does not resume the program.
+    fn run_command(&mut self, this: &mut MiriInterpCx<'_>, command: Command) {
+        match command {
+            Command::Break(breakpoint) => {
+                let id = self.next_breakpoint;
+                self.next_breakpoint = id.strict_add(1);
+                eprintln!("breakpoint {id} at {breakpoint}");
+                self.breakpoints.insert(id, breakpoint);
+            }
+            Command::Delete(id) =>
+                if self.breakpoints.remove(&id).is_none() {
+                    eprintln!("there is no breakpoint {id}");
+                },
+            Command::Breakpoints => {
+                if self.breakpoints.is_empty() {
+                    eprintln!("there are no breakpoints");
+                }
+                for (id, breakpoint) in &self.breakpoints {
+                    eprintln!("{id}: {breakpoint}");
+                }
+            }
+            Command::Backtrace => {
+                let stack = this.active_thread_stack();
+                for (i, frame) in stack.iter().rev().enumerate() {
+                    let marker = if i == self.selected_frame { ">" } else { " " };
+                    eprintln!("{marker}#{i} {}", describe_frame(this, frame));
+                }
+            }
+            Command::Frame(i) => {
+                let stack = this.active_thread_stack();
+                if i >= stack.len() {
+                    eprintln!("there are only {} frames", stack.len());
+                } else {
+                    self.selected_frame = i;
+                    let frame = &stack[stack.len() - 1 - i];
+                    eprintln!("#{i} {}", describe_frame(this, frame));
+                }
+            }
+            Command::Locals => print_locals(this, self.selected_frame),
+            Command::Memory(alloc_id) => eprintln!("{:?}", this.dump_alloc(alloc_id)),
+            Command::Borrows(alloc_id) =>
+                if this.machine.borrow_tracker.is_none() {
+                    eprintln!("the borrow tracker is disabled");
+                } else if let Err(err) = this.print_borrow_state(alloc_id, true) {
+                    eprintln!("{}", format_interp_error(this.tcx.dcx(), err));
+                },
+            Command::Quit => {
+                this.machine.handle_abnormal_termination();
+                std::process::exit(1);
+            }
+            Command::Help => eprintln!("{HELP}"),
+            Command::Continue
+            | Command::Step
+            | Command::Next
+            | Command::StepInstruction
+            | Command::Finish => unreachable!(),
+        }
+    }
+}
+
+/// The source location of the code a frame executes. For code expanded from a macro, this is the
+/// location of the macro call.
+fn frame_loc(this: &MiriInterpCx<'_>, frame: &Frame<'_, Provenance, FrameExtra<'_>>) -> Loc {
+    this.tcx.sess.source_map().lookup_char_pos(frame.current_span().source_callsite().lo())
+}
+
+fn current_position(this: &MiriInterpCx<'_>) -> Option<Position> {
+    let stack = this.active_thread_stack();
+    let frame = stack.last()?;
+    let loc = frame_loc(this, frame);
+    Some(Position {
+        thread: this.active_thread(),
+        depth: stack.len(),
+        file: loc.file.name.prefer_local().to_string(),
+        line: loc.line,
+        col: loc.col_display.strict_add(1),
+        at_entry: frame.current_loc() == Either::Left(mir::Location::START),
+    })
+}
+
+fn describe_span(this: &MiriInterpCx<'_>, span: Span) -> String {
+    this.tcx.sess.source_map().span_to_embeddable_string(span.source_callsite())
+}
+
+fn describe_frame<'tcx>(
+    this: &MiriInterpCx<'tcx>,
+    frame: &Frame<'tcx, Provenance, FrameExtra<'tcx>>,
+) -> String {
+    format!("{} at {}", frame.instance, describe_span(this, frame.current_span()))
+}
+
+/// Prints the source line of `position`.
+fn print_position(this: &MiriInterpCx<'_>, position: &Position) {
+    let frame = this.active_thread_stack().last().unwrap();
+    eprintln!(
+        "stopped in {} at {}:{}:{}",
+        frame.instance, position.file, position.line, position.col
+    );
+    let loc = frame_loc(this, frame);
+    if let Some(text) = loc.file.get_line(loc.line.saturating_sub(1)) {
+        eprintln!("{:>5} | {}", position.line, text.trim_end());
+    }
+}
+
+/// Prints the user variables of a frame, along with their borrow tracker tags.
+fn print_locals(this: &MiriInterpCx<'_>, selected_frame: usize) {
+    let stack = this.active_thread_stack();
+    let Some(frame) = stack.iter().rev().nth(selected_frame) else {
+        eprintln!("there is no frame {selected_frame}");
+        return;
+    };
+    let mut printed = false;
+    for var in &frame.body.var_debug_info {
+        let mir::VarDebugInfoContents::Place(place) = var.value else { continue };
+        let Some(local) = place.as_local() else { continue };
+        printed = true;
+        let ty = frame.instance.instantiate_mir_and_normalize_erasing_regions(
+            *this.tcx,
+            ty::ParamEnv::reveal_all(),
+            ty::EarlyBinder::bind(frame.body.local_decls[local].ty),
+        );
+        eprintln!("{}: {ty} = {}", var.name, describe_local(this, frame, local, ty));
+    }
+    if !printed {
+        eprintln!("there are no local variables in this frame");
+    }
+}
+
+/// Describes the value of a local without accessing memory, since that could change the state of
+/// the borrow tracker and the data race detector.
+fn describe_local<'tcx>(
+    this: &MiriInterpCx<'tcx>,
+    frame: &Frame<'tcx, Provenance, FrameExtra<'tcx>>,
+    local: mir::Local,
+    ty: ty::Ty<'tcx>,
+) -> String {
+    match frame.locals[local].as_mplace_or_imm() {
+        None => "<dead>".to_owned(),
+        Some(Either::Left((ptr, _meta))) => format!("<in memory at {ptr:?}>"),
+        Some(Either::Right(Immediate::Uninit)) => "<uninit>".to_owned(),
+        Some(Either::Right(Immediate::Scalar(scalar))) =>
+            match this.layout_of(ty) {
+                Ok(layout) => ImmTy::from_scalar(scalar, layout).to_string(),
+                Err(_) => format!("{scalar:?}"),
+            },
+        Some(Either::Right(Immediate::ScalarPair(a, b))) => format!("({a:?}, {b:?})"),
+    }
+}
+
+impl<'tcx> EvalContextExt<'tcx> for crate::MiriInterpCx<'tcx> {}
+pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
+    /// Called before each step of the interpreter. Stops and prompts for commands if needed.
+    fn debugger_before_step(&mut self) {
+        let this = self.eval_context_mut();
+        let Some(mut debugger) = this.machine.debugger.take() else { return };
+        if let Some(position) = current_position(this) {
+            if debugger.should_stop(this, &position) {
+                debugger.step = Step::Continue;
+                debugger.selected_frame = 0;
+                print_position(this, &position);
+                debugger.prompt(this, Some(&position));
+            }
+            debugger.last_position = Some(position);
+        }
+        this.machine.debugger = Some(debugger);
+    }
+
+    /// Called after the program hit an error, to inspect the state it was in.
+    fn debugger_after_error(&mut self) {
+        let this = self.eval_context_mut();
+        let Some(mut debugger) = this.machine.debugger.take() else { return };
+        if this.active_thread_stack().is_empty() {
+            return;
+        }
+        debugger.selected_frame = 0;
+        eprintln!("the program stopped because of the error above; its state can be inspected");
+        debugger.prompt(this, None);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_breakpoints() {
+        assert_eq!(Breakpoint::parse("main"), Breakpoint::Function("main".to_owned()));
+        assert_eq!(
+            Breakpoint::parse("std::vec::Vec::<T>::push"),
+            Breakpoint::Function("std::vec::Vec::<T>::push".to_owned())
+        );
+        assert_eq!(
+            Breakpoint::parse("src/main.rs:12"),
+            Breakpoint::Line { file: "src/main.rs".to_owned(), line: 12 }
+        );
+        assert_eq!(Breakpoint::parse("::12"), Breakpoint::Function("::12".to_owned()));
+    }
+
+    #[test]
+    fn parse_commands() {
+        assert_eq!(
+            Command::parse("b foo::bar\n"),
+            Ok(Command::Break(Breakpoint::Function("foo::bar".to_owned())))
+        );
+        assert_eq!(Command::parse("  next "), Ok(Command::Next));
+        assert_eq!(Command::parse("f 3"), Ok(Command::Frame(3)));
+        let alloc_id = |id| AllocId(NonZero::new(id).unwrap());
+        assert_eq!(Command::parse("x alloc42"), Ok(Command::Memory(alloc_id(42))));
+        assert_eq!(Command::parse("borrows 7"), Ok(Command::Borrows(alloc_id(7))));
+        assert!(Command::parse("x alloc0").is_err());
+        assert!(Command::parse("frame").is_err());
+        assert!(Command::parse("step 2").is_err());
+        assert!(Command::parse("frobnicate").is_err());
+    }
+
+    #[test]
+    fn match_paths() {
+        assert!(path_matches("foo::bar", "bar"));
+        assert!(path_matches("foo::bar", "foo::bar"));
+        assert!(!path_matches("foo::foobar", "bar"));
+        assert!(file_matches("/home/user/src/main.rs", "main.rs"));
+        assert!(file_matches("src/main.rs", "src/main.rs"));
+        assert!(!file_matches("src/domain.rs", "main.rs"));
+    }
+}
@@ -1,5 +1,6 @@
 //! Main evaluator loop and setting up the initial stack frame.
 
+use std::cell::RefCell;
 use std::ffi::{OsStr, OsString};
 use std::iter;
 use std::panic::{self, AssertUnwindSafe};
@@ -137,6 +138,12 @@ pub struct MiriConfig {
     pub mute_stdout_stderr: bool,
     /// The probability of the active thread being preempted at the end of each basic block.
     pub preemption_rate: f64,
+    /// If set, run the program once for every schedule with at most this many delays.
+    pub explore_schedules: Option<usize>,
+    /// If set, scheduling decisions are taken from this schedule instead of being random.
+    pub schedule: Option<Schedule>,
+    /// Whether to stop at breakpoints and read debugger commands from the standard input.
+    pub debugger: bool,
     /// Report the current instruction being executed every N basic blocks.
     pub report_progress: Option<u32>,
     /// Whether Stacked Borrows and Tree Borrows retagging should recurse into fields of datatypes.
@@ -144,6 +151,8 @@ pub struct MiriConfig {
     /// The location of a shared object file to load when calling external functions
     /// FIXME! consider allowing users to specify paths to multiple files, or to a directory
     pub native_lib: Option<PathBuf>,
+    /// The host directory to copy into the virtual file system, as an absolute path.
+    pub virtual_fs: Option<PathBuf>,
     /// Run a garbage collector for BorTags every N basic blocks.
     pub gc_interval: u32,
     /// The number of CPUs to be reported by miri.
@@ -186,9 +195,13 @@ fn default() -> MiriConfig {
             provenance_mode: ProvenanceMode::Default,
             mute_stdout_stderr: false,
             preemption_rate: 0.01, // 1%
+            explore_schedules: None,
+            schedule: None,
+            debugger: false,
             report_progress: None,
             retag_fields: RetagFields::Yes,
             native_lib: None,
+            virtual_fs: None,
             gc_interval: 10_000,
             num_cpus: 1,
             page_size: None,
@@ -428,10 +441,45 @@ pub fn eval_entry<'tcx>(
     entry_type: EntryFnType,
     config: MiriConfig,
 ) -> Option<i64> {
-    // Copy setting before we move `config`.
-    let ignore_leaks = config.ignore_leaks;
+    let Some(bound) = config.explore_schedules else {
+        return eval_entry_once(tcx, entry_id, entry_type, &config).0;
+    };
+
+    // Run the program on every schedule within the bound, stopping at the first one that fails.
+    let mut config = config;
+    let mut schedule = Schedule::default();
+    let mut explored = 0usize;
+    loop {
+        config.schedule = Some(schedule);
+        explored = explored.strict_add(1);
+        let (return_code, explorer) = eval_entry_once(tcx, entry_id, entry_type, &config);
+        let explorer = explorer.expect("schedule exploration always uses an explorer");
+        if return_code != Some(0) {
+            tcx.dcx().note(format!(
+                "this happened on schedule {explored} of the exploration; \
+                use `-Zmiri-replay-schedule={}` to run only that schedule",
+                explorer.schedule()
+            ));
+            return return_code;
+        }
+        match explorer.next_schedule(bound) {
+            Some(next) => schedule = next,
+            None => break,
+        }
+    }
+    tcx.dcx().note(format!("explored {explored} schedules with at most {bound} delays"));
+    Some(0)
+}
 
-    let mut ecx = match create_ecx(tcx, entry_id, entry_type, &config) {
+/// Runs the program once. Also returns the schedule explorer, if there was one, so that the
+/// caller can tell which schedule was followed.
+fn eval_entry_once<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    entry_id: DefId,
+    entry_type: EntryFnType,
+    config: &MiriConfig,
+) -> (Option<i64>, Option<ScheduleExplorer>) {
+    let mut ecx = match create_ecx(tcx, entry_id, entry_type, config) {
         Ok(v) => v,
         Err(err) => {
             let (kind, backtrace) = err.into_parts();
@@ -464,7 +512,26 @@ pub fn eval_entry<'tcx>(
     }
 
     // Process the result.
-    let (return_code, leak_check) = report_error(&ecx, res)?;
+    let return_code = check_result(&ecx, res, config.ignore_leaks);
+    if return_code.is_none() {
+        ecx.debugger_after_error();
+    }
+    let explorer = ecx.machine.schedule_explorer.take().map(RefCell::into_inner);
+    if explorer.as_ref().is_some_and(|explorer| explorer.diverged()) {
+        tcx.dcx().warn("the program did not make the same scheduling decisions as the schedule \
+            it was given, so the rest of the run used default decisions");
+    }
+    (return_code, explorer)
+}
+
+/// Reports the outcome of a run, including leaks, and determines the exit code.
+fn check_result<'tcx>(
+    ecx: &MiriInterpCx<'tcx>,
+    res: InterpErrorInfo<'tcx>,
+    ignore_leaks: bool,
+) -> Option<i64> {
+    let tcx = *ecx.tcx;
+    let (return_code, leak_check) = report_error(ecx, res)?;
     if leak_check && !ignore_leaks {
         // Check for thread leaks.
         if !ecx.have_all_terminated() {
@@ -476,7 +543,7 @@ pub fn eval_entry<'tcx>(
         info!("Additional static roots: {:?}", ecx.machine.static_roots);
         let leaks = ecx.find_leaked_allocations(&ecx.machine.static_roots);
         if !leaks.is_empty() {
-            report_leaks(&ecx, leaks);
+            report_leaks(ecx, leaks);
             tcx.dcx().note("set `MIRIFLAGS=-Zmiri-ignore-leaks` to disable this check");
             // Ignore the provided return code - let the reported error
             // determine the return code.
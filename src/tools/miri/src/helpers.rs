@@ -97,6 +97,25 @@ pub enum AccessKind {
     ]
 };
 
+/// An `io::Error` payload standing for the libc error constant of the given name, for errors
+/// that have no `io::ErrorKind` of their own. Only supported on Unix targets.
+#[derive(Debug)]
+pub struct LibcError(pub &'static str);
+
+impl std::fmt::Display for LibcError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "libc error {}", self.0)
+    }
+}
+
+impl std::error::Error for LibcError {}
+
+impl LibcError {
+    pub fn new(name: &'static str) -> std::io::Error {
+        std::io::Error::other(LibcError(name))
+    }
+}
+
 /// Gets an instance for a path.
 ///
 /// A `None` namespace indicates we are looking for a module.
@@ -793,6 +812,9 @@ fn io_error_to_errnum(&self, err: std::io::Error) -> InterpResult<'tcx, Scalar>
         let this = self.eval_context_ref();
         let target = &this.tcx.sess.target;
         if target.families.iter().any(|f| f == "unix") {
+            if let Some(LibcError(name)) = err.get_ref().and_then(|e| e.downcast_ref()) {
+                return Ok(this.eval_libc(name));
+            }
             for &(name, kind) in UNIX_IO_ERROR_TABLE {
                 if err.kind() == kind {
                     return Ok(this.eval_libc(name));
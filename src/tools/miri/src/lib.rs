@@ -77,6 +77,7 @@
 mod borrow_tracker;
 mod clock;
 mod concurrency;
+mod debugger;
 mod diagnostics;
 mod eval;
 mod helpers;
@@ -113,6 +114,7 @@
 pub use crate::shims::foreign_items::{DynSym, EvalContextExt as _};
 pub use crate::shims::os_str::EvalContextExt as _;
 pub use crate::shims::panic::{CatchUnwindData, EvalContextExt as _};
+pub use crate::shims::signal::EvalContextExt as _;
 pub use crate::shims::time::EvalContextExt as _;
 pub use crate::shims::tls::TlsData;
 pub use crate::shims::EmulateItemResult;
@@ -126,10 +128,11 @@
 pub use crate::borrow_tracker::{
     BorTag, BorrowTrackerMethod, CallId, EvalContextExt as _, RetagFields,
 };
-pub use crate::clock::{Clock, Instant};
+pub use crate::clock::{Clock, Instant, TimerId, Timers};
 pub use crate::concurrency::{
     cpu_affinity::MAX_CPUS,
     data_race::{AtomicFenceOrd, AtomicReadOrd, AtomicRwOrd, AtomicWriteOrd, EvalContextExt as _},
+    exploration::{Schedule, ScheduleExplorer},
     init_once::{EvalContextExt as _, InitOnceId},
     sync::{CondvarId, EvalContextExt as _, MutexId, RwLockId, SynchronizationObjects},
     thread::{
@@ -137,13 +140,14 @@
         TimeoutAnchor, TimeoutClock, UnblockCallback,
     },
 };
+pub use crate::debugger::{Debugger, EvalContextExt as _};
 pub use crate::diagnostics::{
     report_error, EvalContextExt as _, NonHaltingDiagnostic, TerminationInfo,
 };
 pub use crate::eval::{
     create_ecx, eval_entry, AlignmentCheck, BacktraceStyle, IsolatedOp, MiriConfig, RejectOpWith,
 };
-pub use crate::helpers::{AccessKind, EvalContextExt as _};
+pub use crate::helpers::{AccessKind, EvalContextExt as _, LibcError};
 pub use crate::machine::{
     AllocExtra, FrameExtra, MemoryKind, MiriInterpCx, MiriInterpCxExt, MiriMachine, MiriMemoryKind,
     PrimitiveLayouts, Provenance, ProvenanceExtra,
@@ -73,6 +73,10 @@ pub struct FrameExtra<'tcx> {
     /// This is used by `MiriMachine::current_span` and `MiriMachine::caller_span`
     pub is_user_relevant: bool,
 
+    /// If this is Some(), then this is the frame of a signal handler, and we have to restore the
+    /// signal mask of the thread when it returns.
+    pub signal_handler: Option<shims::SignalHandlerFrame>,
+
     /// We have a cache for the mapping from [`mir::Const`] to resulting [`AllocId`].
     /// However, we don't want all frames to always get the same result, so we insert
     /// an additional bit of "salt" into the cache key. This salt is fixed per-frame
@@ -83,22 +87,36 @@ pub struct FrameExtra<'tcx> {
 impl<'tcx> std::fmt::Debug for FrameExtra<'tcx> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         // Omitting `timing`, it does not support `Debug`.
-        let FrameExtra { borrow_tracker, catch_unwind, timing: _, is_user_relevant: _, salt: _ } =
-            self;
+        let FrameExtra {
+            borrow_tracker,
+            catch_unwind,
+            timing: _,
+            is_user_relevant: _,
+            signal_handler,
+            salt: _,
+        } = self;
         f.debug_struct("FrameData")
             .field("borrow_tracker", borrow_tracker)
             .field("catch_unwind", catch_unwind)
+            .field("signal_handler", signal_handler)
             .finish()
     }
 }
 
 impl VisitProvenance for FrameExtra<'_> {
     fn visit_provenance(&self, visit: &mut VisitWith<'_>) {
-        let FrameExtra { catch_unwind, borrow_tracker, timing: _, is_user_relevant: _, salt: _ } =
-            self;
+        let FrameExtra {
+            catch_unwind,
+            borrow_tracker,
+            timing: _,
+            is_user_relevant: _,
+            signal_handler,
+            salt: _,
+        } = self;
 
         catch_unwind.visit_provenance(visit);
         borrow_tracker.visit_provenance(visit);
+        signal_handler.visit_provenance(visit);
     }
 }
 
@@ -466,6 +484,14 @@ pub struct MiriMachine<'tcx> {
     pub(crate) fds: shims::FdTable,
     /// The table of directory descriptors.
     pub(crate) dirs: shims::DirTable,
+    /// The virtual loopback network of sockets.
+    pub(crate) network: shims::VirtualNetwork,
+    /// The in-memory file system set up with `-Zmiri-virtual-fs`.
+    pub(crate) virtual_fs: Option<shims::VirtualFs>,
+    /// Signal handlers, masks and pending signals.
+    pub(crate) signals: shims::SignalState,
+    /// The timers of `alarm` and `timerfd`, which expire according to `clock`.
+    pub(crate) timers: Timers<shims::TimerEvent>,
 
     /// This machine's monotone clock.
     pub(crate) clock: Clock,
@@ -537,6 +563,13 @@ pub struct MiriMachine<'tcx> {
     /// The probability of the active thread being preempted at the end of each basic block.
     pub(crate) preemption_rate: f64,
 
+    /// Resolves scheduling decisions when exploring or replaying schedules. When set, the
+    /// preemption rate and the random choices of the weak memory emulation are not used.
+    pub(crate) schedule_explorer: Option<RefCell<ScheduleExplorer>>,
+
+    /// The interactive debugger, if enabled with `-Zmiri-debug`.
+    pub(crate) debugger: Option<Debugger>,
+
     /// If `Some`, we will report the current stack every N basic blocks.
     pub(crate) report_progress: Option<u32>,
     // The total number of blocks that have been executed.
@@ -662,6 +695,17 @@ pub(crate) fn new(config: &MiriConfig, layout_cx: LayoutCx<'tcx, TyCtxt<'tcx>>)
             validate: config.validate,
             fds: shims::FdTable::init(config.mute_stdout_stderr),
             dirs: Default::default(),
+            network: Default::default(),
+            virtual_fs: config.virtual_fs.as_ref().map(|dir| {
+                shims::VirtualFs::snapshot(dir).unwrap_or_else(|err| {
+                    tcx.dcx().fatal(format!(
+                        "failed to copy `{}` into the virtual file system: {err}",
+                        dir.display()
+                    ))
+                })
+            }),
+            signals: Default::default(),
+            timers: Default::default(),
             layouts,
             threads,
             thread_cpu_affinity,
@@ -682,6 +726,11 @@ pub(crate) fn new(config: &MiriConfig, layout_cx: LayoutCx<'tcx, TyCtxt<'tcx>>)
             mute_stdout_stderr: config.mute_stdout_stderr,
             weak_memory: config.weak_memory_emulation,
             preemption_rate: config.preemption_rate,
+            schedule_explorer: config
+                .schedule
+                .clone()
+                .map(|schedule| RefCell::new(ScheduleExplorer::new(schedule))),
+            debugger: config.debugger.then(Debugger::default),
             report_progress: config.report_progress,
             basic_block_count: 0,
             clock: Clock::new(config.isolated_op == IsolatedOp::Allow),
@@ -799,6 +848,10 @@ fn visit_provenance(&self, visit: &mut VisitWith<'_>) {
             data_race,
             alloc_addresses,
             fds,
+            network: _,
+            virtual_fs: _,
+            signals,
+            timers: _,
             tcx: _,
             isolated_op: _,
             validate: _,
@@ -819,6 +872,8 @@ fn visit_provenance(&self, visit: &mut VisitWith<'_>) {
             mute_stdout_stderr: _,
             weak_memory: _,
             preemption_rate: _,
+            schedule_explorer: _,
+            debugger: _,
             report_progress: _,
             basic_block_count: _,
             native_lib: _,
@@ -839,6 +894,7 @@ fn visit_provenance(&self, visit: &mut VisitWith<'_>) {
         env_vars.visit_provenance(visit);
         dirs.visit_provenance(visit);
         fds.visit_provenance(visit);
+        signals.visit_provenance(visit);
         data_race.visit_provenance(visit);
         borrow_tracker.visit_provenance(visit);
         alloc_addresses.visit_provenance(visit);
@@ -1379,6 +1435,7 @@ fn init_frame(
             catch_unwind: None,
             timing,
             is_user_relevant: ecx.machine.is_user_relevant(&frame),
+            signal_handler: None,
             salt: ecx.machine.rng.borrow_mut().gen::<usize>() % ADDRS_PER_CONST,
         };
 
@@ -1471,10 +1528,14 @@ fn after_stack_pop(
             // Move `frame`` into a sub-scope so we control when it will be dropped.
             let mut frame = frame;
             let timing = frame.extra.timing.take();
+            let signal_handler = frame.extra.signal_handler.take();
             let res = ecx.handle_stack_pop_unwind(frame.extra, unwinding);
             if let Some(profiler) = ecx.machine.profiler.as_ref() {
                 profiler.finish_recording_interval_event(timing.unwrap());
             }
+            if let Some(signal_handler) = signal_handler {
+                ecx.return_from_signal_handler(signal_handler)?;
+            }
             res
         };
         // Needs to be done after dropping frame to show up on the right nesting level.
@@ -0,0 +1,144 @@
+use rustc_span::Symbol;
+use rustc_target::spec::abi::Abi;
+
+use crate::*;
+
+mod neon;
+
+impl<'tcx> EvalContextExt<'tcx> for crate::MiriInterpCx<'tcx> {}
+pub(super) trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
+    fn emulate_aarch64_intrinsic(
+        &mut self,
+        link_name: Symbol,
+        abi: Abi,
+        args: &[OpTy<'tcx>],
+        dest: &MPlaceTy<'tcx>,
+    ) -> InterpResult<'tcx, EmulateItemResult> {
+        let this = self.eval_context_mut();
+        // Prefix should have already been checked.
+        let unprefixed_name = link_name.as_str().strip_prefix("llvm.aarch64.").unwrap();
+        match unprefixed_name {
+            "isb" => {
+                let [arg] = this.check_shim(abi, Abi::Unadjusted, link_name, args)?;
+                let arg = this.read_scalar(arg)?.to_i32()?;
+                match arg {
+                    // SY ("full system scope")
+                    15 => {
+                        this.yield_active_thread();
+                    }
+                    _ => {
+                        throw_unsup_format!("unsupported llvm.aarch64.isb argument {}", arg);
+                    }
+                }
+            }
+
+            // Used to implement the `__crc32{b,h,w,d}` and `__crc32c{b,h,w,d}` functions.
+            // Accumulates `data` into the 32-bit `crc`, using the CRC-32 polynomial `0x04C11DB7`
+            // or, for the `c` variants, the CRC-32C polynomial `0x1EDC6F41`. Unlike most CRC
+            // implementations, the instructions neither invert the input nor the output.
+            // https://developer.arm.com/documentation/ddi0602/2024-06/Base-Instructions/CRC32B--CRC32H--CRC32W--CRC32X--CRC32-checksum-
+            "crc32b" | "crc32h" | "crc32w" | "crc32x" | "crc32cb" | "crc32ch" | "crc32cw"
+            | "crc32cx" => {
+                this.expect_target_feature_for_intrinsic(link_name, "crc")?;
+
+                let [crc, data] = this.check_shim(abi, Abi::Unadjusted, link_name, args)?;
+                let crc = this.read_scalar(crc)?.to_u32()?;
+                let data = this.read_scalar(data)?;
+                // The byte and halfword variants take a 32-bit argument but only use its low bits.
+                let (data, bit_size) = match unprefixed_name.as_bytes().last() {
+                    Some(b'b') => (u64::from(data.to_u32()? & 0xff), 8),
+                    Some(b'h') => (u64::from(data.to_u32()? & 0xffff), 16),
+                    Some(b'w') => (u64::from(data.to_u32()?), 32),
+                    Some(b'x') => (data.to_u64()?, 64),
+                    _ => unreachable!(),
+                };
+                // The instructions process the bits from the least significant one onwards, so
+                // they use the bit-reversed polynomials.
+                let polynomial = if unprefixed_name.starts_with("crc32c") {
+                    0x82F63B78
+                } else {
+                    0xEDB88320
+                };
+
+                let mut value = u64::from(crc) ^ data;
+                for _ in 0..bit_size {
+                    value = if value & 1 == 1 { (value >> 1) ^ polynomial } else { value >> 1 };
+                }
+                let res = u32::try_from(value & u64::from(u32::MAX)).unwrap();
+
+                this.write_scalar(Scalar::from_u32(res), dest)?;
+            }
+
+            // Used to implement the `vaese{q}_u8`, `vaesd{q}_u8`, `vaesmc{q}_u8` and
+            // `vaesimc{q}_u8` functions.
+            // Unlike their x86 counterparts, AESE and AESD perform the `AddRoundKey` step first
+            // and never `MixColumns`; that step is done by the separate AESMC and AESIMC.
+            // https://developer.arm.com/documentation/ddi0602/2024-06/SIMD-FP-Instructions/AESE--AES-single-round-encryption-
+            "crypto.aese" | "crypto.aesd" => {
+                this.expect_target_feature_for_intrinsic(link_name, "aes")?;
+
+                let [data, key] = this.check_shim(abi, Abi::Unadjusted, link_name, args)?;
+                let data = read_u128(this, data)?;
+                let key = read_u128(this, key)?;
+
+                let mut state = aes::Block::from((data ^ key).to_le_bytes());
+                let zero = aes::Block::from([0; 16]);
+                if unprefixed_name == "crypto.aese" {
+                    // `aes::hazmat::cipher_round` does SubBytes, ShiftRows, MixColumns and then
+                    // XORs the key. Use a zeroed key and undo the MixColumns.
+                    aes::hazmat::cipher_round(&mut state, &zero);
+                    aes::hazmat::inv_mix_columns(&mut state);
+                } else {
+                    // Likewise for InvSubBytes, InvShiftRows and InvMixColumns.
+                    aes::hazmat::equiv_inv_cipher_round(&mut state, &zero);
+                    aes::hazmat::mix_columns(&mut state);
+                }
+
+                write_u128(this, u128::from_le_bytes(state.into()), dest)?;
+            }
+            "crypto.aesmc" | "crypto.aesimc" => {
+                this.expect_target_feature_for_intrinsic(link_name, "aes")?;
+
+                let [data] = this.check_shim(abi, Abi::Unadjusted, link_name, args)?;
+                let data = read_u128(this, data)?;
+
+                let mut state = aes::Block::from(data.to_le_bytes());
+                if unprefixed_name == "crypto.aesmc" {
+                    aes::hazmat::mix_columns(&mut state);
+                } else {
+                    aes::hazmat::inv_mix_columns(&mut state);
+                }
+
+                write_u128(this, u128::from_le_bytes(state.into()), dest)?;
+            }
+
+            name if name.starts_with("neon.") => {
+                return neon::EvalContextExt::emulate_aarch64_neon_intrinsic(
+                    this, link_name, abi, args, dest,
+                );
+            }
+
+            _ => return Ok(EmulateItemResult::NotSupported),
+        }
+        Ok(EmulateItemResult::NeedsReturn)
+    }
+}
+
+/// Reads a 128-bit vector as a little-endian `u128`.
+fn read_u128<'tcx>(
+    this: &mut crate::MiriInterpCx<'tcx>,
+    op: &OpTy<'tcx>,
+) -> InterpResult<'tcx, u128> {
+    let op = op.transmute(this.machine.layouts.u128, this)?;
+    this.read_scalar(&op)?.to_u128()
+}
+
+/// Writes a little-endian `u128` to a 128-bit vector.
+fn write_u128<'tcx>(
+    this: &mut crate::MiriInterpCx<'tcx>,
+    value: u128,
+    dest: &MPlaceTy<'tcx>,
+) -> InterpResult<'tcx, ()> {
+    let dest = dest.transmute(this.machine.layouts.u128, this)?;
+    this.write_scalar(Scalar::from_u128(value), &dest)
+}
@@ -0,0 +1,472 @@
+use rustc_apfloat::ieee::{Double, Single};
+use rustc_apfloat::Float;
+use rustc_span::Symbol;
+use rustc_target::abi::Size;
+use rustc_target::spec::abi::Abi;
+
+use crate::*;
+
+impl<'tcx> EvalContextExt<'tcx> for crate::MiriInterpCx<'tcx> {}
+pub(super) trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
+    fn emulate_aarch64_neon_intrinsic(
+        &mut self,
+        link_name: Symbol,
+        abi: Abi,
+        args: &[OpTy<'tcx>],
+        dest: &MPlaceTy<'tcx>,
+    ) -> InterpResult<'tcx, EmulateItemResult> {
+        let this = self.eval_context_mut();
+        this.expect_target_feature_for_intrinsic(link_name, "neon")?;
+        // Prefix should have already been checked.
+        let unprefixed_name = link_name.as_str().strip_prefix("llvm.aarch64.neon.").unwrap();
+        // The rest of the name lists the vector types involved, but those can be taken from
+        // the layouts of the arguments and the return place. The signedness of an integer
+        // operation is given by the `s`/`u` prefix of its name, not by the argument types.
+        let op_name = unprefixed_name.split('.').next().unwrap();
+        let signed = op_name.starts_with('s');
+
+        match op_name {
+            // Used to implement the `vqadd`, `vqsub`, `vmax`, `vmin`, `vabd`, `vhadd`, `vrhadd`
+            // and `vhsub` families of functions on integer vectors.
+            "uqadd" | "sqadd" | "uqsub" | "sqsub" | "umax" | "smax" | "umin" | "smin" | "uabd"
+            | "sabd" | "uhadd" | "shadd" | "urhadd" | "srhadd" | "uhsub" | "shsub" => {
+                let [left, right] = this.check_shim(abi, Abi::Unadjusted, link_name, args)?;
+                let which = match &op_name[1..] {
+                    "qadd" => IntBinOp::SaturatingAdd,
+                    "qsub" => IntBinOp::SaturatingSub,
+                    "max" => IntBinOp::Max,
+                    "min" => IntBinOp::Min,
+                    "abd" => IntBinOp::AbsDiff,
+                    "hadd" => IntBinOp::HalvingAdd,
+                    "rhadd" => IntBinOp::RoundingHalvingAdd,
+                    "hsub" => IntBinOp::HalvingSub,
+                    _ => unreachable!(),
+                };
+
+                let (left, left_len) = this.operand_to_simd(left)?;
+                let (right, right_len) = this.operand_to_simd(right)?;
+                let (dest, dest_len) = this.mplace_to_simd(dest)?;
+
+                assert_eq!(dest_len, left_len);
+                assert_eq!(dest_len, right_len);
+
+                let size = dest.layout.field(this, 0).size;
+                for i in 0..dest_len {
+                    let left = read_int_lane(this, &left, i, signed)?;
+                    let right = read_int_lane(this, &right, i, signed)?;
+                    write_int_lane(this, &dest, i, which.apply(left, right, size, signed))?;
+                }
+            }
+            // Used to implement the `vpadd`, `vpmax` and `vpmin` families of functions on
+            // integer vectors.
+            // The i-th output element is calculated from the elements 2*i and 2*i+1 of the
+            // concatenation of `left` and `right`.
+            "addp" | "umaxp" | "smaxp" | "uminp" | "sminp" => {
+                let [left, right] = this.check_shim(abi, Abi::Unadjusted, link_name, args)?;
+                let which = match op_name {
+                    "addp" => IntBinOp::WrappingAdd,
+                    "umaxp" | "smaxp" => IntBinOp::Max,
+                    _ => IntBinOp::Min,
+                };
+
+                let (left, left_len) = this.operand_to_simd(left)?;
+                let (right, right_len) = this.operand_to_simd(right)?;
+                let (dest, dest_len) = this.mplace_to_simd(dest)?;
+
+                assert_eq!(dest_len, left_len);
+                assert_eq!(dest_len, right_len);
+
+                let size = dest.layout.field(this, 0).size;
+                let middle = dest_len / 2;
+                for i in 0..dest_len {
+                    let (src, j) =
+                        if i < middle { (&left, i) } else { (&right, i.strict_sub(middle)) };
+                    let first = read_int_lane(this, src, j.strict_mul(2), signed)?;
+                    let second = read_int_lane(this, src, j.strict_mul(2).strict_add(1), signed)?;
+                    write_int_lane(this, &dest, i, which.apply(first, second, size, signed))?;
+                }
+            }
+            // Used to implement the `vaddv`, `vmaxv` and `vminv` families of functions on integer
+            // vectors, which reduce all elements of a vector to a single one. The result has the
+            // size of an element, but is returned extended to 32 bits for small elements.
+            "uaddv" | "saddv" | "umaxv" | "smaxv" | "uminv" | "sminv" => {
+                let [op] = this.check_shim(abi, Abi::Unadjusted, link_name, args)?;
+                let which = match &op_name[1..] {
+                    "addv" => IntBinOp::WrappingAdd,
+                    "maxv" => IntBinOp::Max,
+                    _ => IntBinOp::Min,
+                };
+
+                let (op, op_len) = this.operand_to_simd(op)?;
+                let size = op.layout.field(this, 0).size;
+
+                let mut res = read_int_lane(this, &op, 0, signed)?;
+                for i in 1..op_len {
+                    let lane = read_int_lane(this, &op, i, signed)?;
+                    res = which.apply(res, lane, size, signed);
+                }
+
+                write_int(this, dest, wrap(res, size, signed))?;
+            }
+            // Used to implement the `vaddlv` family of functions, which sum all elements of a
+            // vector without truncating the result to the size of an element.
+            "uaddlv" | "saddlv" => {
+                let [op] = this.check_shim(abi, Abi::Unadjusted, link_name, args)?;
+
+                let (op, op_len) = this.operand_to_simd(op)?;
+
+                let mut res = 0i128;
+                for i in 0..op_len {
+                    res = res.strict_add(read_int_lane(this, &op, i, signed)?);
+                }
+
+                write_int(this, dest, res)?;
+            }
+            // Used to implement the `vqmovn` and `vqmovun` families of functions.
+            // Narrows each element to half its size, saturating values that do not fit. `sqxtun`
+            // narrows signed elements to unsigned ones.
+            "sqxtn" | "uqxtn" | "sqxtun" => {
+                let [op] = this.check_shim(abi, Abi::Unadjusted, link_name, args)?;
+
+                let (op, op_len) = this.operand_to_simd(op)?;
+                let (dest, dest_len) = this.mplace_to_simd(dest)?;
+
+                assert_eq!(dest_len, op_len);
+
+                let size = dest.layout.field(this, 0).size;
+                let dest_signed = op_name == "sqxtn";
+                for i in 0..dest_len {
+                    let value = read_int_lane(this, &op, i, signed)?;
+                    write_int_lane(this, &dest, i, saturate(value, size, dest_signed))?;
+                }
+            }
+            // Used to implement the `vmull` family of functions on integer vectors.
+            // Multiplies the elements of `left` and `right`, producing elements of twice the size.
+            "umull" | "smull" => {
+                let [left, right] = this.check_shim(abi, Abi::Unadjusted, link_name, args)?;
+
+                let (left, left_len) = this.operand_to_simd(left)?;
+                let (right, right_len) = this.operand_to_simd(right)?;
+                let (dest, dest_len) = this.mplace_to_simd(dest)?;
+
+                assert_eq!(dest_len, left_len);
+                assert_eq!(dest_len, right_len);
+
+                for i in 0..dest_len {
+                    let left = read_int_lane(this, &left, i, signed)?;
+                    let right = read_int_lane(this, &right, i, signed)?;
+                    write_int_lane(this, &dest, i, left.strict_mul(right))?;
+                }
+            }
+            // Used to implement the `vqabs`, `vqneg` and (on older versions of the standard
+            // library) `vabs` families of functions on integer vectors.
+            // The `q` variants saturate instead of wrapping for the minimum value.
+            "sqabs" | "sqneg" | "abs" => {
+                let [op] = this.check_shim(abi, Abi::Unadjusted, link_name, args)?;
+
+                let (op, op_len) = this.operand_to_simd(op)?;
+                let (dest, dest_len) = this.mplace_to_simd(dest)?;
+
+                assert_eq!(dest_len, op_len);
+
+                let size = dest.layout.field(this, 0).size;
+                for i in 0..dest_len {
+                    let value = read_int_lane(this, &op, i, /* signed */ true)?;
+                    let res = match op_name {
+                        "sqabs" => saturate(value.abs(), size, true),
+                        "sqneg" => saturate(value.strict_neg(), size, true),
+                        _ => wrap(value.abs(), size, true),
+                    };
+                    write_int_lane(this, &dest, i, res)?;
+                }
+            }
+            // Used to implement the `vrbit` family of functions.
+            // Reverses the order of the bits in each byte.
+            "rbit" => {
+                let [op] = this.check_shim(abi, Abi::Unadjusted, link_name, args)?;
+
+                let (op, op_len) = this.operand_to_simd(op)?;
+                let (dest, dest_len) = this.mplace_to_simd(dest)?;
+
+                assert_eq!(dest_len, op_len);
+
+                for i in 0..dest_len {
+                    let byte = this.read_scalar(&this.project_index(&op, i)?)?.to_u8()?;
+                    this.write_scalar(
+                        Scalar::from_u8(byte.reverse_bits()),
+                        &this.project_index(&dest, i)?,
+                    )?;
+                }
+            }
+            // Used to implement the `vqtbl{1,2,3,4}`, `vqtbx{1,2,3,4}`, `vtbl{1,2,3,4}` and
+            // `vtbx{1,2,3,4}` functions.
+            // Each byte of the last argument indexes into the concatenation of the 16-byte
+            // tables. Indices that are out of range produce 0 for `tbl`, and leave the byte of
+            // the first argument unchanged for `tbx`.
+            "tbl1" | "tbl2" | "tbl3" | "tbl4" | "tbx1" | "tbx2" | "tbx3" | "tbx4" => {
+                this.check_abi_and_shim_symbol_clash(abi, Abi::Unadjusted, link_name)?;
+                let extended = op_name.starts_with("tbx");
+                let num_tables: usize = op_name[3..].parse().unwrap();
+                let num_args = num_tables.strict_add(usize::from(extended)).strict_add(1);
+                if args.len() != num_args {
+                    throw_ub_format!(
+                        "incorrect number of arguments: got {}, expected {num_args}",
+                        args.len()
+                    );
+                }
+                let (indices, args) = args.split_last().unwrap();
+                let (fallback, tables) =
+                    if extended { (args.first(), &args[1..]) } else { (None, args) };
+
+                let mut table = Vec::new();
+                for op in tables {
+                    let (op, op_len) = this.operand_to_simd(op)?;
+                    for i in 0..op_len {
+                        table.push(this.read_scalar(&this.project_index(&op, i)?)?.to_u8()?);
+                    }
+                }
+                let fallback = fallback.map(|op| this.operand_to_simd(op)).transpose()?;
+                let (indices, indices_len) = this.operand_to_simd(indices)?;
+                let (dest, dest_len) = this.mplace_to_simd(dest)?;
+
+                assert_eq!(dest_len, indices_len);
+
+                for i in 0..dest_len {
+                    let index = this.read_scalar(&this.project_index(&indices, i)?)?.to_u8()?;
+                    let res = match (table.get(usize::from(index)), &fallback) {
+                        (Some(&byte), _) => byte,
+                        (None, Some((fallback, _))) =>
+                            this.read_scalar(&this.project_index(fallback, i)?)?.to_u8()?,
+                        (None, None) => 0,
+                    };
+                    this.write_scalar(Scalar::from_u8(res), &this.project_index(&dest, i)?)?;
+                }
+            }
+            // Used to implement the `vmax`, `vmin`, `vmaxnm`, `vminnm` and `vabd` families of
+            // functions on floating-point vectors.
+            "fmax" | "fmin" | "fmaxnm" | "fminnm" | "fabd" => {
+                let [left, right] = this.check_shim(abi, Abi::Unadjusted, link_name, args)?;
+                let which = FloatBinOp::from_name(op_name);
+
+                let (left, left_len) = this.operand_to_simd(left)?;
+                let (right, right_len) = this.operand_to_simd(right)?;
+                let (dest, dest_len) = this.mplace_to_simd(dest)?;
+
+                assert_eq!(dest_len, left_len);
+                assert_eq!(dest_len, right_len);
+
+                for i in 0..dest_len {
+                    let left = this.read_scalar(&this.project_index(&left, i)?)?;
+                    let right = this.read_scalar(&this.project_index(&right, i)?)?;
+                    let dest = this.project_index(&dest, i)?;
+                    let res = float_bin_op(this, which, left, right, dest.layout.size)?;
+                    this.write_scalar(res, &dest)?;
+                }
+            }
+            // Used to implement the `vpadd`, `vpmax`, `vpmin`, `vpmaxnm` and `vpminnm` families
+            // of functions on floating-point vectors. Works like the integer versions above.
+            "faddp" | "fmaxp" | "fminp" | "fmaxnmp" | "fminnmp" => {
+                let [left, right] = this.check_shim(abi, Abi::Unadjusted, link_name, args)?;
+                let which = FloatBinOp::from_name(op_name.strip_suffix('p').unwrap());
+
+                let (left, left_len) = this.operand_to_simd(left)?;
+                let (right, right_len) = this.operand_to_simd(right)?;
+                let (dest, dest_len) = this.mplace_to_simd(dest)?;
+
+                assert_eq!(dest_len, left_len);
+                assert_eq!(dest_len, right_len);
+
+                let middle = dest_len / 2;
+                for i in 0..dest_len {
+                    let (src, j) =
+                        if i < middle { (&left, i) } else { (&right, i.strict_sub(middle)) };
+                    let first = this.read_scalar(&this.project_index(src, j.strict_mul(2))?)?;
+                    let second =
+                        this.read_scalar(&this.project_index(src, j.strict_mul(2).strict_add(1))?)?;
+                    let dest = this.project_index(&dest, i)?;
+                    let res = float_bin_op(this, which, first, second, dest.layout.size)?;
+                    this.write_scalar(res, &dest)?;
+                }
+            }
+            // Used to implement the `vmaxv`, `vminv`, `vmaxnmv` and `vminnmv` families of
+            // functions on floating-point vectors.
+            "fmaxv" | "fminv" | "fmaxnmv" | "fminnmv" => {
+                let [op] = this.check_shim(abi, Abi::Unadjusted, link_name, args)?;
+                let which = FloatBinOp::from_name(op_name.strip_suffix('v').unwrap());
+
+                let (op, op_len) = this.operand_to_simd(op)?;
+
+                let mut res = this.read_scalar(&this.project_index(&op, 0)?)?;
+                for i in 1..op_len {
+                    let lane = this.read_scalar(&this.project_index(&op, i)?)?;
+                    res = float_bin_op(this, which, res, lane, dest.layout.size)?;
+                }
+
+                this.write_scalar(res, dest)?;
+            }
+            _ => return Ok(EmulateItemResult::NotSupported),
+        }
+        Ok(EmulateItemResult::NeedsReturn)
+    }
+}
+
+#[derive(Copy, Clone)]
+enum IntBinOp {
+    WrappingAdd,
+    SaturatingAdd,
+    SaturatingSub,
+    Max,
+    Min,
+    /// The absolute value of the difference, as an unsigned value.
+    AbsDiff,
+    /// `(left + right) >> 1`, without intermediate overflow.
+    HalvingAdd,
+    /// `(left + right + 1) >> 1`, without intermediate overflow.
+    RoundingHalvingAdd,
+    /// `(left - right) >> 1`, without intermediate overflow.
+    HalvingSub,
+}
+
+impl IntBinOp {
+    /// Applies the operation to two elements of the given size. The result is not wrapped to
+    /// the element size; `write_int_lane` takes care of that.
+    fn apply(self, left: i128, right: i128, size: Size, signed: bool) -> i128 {
+        // Elements are at most 64 bits wide, so none of this can overflow an `i128`.
+        match self {
+            IntBinOp::WrappingAdd => wrap(left.strict_add(right), size, signed),
+            IntBinOp::SaturatingAdd => saturate(left.strict_add(right), size, signed),
+            IntBinOp::SaturatingSub => saturate(left.strict_sub(right), size, signed),
+            IntBinOp::Max => left.max(right),
+            IntBinOp::Min => left.min(right),
+            IntBinOp::AbsDiff => left.abs_diff(right).try_into().unwrap(),
+            IntBinOp::HalvingAdd => left.strict_add(right).strict_shr(1),
+            IntBinOp::RoundingHalvingAdd => left.strict_add(right).strict_add(1).strict_shr(1),
+            IntBinOp::HalvingSub => left.strict_sub(right).strict_shr(1),
+        }
+    }
+}
+
+/// Interprets the low `size` bits of `bits` as a signed or unsigned integer.
+fn int_from_bits(bits: u128, size: Size, signed: bool) -> i128 {
+    if signed { size.sign_extend(bits) } else { i128::try_from(size.truncate(bits)).unwrap() }
+}
+
+/// Wraps `value` around to fit into an integer of the given size and signedness.
+fn wrap(value: i128, size: Size, signed: bool) -> i128 {
+    #[allow(clippy::cast_sign_loss)] // We want the two's complement representation.
+    int_from_bits(value as u128, size, signed)
+}
+
+/// Clamps `value` to the range of an integer of the given size and signedness.
+fn saturate(value: i128, size: Size, signed: bool) -> i128 {
+    if signed {
+        value.clamp(size.signed_int_min(), size.signed_int_max())
+    } else {
+        value.clamp(0, size.unsigned_int_max().try_into().unwrap())
+    }
+}
+
+/// Reads the `i`-th element of `op` as a signed or unsigned integer.
+fn read_int_lane<'tcx>(
+    this: &mut crate::MiriInterpCx<'tcx>,
+    op: &MPlaceTy<'tcx>,
+    i: u64,
+    signed: bool,
+) -> InterpResult<'tcx, i128> {
+    let lane = this.project_index(op, i)?;
+    let bits = this.read_scalar(&lane)?.to_bits(lane.layout.size)?;
+    Ok(int_from_bits(bits, lane.layout.size, signed))
+}
+
+/// Writes `value` to the `i`-th element of `dest`, wrapping it to the element size.
+fn write_int_lane<'tcx>(
+    this: &mut crate::MiriInterpCx<'tcx>,
+    dest: &MPlaceTy<'tcx>,
+    i: u64,
+    value: i128,
+) -> InterpResult<'tcx, ()> {
+    let dest = this.project_index(dest, i)?;
+    write_int(this, &dest, value)
+}
+
+/// Writes `value` to `dest`, wrapping it to its size.
+fn write_int<'tcx>(
+    this: &mut crate::MiriInterpCx<'tcx>,
+    dest: &MPlaceTy<'tcx>,
+    value: i128,
+) -> InterpResult<'tcx, ()> {
+    let size = dest.layout.size;
+    #[allow(clippy::cast_sign_loss)] // We want the two's complement representation.
+    let bits = size.truncate(value as u128);
+    this.write_scalar(Scalar::from_uint(bits, size), dest)
+}
+
+#[derive(Copy, Clone)]
+enum FloatBinOp {
+    Add,
+    /// The absolute value of the difference.
+    AbsDiff,
+    /// The maximum, which is NaN if either input is NaN, and where +0 is larger than -0.
+    Max,
+    /// The minimum, which is NaN if either input is NaN, and where -0 is smaller than +0.
+    Min,
+    /// The IEEE 754 `maxNum` operation, which ignores a quiet NaN input.
+    MaxNum,
+    /// The IEEE 754 `minNum` operation, which ignores a quiet NaN input.
+    MinNum,
+}
+
+impl FloatBinOp {
+    fn from_name(name: &str) -> Self {
+        match name {
+            "fadd" => FloatBinOp::Add,
+            "fabd" => FloatBinOp::AbsDiff,
+            "fmax" => FloatBinOp::Max,
+            "fmin" => FloatBinOp::Min,
+            "fmaxnm" => FloatBinOp::MaxNum,
+            "fminnm" => FloatBinOp::MinNum,
+            _ => unreachable!(),
+        }
+    }
+}
+
+/// Performs `which` on two floating-point elements of the given size.
+fn float_bin_op<'tcx>(
+    this: &crate::MiriInterpCx<'tcx>,
+    which: FloatBinOp,
+    left: Scalar,
+    right: Scalar,
+    size: Size,
+) -> InterpResult<'tcx, Scalar> {
+    match size.bits() {
+        32 => float_bin_op_generic::<Single>(this, which, left, right),
+        64 => float_bin_op_generic::<Double>(this, which, left, right),
+        _ => bug!("unexpected floating-point element size {size:?}"),
+    }
+}
+
+fn float_bin_op_generic<'tcx, F>(
+    this: &crate::MiriInterpCx<'tcx>,
+    which: FloatBinOp,
+    left: Scalar,
+    right: Scalar,
+) -> InterpResult<'tcx, Scalar>
+where
+    F: Float + rustc_apfloat::FloatConvert<F> + Into<Scalar>,
+{
+    let left = left.to_float::<F>()?;
+    let right = right.to_float::<F>()?;
+    let res = match which {
+        FloatBinOp::Add => (left + right).value,
+        FloatBinOp::AbsDiff => (left - right).value.abs(),
+        FloatBinOp::Max | FloatBinOp::Min if left.is_nan() || right.is_nan() => F::NAN,
+        // Both zeros compare equal, so order them by their sign.
+        FloatBinOp::Max | FloatBinOp::MaxNum if left.is_zero() && right.is_zero() =>
+            if left.is_negative() { right } else { left },
+        FloatBinOp::Min | FloatBinOp::MinNum if left.is_zero() && right.is_zero() =>
+            if left.is_negative() { left } else { right },
+        FloatBinOp::Max | FloatBinOp::MaxNum => left.max(right),
+        FloatBinOp::Min | FloatBinOp::MinNum => left.min(right),
+    };
+    Ok(this.adjust_nan(res, &[left, right]).into())
+}
@@ -1,5 +1,6 @@
 #![warn(clippy::arithmetic_side_effects)]
 
+mod aarch64;
 mod alloc;
 mod backtrace;
 #[cfg(target_os = "linux")]
@@ -17,7 +18,10 @@
 pub mod time;
 pub mod tls;
 
-pub use unix::{DirTable, FdTable};
+pub use unix::{
+    signal, DirTable, FdTable, SignalHandlerFrame, SignalState, TimerEvent, VirtualFs,
+    VirtualNetwork,
+};
 
 /// What needs to be done after emulating an item (a shim or an intrinsic) is done.
 pub enum EmulateItemResult {
@@ -307,33 +307,59 @@ fn mach_timebase_info(&mut self, info_op: &OpTy<'tcx>) -> InterpResult<'tcx, Sca
     fn nanosleep(
         &mut self,
         req_op: &OpTy<'tcx>,
-        _rem: &OpTy<'tcx>, // Signal handlers are not supported, so rem will never be written to.
-    ) -> InterpResult<'tcx, i32> {
+        rem_op: &OpTy<'tcx>,
+        dest: &MPlaceTy<'tcx>,
+    ) -> InterpResult<'tcx> {
         let this = self.eval_context_mut();
 
         this.assert_target_os_is_unix("nanosleep");
 
         let req = this.deref_pointer_as(req_op, this.libc_ty_layout("timespec"))?;
+        let rem = this.read_pointer(rem_op)?;
 
         let duration = match this.read_timespec(&req)? {
             Some(duration) => duration,
             None => {
                 let einval = this.eval_libc("EINVAL");
                 this.set_last_error(einval)?;
-                return Ok(-1);
+                return this.write_int(-1, dest);
             }
         };
 
+        let now = this.machine.clock.now().duration_since(this.machine.clock.epoch());
+        let deadline = now.saturating_add(duration);
+        this.write_null(dest)?;
         this.block_thread(
             BlockReason::Sleep,
-            Some((TimeoutClock::Monotonic, TimeoutAnchor::Relative, duration)),
+            Some((TimeoutClock::Monotonic, TimeoutAnchor::Absolute, deadline)),
             callback!(
-                @capture<'tcx> {}
-                @unblock = |_this| { panic!("sleeping thread unblocked before time is up") }
+                @capture<'tcx> {
+                    rem: Pointer,
+                    deadline: Duration,
+                    dest: MPlaceTy<'tcx>,
+                }
+                @unblock = |this| {
+                    // Only signals wake up a sleeping thread early.
+                    let interrupted = this.machine.signals.take_interrupted(this.active_thread());
+                    assert!(interrupted, "sleeping thread unblocked before time is up");
+                    if !this.ptr_is_null(rem)? {
+                        let clock = &this.machine.clock;
+                        let remaining =
+                            deadline.saturating_sub(clock.now().duration_since(clock.epoch()));
+                        let rem = this.ptr_to_mplace(rem, this.libc_ty_layout("timespec"));
+                        this.write_int_fields(
+                            &[remaining.as_secs().into(), remaining.subsec_nanos().into()],
+                            &rem,
+                        )?;
+                    }
+                    let eintr = this.eval_libc("EINTR");
+                    this.set_last_error(eintr)?;
+                    this.write_int(-1, &dest)
+                }
                 @timeout = |_this| { Ok(()) }
             ),
         );
-        Ok(0)
+        Ok(())
     }
 
     #[allow(non_snake_case)]
@@ -211,14 +211,20 @@ fn getcwd(&mut self, buf_op: &OpTy<'tcx>, size_op: &OpTy<'tcx>) -> InterpResult<
         let buf = this.read_pointer(buf_op)?;
         let size = this.read_target_usize(size_op)?;
 
-        if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
-            this.reject_in_isolation("`getcwd`", reject_with)?;
-            this.set_last_error_from_io_error(ErrorKind::PermissionDenied.into())?;
-            return Ok(Pointer::null());
-        }
+        // The program runs in the mount point of the virtual file system.
+        let cwd = if let Some(virtual_fs) = &this.machine.virtual_fs {
+            Ok(virtual_fs.mount_point().to_owned())
+        } else {
+            if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
+                this.reject_in_isolation("`getcwd`", reject_with)?;
+                this.set_last_error_from_io_error(ErrorKind::PermissionDenied.into())?;
+                return Ok(Pointer::null());
+            }
+            env::current_dir()
+        };
 
         // If we cannot get the current directory, we return null
-        match env::current_dir() {
+        match cwd {
             Ok(cwd) => {
                 if this.write_path_to_c_str(&cwd, buf, size)?.0 {
                     return Ok(buf);
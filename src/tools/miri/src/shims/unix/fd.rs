@@ -5,10 +5,13 @@
 use std::cell::{Ref, RefCell, RefMut};
 use std::collections::BTreeMap;
 use std::io::{self, ErrorKind, IsTerminal, Read, SeekFrom, Write};
+use std::mem;
 use std::rc::Rc;
+use std::time::Duration;
 
 use rustc_target::abi::Size;
 
+use crate::shims::unix::linux::epoll::EpollReadyEvents;
 use crate::shims::unix::*;
 use crate::*;
 
@@ -82,6 +85,12 @@ fn is_tty(&self, _communicate_allowed: bool) -> bool {
         // so we use a default impl here.
         false
     }
+
+    /// Returns the I/O events this file description is currently ready for, as reported by
+    /// `epoll_wait`.
+    fn get_epoll_ready_events<'tcx>(&self) -> InterpResult<'tcx, EpollReadyEvents> {
+        throw_unsup_format!("{}: epoll does not support this file description", self.name());
+    }
 }
 
 impl dyn FileDescription {
@@ -214,6 +223,13 @@ pub fn close<'ctx>(self, communicate_allowed: bool) -> InterpResult<'ctx, io::Re
 #[derive(Debug)]
 pub struct FdTable {
     pub fds: BTreeMap<i32, FileDescriptor>,
+    /// Threads blocked until some file description changes its readiness,
+    /// e.g. in `accept`, `recv` or `epoll_wait`, and whether their call is restarted after a
+    /// signal handler installed with `SA_RESTART`.
+    io_waiters: Vec<(ThreadId, bool)>,
+    /// Counts the changes of readiness of all file descriptions, so that edge-triggered
+    /// `epoll` interests can tell whether anything happened since they were last reported.
+    pub io_events: u64,
 }
 
 impl VisitProvenance for FdTable {
@@ -224,7 +240,7 @@ fn visit_provenance(&self, _visit: &mut VisitWith<'_>) {
 
 impl FdTable {
     fn new() -> Self {
-        FdTable { fds: BTreeMap::new() }
+        FdTable { fds: BTreeMap::new(), io_waiters: Vec::new(), io_events: 0 }
     }
     pub(crate) fn init(mute_stdout_stderr: bool) -> FdTable {
         let mut fds = FdTable::new();
@@ -296,8 +312,88 @@ pub fn is_fd(&self, fd: i32) -> bool {
     }
 }
 
+/// Wraps the callback of a thread blocked on I/O, to take the thread off the list of waiters
+/// when it times out, and to fail with `EINTR` when a signal interrupts it.
+struct IoWaiter<'tcx, C> {
+    callback: C,
+    dest: MPlaceTy<'tcx>,
+}
+
+impl<C: VisitProvenance> VisitProvenance for IoWaiter<'_, C> {
+    fn visit_provenance(&self, visit: &mut VisitWith<'_>) {
+        self.callback.visit_provenance(visit);
+        self.dest.visit_provenance(visit);
+    }
+}
+
+impl<'tcx, C: UnblockCallback<'tcx>> UnblockCallback<'tcx> for IoWaiter<'tcx, C> {
+    fn unblock(self: Box<Self>, ecx: &mut MiriInterpCx<'tcx>) -> InterpResult<'tcx> {
+        if ecx.machine.signals.take_interrupted(ecx.active_thread()) {
+            let eintr = ecx.eval_libc("EINTR");
+            ecx.set_last_error(eintr)?;
+            return ecx.write_int(-1, &self.dest);
+        }
+        Box::new(self.callback).unblock(ecx)
+    }
+
+    fn timeout(self: Box<Self>, ecx: &mut MiriInterpCx<'tcx>) -> InterpResult<'tcx> {
+        let thread = ecx.active_thread();
+        ecx.machine.fds.io_waiters.retain(|&(waiter, _)| waiter != thread);
+        Box::new(self.callback).timeout(ecx)
+    }
+}
+
 impl<'tcx> EvalContextExt<'tcx> for crate::MiriInterpCx<'tcx> {}
 pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
+    /// Blocks the active thread until the readiness of some file description changes, or
+    /// until `deadline` (measured on the monotonic clock) has passed. The callback is expected
+    /// to retry the operation, and to block again if it still cannot make progress.
+    ///
+    /// If a signal interrupts the call, -1 is written to `dest` and `errno` is set to `EINTR`.
+    /// `restartable` calls are not interrupted by handlers installed with `SA_RESTART`.
+    fn block_on_io(
+        &mut self,
+        deadline: Option<Duration>,
+        restartable: bool,
+        dest: MPlaceTy<'tcx>,
+        callback: impl UnblockCallback<'tcx> + 'tcx,
+    ) {
+        let this = self.eval_context_mut();
+        let thread = this.active_thread();
+        this.machine.fds.io_waiters.push((thread, restartable));
+        this.block_thread(
+            BlockReason::Io,
+            deadline.map(|deadline| (TimeoutClock::Monotonic, TimeoutAnchor::Absolute, deadline)),
+            IoWaiter { callback, dest },
+        );
+    }
+
+    /// Takes `thread` off the list of threads blocked on I/O because a signal interrupts it.
+    /// Returns `false` if the call of the thread gets restarted instead.
+    fn interrupt_io_wait(&mut self, thread: ThreadId, restart: bool) -> bool {
+        let this = self.eval_context_mut();
+        let waiters = &mut this.machine.fds.io_waiters;
+        let Some(index) = waiters.iter().position(|&(waiter, _)| waiter == thread) else {
+            return false;
+        };
+        if restart && waiters[index].1 {
+            return false;
+        }
+        waiters.remove(index);
+        true
+    }
+
+    /// Signals that the readiness of some file description may have changed, and wakes up
+    /// all threads blocked on I/O so that they can retry their operation.
+    fn wake_io_waiters(&mut self) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+        this.machine.fds.io_events = this.machine.fds.io_events.wrapping_add(1);
+        for (thread, _) in mem::take(&mut this.machine.fds.io_waiters) {
+            this.unblock_thread(thread, BlockReason::Io)?;
+        }
+        Ok(())
+    }
+
     fn dup(&mut self, old_fd: i32) -> InterpResult<'tcx, i32> {
         let this = self.eval_context_mut();
 
@@ -319,6 +415,7 @@ fn dup2(&mut self, old_fd: i32, new_fd: i32) -> InterpResult<'tcx, i32> {
             if let Some(file_descriptor) = this.machine.fds.fds.insert(new_fd, dup_fd) {
                 // Ignore close error (not interpreter's) according to dup2() doc.
                 file_descriptor.close(this.machine.communicate())?.ok();
+                this.wake_io_waiters()?;
             }
         }
         Ok(new_fd)
@@ -389,6 +486,8 @@ fn close(&mut self, fd_op: &OpTy<'tcx>) -> InterpResult<'tcx, Scalar> {
             return Ok(Scalar::from_i32(this.fd_not_found()?));
         };
         let result = file_descriptor.close(this.machine.communicate())?;
+        // Closing may have hung up the peer of a socket, or made an `epoll` interest stale.
+        this.wake_io_waiters()?;
         // return `0` if close is successful
         let result = result.map(|()| 0i32);
         Ok(Scalar::from_i32(this.try_unwrap_io_result(result)?))
@@ -467,6 +566,7 @@ fn read(
                     buf,
                     bytes[..usize::try_from(read_bytes).unwrap()].iter().copied(),
                 )?;
+                this.wake_io_waiters()?;
                 Ok(read_bytes)
             }
             Err(e) => {
@@ -517,6 +617,9 @@ fn write(
         drop(fd);
 
         let result = result?.map(|c| i64::try_from(c).unwrap());
+        if result.is_ok() {
+            this.wake_io_waiters()?;
+        }
         this.try_unwrap_io_result(result)
     }
 }
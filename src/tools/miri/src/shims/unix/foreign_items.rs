@@ -289,6 +289,102 @@ fn emulate_foreign_item_inner(
                 let result = this.socketpair(domain, type_, protocol, sv)?;
                 this.write_scalar(result, dest)?;
             }
+            "socket" => {
+                let [domain, type_, protocol] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.socket(domain, type_, protocol)?;
+                this.write_scalar(result, dest)?;
+            }
+            "bind" => {
+                let [socket, address, address_len] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.bind(socket, address, address_len)?;
+                this.write_scalar(result, dest)?;
+            }
+            "listen" => {
+                let [socket, backlog] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.listen(socket, backlog)?;
+                this.write_scalar(result, dest)?;
+            }
+            "accept" => {
+                let [socket, address, address_len] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                this.accept(socket, address, address_len, None, dest)?;
+            }
+            "accept4" => {
+                // `accept4` does not exist on macOS.
+                let target_os = &*this.tcx.sess.target.os;
+                if !matches!(target_os, "linux" | "android" | "freebsd") {
+                    throw_unsup_format!("`accept4` is not supported on {target_os}");
+                }
+                let [socket, address, address_len, flags] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                this.accept(socket, address, address_len, Some(flags), dest)?;
+            }
+            "connect" => {
+                let [socket, address, address_len] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                this.connect(socket, address, address_len, dest)?;
+            }
+            "send" => {
+                let [socket, buffer, length, flags] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                this.send(socket, buffer, length, flags, None, dest)?;
+            }
+            "sendto" => {
+                let [socket, buffer, length, flags, address, address_len] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                this.send(socket, buffer, length, flags, Some((address, address_len)), dest)?;
+            }
+            "recv" => {
+                let [socket, buffer, length, flags] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                this.recv(socket, buffer, length, flags, None, dest)?;
+            }
+            "recvfrom" => {
+                let [socket, buffer, length, flags, address, address_len] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                this.recv(socket, buffer, length, flags, Some((address, address_len)), dest)?;
+            }
+            "getsockname" => {
+                let [socket, address, address_len] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.getsockname(socket, address, address_len)?;
+                this.write_scalar(result, dest)?;
+            }
+            "getpeername" => {
+                let [socket, address, address_len] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.getpeername(socket, address, address_len)?;
+                this.write_scalar(result, dest)?;
+            }
+            "setsockopt" => {
+                let [socket, level, option_name, option_value, option_len] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result =
+                    this.setsockopt(socket, level, option_name, option_value, option_len)?;
+                this.write_scalar(result, dest)?;
+            }
+            "getsockopt" => {
+                let [socket, level, option_name, option_value, option_len] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result =
+                    this.getsockopt(socket, level, option_name, option_value, option_len)?;
+                this.write_scalar(result, dest)?;
+            }
+            "shutdown" => {
+                let [socket, how] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.shutdown(socket, how)?;
+                this.write_scalar(result, dest)?;
+            }
+            "ioctl" => {
+                // `ioctl` is variadic. The argument count is checked based on the request
+                // in `this.ioctl()`, so we do not use `check_shim` here.
+                this.check_abi_and_shim_symbol_clash(abi, Abi::C { unwind: false }, link_name)?;
+                let result = this.ioctl(args)?;
+                this.write_scalar(Scalar::from_i32(result), dest)?;
+            }
 
             // Time
             "gettimeofday" => {
@@ -610,8 +706,7 @@ fn emulate_foreign_item_inner(
             }
             "nanosleep" => {
                 let [req, rem] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
-                let result = this.nanosleep(req, rem)?;
-                this.write_scalar(Scalar::from_i32(result), dest)?;
+                this.nanosleep(req, rem, dest)?;
             }
             "sched_getaffinity" => {
                 // Currently this function does not exist on all Unixes, e.g. on macOS.
@@ -707,6 +802,77 @@ fn emulate_foreign_item_inner(
                 }
             }
 
+            // Signals
+            "sigemptyset" => {
+                let [set] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.sigemptyset(set)?;
+                this.write_scalar(Scalar::from_i32(result), dest)?;
+            }
+            "sigfillset" => {
+                let [set] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.sigfillset(set)?;
+                this.write_scalar(Scalar::from_i32(result), dest)?;
+            }
+            "sigaddset" | "sigdelset" | "sigismember" => {
+                let [set, signal] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.sigset_member_op(link_name.as_str(), set, signal)?;
+                this.write_scalar(Scalar::from_i32(result), dest)?;
+            }
+            "sigaction" => {
+                let [signal, act, oldact] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.sigaction(signal, act, oldact)?;
+                this.write_scalar(Scalar::from_i32(result), dest)?;
+            }
+            "signal" => {
+                let [signal, handler] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.signal(signal, handler)?;
+                this.write_scalar(Scalar::from_target_usize(result, this), dest)?;
+            }
+            "sigprocmask" => {
+                let [how, set, oldset] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.sigprocmask(how, set, oldset)?;
+                this.write_scalar(Scalar::from_i32(result), dest)?;
+            }
+            "pthread_sigmask" => {
+                let [how, set, oldset] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.pthread_sigmask(how, set, oldset)?;
+                this.write_scalar(result, dest)?;
+            }
+            "sigpending" => {
+                let [set] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.sigpending(set)?;
+                this.write_scalar(Scalar::from_i32(result), dest)?;
+            }
+            "raise" => {
+                let [signal] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.raise(signal)?;
+                this.write_scalar(Scalar::from_i32(result), dest)?;
+            }
+            "kill" => {
+                let [pid, signal] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.kill(pid, signal)?;
+                this.write_scalar(Scalar::from_i32(result), dest)?;
+            }
+            "pthread_kill" => {
+                let [thread, signal] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.pthread_kill(thread, signal)?;
+                this.write_scalar(result, dest)?;
+            }
+            "alarm" => {
+                let [seconds] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.alarm(seconds)?;
+                this.write_scalar(Scalar::from_u32(result), dest)?;
+            }
+            "pause" => {
+                let [] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                this.pause(dest)?;
+            }
+
             // Miscellaneous
             "isatty" => {
                 let [fd] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
@@ -857,14 +1023,12 @@ fn emulate_foreign_item_inner(
                 this.write_null(dest)?;
             }
 
-            | "signal"
-            | "sigaltstack"
+            "sigaltstack"
             if this.frame_in_std() => {
                 let [_, _] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
                 this.write_null(dest)?;
             }
-            | "sigaction"
-            | "mprotect"
+            "mprotect"
             if this.frame_in_std() => {
                 let [_, _, _] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
                 this.write_null(dest)?;
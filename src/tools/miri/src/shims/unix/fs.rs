@@ -1,6 +1,7 @@
 //! File and file system access
 
 use std::borrow::Cow;
+use std::ffi::OsString;
 use std::fs::{
     read_dir, remove_dir, remove_file, rename, DirBuilder, File, FileType, OpenOptions, ReadDir,
 };
@@ -12,6 +13,9 @@
 use rustc_target::abi::Size;
 
 use crate::shims::os_str::bytes_to_os_str;
+use crate::shims::unix::virtual_fs::{
+    VirtualDirEntry, VirtualFileHandle, VirtualMetadata, VirtualOpenOptions, VirtualPath,
+};
 use crate::shims::unix::*;
 use crate::*;
 use shims::time::system_time_to_duration;
@@ -134,6 +138,28 @@ fn is_tty(&self, communicate_allowed: bool) -> bool {
 
 impl<'tcx> EvalContextExtPrivate<'tcx> for crate::MiriInterpCx<'tcx> {}
 trait EvalContextExtPrivate<'tcx>: crate::MiriInterpCxExt<'tcx> {
+    /// Returns the location of `path` in the virtual file system, if `-Zmiri-virtual-fs` is set
+    /// and `path` is below its mount point.
+    fn virtual_path(&self, path: &Path) -> Option<VirtualPath> {
+        self.eval_context_ref().machine.virtual_fs.as_ref()?.resolve(path)
+    }
+
+    fn virtual_fs(&mut self) -> &mut VirtualFs {
+        self.eval_context_mut().machine.virtual_fs.as_mut().unwrap()
+    }
+
+    /// Returns how to reject an operation on an open file or directory, if isolation is enabled.
+    /// With a virtual file system, all open files and directories are virtual, so nothing needs
+    /// to be rejected.
+    fn isolated_file_op(&self) -> Option<RejectOpWith> {
+        let this = self.eval_context_ref();
+        match this.machine.isolated_op {
+            IsolatedOp::Reject(reject_with) if this.machine.virtual_fs.is_none() =>
+                Some(reject_with),
+            _ => None,
+        }
+    }
+
     fn macos_stat_write_buf(
         &mut self,
         metadata: FileMetadata,
@@ -217,24 +243,78 @@ fn file_type_to_d_type(
                 },
         }
     }
+
+    /// Returns the next entry of the directory stream `dirp`, or `None` at its end.
+    fn next_dir_entry(
+        &mut self,
+        dirp: u64,
+        op_name: &str,
+    ) -> InterpResult<'tcx, Option<io::Result<DirEntry>>> {
+        let this = self.eval_context_mut();
+
+        let open_dir = this.machine.dirs.streams.get_mut(&dirp).ok_or_else(|| {
+            err_unsup_format!("the DIR pointer passed to {op_name} did not come from opendir")
+        })?;
+        match &mut open_dir.read_dir {
+            DirStream::Host(read_dir) =>
+                match read_dir.next() {
+                    Some(Ok(dir_entry)) => {
+                        // If the host is a Unix system, fill in the inode number with its real value.
+                        // If not, use 0 as a fallback value.
+                        #[cfg(unix)]
+                        let ino = std::os::unix::fs::DirEntryExt::ino(&dir_entry);
+                        #[cfg(not(unix))]
+                        let ino = 0u64;
+
+                        let d_type = this.file_type_to_d_type(dir_entry.file_type())?;
+                        Ok(Some(Ok(DirEntry { name: dir_entry.file_name(), ino, d_type })))
+                    }
+                    Some(Err(e)) => Ok(Some(Err(e))),
+                    None => Ok(None),
+                },
+            DirStream::Virtual(entries) => {
+                let Some(entry) = entries.next() else {
+                    return Ok(None);
+                };
+                let d_type = this.eval_libc(if entry.is_dir { "DT_DIR" } else { "DT_REG" });
+                let d_type = d_type.to_u8()?.into();
+                Ok(Some(Ok(DirEntry { name: entry.name, ino: entry.ino, d_type })))
+            }
+        }
+    }
+}
+
+/// The entries of an open directory.
+#[derive(Debug)]
+enum DirStream {
+    /// The directory reader on the host.
+    Host(ReadDir),
+    /// The entries of a directory of the virtual file system, as of `opendir`.
+    Virtual(std::vec::IntoIter<VirtualDirEntry>),
 }
 
 /// An open directory, tracked by DirHandler.
 #[derive(Debug)]
 struct OpenDir {
-    /// The directory reader on the host.
-    read_dir: ReadDir,
+    read_dir: DirStream,
     /// The most recent entry returned by readdir().
     /// Will be freed by the next call.
     entry: Option<Pointer>,
 }
 
 impl OpenDir {
-    fn new(read_dir: ReadDir) -> Self {
+    fn new(read_dir: DirStream) -> Self {
         Self { read_dir, entry: None }
     }
 }
 
+/// A directory entry returned by readdir(), from the host or the virtual file system.
+struct DirEntry {
+    name: OsString,
+    ino: u64,
+    d_type: i32,
+}
+
 /// The table of open directories.
 /// Curiously, Unix/POSIX does not unify this into the "file descriptor" concept... everything
 /// is a file, except a directory is not?
@@ -256,7 +336,7 @@ pub struct DirTable {
 
 impl DirTable {
     #[allow(clippy::arithmetic_side_effects)]
-    fn insert_new(&mut self, read_dir: ReadDir) -> u64 {
+    fn insert_new(&mut self, read_dir: DirStream) -> u64 {
         let id = self.next_id;
         self.next_id += 1;
         self.streams.try_insert(id, OpenDir::new(read_dir)).unwrap();
@@ -317,6 +397,7 @@ fn open(&mut self, args: &[OpTy<'tcx>]) -> InterpResult<'tcx, i32> {
         let flag = this.read_scalar(&args[1])?.to_i32()?;
 
         let mut options = OpenOptions::new();
+        let mut virtual_options = VirtualOpenOptions::default();
 
         let o_rdonly = this.eval_libc_i32("O_RDONLY");
         let o_wronly = this.eval_libc_i32("O_WRONLY");
@@ -335,10 +416,14 @@ fn open(&mut self, args: &[OpTy<'tcx>]) -> InterpResult<'tcx, i32> {
         if access_mode == o_rdonly {
             writable = false;
             options.read(true);
+            virtual_options.read = true;
         } else if access_mode == o_wronly {
             options.write(true);
+            virtual_options.write = true;
         } else if access_mode == o_rdwr {
             options.read(true).write(true);
+            virtual_options.read = true;
+            virtual_options.write = true;
         } else {
             throw_unsup_format!("unsupported access mode {:#x}", access_mode);
         }
@@ -350,11 +435,13 @@ fn open(&mut self, args: &[OpTy<'tcx>]) -> InterpResult<'tcx, i32> {
         let o_append = this.eval_libc_i32("O_APPEND");
         if flag & o_append == o_append {
             options.append(true);
+            virtual_options.append = true;
             mirror |= o_append;
         }
         let o_trunc = this.eval_libc_i32("O_TRUNC");
         if flag & o_trunc == o_trunc {
             options.truncate(true);
+            virtual_options.truncate = true;
             mirror |= o_trunc;
         }
         let o_creat = this.eval_libc_i32("O_CREAT");
@@ -394,8 +481,10 @@ fn open(&mut self, args: &[OpTy<'tcx>]) -> InterpResult<'tcx, i32> {
             if flag & o_excl == o_excl {
                 mirror |= o_excl;
                 options.create_new(true);
+                virtual_options.create_new = true;
             } else {
                 options.create(true);
+                virtual_options.create = true;
             }
         }
         let o_cloexec = this.eval_libc_i32("O_CLOEXEC");
@@ -443,6 +532,15 @@ fn open(&mut self, args: &[OpTy<'tcx>]) -> InterpResult<'tcx, i32> {
             throw_unsup_format!("unsupported flags {:#x}", flag & !mirror);
         }
 
+        // The virtual file system has no symbolic links, so `O_NOFOLLOW` has no effect on it.
+        if let Some(path) = this.virtual_path(&path) {
+            let fd = this
+                .virtual_fs()
+                .open(&path, &virtual_options)
+                .map(|handle| this.machine.fds.insert_fd(handle));
+            return this.try_unwrap_io_result(fd);
+        }
+
         // Reject if isolation is enabled.
         if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
             this.reject_in_isolation("`open`", reject_with)?;
@@ -500,6 +598,11 @@ fn unlink(&mut self, path_op: &OpTy<'tcx>) -> InterpResult<'tcx, i32> {
 
         let path = this.read_path_from_c_str(this.read_pointer(path_op)?)?;
 
+        if let Some(path) = this.virtual_path(&path) {
+            let result = this.virtual_fs().unlink(&path).map(|()| 0);
+            return this.try_unwrap_io_result(result);
+        }
+
         // Reject if isolation is enabled.
         if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
             this.reject_in_isolation("`unlink`", reject_with)?;
@@ -531,6 +634,10 @@ fn create_link(src: &Path, dst: &Path) -> std::io::Result<()> {
         let target = this.read_path_from_c_str(this.read_pointer(target_op)?)?;
         let linkpath = this.read_path_from_c_str(this.read_pointer(linkpath_op)?)?;
 
+        if this.virtual_path(&linkpath).is_some() {
+            throw_unsup_format!("`symlink`: the virtual file system has no symbolic links");
+        }
+
         // Reject if isolation is enabled.
         if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
             this.reject_in_isolation("`symlink`", reject_with)?;
@@ -556,6 +663,15 @@ fn macos_fbsd_stat(
         let path_scalar = this.read_pointer(path_op)?;
         let path = this.read_path_from_c_str(path_scalar)?.into_owned();
 
+        if let Some(path) = this.virtual_path(&path) {
+            let metadata = this.virtual_fs().metadata(&path);
+            let metadata = match FileMetadata::from_virtual(this, metadata)? {
+                Some(metadata) => metadata,
+                None => return Ok(Scalar::from_i32(-1)), // `FileMetadata` has set errno
+            };
+            return Ok(Scalar::from_i32(this.macos_stat_write_buf(metadata, buf_op)?));
+        }
+
         // Reject if isolation is enabled.
         if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
             this.reject_in_isolation("`stat`", reject_with)?;
@@ -588,6 +704,15 @@ fn macos_fbsd_lstat(
         let path_scalar = this.read_pointer(path_op)?;
         let path = this.read_path_from_c_str(path_scalar)?.into_owned();
 
+        if let Some(path) = this.virtual_path(&path) {
+            let metadata = this.virtual_fs().metadata(&path);
+            let metadata = match FileMetadata::from_virtual(this, metadata)? {
+                Some(metadata) => metadata,
+                None => return Ok(Scalar::from_i32(-1)), // `FileMetadata` has set errno
+            };
+            return Ok(Scalar::from_i32(this.macos_stat_write_buf(metadata, buf_op)?));
+        }
+
         // Reject if isolation is enabled.
         if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
             this.reject_in_isolation("`lstat`", reject_with)?;
@@ -618,7 +743,7 @@ fn macos_fbsd_fstat(
         let fd = this.read_scalar(fd_op)?.to_i32()?;
 
         // Reject if isolation is enabled.
-        if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
+        if let Some(reject_with) = this.isolated_file_op() {
             this.reject_in_isolation("`fstat`", reject_with)?;
             // Set error code as "EBADF" (bad fd)
             return Ok(Scalar::from_i32(this.fd_not_found()?));
@@ -680,8 +805,20 @@ fn linux_statx(
             )
         }
 
-        // Reject if isolation is enabled.
-        if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
+        let from_fd = path.as_os_str().is_empty() && empty_path_flag;
+        let virtual_path = if from_fd { None } else { this.virtual_path(&path) };
+
+        // Reject if isolation is enabled, unless the file is in the virtual file system.
+        let isolated_op = if from_fd {
+            this.isolated_file_op()
+        } else if virtual_path.is_none()
+            && let IsolatedOp::Reject(reject_with) = this.machine.isolated_op
+        {
+            Some(reject_with)
+        } else {
+            None
+        };
+        if let Some(reject_with) = isolated_op {
             this.reject_in_isolation("`statx`", reject_with)?;
             let ecode = if path.is_absolute() || dirfd == this.eval_libc_i32("AT_FDCWD") {
                 // since `path` is provided, either absolute or
@@ -710,8 +847,11 @@ fn linux_statx(
 
         // If the path is empty, and the AT_EMPTY_PATH flag is set, we query the open file
         // represented by dirfd, whether it's a directory or otherwise.
-        let metadata = if path.as_os_str().is_empty() && empty_path_flag {
+        let metadata = if from_fd {
             FileMetadata::from_fd(this, dirfd)?
+        } else if let Some(path) = virtual_path {
+            let metadata = this.virtual_fs().metadata(&path);
+            FileMetadata::from_virtual(this, metadata)?
         } else {
             FileMetadata::from_path(this, &path, follow_symlink)?
         };
@@ -832,6 +972,19 @@ fn rename(
         let oldpath = this.read_path_from_c_str(oldpath_ptr)?;
         let newpath = this.read_path_from_c_str(newpath_ptr)?;
 
+        match (this.virtual_path(&oldpath), this.virtual_path(&newpath)) {
+            (Some(oldpath), Some(newpath)) => {
+                let result = this.virtual_fs().rename(&oldpath, &newpath).map(|()| 0);
+                return this.try_unwrap_io_result(result);
+            }
+            (Some(_), None) | (None, Some(_)) => {
+                // Files cannot be moved between the virtual and the host file system.
+                this.set_last_error_from_io_error(ErrorKind::CrossesDevices.into())?;
+                return Ok(-1);
+            }
+            (None, None) => {}
+        }
+
         // Reject if isolation is enabled.
         if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
             this.reject_in_isolation("`rename`", reject_with)?;
@@ -856,6 +1009,12 @@ fn mkdir(&mut self, path_op: &OpTy<'tcx>, mode_op: &OpTy<'tcx>) -> InterpResult<
 
         let path = this.read_path_from_c_str(this.read_pointer(path_op)?)?;
 
+        // The virtual file system does not track permissions, so the mode is ignored.
+        if let Some(path) = this.virtual_path(&path) {
+            let result = this.virtual_fs().mkdir(&path).map(|()| 0);
+            return this.try_unwrap_io_result(result);
+        }
+
         // Reject if isolation is enabled.
         if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
             this.reject_in_isolation("`mkdir`", reject_with)?;
@@ -884,6 +1043,11 @@ fn rmdir(&mut self, path_op: &OpTy<'tcx>) -> InterpResult<'tcx, i32> {
 
         let path = this.read_path_from_c_str(this.read_pointer(path_op)?)?;
 
+        if let Some(path) = this.virtual_path(&path) {
+            let result = this.virtual_fs().rmdir(&path).map(|()| 0);
+            return this.try_unwrap_io_result(result);
+        }
+
         // Reject if isolation is enabled.
         if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
             this.reject_in_isolation("`rmdir`", reject_with)?;
@@ -901,6 +1065,19 @@ fn opendir(&mut self, name_op: &OpTy<'tcx>) -> InterpResult<'tcx, Scalar> {
 
         let name = this.read_path_from_c_str(this.read_pointer(name_op)?)?;
 
+        if let Some(path) = this.virtual_path(&name) {
+            return match this.virtual_fs().read_dir(&path) {
+                Ok(entries) => {
+                    let id = this.machine.dirs.insert_new(DirStream::Virtual(entries.into_iter()));
+                    Ok(Scalar::from_target_usize(id, this))
+                }
+                Err(e) => {
+                    this.set_last_error_from_io_error(e)?;
+                    Ok(Scalar::null_ptr(this))
+                }
+            };
+        }
+
         // Reject if isolation is enabled.
         if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
             this.reject_in_isolation("`opendir`", reject_with)?;
@@ -913,7 +1090,7 @@ fn opendir(&mut self, name_op: &OpTy<'tcx>) -> InterpResult<'tcx, Scalar> {
 
         match result {
             Ok(dir_iter) => {
-                let id = this.machine.dirs.insert_new(dir_iter);
+                let id = this.machine.dirs.insert_new(DirStream::Host(dir_iter));
 
                 // The libc API for opendir says that this method returns a pointer to an opaque
                 // structure, but we are returning an ID number. Thus, pass it as a scalar of
@@ -935,18 +1112,14 @@ fn linux_readdir64(&mut self, dirp_op: &OpTy<'tcx>) -> InterpResult<'tcx, Scalar
         let dirp = this.read_target_usize(dirp_op)?;
 
         // Reject if isolation is enabled.
-        if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
+        if let Some(reject_with) = this.isolated_file_op() {
             this.reject_in_isolation("`readdir`", reject_with)?;
             let eacc = this.eval_libc("EBADF");
             this.set_last_error(eacc)?;
             return Ok(Scalar::null_ptr(this));
         }
 
-        let open_dir = this.machine.dirs.streams.get_mut(&dirp).ok_or_else(|| {
-            err_unsup_format!("the DIR pointer passed to readdir64 did not come from opendir")
-        })?;
-
-        let entry = match open_dir.read_dir.next() {
+        let entry = match this.next_dir_entry(dirp, "readdir64")? {
             Some(Ok(dir_entry)) => {
                 // Write the directory entry into a newly allocated buffer.
                 // The name is written with write_bytes, while the rest of the
@@ -961,7 +1134,7 @@ fn linux_readdir64(&mut self, dirp_op: &OpTy<'tcx>) -> InterpResult<'tcx, Scalar
                 //     pub d_name: [c_char; 256],
                 // }
 
-                let mut name = dir_entry.file_name(); // not a Path as there are no separators!
+                let mut name = dir_entry.name; // not a Path as there are no separators!
                 name.push("\0"); // Add a NUL terminator
                 let name_bytes = name.as_encoded_bytes();
                 let name_len = u64::try_from(name_bytes.len()).unwrap();
@@ -977,21 +1150,12 @@ fn linux_readdir64(&mut self, dirp_op: &OpTy<'tcx>) -> InterpResult<'tcx, Scalar
                 )?;
                 let entry: Pointer = entry.into();
 
-                // If the host is a Unix system, fill in the inode number with its real value.
-                // If not, use 0 as a fallback value.
-                #[cfg(unix)]
-                let ino = std::os::unix::fs::DirEntryExt::ino(&dir_entry);
-                #[cfg(not(unix))]
-                let ino = 0u64;
-
-                let file_type = this.file_type_to_d_type(dir_entry.file_type())?;
-
                 this.write_int_fields_named(
                     &[
-                        ("d_ino", ino.into()),
+                        ("d_ino", dir_entry.ino.into()),
                         ("d_off", 0),
                         ("d_reclen", size.into()),
-                        ("d_type", file_type.into()),
+                        ("d_type", dir_entry.d_type.into()),
                     ],
                     &this.ptr_to_mplace(entry, dirent64_layout),
                 )?;
@@ -1035,16 +1199,13 @@ fn macos_fbsd_readdir_r(
         let dirp = this.read_target_usize(dirp_op)?;
 
         // Reject if isolation is enabled.
-        if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
+        if let Some(reject_with) = this.isolated_file_op() {
             this.reject_in_isolation("`readdir_r`", reject_with)?;
             // Set error code as "EBADF" (bad fd)
             return Ok(Scalar::from_i32(this.fd_not_found()?));
         }
 
-        let open_dir = this.machine.dirs.streams.get_mut(&dirp).ok_or_else(|| {
-            err_unsup_format!("the DIR pointer passed to readdir_r did not come from opendir")
-        })?;
-        Ok(Scalar::from_i32(match open_dir.read_dir.next() {
+        Ok(Scalar::from_i32(match this.next_dir_entry(dirp, "readdir_r")? {
             Some(Ok(dir_entry)) => {
                 // Write into entry, write pointer to result, return 0 on success.
                 // The name is written with write_os_str_to_c_str, while the rest of the
@@ -1063,7 +1224,7 @@ fn macos_fbsd_readdir_r(
                 let entry_place = this.deref_pointer_as(entry_op, this.libc_ty_layout("dirent"))?;
                 let name_place = this.project_field_named(&entry_place, "d_name")?;
 
-                let file_name = dir_entry.file_name(); // not a Path as there are no separators!
+                let file_name = dir_entry.name; // not a Path as there are no separators!
                 let (name_fits, file_name_buf_len) = this.write_os_str_to_c_str(
                     &file_name,
                     name_place.ptr(),
@@ -1076,21 +1237,14 @@ fn macos_fbsd_readdir_r(
                     );
                 }
 
-                // If the host is a Unix system, fill in the inode number with its real value.
-                // If not, use 0 as a fallback value.
-                #[cfg(unix)]
-                let ino = std::os::unix::fs::DirEntryExt::ino(&dir_entry);
-                #[cfg(not(unix))]
-                let ino = 0u64;
-
-                let file_type = this.file_type_to_d_type(dir_entry.file_type())?;
+                let ino = dir_entry.ino;
 
                 // Common fields.
                 this.write_int_fields_named(
                     &[
                         ("d_reclen", 0),
                         ("d_namlen", file_name_len.into()),
-                        ("d_type", file_type.into()),
+                        ("d_type", dir_entry.d_type.into()),
                     ],
                     &entry_place,
                 )?;
@@ -1149,7 +1303,7 @@ fn closedir(&mut self, dirp_op: &OpTy<'tcx>) -> InterpResult<'tcx, i32> {
         let dirp = this.read_target_usize(dirp_op)?;
 
         // Reject if isolation is enabled.
-        if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
+        if let Some(reject_with) = this.isolated_file_op() {
             this.reject_in_isolation("`closedir`", reject_with)?;
             // Set error code as "EBADF" (bad fd)
             return this.fd_not_found();
@@ -1170,16 +1324,25 @@ fn ftruncate64(&mut self, fd: i32, length: i128) -> InterpResult<'tcx, Scalar> {
         let this = self.eval_context_mut();
 
         // Reject if isolation is enabled.
-        if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
+        if let Some(reject_with) = this.isolated_file_op() {
             this.reject_in_isolation("`ftruncate64`", reject_with)?;
             // Set error code as "EBADF" (bad fd)
             return Ok(Scalar::from_i32(this.fd_not_found()?));
         }
 
-        let Some(file_descriptor) = this.machine.fds.get(fd) else {
+        let Some(mut file_descriptor) = this.machine.fds.get_mut(fd) else {
             return Ok(Scalar::from_i32(this.fd_not_found()?));
         };
 
+        if let Some(handle) = file_descriptor.downcast_mut::<VirtualFileHandle>() {
+            let result = match u64::try_from(length) {
+                Ok(length) if handle.is_writable() => handle.set_len(length).map(|()| 0i32),
+                _ => Err(ErrorKind::InvalidInput.into()),
+            };
+            drop(file_descriptor);
+            return Ok(Scalar::from_i32(this.try_unwrap_io_result(result)?));
+        }
+
         // FIXME: Support ftruncate64 for all FDs
         let FileHandle { file, writable } =
             file_descriptor.downcast_ref::<FileHandle>().ok_or_else(|| {
@@ -1218,7 +1381,7 @@ fn fsync(&mut self, fd_op: &OpTy<'tcx>) -> InterpResult<'tcx, i32> {
         let fd = this.read_scalar(fd_op)?.to_i32()?;
 
         // Reject if isolation is enabled.
-        if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
+        if let Some(reject_with) = this.isolated_file_op() {
             this.reject_in_isolation("`fsync`", reject_with)?;
             // Set error code as "EBADF" (bad fd)
             return this.fd_not_found();
@@ -1232,6 +1395,10 @@ fn ffullsync_fd(&mut self, fd: i32) -> InterpResult<'tcx, i32> {
         let Some(file_descriptor) = this.machine.fds.get(fd) else {
             return Ok(this.fd_not_found()?);
         };
+        // Virtual files are never written to disk.
+        if file_descriptor.downcast_ref::<VirtualFileHandle>().is_some() {
+            return Ok(0);
+        }
         // Only regular files support synchronization.
         let FileHandle { file, writable } =
             file_descriptor.downcast_ref::<FileHandle>().ok_or_else(|| {
@@ -1248,7 +1415,7 @@ fn fdatasync(&mut self, fd_op: &OpTy<'tcx>) -> InterpResult<'tcx, i32> {
         let fd = this.read_scalar(fd_op)?.to_i32()?;
 
         // Reject if isolation is enabled.
-        if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
+        if let Some(reject_with) = this.isolated_file_op() {
             this.reject_in_isolation("`fdatasync`", reject_with)?;
             // Set error code as "EBADF" (bad fd)
             return this.fd_not_found();
@@ -1257,6 +1424,9 @@ fn fdatasync(&mut self, fd_op: &OpTy<'tcx>) -> InterpResult<'tcx, i32> {
         let Some(file_descriptor) = this.machine.fds.get(fd) else {
             return Ok(this.fd_not_found()?);
         };
+        if file_descriptor.downcast_ref::<VirtualFileHandle>().is_some() {
+            return Ok(0);
+        }
         // Only regular files support synchronization.
         let FileHandle { file, writable } =
             file_descriptor.downcast_ref::<FileHandle>().ok_or_else(|| {
@@ -1296,7 +1466,7 @@ fn sync_file_range(
         }
 
         // Reject if isolation is enabled.
-        if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
+        if let Some(reject_with) = this.isolated_file_op() {
             this.reject_in_isolation("`sync_file_range`", reject_with)?;
             // Set error code as "EBADF" (bad fd)
             return Ok(Scalar::from_i32(this.fd_not_found()?));
@@ -1305,6 +1475,9 @@ fn sync_file_range(
         let Some(file_descriptor) = this.machine.fds.get(fd) else {
             return Ok(Scalar::from_i32(this.fd_not_found()?));
         };
+        if file_descriptor.downcast_ref::<VirtualFileHandle>().is_some() {
+            return Ok(Scalar::from_i32(0));
+        }
         // Only regular files support synchronization.
         let FileHandle { file, writable } =
             file_descriptor.downcast_ref::<FileHandle>().ok_or_else(|| {
@@ -1329,6 +1502,15 @@ fn readlink(
         let buf = this.read_pointer(buf_op)?;
         let bufsize = this.read_target_usize(bufsize_op)?;
 
+        if let Some(path) = this.virtual_path(&pathname) {
+            // There are no symbolic links in the virtual file system.
+            let result = this
+                .virtual_fs()
+                .metadata(&path)
+                .and_then(|_| Err(io::Error::from(ErrorKind::InvalidInput)));
+            return this.try_unwrap_io_result(result);
+        }
+
         // Reject if isolation is enabled.
         if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
             this.reject_in_isolation("`readlink`", reject_with)?;
@@ -1392,15 +1574,20 @@ fn realpath(
         let pathname = this.read_path_from_c_str(this.read_pointer(path_op)?)?;
         let processed_ptr = this.read_pointer(processed_path_op)?;
 
-        // Reject if isolation is enabled.
-        if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
-            this.reject_in_isolation("`realpath`", reject_with)?;
-            let eacc = this.eval_libc("EACCES");
-            this.set_last_error(eacc)?;
-            return Ok(Scalar::from_target_usize(0, this));
-        }
+        let result = if let Some(path) = this.virtual_path(&pathname) {
+            let virtual_fs = this.virtual_fs();
+            virtual_fs.metadata(&path).map(|_| virtual_fs.absolute_path(&path))
+        } else {
+            // Reject if isolation is enabled.
+            if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
+                this.reject_in_isolation("`realpath`", reject_with)?;
+                let eacc = this.eval_libc("EACCES");
+                this.set_last_error(eacc)?;
+                return Ok(Scalar::from_target_usize(0, this));
+            }
 
-        let result = std::fs::canonicalize(pathname);
+            std::fs::canonicalize(pathname)
+        };
         match result {
             Ok(resolved) => {
                 let path_max = this
@@ -1613,6 +1800,12 @@ fn from_fd<'tcx>(
             return ecx.fd_not_found().map(|_: i32| None);
         };
 
+        if let Some(handle) = file_descriptor.downcast_ref::<VirtualFileHandle>() {
+            let metadata = handle.metadata();
+            drop(file_descriptor);
+            return FileMetadata::from_virtual(ecx, Ok(metadata));
+        }
+
         let file = &file_descriptor
             .downcast_ref::<FileHandle>()
             .ok_or_else(|| {
@@ -1627,6 +1820,28 @@ fn from_fd<'tcx>(
         FileMetadata::from_meta(ecx, metadata)
     }
 
+    /// Timestamps are not available for files of the virtual file system.
+    fn from_virtual<'tcx>(
+        ecx: &mut MiriInterpCx<'tcx>,
+        metadata: io::Result<VirtualMetadata>,
+    ) -> InterpResult<'tcx, Option<FileMetadata>> {
+        let metadata = match metadata {
+            Ok(metadata) => metadata,
+            Err(e) => {
+                ecx.set_last_error_from_io_error(e)?;
+                return Ok(None);
+            }
+        };
+        let mode = ecx.eval_libc(if metadata.is_dir { "S_IFDIR" } else { "S_IFREG" });
+        Ok(Some(FileMetadata {
+            mode,
+            size: metadata.size,
+            created: None,
+            accessed: None,
+            modified: None,
+        }))
+    }
+
     fn from_meta<'tcx>(
         ecx: &mut MiriInterpCx<'tcx>,
         metadata: Result<std::fs::Metadata, std::io::Error>,
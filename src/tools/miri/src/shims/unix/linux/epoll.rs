@@ -1,6 +1,8 @@
+use std::collections::BTreeMap;
 use std::io;
+use std::time::Duration;
 
-use rustc_data_structures::fx::FxHashMap;
+use rustc_target::abi::Size;
 
 use crate::shims::unix::*;
 use crate::*;
@@ -9,11 +11,11 @@
 #[derive(Clone, Debug, Default)]
 struct Epoll {
     /// The file descriptors we are watching, and what we are watching for.
-    file_descriptors: FxHashMap<i32, EpollEvent>,
+    /// This is ordered so that `epoll_wait` reports ready file descriptors deterministically.
+    file_descriptors: BTreeMap<i32, EpollEvent>,
 }
 
 /// Epoll Events associate events with data.
-/// These fields are currently unused by miri.
 /// This matches the `epoll_event` struct defined
 /// by the epoll_ctl man page. For more information
 /// see the man page:
@@ -21,12 +23,48 @@ struct Epoll {
 /// <https://man7.org/linux/man-pages/man2/epoll_ctl.2.html>
 #[derive(Clone, Debug)]
 struct EpollEvent {
-    #[allow(dead_code)]
     events: u32,
     /// `Scalar` is used to represent the
     /// `epoll_data` type union.
-    #[allow(dead_code)]
     data: Scalar,
+    /// The value of `FdTable::io_events` when this interest was last reported as ready.
+    /// This is how we implement `EPOLLET` and `EPOLLONESHOT`.
+    last_reported: Option<u64>,
+}
+
+/// The I/O events a file description is ready for.
+#[derive(Debug, Default)]
+pub struct EpollReadyEvents {
+    /// The associated file is available for read(2) operations.
+    pub epollin: bool,
+    /// The associated file is available for write(2) operations.
+    pub epollout: bool,
+    /// Stream socket peer closed connection, or shut down writing
+    /// half of connection.
+    pub epollrdhup: bool,
+    /// Hang up happened on the associated file descriptor.
+    pub epollhup: bool,
+    /// Error condition happened on the associated file descriptor.
+    pub epollerr: bool,
+}
+
+impl EpollReadyEvents {
+    /// Returns these events as a bitmask of the `EPOLL*` flags.
+    pub fn get_event_bitmask<'tcx>(&self, ecx: &MiriInterpCx<'tcx>) -> u32 {
+        let mut bitmask = 0;
+        for (ready, name) in [
+            (self.epollin, "EPOLLIN"),
+            (self.epollout, "EPOLLOUT"),
+            (self.epollrdhup, "EPOLLRDHUP"),
+            (self.epollhup, "EPOLLHUP"),
+            (self.epollerr, "EPOLLERR"),
+        ] {
+            if ready {
+                bitmask |= ecx.eval_libc_u32(name);
+            }
+        }
+        bitmask
+    }
 }
 
 impl FileDescription for Epoll {
@@ -106,7 +144,7 @@ fn epoll_ctl(
             let events = this.read_scalar(&events)?.to_u32()?;
             let data = this.project_field(&event, 1)?;
             let data = this.read_scalar(&data)?;
-            let event = EpollEvent { events, data };
+            let event = EpollEvent { events, data, last_reported: None };
 
             let Some(mut epfd) = this.machine.fds.get_mut(epfd) else {
                 return Ok(Scalar::from_i32(this.fd_not_found()?));
@@ -169,22 +207,129 @@ fn epoll_wait(
         events: &OpTy<'tcx>,
         maxevents: &OpTy<'tcx>,
         timeout: &OpTy<'tcx>,
-    ) -> InterpResult<'tcx, Scalar> {
+        dest: &MPlaceTy<'tcx>,
+    ) -> InterpResult<'tcx> {
         let this = self.eval_context_mut();
 
         let epfd = this.read_scalar(epfd)?.to_i32()?;
-        let _events = this.read_scalar(events)?.to_pointer(this)?;
-        let _maxevents = this.read_scalar(maxevents)?.to_i32()?;
-        let _timeout = this.read_scalar(timeout)?.to_i32()?;
+        let events = this.read_pointer(events)?;
+        let maxevents = this.read_scalar(maxevents)?.to_i32()?;
+        let timeout = this.read_scalar(timeout)?.to_i32()?;
+
+        if maxevents <= 0 {
+            let einval = this.eval_libc("EINVAL");
+            this.set_last_error(einval)?;
+            this.write_int(-1, dest)?;
+            return Ok(());
+        }
+
+        // A negative timeout means to wait forever.
+        let deadline = u64::try_from(timeout).ok().map(|timeout| {
+            let now = this.machine.clock.now().duration_since(this.machine.clock.epoch());
+            now.saturating_add(Duration::from_millis(timeout))
+        });
+
+        this.epoll_wait_until(epfd, events, maxevents, deadline, dest.clone())
+    }
+
+    /// Reports the ready events of the interest list of `epfd` into `events`, blocking until
+    /// at least one is ready or until `deadline` has passed.
+    fn epoll_wait_until(
+        &mut self,
+        epfd: i32,
+        events: Pointer,
+        maxevents: i32,
+        deadline: Option<Duration>,
+        dest: MPlaceTy<'tcx>,
+    ) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
 
-        let Some(mut epfd) = this.machine.fds.get_mut(epfd) else {
-            return Ok(Scalar::from_i32(this.fd_not_found()?));
+        let Some(epoll_fd) = this.machine.fds.dup(epfd) else {
+            let result: i32 = this.fd_not_found()?;
+            this.write_int(result, &dest)?;
+            return Ok(());
         };
-        let _epfd = epfd
-            .downcast_mut::<Epoll>()
-            .ok_or_else(|| err_unsup_format!("non-epoll FD passed to `epoll_wait`"))?;
+        let interests = epoll_fd
+            .borrow()
+            .downcast_ref::<Epoll>()
+            .ok_or_else(|| err_unsup_format!("non-epoll FD passed to `epoll_wait`"))?
+            .file_descriptors
+            .clone();
+
+        let epollet = this.eval_libc_u32("EPOLLET");
+        let epolloneshot = this.eval_libc_u32("EPOLLONESHOT");
+        // These are always reported, whether they were asked for or not.
+        let always = this.eval_libc_u32("EPOLLHUP") | this.eval_libc_u32("EPOLLERR");
+        let io_events = this.machine.fds.io_events;
+        let epoll_event_layout = this.libc_ty_layout("epoll_event");
+        let maxevents = usize::try_from(maxevents).unwrap();
+
+        let mut ready = Vec::new();
+        for (fd, interest) in interests {
+            if ready.len() >= maxevents {
+                break;
+            }
+            // Interests in file descriptors that have been closed since are never ready.
+            let Some(file) = this.machine.fds.get(fd) else { continue };
+            let flags = file.get_epoll_ready_events()?.get_event_bitmask(this)
+                & (interest.events | always);
+            drop(file);
+            if flags == 0 {
+                continue;
+            }
+            if interest.events & epolloneshot != 0 && interest.last_reported.is_some() {
+                // Disabled until it is rearmed with `EPOLL_CTL_MOD`.
+                continue;
+            }
+            if interest.events & epollet != 0 && interest.last_reported == Some(io_events) {
+                // Nothing happened since this was last reported.
+                continue;
+            }
+            ready.push((fd, flags, interest.data));
+        }
 
-        // FIXME return number of events ready when scheme for marking events ready exists
-        throw_unsup_format!("returning ready events from epoll_wait is not yet implemented");
+        let now = this.machine.clock.now().duration_since(this.machine.clock.epoch());
+        if ready.is_empty() && deadline.map_or(true, |deadline| now < deadline) {
+            this.block_on_io(
+                deadline,
+                false,
+                dest.clone(),
+                callback!(
+                    @capture<'tcx> {
+                        epfd: i32,
+                        events: Pointer,
+                        maxevents: i32,
+                        deadline: Option<Duration>,
+                        dest: MPlaceTy<'tcx>,
+                    }
+                    @unblock = |this| {
+                        this.epoll_wait_until(epfd, events, maxevents, deadline, dest)
+                    }
+                    @timeout = |this| {
+                        this.epoll_wait_until(epfd, events, maxevents, deadline, dest)
+                    }
+                ),
+            );
+            return Ok(());
+        }
+
+        let mut epoll = epoll_fd.borrow_mut();
+        let epoll = epoll.downcast_mut::<Epoll>().unwrap();
+        for (fd, _, _) in &ready {
+            if let Some(interest) = epoll.file_descriptors.get_mut(fd) {
+                interest.last_reported = Some(io_events);
+            }
+        }
+        drop(epoll);
+
+        let events = this.ptr_to_mplace(events, epoll_event_layout);
+        for (i, (_fd, flags, data)) in ready.iter().enumerate() {
+            let offset = epoll_event_layout.size.bytes().strict_mul(u64::try_from(i).unwrap());
+            let event = events.offset(Size::from_bytes(offset), epoll_event_layout, this)?;
+            this.write_int(*flags, &this.project_field(&event, 0)?)?;
+            this.write_scalar(*data, &this.project_field(&event, 1)?)?;
+        }
+        this.write_int(i32::try_from(ready.len()).unwrap(), &dest)?;
+        Ok(())
     }
 }
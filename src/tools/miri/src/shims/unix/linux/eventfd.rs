@@ -5,6 +5,7 @@
 
 use rustc_target::abi::Endian;
 
+use crate::shims::unix::linux::epoll::EpollReadyEvents;
 use crate::shims::unix::*;
 use crate::{concurrency::VClock, *};
 
@@ -42,6 +43,14 @@ fn close<'tcx>(
         Ok(Ok(()))
     }
 
+    fn get_epoll_ready_events<'tcx>(&self) -> InterpResult<'tcx, EpollReadyEvents> {
+        Ok(EpollReadyEvents {
+            epollin: self.counter != 0,
+            epollout: self.counter < MAX_COUNTER,
+            ..EpollReadyEvents::default()
+        })
+    }
+
     /// Read the counter in the buffer and return the counter if succeeded.
     fn read<'tcx>(
         &mut self,
@@ -0,0 +1,339 @@
+//! Linux `timerfd` implementation.
+use std::cell::RefCell;
+use std::io;
+use std::io::{Error, ErrorKind};
+use std::mem;
+use std::rc::{Rc, Weak};
+use std::time::{Duration, SystemTime};
+
+use rustc_target::abi::Endian;
+
+use crate::shims::time::system_time_to_duration;
+use crate::shims::unix::linux::epoll::EpollReadyEvents;
+use crate::shims::unix::*;
+use crate::*;
+
+// Reads return the number of expirations as a u64.
+const U64_ARRAY_SIZE: usize = mem::size_of::<u64>();
+
+/// The state of a timer, shared between its file description and the entry in the timers of the
+/// machine, which only holds a weak reference so that closing the timer disarms it.
+#[derive(Debug)]
+pub struct TimerFdState {
+    timer: TimerId,
+    /// Whether the timer was created for `CLOCK_REALTIME`, which only matters for absolute
+    /// expiration times. All other clocks behave like the monotone clock.
+    realtime: bool,
+    /// When the timer expires next, as a duration since the epoch of the monotone clock.
+    next_expiration: Option<Duration>,
+    /// The period of the timer, or zero if it expires only once.
+    interval: Duration,
+    /// The number of expirations since the last `read` or `timerfd_settime`.
+    expirations: u64,
+}
+
+/// A file descriptor created by `timerfd_create`.
+/// <https://man7.org/linux/man-pages/man2/timerfd_create.2.html>
+#[derive(Debug)]
+struct TimerFd {
+    state: Rc<RefCell<TimerFdState>>,
+    is_nonblock: bool,
+}
+
+impl FileDescription for TimerFd {
+    fn name(&self) -> &'static str {
+        "timerfd"
+    }
+
+    fn close<'tcx>(
+        self: Box<Self>,
+        _communicate_allowed: bool,
+    ) -> InterpResult<'tcx, io::Result<()>> {
+        Ok(Ok(()))
+    }
+
+    fn get_epoll_ready_events<'tcx>(&self) -> InterpResult<'tcx, EpollReadyEvents> {
+        Ok(EpollReadyEvents {
+            epollin: self.state.borrow().expirations != 0,
+            ..EpollReadyEvents::default()
+        })
+    }
+
+    /// Returns the number of expirations since the last read and resets it.
+    fn read<'tcx>(
+        &mut self,
+        _communicate_allowed: bool,
+        bytes: &mut [u8],
+        ecx: &mut MiriInterpCx<'tcx>,
+    ) -> InterpResult<'tcx, io::Result<usize>> {
+        let Some(bytes) = bytes.first_chunk_mut::<U64_ARRAY_SIZE>() else {
+            return Ok(Err(Error::from(ErrorKind::InvalidInput)));
+        };
+        let mut state = self.state.borrow_mut();
+        if state.expirations == 0 {
+            if self.is_nonblock {
+                return Ok(Err(Error::from(ErrorKind::WouldBlock)));
+            } else {
+                //FIXME: blocking is not supported
+                throw_unsup_format!("timerfd: blocking is unsupported");
+            }
+        }
+        *bytes = match ecx.tcx.sess.target.endian {
+            Endian::Little => state.expirations.to_le_bytes(),
+            Endian::Big => state.expirations.to_be_bytes(),
+        };
+        state.expirations = 0;
+        Ok(Ok(U64_ARRAY_SIZE))
+    }
+}
+
+impl<'tcx> EvalContextExtPriv<'tcx> for crate::MiriInterpCx<'tcx> {}
+trait EvalContextExtPriv<'tcx>: crate::MiriInterpCxExt<'tcx> {
+    /// Returns the state of the timer behind `fd`, or the value to return from the shim if `fd`
+    /// does not refer to a timer.
+    fn timerfd_state(
+        &mut self,
+        fd: i32,
+    ) -> InterpResult<'tcx, Result<Rc<RefCell<TimerFdState>>, Scalar>> {
+        let this = self.eval_context_mut();
+        let state = this
+            .machine
+            .fds
+            .get(fd)
+            .map(|fd| fd.downcast_ref::<TimerFd>().map(|timerfd| timerfd.state.clone()));
+        match state {
+            Some(Some(state)) => Ok(Ok(state)),
+            Some(None) => {
+                let einval = this.eval_libc("EINVAL");
+                this.set_last_error(einval)?;
+                Ok(Err(Scalar::from_i32(-1)))
+            }
+            None => Ok(Err(Scalar::from_i32(this.fd_not_found()?))),
+        }
+    }
+
+    /// Writes the current setting of the timer to an `itimerspec`.
+    fn write_itimerspec(
+        &mut self,
+        state: &TimerFdState,
+        itimerspec: &MPlaceTy<'tcx>,
+    ) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+        let now = this.machine.clock.now().duration_since(this.machine.clock.epoch());
+        // A disarmed timer reports zero as its remaining time.
+        let remaining = state
+            .next_expiration
+            .map_or(Duration::ZERO, |expiration| expiration.saturating_sub(now));
+        for (name, duration) in [("it_interval", state.interval), ("it_value", remaining)] {
+            let field = this.project_field_named(itimerspec, name)?;
+            this.write_int_fields(
+                &[duration.as_secs().into(), duration.subsec_nanos().into()],
+                &field,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl<'tcx> EvalContextExt<'tcx> for crate::MiriInterpCx<'tcx> {}
+pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
+    /// Creates a timer that counts on the given clock. Reading from the returned file descriptor
+    /// yields the number of times the timer expired, and `epoll` reports it as readable when
+    /// that number is not 0.
+    ///
+    /// <https://man7.org/linux/man-pages/man2/timerfd_create.2.html>
+    fn timerfd_create(
+        &mut self,
+        clockid: &OpTy<'tcx>,
+        flags: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        // timerfd is Linux specific.
+        this.assert_target_os("linux", "timerfd_create");
+
+        let clockid = this.read_scalar(clockid)?.to_i32()?;
+        let mut flags = this.read_scalar(flags)?.to_i32()?;
+
+        let realtime = clockid == this.eval_libc_i32("CLOCK_REALTIME");
+        if !realtime
+            && clockid != this.eval_libc_i32("CLOCK_MONOTONIC")
+            && clockid != this.eval_libc_i32("CLOCK_BOOTTIME")
+        {
+            let einval = this.eval_libc("EINVAL");
+            this.set_last_error(einval)?;
+            return Ok(Scalar::from_i32(-1));
+        }
+
+        let tfd_cloexec = this.eval_libc_i32("TFD_CLOEXEC");
+        let tfd_nonblock = this.eval_libc_i32("TFD_NONBLOCK");
+        let mut is_nonblock = false;
+        if flags & tfd_cloexec == tfd_cloexec {
+            // cloexec is ignored because Miri does not support exec.
+            flags &= !tfd_cloexec;
+        }
+        if flags & tfd_nonblock == tfd_nonblock {
+            flags &= !tfd_nonblock;
+            is_nonblock = true;
+        }
+        if flags != 0 {
+            let einval = this.eval_libc("EINVAL");
+            this.set_last_error(einval)?;
+            return Ok(Scalar::from_i32(-1));
+        }
+
+        let timer = this.machine.timers.new_timer();
+        let state = TimerFdState {
+            timer,
+            realtime,
+            next_expiration: None,
+            interval: Duration::ZERO,
+            expirations: 0,
+        };
+        let fd = this
+            .machine
+            .fds
+            .insert_fd(TimerFd { state: Rc::new(RefCell::new(state)), is_nonblock });
+        Ok(Scalar::from_i32(fd))
+    }
+
+    /// Arms or disarms the timer, and returns its previous setting in `old_value`.
+    ///
+    /// <https://man7.org/linux/man-pages/man2/timerfd_settime.2.html>
+    fn timerfd_settime(
+        &mut self,
+        fd: &OpTy<'tcx>,
+        flags: &OpTy<'tcx>,
+        new_value: &OpTy<'tcx>,
+        old_value: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let fd = this.read_scalar(fd)?.to_i32()?;
+        let flags = this.read_scalar(flags)?.to_i32()?;
+        let new_value = this.deref_pointer_as(new_value, this.libc_ty_layout("itimerspec"))?;
+        let old_value = this.read_pointer(old_value)?;
+
+        let tfd_timer_abstime = this.eval_libc_i32("TFD_TIMER_ABSTIME");
+        let tfd_timer_cancel_on_set = this.eval_libc_i32("TFD_TIMER_CANCEL_ON_SET");
+        if flags & tfd_timer_cancel_on_set != 0 {
+            throw_unsup_format!("timerfd_settime: TFD_TIMER_CANCEL_ON_SET is unsupported");
+        }
+        let interval = this.project_field_named(&new_value, "it_interval")?;
+        let interval = this.read_timespec(&interval)?;
+        let value = this.project_field_named(&new_value, "it_value")?;
+        let value = this.read_timespec(&value)?;
+        let (Some(interval), Some(value)) = (interval, value) else {
+            let einval = this.eval_libc("EINVAL");
+            this.set_last_error(einval)?;
+            return Ok(Scalar::from_i32(-1));
+        };
+        if flags & !tfd_timer_abstime != 0 {
+            let einval = this.eval_libc("EINVAL");
+            this.set_last_error(einval)?;
+            return Ok(Scalar::from_i32(-1));
+        }
+
+        let state = match this.timerfd_state(fd)? {
+            Ok(state) => state,
+            Err(result) => return Ok(result),
+        };
+        if !this.ptr_is_null(old_value)? {
+            let old_value = this.ptr_to_mplace(old_value, this.libc_ty_layout("itimerspec"));
+            this.write_itimerspec(&state.borrow(), &old_value)?;
+        }
+
+        let now = this.machine.clock.now().duration_since(this.machine.clock.epoch());
+        let next_expiration = if value.is_zero() {
+            // A zero expiration time disarms the timer.
+            None
+        } else if flags & tfd_timer_abstime != 0 {
+            if state.borrow().realtime {
+                this.check_no_isolation("`timerfd_settime` with an absolute `REALTIME` time")?;
+                let system_now = system_time_to_duration(&SystemTime::now())?;
+                Some(now.saturating_add(value.saturating_sub(system_now)))
+            } else {
+                Some(value)
+            }
+        } else {
+            Some(now.saturating_add(value))
+        };
+
+        let mut state_ref = state.borrow_mut();
+        state_ref.next_expiration = next_expiration;
+        state_ref.interval = interval;
+        state_ref.expirations = 0;
+        match next_expiration {
+            Some(expiration) => {
+                let event = TimerEvent::TimerFd(Rc::downgrade(&state));
+                this.machine.timers.arm(state_ref.timer, expiration, event);
+            }
+            None => {
+                this.machine.timers.disarm(state_ref.timer);
+            }
+        }
+        Ok(Scalar::from_i32(0))
+    }
+
+    /// Returns the time until the timer expires next, and its interval.
+    ///
+    /// <https://man7.org/linux/man-pages/man2/timerfd_gettime.2.html>
+    fn timerfd_gettime(
+        &mut self,
+        fd: &OpTy<'tcx>,
+        curr_value: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let fd = this.read_scalar(fd)?.to_i32()?;
+        let curr_value = this.deref_pointer_as(curr_value, this.libc_ty_layout("itimerspec"))?;
+
+        let state = match this.timerfd_state(fd)? {
+            Ok(state) => state,
+            Err(result) => return Ok(result),
+        };
+        this.write_itimerspec(&state.borrow(), &curr_value)?;
+        Ok(Scalar::from_i32(0))
+    }
+
+    /// Counts the expirations of a timer whose expiration time has passed, and re-arms it if it
+    /// is periodic.
+    fn timerfd_expired(
+        &mut self,
+        timer: TimerId,
+        state: Weak<RefCell<TimerFdState>>,
+    ) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+        let Some(state_rc) = state.upgrade() else {
+            // The timer was closed.
+            return Ok(());
+        };
+        let mut state = state_rc.borrow_mut();
+        let Some(expiration) = state.next_expiration else {
+            return Ok(());
+        };
+
+        let now = this.machine.clock.now().duration_since(this.machine.clock.epoch());
+        if state.interval.is_zero() {
+            state.expirations = state.expirations.saturating_add(1);
+            state.next_expiration = None;
+        } else {
+            // We may be late, e.g. because the scheduler only checks timers between steps. Count
+            // every period that has passed since then, like Linux does.
+            let interval = state.interval.as_nanos();
+            let missed = now.saturating_sub(expiration).as_nanos().strict_div(interval);
+            let expirations = u64::try_from(missed.saturating_add(1)).unwrap_or(u64::MAX);
+            state.expirations = state.expirations.saturating_add(expirations);
+            let next = expiration
+                .as_nanos()
+                .saturating_add(interval.saturating_mul(missed.saturating_add(1)));
+            let next = Duration::from_nanos(u64::try_from(next).unwrap_or(u64::MAX));
+            state.next_expiration = Some(next);
+            this.machine.timers.arm(timer, next, TimerEvent::TimerFd(Rc::downgrade(&state_rc)));
+        }
+        drop(state);
+
+        // The timer is readable now.
+        this.wake_io_waiters()
+    }
+}
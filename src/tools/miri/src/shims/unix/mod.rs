@@ -1,12 +1,15 @@
 pub mod foreign_items;
+pub mod signal;
 
 mod env;
 mod fd;
 mod fs;
 mod mem;
+mod net;
 mod socket;
 mod sync;
 mod thread;
+mod virtual_fs;
 
 mod android;
 mod freebsd;
@@ -17,11 +20,15 @@
 pub use env::UnixEnvVars;
 pub use fd::{FdTable, FileDescription};
 pub use fs::DirTable;
+pub use net::VirtualNetwork;
+pub use signal::{SignalHandlerFrame, SignalState, TimerEvent};
+pub use virtual_fs::VirtualFs;
 // All the Unix-specific extension traits
 pub use env::EvalContextExt as _;
 pub use fd::EvalContextExt as _;
 pub use fs::EvalContextExt as _;
 pub use mem::EvalContextExt as _;
+pub use net::EvalContextExt as _;
 pub use socket::EvalContextExt as _;
 pub use sync::EvalContextExt as _;
 pub use thread::EvalContextExt as _;
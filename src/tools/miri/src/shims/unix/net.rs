@@ -0,0 +1,1637 @@
+//! Emulation of `AF_INET` and `AF_INET6` sockets on a virtual loopback network.
+//!
+//! Sockets can only talk to other sockets of the interpreted program: they can only be bound to
+//! loopback (or unspecified) addresses, and connecting or sending to any other address fails with
+//! `ENETUNREACH`. A TCP connection is a pair of buffers, like a `socketpair`, and a UDP socket
+//! has a queue of datagrams.
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::io;
+use std::iter;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::ops::RangeInclusive;
+use std::rc::{Rc, Weak};
+use std::time::Duration;
+
+use rustc_data_structures::fx::FxHashMap;
+use rustc_target::abi::Size;
+
+use crate::shims::unix::linux::epoll::EpollReadyEvents;
+use crate::shims::unix::socket::SocketPair;
+use crate::shims::unix::*;
+use crate::{concurrency::VClock, *};
+
+/// The maximum number of bytes buffered in each direction of a TCP connection.
+/// Like for `socketpair`, this number is arbitrary.
+const MAX_STREAM_BUFFER_CAPACITY: usize = 212992;
+
+/// The largest payload of a UDP datagram over IPv4.
+const MAX_DATAGRAM_SIZE: usize = 65507;
+
+/// The number of datagrams queued on a UDP socket before further ones are dropped.
+const MAX_QUEUED_DATAGRAMS: usize = 256;
+
+/// The largest backlog of a listening socket, like the default `SOMAXCONN` on Linux.
+const MAX_BACKLOG: usize = 4096;
+
+/// The ports handed out when binding to port 0, like the default `ip_local_port_range` on Linux.
+const EPHEMERAL_PORTS: RangeInclusive<u16> = 32768..=60999;
+
+/// The outcome of a socket operation. Errors are the names of `errno` values.
+type SockResult<T> = Result<T, &'static str>;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Protocol {
+    Tcp,
+    Udp,
+}
+
+/// An address in use by some sockets. It is released when the last of them is closed.
+#[derive(Debug)]
+struct Binding {
+    protocol: Protocol,
+    addr: SocketAddr,
+}
+
+/// Whether something sent to `a` could reach a socket bound to `b`, or the other way round.
+/// IPv6 sockets are always `IPV6_V6ONLY`, so IPv4 and IPv6 addresses never overlap.
+fn addrs_overlap(a: SocketAddr, b: SocketAddr) -> bool {
+    a.port() == b.port()
+        && a.is_ipv4() == b.is_ipv4()
+        && (a.ip() == b.ip() || a.ip().is_unspecified() || b.ip().is_unspecified())
+}
+
+/// The state of the virtual network: the addresses in use, and where to deliver connections
+/// and datagrams.
+#[derive(Debug)]
+pub struct VirtualNetwork {
+    bindings: Vec<Weak<Binding>>,
+    listeners: Vec<(SocketAddr, Weak<RefCell<Listener>>)>,
+    datagram_sockets: Vec<(SocketAddr, Weak<RefCell<DatagramQueue>>)>,
+    next_ephemeral_port: u16,
+}
+
+impl Default for VirtualNetwork {
+    fn default() -> Self {
+        VirtualNetwork {
+            bindings: Vec::new(),
+            listeners: Vec::new(),
+            datagram_sockets: Vec::new(),
+            next_ephemeral_port: *EPHEMERAL_PORTS.start(),
+        }
+    }
+}
+
+impl VirtualNetwork {
+    fn in_use(&self, protocol: Protocol, addr: SocketAddr) -> bool {
+        self.bindings
+            .iter()
+            .filter_map(Weak::upgrade)
+            .any(|binding| binding.protocol == protocol && addrs_overlap(binding.addr, addr))
+    }
+
+    /// Reserves `addr`, picking an ephemeral port if its port is 0.
+    fn bind(&mut self, protocol: Protocol, mut addr: SocketAddr) -> SockResult<Rc<Binding>> {
+        self.bindings.retain(|binding| binding.strong_count() > 0);
+        if addr.port() == 0 {
+            let mut port = None;
+            for _ in EPHEMERAL_PORTS {
+                let candidate = self.next_ephemeral_port;
+                self.next_ephemeral_port = if candidate == *EPHEMERAL_PORTS.end() {
+                    *EPHEMERAL_PORTS.start()
+                } else {
+                    candidate.strict_add(1)
+                };
+                if !self.in_use(protocol, SocketAddr::new(addr.ip(), candidate)) {
+                    port = Some(candidate);
+                    break;
+                }
+            }
+            addr.set_port(port.ok_or("EADDRINUSE")?);
+        } else if self.in_use(protocol, addr) {
+            return Err("EADDRINUSE");
+        }
+        let binding = Rc::new(Binding { protocol, addr });
+        self.bindings.push(Rc::downgrade(&binding));
+        Ok(binding)
+    }
+
+    fn find_listener(&mut self, addr: SocketAddr) -> Option<Rc<RefCell<Listener>>> {
+        self.listeners.retain(|(_, listener)| listener.strong_count() > 0);
+        self.listeners
+            .iter()
+            .find(|(listener_addr, _)| addrs_overlap(*listener_addr, addr))
+            .and_then(|(_, listener)| listener.upgrade())
+    }
+
+    fn find_datagram_socket(&mut self, addr: SocketAddr) -> Option<Rc<RefCell<DatagramQueue>>> {
+        self.datagram_sockets.retain(|(_, queue)| queue.strong_count() > 0);
+        self.datagram_sockets
+            .iter()
+            .find(|(socket_addr, _)| addrs_overlap(*socket_addr, addr))
+            .and_then(|(_, queue)| queue.upgrade())
+    }
+}
+
+/// A listening TCP socket.
+#[derive(Debug)]
+struct Listener {
+    binding: Rc<Binding>,
+    /// The server ends of the connections that have not been accepted yet, with the clock of
+    /// the `connect` that created them.
+    backlog: VecDeque<(Connection, VClock)>,
+    max_backlog: usize,
+}
+
+/// One end of a TCP connection.
+#[derive(Debug)]
+struct Connection {
+    local: SocketAddr,
+    peer: SocketAddr,
+    /// Keeps the local address in use while the connection is open.
+    _binding: Rc<Binding>,
+    readbuf: Rc<RefCell<StreamBuffer>>,
+    // Like for `socketpair`, the write link is weak so that `send` can detect
+    // that the peer is gone, and fail with EPIPE.
+    writebuf: Weak<RefCell<StreamBuffer>>,
+    read_shutdown: bool,
+    write_shutdown: bool,
+}
+
+impl Connection {
+    /// Signals end-of-file to the peer.
+    fn shutdown_write(&mut self) {
+        self.write_shutdown = true;
+        if let Some(writebuf) = self.writebuf.upgrade() {
+            writebuf.borrow_mut().has_writer = false;
+        }
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        self.shutdown_write();
+    }
+}
+
+#[derive(Debug)]
+struct StreamBuffer {
+    buf: VecDeque<u8>,
+    clock: VClock,
+    /// Becomes false once the peer has shut down writing or is closed, after which reading
+    /// an empty buffer indicates EOF instead of blocking.
+    has_writer: bool,
+}
+
+impl StreamBuffer {
+    fn new() -> Rc<RefCell<StreamBuffer>> {
+        Rc::new(RefCell::new(StreamBuffer {
+            buf: VecDeque::new(),
+            clock: VClock::default(),
+            has_writer: true,
+        }))
+    }
+}
+
+/// The datagrams received by a UDP socket.
+#[derive(Debug, Default)]
+struct DatagramQueue {
+    datagrams: VecDeque<(SocketAddr, Vec<u8>)>,
+    clock: VClock,
+    /// If the socket is connected, the only address datagrams are accepted from.
+    peer: Option<SocketAddr>,
+}
+
+#[derive(Debug)]
+enum SocketState {
+    /// A TCP socket that is neither listening nor connected.
+    Stream {
+        binding: Option<Rc<Binding>>,
+    },
+    Listening(Rc<RefCell<Listener>>),
+    Connected(Connection),
+    /// A UDP socket. It only receives datagrams once it is bound.
+    Datagram {
+        binding: Option<Rc<Binding>>,
+        queue: Rc<RefCell<DatagramQueue>>,
+    },
+}
+
+/// A socket created by `socket`.
+#[derive(Debug)]
+struct InetSocket {
+    is_ipv6: bool,
+    state: SocketState,
+    is_nonblock: bool,
+    /// The timeouts set with `SO_RCVTIMEO` and `SO_SNDTIMEO`.
+    recv_timeout: Option<Duration>,
+    send_timeout: Option<Duration>,
+    /// The integer options set with `setsockopt`, by level and name.
+    options: FxHashMap<(i32, i32), i32>,
+}
+
+impl InetSocket {
+    fn new(is_ipv6: bool, state: SocketState, is_nonblock: bool) -> InetSocket {
+        InetSocket {
+            is_ipv6,
+            state,
+            is_nonblock,
+            recv_timeout: None,
+            send_timeout: None,
+            options: FxHashMap::default(),
+        }
+    }
+
+    fn protocol(&self) -> Protocol {
+        match self.state {
+            SocketState::Datagram { .. } => Protocol::Udp,
+            _ => Protocol::Tcp,
+        }
+    }
+
+    fn unspecified_addr(&self) -> SocketAddr {
+        if self.is_ipv6 {
+            SocketAddr::new(Ipv6Addr::UNSPECIFIED.into(), 0)
+        } else {
+            SocketAddr::new(Ipv4Addr::UNSPECIFIED.into(), 0)
+        }
+    }
+
+    fn local_addr(&self) -> Option<SocketAddr> {
+        match &self.state {
+            SocketState::Stream { binding } | SocketState::Datagram { binding, .. } => {
+                binding.as_ref().map(|binding| binding.addr)
+            }
+            SocketState::Listening(listener) => Some(listener.borrow().binding.addr),
+            SocketState::Connected(connection) => Some(connection.local),
+        }
+    }
+
+    fn peer_addr(&self) -> SockResult<SocketAddr> {
+        match &self.state {
+            SocketState::Connected(connection) => Ok(connection.peer),
+            SocketState::Datagram { queue, .. } => queue.borrow().peer.ok_or("ENOTCONN"),
+            _ => Err("ENOTCONN"),
+        }
+    }
+
+    /// Checks that `addr` is reachable from this socket. The unspecified address stands for
+    /// the loopback address, like on Linux.
+    fn check_destination(&self, addr: SocketAddr) -> SockResult<SocketAddr> {
+        if addr.is_ipv6() != self.is_ipv6 {
+            return Err("EAFNOSUPPORT");
+        }
+        let ip = match addr.ip() {
+            IpAddr::V4(ip) if ip.is_unspecified() => Ipv4Addr::LOCALHOST.into(),
+            IpAddr::V6(ip) if ip.is_unspecified() => Ipv6Addr::LOCALHOST.into(),
+            ip if ip.is_loopback() => ip,
+            _ => return Err("ENETUNREACH"),
+        };
+        Ok(SocketAddr::new(ip, addr.port()))
+    }
+
+    fn bind(&mut self, network: &mut VirtualNetwork, addr: SocketAddr) -> SockResult<()> {
+        if addr.is_ipv6() != self.is_ipv6 {
+            return Err("EAFNOSUPPORT");
+        }
+        if !addr.ip().is_loopback() && !addr.ip().is_unspecified() {
+            return Err("EADDRNOTAVAIL");
+        }
+        let protocol = self.protocol();
+        match &mut self.state {
+            SocketState::Stream { binding: binding @ None } => {
+                *binding = Some(network.bind(protocol, addr)?)
+            }
+            SocketState::Datagram { binding: binding @ None, queue } => {
+                let new_binding = network.bind(protocol, addr)?;
+                network.datagram_sockets.push((new_binding.addr, Rc::downgrade(queue)));
+                *binding = Some(new_binding);
+            }
+            _ => return Err("EINVAL"),
+        }
+        Ok(())
+    }
+
+    /// Binds the socket to an ephemeral port if it is not bound yet.
+    fn autobind(&mut self, network: &mut VirtualNetwork) -> SockResult<()> {
+        if self.local_addr().is_none() {
+            self.bind(network, self.unspecified_addr())?;
+        }
+        Ok(())
+    }
+
+    fn listen(&mut self, network: &mut VirtualNetwork, backlog: i32) -> SockResult<()> {
+        let max_backlog = usize::try_from(backlog).unwrap_or(0).clamp(1, MAX_BACKLOG);
+        match &self.state {
+            SocketState::Stream { .. } => {
+                self.autobind(network)?;
+                let SocketState::Stream { binding: Some(binding) } = &self.state else {
+                    unreachable!()
+                };
+                let listener = Rc::new(RefCell::new(Listener {
+                    binding: Rc::clone(binding),
+                    backlog: VecDeque::new(),
+                    max_backlog,
+                }));
+                network.listeners.push((binding.addr, Rc::downgrade(&listener)));
+                self.state = SocketState::Listening(listener);
+                Ok(())
+            }
+            SocketState::Listening(listener) => {
+                listener.borrow_mut().max_backlog = max_backlog;
+                Ok(())
+            }
+            SocketState::Connected(_) => Err("EINVAL"),
+            SocketState::Datagram { .. } => Err("EOPNOTSUPP"),
+        }
+    }
+
+    fn accept<'tcx>(
+        &mut self,
+        ecx: &mut MiriInterpCx<'tcx>,
+        is_nonblock: bool,
+    ) -> SockResult<InetSocket> {
+        let SocketState::Listening(listener) = &self.state else {
+            return Err("EINVAL");
+        };
+        let Some((connection, clock)) = listener.borrow_mut().backlog.pop_front() else {
+            return Err("EAGAIN");
+        };
+        // Synchronize with the `connect` that created this connection.
+        ecx.acquire_clock(&clock);
+        Ok(InetSocket::new(self.is_ipv6, SocketState::Connected(connection), is_nonblock))
+    }
+
+    fn connect<'tcx>(&mut self, ecx: &mut MiriInterpCx<'tcx>, addr: SocketAddr) -> SockResult<()> {
+        let addr = self.check_destination(addr)?;
+        match &mut self.state {
+            SocketState::Stream { binding } => {
+                let listener = ecx.machine.network.find_listener(addr).ok_or("ECONNREFUSED")?;
+                let mut listener = listener.borrow_mut();
+                if listener.backlog.len() >= listener.max_backlog {
+                    return Err("EAGAIN");
+                }
+                let binding = match binding.take() {
+                    Some(binding) => binding,
+                    None => {
+                        ecx.machine.network.bind(Protocol::Tcp, SocketAddr::new(addr.ip(), 0))?
+                    }
+                };
+                let local = if binding.addr.ip().is_unspecified() {
+                    SocketAddr::new(addr.ip(), binding.addr.port())
+                } else {
+                    binding.addr
+                };
+                let to_server = StreamBuffer::new();
+                let to_client = StreamBuffer::new();
+                let server = Connection {
+                    local: addr,
+                    peer: local,
+                    _binding: Rc::clone(&listener.binding),
+                    readbuf: Rc::clone(&to_server),
+                    writebuf: Rc::downgrade(&to_client),
+                    read_shutdown: false,
+                    write_shutdown: false,
+                };
+                let client = Connection {
+                    local,
+                    peer: addr,
+                    _binding: binding,
+                    readbuf: to_client,
+                    writebuf: Rc::downgrade(&to_server),
+                    read_shutdown: false,
+                    write_shutdown: false,
+                };
+                // Remember this clock so `accept` can synchronize with us.
+                let mut clock = VClock::default();
+                if let Some(release) = &ecx.release_clock() {
+                    clock.join(release);
+                }
+                listener.backlog.push_back((server, clock));
+                drop(listener);
+                self.state = SocketState::Connected(client);
+                Ok(())
+            }
+            SocketState::Datagram { .. } => {
+                // Bind the socket so that the peer can answer.
+                self.autobind(&mut ecx.machine.network)?;
+                let SocketState::Datagram { queue, .. } = &self.state else { unreachable!() };
+                queue.borrow_mut().peer = Some(addr);
+                Ok(())
+            }
+            SocketState::Listening(_) => Err("EINVAL"),
+            SocketState::Connected(_) => Err("EISCONN"),
+        }
+    }
+
+    fn send<'tcx>(
+        &mut self,
+        ecx: &mut MiriInterpCx<'tcx>,
+        bytes: &[u8],
+        to: Option<SocketAddr>,
+    ) -> SockResult<usize> {
+        match &mut self.state {
+            // The destination is ignored for connected streams, like on Linux.
+            SocketState::Connected(connection) => {
+                if connection.write_shutdown {
+                    return Err("EPIPE");
+                }
+                let Some(writebuf) = connection.writebuf.upgrade() else {
+                    // The peer is gone.
+                    return Err("EPIPE");
+                };
+                let mut writebuf = writebuf.borrow_mut();
+                if bytes.is_empty() {
+                    return Ok(0);
+                }
+                let available_space = MAX_STREAM_BUFFER_CAPACITY.strict_sub(writebuf.buf.len());
+                if available_space == 0 {
+                    return Err("EAGAIN");
+                }
+                // Remember this clock so `recv` can synchronize with us.
+                if let Some(clock) = &ecx.release_clock() {
+                    writebuf.clock.join(clock);
+                }
+                let written = bytes.len().min(available_space);
+                writebuf.buf.extend(&bytes[..written]);
+                Ok(written)
+            }
+            SocketState::Stream { .. } | SocketState::Listening(_) => Err("ENOTCONN"),
+            SocketState::Datagram { .. } => {
+                let to = match to {
+                    Some(to) => self.check_destination(to)?,
+                    None => self.peer_addr().map_err(|_| "EDESTADDRREQ")?,
+                };
+                if bytes.len() > MAX_DATAGRAM_SIZE {
+                    return Err("EMSGSIZE");
+                }
+                self.autobind(&mut ecx.machine.network)?;
+                let local = self.local_addr().unwrap();
+                let from = if local.ip().is_unspecified() {
+                    SocketAddr::new(to.ip(), local.port())
+                } else {
+                    local
+                };
+                // Like on a real network, datagrams nobody is waiting for are silently dropped.
+                if let Some(receiver) = ecx.machine.network.find_datagram_socket(to) {
+                    let mut receiver = receiver.borrow_mut();
+                    if receiver.peer.is_none_or(|peer| peer == from)
+                        && receiver.datagrams.len() < MAX_QUEUED_DATAGRAMS
+                    {
+                        // Remember this clock so `recv` can synchronize with us.
+                        if let Some(clock) = &ecx.release_clock() {
+                            receiver.clock.join(clock);
+                        }
+                        receiver.datagrams.push_back((from, bytes.to_vec()));
+                    }
+                }
+                Ok(bytes.len())
+            }
+        }
+    }
+
+    /// Receives into `bytes`, and returns the number of bytes received and, for datagrams,
+    /// their source. With `peek`, the data stays queued. With `trunc`, the full length of a
+    /// datagram is returned even if it did not fit into `bytes`.
+    fn recv<'tcx>(
+        &mut self,
+        ecx: &mut MiriInterpCx<'tcx>,
+        bytes: &mut [u8],
+        peek: bool,
+        trunc: bool,
+    ) -> SockResult<(usize, Option<SocketAddr>)> {
+        match &self.state {
+            SocketState::Connected(connection) => {
+                let mut readbuf = connection.readbuf.borrow_mut();
+                if bytes.is_empty() {
+                    return Ok((0, None));
+                }
+                if readbuf.buf.is_empty() {
+                    if !readbuf.has_writer || connection.read_shutdown {
+                        // 0 bytes successfully read indicates end-of-file.
+                        return Ok((0, None));
+                    }
+                    return Err("EAGAIN");
+                }
+                // Synchronize with all previous writes to this buffer.
+                // FIXME: this over-synchronizes; a more precise approach would be to
+                // only sync with the writes whose data we will read.
+                ecx.acquire_clock(&readbuf.clock);
+                let len = bytes.len().min(readbuf.buf.len());
+                for (byte, buffered) in iter::zip(&mut bytes[..len], &readbuf.buf) {
+                    *byte = *buffered;
+                }
+                if !peek {
+                    readbuf.buf.drain(..len);
+                }
+                Ok((len, None))
+            }
+            SocketState::Datagram { queue, .. } => {
+                let mut queue = queue.borrow_mut();
+                let Some((from, datagram)) = queue.datagrams.front() else {
+                    return Err("EAGAIN");
+                };
+                let (from, size) = (*from, datagram.len());
+                let len = bytes.len().min(size);
+                bytes[..len].copy_from_slice(&datagram[..len]);
+                ecx.acquire_clock(&queue.clock);
+                if !peek {
+                    queue.datagrams.pop_front();
+                }
+                Ok((if trunc { size } else { len }, Some(from)))
+            }
+            SocketState::Stream { .. } | SocketState::Listening(_) => Err("ENOTCONN"),
+        }
+    }
+
+    fn shutdown(&mut self, read: bool, write: bool) -> SockResult<()> {
+        let SocketState::Connected(connection) = &mut self.state else {
+            return Err("ENOTCONN");
+        };
+        if read {
+            connection.read_shutdown = true;
+        }
+        if write {
+            connection.shutdown_write();
+        }
+        Ok(())
+    }
+
+    /// Converts the outcome of a socket operation for `read` and `write`, which cannot block.
+    fn io_result<'tcx, T>(
+        &self,
+        result: SockResult<T>,
+        op: &str,
+    ) -> InterpResult<'tcx, io::Result<T>> {
+        let kind = match result {
+            Ok(value) => return Ok(Ok(value)),
+            Err("EAGAIN") if self.is_nonblock => io::ErrorKind::WouldBlock,
+            Err("EAGAIN") => {
+                throw_unsup_format!("socket {op}: blocking is only supported by `recv` and `send`")
+            }
+            Err("EPIPE") => io::ErrorKind::BrokenPipe,
+            Err("ENOTCONN") => io::ErrorKind::NotConnected,
+            Err(errno) => throw_unsup_format!("socket {op}: failing with {errno} is unsupported"),
+        };
+        Ok(Err(kind.into()))
+    }
+}
+
+impl FileDescription for InetSocket {
+    fn name(&self) -> &'static str {
+        "socket"
+    }
+
+    fn read<'tcx>(
+        &mut self,
+        _communicate_allowed: bool,
+        bytes: &mut [u8],
+        ecx: &mut MiriInterpCx<'tcx>,
+    ) -> InterpResult<'tcx, io::Result<usize>> {
+        let result = self.recv(ecx, bytes, /* peek */ false, /* trunc */ false);
+        self.io_result(result.map(|(len, _from)| len), "read")
+    }
+
+    fn write<'tcx>(
+        &mut self,
+        _communicate_allowed: bool,
+        bytes: &[u8],
+        ecx: &mut MiriInterpCx<'tcx>,
+    ) -> InterpResult<'tcx, io::Result<usize>> {
+        let result = self.send(ecx, bytes, None);
+        self.io_result(result, "write")
+    }
+
+    fn close<'tcx>(
+        self: Box<Self>,
+        _communicate_allowed: bool,
+    ) -> InterpResult<'tcx, io::Result<()>> {
+        // Dropping the socket releases its address, and signals EOF to the peer of a connection.
+        Ok(Ok(()))
+    }
+
+    fn get_epoll_ready_events<'tcx>(&self) -> InterpResult<'tcx, EpollReadyEvents> {
+        let mut ready = EpollReadyEvents::default();
+        match &self.state {
+            // Like on Linux, a stream socket that is not connected counts as hung up.
+            SocketState::Stream { .. } => {
+                ready.epollout = true;
+                ready.epollhup = true;
+            }
+            SocketState::Listening(listener) => {
+                ready.epollin = !listener.borrow().backlog.is_empty()
+            }
+            SocketState::Connected(connection) => {
+                let readbuf = connection.readbuf.borrow();
+                let eof = !readbuf.has_writer || connection.read_shutdown;
+                let writebuf = connection.writebuf.upgrade();
+                ready.epollin = !readbuf.buf.is_empty() || eof;
+                ready.epollrdhup = eof;
+                // Writing fails right away if it was shut down or the peer is gone.
+                ready.epollout = writebuf
+                    .as_ref()
+                    .is_none_or(|buf| buf.borrow().buf.len() < MAX_STREAM_BUFFER_CAPACITY)
+                    || connection.write_shutdown;
+                ready.epollhup = eof && (writebuf.is_none() || connection.write_shutdown);
+            }
+            SocketState::Datagram { queue, .. } => {
+                ready.epollin = !queue.borrow().datagrams.is_empty();
+                ready.epollout = true;
+            }
+        }
+        Ok(ready)
+    }
+}
+
+/// Runs `f` on the socket `fd` refers to.
+fn with_socket<'tcx, T>(
+    ecx: &mut MiriInterpCx<'tcx>,
+    fd: i32,
+    f: impl FnOnce(&mut InetSocket, &mut MiriInterpCx<'tcx>) -> InterpResult<'tcx, SockResult<T>>,
+) -> InterpResult<'tcx, SockResult<T>> {
+    // We temporarily dup the FD to be able to retain mutable access to `ecx`.
+    let Some(file_descriptor) = ecx.machine.fds.dup(fd) else {
+        return Ok(Err("EBADF"));
+    };
+    let mut file = file_descriptor.borrow_mut();
+    if let Some(socket) = file.downcast_mut::<InetSocket>() {
+        return f(socket, ecx);
+    }
+    if file.downcast_ref::<SocketPair>().is_some() {
+        throw_unsup_format!("sockets created by `socketpair` only support `read` and `write`");
+    }
+    Ok(Err("ENOTSOCK"))
+}
+
+impl<'tcx> EvalContextExt<'tcx> for crate::MiriInterpCx<'tcx> {}
+pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
+    /// For more information on the arguments see the socket manpage:
+    /// <https://man7.org/linux/man-pages/man2/socket.2.html>
+    fn socket(
+        &mut self,
+        domain: &OpTy<'tcx>,
+        type_: &OpTy<'tcx>,
+        protocol: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let domain = this.read_scalar(domain)?.to_i32()?;
+        let mut type_ = this.read_scalar(type_)?.to_i32()?;
+        let protocol = this.read_scalar(protocol)?.to_i32()?;
+
+        let os = &*this.tcx.sess.target.os;
+        if !matches!(os, "linux" | "android" | "freebsd" | "macos") {
+            throw_unsup_format!("`socket` is not supported on {os}");
+        }
+
+        let mut is_sock_nonblock = false;
+        // SOCK_NONBLOCK and SOCK_CLOEXEC do not exist on macOS.
+        if os != "macos" {
+            let sock_nonblock = this.eval_libc_i32("SOCK_NONBLOCK");
+            let sock_cloexec = this.eval_libc_i32("SOCK_CLOEXEC");
+            if type_ & sock_nonblock == sock_nonblock {
+                is_sock_nonblock = true;
+                type_ &= !sock_nonblock;
+            }
+            // Miri does not support exec, so SOCK_CLOEXEC has no effect.
+            if type_ & sock_cloexec == sock_cloexec {
+                type_ &= !sock_cloexec;
+            }
+        }
+
+        let is_ipv6 = if domain == this.eval_libc_i32("AF_INET") {
+            false
+        } else if domain == this.eval_libc_i32("AF_INET6") {
+            true
+        } else if domain == this.eval_libc_i32("AF_UNIX") {
+            throw_unsup_format!("socket: AF_UNIX sockets can only be created with `socketpair`");
+        } else {
+            return Ok(Scalar::from_i32(this.socket_error("EAFNOSUPPORT")?));
+        };
+
+        let (state, expected_protocol) = if type_ == this.eval_libc_i32("SOCK_STREAM") {
+            (SocketState::Stream { binding: None }, this.eval_libc_i32("IPPROTO_TCP"))
+        } else if type_ == this.eval_libc_i32("SOCK_DGRAM") {
+            let queue = Rc::new(RefCell::new(DatagramQueue::default()));
+            (SocketState::Datagram { binding: None, queue }, this.eval_libc_i32("IPPROTO_UDP"))
+        } else {
+            throw_unsup_format!(
+                "socket: type {type_:#x} is unsupported, only SOCK_STREAM, SOCK_DGRAM, \
+                 SOCK_CLOEXEC and SOCK_NONBLOCK are allowed"
+            );
+        };
+        if protocol != 0 && protocol != expected_protocol {
+            return Ok(Scalar::from_i32(this.socket_error("EPROTONOSUPPORT")?));
+        }
+
+        let fd = this.machine.fds.insert_fd(InetSocket::new(is_ipv6, state, is_sock_nonblock));
+        Ok(Scalar::from_i32(fd))
+    }
+
+    fn bind(
+        &mut self,
+        socket: &OpTy<'tcx>,
+        address: &OpTy<'tcx>,
+        address_len: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let fd = this.read_scalar(socket)?.to_i32()?;
+        let address = this.read_pointer(address)?;
+        let address_len = this.read_scalar(address_len)?.to_u32()?;
+
+        let result = match this.read_socket_addr(address, address_len)? {
+            Ok(addr) => with_socket(this, fd, |socket, this| {
+                Ok(socket.bind(&mut this.machine.network, addr))
+            })?,
+            Err(errno) => Err(errno),
+        };
+        this.socket_result(result.map(|()| 0))
+    }
+
+    fn listen(&mut self, socket: &OpTy<'tcx>, backlog: &OpTy<'tcx>) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let fd = this.read_scalar(socket)?.to_i32()?;
+        let backlog = this.read_scalar(backlog)?.to_i32()?;
+
+        let result = with_socket(this, fd, |socket, this| {
+            Ok(socket.listen(&mut this.machine.network, backlog))
+        })?;
+        this.socket_result(result.map(|()| 0))
+    }
+
+    /// Implements both `accept` and `accept4`, which additionally takes `flags`.
+    fn accept(
+        &mut self,
+        socket: &OpTy<'tcx>,
+        address: &OpTy<'tcx>,
+        address_len: &OpTy<'tcx>,
+        flags: Option<&OpTy<'tcx>>,
+        dest: &MPlaceTy<'tcx>,
+    ) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+
+        let fd = this.read_scalar(socket)?.to_i32()?;
+        let address = this.read_pointer(address)?;
+        let address_len = this.read_pointer(address_len)?;
+
+        let mut is_sock_nonblock = false;
+        if let Some(flags) = flags {
+            let mut flags = this.read_scalar(flags)?.to_i32()?;
+            let sock_nonblock = this.eval_libc_i32("SOCK_NONBLOCK");
+            let sock_cloexec = this.eval_libc_i32("SOCK_CLOEXEC");
+            if flags & sock_nonblock == sock_nonblock {
+                is_sock_nonblock = true;
+                flags &= !sock_nonblock;
+            }
+            // Miri does not support exec, so SOCK_CLOEXEC has no effect.
+            if flags & sock_cloexec == sock_cloexec {
+                flags &= !sock_cloexec;
+            }
+            if flags != 0 {
+                throw_unsup_format!(
+                    "accept4: flag {flags:#x} is unsupported, only SOCK_CLOEXEC \
+                     and SOCK_NONBLOCK are allowed"
+                );
+            }
+        }
+
+        let deadline = this.socket_deadline(fd, /* send */ false)?;
+        this.accept_inner(fd, address, address_len, is_sock_nonblock, deadline, dest.clone())
+    }
+
+    fn accept_inner(
+        &mut self,
+        fd: i32,
+        address: Pointer,
+        address_len: Pointer,
+        is_sock_nonblock: bool,
+        deadline: Option<Duration>,
+        dest: MPlaceTy<'tcx>,
+    ) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+
+        let mut nonblock = false;
+        let result = with_socket(this, fd, |socket, this| {
+            nonblock = socket.is_nonblock;
+            Ok(socket.accept(this, is_sock_nonblock))
+        })?;
+        match result {
+            Ok(socket) => {
+                let peer = socket.peer_addr().unwrap();
+                let new_fd = this.machine.fds.insert_fd(socket);
+                this.write_socket_addr(peer, address, address_len)?;
+                this.wake_io_waiters()?;
+                this.write_int(new_fd, &dest)
+            }
+            Err("EAGAIN") if !nonblock && !this.deadline_passed(deadline) => {
+                this.block_on_io(
+                    deadline,
+                    // Socket calls with a timeout are not restarted after a signal handler.
+                    deadline.is_none(),
+                    dest.clone(),
+                    callback!(
+                        @capture<'tcx> {
+                            fd: i32,
+                            address: Pointer,
+                            address_len: Pointer,
+                            is_sock_nonblock: bool,
+                            deadline: Option<Duration>,
+                            dest: MPlaceTy<'tcx>,
+                        }
+                        @unblock = |this| {
+                            this.accept_inner(
+                                fd, address, address_len, is_sock_nonblock, deadline, dest,
+                            )
+                        }
+                        @timeout = |this| {
+                            this.accept_inner(
+                                fd, address, address_len, is_sock_nonblock, deadline, dest,
+                            )
+                        }
+                    ),
+                );
+                Ok(())
+            }
+            Err(errno) => {
+                let result = this.socket_error(errno)?;
+                this.write_int(result, &dest)
+            }
+        }
+    }
+
+    fn connect(
+        &mut self,
+        socket: &OpTy<'tcx>,
+        address: &OpTy<'tcx>,
+        address_len: &OpTy<'tcx>,
+        dest: &MPlaceTy<'tcx>,
+    ) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+
+        let fd = this.read_scalar(socket)?.to_i32()?;
+        let address = this.read_pointer(address)?;
+        let address_len = this.read_scalar(address_len)?.to_u32()?;
+
+        let addr = match this.read_socket_addr(address, address_len)? {
+            Ok(addr) => addr,
+            Err(errno) => {
+                let result = this.socket_error(errno)?;
+                return this.write_int(result, dest);
+            }
+        };
+        let deadline = this.socket_deadline(fd, /* send */ true)?;
+        this.connect_inner(fd, addr, deadline, dest.clone())
+    }
+
+    fn connect_inner(
+        &mut self,
+        fd: i32,
+        addr: SocketAddr,
+        deadline: Option<Duration>,
+        dest: MPlaceTy<'tcx>,
+    ) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+
+        let mut nonblock = false;
+        let result = with_socket(this, fd, |socket, this| {
+            nonblock = socket.is_nonblock;
+            Ok(socket.connect(this, addr))
+        })?;
+        match result {
+            Ok(()) => {
+                this.wake_io_waiters()?;
+                this.write_int(0, &dest)
+            }
+            // The backlog of the listener is full.
+            Err("EAGAIN") if !nonblock && !this.deadline_passed(deadline) => {
+                this.block_on_io(
+                    deadline,
+                    // Socket calls with a timeout are not restarted after a signal handler.
+                    deadline.is_none(),
+                    dest.clone(),
+                    callback!(
+                        @capture<'tcx> {
+                            fd: i32,
+                            addr: SocketAddr,
+                            deadline: Option<Duration>,
+                            dest: MPlaceTy<'tcx>,
+                        }
+                        @unblock = |this| {
+                            this.connect_inner(fd, addr, deadline, dest)
+                        }
+                        @timeout = |this| {
+                            this.connect_inner(fd, addr, deadline, dest)
+                        }
+                    ),
+                );
+                Ok(())
+            }
+            Err(errno) => {
+                let result = this.socket_error(errno)?;
+                this.write_int(result, &dest)
+            }
+        }
+    }
+
+    /// Implements both `send` and `sendto`, which additionally takes the destination in `to`.
+    fn send(
+        &mut self,
+        socket: &OpTy<'tcx>,
+        buffer: &OpTy<'tcx>,
+        length: &OpTy<'tcx>,
+        flags: &OpTy<'tcx>,
+        to: Option<(&OpTy<'tcx>, &OpTy<'tcx>)>,
+        dest: &MPlaceTy<'tcx>,
+    ) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+
+        let fd = this.read_scalar(socket)?.to_i32()?;
+        let buffer = this.read_pointer(buffer)?;
+        let length = this.read_target_usize(length)?;
+        let mut flags = this.read_scalar(flags)?.to_i32()?;
+
+        let mut dontwait = false;
+        let msg_dontwait = this.eval_libc_i32("MSG_DONTWAIT");
+        if flags & msg_dontwait == msg_dontwait {
+            dontwait = true;
+            flags &= !msg_dontwait;
+        }
+        // MSG_NOSIGNAL does not exist on macOS. Miri does not emulate `SIGPIPE` anyway,
+        // so sending on a broken connection always just fails with EPIPE.
+        if this.tcx.sess.target.os != "macos" {
+            let msg_nosignal = this.eval_libc_i32("MSG_NOSIGNAL");
+            if flags & msg_nosignal == msg_nosignal {
+                flags &= !msg_nosignal;
+            }
+        }
+        if flags != 0 {
+            throw_unsup_format!(
+                "send: flag {flags:#x} is unsupported, only MSG_DONTWAIT and MSG_NOSIGNAL are allowed"
+            );
+        }
+
+        let to = match to {
+            Some((address, address_len)) => {
+                let address = this.read_pointer(address)?;
+                let address_len = this.read_scalar(address_len)?.to_u32()?;
+                if this.ptr_is_null(address)? {
+                    None
+                } else {
+                    match this.read_socket_addr(address, address_len)? {
+                        Ok(addr) => Some(addr),
+                        Err(errno) => {
+                            let result = this.socket_error(errno)?;
+                            return this.write_int(result, dest);
+                        }
+                    }
+                }
+            }
+            None => None,
+        };
+
+        // Check that the *entire* buffer is actually valid memory.
+        this.check_ptr_access(buffer, Size::from_bytes(length), CheckInAllocMsg::MemoryAccessTest)?;
+
+        let deadline = this.socket_deadline(fd, /* send */ true)?;
+        this.send_inner(fd, buffer, length, dontwait, to, deadline, dest.clone())
+    }
+
+    fn send_inner(
+        &mut self,
+        fd: i32,
+        buffer: Pointer,
+        length: u64,
+        dontwait: bool,
+        to: Option<SocketAddr>,
+        deadline: Option<Duration>,
+        dest: MPlaceTy<'tcx>,
+    ) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+
+        let bytes =
+            this.read_bytes_ptr_strip_provenance(buffer, Size::from_bytes(length))?.to_owned();
+        let mut nonblock = dontwait;
+        let result = with_socket(this, fd, |socket, this| {
+            nonblock |= socket.is_nonblock;
+            Ok(socket.send(this, &bytes, to))
+        })?;
+        match result {
+            Ok(sent) => {
+                this.wake_io_waiters()?;
+                this.write_int(u64::try_from(sent).unwrap(), &dest)
+            }
+            Err("EAGAIN") if !nonblock && !this.deadline_passed(deadline) => {
+                this.block_on_io(
+                    deadline,
+                    // Socket calls with a timeout are not restarted after a signal handler.
+                    deadline.is_none(),
+                    dest.clone(),
+                    callback!(
+                        @capture<'tcx> {
+                            fd: i32,
+                            buffer: Pointer,
+                            length: u64,
+                            dontwait: bool,
+                            to: Option<SocketAddr>,
+                            deadline: Option<Duration>,
+                            dest: MPlaceTy<'tcx>,
+                        }
+                        @unblock = |this| {
+                            this.send_inner(fd, buffer, length, dontwait, to, deadline, dest)
+                        }
+                        @timeout = |this| {
+                            this.send_inner(fd, buffer, length, dontwait, to, deadline, dest)
+                        }
+                    ),
+                );
+                Ok(())
+            }
+            Err(errno) => {
+                let result = this.socket_error(errno)?;
+                this.write_int(result, &dest)
+            }
+        }
+    }
+
+    /// Implements both `recv` and `recvfrom`, which additionally reports the source in `from`.
+    fn recv(
+        &mut self,
+        socket: &OpTy<'tcx>,
+        buffer: &OpTy<'tcx>,
+        length: &OpTy<'tcx>,
+        flags: &OpTy<'tcx>,
+        from: Option<(&OpTy<'tcx>, &OpTy<'tcx>)>,
+        dest: &MPlaceTy<'tcx>,
+    ) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+
+        let fd = this.read_scalar(socket)?.to_i32()?;
+        let buffer = this.read_pointer(buffer)?;
+        let length = this.read_target_usize(length)?;
+        let mut flags = this.read_scalar(flags)?.to_i32()?;
+        let (address, address_len) = match from {
+            Some((address, address_len)) => {
+                (this.read_pointer(address)?, this.read_pointer(address_len)?)
+            }
+            None => (Pointer::null(), Pointer::null()),
+        };
+
+        let mut peek = false;
+        let mut dontwait = false;
+        let mut trunc = false;
+        let msg_peek = this.eval_libc_i32("MSG_PEEK");
+        let msg_dontwait = this.eval_libc_i32("MSG_DONTWAIT");
+        if flags & msg_peek == msg_peek {
+            peek = true;
+            flags &= !msg_peek;
+        }
+        if flags & msg_dontwait == msg_dontwait {
+            dontwait = true;
+            flags &= !msg_dontwait;
+        }
+        // Reporting the full length of truncated datagrams is Linux-specific.
+        if matches!(&*this.tcx.sess.target.os, "linux" | "android") {
+            let msg_trunc = this.eval_libc_i32("MSG_TRUNC");
+            if flags & msg_trunc == msg_trunc {
+                trunc = true;
+                flags &= !msg_trunc;
+            }
+        }
+        if flags != 0 {
+            throw_unsup_format!(
+                "recv: flag {flags:#x} is unsupported, only MSG_PEEK, MSG_DONTWAIT \
+                 and MSG_TRUNC are allowed"
+            );
+        }
+
+        // Check that the *entire* buffer is actually valid memory.
+        this.check_ptr_access(buffer, Size::from_bytes(length), CheckInAllocMsg::MemoryAccessTest)?;
+
+        let deadline = this.socket_deadline(fd, /* send */ false)?;
+        this.recv_inner(
+            fd,
+            buffer,
+            length,
+            peek,
+            trunc,
+            dontwait,
+            address,
+            address_len,
+            deadline,
+            dest.clone(),
+        )
+    }
+
+    fn recv_inner(
+        &mut self,
+        fd: i32,
+        buffer: Pointer,
+        length: u64,
+        peek: bool,
+        trunc: bool,
+        dontwait: bool,
+        address: Pointer,
+        address_len: Pointer,
+        deadline: Option<Duration>,
+        dest: MPlaceTy<'tcx>,
+    ) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+
+        // We cap the number of read bytes to the largest value that we are able to fit in both
+        // the host's and target's `isize`, like `read` does.
+        let length = length
+            .min(u64::try_from(this.target_isize_max()).unwrap())
+            .min(u64::try_from(isize::MAX).unwrap());
+        let mut bytes = vec![0; usize::try_from(length).unwrap()];
+        let mut nonblock = dontwait;
+        let result = with_socket(this, fd, |socket, this| {
+            nonblock |= socket.is_nonblock;
+            Ok(socket.recv(this, &mut bytes, peek, trunc))
+        })?;
+        match result {
+            Ok((received, from)) => {
+                let written = received.min(bytes.len());
+                this.write_bytes_ptr(buffer, bytes[..written].iter().copied())?;
+                if let Some(from) = from {
+                    this.write_socket_addr(from, address, address_len)?;
+                } else if !this.ptr_is_null(address_len)? {
+                    // Streams have no source address to report.
+                    let address_len =
+                        this.ptr_to_mplace(address_len, this.libc_ty_layout("socklen_t"));
+                    this.write_int(0, &address_len)?;
+                }
+                if !peek {
+                    this.wake_io_waiters()?;
+                }
+                this.write_int(u64::try_from(received).unwrap(), &dest)
+            }
+            Err("EAGAIN") if !nonblock && !this.deadline_passed(deadline) => {
+                this.block_on_io(
+                    deadline,
+                    // Socket calls with a timeout are not restarted after a signal handler.
+                    deadline.is_none(),
+                    dest.clone(),
+                    callback!(
+                        @capture<'tcx> {
+                            fd: i32,
+                            buffer: Pointer,
+                            length: u64,
+                            peek: bool,
+                            trunc: bool,
+                            dontwait: bool,
+                            address: Pointer,
+                            address_len: Pointer,
+                            deadline: Option<Duration>,
+                            dest: MPlaceTy<'tcx>,
+                        }
+                        @unblock = |this| {
+                            this.recv_inner(
+                                fd, buffer, length, peek, trunc, dontwait, address, address_len,
+                                deadline, dest,
+                            )
+                        }
+                        @timeout = |this| {
+                            this.recv_inner(
+                                fd, buffer, length, peek, trunc, dontwait, address, address_len,
+                                deadline, dest,
+                            )
+                        }
+                    ),
+                );
+                Ok(())
+            }
+            Err(errno) => {
+                let result = this.socket_error(errno)?;
+                this.write_int(result, &dest)
+            }
+        }
+    }
+
+    fn getsockname(
+        &mut self,
+        socket: &OpTy<'tcx>,
+        address: &OpTy<'tcx>,
+        address_len: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let fd = this.read_scalar(socket)?.to_i32()?;
+        let address = this.read_pointer(address)?;
+        let address_len = this.read_pointer(address_len)?;
+
+        // Unbound sockets report the unspecified address with port 0.
+        let result = with_socket(this, fd, |socket, _this| {
+            Ok(Ok(socket.local_addr().unwrap_or_else(|| socket.unspecified_addr())))
+        })?;
+        if let Ok(addr) = result {
+            this.write_socket_addr(addr, address, address_len)?;
+        }
+        this.socket_result(result.map(|_| 0))
+    }
+
+    fn getpeername(
+        &mut self,
+        socket: &OpTy<'tcx>,
+        address: &OpTy<'tcx>,
+        address_len: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let fd = this.read_scalar(socket)?.to_i32()?;
+        let address = this.read_pointer(address)?;
+        let address_len = this.read_pointer(address_len)?;
+
+        let result = with_socket(this, fd, |socket, _this| Ok(socket.peer_addr()))?;
+        if let Ok(addr) = result {
+            this.write_socket_addr(addr, address, address_len)?;
+        }
+        this.socket_result(result.map(|_| 0))
+    }
+
+    fn setsockopt(
+        &mut self,
+        socket: &OpTy<'tcx>,
+        level: &OpTy<'tcx>,
+        option_name: &OpTy<'tcx>,
+        option_value: &OpTy<'tcx>,
+        option_len: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let fd = this.read_scalar(socket)?.to_i32()?;
+        let level = this.read_scalar(level)?.to_i32()?;
+        let option_name = this.read_scalar(option_name)?.to_i32()?;
+        let option_value = this.read_pointer(option_value)?;
+        let option_len = u64::from(this.read_scalar(option_len)?.to_u32()?);
+
+        let is_ipv6 = match with_socket(this, fd, |socket, _this| Ok(Ok(socket.is_ipv6)))? {
+            Ok(is_ipv6) => is_ipv6,
+            Err(errno) => return this.socket_result(Err(errno)),
+        };
+
+        let sol_socket = this.eval_libc_i32("SOL_SOCKET");
+        if level == sol_socket
+            && (option_name == this.eval_libc_i32("SO_RCVTIMEO")
+                || option_name == this.eval_libc_i32("SO_SNDTIMEO"))
+        {
+            let timeval = this.libc_ty_layout("timeval");
+            if option_len < timeval.size.bytes() {
+                return this.socket_result(Err("EINVAL"));
+            }
+            let timeval = this.ptr_to_mplace(option_value, timeval);
+            let seconds = this.project_field_named(&timeval, "tv_sec")?;
+            let seconds = this.read_scalar(&seconds)?.to_int(seconds.layout.size)?;
+            let micros = this.project_field_named(&timeval, "tv_usec")?;
+            let micros = this.read_scalar(&micros)?.to_int(micros.layout.size)?;
+            let (Ok(seconds), Ok(micros)) = (u64::try_from(seconds), u32::try_from(micros)) else {
+                return this.socket_result(Err("EDOM"));
+            };
+            if micros >= 1_000_000 {
+                return this.socket_result(Err("EDOM"));
+            }
+            // A zero timeout means to block forever.
+            let timeout = Duration::new(seconds, micros.strict_mul(1000));
+            let timeout = (!timeout.is_zero()).then_some(timeout);
+            let is_recv = option_name == this.eval_libc_i32("SO_RCVTIMEO");
+            let result = with_socket(this, fd, |socket, _this| {
+                if is_recv {
+                    socket.recv_timeout = timeout;
+                } else {
+                    socket.send_timeout = timeout;
+                }
+                Ok(Ok(0))
+            })?;
+            return this.socket_result(result);
+        }
+
+        let Some(_default) = this.socket_option_default(is_ipv6, level, option_name) else {
+            throw_unsup_format!(
+                "setsockopt: option {option_name:#x} at level {level:#x} is unsupported"
+            );
+        };
+        if option_len < 4 {
+            return this.socket_result(Err("EINVAL"));
+        }
+        let value = this.ptr_to_mplace(option_value, this.machine.layouts.i32);
+        let value = this.read_scalar(&value)?.to_i32()?;
+        if is_ipv6
+            && level == this.eval_libc_i32("IPPROTO_IPV6")
+            && option_name == this.eval_libc_i32("IPV6_V6ONLY")
+            && value == 0
+        {
+            throw_unsup_format!("setsockopt: IPv6 sockets that also handle IPv4 are unsupported");
+        }
+        let result = with_socket(this, fd, |socket, _this| {
+            socket.options.insert((level, option_name), value);
+            Ok(Ok(0))
+        })?;
+        this.socket_result(result)
+    }
+
+    fn getsockopt(
+        &mut self,
+        socket: &OpTy<'tcx>,
+        level: &OpTy<'tcx>,
+        option_name: &OpTy<'tcx>,
+        option_value: &OpTy<'tcx>,
+        option_len: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let fd = this.read_scalar(socket)?.to_i32()?;
+        let level = this.read_scalar(level)?.to_i32()?;
+        let option_name = this.read_scalar(option_name)?.to_i32()?;
+        let option_value = this.read_pointer(option_value)?;
+        let option_len = this.deref_pointer_as(option_len, this.libc_ty_layout("socklen_t"))?;
+
+        let is_ipv6 = match with_socket(this, fd, |socket, _this| Ok(Ok(socket.is_ipv6)))? {
+            Ok(is_ipv6) => is_ipv6,
+            Err(errno) => return this.socket_result(Err(errno)),
+        };
+        let len = u64::from(this.read_scalar(&option_len)?.to_u32()?);
+
+        let sol_socket = this.eval_libc_i32("SOL_SOCKET");
+        if level == sol_socket
+            && (option_name == this.eval_libc_i32("SO_RCVTIMEO")
+                || option_name == this.eval_libc_i32("SO_SNDTIMEO"))
+        {
+            let timeval = this.libc_ty_layout("timeval");
+            if len < timeval.size.bytes() {
+                return this.socket_result(Err("EINVAL"));
+            }
+            let is_recv = option_name == this.eval_libc_i32("SO_RCVTIMEO");
+            let timeout = with_socket(this, fd, |socket, _this| {
+                Ok(Ok(if is_recv { socket.recv_timeout } else { socket.send_timeout }))
+            })?
+            .unwrap()
+            .unwrap_or_default();
+            let timeval_place = this.ptr_to_mplace(option_value, timeval);
+            this.write_int_fields_named(
+                &[
+                    ("tv_sec", timeout.as_secs().into()),
+                    ("tv_usec", timeout.subsec_micros().into()),
+                ],
+                &timeval_place,
+            )?;
+            this.write_int(timeval.size.bytes(), &option_len)?;
+            return this.socket_result(Ok(0));
+        }
+
+        let value = if level == sol_socket && option_name == this.eval_libc_i32("SO_ERROR") {
+            // Errors are always reported right away, so there is never a pending one.
+            0
+        } else if level == sol_socket && option_name == this.eval_libc_i32("SO_TYPE") {
+            let protocol = with_socket(this, fd, |socket, _this| Ok(Ok(socket.protocol())))?;
+            match protocol.unwrap() {
+                Protocol::Tcp => this.eval_libc_i32("SOCK_STREAM"),
+                Protocol::Udp => this.eval_libc_i32("SOCK_DGRAM"),
+            }
+        } else if let Some(default) = this.socket_option_default(is_ipv6, level, option_name) {
+            let value = with_socket(this, fd, |socket, _this| {
+                Ok(Ok(socket.options.get(&(level, option_name)).copied()))
+            })?;
+            value.unwrap().unwrap_or(default)
+        } else {
+            throw_unsup_format!(
+                "getsockopt: option {option_name:#x} at level {level:#x} is unsupported"
+            );
+        };
+        if len < 4 {
+            return this.socket_result(Err("EINVAL"));
+        }
+        let value_place = this.ptr_to_mplace(option_value, this.machine.layouts.i32);
+        this.write_int(value, &value_place)?;
+        this.write_int(4, &option_len)?;
+        this.socket_result(Ok(0))
+    }
+
+    fn shutdown(&mut self, socket: &OpTy<'tcx>, how: &OpTy<'tcx>) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let fd = this.read_scalar(socket)?.to_i32()?;
+        let how = this.read_scalar(how)?.to_i32()?;
+
+        let (read, write) = if how == this.eval_libc_i32("SHUT_RD") {
+            (true, false)
+        } else if how == this.eval_libc_i32("SHUT_WR") {
+            (false, true)
+        } else if how == this.eval_libc_i32("SHUT_RDWR") {
+            (true, true)
+        } else {
+            return this.socket_result(Err("EINVAL"));
+        };
+        let result = with_socket(this, fd, |socket, _this| Ok(socket.shutdown(read, write)))?;
+        if result.is_ok() {
+            this.wake_io_waiters()?;
+        }
+        this.socket_result(result.map(|()| 0))
+    }
+
+    fn ioctl(&mut self, args: &[OpTy<'tcx>]) -> InterpResult<'tcx, i32> {
+        let this = self.eval_context_mut();
+
+        if args.len() < 2 {
+            throw_ub_format!(
+                "incorrect number of arguments for ioctl: got {}, expected at least 2",
+                args.len()
+            );
+        }
+        let fd = this.read_scalar(&args[0])?.to_i32()?;
+        let request = this.read_scalar(&args[1])?;
+
+        if request == this.eval_libc("FIOCLEX") {
+            // Miri does not support exec, so FD_CLOEXEC has no effect, and is always reported
+            // as set by `fcntl`.
+            if this.machine.fds.is_fd(fd) { Ok(0) } else { this.fd_not_found() }
+        } else if request == this.eval_libc("FIONBIO") {
+            if args.len() < 3 {
+                throw_ub_format!(
+                    "incorrect number of arguments for ioctl with request=`FIONBIO`: got {}, expected at least 3",
+                    args.len()
+                );
+            }
+            let value = this.deref_pointer_as(&args[2], this.machine.layouts.i32)?;
+            let nonblock = this.read_scalar(&value)?.to_i32()? != 0;
+            let Some(file_descriptor) = this.machine.fds.dup(fd) else {
+                return this.fd_not_found();
+            };
+            let mut file = file_descriptor.borrow_mut();
+            let Some(socket) = file.downcast_mut::<InetSocket>() else {
+                throw_unsup_format!("ioctl: FIONBIO is only supported on sockets");
+            };
+            socket.is_nonblock = nonblock;
+            Ok(0)
+        } else {
+            throw_unsup_format!("ioctl: request {request:#x} is unsupported");
+        }
+    }
+
+    /// Reads the socket address at `address`, which is `address_len` bytes long.
+    fn read_socket_addr(
+        &mut self,
+        address: Pointer,
+        address_len: u32,
+    ) -> InterpResult<'tcx, SockResult<SocketAddr>> {
+        let this = self.eval_context_mut();
+
+        let address_len = u64::from(address_len);
+        let sockaddr_in = this.libc_ty_layout("sockaddr_in");
+        if address_len < sockaddr_in.size.bytes() {
+            return Ok(Err("EINVAL"));
+        }
+        // Every kind of socket address starts with its family.
+        let addr = this.ptr_to_mplace(address, sockaddr_in);
+        let family = this.project_field_named(&addr, "sin_family")?;
+        let family = this.read_scalar(&family)?.to_uint(family.layout.size)?;
+
+        // The port and the IP address are in network byte order.
+        if family == u128::try_from(this.eval_libc_i32("AF_INET")).unwrap() {
+            let port = this.project_field_named(&addr, "sin_port")?;
+            let port: [u8; 2] = this
+                .read_bytes_ptr_strip_provenance(port.ptr(), Size::from_bytes(2))?
+                .try_into()
+                .unwrap();
+            let ip = this.project_field_named(&addr, "sin_addr")?;
+            let ip: [u8; 4] = this
+                .read_bytes_ptr_strip_provenance(ip.ptr(), Size::from_bytes(4))?
+                .try_into()
+                .unwrap();
+            Ok(Ok(SocketAddr::new(Ipv4Addr::from(ip).into(), u16::from_be_bytes(port))))
+        } else if family == u128::try_from(this.eval_libc_i32("AF_INET6")).unwrap() {
+            let sockaddr_in6 = this.libc_ty_layout("sockaddr_in6");
+            if address_len < sockaddr_in6.size.bytes() {
+                return Ok(Err("EINVAL"));
+            }
+            let addr = this.ptr_to_mplace(address, sockaddr_in6);
+            let port = this.project_field_named(&addr, "sin6_port")?;
+            let port: [u8; 2] = this
+                .read_bytes_ptr_strip_provenance(port.ptr(), Size::from_bytes(2))?
+                .try_into()
+                .unwrap();
+            let ip = this.project_field_named(&addr, "sin6_addr")?;
+            let ip: [u8; 16] = this
+                .read_bytes_ptr_strip_provenance(ip.ptr(), Size::from_bytes(16))?
+                .try_into()
+                .unwrap();
+            Ok(Ok(SocketAddr::new(Ipv6Addr::from(ip).into(), u16::from_be_bytes(port))))
+        } else {
+            Ok(Err("EAFNOSUPPORT"))
+        }
+    }
+
+    /// Writes `addr` to the buffer at `address`, whose length is stored at `address_len`.
+    /// Like the real system calls, this truncates the address if the buffer is too short,
+    /// and stores the full length of the address at `address_len`.
+    /// Nothing is written if `address` is null.
+    fn write_socket_addr(
+        &mut self,
+        addr: SocketAddr,
+        address: Pointer,
+        address_len: Pointer,
+    ) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+
+        if this.ptr_is_null(address)? {
+            return Ok(());
+        }
+        let len_place = this.ptr_to_mplace(address_len, this.libc_ty_layout("socklen_t"));
+        let len = u64::from(this.read_scalar(&len_place)?.to_u32()?);
+
+        let (layout, family, prefix) = match addr {
+            SocketAddr::V4(_) => (this.libc_ty_layout("sockaddr_in"), "AF_INET", "sin"),
+            SocketAddr::V6(_) => (this.libc_ty_layout("sockaddr_in6"), "AF_INET6", "sin6"),
+        };
+        let size = layout.size.bytes();
+
+        // Build the address in a temporary buffer, so that we can copy only a part of it.
+        let sockaddr = this.allocate(layout, MiriMemoryKind::Runtime.into())?;
+        this.write_bytes_ptr(sockaddr.ptr(), iter::repeat(0).take(usize::try_from(size).unwrap()))?;
+        // The BSDs store the length of the address in the address itself.
+        let len_field = format!("{prefix}_len");
+        if this.projectable_has_field(&sockaddr, &len_field) {
+            this.write_int(size, &this.project_field_named(&sockaddr, &len_field)?)?;
+        }
+        let family = this.eval_libc_i32(family);
+        this.write_int(family, &this.project_field_named(&sockaddr, &format!("{prefix}_family"))?)?;
+        let port = this.project_field_named(&sockaddr, &format!("{prefix}_port"))?;
+        this.write_bytes_ptr(port.ptr(), addr.port().to_be_bytes())?;
+        let ip = this.project_field_named(&sockaddr, &format!("{prefix}_addr"))?;
+        match addr.ip() {
+            IpAddr::V4(ip_addr) => this.write_bytes_ptr(ip.ptr(), ip_addr.octets())?,
+            IpAddr::V6(ip_addr) => this.write_bytes_ptr(ip.ptr(), ip_addr.octets())?,
+        }
+
+        this.mem_copy(sockaddr.ptr(), address, Size::from_bytes(len.min(size)), true)?;
+        this.deallocate_ptr(sockaddr.ptr(), None, MiriMemoryKind::Runtime.into())?;
+        this.write_int(size, &len_place)
+    }
+
+    /// Returns the default of the integer option `option_name` at `level`, or `None` if this
+    /// option is not supported. Most options are accepted, but have no effect.
+    fn socket_option_default(&self, is_ipv6: bool, level: i32, option_name: i32) -> Option<i32> {
+        let this = self.eval_context_ref();
+
+        let buffer_size = i32::try_from(MAX_STREAM_BUFFER_CAPACITY).unwrap();
+        let mut options = vec![
+            ("SOL_SOCKET", "SO_REUSEADDR", 0),
+            ("SOL_SOCKET", "SO_REUSEPORT", 0),
+            ("SOL_SOCKET", "SO_KEEPALIVE", 0),
+            ("SOL_SOCKET", "SO_BROADCAST", 0),
+            ("SOL_SOCKET", "SO_RCVBUF", buffer_size),
+            ("SOL_SOCKET", "SO_SNDBUF", buffer_size),
+            ("IPPROTO_TCP", "TCP_NODELAY", 0),
+        ];
+        if is_ipv6 {
+            // IPv6 sockets never handle IPv4 traffic.
+            options.push(("IPPROTO_IPV6", "IPV6_V6ONLY", 1));
+        } else {
+            options.push(("IPPROTO_IP", "IP_TTL", 64));
+        }
+        if this.tcx.sess.target.os == "macos" {
+            // The standard library sets this on every socket.
+            options.push(("SOL_SOCKET", "SO_NOSIGPIPE", 0));
+        }
+        options
+            .into_iter()
+            .find(|&(option_level, name, _)| {
+                level == this.eval_libc_i32(option_level) && option_name == this.eval_libc_i32(name)
+            })
+            .map(|(_, _, default)| default)
+    }
+
+    /// Returns when an operation on `fd` starting now times out, according to its `SO_SNDTIMEO`
+    /// or `SO_RCVTIMEO` option.
+    fn socket_deadline(&mut self, fd: i32, send: bool) -> InterpResult<'tcx, Option<Duration>> {
+        let this = self.eval_context_mut();
+        let timeout = with_socket(this, fd, |socket, _this| {
+            Ok(Ok(if send { socket.send_timeout } else { socket.recv_timeout }))
+        })?;
+        let now = this.machine.clock.now().duration_since(this.machine.clock.epoch());
+        Ok(timeout.ok().flatten().map(|timeout| now.saturating_add(timeout)))
+    }
+
+    fn deadline_passed(&self, deadline: Option<Duration>) -> bool {
+        let this = self.eval_context_ref();
+        let now = this.machine.clock.now().duration_since(this.machine.clock.epoch());
+        deadline.is_some_and(|deadline| now >= deadline)
+    }
+
+    /// Sets the last error to `errno` and returns -1.
+    fn socket_error(&mut self, errno: &str) -> InterpResult<'tcx, i32> {
+        let this = self.eval_context_mut();
+        let errno = this.eval_libc(errno);
+        this.set_last_error(errno)?;
+        Ok(-1)
+    }
+
+    fn socket_result(&mut self, result: SockResult<i32>) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+        match result {
+            Ok(value) => Ok(Scalar::from_i32(value)),
+            Err(errno) => Ok(Scalar::from_i32(this.socket_error(errno)?)),
+        }
+    }
+}
@@ -0,0 +1,808 @@
+//! Emulation of POSIX signals and of `alarm`.
+//!
+//! Signals are sent either to the whole process (`kill`, `alarm`) or to one thread (`raise`,
+//! `pthread_kill`). A process-directed signal goes to some thread that does not block it, or stays
+//! pending until a thread unblocks it. Handlers only run at *safe points*: when the thread that
+//! receives the signal is about to start a basic block, we push the stack frame of the handler
+//! and let it return to the beginning of that same block. In particular, a signal that a shim
+//! raises for the current thread is handled right after the shim returns.
+//!
+//! A thread that is blocked in `nanosleep`, `pause` or on I/O is woken up and its call fails with
+//! `EINTR`. If the handler was installed with `SA_RESTART` and the call is one that the kernel
+//! would restart, we simply let the call finish before the handler runs. Threads blocked on other
+//! things (e.g. a mutex) run the handler once they are unblocked.
+
+use std::cell::RefCell;
+use std::rc::Weak;
+use std::time::Duration;
+
+use rustc_data_structures::fx::{FxHashMap, FxHashSet};
+use rustc_index::Idx;
+use rustc_middle::mir;
+use rustc_target::abi::{Endian, Size};
+use rustc_target::spec::abi::Abi;
+
+use super::linux::timerfd::{EvalContextExt as _, TimerFdState};
+use crate::shims::unix::*;
+use crate::*;
+
+/// What happens when a signal is delivered.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum Disposition {
+    /// `SIG_DFL`
+    #[default]
+    Default,
+    /// `SIG_IGN`
+    Ignore,
+    /// The address of a handler function.
+    Handler(u64),
+}
+
+impl Disposition {
+    fn from_addr(addr: u64) -> Self {
+        match addr {
+            0 => Disposition::Default,
+            1 => Disposition::Ignore,
+            addr => Disposition::Handler(addr),
+        }
+    }
+
+    fn to_addr(self) -> u64 {
+        match self {
+            Disposition::Default => 0,
+            Disposition::Ignore => 1,
+            Disposition::Handler(addr) => addr,
+        }
+    }
+}
+
+/// What the system does with a signal that has no handler.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum DefaultAction {
+    Terminate,
+    Ignore,
+    Stop,
+}
+
+/// The action for a signal, as set by `sigaction`.
+#[derive(Clone, Copy, Debug, Default)]
+struct SignalAction {
+    disposition: Disposition,
+    flags: i32,
+    /// Signals to block while the handler runs.
+    mask: u64,
+}
+
+/// The signal state of the process. Sets of signals are bitmasks where signal `n` is bit `n - 1`.
+#[derive(Debug, Default)]
+pub struct SignalState {
+    /// The actions that differ from the default one.
+    actions: FxHashMap<i32, SignalAction>,
+    /// The signals blocked by each thread. Threads that are not in the map block nothing.
+    masks: FxHashMap<ThreadId, u64>,
+    /// Signals sent to a thread that were not delivered yet.
+    thread_pending: FxHashMap<ThreadId, u64>,
+    /// Signals sent to the process that every thread blocks.
+    process_pending: u64,
+    /// Threads whose blocking call got interrupted by a signal and has to fail with `EINTR`.
+    interrupted: FxHashSet<ThreadId>,
+    /// The timer used by `alarm`.
+    alarm: Option<TimerId>,
+}
+
+impl SignalState {
+    fn mask(&self, thread: ThreadId) -> u64 {
+        self.masks.get(&thread).copied().unwrap_or(0)
+    }
+
+    fn set_mask(&mut self, thread: ThreadId, mask: u64) {
+        self.masks.insert(thread, mask);
+    }
+
+    fn action(&self, signal: i32) -> SignalAction {
+        self.actions.get(&signal).copied().unwrap_or_default()
+    }
+
+    /// Signals that may be delivered to `thread`.
+    fn deliverable(&self, thread: ThreadId) -> u64 {
+        let pending = self.thread_pending.get(&thread).copied().unwrap_or(0) | self.process_pending;
+        pending & !self.mask(thread)
+    }
+
+    /// A new thread starts with the signal mask of the thread that created it.
+    pub fn thread_created(&mut self, parent: ThreadId, child: ThreadId) {
+        if let Some(mask) = self.masks.get(&parent).copied() {
+            self.masks.insert(child, mask);
+        }
+    }
+
+    /// Returns whether the blocking call of `thread` was interrupted, and resets that state.
+    pub fn take_interrupted(&mut self, thread: ThreadId) -> bool {
+        self.interrupted.remove(&thread)
+    }
+}
+
+impl VisitProvenance for SignalState {
+    fn visit_provenance(&self, _visit: &mut VisitWith<'_>) {
+        // Handlers are stored as addresses, which were exposed when the program cast them.
+    }
+}
+
+/// What to do when a timer expires.
+#[derive(Debug)]
+pub enum TimerEvent {
+    /// Send a signal to the process.
+    Signal(i32),
+    /// A `timerfd` expired.
+    TimerFd(Weak<RefCell<TimerFdState>>),
+}
+
+/// Extra data for the stack frame of a signal handler, to restore the state of the interrupted
+/// thread once the handler returns.
+#[derive(Debug)]
+pub struct SignalHandlerFrame {
+    /// The signal mask before the handler was invoked.
+    mask: u64,
+    /// The `siginfo_t` passed to handlers installed with `SA_SIGINFO`.
+    siginfo: Option<Pointer>,
+}
+
+impl VisitProvenance for SignalHandlerFrame {
+    fn visit_provenance(&self, visit: &mut VisitWith<'_>) {
+        self.siginfo.visit_provenance(visit);
+    }
+}
+
+impl<'tcx> EvalContextExtPriv<'tcx> for crate::MiriInterpCx<'tcx> {}
+trait EvalContextExtPriv<'tcx>: crate::MiriInterpCxExt<'tcx> {
+    /// Returns the bit of `signal` in a set of signals, or `None` if it is not a valid signal.
+    fn signal_bit(&self, signal: i32) -> Option<u64> {
+        let this = self.eval_context_ref();
+        // Linux has 64 signals, the other targets only 31 (more or less).
+        let max = if matches!(&*this.tcx.sess.target.os, "linux" | "android") { 64 } else { 31 };
+        if !(1..=max).contains(&signal) {
+            return None;
+        }
+        Some(1u64 << signal.strict_sub(1))
+    }
+
+    fn signal_name(&self, signal: i32) -> String {
+        let this = self.eval_context_ref();
+        const NAMES: &[&str] = &[
+            "SIGHUP",
+            "SIGINT",
+            "SIGQUIT",
+            "SIGILL",
+            "SIGTRAP",
+            "SIGABRT",
+            "SIGBUS",
+            "SIGFPE",
+            "SIGKILL",
+            "SIGUSR1",
+            "SIGSEGV",
+            "SIGUSR2",
+            "SIGPIPE",
+            "SIGALRM",
+            "SIGTERM",
+            "SIGCHLD",
+            "SIGCONT",
+            "SIGSTOP",
+            "SIGTSTP",
+            "SIGTTIN",
+            "SIGTTOU",
+            "SIGURG",
+            "SIGXCPU",
+            "SIGXFSZ",
+            "SIGVTALRM",
+            "SIGPROF",
+            "SIGWINCH",
+            "SIGIO",
+            "SIGSYS",
+        ];
+        NAMES
+            .iter()
+            .find(|name| this.eval_libc_i32(name) == signal)
+            .map_or_else(|| format!("signal {signal}"), |name| name.to_string())
+    }
+
+    fn default_signal_action(&self, signal: i32) -> DefaultAction {
+        let this = self.eval_context_ref();
+        let is_any = |names: &[&str]| names.iter().any(|name| this.eval_libc_i32(name) == signal);
+        if is_any(&["SIGCHLD", "SIGCONT", "SIGURG", "SIGWINCH"]) {
+            DefaultAction::Ignore
+        } else if is_any(&["SIGSTOP", "SIGTSTP", "SIGTTIN", "SIGTTOU"]) {
+            DefaultAction::Stop
+        } else {
+            DefaultAction::Terminate
+        }
+    }
+
+    /// Performs the default action of `signal` once it reaches a thread that does not block it.
+    fn default_signal_action_now(&mut self, signal: i32) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+        match this.default_signal_action(signal) {
+            DefaultAction::Ignore => Ok(()),
+            DefaultAction::Stop =>
+                throw_unsup_format!(
+                    "stopping the process with {} is not supported",
+                    this.signal_name(signal)
+                ),
+            DefaultAction::Terminate =>
+                throw_machine_stop!(TerminationInfo::Abort(format!(
+                    "the program was terminated by {}",
+                    this.signal_name(signal)
+                ))),
+        }
+    }
+
+    /// Returns the size of the words that make up a `sigset_t`.
+    fn sigset_word_size(&self) -> Size {
+        let this = self.eval_context_ref();
+        if matches!(&*this.tcx.sess.target.os, "linux" | "android") {
+            this.pointer_size()
+        } else {
+            Size::from_bytes(4)
+        }
+    }
+
+    /// Reads the first 64 signals of a `sigset_t`.
+    fn read_sigset(&self, set: &MPlaceTy<'tcx>) -> InterpResult<'tcx, u64> {
+        let this = self.eval_context_ref();
+        let size = set.layout.size.min(Size::from_bytes(8));
+        let bytes = this.read_bytes_ptr_strip_provenance(set.ptr(), size)?;
+        let word_size = usize::try_from(this.sigset_word_size().bytes()).unwrap();
+        // Put the words into little-endian order, so that we can read them as one `u64`.
+        let mut mask = [0u8; 8];
+        for (word, chunk) in mask.chunks_exact_mut(word_size).zip(bytes.chunks_exact(word_size)) {
+            word.copy_from_slice(chunk);
+            if this.tcx.sess.target.endian == Endian::Big {
+                word.reverse();
+            }
+        }
+        Ok(u64::from_le_bytes(mask))
+    }
+
+    /// Writes a `sigset_t` that contains the signals in `mask`.
+    fn write_sigset(&mut self, mask: u64, set: &MPlaceTy<'tcx>) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+        let size = usize::try_from(set.layout.size.bytes()).unwrap();
+        let word_size = usize::try_from(this.sigset_word_size().bytes()).unwrap();
+        let mut bytes = vec![0u8; size];
+        // The signals we know about fit into the first 8 bytes; everything after them stays 0.
+        let mask = mask.to_le_bytes();
+        for (word, chunk) in bytes.chunks_exact_mut(word_size).zip(mask.chunks_exact(word_size)) {
+            word.copy_from_slice(chunk);
+            if this.tcx.sess.target.endian == Endian::Big {
+                word.reverse();
+            }
+        }
+        this.write_bytes_ptr(set.ptr(), bytes)
+    }
+
+    /// Picks a thread to receive a signal sent to the process.
+    fn process_signal_target(&self, bit: u64) -> Option<ThreadId> {
+        let this = self.eval_context_ref();
+        let threads = &this.machine.threads;
+        // Prefer the thread that sent the signal, so that `kill(getpid(), ..)` behaves like
+        // `raise` in single-threaded programs.
+        std::iter::once(this.active_thread())
+            .chain((0..threads.get_total_thread_count()).map(ThreadId::new))
+            .find(|&thread| {
+                !threads.has_terminated(thread) && this.machine.signals.mask(thread) & bit == 0
+            })
+    }
+
+    /// Lets the blocking call of `thread` fail with `EINTR` so that it can handle a signal.
+    fn interrupt_blocking_call(&mut self, thread: ThreadId, restart: bool) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+        match this.machine.threads.get_block_reason(thread) {
+            Some(BlockReason::Sleep) => {
+                // `nanosleep` and `pause` are never restarted.
+                this.machine.signals.interrupted.insert(thread);
+                this.unblock_thread(thread, BlockReason::Sleep)
+            }
+            Some(BlockReason::Io) =>
+                if this.interrupt_io_wait(thread, restart) {
+                    this.machine.signals.interrupted.insert(thread);
+                    this.unblock_thread(thread, BlockReason::Io)
+                } else {
+                    Ok(())
+                },
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the block that the active thread is about to start if it is at a point where a
+    /// signal handler may run.
+    fn signal_safe_point(&self) -> Option<mir::BasicBlock> {
+        let this = self.eval_context_ref();
+        let frame = this.active_thread_stack().last()?;
+        let either::Either::Left(loc) = frame.current_loc() else {
+            return None;
+        };
+        // Do not interrupt cleanup code, which runs during unwinding.
+        if loc.statement_index != 0 || frame.body.basic_blocks[loc.block].is_cleanup {
+            return None;
+        }
+        Some(loc.block)
+    }
+
+    /// Changes the mask of the active thread as `sigprocmask` does, and returns the old mask.
+    fn change_signal_mask(
+        &mut self,
+        how: i32,
+        set: &OpTy<'tcx>,
+        oldset: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, Result<(), Scalar>> {
+        let this = self.eval_context_mut();
+        let thread = this.active_thread();
+        let old_mask = this.machine.signals.mask(thread);
+
+        let set = this.read_pointer(set)?;
+        let new_mask = if this.ptr_is_null(set)? {
+            old_mask
+        } else {
+            let set = this.ptr_to_mplace(set, this.libc_ty_layout("sigset_t"));
+            let set = this.read_sigset(&set)?;
+            if how == this.eval_libc_i32("SIG_BLOCK") {
+                old_mask | set
+            } else if how == this.eval_libc_i32("SIG_UNBLOCK") {
+                old_mask & !set
+            } else if how == this.eval_libc_i32("SIG_SETMASK") {
+                set
+            } else {
+                return Ok(Err(this.eval_libc("EINVAL")));
+            }
+        };
+        let oldset = this.read_pointer(oldset)?;
+        if !this.ptr_is_null(oldset)? {
+            let oldset = this.ptr_to_mplace(oldset, this.libc_ty_layout("sigset_t"));
+            this.write_sigset(old_mask, &oldset)?;
+        }
+
+        // `SIGKILL` and `SIGSTOP` cannot be blocked, and attempts to do so are silently ignored.
+        let unblockable = ["SIGKILL", "SIGSTOP"]
+            .iter()
+            .filter_map(|name| this.signal_bit(this.eval_libc_i32(name)))
+            .fold(0, |mask, bit| mask | bit);
+        this.machine.signals.set_mask(thread, new_mask & !unblockable);
+        // Signals that were unblocked are delivered at the next safe point.
+        Ok(Ok(()))
+    }
+}
+
+impl<'tcx> EvalContextExt<'tcx> for crate::MiriInterpCx<'tcx> {}
+pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
+    fn sigemptyset(&mut self, set: &OpTy<'tcx>) -> InterpResult<'tcx, i32> {
+        let this = self.eval_context_mut();
+        let set = this.deref_pointer_as(set, this.libc_ty_layout("sigset_t"))?;
+        this.write_sigset(0, &set)?;
+        Ok(0)
+    }
+
+    fn sigfillset(&mut self, set: &OpTy<'tcx>) -> InterpResult<'tcx, i32> {
+        let this = self.eval_context_mut();
+        let set = this.deref_pointer_as(set, this.libc_ty_layout("sigset_t"))?;
+        this.write_sigset(u64::MAX, &set)?;
+        Ok(0)
+    }
+
+    /// Implements `sigaddset`, `sigdelset` and `sigismember`, depending on `name`.
+    fn sigset_member_op(
+        &mut self,
+        name: &str,
+        set: &OpTy<'tcx>,
+        signal: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, i32> {
+        let this = self.eval_context_mut();
+        let set = this.deref_pointer_as(set, this.libc_ty_layout("sigset_t"))?;
+        let signal = this.read_scalar(signal)?.to_i32()?;
+        let Some(bit) = this.signal_bit(signal) else {
+            let einval = this.eval_libc("EINVAL");
+            this.set_last_error(einval)?;
+            return Ok(-1);
+        };
+        let mask = this.read_sigset(&set)?;
+        match name {
+            "sigaddset" => this.write_sigset(mask | bit, &set)?,
+            "sigdelset" => this.write_sigset(mask & !bit, &set)?,
+            "sigismember" => return Ok(i32::from(mask & bit != 0)),
+            _ => unreachable!(),
+        }
+        Ok(0)
+    }
+
+    fn sigaction(
+        &mut self,
+        signal: &OpTy<'tcx>,
+        act: &OpTy<'tcx>,
+        oldact: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, i32> {
+        let this = self.eval_context_mut();
+        let signal = this.read_scalar(signal)?.to_i32()?;
+        let act = this.read_pointer(act)?;
+        let oldact = this.read_pointer(oldact)?;
+
+        let unchangeable = [this.eval_libc_i32("SIGKILL"), this.eval_libc_i32("SIGSTOP")];
+        if this.signal_bit(signal).is_none()
+            || (!this.ptr_is_null(act)? && unchangeable.contains(&signal))
+        {
+            let einval = this.eval_libc("EINVAL");
+            this.set_last_error(einval)?;
+            return Ok(-1);
+        }
+
+        // Read the new action before writing the old one, they may be the same struct.
+        let layout = this.libc_ty_layout("sigaction");
+        let new_action = if this.ptr_is_null(act)? {
+            None
+        } else {
+            let act = this.ptr_to_mplace(act, layout);
+            let handler = this.project_field_named(&act, "sa_sigaction")?;
+            let handler = this.read_target_usize(&handler)?;
+            let mask = this.project_field_named(&act, "sa_mask")?;
+            let mask = this.read_sigset(&mask)?;
+            let flags = this.project_field_named(&act, "sa_flags")?;
+            let flags = this.read_scalar(&flags)?.to_i32()?;
+            Some(SignalAction { disposition: Disposition::from_addr(handler), flags, mask })
+        };
+
+        if !this.ptr_is_null(oldact)? {
+            let old_action = this.machine.signals.action(signal);
+            let oldact = this.ptr_to_mplace(oldact, layout);
+            // Zero the struct first, in case it has fields we do not know about (such as
+            // `sa_restorer`).
+            this.write_bytes_ptr(
+                oldact.ptr(),
+                std::iter::repeat(0u8).take(usize::try_from(layout.size.bytes()).unwrap()),
+            )?;
+            let handler = this.project_field_named(&oldact, "sa_sigaction")?;
+            this.write_int(old_action.disposition.to_addr(), &handler)?;
+            let mask = this.project_field_named(&oldact, "sa_mask")?;
+            this.write_sigset(old_action.mask, &mask)?;
+            let flags = this.project_field_named(&oldact, "sa_flags")?;
+            this.write_int(old_action.flags, &flags)?;
+        }
+
+        if let Some(action) = new_action {
+            this.machine.signals.actions.insert(signal, action);
+            if action.disposition == Disposition::Ignore
+                || (action.disposition == Disposition::Default
+                    && this.default_signal_action(signal) == DefaultAction::Ignore)
+            {
+                // Pending signals that are now ignored get discarded.
+                let bit = this.signal_bit(signal).unwrap();
+                let signals = &mut this.machine.signals;
+                signals.process_pending &= !bit;
+                signals.thread_pending.values_mut().for_each(|pending| *pending &= !bit);
+            }
+        }
+        Ok(0)
+    }
+
+    /// Implements `signal`, which installs a handler with BSD semantics (that is, with
+    /// `SA_RESTART`), and returns the previous one or `SIG_ERR`.
+    fn signal(&mut self, signal: &OpTy<'tcx>, handler: &OpTy<'tcx>) -> InterpResult<'tcx, u64> {
+        let this = self.eval_context_mut();
+        let signal = this.read_scalar(signal)?.to_i32()?;
+        let handler = this.read_target_usize(handler)?;
+
+        let unchangeable = [this.eval_libc_i32("SIGKILL"), this.eval_libc_i32("SIGSTOP")];
+        if this.signal_bit(signal).is_none() || unchangeable.contains(&signal) {
+            let einval = this.eval_libc("EINVAL");
+            this.set_last_error(einval)?;
+            // `SIG_ERR` is -1.
+            return Ok(this.target_usize_max());
+        }
+
+        let old_action = this.machine.signals.action(signal);
+        let action = SignalAction {
+            disposition: Disposition::from_addr(handler),
+            flags: this.eval_libc_i32("SA_RESTART"),
+            mask: 0,
+        };
+        this.machine.signals.actions.insert(signal, action);
+        Ok(old_action.disposition.to_addr())
+    }
+
+    fn sigprocmask(
+        &mut self,
+        how: &OpTy<'tcx>,
+        set: &OpTy<'tcx>,
+        oldset: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, i32> {
+        let this = self.eval_context_mut();
+        let how = this.read_scalar(how)?.to_i32()?;
+        match this.change_signal_mask(how, set, oldset)? {
+            Ok(()) => Ok(0),
+            Err(errno) => {
+                this.set_last_error(errno)?;
+                Ok(-1)
+            }
+        }
+    }
+
+    fn pthread_sigmask(
+        &mut self,
+        how: &OpTy<'tcx>,
+        set: &OpTy<'tcx>,
+        oldset: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+        let how = this.read_scalar(how)?.to_i32()?;
+        match this.change_signal_mask(how, set, oldset)? {
+            Ok(()) => Ok(Scalar::from_i32(0)),
+            Err(errno) => Ok(errno),
+        }
+    }
+
+    fn sigpending(&mut self, set: &OpTy<'tcx>) -> InterpResult<'tcx, i32> {
+        let this = self.eval_context_mut();
+        let set = this.deref_pointer_as(set, this.libc_ty_layout("sigset_t"))?;
+        let thread = this.active_thread();
+        let signals = &this.machine.signals;
+        let pending =
+            signals.thread_pending.get(&thread).copied().unwrap_or(0) | signals.process_pending;
+        // Only blocked signals can be pending, the others are delivered right away.
+        let pending = pending & signals.mask(thread);
+        this.write_sigset(pending, &set)?;
+        Ok(0)
+    }
+
+    fn raise(&mut self, signal: &OpTy<'tcx>) -> InterpResult<'tcx, i32> {
+        let this = self.eval_context_mut();
+        let signal = this.read_scalar(signal)?.to_i32()?;
+        if this.signal_bit(signal).is_none() {
+            let einval = this.eval_libc("EINVAL");
+            this.set_last_error(einval)?;
+            return Ok(-1);
+        }
+        this.send_signal(Some(this.active_thread()), signal)?;
+        Ok(0)
+    }
+
+    fn kill(&mut self, pid: &OpTy<'tcx>, signal: &OpTy<'tcx>) -> InterpResult<'tcx, i32> {
+        let this = self.eval_context_mut();
+        let pid = this.read_scalar(pid)?.to_int(this.libc_ty_layout("pid_t").size)?;
+        let signal = this.read_scalar(signal)?.to_i32()?;
+
+        // We are the only process in our process group, so sending to the group (0) is the same
+        // as sending to ourselves.
+        if pid != 0 && pid != i128::from(this.get_pid()) {
+            throw_unsup_format!("`kill`: sending signals to other processes is not supported");
+        }
+        if signal == 0 {
+            // Only checks whether the process exists.
+            return Ok(0);
+        }
+        if this.signal_bit(signal).is_none() {
+            let einval = this.eval_libc("EINVAL");
+            this.set_last_error(einval)?;
+            return Ok(-1);
+        }
+        this.send_signal(None, signal)?;
+        Ok(0)
+    }
+
+    fn pthread_kill(
+        &mut self,
+        thread: &OpTy<'tcx>,
+        signal: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+        let thread = this.read_scalar(thread)?.to_int(this.libc_ty_layout("pthread_t").size)?;
+        let signal = this.read_scalar(signal)?.to_i32()?;
+
+        let thread_count = this.machine.threads.get_total_thread_count();
+        let Some(thread) = ThreadId::try_from(thread).ok().filter(|&thread| {
+            usize::try_from(thread.to_u32()).unwrap() < thread_count
+                && !this.machine.threads.has_terminated(thread)
+        }) else {
+            return Ok(this.eval_libc("ESRCH"));
+        };
+        if signal == 0 {
+            return Ok(Scalar::from_i32(0));
+        }
+        if this.signal_bit(signal).is_none() {
+            return Ok(this.eval_libc("EINVAL"));
+        }
+        this.send_signal(Some(thread), signal)?;
+        Ok(Scalar::from_i32(0))
+    }
+
+    /// Arranges for `SIGALRM` to be sent to the process after `seconds`, and returns how many
+    /// seconds were left until the previous alarm.
+    fn alarm(&mut self, seconds: &OpTy<'tcx>) -> InterpResult<'tcx, u32> {
+        let this = self.eval_context_mut();
+        let seconds = this.read_scalar(seconds)?.to_u32()?;
+
+        let now = this.machine.clock.now().duration_since(this.machine.clock.epoch());
+        let timer =
+            *this.machine.signals.alarm.get_or_insert_with(|| this.machine.timers.new_timer());
+        let remaining = this
+            .machine
+            .timers
+            .disarm(timer)
+            .map_or(Duration::ZERO, |deadline| deadline.saturating_sub(now));
+        if seconds != 0 {
+            let deadline = now.saturating_add(Duration::from_secs(seconds.into()));
+            let sigalrm = this.eval_libc_i32("SIGALRM");
+            this.machine.timers.arm(timer, deadline, TimerEvent::Signal(sigalrm));
+        }
+
+        // Like Linux, round to the nearest second, but never report a pending alarm as 0.
+        if remaining.is_zero() {
+            return Ok(0);
+        }
+        let rounded = remaining.saturating_add(Duration::from_millis(500)).as_secs().max(1);
+        Ok(u32::try_from(rounded).unwrap_or(u32::MAX))
+    }
+
+    /// Blocks the active thread until a signal handler runs, then fails with `EINTR`.
+    fn pause(&mut self, dest: &MPlaceTy<'tcx>) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+        this.block_thread(
+            BlockReason::Sleep,
+            None,
+            callback!(
+                @capture<'tcx> {
+                    dest: MPlaceTy<'tcx>,
+                }
+                @unblock = |this| {
+                    let interrupted = this.machine.signals.take_interrupted(this.active_thread());
+                    assert!(interrupted, "paused thread unblocked without a signal");
+                    let eintr = this.eval_libc("EINTR");
+                    this.set_last_error(eintr)?;
+                    this.write_int(-1, &dest)
+                }
+                @timeout = |_this| { unreachable!("paused thread timed out") }
+            ),
+        );
+        Ok(())
+    }
+
+    /// Generates `signal` for `thread`, or for the process if `thread` is `None`.
+    fn send_signal(&mut self, thread: Option<ThreadId>, signal: i32) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+        let bit = this.signal_bit(signal).unwrap();
+        let action = this.machine.signals.action(signal);
+        let ignored = match action.disposition {
+            Disposition::Ignore => true,
+            Disposition::Default => this.default_signal_action(signal) == DefaultAction::Ignore,
+            Disposition::Handler(_) => false,
+        };
+        if ignored {
+            // Ignored signals are discarded right away, even if they are blocked.
+            return Ok(());
+        }
+
+        let target = match thread {
+            Some(thread) => {
+                *this.machine.signals.thread_pending.entry(thread).or_default() |= bit;
+                Some(thread).filter(|&thread| this.machine.signals.mask(thread) & bit == 0)
+            }
+            None => match this.process_signal_target(bit) {
+                Some(thread) => {
+                    *this.machine.signals.thread_pending.entry(thread).or_default() |= bit;
+                    Some(thread)
+                }
+                None => {
+                    this.machine.signals.process_pending |= bit;
+                    None
+                }
+            },
+        };
+        // If every candidate blocks the signal, it stays pending until one of them unblocks it.
+        let Some(target) = target else { return Ok(()) };
+
+        if action.disposition == Disposition::Default {
+            // The process would not wait for the target thread to reach a safe point.
+            return this.default_signal_action_now(signal);
+        }
+        let restart = action.flags & this.eval_libc_i32("SA_RESTART") != 0;
+        this.interrupt_blocking_call(target, restart)
+    }
+
+    /// Delivers a pending signal to the active thread if it is at a safe point. If a handler has
+    /// to run, its stack frame gets pushed.
+    fn deliver_pending_signal(&mut self) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+        let thread = this.active_thread();
+        let deliverable = this.machine.signals.deliverable(thread);
+        if deliverable == 0 {
+            return Ok(());
+        }
+        let Some(block) = this.signal_safe_point() else {
+            return Ok(());
+        };
+
+        // Deliver the lowest signal first, like Linux does.
+        let bit = deliverable & deliverable.wrapping_neg();
+        let signal = i32::try_from(bit.trailing_zeros()).unwrap().strict_add(1);
+        let signals = &mut this.machine.signals;
+        match signals.thread_pending.get_mut(&thread) {
+            Some(pending) if *pending & bit != 0 => {
+                *pending &= !bit;
+                if *pending == 0 {
+                    signals.thread_pending.remove(&thread);
+                }
+            }
+            _ => signals.process_pending &= !bit,
+        }
+
+        let action = signals.action(signal);
+        let addr = match action.disposition {
+            Disposition::Ignore => return Ok(()),
+            Disposition::Default => return this.default_signal_action_now(signal),
+            Disposition::Handler(addr) => addr,
+        };
+        if action.flags & this.eval_libc_i32("SA_RESETHAND") != 0 {
+            this.machine.signals.actions.remove(&signal);
+        }
+
+        // Block the signal itself and the ones from `sa_mask` while the handler runs.
+        let old_mask = this.machine.signals.mask(thread);
+        let mut mask = old_mask | action.mask;
+        if action.flags & this.eval_libc_i32("SA_NODEFER") == 0 {
+            mask |= bit;
+        }
+        this.machine.signals.set_mask(thread, mask);
+
+        let mut args = vec![Scalar::from_i32(signal).into()];
+        let siginfo = if action.flags & this.eval_libc_i32("SA_SIGINFO") != 0 {
+            let siginfo =
+                this.allocate(this.libc_ty_layout("siginfo_t"), MiriMemoryKind::Machine.into())?;
+            this.write_bytes_ptr(
+                siginfo.ptr(),
+                std::iter::repeat(0u8).take(usize::try_from(siginfo.layout.size.bytes()).unwrap()),
+            )?;
+            this.write_int_fields_named(&[("si_signo", signal.into())], &siginfo)?;
+            args.push(Scalar::from_maybe_pointer(siginfo.ptr(), this).into());
+            // We do not provide a `ucontext_t`.
+            args.push(Scalar::null_ptr(this).into());
+            Some(siginfo.ptr())
+        } else {
+            None
+        };
+
+        // The program cast the handler to an integer to store it in the `sigaction` struct, which
+        // exposed it. Casting back would emit a warning at whatever the thread is executing, so
+        // we construct the wildcard pointer directly.
+        let handler = Pointer::new(Some(Provenance::Wildcard), Size::from_bytes(addr));
+        let handler = this.get_ptr_fn(handler)?.as_instance()?;
+        this.call_function(
+            handler,
+            Abi::C { unwind: false },
+            &args,
+            None,
+            // Returning continues the interrupted thread where it was. Handlers may not unwind.
+            StackPopCleanup::Goto { ret: Some(block), unwind: mir::UnwindAction::Unreachable },
+        )?;
+        this.frame_mut().extra.signal_handler =
+            Some(SignalHandlerFrame { mask: old_mask, siginfo });
+        Ok(())
+    }
+
+    /// Restores the state of the thread after a signal handler returned.
+    fn return_from_signal_handler(&mut self, frame: SignalHandlerFrame) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+        let thread = this.active_thread();
+        this.machine.signals.set_mask(thread, frame.mask);
+        if let Some(siginfo) = frame.siginfo {
+            this.deallocate_ptr(siginfo, None, MiriMemoryKind::Machine.into())?;
+        }
+        Ok(())
+    }
+
+    /// Runs the events of all timers that expired.
+    fn expire_timers(&mut self) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+        while let Some((timer, event)) = this.machine.timers.pop_expired(&this.machine.clock) {
+            match event {
+                TimerEvent::Signal(signal) => this.send_signal(None, signal)?,
+                TimerEvent::TimerFd(state) => this.timerfd_expired(timer, state)?,
+            }
+        }
+        Ok(())
+    }
+}
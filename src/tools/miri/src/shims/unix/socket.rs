@@ -4,6 +4,7 @@
 use std::io::{Error, ErrorKind, Read};
 use std::rc::{Rc, Weak};
 
+use crate::shims::unix::linux::epoll::EpollReadyEvents;
 use crate::shims::unix::*;
 use crate::{concurrency::VClock, *};
 
@@ -14,7 +15,7 @@
 
 /// Pair of connected sockets.
 #[derive(Debug)]
-struct SocketPair {
+pub(super) struct SocketPair {
     // By making the write link weak, a `write` can detect when all readers are
     // gone, and trigger EPIPE as appropriate.
     writebuf: Weak<RefCell<Buffer>>,
@@ -49,6 +50,24 @@ fn close<'tcx>(
         Ok(Ok(()))
     }
 
+    fn get_epoll_ready_events<'tcx>(&self) -> InterpResult<'tcx, EpollReadyEvents> {
+        let readbuf = self.readbuf.borrow();
+        let mut ready = EpollReadyEvents {
+            epollin: !readbuf.buf.is_empty() || !readbuf.buf_has_writer,
+            epollrdhup: !readbuf.buf_has_writer,
+            ..EpollReadyEvents::default()
+        };
+        match self.writebuf.upgrade() {
+            Some(writebuf) =>
+                ready.epollout = writebuf.borrow().buf.len() < MAX_SOCKETPAIR_BUFFER_CAPACITY,
+            // All read ends of our write buffer are gone, so writing fails right away.
+            None => ready.epollout = true,
+        }
+        // The peer has closed the connection entirely.
+        ready.epollhup = !readbuf.buf_has_writer && self.writebuf.strong_count() == 0;
+        Ok(ready)
+    }
+
     fn read<'tcx>(
         &mut self,
         _communicate_allowed: bool,
@@ -0,0 +1,464 @@
+//! An in-memory file system, used instead of the host file system with `-Zmiri-virtual-fs`.
+//!
+//! At startup, a host directory is copied into memory and mounted at the same path. File system
+//! operations on paths below the mount point are then served from memory, so they work under
+//! isolation and are deterministic. Changes are never written back to the host. Paths outside of
+//! the mount point still go through the usual isolation checks.
+//!
+//! The virtual file system only has regular files and directories: symbolic links to files are
+//! replaced by copies of the files at startup. It does not track permissions or timestamps.
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::fs;
+use std::io::{self, ErrorKind, SeekFrom};
+use std::path::{Component, Path, PathBuf};
+use std::rc::Rc;
+
+use crate::shims::unix::*;
+use crate::*;
+
+/// A path relative to the mount point, as a list of file names.
+pub type VirtualPath = Vec<OsString>;
+
+#[derive(Debug)]
+pub struct VirtualFs {
+    /// The absolute path of the snapshotted host directory, where the virtual file system is
+    /// mounted. It is also the current directory of the program.
+    mount_point: PathBuf,
+    root: Node,
+    next_ino: u64,
+}
+
+#[derive(Debug)]
+enum Node {
+    File(Rc<RefCell<VirtualFile>>),
+    Dir(VirtualDir),
+}
+
+#[derive(Debug)]
+struct VirtualDir {
+    ino: u64,
+    entries: BTreeMap<OsString, Node>,
+}
+
+/// The contents of a file. Open files keep them alive even after the file has been unlinked.
+#[derive(Debug)]
+struct VirtualFile {
+    ino: u64,
+    data: Vec<u8>,
+}
+
+/// The information about a file or directory reported by `stat`.
+#[derive(Debug)]
+pub struct VirtualMetadata {
+    pub is_dir: bool,
+    pub size: u64,
+    pub ino: u64,
+}
+
+#[derive(Debug)]
+pub struct VirtualDirEntry {
+    pub name: OsString,
+    pub ino: u64,
+    pub is_dir: bool,
+}
+
+/// The subset of the `open` flags that matter to the virtual file system.
+#[derive(Debug, Default)]
+pub struct VirtualOpenOptions {
+    pub read: bool,
+    pub write: bool,
+    pub append: bool,
+    pub truncate: bool,
+    pub create: bool,
+    pub create_new: bool,
+}
+
+impl Node {
+    fn metadata(&self) -> VirtualMetadata {
+        match self {
+            Node::File(file) => {
+                let file = file.borrow();
+                VirtualMetadata {
+                    is_dir: false,
+                    size: u64::try_from(file.data.len()).unwrap(),
+                    ino: file.ino,
+                }
+            }
+            Node::Dir(dir) => VirtualMetadata { is_dir: true, size: 0, ino: dir.ino },
+        }
+    }
+}
+
+impl VirtualFs {
+    /// Copies the contents of the host directory `dir`, which must be an absolute path, into
+    /// memory.
+    pub fn snapshot(dir: &Path) -> io::Result<VirtualFs> {
+        assert!(dir.is_absolute(), "the virtual file system must be mounted at an absolute path");
+        let mut fs = VirtualFs {
+            mount_point: dir.to_owned(),
+            root: Node::Dir(VirtualDir { ino: 0, entries: BTreeMap::new() }),
+            next_ino: 1,
+        };
+        fs.root = Node::Dir(fs.snapshot_dir(dir)?);
+        Ok(fs)
+    }
+
+    fn new_ino(&mut self) -> u64 {
+        let ino = self.next_ino;
+        self.next_ino = ino.strict_add(1);
+        ino
+    }
+
+    fn snapshot_dir(&mut self, host_dir: &Path) -> io::Result<VirtualDir> {
+        let ino = self.new_ino();
+        // Sort the entries so that inode numbers do not depend on the order of the host.
+        let mut host_entries = fs::read_dir(host_dir)?.collect::<io::Result<Vec<_>>>()?;
+        host_entries.sort_by_key(|entry| entry.file_name());
+
+        let mut entries = BTreeMap::new();
+        for entry in host_entries {
+            let path = entry.path();
+            let file_type = entry.file_type()?;
+            let node = if file_type.is_dir() {
+                Node::Dir(self.snapshot_dir(&path)?)
+            } else if file_type.is_file() || (file_type.is_symlink() && path.is_file()) {
+                // Symbolic links to directories are skipped, as they might form cycles.
+                let data = fs::read(&path)?;
+                Node::File(Rc::new(RefCell::new(VirtualFile { ino: self.new_ino(), data })))
+            } else {
+                // Devices, sockets and the like cannot be copied.
+                continue;
+            };
+            entries.insert(entry.file_name(), node);
+        }
+        Ok(VirtualDir { ino, entries })
+    }
+
+    pub fn mount_point(&self) -> &Path {
+        &self.mount_point
+    }
+
+    /// Returns the path of `path` relative to the mount point, or `None` if it is outside of the
+    /// virtual file system. Relative paths are relative to the mount point, and `.` and `..` are
+    /// resolved lexically.
+    pub fn resolve(&self, path: &Path) -> Option<VirtualPath> {
+        if path.as_os_str().is_empty() {
+            return None;
+        }
+        let mut absolute = PathBuf::new();
+        for component in self.mount_point.join(path).components() {
+            match component {
+                Component::CurDir => {}
+                Component::ParentDir => {
+                    absolute.pop();
+                }
+                Component::Prefix(_) | Component::RootDir | Component::Normal(_) =>
+                    absolute.push(component),
+            }
+        }
+        let relative = absolute.strip_prefix(&self.mount_point).ok()?;
+        Some(relative.iter().map(ToOwned::to_owned).collect())
+    }
+
+    /// Returns the absolute path of `path`, as seen by the program.
+    pub fn absolute_path(&self, path: &[OsString]) -> PathBuf {
+        let mut absolute = self.mount_point.clone();
+        absolute.extend(path);
+        absolute
+    }
+
+    fn lookup(&self, path: &[OsString]) -> io::Result<&Node> {
+        let mut node = &self.root;
+        for name in path {
+            let Node::Dir(dir) = node else {
+                return Err(ErrorKind::NotADirectory.into());
+            };
+            node = dir.entries.get(name).ok_or(ErrorKind::NotFound)?;
+        }
+        Ok(node)
+    }
+
+    fn lookup_dir_mut(&mut self, path: &[OsString]) -> io::Result<&mut VirtualDir> {
+        let mut node = &mut self.root;
+        for name in path {
+            let Node::Dir(dir) = node else {
+                return Err(ErrorKind::NotADirectory.into());
+            };
+            node = dir.entries.get_mut(name).ok_or(ErrorKind::NotFound)?;
+        }
+        match node {
+            Node::Dir(dir) => Ok(dir),
+            Node::File(_) => Err(ErrorKind::NotADirectory.into()),
+        }
+    }
+
+    pub fn metadata(&self, path: &[OsString]) -> io::Result<VirtualMetadata> {
+        self.lookup(path).map(Node::metadata)
+    }
+
+    pub fn open(
+        &mut self,
+        path: &[OsString],
+        options: &VirtualOpenOptions,
+    ) -> io::Result<VirtualFileHandle> {
+        // Directories can only be opened with `opendir`.
+        let Some((name, parent)) = path.split_last() else {
+            return Err(ErrorKind::IsADirectory.into());
+        };
+        let ino = self.next_ino;
+        let dir = self.lookup_dir_mut(parent)?;
+        let file = match dir.entries.get(name) {
+            Some(_) if options.create_new => return Err(ErrorKind::AlreadyExists.into()),
+            Some(Node::Dir(_)) => return Err(ErrorKind::IsADirectory.into()),
+            Some(Node::File(file)) => Rc::clone(file),
+            None if options.create || options.create_new => {
+                let file = Rc::new(RefCell::new(VirtualFile { ino, data: Vec::new() }));
+                dir.entries.insert(name.clone(), Node::File(Rc::clone(&file)));
+                self.next_ino = ino.strict_add(1);
+                file
+            }
+            None => return Err(ErrorKind::NotFound.into()),
+        };
+        if options.truncate && options.write {
+            file.borrow_mut().data.clear();
+        }
+        Ok(VirtualFileHandle {
+            file,
+            position: 0,
+            readable: options.read,
+            writable: options.write,
+            append: options.append,
+        })
+    }
+
+    /// Returns the entries of the directory at `path`, sorted by name.
+    pub fn read_dir(&self, path: &[OsString]) -> io::Result<Vec<VirtualDirEntry>> {
+        let Node::Dir(dir) = self.lookup(path)? else {
+            return Err(ErrorKind::NotADirectory.into());
+        };
+        Ok(dir
+            .entries
+            .iter()
+            .map(|(name, node)| {
+                let metadata = node.metadata();
+                VirtualDirEntry { name: name.clone(), ino: metadata.ino, is_dir: metadata.is_dir }
+            })
+            .collect())
+    }
+
+    pub fn mkdir(&mut self, path: &[OsString]) -> io::Result<()> {
+        let Some((name, parent)) = path.split_last() else {
+            return Err(ErrorKind::AlreadyExists.into());
+        };
+        let ino = self.next_ino;
+        let dir = self.lookup_dir_mut(parent)?;
+        if dir.entries.contains_key(name) {
+            return Err(ErrorKind::AlreadyExists.into());
+        }
+        dir.entries.insert(name.clone(), Node::Dir(VirtualDir { ino, entries: BTreeMap::new() }));
+        self.next_ino = ino.strict_add(1);
+        Ok(())
+    }
+
+    pub fn rmdir(&mut self, path: &[OsString]) -> io::Result<()> {
+        let Some((name, parent)) = path.split_last() else {
+            // The mount point cannot be removed.
+            return Err(ErrorKind::ResourceBusy.into());
+        };
+        let dir = self.lookup_dir_mut(parent)?;
+        match dir.entries.get(name) {
+            None => return Err(ErrorKind::NotFound.into()),
+            Some(Node::File(_)) => return Err(ErrorKind::NotADirectory.into()),
+            Some(Node::Dir(removed)) if !removed.entries.is_empty() =>
+                return Err(ErrorKind::DirectoryNotEmpty.into()),
+            Some(Node::Dir(_)) => {}
+        }
+        dir.entries.remove(name);
+        Ok(())
+    }
+
+    pub fn unlink(&mut self, path: &[OsString]) -> io::Result<()> {
+        let Some((name, parent)) = path.split_last() else {
+            return Err(ErrorKind::IsADirectory.into());
+        };
+        let dir = self.lookup_dir_mut(parent)?;
+        match dir.entries.get(name) {
+            None => return Err(ErrorKind::NotFound.into()),
+            Some(Node::Dir(_)) => return Err(ErrorKind::IsADirectory.into()),
+            Some(Node::File(_)) => {}
+        }
+        dir.entries.remove(name);
+        Ok(())
+    }
+
+    pub fn rename(&mut self, from: &[OsString], to: &[OsString]) -> io::Result<()> {
+        let (Some((from_name, from_parent)), Some((to_name, to_parent))) =
+            (from.split_last(), to.split_last())
+        else {
+            // The mount point cannot be moved, and nothing can replace it.
+            return Err(ErrorKind::ResourceBusy.into());
+        };
+        // Check that the rename can be done before changing anything.
+        let from_is_dir = self.metadata(from)?.is_dir;
+        if from == to {
+            return Ok(());
+        }
+        if from_is_dir && to.starts_with(from) {
+            // A directory cannot be moved into itself.
+            return Err(ErrorKind::InvalidInput.into());
+        }
+        match self.lookup_dir_mut(to_parent)?.entries.get(to_name) {
+            Some(Node::Dir(_)) if !from_is_dir => return Err(ErrorKind::IsADirectory.into()),
+            Some(Node::File(_)) if from_is_dir => return Err(ErrorKind::NotADirectory.into()),
+            Some(Node::Dir(replaced)) if !replaced.entries.is_empty() =>
+                return Err(ErrorKind::DirectoryNotEmpty.into()),
+            _ => {}
+        }
+
+        let node = self.lookup_dir_mut(from_parent)?.entries.remove(from_name).unwrap();
+        self.lookup_dir_mut(to_parent)?.entries.insert(to_name.clone(), node);
+        Ok(())
+    }
+}
+
+/// An open file of the virtual file system.
+#[derive(Debug)]
+pub struct VirtualFileHandle {
+    file: Rc<RefCell<VirtualFile>>,
+    position: u64,
+    readable: bool,
+    writable: bool,
+    append: bool,
+}
+
+impl VirtualFileHandle {
+    pub fn metadata(&self) -> VirtualMetadata {
+        let file = self.file.borrow();
+        VirtualMetadata { is_dir: false, size: u64::try_from(file.data.len()).unwrap(), ino: file.ino }
+    }
+
+    pub fn is_writable(&self) -> bool {
+        self.writable
+    }
+
+    pub fn set_len(&mut self, len: u64) -> io::Result<()> {
+        let len = usize::try_from(len).map_err(|_| ErrorKind::FileTooLarge)?;
+        self.file.borrow_mut().data.resize(len, 0);
+        Ok(())
+    }
+
+    fn read_at(&self, bytes: &mut [u8], offset: u64) -> usize {
+        let file = self.file.borrow();
+        let Ok(offset) = usize::try_from(offset) else { return 0 };
+        let available = file.data.get(offset..).unwrap_or_default();
+        let len = bytes.len().min(available.len());
+        bytes[..len].copy_from_slice(&available[..len]);
+        len
+    }
+
+    fn write_at(&self, bytes: &[u8], offset: u64) -> io::Result<usize> {
+        let mut file = self.file.borrow_mut();
+        let start = usize::try_from(offset).map_err(|_| ErrorKind::FileTooLarge)?;
+        let end = start.checked_add(bytes.len()).ok_or(ErrorKind::FileTooLarge)?;
+        if file.data.len() < end {
+            // Writing past the end fills the gap with zeros.
+            file.data.resize(end, 0);
+        }
+        file.data[start..end].copy_from_slice(bytes);
+        Ok(bytes.len())
+    }
+}
+
+impl FileDescription for VirtualFileHandle {
+    fn name(&self) -> &'static str {
+        "file"
+    }
+
+    fn read<'tcx>(
+        &mut self,
+        _communicate_allowed: bool,
+        bytes: &mut [u8],
+        _ecx: &mut MiriInterpCx<'tcx>,
+    ) -> InterpResult<'tcx, io::Result<usize>> {
+        if !self.readable {
+            return Ok(Err(LibcError::new("EBADF")));
+        }
+        let len = self.read_at(bytes, self.position);
+        self.position = self.position.strict_add(u64::try_from(len).unwrap());
+        Ok(Ok(len))
+    }
+
+    fn write<'tcx>(
+        &mut self,
+        _communicate_allowed: bool,
+        bytes: &[u8],
+        _ecx: &mut MiriInterpCx<'tcx>,
+    ) -> InterpResult<'tcx, io::Result<usize>> {
+        if !self.writable {
+            return Ok(Err(LibcError::new("EBADF")));
+        }
+        if self.append {
+            self.position = u64::try_from(self.file.borrow().data.len()).unwrap();
+        }
+        let result = self.write_at(bytes, self.position);
+        if let Ok(len) = result {
+            self.position = self.position.strict_add(u64::try_from(len).unwrap());
+        }
+        Ok(result)
+    }
+
+    fn pread<'tcx>(
+        &mut self,
+        _communicate_allowed: bool,
+        bytes: &mut [u8],
+        offset: u64,
+        _ecx: &mut MiriInterpCx<'tcx>,
+    ) -> InterpResult<'tcx, io::Result<usize>> {
+        if !self.readable {
+            return Ok(Err(LibcError::new("EBADF")));
+        }
+        Ok(Ok(self.read_at(bytes, offset)))
+    }
+
+    fn pwrite<'tcx>(
+        &mut self,
+        _communicate_allowed: bool,
+        bytes: &[u8],
+        offset: u64,
+        _ecx: &mut MiriInterpCx<'tcx>,
+    ) -> InterpResult<'tcx, io::Result<usize>> {
+        if !self.writable {
+            return Ok(Err(LibcError::new("EBADF")));
+        }
+        Ok(self.write_at(bytes, offset))
+    }
+
+    fn seek<'tcx>(
+        &mut self,
+        _communicate_allowed: bool,
+        offset: SeekFrom,
+    ) -> InterpResult<'tcx, io::Result<u64>> {
+        let position = match offset {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::Current(offset) => self.position.checked_add_signed(offset),
+            SeekFrom::End(offset) =>
+                u64::try_from(self.file.borrow().data.len()).unwrap().checked_add_signed(offset),
+        };
+        // Seeking before the start of the file fails with EINVAL.
+        let Some(position) = position else {
+            return Ok(Err(ErrorKind::InvalidInput.into()));
+        };
+        self.position = position;
+        Ok(Ok(position))
+    }
+
+    fn close<'tcx>(
+        self: Box<Self>,
+        _communicate_allowed: bool,
+    ) -> InterpResult<'tcx, io::Result<()>> {
+        Ok(Ok(()))
+    }
+}
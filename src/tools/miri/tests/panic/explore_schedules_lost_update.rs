@@ -0,0 +1,25 @@
+//@compile-flags: -Zmiri-explore-schedules=1
+//@normalize-stderr-test: "schedule [0-9]+ of" -> "schedule $$N of"
+//@normalize-stderr-test: "-Zmiri-replay-schedule=[0-9:,]*" -> "-Zmiri-replay-schedule=$$SCHEDULE"
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+fn main() {
+    // The increment is not atomic, so an update gets lost if the other thread runs between the
+    // load and the store. A single preemption is enough to find that schedule.
+    let threads: Vec<_> = (0..2)
+        .map(|_| {
+            thread::spawn(|| {
+                let value = COUNTER.load(Ordering::Relaxed);
+                COUNTER.store(value + 1, Ordering::Relaxed);
+            })
+        })
+        .collect();
+    for thread in threads {
+        thread.join().unwrap();
+    }
+    assert_eq!(COUNTER.load(Ordering::Relaxed), 2);
+}
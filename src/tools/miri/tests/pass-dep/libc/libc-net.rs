@@ -0,0 +1,391 @@
+//@ignore-target-windows: No libc sockets on Windows
+//@ignore-target-solaris: Sockets are not supported on Solaris
+//@ignore-target-illumos: Sockets are not supported on illumos
+// test_tcp_blocking depends on a deterministic schedule.
+//@compile-flags: -Zmiri-preemption-rate=0
+
+use std::mem;
+use std::thread;
+
+fn main() {
+    test_tcp();
+    test_tcp_blocking();
+    test_tcp_nonblocking();
+    test_tcp_shutdown();
+    test_udp();
+    test_errors();
+    test_sockopt();
+    #[cfg(target_os = "linux")]
+    test_epoll();
+}
+
+fn errno() -> i32 {
+    std::io::Error::last_os_error().raw_os_error().unwrap()
+}
+
+fn loopback(port: u16) -> libc::sockaddr_in {
+    let mut addr: libc::sockaddr_in = unsafe { mem::zeroed() };
+    addr.sin_family = libc::AF_INET as libc::sa_family_t;
+    addr.sin_port = port.to_be();
+    addr.sin_addr = libc::in_addr { s_addr: u32::from_be_bytes([127, 0, 0, 1]).to_be() };
+    addr
+}
+
+fn socket(type_: i32) -> i32 {
+    let fd = unsafe { libc::socket(libc::AF_INET, type_, 0) };
+    assert!(fd >= 0);
+    fd
+}
+
+fn bind(fd: i32, addr: &libc::sockaddr_in) -> i32 {
+    let len = mem::size_of::<libc::sockaddr_in>() as libc::socklen_t;
+    unsafe { libc::bind(fd, (addr as *const libc::sockaddr_in).cast(), len) }
+}
+
+fn connect(fd: i32, addr: &libc::sockaddr_in) -> i32 {
+    let len = mem::size_of::<libc::sockaddr_in>() as libc::socklen_t;
+    unsafe { libc::connect(fd, (addr as *const libc::sockaddr_in).cast(), len) }
+}
+
+fn local_addr(fd: i32) -> libc::sockaddr_in {
+    let mut addr: libc::sockaddr_in = unsafe { mem::zeroed() };
+    let mut len = mem::size_of::<libc::sockaddr_in>() as libc::socklen_t;
+    let res =
+        unsafe { libc::getsockname(fd, (&mut addr as *mut libc::sockaddr_in).cast(), &mut len) };
+    assert_eq!(res, 0);
+    assert_eq!(len as usize, mem::size_of::<libc::sockaddr_in>());
+    addr
+}
+
+/// Returns a listening socket and its address.
+fn listener() -> (i32, libc::sockaddr_in) {
+    let fd = socket(libc::SOCK_STREAM);
+    assert_eq!(bind(fd, &loopback(0)), 0);
+    assert_eq!(unsafe { libc::listen(fd, 16) }, 0);
+    let addr = local_addr(fd);
+    (fd, addr)
+}
+
+fn send(fd: i32, data: &[u8]) -> isize {
+    unsafe { libc::send(fd, data.as_ptr().cast(), data.len(), 0) }
+}
+
+/// Like `send`, but fails with EPIPE instead of raising `SIGPIPE` on a broken connection.
+fn send_nosignal(fd: i32, data: &[u8]) -> isize {
+    #[cfg(not(target_os = "macos"))]
+    let flags = libc::MSG_NOSIGNAL;
+    // Miri never raises `SIGPIPE`.
+    #[cfg(target_os = "macos")]
+    let flags = 0;
+    unsafe { libc::send(fd, data.as_ptr().cast(), data.len(), flags) }
+}
+
+fn recv(fd: i32, buf: &mut [u8], flags: i32) -> isize {
+    unsafe { libc::recv(fd, buf.as_mut_ptr().cast(), buf.len(), flags) }
+}
+
+fn test_tcp() {
+    let (listener, addr) = listener();
+    assert_eq!(addr.sin_addr.s_addr, loopback(0).sin_addr.s_addr);
+    assert_ne!(addr.sin_port, 0);
+
+    let client = socket(libc::SOCK_STREAM);
+    assert_eq!(connect(client, &addr), 0);
+
+    let mut peer: libc::sockaddr_in = unsafe { mem::zeroed() };
+    let mut len = mem::size_of::<libc::sockaddr_in>() as libc::socklen_t;
+    let server =
+        unsafe { libc::accept(listener, (&mut peer as *mut libc::sockaddr_in).cast(), &mut len) };
+    assert!(server >= 0);
+    // The peer of the server end is the client, and the other way round.
+    assert_eq!(peer.sin_port, local_addr(client).sin_port);
+    let mut client_peer: libc::sockaddr_in = unsafe { mem::zeroed() };
+    let res = unsafe {
+        libc::getpeername(client, (&mut client_peer as *mut libc::sockaddr_in).cast(), &mut len)
+    };
+    assert_eq!(res, 0);
+    assert_eq!(client_peer.sin_port, addr.sin_port);
+
+    assert_eq!(send(client, b"hello"), 5);
+    let mut buf = [0; 8];
+    // Peeking leaves the data in place.
+    assert_eq!(recv(server, &mut buf, libc::MSG_PEEK), 5);
+    assert_eq!(recv(server, &mut buf[..3], 0), 3);
+    assert_eq!(&buf[..3], b"hel");
+    assert_eq!(recv(server, &mut buf, 0), 2);
+    assert_eq!(&buf[..2], b"lo");
+
+    // `read` and `write` work on connected sockets too.
+    let res = unsafe { libc::write(server, b"world".as_ptr().cast(), 5) };
+    assert_eq!(res, 5);
+    let res = unsafe { libc::read(client, buf.as_mut_ptr().cast(), buf.len()) };
+    assert_eq!(res, 5);
+    assert_eq!(&buf[..5], b"world");
+
+    // Closing one end signals EOF to the other one.
+    assert_eq!(unsafe { libc::close(client) }, 0);
+    assert_eq!(recv(server, &mut buf, 0), 0);
+    assert_eq!(send_nosignal(server, b"x"), -1);
+    assert_eq!(errno(), libc::EPIPE);
+
+    assert_eq!(unsafe { libc::close(server) }, 0);
+    assert_eq!(unsafe { libc::close(listener) }, 0);
+}
+
+fn test_tcp_blocking() {
+    let (listener, addr) = listener();
+    let server = thread::spawn(move || {
+        // This blocks until the client connects.
+        let server = unsafe { libc::accept(listener, std::ptr::null_mut(), std::ptr::null_mut()) };
+        assert!(server >= 0);
+        // This blocks until the client sends something.
+        let mut buf = [0; 4];
+        assert_eq!(recv(server, &mut buf, 0), 4);
+        assert_eq!(&buf, b"ping");
+        assert_eq!(send(server, b"pong"), 4);
+        server
+    });
+    thread::yield_now();
+
+    let client = socket(libc::SOCK_STREAM);
+    assert_eq!(connect(client, &addr), 0);
+    assert_eq!(send(client, b"ping"), 4);
+    let mut buf = [0; 4];
+    assert_eq!(recv(client, &mut buf, 0), 4);
+    assert_eq!(&buf, b"pong");
+    let server = server.join().unwrap();
+
+    unsafe {
+        assert_eq!(libc::close(client), 0);
+        assert_eq!(libc::close(server), 0);
+        assert_eq!(libc::close(listener), 0);
+    }
+}
+
+fn test_tcp_nonblocking() {
+    let (listener, addr) = listener();
+    let mut nonblock: libc::c_int = 1;
+    assert_eq!(unsafe { libc::ioctl(listener, libc::FIONBIO, &mut nonblock) }, 0);
+    let res = unsafe { libc::accept(listener, std::ptr::null_mut(), std::ptr::null_mut()) };
+    assert_eq!(res, -1);
+    assert_eq!(errno(), libc::EAGAIN);
+
+    let client = socket(libc::SOCK_STREAM);
+    assert_eq!(connect(client, &addr), 0);
+    let server = unsafe { libc::accept(listener, std::ptr::null_mut(), std::ptr::null_mut()) };
+    assert!(server >= 0);
+
+    // The accepted socket is blocking, but `MSG_DONTWAIT` makes a single call nonblocking.
+    let mut buf = [0; 4];
+    assert_eq!(recv(server, &mut buf, libc::MSG_DONTWAIT), -1);
+    assert_eq!(errno(), libc::EAGAIN);
+
+    unsafe {
+        assert_eq!(libc::close(client), 0);
+        assert_eq!(libc::close(server), 0);
+        assert_eq!(libc::close(listener), 0);
+    }
+}
+
+fn test_tcp_shutdown() {
+    let (listener, addr) = listener();
+    let client = socket(libc::SOCK_STREAM);
+    assert_eq!(connect(client, &addr), 0);
+    let server = unsafe { libc::accept(listener, std::ptr::null_mut(), std::ptr::null_mut()) };
+    assert!(server >= 0);
+
+    assert_eq!(send(client, b"bye"), 3);
+    assert_eq!(unsafe { libc::shutdown(client, libc::SHUT_WR) }, 0);
+    assert_eq!(send_nosignal(client, b"x"), -1);
+    assert_eq!(errno(), libc::EPIPE);
+
+    // The buffered data is still received before EOF.
+    let mut buf = [0; 8];
+    assert_eq!(recv(server, &mut buf, 0), 3);
+    assert_eq!(recv(server, &mut buf, 0), 0);
+    // The other direction still works.
+    assert_eq!(send(server, b"ok"), 2);
+    assert_eq!(recv(client, &mut buf, 0), 2);
+
+    assert_eq!(unsafe { libc::shutdown(listener, libc::SHUT_RDWR) }, -1);
+    assert_eq!(errno(), libc::ENOTCONN);
+
+    unsafe {
+        assert_eq!(libc::close(client), 0);
+        assert_eq!(libc::close(server), 0);
+        assert_eq!(libc::close(listener), 0);
+    }
+}
+
+fn test_udp() {
+    let a = socket(libc::SOCK_DGRAM);
+    let b = socket(libc::SOCK_DGRAM);
+    assert_eq!(bind(a, &loopback(0)), 0);
+    assert_eq!(bind(b, &loopback(0)), 0);
+    let (a_addr, b_addr) = (local_addr(a), local_addr(b));
+    let len = mem::size_of::<libc::sockaddr_in>() as libc::socklen_t;
+
+    let res = unsafe {
+        libc::sendto(
+            a,
+            b"abc".as_ptr().cast(),
+            3,
+            0,
+            (&b_addr as *const libc::sockaddr_in).cast(),
+            len,
+        )
+    };
+    assert_eq!(res, 3);
+
+    let mut buf = [0; 8];
+    let mut from: libc::sockaddr_in = unsafe { mem::zeroed() };
+    let mut from_len = len;
+    let res = unsafe {
+        libc::recvfrom(
+            b,
+            buf.as_mut_ptr().cast(),
+            buf.len(),
+            0,
+            (&mut from as *mut libc::sockaddr_in).cast(),
+            &mut from_len,
+        )
+    };
+    assert_eq!(res, 3);
+    assert_eq!(&buf[..3], b"abc");
+    assert_eq!(from.sin_port, a_addr.sin_port);
+
+    // Datagrams are truncated to the buffer, and the rest is discarded.
+    assert_eq!(connect(a, &b_addr), 0);
+    assert_eq!(send(a, b"abcdef"), 6);
+    assert_eq!(send(a, b"gh"), 2);
+    assert_eq!(recv(b, &mut buf[..4], 0), 4);
+    assert_eq!(&buf[..4], b"abcd");
+    assert_eq!(recv(b, &mut buf, 0), 2);
+    assert_eq!(&buf[..2], b"gh");
+    assert_eq!(recv(b, &mut buf, libc::MSG_DONTWAIT), -1);
+    assert_eq!(errno(), libc::EAGAIN);
+
+    unsafe {
+        assert_eq!(libc::close(a), 0);
+        assert_eq!(libc::close(b), 0);
+    }
+}
+
+fn test_errors() {
+    let (listener, addr) = listener();
+
+    // The address is in use.
+    let other = socket(libc::SOCK_STREAM);
+    assert_eq!(bind(other, &addr), -1);
+    assert_eq!(errno(), libc::EADDRINUSE);
+
+    // Only loopback addresses are available.
+    let mut remote = loopback(80);
+    remote.sin_addr = libc::in_addr { s_addr: u32::from_be_bytes([192, 0, 2, 1]).to_be() };
+    assert_eq!(bind(other, &remote), -1);
+    assert_eq!(errno(), libc::EADDRNOTAVAIL);
+    assert_eq!(connect(other, &remote), -1);
+    assert_eq!(errno(), libc::ENETUNREACH);
+
+    // Nobody listens on this port.
+    assert_eq!(unsafe { libc::close(listener) }, 0);
+    assert_eq!(connect(other, &addr), -1);
+    assert_eq!(errno(), libc::ECONNREFUSED);
+
+    // Not a socket.
+    assert_eq!(unsafe { libc::listen(libc::STDOUT_FILENO, 1) }, -1);
+    assert_eq!(errno(), libc::ENOTSOCK);
+    assert_eq!(unsafe { libc::listen(-1, 1) }, -1);
+    assert_eq!(errno(), libc::EBADF);
+
+    assert_eq!(unsafe { libc::close(other) }, 0);
+}
+
+fn test_sockopt() {
+    let fd = socket(libc::SOCK_STREAM);
+
+    let one: libc::c_int = 1;
+    let int_len = mem::size_of::<libc::c_int>() as libc::socklen_t;
+    let res = unsafe {
+        libc::setsockopt(
+            fd,
+            libc::IPPROTO_TCP,
+            libc::TCP_NODELAY,
+            (&one as *const i32).cast(),
+            int_len,
+        )
+    };
+    assert_eq!(res, 0);
+    let mut value: libc::c_int = 0;
+    let mut len = int_len;
+    let res = unsafe {
+        libc::getsockopt(
+            fd,
+            libc::IPPROTO_TCP,
+            libc::TCP_NODELAY,
+            (&mut value as *mut i32).cast(),
+            &mut len,
+        )
+    };
+    assert_eq!(res, 0);
+    assert_eq!(value, 1);
+
+    let timeout = libc::timeval { tv_sec: 1, tv_usec: 500_000 };
+    let timeval_len = mem::size_of::<libc::timeval>() as libc::socklen_t;
+    let res = unsafe {
+        libc::setsockopt(
+            fd,
+            libc::SOL_SOCKET,
+            libc::SO_RCVTIMEO,
+            (&timeout as *const libc::timeval).cast(),
+            timeval_len,
+        )
+    };
+    assert_eq!(res, 0);
+    let mut read_back = libc::timeval { tv_sec: 0, tv_usec: 0 };
+    let mut len = timeval_len;
+    let res = unsafe {
+        libc::getsockopt(
+            fd,
+            libc::SOL_SOCKET,
+            libc::SO_RCVTIMEO,
+            (&mut read_back as *mut libc::timeval).cast(),
+            &mut len,
+        )
+    };
+    assert_eq!(res, 0);
+    assert_eq!((read_back.tv_sec, read_back.tv_usec), (1, 500_000));
+
+    assert_eq!(unsafe { libc::close(fd) }, 0);
+}
+
+#[cfg(target_os = "linux")]
+fn test_epoll() {
+    let (listener, addr) = listener();
+    let epfd = unsafe { libc::epoll_create1(0) };
+    assert!(epfd >= 0);
+    let mut event = libc::epoll_event { events: libc::EPOLLIN as u32, u64: 42 };
+    assert_eq!(unsafe { libc::epoll_ctl(epfd, libc::EPOLL_CTL_ADD, listener, &mut event) }, 0);
+
+    let mut events = [libc::epoll_event { events: 0, u64: 0 }; 4];
+    let res = unsafe { libc::epoll_wait(epfd, events.as_mut_ptr(), 4, 0) };
+    assert_eq!(res, 0);
+
+    let client = thread::spawn(move || {
+        let client = socket(libc::SOCK_STREAM);
+        assert_eq!(connect(client, &addr), 0);
+        client
+    });
+    // This blocks until the listener has a pending connection.
+    let res = unsafe { libc::epoll_wait(epfd, events.as_mut_ptr(), 4, -1) };
+    assert_eq!(res, 1);
+    assert_eq!({ events[0].u64 }, 42);
+    assert_eq!({ events[0].events }, libc::EPOLLIN as u32);
+    let client = client.join().unwrap();
+
+    unsafe {
+        assert_eq!(libc::close(client), 0);
+        assert_eq!(libc::close(listener), 0);
+        assert_eq!(libc::close(epfd), 0);
+    }
+}
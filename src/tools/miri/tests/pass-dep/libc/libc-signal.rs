@@ -0,0 +1,177 @@
+//@ignore-target-windows: no signals on Windows
+// The tests that interrupt a sleeping thread depend on a deterministic schedule.
+//@compile-flags: -Zmiri-preemption-rate=0
+
+use std::os::unix::thread::JoinHandleExt;
+use std::sync::atomic::{AtomicI32, AtomicUsize, Ordering};
+use std::{io, mem, ptr, thread};
+
+static HANDLED: AtomicUsize = AtomicUsize::new(0);
+static LAST_SIGNAL: AtomicI32 = AtomicI32::new(0);
+
+extern "C" fn handler(signal: libc::c_int) {
+    HANDLED.fetch_add(1, Ordering::Relaxed);
+    LAST_SIGNAL.store(signal, Ordering::Relaxed);
+}
+
+extern "C" fn siginfo_handler(
+    signal: libc::c_int,
+    info: *mut libc::siginfo_t,
+    _context: *mut libc::c_void,
+) {
+    assert_eq!(unsafe { (*info).si_signo }, signal);
+    handler(signal);
+}
+
+fn set_action(signal: libc::c_int, handler: libc::sighandler_t, flags: libc::c_int) {
+    unsafe {
+        let mut action: libc::sigaction = mem::zeroed();
+        action.sa_sigaction = handler;
+        action.sa_flags = flags;
+        libc::sigemptyset(&mut action.sa_mask);
+        assert_eq!(libc::sigaction(signal, &action, ptr::null_mut()), 0);
+    }
+}
+
+fn handled() -> usize {
+    HANDLED.swap(0, Ordering::Relaxed)
+}
+
+fn test_sigset() {
+    unsafe {
+        let mut set: libc::sigset_t = mem::zeroed();
+        assert_eq!(libc::sigemptyset(&mut set), 0);
+        assert_eq!(libc::sigismember(&set, libc::SIGUSR1), 0);
+        assert_eq!(libc::sigaddset(&mut set, libc::SIGUSR1), 0);
+        assert_eq!(libc::sigismember(&set, libc::SIGUSR1), 1);
+        assert_eq!(libc::sigismember(&set, libc::SIGUSR2), 0);
+        assert_eq!(libc::sigdelset(&mut set, libc::SIGUSR1), 0);
+        assert_eq!(libc::sigismember(&set, libc::SIGUSR1), 0);
+        assert_eq!(libc::sigfillset(&mut set), 0);
+        assert_eq!(libc::sigismember(&set, libc::SIGUSR2), 1);
+
+        assert_eq!(libc::sigaddset(&mut set, 0), -1);
+        assert_eq!(io::Error::last_os_error().raw_os_error(), Some(libc::EINVAL));
+    }
+}
+
+fn test_raise() {
+    set_action(libc::SIGUSR1, handler as libc::sighandler_t, 0);
+    assert_eq!(unsafe { libc::raise(libc::SIGUSR1) }, 0);
+    // The handler runs as soon as `raise` returns.
+    assert_eq!(handled(), 1);
+    assert_eq!(LAST_SIGNAL.load(Ordering::Relaxed), libc::SIGUSR1);
+
+    assert_eq!(unsafe { libc::kill(libc::getpid(), libc::SIGUSR1) }, 0);
+    assert_eq!(handled(), 1);
+    // Signal 0 only checks that the process exists.
+    assert_eq!(unsafe { libc::kill(libc::getpid(), 0) }, 0);
+    assert_eq!(handled(), 0);
+
+    // The previous action is reported back.
+    unsafe {
+        let mut old: libc::sigaction = mem::zeroed();
+        assert_eq!(libc::sigaction(libc::SIGUSR1, ptr::null(), &mut old), 0);
+        assert_eq!(old.sa_sigaction, handler as libc::sighandler_t);
+    }
+}
+
+fn test_siginfo() {
+    set_action(libc::SIGUSR2, siginfo_handler as libc::sighandler_t, libc::SA_SIGINFO);
+    assert_eq!(unsafe { libc::raise(libc::SIGUSR2) }, 0);
+    assert_eq!(handled(), 1);
+    assert_eq!(LAST_SIGNAL.load(Ordering::Relaxed), libc::SIGUSR2);
+}
+
+fn test_ignore() {
+    set_action(libc::SIGUSR1, libc::SIG_IGN, 0);
+    assert_eq!(unsafe { libc::raise(libc::SIGUSR1) }, 0);
+    assert_eq!(handled(), 0);
+
+    // `signal` returns the previous handler.
+    let old = unsafe { libc::signal(libc::SIGUSR1, handler as libc::sighandler_t) };
+    assert_eq!(old, libc::SIG_IGN);
+    assert_eq!(unsafe { libc::signal(-1, libc::SIG_IGN) }, libc::SIG_ERR);
+}
+
+fn test_mask() {
+    set_action(libc::SIGUSR1, handler as libc::sighandler_t, 0);
+    unsafe {
+        let mut set: libc::sigset_t = mem::zeroed();
+        libc::sigemptyset(&mut set);
+        libc::sigaddset(&mut set, libc::SIGUSR1);
+        assert_eq!(libc::sigprocmask(libc::SIG_BLOCK, &set, ptr::null_mut()), 0);
+
+        // A blocked signal stays pending.
+        assert_eq!(libc::raise(libc::SIGUSR1), 0);
+        assert_eq!(handled(), 0);
+        let mut pending: libc::sigset_t = mem::zeroed();
+        assert_eq!(libc::sigpending(&mut pending), 0);
+        assert_eq!(libc::sigismember(&pending, libc::SIGUSR1), 1);
+
+        // Unblocking it delivers it.
+        let mut old: libc::sigset_t = mem::zeroed();
+        assert_eq!(libc::pthread_sigmask(libc::SIG_UNBLOCK, &set, &mut old), 0);
+        assert_eq!(libc::sigismember(&old, libc::SIGUSR1), 1);
+        assert_eq!(handled(), 1);
+        assert_eq!(libc::sigpending(&mut pending), 0);
+        assert_eq!(libc::sigismember(&pending, libc::SIGUSR1), 0);
+
+        assert_eq!(libc::pthread_sigmask(-1, &set, ptr::null_mut()), libc::EINVAL);
+    }
+}
+
+fn test_resethand() {
+    set_action(libc::SIGUSR1, handler as libc::sighandler_t, libc::SA_RESETHAND);
+    assert_eq!(unsafe { libc::raise(libc::SIGUSR1) }, 0);
+    assert_eq!(handled(), 1);
+    unsafe {
+        let mut old: libc::sigaction = mem::zeroed();
+        assert_eq!(libc::sigaction(libc::SIGUSR1, ptr::null(), &mut old), 0);
+        assert_eq!(old.sa_sigaction, libc::SIG_DFL);
+    }
+}
+
+fn test_alarm() {
+    set_action(libc::SIGALRM, handler as libc::sighandler_t, 0);
+    unsafe {
+        assert_eq!(libc::alarm(10), 0);
+        // Replacing an alarm returns how long the previous one had left.
+        assert_eq!(libc::alarm(1), 10);
+        // `pause` only returns after a handler ran.
+        assert_eq!(libc::pause(), -1);
+        assert_eq!(io::Error::last_os_error().raw_os_error(), Some(libc::EINTR));
+    }
+    assert_eq!(handled(), 1);
+    assert_eq!(LAST_SIGNAL.load(Ordering::Relaxed), libc::SIGALRM);
+    assert_eq!(unsafe { libc::alarm(0) }, 0);
+}
+
+fn test_interrupt_sleep() {
+    set_action(libc::SIGUSR2, handler as libc::sighandler_t, 0);
+    let sleeper = thread::spawn(|| {
+        let request = libc::timespec { tv_sec: 100, tv_nsec: 0 };
+        let mut remaining = libc::timespec { tv_sec: 0, tv_nsec: 0 };
+        let res = unsafe { libc::nanosleep(&request, &mut remaining) };
+        assert_eq!(res, -1);
+        assert_eq!(io::Error::last_os_error().raw_os_error(), Some(libc::EINTR));
+        assert!(remaining.tv_sec > 0 && remaining.tv_sec < 100);
+        // The handler ran on this thread before `nanosleep` returned.
+        assert_eq!(handled(), 1);
+    });
+    // Let the other thread fall asleep.
+    thread::sleep(std::time::Duration::from_millis(100));
+    assert_eq!(unsafe { libc::pthread_kill(sleeper.as_pthread_t(), libc::SIGUSR2) }, 0);
+    sleeper.join().unwrap();
+}
+
+fn main() {
+    test_sigset();
+    test_raise();
+    test_siginfo();
+    test_ignore();
+    test_mask();
+    test_resethand();
+    test_alarm();
+    test_interrupt_sleep();
+}
@@ -0,0 +1,114 @@
+//@only-target-linux
+
+use std::{io, mem, ptr};
+
+fn errno() -> i32 {
+    io::Error::last_os_error().raw_os_error().unwrap()
+}
+
+fn itimerspec(value_ms: i64, interval_ms: i64) -> libc::itimerspec {
+    let timespec = |ms: i64| libc::timespec { tv_sec: ms / 1000, tv_nsec: ms % 1000 * 1_000_000 };
+    libc::itimerspec { it_interval: timespec(interval_ms), it_value: timespec(value_ms) }
+}
+
+fn read_expirations(fd: i32) -> Result<u64, i32> {
+    let mut buf = [0u8; 8];
+    let res = unsafe { libc::read(fd, buf.as_mut_ptr().cast(), buf.len()) };
+    if res < 0 { Err(errno()) } else { Ok(u64::from_ne_bytes(buf)) }
+}
+
+fn main() {
+    test_create();
+    test_oneshot();
+    test_periodic();
+    test_epoll();
+}
+
+fn test_create() {
+    let fd = unsafe { libc::timerfd_create(libc::CLOCK_PROCESS_CPUTIME_ID, 0) };
+    assert_eq!(fd, -1);
+    assert_eq!(errno(), libc::EINVAL);
+
+    let fd = unsafe { libc::timerfd_create(libc::CLOCK_MONOTONIC, libc::TFD_NONBLOCK) };
+    assert!(fd >= 0);
+    // A timer that was never armed has nothing to read.
+    assert_eq!(read_expirations(fd), Err(libc::EAGAIN));
+    let mut curr = itimerspec(1, 1);
+    assert_eq!(unsafe { libc::timerfd_gettime(fd, &mut curr) }, 0);
+    assert_eq!(curr.it_value.tv_sec, 0);
+    assert_eq!(curr.it_value.tv_nsec, 0);
+    assert_eq!(curr.it_interval.tv_nsec, 0);
+
+    // Only timers can be armed.
+    let new = itimerspec(1, 0);
+    let res = unsafe { libc::timerfd_settime(1, 0, &new, ptr::null_mut()) };
+    assert_eq!(res, -1);
+    assert_eq!(errno(), libc::EINVAL);
+    assert_eq!(unsafe { libc::close(fd) }, 0);
+}
+
+fn test_oneshot() {
+    let fd = unsafe { libc::timerfd_create(libc::CLOCK_MONOTONIC, libc::TFD_NONBLOCK) };
+    let new = itimerspec(500, 0);
+    assert_eq!(unsafe { libc::timerfd_settime(fd, 0, &new, ptr::null_mut()) }, 0);
+
+    let mut curr = itimerspec(0, 0);
+    assert_eq!(unsafe { libc::timerfd_gettime(fd, &mut curr) }, 0);
+    assert_eq!(curr.it_value.tv_sec, 0);
+    assert!(curr.it_value.tv_nsec > 0 && curr.it_value.tv_nsec <= 500_000_000);
+    assert_eq!(read_expirations(fd), Err(libc::EAGAIN));
+
+    std::thread::sleep(std::time::Duration::from_secs(1));
+    assert_eq!(read_expirations(fd), Ok(1));
+    assert_eq!(read_expirations(fd), Err(libc::EAGAIN));
+
+    // Disarming the timer returns the old setting.
+    let new = itimerspec(10_000, 0);
+    assert_eq!(unsafe { libc::timerfd_settime(fd, 0, &new, ptr::null_mut()) }, 0);
+    let mut old = itimerspec(0, 0);
+    let zero = itimerspec(0, 0);
+    assert_eq!(unsafe { libc::timerfd_settime(fd, 0, &zero, &mut old) }, 0);
+    assert!(old.it_value.tv_sec > 0);
+    std::thread::sleep(std::time::Duration::from_secs(11));
+    assert_eq!(read_expirations(fd), Err(libc::EAGAIN));
+    assert_eq!(unsafe { libc::close(fd) }, 0);
+}
+
+fn test_periodic() {
+    let fd = unsafe { libc::timerfd_create(libc::CLOCK_MONOTONIC, libc::TFD_NONBLOCK) };
+    let new = itimerspec(100, 100);
+    assert_eq!(unsafe { libc::timerfd_settime(fd, 0, &new, ptr::null_mut()) }, 0);
+    std::thread::sleep(std::time::Duration::from_millis(350));
+    // The timer expired at 100, 200 and 300 milliseconds.
+    assert_eq!(read_expirations(fd), Ok(3));
+
+    // Absolute expiration times are based on the clock of the timer.
+    let mut now = libc::timespec { tv_sec: 0, tv_nsec: 0 };
+    assert_eq!(unsafe { libc::clock_gettime(libc::CLOCK_MONOTONIC, &mut now) }, 0);
+    let new =
+        libc::itimerspec { it_interval: libc::timespec { tv_sec: 0, tv_nsec: 0 }, it_value: now };
+    let res = unsafe { libc::timerfd_settime(fd, libc::TFD_TIMER_ABSTIME, &new, ptr::null_mut()) };
+    assert_eq!(res, 0);
+    // The expiration time already passed, so the timer expires right away.
+    assert_eq!(read_expirations(fd), Ok(1));
+    assert_eq!(unsafe { libc::close(fd) }, 0);
+}
+
+fn test_epoll() {
+    let fd = unsafe { libc::timerfd_create(libc::CLOCK_MONOTONIC, libc::TFD_CLOEXEC) };
+    let epfd = unsafe { libc::epoll_create1(0) };
+    let mut event = libc::epoll_event { events: libc::EPOLLIN as u32, u64: 7 };
+    assert_eq!(unsafe { libc::epoll_ctl(epfd, libc::EPOLL_CTL_ADD, fd, &mut event) }, 0);
+
+    let new = itimerspec(200, 0);
+    assert_eq!(unsafe { libc::timerfd_settime(fd, 0, &new, ptr::null_mut()) }, 0);
+    let mut events: [libc::epoll_event; 1] = unsafe { mem::zeroed() };
+    assert_eq!(unsafe { libc::epoll_wait(epfd, events.as_mut_ptr(), 1, 0) }, 0);
+    // Wait until the timer expires.
+    assert_eq!(unsafe { libc::epoll_wait(epfd, events.as_mut_ptr(), 1, -1) }, 1);
+    assert_eq!({ events[0].u64 }, 7);
+    assert_eq!(read_expirations(fd), Ok(1));
+
+    assert_eq!(unsafe { libc::close(epfd) }, 0);
+    assert_eq!(unsafe { libc::close(fd) }, 0);
+}
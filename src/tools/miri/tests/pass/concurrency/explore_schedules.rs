@@ -0,0 +1,44 @@
+//@compile-flags: -Zmiri-explore-schedules
+//@normalize-stderr-test: "explored [0-9]+ schedules" -> "explored $$N schedules"
+
+use std::cell::UnsafeCell;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+struct Data(UnsafeCell<u32>);
+
+unsafe impl Sync for Data {}
+
+fn message_passing() {
+    static DATA: Data = Data(UnsafeCell::new(0));
+    static READY: AtomicBool = AtomicBool::new(false);
+
+    let writer = thread::spawn(|| {
+        unsafe { *DATA.0.get() = 42 };
+        READY.store(true, Ordering::Release);
+    });
+    let reader = thread::spawn(|| {
+        // On every schedule, either the flag is not set yet or the data is visible.
+        if READY.load(Ordering::Acquire) {
+            assert_eq!(unsafe { *DATA.0.get() }, 42);
+        }
+    });
+    writer.join().unwrap();
+    reader.join().unwrap();
+}
+
+fn mutex_counter() {
+    static COUNTER: Mutex<u32> = Mutex::new(0);
+
+    let threads: Vec<_> = (0..2).map(|_| thread::spawn(|| *COUNTER.lock().unwrap() += 1)).collect();
+    for thread in threads {
+        thread.join().unwrap();
+    }
+    assert_eq!(*COUNTER.lock().unwrap(), 2);
+}
+
+fn main() {
+    message_passing();
+    mutex_counter();
+}
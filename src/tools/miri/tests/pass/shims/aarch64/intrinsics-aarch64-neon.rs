@@ -0,0 +1,221 @@
+// We're testing aarch64 target specific features
+//@only-target-aarch64
+//@compile-flags: -C target-feature=+neon,+crc,+aes
+
+use std::arch::aarch64::*;
+use std::arch::is_aarch64_feature_detected;
+use std::mem::transmute;
+
+fn main() {
+    assert!(is_aarch64_feature_detected!("neon"));
+    assert!(is_aarch64_feature_detected!("crc"));
+    assert!(is_aarch64_feature_detected!("aes"));
+
+    unsafe {
+        test_crc32();
+        test_aes();
+        test_neon_int();
+        test_neon_table_lookup();
+        test_neon_float();
+    }
+}
+
+#[target_feature(enable = "crc")]
+unsafe fn test_crc32() {
+    // The standard check values of CRC-32 and CRC-32C. The instructions do not invert the
+    // input and output, so do that here.
+    let data = b"123456789";
+    let mut crc = !0;
+    let mut crc_c = !0;
+    for &byte in data {
+        crc = __crc32b(crc, byte);
+        crc_c = __crc32cb(crc_c, byte);
+    }
+    assert_eq!(!crc, 0xCBF43926);
+    assert_eq!(!crc_c, 0xE3069283);
+
+    // The wider variants agree with processing the bytes one by one in little-endian order.
+    let value = 0x0123_4567_89AB_CDEFu64;
+    let bytewise = |f: unsafe fn(u32, u8) -> u32, bytes: &[u8]| {
+        bytes.iter().fold(0x1234_5678, |crc, &byte| unsafe { f(crc, byte) })
+    };
+    let bytes = value.to_le_bytes();
+    assert_eq!(__crc32h(0x1234_5678, value as u16), bytewise(__crc32b, &bytes[..2]));
+    assert_eq!(__crc32w(0x1234_5678, value as u32), bytewise(__crc32b, &bytes[..4]));
+    assert_eq!(__crc32d(0x1234_5678, value), bytewise(__crc32b, &bytes));
+    assert_eq!(__crc32ch(0x1234_5678, value as u16), bytewise(__crc32cb, &bytes[..2]));
+    assert_eq!(__crc32cw(0x1234_5678, value as u32), bytewise(__crc32cb, &bytes[..4]));
+    assert_eq!(__crc32cd(0x1234_5678, value), bytewise(__crc32cb, &bytes));
+}
+
+#[target_feature(enable = "aes")]
+unsafe fn test_aes() {
+    // The first round of the AES-128 example in appendix B of FIPS-197.
+    let input: uint8x16_t = transmute([
+        0x32u8, 0x43, 0xf6, 0xa8, 0x88, 0x5a, 0x30, 0x8d, 0x31, 0x31, 0x98, 0xa2, 0xe0, 0x37, 0x07,
+        0x34,
+    ]);
+    let key: uint8x16_t = transmute([
+        0x2bu8, 0x7e, 0x15, 0x16, 0x28, 0xae, 0xd2, 0xa6, 0xab, 0xf7, 0x15, 0x88, 0x09, 0xcf, 0x4f,
+        0x3c,
+    ]);
+    // After `AddRoundKey`, `SubBytes` and `ShiftRows`.
+    let shifted = [
+        0xd4u8, 0xbf, 0x5d, 0x30, 0xe0, 0xb4, 0x52, 0xae, 0xb8, 0x41, 0x11, 0xf1, 0x1e, 0x27, 0x98,
+        0xe5,
+    ];
+    // After `MixColumns`.
+    let mixed = [
+        0x04u8, 0x66, 0x81, 0xe5, 0xe0, 0xcb, 0x19, 0x9a, 0x48, 0xf8, 0xd3, 0x7a, 0x28, 0x06, 0x26,
+        0x4c,
+    ];
+    let added: [u8; 16] = transmute(veorq_u8(input, key));
+
+    let r = vaeseq_u8(input, key);
+    assert_eq!(transmute::<_, [u8; 16]>(r), shifted);
+    let r = vaesmcq_u8(r);
+    assert_eq!(transmute::<_, [u8; 16]>(r), mixed);
+
+    // Undo all of it.
+    let r = vaesimcq_u8(r);
+    assert_eq!(transmute::<_, [u8; 16]>(r), shifted);
+    let r = vaesdq_u8(r, vdupq_n_u8(0));
+    assert_eq!(transmute::<_, [u8; 16]>(r), added);
+}
+
+#[target_feature(enable = "neon")]
+unsafe fn test_neon_int() {
+    let a: int8x16_t =
+        transmute([0i8, 1, -1, 100, -100, 127, -128, 50, -50, 64, -64, 2, -2, 3, -3, 4]);
+    let b: int8x16_t =
+        transmute([0i8, 1, 1, 100, -100, 1, -1, 100, -100, 64, -65, 2, 3, -4, 5, -6]);
+
+    let r: [i8; 16] = transmute(vqaddq_s8(a, b));
+    assert_eq!(r, [0, 2, 0, 127, -128, 127, -128, 127, -128, 127, -128, 4, 1, -1, 2, -2]);
+    let r: [i8; 16] = transmute(vqsubq_s8(a, b));
+    assert_eq!(r, [0, 0, -2, 0, 0, 126, -127, -50, 50, 0, 1, 0, -5, 7, -8, 10]);
+    let r: [u8; 16] = transmute(vabdq_s8(a, b));
+    assert_eq!(r, [0, 0, 2, 0, 0, 126, 127, 50, 50, 0, 1, 0, 5, 7, 8, 10]);
+    let r: [i8; 16] = transmute(vhaddq_s8(a, b));
+    assert_eq!(r, [0, 1, 0, 100, -100, 64, -65, 75, -75, 64, -65, 2, 0, -1, 1, -1]);
+    let r: [i8; 16] = transmute(vqabsq_s8(a));
+    assert_eq!(r, [0, 1, 1, 100, 100, 127, 127, 50, 50, 64, 64, 2, 2, 3, 3, 4]);
+    let r: [i8; 16] = transmute(vqnegq_s8(a));
+    assert_eq!(r, [0, -1, 1, -100, 100, -127, 127, -50, 50, -64, 64, -2, 2, -3, 3, -4]);
+
+    let a: uint8x16_t =
+        transmute([0u8, 1, 2, 3, 4, 5, 6, 7, 250, 251, 252, 253, 254, 255, 128, 127]);
+    let b: uint8x16_t = transmute([1u8, 1, 1, 1, 9, 9, 9, 9, 10, 10, 10, 10, 0, 255, 128, 128]);
+
+    let r: [u8; 16] = transmute(vqaddq_u8(a, b));
+    assert_eq!(r, [1, 2, 3, 4, 13, 14, 15, 16, 255, 255, 255, 255, 254, 255, 255, 255]);
+    let r: [u8; 16] = transmute(vqsubq_u8(a, b));
+    assert_eq!(r, [0, 0, 1, 2, 0, 0, 0, 0, 240, 241, 242, 243, 254, 0, 0, 0]);
+    let r: [u8; 16] = transmute(vabdq_u8(a, b));
+    assert_eq!(r, [1, 0, 1, 2, 5, 4, 3, 2, 240, 241, 242, 243, 254, 0, 0, 1]);
+    let r: [u8; 16] = transmute(vhaddq_u8(a, b));
+    assert_eq!(r, [0, 1, 1, 2, 6, 7, 7, 8, 130, 130, 131, 131, 127, 255, 128, 127]);
+    let r: [u8; 16] = transmute(vrhaddq_u8(a, b));
+    assert_eq!(r, [1, 1, 2, 2, 7, 7, 8, 8, 130, 131, 131, 132, 127, 255, 128, 128]);
+    let r: [u8; 16] = transmute(vpaddq_u8(a, b));
+    assert_eq!(r, [1, 5, 9, 13, 245, 249, 253, 255, 2, 2, 18, 18, 20, 20, 255, 0]);
+    let r: [u8; 16] = transmute(vpmaxq_u8(a, b));
+    assert_eq!(r, [1, 3, 5, 7, 251, 253, 255, 128, 1, 1, 9, 9, 10, 10, 255, 128]);
+    let r: [u8; 16] = transmute(vpminq_u8(a, b));
+    assert_eq!(r, [0, 2, 4, 6, 250, 252, 254, 127, 1, 1, 9, 9, 10, 10, 0, 128]);
+
+    assert_eq!(vmaxvq_u8(a), 255);
+    assert_eq!(vminvq_u8(a), 0);
+    assert_eq!(vaddvq_u8(b), 79); // 591 wraps around.
+    assert_eq!(vaddlvq_u8(b), 591);
+    assert_eq!(vmaxvq_s8(vreinterpretq_s8_u8(a)), 127);
+    assert_eq!(vminvq_s8(vreinterpretq_s8_u8(a)), -128);
+    assert_eq!(vaddlvq_s8(vreinterpretq_s8_u8(a)), 6);
+
+    let r: [u8; 16] = transmute(vrbitq_u8(a));
+    assert_eq!(r.map(u8::reverse_bits), transmute::<_, [u8; 16]>(a));
+
+    let wide: int16x8_t = transmute([0i16, 1, -1, 127, 128, -128, -129, i16::MAX]);
+    let r: [i8; 8] = transmute(vqmovn_s16(wide));
+    assert_eq!(r, [0, 1, -1, 127, 127, -128, -128, 127]);
+    let r: [u8; 8] = transmute(vqmovun_s16(wide));
+    assert_eq!(r, [0, 1, 0, 127, 128, 0, 0, 255]);
+    let r: [u8; 8] = transmute(vqmovn_u16(vreinterpretq_u16_s16(wide)));
+    assert_eq!(r, [0, 1, 255, 127, 128, 255, 255, 255]);
+
+    let r: [u16; 8] = transmute(vmull_u8(vget_high_u8(a), vget_high_u8(b)));
+    assert_eq!(r, [2500, 2510, 2520, 2530, 0, 65025, 16384, 16256]);
+    let r: [i16; 8] = transmute(vmull_s8(
+        vget_high_s8(vreinterpretq_s8_u8(a)),
+        vget_high_s8(vreinterpretq_s8_u8(b)),
+    ));
+    assert_eq!(r, [-60, -50, -40, -30, 0, 1, 16384, -16256]);
+}
+
+#[target_feature(enable = "neon")]
+unsafe fn test_neon_table_lookup() {
+    let table0: uint8x16_t =
+        transmute([0u8, 10, 20, 30, 40, 50, 60, 70, 80, 90, 100, 110, 120, 130, 140, 150]);
+    let table1: uint8x16_t =
+        transmute([1u8, 11, 21, 31, 41, 51, 61, 71, 81, 91, 101, 111, 121, 131, 141, 151]);
+    let indices: uint8x16_t =
+        transmute([0u8, 15, 16, 31, 32, 255, 1, 17, 2, 18, 3, 19, 4, 20, 5, 21]);
+    let fallback = vdupq_n_u8(42);
+
+    let r: [u8; 16] = transmute(vqtbl1q_u8(table0, indices));
+    assert_eq!(r, [0, 150, 0, 0, 0, 0, 10, 0, 20, 0, 30, 0, 40, 0, 50, 0]);
+    let r: [u8; 16] = transmute(vqtbx1q_u8(fallback, table0, indices));
+    assert_eq!(r, [0, 150, 42, 42, 42, 42, 10, 42, 20, 42, 30, 42, 40, 42, 50, 42]);
+    let tables = uint8x16x2_t(table0, table1);
+    let r: [u8; 16] = transmute(vqtbl2q_u8(tables, indices));
+    assert_eq!(r, [0, 150, 1, 151, 0, 0, 10, 11, 20, 21, 30, 31, 40, 41, 50, 51]);
+    let r: [u8; 16] = transmute(vqtbx2q_u8(fallback, tables, indices));
+    assert_eq!(r, [0, 150, 1, 151, 42, 42, 10, 11, 20, 21, 30, 31, 40, 41, 50, 51]);
+
+    // The 64-bit variants use 8-byte tables.
+    let r: [u8; 8] = transmute(vtbl1_u8(vget_low_u8(table0), vget_low_u8(indices)));
+    assert_eq!(r, [0, 0, 0, 0, 0, 0, 10, 0]);
+}
+
+#[target_feature(enable = "neon")]
+unsafe fn test_neon_float() {
+    let a: float32x4_t = transmute([1.0f32, -0.0, f32::NAN, 3.0]);
+    let b: float32x4_t = transmute([2.0f32, 0.0, 1.0, -4.0]);
+
+    let r: [f32; 4] = transmute(vmaxq_f32(a, b));
+    assert_eq!(r[0], 2.0);
+    assert!(r[1] == 0.0 && r[1].is_sign_positive());
+    assert!(r[2].is_nan());
+    assert_eq!(r[3], 3.0);
+    let r: [f32; 4] = transmute(vminq_f32(a, b));
+    assert_eq!(r[0], 1.0);
+    assert!(r[1] == 0.0 && r[1].is_sign_negative());
+    assert!(r[2].is_nan());
+    assert_eq!(r[3], -4.0);
+    let r: [f32; 4] = transmute(vmaxnmq_f32(a, b));
+    assert_eq!(r, [2.0, 0.0, 1.0, 3.0]);
+    let r: [f32; 4] = transmute(vminnmq_f32(a, b));
+    assert_eq!(r, [1.0, -0.0, 1.0, -4.0]);
+    let r: [f32; 4] = transmute(vabdq_f32(b, b));
+    assert_eq!(r, [0.0; 4]);
+
+    let r: [f32; 4] = transmute(vpaddq_f32(b, b));
+    assert_eq!(r, [2.0, -3.0, 2.0, -3.0]);
+    let r: [f32; 4] = transmute(vpmaxq_f32(b, a));
+    assert_eq!(&r[..3], [2.0, 1.0, 1.0]);
+    assert!(r[3].is_nan());
+    let r: [f32; 4] = transmute(vpmaxnmq_f32(b, a));
+    assert_eq!(r, [2.0, 1.0, 1.0, 3.0]);
+
+    assert!(vmaxvq_f32(a).is_nan());
+    assert_eq!(vmaxnmvq_f32(a), 3.0);
+    assert_eq!(vminnmvq_f32(b), -4.0);
+    assert_eq!(vminvq_f32(b), -4.0);
+
+    let a: float64x2_t = transmute([1.5f64, -2.5]);
+    let b: float64x2_t = transmute([-1.0f64, 0.5]);
+    let r: [f64; 2] = transmute(vabdq_f64(a, b));
+    assert_eq!(r, [2.5, 3.0]);
+    let r: [f64; 2] = transmute(vmaxq_f64(a, b));
+    assert_eq!(r, [1.5, 0.5]);
+}
@@ -0,0 +1,97 @@
+//@ignore-target-windows: File handling is not implemented yet
+//@compile-flags: -Zmiri-virtual-fs=tests/utils -Zmiri-isolation-error=hide
+
+#![feature(io_error_more)]
+
+use std::env;
+use std::fs::{self, File, OpenOptions};
+use std::io::{ErrorKind, Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+
+/// `EBADF` on all Unix targets Miri supports.
+const EBADF: i32 = 9;
+
+fn main() {
+    let root = env::current_dir().unwrap();
+    assert!(root.is_absolute() && root.ends_with("tests/utils"), "{root:?}");
+
+    test_snapshot(&root);
+    test_file(&root);
+    test_directory(&root);
+    test_outside(&root);
+}
+
+fn test_snapshot(root: &PathBuf) {
+    let mut names: Vec<_> =
+        fs::read_dir(root).unwrap().map(|entry| entry.unwrap().file_name()).collect();
+    names.sort();
+    assert!(names.iter().any(|name| name == "mod.rs"), "{names:?}");
+
+    // Relative and absolute paths refer to the same copy.
+    let contents = fs::read_to_string("mod.rs").unwrap();
+    assert_eq!(contents, fs::read_to_string(root.join("mod.rs")).unwrap());
+    assert!(contents.contains("mod fs;"));
+    assert_eq!(fs::metadata("mod.rs").unwrap().len(), contents.len() as u64);
+    assert!(fs::metadata(".").unwrap().is_dir());
+}
+
+fn test_file(root: &PathBuf) {
+    let path = root.join("virtual.txt");
+    let mut file = File::create(&path).unwrap();
+    file.write_all(b"Hello, World!").unwrap();
+    file.seek(SeekFrom::Start(7)).unwrap();
+    file.write_all(b"Miri").unwrap();
+    drop(file);
+    assert_eq!(fs::read(&path).unwrap(), b"Hello, Mirid!");
+
+    let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+    file.write_all(b"?").unwrap();
+    file.set_len(5).unwrap();
+    drop(file);
+    let mut contents = String::new();
+    File::open(&path).unwrap().read_to_string(&mut contents).unwrap();
+    assert_eq!(contents, "Hello");
+
+    // Handles can only be used in the modes they were opened with.
+    let mut write_only = OpenOptions::new().write(true).open(&path).unwrap();
+    assert_eq!(write_only.read(&mut [0; 1]).unwrap_err().raw_os_error(), Some(EBADF));
+    assert_eq!(File::open(&path).unwrap().write(b"?").unwrap_err().raw_os_error(), Some(EBADF));
+    drop(write_only);
+
+    let err = OpenOptions::new().write(true).create_new(true).open(&path).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::AlreadyExists);
+
+    let renamed = root.join("renamed.txt");
+    fs::rename(&path, &renamed).unwrap();
+    assert_eq!(File::open(&path).unwrap_err().kind(), ErrorKind::NotFound);
+    fs::remove_file(&renamed).unwrap();
+    assert_eq!(fs::remove_file(&renamed).unwrap_err().kind(), ErrorKind::NotFound);
+}
+
+fn test_directory(root: &PathBuf) {
+    let dir = root.join("virtual_dir");
+    fs::create_dir(&dir).unwrap();
+    assert_eq!(fs::create_dir(&dir).unwrap_err().kind(), ErrorKind::AlreadyExists);
+    fs::write(dir.join("a"), b"a").unwrap();
+    fs::create_dir(dir.join("b")).unwrap();
+
+    let mut entries: Vec<_> = fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| {
+            let entry = entry.unwrap();
+            (entry.file_name().into_string().unwrap(), entry.file_type().unwrap().is_dir())
+        })
+        .collect();
+    entries.sort();
+    assert_eq!(entries, [("a".to_owned(), false), ("b".to_owned(), true)]);
+
+    assert_eq!(fs::remove_dir(&dir).unwrap_err().kind(), ErrorKind::DirectoryNotEmpty);
+    fs::remove_dir_all(&dir).unwrap();
+    assert!(!dir.exists());
+}
+
+fn test_outside(root: &PathBuf) {
+    // Everything outside the mount point is still subject to isolation.
+    let outside = root.parent().unwrap().join("ui.rs");
+    assert_eq!(File::open(outside).unwrap_err().kind(), ErrorKind::PermissionDenied);
+}
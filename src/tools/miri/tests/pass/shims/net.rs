@@ -0,0 +1,71 @@
+//@ignore-target-windows: No libc sockets on Windows
+//@ignore-target-solaris: Sockets are not supported on Solaris
+//@ignore-target-illumos: Sockets are not supported on illumos
+
+use std::io::{ErrorKind, Read, Write};
+use std::net::{TcpListener, TcpStream, UdpSocket};
+use std::thread;
+use std::time::{Duration, Instant};
+
+fn main() {
+    test_tcp();
+    test_udp();
+    test_read_timeout();
+}
+
+fn test_tcp() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let server = thread::spawn(move || {
+        let (mut stream, peer) = listener.accept().unwrap();
+        assert_eq!(stream.peer_addr().unwrap(), peer);
+        let mut buf = String::new();
+        stream.read_to_string(&mut buf).unwrap();
+        assert_eq!(buf, "hello world");
+        stream.write_all(b"bye").unwrap();
+    });
+
+    let mut stream = TcpStream::connect(addr).unwrap();
+    assert_eq!(stream.peer_addr().unwrap(), addr);
+    stream.set_nodelay(true).unwrap();
+    stream.write_all(b"hello ").unwrap();
+    stream.write_all(b"world").unwrap();
+    stream.shutdown(std::net::Shutdown::Write).unwrap();
+    let mut buf = Vec::new();
+    stream.read_to_end(&mut buf).unwrap();
+    assert_eq!(buf, b"bye");
+    server.join().unwrap();
+
+    // Nothing is listening anymore.
+    assert_eq!(TcpStream::connect(addr).unwrap_err().kind(), ErrorKind::ConnectionRefused);
+}
+
+fn test_udp() {
+    let a = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let b = UdpSocket::bind("127.0.0.1:0").unwrap();
+    a.send_to(b"ping", b.local_addr().unwrap()).unwrap();
+    let mut buf = [0; 8];
+    let (len, from) = b.recv_from(&mut buf).unwrap();
+    assert_eq!(&buf[..len], b"ping");
+    assert_eq!(from, a.local_addr().unwrap());
+
+    b.connect(from).unwrap();
+    b.send(b"pong").unwrap();
+    let len = a.recv(&mut buf).unwrap();
+    assert_eq!(&buf[..len], b"pong");
+
+    a.set_nonblocking(true).unwrap();
+    assert_eq!(a.recv(&mut buf).unwrap_err().kind(), ErrorKind::WouldBlock);
+}
+
+fn test_read_timeout() {
+    let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+    socket.set_read_timeout(Some(Duration::from_millis(100))).unwrap();
+    assert_eq!(socket.read_timeout().unwrap(), Some(Duration::from_millis(100)));
+
+    let start = Instant::now();
+    let err = socket.recv(&mut [0; 4]).unwrap_err();
+    assert!(matches!(err.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut));
+    assert!(start.elapsed() >= Duration::from_millis(100));
+}
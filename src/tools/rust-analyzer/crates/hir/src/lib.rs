@@ -2597,6 +2597,13 @@ pub fn items(self, db: &dyn HirDatabase) -> Vec<AssocItem> {
         db.trait_data(self.id).items.iter().map(|(_name, it)| (*it).into()).collect()
     }
 
+    /// Returns the traits named in this trait's supertrait bounds, without the ones they imply.
+    pub fn direct_supertraits(self, db: &dyn HirDatabase) -> Vec<Trait> {
+        let mut traits = Vec::new();
+        hir_ty::direct_super_traits(db.upcast(), self.id, |it| traits.push(it.into()));
+        traits
+    }
+
     pub fn items_with_supertraits(self, db: &dyn HirDatabase) -> Vec<AssocItem> {
         let traits = all_super_traits(db.upcast(), self.into());
         traits.iter().flat_map(|tr| Trait::from(*tr).items(db)).collect()
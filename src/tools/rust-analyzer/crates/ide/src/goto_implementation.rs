@@ -4,7 +4,7 @@
     helpers::pick_best_token,
     RootDatabase,
 };
-use syntax::{ast, AstNode, SyntaxKind::*, T};
+use syntax::{ast, AstNode, SyntaxKind::*, TextRange, T};
 
 use crate::{FilePosition, NavigationTarget, RangeInfo, TryToNav};
 
@@ -21,9 +21,44 @@
 // image::https://user-images.githubusercontent.com/48062697/113065566-02f85480-91b1-11eb-9288-aaad8abd8841.gif[]
 pub(crate) fn goto_implementation(
     db: &RootDatabase,
-    FilePosition { file_id, offset }: FilePosition,
+    position: FilePosition,
 ) -> Option<RangeInfo<Vec<NavigationTarget>>> {
     let sema = Semantics::new(db);
+    let (range, def) = definition_at(&sema, position)?;
+    let navs = def
+        .and_then(|def| {
+            let navs = match def {
+                Definition::Trait(trait_) => impls_for_trait(&sema, trait_),
+                Definition::Adt(adt) => impls_for_ty(&sema, adt.ty(sema.db)),
+                Definition::TypeAlias(alias) => impls_for_ty(&sema, alias.ty(sema.db)),
+                Definition::BuiltinType(builtin) => impls_for_ty(&sema, builtin.ty(sema.db)),
+                Definition::Function(f) => {
+                    let assoc = f.as_assoc_item(sema.db)?;
+                    let name = assoc.name(sema.db)?;
+                    let trait_ = assoc.container_or_implemented_trait(sema.db)?;
+                    impls_for_trait_item(&sema, trait_, name)
+                }
+                Definition::Const(c) => {
+                    let assoc = c.as_assoc_item(sema.db)?;
+                    let name = assoc.name(sema.db)?;
+                    let trait_ = assoc.container_or_implemented_trait(sema.db)?;
+                    impls_for_trait_item(&sema, trait_, name)
+                }
+                _ => return None,
+            };
+            Some(navs)
+        })
+        .unwrap_or_default();
+
+    Some(RangeInfo { range, info: navs })
+}
+
+/// Returns the range of the identifier at `position` together with the definition it names or
+/// refers to, if any.
+pub(crate) fn definition_at(
+    sema: &Semantics<'_, RootDatabase>,
+    FilePosition { file_id, offset }: FilePosition,
+) -> Option<(TextRange, Option<Definition>)> {
     let source_file = sema.parse_guess_edition(file_id);
     let syntax = source_file.syntax().clone();
 
@@ -32,50 +67,27 @@ pub(crate) fn goto_implementation(
         _ => 0,
     })?;
     let range = original_token.text_range();
-    let navs =
+    let def =
         sema.descend_into_macros_single(DescendPreference::SameText, original_token)
             .parent()
             .and_then(ast::NameLike::cast)
             .and_then(|node| match &node {
                 ast::NameLike::Name(name) => {
-                    NameClass::classify(&sema, name).and_then(|class| match class {
+                    NameClass::classify(sema, name).and_then(|class| match class {
                         NameClass::Definition(it) | NameClass::ConstReference(it) => Some(it),
                         NameClass::PatFieldShorthand { .. } => None,
                     })
                 }
-                ast::NameLike::NameRef(name_ref) => NameRefClass::classify(&sema, name_ref)
+                ast::NameLike::NameRef(name_ref) => NameRefClass::classify(sema, name_ref)
                     .and_then(|class| match class {
                         NameRefClass::Definition(def) => Some(def),
                         NameRefClass::FieldShorthand { .. }
                         | NameRefClass::ExternCrateShorthand { .. } => None,
                     }),
                 ast::NameLike::Lifetime(_) => None,
-            })
-            .and_then(|def| {
-                let navs = match def {
-                    Definition::Trait(trait_) => impls_for_trait(&sema, trait_),
-                    Definition::Adt(adt) => impls_for_ty(&sema, adt.ty(sema.db)),
-                    Definition::TypeAlias(alias) => impls_for_ty(&sema, alias.ty(sema.db)),
-                    Definition::BuiltinType(builtin) => impls_for_ty(&sema, builtin.ty(sema.db)),
-                    Definition::Function(f) => {
-                        let assoc = f.as_assoc_item(sema.db)?;
-                        let name = assoc.name(sema.db)?;
-                        let trait_ = assoc.container_or_implemented_trait(sema.db)?;
-                        impls_for_trait_item(&sema, trait_, name)
-                    }
-                    Definition::Const(c) => {
-                        let assoc = c.as_assoc_item(sema.db)?;
-                        let name = assoc.name(sema.db)?;
-                        let trait_ = assoc.container_or_implemented_trait(sema.db)?;
-                        impls_for_trait_item(&sema, trait_, name)
-                    }
-                    _ => return None,
-                };
-                Some(navs)
-            })
-            .unwrap_or_default();
+            });
 
-    Some(RangeInfo { range, info: navs })
+    Some((range, def))
 }
 
 fn impls_for_ty(sema: &Semantics<'_, RootDatabase>, ty: hir::Type) -> Vec<NavigationTarget> {
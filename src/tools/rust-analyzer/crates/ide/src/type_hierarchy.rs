@@ -0,0 +1,301 @@
+//! Entry point for type-hierarchy
+
+use std::hash::Hash;
+
+use either::Either;
+use hir::{Impl, Semantics};
+use ide_db::{defs::Definition, FxIndexSet, RootDatabase, SymbolKind};
+use syntax::{ast, AstNode, ToSmolStr};
+
+use crate::{
+    goto_implementation::definition_at, FilePosition, NavigationTarget, RangeInfo, TryToNav,
+};
+
+pub(crate) fn type_hierarchy(
+    db: &RootDatabase,
+    position: FilePosition,
+) -> Option<RangeInfo<Vec<NavigationTarget>>> {
+    let sema = Semantics::new(db);
+    let (range, def) = definition_at(&sema, position)?;
+    let navs = match def? {
+        Definition::Trait(trait_) => trait_.try_to_nav(db)?.collect(),
+        Definition::Adt(adt) => adt.try_to_nav(db)?.collect(),
+        Definition::TypeAlias(alias) => alias.try_to_nav(db)?.collect(),
+        // Builtin types aren't declared anywhere, so the item is the reference to it, which
+        // resolves to the same type when asking for its supertypes.
+        Definition::BuiltinType(builtin) => vec![NavigationTarget::from_syntax(
+            position.file_id,
+            builtin.name().display_no_db().to_smolstr(),
+            Some(range),
+            range,
+            SymbolKind::TypeAlias,
+        )],
+        _ => return None,
+    };
+    Some(RangeInfo::new(range, navs))
+}
+
+/// The supertypes of a trait are its supertraits, the supertypes of a type are the traits it
+/// implements.
+pub(crate) fn supertypes(
+    db: &RootDatabase,
+    position: FilePosition,
+) -> Option<Vec<NavigationTarget>> {
+    let sema = Semantics::new(db);
+    let traits = match definition_at(&sema, position)?.1 {
+        Some(Definition::Trait(trait_)) => trait_.direct_supertraits(db),
+        Some(Definition::Adt(adt)) => traits_for_ty(db, adt.ty(db)),
+        Some(Definition::TypeAlias(alias)) => traits_for_ty(db, alias.ty(db)),
+        Some(Definition::BuiltinType(builtin)) => traits_for_ty(db, builtin.ty(db)),
+        _ => {
+            let imp = impl_at(&sema, position)?;
+            let self_ty = imp.self_ty(db);
+            let self_ty = self_ty.remove_ref().unwrap_or(self_ty);
+            imp.trait_(db).into_iter().chain(traits_for_ty(db, self_ty)).collect()
+        }
+    };
+    Some(to_navs(db, traits))
+}
+
+/// The subtypes of a trait are the types implementing it. Implementations for anything other than
+/// an ADT, like references or blanket implementations, are listed as their impl blocks.
+pub(crate) fn subtypes(db: &RootDatabase, position: FilePosition) -> Option<Vec<NavigationTarget>> {
+    let sema = Semantics::new(db);
+    let impls = match definition_at(&sema, position)?.1 {
+        Some(Definition::Trait(trait_)) => Impl::all_for_trait(db, trait_),
+        Some(Definition::Adt(_) | Definition::TypeAlias(_) | Definition::BuiltinType(_)) => {
+            return Some(Vec::new())
+        }
+        _ => {
+            impl_at(&sema, position)?;
+            return Some(Vec::new());
+        }
+    };
+    let subtypes = impls
+        .into_iter()
+        .filter(|imp| !imp.is_negative(db))
+        .map(|imp| imp.self_ty(db).as_adt().map_or(Either::Right(imp), Either::Left));
+    Some(to_navs(db, subtypes))
+}
+
+/// Returns the impl whose self type starts at `position`.
+///
+/// Subtypes listed as impl blocks point at their self type, which can be a type parameter or a
+/// type without a definition, like `&Foo`, so the item stands for the self type of the impl. Its
+/// supertypes are the trait of the impl and the other traits implemented for the self type.
+fn impl_at(sema: &Semantics<'_, RootDatabase>, position: FilePosition) -> Option<Impl> {
+    let file = sema.parse_guess_edition(position.file_id);
+    let token = file.syntax().token_at_offset(position.offset).right_biased()?;
+    let imp = token.parent_ancestors().find_map(ast::Impl::cast)?;
+    if imp.self_ty()?.syntax().text_range().start() != position.offset {
+        return None;
+    }
+    sema.to_def(&imp)
+}
+
+/// Returns the traits `ty` implements, through impls for it or for references to it.
+fn traits_for_ty(db: &RootDatabase, ty: hir::Type) -> Vec<hir::Trait> {
+    Impl::all_for_type(db, ty)
+        .into_iter()
+        .filter(|imp| !imp.is_negative(db))
+        .filter_map(|imp| imp.trait_(db))
+        .collect()
+}
+
+fn to_navs<T: TryToNav + Hash + Eq>(
+    db: &RootDatabase,
+    items: impl IntoIterator<Item = T>,
+) -> Vec<NavigationTarget> {
+    items
+        .into_iter()
+        .collect::<FxIndexSet<_>>()
+        .into_iter()
+        .filter_map(|it| it.try_to_nav(db))
+        .flatten()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::{expect, Expect};
+    use ide_db::FilePosition;
+    use itertools::Itertools;
+
+    use crate::fixture;
+
+    fn check_hierarchy(
+        ra_fixture: &str,
+        expected_nav: Expect,
+        expected_supertypes: Expect,
+        expected_subtypes: Expect,
+    ) {
+        let (analysis, pos) = fixture::position(ra_fixture);
+
+        let mut navs = analysis.type_hierarchy(pos).unwrap().unwrap().info;
+        assert_eq!(navs.len(), 1);
+        let nav = navs.pop().unwrap();
+        expected_nav.assert_eq(&nav.debug_render());
+
+        let item_pos =
+            FilePosition { file_id: nav.file_id, offset: nav.focus_or_full_range().start() };
+        let supertypes = analysis.supertypes(item_pos).unwrap().unwrap();
+        expected_supertypes.assert_eq(&supertypes.iter().map(|it| it.debug_render()).join("\n"));
+
+        let subtypes = analysis.subtypes(item_pos).unwrap().unwrap();
+        expected_subtypes.assert_eq(&subtypes.iter().map(|it| it.debug_render()).join("\n"));
+    }
+
+    #[test]
+    fn test_type_hierarchy_on_trait() {
+        check_hierarchy(
+            r#"
+trait Base {}
+trait Other {}
+trait Tr$0ait: Base + Other {}
+struct Foo;
+enum Bar {}
+impl Trait for Foo {}
+impl Trait for Bar {}
+impl Trait for &Foo {}
+"#,
+            expect!["Trait Trait FileId(0) 29..57 35..40"],
+            expect![[r#"
+                Base Trait FileId(0) 0..13 6..10
+                Other Trait FileId(0) 14..28 20..25"#]],
+            expect![[r#"
+                Foo Struct FileId(0) 58..69 65..68
+                impl Impl FileId(0) 126..148 141..145
+                Bar Enum FileId(0) 70..81 75..78"#]],
+        );
+    }
+
+    #[test]
+    fn test_type_hierarchy_on_type_ref() {
+        check_hierarchy(
+            r#"
+trait Trait {}
+trait Other {}
+struct Foo;
+impl Foo {}
+impl Trait for Foo {}
+impl Other for Foo {}
+fn f(_: F$0oo) {}
+"#,
+            expect![["Foo Struct FileId(0) 30..41 37..40"]],
+            expect![[r#"
+                Trait Trait FileId(0) 0..14 6..11
+                Other Trait FileId(0) 15..29 21..26"#]],
+            expect![[]],
+        );
+    }
+
+    #[test]
+    fn test_type_hierarchy_on_type_alias() {
+        check_hierarchy(
+            r#"
+trait Trait {}
+struct Foo;
+impl Trait for Foo {}
+type Al$0ias = Foo;
+"#,
+            expect![["Alias TypeAlias FileId(0) 49..66 54..59"]],
+            expect![["Trait Trait FileId(0) 0..14 6..11"]],
+            expect![[]],
+        );
+    }
+
+    #[test]
+    fn test_type_hierarchy_on_builtin() {
+        check_hierarchy(
+            r#"
+//- /lib.rs crate:main deps:core
+trait Trait {}
+impl Trait for u32 {}
+fn f(_: u3$02) {}
+//- /libcore.rs crate:core
+#![rustc_coherence_is_core]
+pub trait Copy {}
+#[lang = "u32"]
+impl u32 {}
+impl Copy for u32 {}
+"#,
+            expect![["u32 TypeAlias FileId(0) 45..48 45..48"]],
+            expect![[r#"
+                Trait Trait FileId(0) 0..14 6..11
+                Copy Trait FileId(1) 28..45 38..42"#]],
+            expect![[]],
+        );
+    }
+
+    #[test]
+    fn test_type_hierarchy_blanket_impl() {
+        check_hierarchy(
+            r#"
+trait Trait$0 {}
+impl<T> Trait for T {}
+"#,
+            expect![["Trait Trait FileId(0) 0..14 6..11"]],
+            expect![[]],
+            expect![["impl Impl FileId(0) 15..37 33..34"]],
+        );
+    }
+
+    #[test]
+    fn test_type_hierarchy_impl_subtype() {
+        let (analysis, pos) = fixture::position(
+            r#"
+trait Trait$0 {}
+trait Other {}
+struct Foo;
+impl<T> Trait for T {}
+impl Trait for &Foo {}
+impl Other for &Foo {}
+"#,
+        );
+        let subtypes = analysis.subtypes(pos).unwrap().unwrap();
+        expect![[r#"
+            impl Impl FileId(0) 42..64 60..61
+            impl Impl FileId(0) 65..87 80..84"#]]
+        .assert_eq(&subtypes.iter().map(|it| it.debug_render()).join("\n"));
+
+        for (nav, expected) in subtypes.iter().zip([
+            expect!["Trait Trait FileId(0) 0..14 6..11"],
+            expect![[r#"
+                Trait Trait FileId(0) 0..14 6..11
+                Other Trait FileId(0) 15..29 21..26"#]],
+        ]) {
+            let item_pos =
+                FilePosition { file_id: nav.file_id, offset: nav.focus_or_full_range().start() };
+            let supertypes = analysis.supertypes(item_pos).unwrap().unwrap();
+            expected.assert_eq(&supertypes.iter().map(|it| it.debug_render()).join("\n"));
+            assert!(analysis.subtypes(item_pos).unwrap().unwrap().is_empty());
+        }
+    }
+
+    #[test]
+    fn test_type_hierarchy_negative_impl() {
+        check_hierarchy(
+            r#"
+auto trait Send$0 {}
+struct Foo;
+struct Bar;
+impl Send for Foo {}
+impl !Send for Bar {}
+"#,
+            expect![["Send Trait FileId(0) 0..18 11..15"]],
+            expect![[]],
+            expect![["Foo Struct FileId(0) 19..30 26..29"]],
+        );
+    }
+
+    #[test]
+    fn test_type_hierarchy_not_a_type() {
+        let (analysis, pos) = fixture::position(
+            r#"
+fn foo$0() {}
+"#,
+        );
+        assert!(analysis.type_hierarchy(pos).unwrap().is_none());
+        assert!(analysis.supertypes(pos).unwrap().is_none());
+    }
+}
@@ -153,6 +153,7 @@ pub fn server_capabilities(config: &Config) -> ServerCapabilities {
                 "kinds": [ "cargo" ],
             },
             "ssr": true,
+            "typeHierarchyProvider": true,
             "workspaceSymbolScopeKindFiltering": true,
         })),
         diagnostic_provider: None,
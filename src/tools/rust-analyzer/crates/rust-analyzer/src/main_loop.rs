@@ -1064,6 +1064,9 @@ fn on_request(&mut self, req: Request) {
             .on::<NO_RETRY, lsp_request::CallHierarchyPrepare>(handlers::handle_call_hierarchy_prepare)
             .on::<NO_RETRY, lsp_request::CallHierarchyIncomingCalls>(handlers::handle_call_hierarchy_incoming)
             .on::<NO_RETRY, lsp_request::CallHierarchyOutgoingCalls>(handlers::handle_call_hierarchy_outgoing)
+            .on::<NO_RETRY, lsp_request::TypeHierarchyPrepare>(handlers::handle_type_hierarchy_prepare)
+            .on::<NO_RETRY, lsp_request::TypeHierarchySupertypes>(handlers::handle_type_hierarchy_supertypes)
+            .on::<NO_RETRY, lsp_request::TypeHierarchySubtypes>(handlers::handle_type_hierarchy_subtypes)
             // All other request handlers (lsp extension)
             .on::<RETRY, lsp_ext::FetchDependencyList>(handlers::fetch_dependency_list)
             .on::<RETRY, lsp_ext::AnalyzerStatus>(handlers::handle_analyzer_status)
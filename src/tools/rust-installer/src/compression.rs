@@ -9,6 +9,8 @@
 use rayon::prelude::*;
 use xz2::read::XzDecoder;
 use xz2::write::XzEncoder;
+use zstd::stream::read::Decoder as ZstDecoder;
+use zstd::stream::write::Encoder as ZstEncoder;
 
 #[derive(Default, Debug, Copy, Clone)]
 pub enum CompressionProfile {
@@ -48,6 +50,7 @@ fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
 pub enum CompressionFormat {
     Gz,
     Xz,
+    Zst,
 }
 
 impl CompressionFormat {
@@ -55,6 +58,7 @@ pub(crate) fn detect_from_path(path: impl AsRef<Path>) -> Option<Self> {
         match path.as_ref().extension().and_then(|e| e.to_str()) {
             Some("gz") => Some(CompressionFormat::Gz),
             Some("xz") => Some(CompressionFormat::Xz),
+            Some("zst") => Some(CompressionFormat::Zst),
             _ => None,
         }
     }
@@ -63,6 +67,7 @@ pub(crate) fn extension(&self) -> &'static str {
         match self {
             CompressionFormat::Gz => "gz",
             CompressionFormat::Xz => "xz",
+            CompressionFormat::Zst => "zst",
         }
     }
 
@@ -115,6 +120,21 @@ pub(crate) fn encode(
                 let compressor = XzEncoder::new_stream(std::io::BufWriter::new(file), encoder);
                 Box::new(compressor)
             }
+            CompressionFormat::Zst => {
+                let level = match profile {
+                    CompressionProfile::NoOp => panic!(
+                        "compression profile 'no-op' should not call `CompressionFormat::encode`."
+                    ),
+                    CompressionProfile::Fast => 1,
+                    CompressionProfile::Balanced => 9,
+                    CompressionProfile::Best => 19,
+                };
+
+                let mut compressor = ZstEncoder::new(std::io::BufWriter::new(file), level)?;
+                compressor.multithread(6)?;
+                compressor.include_checksum(true)?;
+                Box::new(compressor)
+            }
         })
     }
 
@@ -123,6 +143,7 @@ pub(crate) fn decode(&self, path: impl AsRef<Path>) -> Result<Box<dyn Read>, Err
         Ok(match self {
             CompressionFormat::Gz => Box::new(GzDecoder::new(file)),
             CompressionFormat::Xz => Box::new(XzDecoder::new(file)),
+            CompressionFormat::Zst => Box::new(ZstDecoder::new(file)?),
         })
     }
 }
@@ -140,6 +161,7 @@ fn try_from(value: &str) -> Result<Self, Self::Error> {
             match format.trim() {
                 "gz" => parsed.push(CompressionFormat::Gz),
                 "xz" => parsed.push(CompressionFormat::Xz),
+                "zst" => parsed.push(CompressionFormat::Zst),
                 other => anyhow::bail!("unknown compression format: {}", other),
             }
         }
@@ -165,6 +187,7 @@ fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
                 match format {
                     CompressionFormat::Xz => "xz",
                     CompressionFormat::Gz => "gz",
+                    CompressionFormat::Zst => "zst",
                 },
                 f,
             )?;
@@ -203,6 +226,13 @@ fn finish(self: Box<Self>) -> Result<(), Error> {
     }
 }
 
+impl<W: Send + Write> Encoder for ZstEncoder<'static, W> {
+    fn finish(self: Box<Self>) -> Result<(), Error> {
+        ZstEncoder::finish(*self).context("failed to finish .zst file")?;
+        Ok(())
+    }
+}
+
 pub(crate) struct CombinedEncoder {
     encoders: Vec<Box<dyn Encoder>>,
 }
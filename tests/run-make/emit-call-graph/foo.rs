@@ -0,0 +1,21 @@
+pub trait Shape {
+    fn area(&self) -> u32;
+}
+
+struct Square(u32);
+
+impl Shape for Square {
+    fn area(&self) -> u32 {
+        self.0 * self.0
+    }
+}
+
+fn helper(x: u32) -> u32 {
+    x + 1
+}
+
+pub fn entry(x: u32) -> u32 {
+    let f: fn(u32) -> u32 = helper;
+    let shape: &dyn Shape = &Square(x);
+    f(x) + shape.area() + helper(x)
+}
@@ -0,0 +1,43 @@
+// `--emit=call-graph` writes the monomorphized call graph, with the edges labelled by how an item
+// is used. This checks both output formats on a crate that calls a function directly, through a
+// function pointer, and through a vtable.
+
+use run_make_support::{rfs, rustc};
+
+fn main() {
+    rustc()
+        .crate_type("lib")
+        .input("foo.rs")
+        .emit("call-graph")
+        .arg("-Zunstable-options")
+        .arg("-Zinline-mir=no")
+        .run();
+    let json = rfs::read_to_string("foo.callgraph");
+    assert!(json.contains(r#""crate_name":"foo""#));
+    assert!(json.contains(r#""name":"fn entry""#));
+    assert!(json.contains(r#""name":"fn helper""#));
+    assert!(json.contains(r#""name":"fn <Square as Shape>::area""#));
+
+    rustc()
+        .crate_type("lib")
+        .input("foo.rs")
+        .emit("call-graph=foo.dot")
+        .arg("-Zunstable-options")
+        .arg("-Zinline-mir=no")
+        .arg("-Zcall-graph-format=dot")
+        .run();
+    let dot = rfs::read_to_string("foo.dot");
+    let node = |name: &str| {
+        let line = dot
+            .lines()
+            .find(|line| line.contains(&format!("[label=\"{name}\\l")))
+            .unwrap_or_else(|| panic!("no node for `{name}` in:\n{dot}"));
+        line.trim_start().split_once(' ').unwrap().0.to_owned()
+    };
+    let entry = node("fn entry");
+    let helper = node("fn helper");
+    let area = node("fn <Square as Shape>::area");
+    assert!(dot.contains(&format!("{entry} -> {helper};")));
+    assert!(dot.contains(&format!("{entry} -> {helper} [label=\"reify\"")));
+    assert!(dot.contains(&format!("{entry} -> {area} [label=\"vtable\"")));
+}
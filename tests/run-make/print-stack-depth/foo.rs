@@ -0,0 +1,38 @@
+#![feature(stack_limit)]
+#![crate_type = "lib"]
+
+use std::hint::black_box;
+
+#[inline(never)]
+fn fill(buf: &mut [u8; 256]) {
+    black_box(buf);
+}
+
+#[no_mangle]
+pub extern "C" fn bounded() {
+    let mut buf = [0; 256];
+    fill(&mut buf);
+}
+
+#[no_mangle]
+pub extern "C" fn recursive(n: u32) -> u32 {
+    if n == 0 { 0 } else { black_box(recursive(n - 1)) + 1 }
+}
+
+#[no_mangle]
+pub extern "C" fn indirect(f: extern "C" fn()) {
+    f()
+}
+
+#[no_mangle]
+#[stack_limit = "16"]
+pub extern "C" fn too_deep() {
+    let mut buf = [0; 256];
+    fill(&mut buf);
+}
+
+#[no_mangle]
+#[stack_limit = "4096"]
+pub extern "C" fn unchecked(f: extern "C" fn()) {
+    f()
+}
@@ -0,0 +1,33 @@
+// `-Zprint-stack-depth` joins the frame sizes from the `.stack_sizes` sections with the mono item
+// call graph. This checks that the report tells bounded entry points from the ones that recurse or
+// make indirect calls, and that `#[stack_limit]` budgets are enforced.
+
+//@ ignore-windows
+//@ ignore-apple
+// Reason: `.stack_sizes` sections are only emitted for ELF targets.
+
+use run_make_support::rustc;
+
+fn main() {
+    let output = rustc().input("foo.rs").opt_level("3").arg("-Zprint-stack-depth").run_fail();
+    let stdout = output.stdout_utf8();
+    let entry = |name: &str| {
+        let prefix = format!("print-stack-depth entry: `{name}`: ");
+        stdout
+            .lines()
+            .find(|line| line.starts_with(&prefix))
+            .unwrap_or_else(|| panic!("no entry for `{name}` in:\n{stdout}"))
+    };
+    assert!(!entry("bounded").contains("unbounded"));
+    assert!(entry("recursive").contains("unbounded"));
+    assert!(entry("indirect").contains("unbounded"));
+    output
+        .assert_stdout_contains("print-stack-depth     `fill`: ")
+        .assert_stdout_contains("print-stack-depth     unbounded: `recursive` calls itself")
+        .assert_stdout_contains(
+            "print-stack-depth     unbounded: `indirect` makes an indirect call",
+        )
+        .assert_stderr_contains("`too_deep` may use")
+        .assert_stderr_contains("exceeding its limit of 16 bytes")
+        .assert_stderr_contains("the stack depth of `unchecked` is unbounded");
+}
@@ -0,0 +1,51 @@
+//! The crate, with [`Foo`] and [`bar::Baz`].
+
+#![feature(doc_cfg)]
+
+/// A struct, see [`Foo::new`] and [the enum](bar::Baz "the enum").
+///
+/// ```
+/// let _ = foo::Foo::new();
+/// ```
+///
+/// Code isn't rewritten: `[x](Foo)`.
+pub struct Foo {
+    /// The value.
+    pub value: u32,
+}
+
+impl Foo {
+    /// Creates a [`Foo`].
+    pub fn new() -> Foo {
+        Foo { value: 0 }
+    }
+}
+
+/// Something with an area.
+pub trait Shape {
+    fn area(&self) -> u32;
+}
+
+impl Shape for Foo {
+    fn area(&self) -> u32 {
+        self.value
+    }
+}
+
+impl Clone for Foo {
+    fn clone(&self) -> Foo {
+        Foo { value: self.value }
+    }
+}
+
+pub mod bar {
+    /// Points back to [`Foo`](crate::Foo).
+    pub enum Baz {
+        A,
+        B,
+    }
+
+    /// Only on Unix.
+    #[doc(cfg(unix))]
+    pub fn unix_only() {}
+}
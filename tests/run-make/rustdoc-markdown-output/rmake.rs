@@ -0,0 +1,67 @@
+// Checks the layout and contents of the pages written by `--output-format markdown`: one
+// `index.md` per module, a `{type}.{name}.md` page per item, and intra-doc links rewritten to
+// relative links between these pages.
+
+use std::path::Path;
+
+use run_make_support::{assert_contains, rfs, rustdoc};
+
+fn main() {
+    let out_dir = Path::new("doc");
+    rustdoc()
+        .input("foo.rs")
+        .arg("-Zunstable-options")
+        .output_format("markdown")
+        .output(out_dir)
+        .run();
+    let page = |path: &str| rfs::read_to_string(out_dir.join("foo").join(path));
+
+    let index = page("index.md");
+    assert_contains(&index, "# Crate `foo`\n");
+    assert_contains(
+        &index,
+        "The crate, with [`Foo`](struct.Foo.md) and [`bar::Baz`](bar/enum.Baz.md).",
+    );
+    assert_contains(&index, "## Modules\n\n- [`bar`](bar/index.md)");
+    assert_contains(&index, "## Structs\n\n- [`Foo`](struct.Foo.md)");
+    assert_contains(&index, "## Traits\n\n- [`Shape`](trait.Shape.md)");
+
+    let foo = page("struct.Foo.md");
+    assert_contains(&foo, "# Struct `foo::Foo`\n");
+    assert_contains(&foo, "```rust\npub struct Foo {\n    pub value: u32,\n}\n```");
+    assert_contains(
+        &foo,
+        "A struct, see [`Foo::new`](struct.Foo.md) and [the enum](bar/enum.Baz.md \"the enum\").",
+    );
+    assert_contains(&foo, "```rust\nlet _ = foo::Foo::new();\n```");
+    assert_contains(&foo, "Code isn't rewritten: `[x](Foo)`.");
+    assert_contains(&foo, "## Fields\n\n### `pub value: u32`\n\nThe value.");
+    assert_contains(&foo, "## Implementations\n\n### `impl Foo`\n\n#### `pub fn new() -> Foo`");
+    assert_contains(&foo, "Creates a [`Foo`](struct.Foo.md).");
+    assert_contains(&foo, "## Trait Implementations\n");
+    assert_contains(&foo, "- [`impl Shape for Foo`](trait.Shape.md)");
+    assert_contains(&foo, "impl Clone for Foo`");
+    assert_contains(&foo, "## Auto Trait Implementations\n");
+    assert_contains(&foo, "impl Send for Foo`");
+
+    let shape = page("trait.Shape.md");
+    assert_contains(&shape, "## Required Methods\n\n### `fn area(&self) -> u32`");
+    assert_contains(&shape, "## Implementors\n\n- [`impl Shape for Foo`](struct.Foo.md)");
+
+    let bar = page("bar/index.md");
+    assert_contains(&bar, "# Module `foo::bar`\n");
+    assert_contains(&bar, "- [`Baz`](enum.Baz.md)");
+    assert_contains(&bar, "- [`unix_only`](fn.unix_only.md)");
+
+    let baz = page("bar/enum.Baz.md");
+    assert_contains(&baz, "```rust\npub enum Baz {\n    A,\n    B,\n}\n```");
+    assert_contains(&baz, "Points back to [`Foo`](../struct.Foo.md).");
+    assert_contains(&baz, "## Variants\n\n### `A`\n\n### `B`");
+
+    let unix_only = page("bar/fn.unix_only.md");
+    assert_contains(&unix_only, "> Available on Unix only.");
+
+    // Fields and variants are documented on the page of their parent, and no HTML is written.
+    assert!(!out_dir.join("foo").join("index.html").exists());
+    assert!(!out_dir.join("foo").join("bar").join("variant.A.md").exists());
+}
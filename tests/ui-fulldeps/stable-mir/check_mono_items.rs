@@ -0,0 +1,147 @@
+//@ run-pass
+//! Test that users are able to retrieve the mono items of a crate and the callees of an instance.
+
+//@ ignore-stage1
+//@ ignore-cross-compile
+//@ ignore-remote
+//@ ignore-windows-gnu mingw has troubles with linking https://github.com/rust-lang/rust/pull/116837
+//@ edition: 2021
+
+#![feature(rustc_private)]
+#![feature(assert_matches)]
+#![feature(control_flow_enum)]
+
+#[macro_use]
+extern crate rustc_smir;
+extern crate rustc_driver;
+extern crate rustc_interface;
+extern crate stable_mir;
+
+use rustc_smir::rustc_internal;
+use stable_mir::mir::mono::{CalleeKind, Instance, InstanceKind, MonoItem};
+use std::io::Write;
+use std::ops::ControlFlow;
+
+const CRATE_NAME: &str = "input";
+
+/// This function uses the Stable MIR APIs to get information about the test crate.
+fn test_stable_mir() -> ControlFlow<()> {
+    let items = stable_mir::all_mono_items();
+    assert!(items.iter().all(|item| !item.cgus.is_empty()), "Every item must be placed");
+
+    let instances: Vec<Instance> = items
+        .iter()
+        .filter_map(|item| match item.item {
+            MonoItem::Fn(instance) => Some(instance),
+            MonoItem::Static(_) | MonoItem::GlobalAsm(_) => None,
+        })
+        .collect();
+    let find = |name: &str| {
+        *instances
+            .iter()
+            .find(|instance| instance.trimmed_name() == name)
+            .unwrap_or_else(|| panic!("Expected a mono item for `{name}`"))
+    };
+
+    // The vtable method is a mono item, and a callee of the function doing the unsizing cast.
+    let area = find("<Square as Shape>::area");
+    let make_shape = find("make_shape").callees();
+    assert!(make_shape
+        .iter()
+        .any(|callee| callee.kind == CalleeKind::VTable && callee.instance == area));
+    assert!(make_shape.iter().any(|callee| callee.kind == CalleeKind::Call));
+
+    // Dropping a `Guard` runs its drop glue.
+    let consume = find("consume").callees();
+    assert!(consume.iter().any(
+        |callee| callee.kind == CalleeKind::Drop && callee.instance.kind == InstanceKind::Shim
+    ));
+
+    // Turning a function into a function pointer reifies it.
+    let double = find("double");
+    let pointer = find("pointer").callees();
+    assert!(pointer
+        .iter()
+        .any(|callee| callee.kind == CalleeKind::Reify && callee.instance == double));
+
+    // A `sym` operand of inline assembly reifies the function it names.
+    if cfg!(any(target_arch = "x86_64", target_arch = "aarch64")) {
+        let asm_sym = find("asm_sym").callees();
+        assert!(asm_sym
+            .iter()
+            .any(|callee| callee.kind == CalleeKind::Reify && callee.instance == double));
+    }
+
+    // Calling a method of a trait object is a virtual call.
+    let total_area = find("total_area").callees();
+    assert!(total_area.iter().any(|callee| callee.kind == CalleeKind::Call
+        && matches!(callee.instance.kind, InstanceKind::Virtual { .. })));
+
+    ControlFlow::Continue(())
+}
+
+/// This test will generate and analyze a dummy crate using the stable mir.
+/// For that, it will first write the dummy crate into a file.
+/// Then it will create a `StableMir` using custom arguments and then
+/// it will run the compiler.
+fn main() {
+    let path = "mono_items_input.rs";
+    generate_input(&path).unwrap();
+    let args = vec![
+        "rustc".to_string(),
+        "-Cpanic=abort".to_string(),
+        "--crate-type=lib".to_string(),
+        "--crate-name".to_string(),
+        CRATE_NAME.to_string(),
+        path.to_string(),
+    ];
+    run!(args, test_stable_mir).unwrap();
+}
+
+fn generate_input(path: &str) -> std::io::Result<()> {
+    let mut file = std::fs::File::create(path)?;
+    write!(
+        file,
+        r#"
+    pub trait Shape {{
+        fn area(&self) -> u32;
+    }}
+
+    pub struct Square(pub u32);
+
+    impl Shape for Square {{
+        fn area(&self) -> u32 {{
+            self.0.wrapping_mul(self.0)
+        }}
+    }}
+
+    pub fn make_shape(side: u32) -> Box<dyn Shape> {{
+        Box::new(Square(side))
+    }}
+
+    pub struct Guard(pub Vec<u8>);
+
+    pub fn consume(guard: Guard) -> usize {{
+        guard.0.len()
+    }}
+
+    fn double(x: u32) -> u32 {{
+        x.wrapping_mul(2)
+    }}
+
+    pub fn pointer() -> fn(u32) -> u32 {{
+        double
+    }}
+
+    #[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+    pub fn asm_sym() {{
+        unsafe {{ core::arch::asm!("/* {{}} */", sym double) }}
+    }}
+
+    pub fn total_area(shape: &dyn Shape) -> u32 {{
+        shape.area()
+    }}
+    "#
+    )?;
+    Ok(())
+}
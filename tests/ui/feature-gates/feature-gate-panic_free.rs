@@ -0,0 +1,4 @@
+#![crate_type = "lib"]
+
+#[panic_free] //~ ERROR the `#[panic_free]` attribute is an experimental feature
+pub fn f() {}
@@ -0,0 +1,4 @@
+#![crate_type = "lib"]
+
+#[stack_limit = "1024"] //~ ERROR the `#[stack_limit]` attribute is an experimental feature
+pub fn f() {}
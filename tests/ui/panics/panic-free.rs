@@ -0,0 +1,56 @@
+//@ build-fail
+//@ compile-flags: -Zinline-mir=no
+
+#![feature(panic_free)]
+#![crate_type = "lib"]
+
+#[panic_free]
+pub fn matches(x: Option<u32>) -> u32 {
+    match x {
+        Some(x) => x,
+        None => 0,
+    }
+}
+
+#[panic_free]
+pub fn indexes(v: &[u32]) -> u32 {
+    //~^ ERROR can reach `core::panicking::panic_bounds_check`
+    get(v, 3)
+}
+
+fn get(v: &[u32], i: usize) -> u32 {
+    v[i]
+}
+
+#[panic_free]
+pub fn unwraps(x: Option<u32>) -> u32 {
+    //~^ ERROR can reach `core::option::unwrap_failed`
+    x.unwrap()
+}
+
+#[panic_free]
+pub fn calls_fn_pointer(f: fn() -> u32) -> u32 {
+    //~^ ERROR cannot verify call through function pointer
+    f()
+}
+
+#[panic_free]
+pub fn aborts() -> ! {
+    //~^ ERROR cannot verify call to `std::process::abort` from another crate
+    std::process::abort()
+}
+
+#[panic_free]
+pub fn generic<T: Default>() -> T {
+    //~^ ERROR cannot verify `#[panic_free]` function `generic`
+    T::default()
+}
+
+#[panic_free]
+fn identity<T>(x: T) -> T {
+    x
+}
+
+pub fn uses_identity() -> u32 {
+    identity(1)
+}
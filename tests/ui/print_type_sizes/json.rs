@@ -0,0 +1,27 @@
+//@ compile-flags: -Z print-type-sizes=json --crate-type=lib
+//@ build-pass
+
+// This file checks the JSON form of `-Z print-type-sizes`: field offsets,
+// padding holes, tag encodings and niches are all reported per type.
+//
+// It avoids using u64/i64 because on some targets that is only 4-byte
+// aligned (while on most it is 8-byte aligned) and so the resulting
+// padding and overall computed sizes can be quite different.
+
+#![allow(dead_code)]
+
+struct S {
+    a: u8,
+    b: u8,
+    g: i32,
+}
+
+enum E {
+    A(i32, i8),
+    B(S),
+}
+
+enum N {
+    A(bool),
+    B,
+}
@@ -0,0 +1,26 @@
+//@ compile-flags: --test
+//@ run-fail
+//@ run-flags: --test-threads=1 -Zunstable-options --retries=1
+//@ check-run-results
+//@ exec-env:RUST_BACKTRACE=0
+//@ normalize-stdout-test: "finished in \d+\.\d+s" -> "finished in $$TIME"
+//@ needs-unwind
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static FLAKY_RUNS: AtomicUsize = AtomicUsize::new(0);
+
+#[test]
+fn broken() {
+    panic!("always fails");
+}
+
+#[test]
+fn flaky() {
+    if FLAKY_RUNS.fetch_add(1, Ordering::SeqCst) == 0 {
+        panic!("fails the first time");
+    }
+}
+
+#[test]
+fn it_works() {}
@@ -0,0 +1,15 @@
+//@ compile-flags: --test
+
+#![feature(test_timeout)]
+
+#[test]
+#[test_timeout = "ten"] //~ ERROR `#[test_timeout]` expects a positive number of seconds, found `ten`
+fn not_a_number() {}
+
+#[test]
+#[test_timeout = "0"] //~ ERROR `#[test_timeout]` expects a positive number of seconds, found `0`
+fn zero() {}
+
+#[allow(dead_code)]
+#[test_timeout = "10"] //~ ERROR can only be applied to `#[test]` and `#[bench]` functions
+fn not_a_test() {}
@@ -0,0 +1,30 @@
+//@ no-prefer-dynamic
+//@ compile-flags: --test -Cpanic=abort -Zpanic_abort_tests
+//@ run-flags: --test-threads=1
+//@ run-fail
+//@ check-run-results
+//@ normalize-stdout-test: "finished in \d+\.\d+s" -> "finished in $$TIME"
+
+//@ ignore-android #120567
+//@ ignore-wasm no panic or subprocess support
+//@ ignore-emscripten no panic or subprocess support
+//@ ignore-sgx no subprocess support
+
+#![cfg(test)]
+#![feature(test_timeout)]
+
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn it_works() {
+    assert_eq!(1 + 1, 2);
+}
+
+#[test]
+#[test_timeout = "1"]
+fn hangs() {
+    loop {
+        thread::sleep(Duration::from_secs(1));
+    }
+}
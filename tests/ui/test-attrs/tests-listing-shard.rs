@@ -0,0 +1,20 @@
+//@ no-prefer-dynamic
+//@ compile-flags: --test
+//@ run-flags: --list -Zunstable-options --shard-index 1 --total-shards 2
+//@ run-pass
+//@ check-run-results
+
+// Checks that the listing of tests only includes the tests in the requested shard.
+
+#![cfg(test)]
+#[test]
+fn m_test() {}
+
+#[test]
+fn z_test() {}
+
+#[test]
+fn b_test() {}
+
+#[test]
+fn a_test() {}